@@ -1,90 +1,811 @@
-use crate::{OptLevel, TQuad, Temp};
+use crate::asm::{self, dir, imm, mem, op, reg, sym, AsmProgram, Insn};
+use crate::ir::{self, Block};
+use crate::peephole;
+use crate::{
+    runtime, Imm, IntModel, Label, Levels, OptLevel, PseudoOp, RiscvPointerReg, TCvtOp, TFloatOp,
+    TImmOp, TMemOp, TQuad, TRegOp, Temp,
+};
+use std::collections::{HashMap, HashSet};
 
-pub fn allocate(abs_as: &[TQuad], opt: OptLevel) -> Vec<String> {
+pub fn allocate(abs_as: &[TQuad], opt: OptLevel, model: &IntModel) -> AsmProgram {
+    allocate_levels(abs_as, opt, &Levels::new(), model)
+}
+
+// as allocate, with the functions in levels allocated at their own level
+pub fn allocate_levels(
+    abs_as: &[TQuad],
+    opt: OptLevel,
+    levels: &Levels,
+    model: &IntModel,
+) -> AsmProgram {
+    let strs = runtime::strs(abs_as);
+    let instrs = ir::runs(abs_as, &opt, levels)
+        .into_iter()
+        .flat_map(|(level, quads)| allocate_run(&quads, &level, &strs, model).insns)
+        .collect::<Vec<_>>();
+
+    AsmProgram {
+        insns: [header(), instrs].concat(),
+    }
+}
+
+// functions and statics allocated at opt, without the header. strs are the
+// program's literals, which the quads' are named by their place among
+pub fn allocate_run(
+    abs_as: &[TQuad],
+    opt: &OptLevel,
+    strs: &[&str],
+    model: &IntModel,
+) -> AsmProgram {
     match opt {
-        OptLevel::O0 => allocate_1ac(abs_as),
-        OptLevel::O1 => todo!(),
-        OptLevel::O2 => todo!(),
+        OptLevel::O0 => allocate_1ac(abs_as, strs, model),
+        // -O1 still allocates as a stack machine, with its pushes and pops
+        // cleaned up after
+        OptLevel::O1 => peephole::optimize(allocate_1ac(abs_as, strs, model)),
+        OptLevel::O2 => peephole::optimize(allocate_regs(abs_as, strs, model)),
+    }
+}
+
+fn pop(r: &str, word: Imm) -> Vec<Insn> {
+    vec![
+        op(asm::load(word), [reg(r), mem(0, "sp")]).note(format!("{r} <- pop")),
+        op("addi", [reg("sp"), reg("sp"), imm(8)]).note("shrink stack"),
+    ]
+}
+
+fn push_t2(word: Imm) -> Vec<Insn> {
+    vec![
+        op("addi", [reg("sp"), reg("sp"), imm(-8)]).note("grow stack"),
+        op(asm::store(word), [reg("t2"), mem(0, "sp")]).note("push t2 ->"),
+    ]
+}
+
+// ints narrower than 32 bits are extended after each operation: sign
+// extended for signed ints, and zero extended for unsigned ones. rv64's
+// registers hold them extended from 32 bits, which its w ops keep, and
+// narrower ones are extended from there. bitwise ops on extended operands
+// are already extended, and addptr works on the whole address
+fn extend(r: &str, treg_op: &TRegOp, model: &IntModel) -> Vec<Insn> {
+    let ext = 32 - model.int_width as Imm;
+    let (slli, srai, srli) = match model.word() {
+        8 => ("slliw", "sraiw", "srliw"),
+        _ => ("slli", "srai", "srli"),
+    };
+    let truncate = || op(slli, [reg(r), reg(r), imm(ext)]).note("truncate");
+    match (ext, treg_op) {
+        (0, _) | (_, TRegOp::AddPtr | TRegOp::And | TRegOp::Or | TRegOp::Xor) => vec![],
+        (
+            _,
+            TRegOp::Addu
+            | TRegOp::Subu
+            | TRegOp::Mulu
+            | TRegOp::Sllu
+            | TRegOp::Divu
+            | TRegOp::Remu
+            | TRegOp::Srl,
+        ) => vec![
+            truncate(),
+            op(srli, [reg(r), reg(r), imm(ext)]).note("zero extend"),
+        ],
+        _ => vec![
+            truncate(),
+            op(srai, [reg(r), reg(r), imm(ext)]).note("sign extend"),
+        ],
+    }
+}
+
+// an int op on rv64 is its w form, which works on the low 32 bits and sign
+// extends the result. addptr works on the whole address
+fn reg_op(treg_op: &TRegOp, word: Imm) -> String {
+    match (treg_op, word) {
+        (
+            TRegOp::Add
+            | TRegOp::Sub
+            | TRegOp::Mul
+            | TRegOp::Div
+            | TRegOp::Rem
+            | TRegOp::Addu
+            | TRegOp::Subu
+            | TRegOp::Mulu
+            | TRegOp::Sllu
+            | TRegOp::Divu
+            | TRegOp::Remu
+            | TRegOp::Sll
+            | TRegOp::Sra
+            | TRegOp::Srl,
+            8,
+        ) => format!("{treg_op}w"),
+        _ => treg_op.to_string(),
+    }
+}
+
+fn mem_op(tmem_op: &TMemOp, word: Imm) -> &'static str {
+    match tmem_op {
+        TMemOp::Load => asm::load(word),
+        TMemOp::Store => asm::store(word),
+        TMemOp::LoadByte => "lb",
+        TMemOp::LoadByteU => "lbu",
+        TMemOp::StoreByte => "sb",
+    }
+}
+
+fn pseudo(pseudo_op: &PseudoOp) -> Insn {
+    match pseudo_op {
+        PseudoOp::Call(l) | PseudoOp::CallC(l) => op("call", [sym(l)]),
+        PseudoOp::Jump(l) => op("j", [sym(l)]),
+        PseudoOp::Ret => op("ret", []),
     }
 }
 
-const POP_IMM_T0: &str = POP_RIGHT_T0;
-const POP_RIGHT_T0: &str = "lw t0, 0(sp) # t0 <- pop\naddi sp,sp,8 # shrink stack";
-const POP_LEFT_T1: &str = "lw t1, 0(sp) # t1 <- pop\naddi sp,sp,8 # shrink stack";
-const PUSH_T2: &str = "addi sp,sp,-8 # grow stack\nsw t2, 0(sp) # push t2 ->\n";
+pub fn header() -> Vec<Insn> {
+    vec![
+        dir(".text", []),
+        dir(".globl", ["main"]),
+        dir(".section", [".text"]),
+    ]
+}
+
+fn allocate_1ac(abs_as: &[TQuad], strs: &[&str], model: &IntModel) -> AsmProgram {
+    let word = model.word();
+    let mut func = String::new(); // for the runtime's error reports
 
-fn allocate_1ac(abs_as: &[TQuad]) -> Vec<String> {
     let instrs = abs_as
         .iter()
         .flat_map(|quad| match quad {
             TQuad::Reg(treg_op, _dt, _lt, _rt) => {
-                vec![
-                    POP_RIGHT_T0.to_owned(),
-                    POP_LEFT_T1.to_owned(),
-                    format!("{} t2, t1, t0 # operate", treg_op.to_string()),
-                    PUSH_T2.to_owned(),
+                // riscv doesn't trap on division by zero, so check explicitly
+                let check = match treg_op {
+                    TRegOp::Div | TRegOp::Rem | TRegOp::Divu | TRegOp::Remu => {
+                        runtime::check_div(treg_op, "t1", "t0", model)
+                    }
+                    _ => vec![],
+                };
+                let operate =
+                    op(reg_op(treg_op, word), [reg("t2"), reg("t1"), reg("t0")]).note("operate");
+                [
+                    pop("t0", word),
+                    pop("t1", word),
+                    check,
+                    vec![operate],
+                    extend("t2", treg_op, model),
+                    push_t2(word),
+                ]
+                .concat()
+            }
+            TQuad::Float(tfloat_op, _dt, _lt, _rt) => {
+                // the stack machine's temps are integer registers, so the
+                // bits move into float registers and back around each op
+                let operate = match tfloat_op {
+                    TFloatOp::FLt | TFloatOp::FLe | TFloatOp::FEq => {
+                        vec![op(tfloat_op, [reg("t2"), reg("ft1"), reg("ft0")]).note("compare")]
+                    }
+                    _ => vec![
+                        op(tfloat_op, [reg("ft2"), reg("ft1"), reg("ft0")]).note("operate"),
+                        op("fmv.x.w", [reg("t2"), reg("ft2")]),
+                    ],
+                };
+                let moves = vec![
+                    op("fmv.w.x", [reg("ft0"), reg("t0")]),
+                    op("fmv.w.x", [reg("ft1"), reg("t1")]),
+                ];
+                [
+                    pop("t0", word),
+                    pop("t1", word),
+                    moves,
+                    operate,
+                    push_t2(word),
                 ]
+                .concat()
+            }
+            TQuad::Cvt(tcvt_op, _dt, _st) => {
+                let convert = match tcvt_op {
+                    TCvtOp::FcvtSW | TCvtOp::FcvtSWu => vec![
+                        op(tcvt_op, [reg("ft0"), reg("t0")]).note("convert"),
+                        op("fmv.x.w", [reg("t2"), reg("ft0")]),
+                    ],
+                    TCvtOp::FcvtWS | TCvtOp::FcvtWuS => vec![
+                        op("fmv.w.x", [reg("ft0"), reg("t0")]),
+                        op(tcvt_op, [reg("t2"), reg("ft0"), sym("rtz")])
+                            .note("convert, truncating"),
+                    ],
+                };
+                [pop("t0", word), convert, push_t2(word)].concat()
             }
-            TQuad::Imm(timm_op, dt, lt, imm) => match (dt, lt) {
+            // addi's immediate is 12 bits, so wider constants are loaded whole
+            TQuad::Imm(
+                TImmOp::AddI,
+                Temp::MachineTemp(_),
+                Temp::PointerReg(RiscvPointerReg::Z),
+                k,
+            ) if !(-2048..2048).contains(k) => {
+                [vec![op("li", [reg("t2"), imm(*k)])], push_t2(word)].concat()
+            }
+            TQuad::Imm(timm_op, dt, lt, k) => match (dt, lt) {
                 (Temp::UserTemp(_), Temp::UserTemp(_)) => todo!(),
                 (Temp::UserTemp(_), Temp::MachineTemp(_)) => todo!(),
                 (Temp::UserTemp(_), Temp::PointerReg(_pr)) => todo!(),
                 (Temp::MachineTemp(_), Temp::UserTemp(_)) => todo!(),
-                (Temp::MachineTemp(_), Temp::MachineTemp(_)) => todo!(),
+                (Temp::MachineTemp(_), Temp::MachineTemp(_)) => [
+                    pop("t0", word),
+                    vec![op(timm_op, [reg("t2"), reg("t0"), imm(*k)])],
+                    push_t2(word),
+                ]
+                .concat(),
                 (Temp::MachineTemp(_), Temp::PointerReg(pr)) => {
-                    vec![
-                        format!("{} t2, {}, {}", timm_op.to_string(), pr.to_string(), imm),
-                        PUSH_T2.to_owned(), // push b/c we're using t0
+                    // push b/c we're using t0
+                    [
+                        vec![op(timm_op, [reg("t2"), reg(pr), imm(*k)])],
+                        push_t2(word),
                     ]
+                    .concat()
                 }
                 (Temp::PointerReg(_pr), Temp::UserTemp(_)) => todo!(),
                 (Temp::PointerReg(pr), Temp::MachineTemp(_)) => {
-                    vec![
-                        POP_IMM_T0.to_owned(),
-                        format!("{} {}, t0, {}", timm_op.to_string(), pr.to_string(), imm),
-                    ] // no push b/c we're using pr
+                    // no push b/c we're using pr
+                    [
+                        pop("t0", word),
+                        vec![op(timm_op, [reg(pr), reg("t0"), imm(*k)])],
+                    ]
+                    .concat()
+                }
+                (Temp::PointerReg(dpr), Temp::PointerReg(lpr)) => {
+                    vec![op(timm_op, [reg(dpr), reg(lpr), imm(*k)])]
+                }
+            },
+            TQuad::Mem(tmem_op, temp, offset, base) => match (tmem_op, temp) {
+                (_, Temp::UserTemp(_)) => todo!(),
+                (TMemOp::Load | TMemOp::LoadByte | TMemOp::LoadByteU, Temp::MachineTemp(_)) => [
+                    vec![op(mem_op(tmem_op, word), [reg("t2"), mem(*offset, base)])
+                        .note("load local")],
+                    push_t2(word),
+                ]
+                .concat(),
+                (TMemOp::Store | TMemOp::StoreByte, Temp::MachineTemp(_)) => [
+                    pop("t0", word),
+                    vec![op(mem_op(tmem_op, word), [reg("t0"), mem(*offset, base)])
+                        .note("store local")],
+                ]
+                .concat(),
+                (_, Temp::PointerReg(pr)) => {
+                    vec![op(mem_op(tmem_op, word), [reg(pr), mem(*offset, base)])]
                 }
-                (Temp::PointerReg(dpr), Temp::PointerReg(lpr)) => vec![format!(
-                    "{} {}, {}, {}",
-                    timm_op.to_string(),
-                    dpr.to_string(),
-                    lpr.to_string(),
-                    imm,
-                )],
             },
-            TQuad::Mem(tmem_op, temp, offset, base) => match temp {
-                Temp::UserTemp(_) => todo!(),
-                Temp::MachineTemp(_) => todo!(),
-                Temp::PointerReg(riscv_pointer_reg) => {
-                    vec![format!(
-                        "{} {}, {}({})",
-                        tmem_op.to_string(),
-                        riscv_pointer_reg.to_string(),
-                        offset,
-                        base.to_string()
-                    )]
+            TQuad::Deref(tmem_op, temp, addr) => match (tmem_op, temp, addr) {
+                (
+                    TMemOp::Load | TMemOp::LoadByte | TMemOp::LoadByteU,
+                    Temp::MachineTemp(_),
+                    Temp::MachineTemp(_),
+                ) => [
+                    pop("t0", word),
+                    vec![op(mem_op(tmem_op, word), [reg("t2"), mem(0, "t0")])
+                        .note("load through pointer")],
+                    push_t2(word),
+                ]
+                .concat(),
+                (TMemOp::Store | TMemOp::StoreByte, Temp::MachineTemp(_), Temp::MachineTemp(_)) => {
+                    [
+                        pop("t0", word),
+                        pop("t1", word),
+                        vec![op(mem_op(tmem_op, word), [reg("t1"), mem(0, "t0")])
+                            .note("store through pointer")],
+                    ]
+                    .concat()
                 }
+                _ => todo!(),
             },
-            TQuad::Pseudo(pseudo_op) => vec![pseudo_op.to_string()],
-            TQuad::Label(l) => vec![format!("{}:", l.to_string())],
+            TQuad::Str(Temp::MachineTemp(_), s) => {
+                let n = strs
+                    .iter()
+                    .position(|t| t == s)
+                    .expect("strs has every literal");
+                let la = op("la", [reg("t2"), sym(runtime::str_sym(n))]).note("string literal");
+                [vec![la], push_t2(word)].concat()
+            }
+            TQuad::Str(_, _) => todo!(),
+            TQuad::Static(Temp::MachineTemp(_), alias) => [
+                vec![op("la", [reg("t2"), sym(alias)]).note("static")],
+                push_t2(word),
+            ]
+            .concat(),
+            TQuad::Static(_, _) => todo!(),
+            // statics without .globl are local to the file
+            TQuad::Data(alias, words, global) => data(alias, words, *global, word),
+            TQuad::Branch(tbranch_op, lt, rt, l) => match (lt, rt) {
+                (Temp::MachineTemp(_), Temp::PointerReg(pr)) => [
+                    pop("t0", word),
+                    vec![op(tbranch_op, [reg("t0"), reg(pr), sym(l)])],
+                ]
+                .concat(),
+                _ => todo!(),
+            },
+            TQuad::JumpTable(Temp::MachineTemp(_), table, ls) => {
+                [pop("t0", word), jump_table("t0", table, ls)].concat()
+            }
+            TQuad::JumpTable(_, _, _) => todo!(),
+            TQuad::Pseudo(PseudoOp::Call(l)) => {
+                // back in the caller
+                [vec![op("call", [sym(l)])], runtime::mark_func(&func, word)].concat()
+            }
+            // the stack machine only keeps sp 8 byte aligned, so it's rounded
+            // down to 16 for the call, with the old sp saved just below it
+            TQuad::Pseudo(PseudoOp::CallC(l)) => [
+                vec![
+                    op("mv", [reg("t0"), reg("sp")]),
+                    op("andi", [reg("sp"), reg("sp"), imm(-16)]).note("align"),
+                    op("addi", [reg("sp"), reg("sp"), imm(-16)]),
+                    op(asm::store(word), [reg("t0"), mem(0, "sp")]),
+                    op("call", [sym(l)]),
+                    op(asm::load(word), [reg("sp"), mem(0, "sp")]).note("unalign"),
+                ],
+                runtime::mark_func(&func, word),
+            ]
+            .concat(),
+            TQuad::Pseudo(pseudo_op) => vec![pseudo(pseudo_op)],
+            TQuad::Label(Label::UserLabel(l)) => {
+                func = l.clone();
+                [vec![Insn::Label(l.clone())], runtime::mark_func(l, word)].concat()
+            }
+            TQuad::Label(l) => vec![Insn::Label(l.to_string())],
         })
         .collect::<Vec<_>>();
 
-    let prg_prologue = vec![
-        ".text".to_owned(),
-        ".globl main".to_owned(),
-        ".section .text".to_owned(),
-    ];
+    AsmProgram { insns: instrs }
+}
 
-    let prg_epilogue = vec!["\n".to_owned()];
+// the table sits inline in .text, indexed by t, with each target as an
+// offset from the table. offsets need no relocating, so the table is the
+// same in position independent code, and a word on rv64 too
+fn jump_table(t: &str, table: &Label, ls: &[Label]) -> Vec<Insn> {
+    vec![
+        op("slli", [reg("t0"), reg(t), imm(2)]).note("index -> offset"),
+        op("la", [reg("t1"), sym(table)]),
+        op("add", [reg("t0"), reg("t1"), reg("t0")]),
+        op("lw", [reg("t0"), mem(0, "t0")]).note("load target's offset"),
+        op("add", [reg("t1"), reg("t1"), reg("t0")]),
+        op("jr", [reg("t1")]),
+        dir(".align", ["2"]),
+        Insn::Data(
+            table.to_string(),
+            ".word".to_owned(),
+            ls.iter().map(|l| format!("{l} - {table}")).collect(),
+        ),
+    ]
+}
 
-    prg_prologue
+fn data(alias: &str, words: &[Imm], global: bool, word: Imm) -> Vec<Insn> {
+    let (section, contents) = match words.iter().all(|w| *w == 0) {
+        true => (
+            ".bss",
+            (".zero", vec![(word as usize * words.len()).to_string()]),
+        ),
+        false => (
+            ".data",
+            (
+                asm::words(word),
+                words.iter().map(|w| w.to_string()).collect(),
+            ),
+        ),
+    };
+    global
+        .then(|| dir(".globl", [alias]))
         .into_iter()
-        .chain(instrs)
-        .chain(prg_epilogue)
+        .chain([
+            dir(".section", [section]),
+            asm::align(word),
+            Insn::Data(alias.to_owned(), contents.0.to_owned(), contents.1),
+            dir(".section", [".text"]),
+        ])
         .collect()
 }
 
+// -O2 keeps temps in registers. each function's temps get live intervals,
+// from the first quad they're live at to the last, over its cfg, and a
+// linear scan hands out the callee saved s1-s11, so values survive calls
+// without saving them around each one. when every register is taken, the
+// interval that ends last is spilled to a slot the frame grows by, and
+// loaded into t0 or t1 for a use, or computed into t2 and stored. t0-t2
+// are never allocated, so the runtime and the spill code can clobber them.
+// the s registers a function uses are saved below its locals on entry,
+// and restored before its epilogue restores ra and fp
+const REGS: [&str; 11] = [
+    "s1", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11",
+];
+
+// where a temp lives
+#[derive(Clone, Debug, PartialEq)]
+enum Loc {
+    Reg(usize), // an index into REGS
+    Slot(Imm),  // an offset from fp
+}
+
+// the temps a quad reads and writes
+fn uses_defs(quad: &TQuad) -> (Vec<&Temp>, Vec<&Temp>) {
+    match quad {
+        TQuad::Reg(_, d, l, r) | TQuad::Float(_, d, l, r) => (vec![l, r], vec![d]),
+        TQuad::Imm(_, d, l, _) | TQuad::Cvt(_, d, l) => (vec![l], vec![d]),
+        TQuad::Mem(TMemOp::Load | TMemOp::LoadByte | TMemOp::LoadByteU, d, _, _) => {
+            (vec![], vec![d])
+        }
+        TQuad::Mem(TMemOp::Store | TMemOp::StoreByte, t, _, _) => (vec![t], vec![]),
+        TQuad::Deref(TMemOp::Load | TMemOp::LoadByte | TMemOp::LoadByteU, d, a) => {
+            (vec![a], vec![d])
+        }
+        TQuad::Deref(TMemOp::Store | TMemOp::StoreByte, t, a) => (vec![t, a], vec![]),
+        TQuad::Str(d, _) | TQuad::Static(d, _) => (vec![], vec![d]),
+        TQuad::Branch(_, l, r, _) => (vec![l, r], vec![]),
+        TQuad::JumpTable(t, _, _) => (vec![t], vec![]),
+        _ => (vec![], vec![]),
+    }
+}
+
+fn machine(temps: Vec<&Temp>) -> impl Iterator<Item = usize> + '_ {
+    temps.into_iter().filter_map(|t| match t {
+        Temp::MachineTemp(n) => Some(*n),
+        _ => None,
+    })
+}
+
+// each machine temp's interval, as the first and last index into the
+// function's quads it's live at. a temp live into or out of a block is
+// live at the block's first or last quad, and the holes between are taken
+// to be live too
+fn intervals(blocks: &[Block], offset: usize) -> Vec<(usize, usize, usize)> {
+    let mut live_in: Vec<HashSet<usize>> = vec![HashSet::new(); blocks.len()];
+    let mut live_out: Vec<HashSet<usize>> = vec![HashSet::new(); blocks.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (i, b) in blocks.iter().enumerate().rev() {
+            let out = b
+                .succs
+                .iter()
+                .filter_map(|s| s.checked_sub(offset).and_then(|s| live_in.get(s)))
+                .flatten()
+                .copied()
+                .collect::<HashSet<_>>();
+            let mut live = out.clone();
+            for quad in b.quads.iter().rev() {
+                let (uses, defs) = uses_defs(quad);
+                machine(defs).for_each(|d| {
+                    live.remove(&d);
+                });
+                live.extend(machine(uses));
+            }
+            if live != live_in[i] || out != live_out[i] {
+                (live_in[i], live_out[i]) = (live, out);
+                changed = true;
+            }
+        }
+    }
+
+    let mut spans: HashMap<usize, (usize, usize)> = HashMap::new();
+    let mut extend = |t: usize, at: usize| {
+        let span = spans.entry(t).or_insert((at, at));
+        *span = (span.0.min(at), span.1.max(at));
+    };
+    let mut at = 0;
+    for (i, b) in blocks.iter().enumerate() {
+        let end = at + b.quads.len().max(1) - 1;
+        live_in[i].iter().for_each(|t| extend(*t, at));
+        live_out[i].iter().for_each(|t| extend(*t, end));
+        for quad in &b.quads {
+            let (uses, defs) = uses_defs(quad);
+            machine(uses)
+                .chain(machine(defs))
+                .for_each(|t| extend(t, at));
+            at += 1;
+        }
+    }
+
+    let mut intervals = spans
+        .into_iter()
+        .map(|(t, (start, end))| (t, start, end))
+        .collect::<Vec<_>>();
+    intervals.sort_by_key(|(t, start, _)| (*start, *t));
+    intervals
+}
+
+// linear scan over intervals sorted by start, with regs registers. spilled
+// temps are numbered in the order they're spilled
+fn scan(intervals: &[(usize, usize, usize)], regs: usize) -> (HashMap<usize, Loc>, usize) {
+    let mut locs = HashMap::new();
+    let mut active: Vec<(usize, usize, usize)> = vec![]; // (end, temp, reg)
+    let mut free = (0..regs).rev().collect::<Vec<_>>();
+    let mut spills = 0;
+    for &(t, start, end) in intervals {
+        // an interval that ends where this one starts is still read there
+        active.retain(|&(e, _, r)| {
+            let live = e >= start;
+            if !live {
+                free.push(r);
+            }
+            live
+        });
+        free.sort_by(|a, b| b.cmp(a));
+        match free.pop() {
+            Some(r) => {
+                locs.insert(t, Loc::Reg(r));
+                active.push((end, t, r));
+            }
+            None => {
+                spills += 1;
+                let slot = Loc::Slot(spills as Imm);
+                let last = active.iter().copied().enumerate().max_by_key(|(_, a)| a.0);
+                match last {
+                    Some((i, (e, victim, r))) if e > end => {
+                        locs.insert(victim, slot);
+                        locs.insert(t, Loc::Reg(r));
+                        active[i] = (end, t, r);
+                    }
+                    _ => {
+                        locs.insert(t, slot);
+                    }
+                }
+            }
+        }
+    }
+    (locs, spills)
+}
+
+fn allocate_regs(abs_as: &[TQuad], strs: &[&str], model: &IntModel) -> AsmProgram {
+    let word = model.word();
+    let cfg = ir::build(abs_as.to_vec());
+
+    let mut funcs = vec![];
+    let mut start = 0;
+    for (i, b) in cfg.blocks.iter().enumerate().skip(1) {
+        if matches!(b.label(), Some(Label::UserLabel(_))) || matches!(b.quads[0], TQuad::Data(..)) {
+            funcs.push(start..i);
+            start = i;
+        }
+    }
+    funcs.push(start..cfg.blocks.len());
+
+    let mut instrs = vec![];
+    for range in funcs {
+        let blocks = &cfg.blocks[range.clone()];
+        let quads = blocks
+            .iter()
+            .flat_map(|b| b.quads.clone())
+            .collect::<Vec<_>>();
+        let Some(size) = ir::frame_size(&quads) else {
+            // statics
+            for quad in &quads {
+                if let TQuad::Data(alias, words, global) = quad {
+                    instrs.extend(data(alias, words, *global, word));
+                }
+            }
+            continue;
+        };
+
+        let (mut locs, spills) = scan(&intervals(blocks, range.start), REGS.len());
+        let mut used = locs
+            .values()
+            .filter_map(|l| match l {
+                Loc::Reg(r) => Some(*r),
+                Loc::Slot(_) => None,
+            })
+            .collect::<Vec<_>>();
+        used.sort();
+        used.dedup();
+
+        // spill slots, then the saved registers, below the locals
+        for loc in locs.values_mut() {
+            if let Loc::Slot(n) = loc {
+                *loc = Loc::Slot(-size - word * *n);
+            }
+        }
+        let saves = used
+            .iter()
+            .enumerate()
+            .map(|(i, r)| (*r, -size - word * (spills + i + 1) as Imm))
+            .collect::<Vec<_>>();
+        let words = (spills + used.len()) as Imm;
+        let quads = ir::grow(quads, size, size + (word * words + 15) / 16 * 16);
+
+        let mut func = String::new();
+        for quad in &quads {
+            instrs.extend(emit(quad, &locs, model, strs));
+            match quad {
+                TQuad::Label(Label::UserLabel(l)) => {
+                    func = l.clone();
+                    instrs.extend(runtime::mark_func(l, word));
+                }
+                // the prologue's last quad sets fp
+                TQuad::Imm(_, Temp::PointerReg(RiscvPointerReg::Fp), _, _) => {
+                    for (r, off) in &saves {
+                        instrs.push(
+                            op(asm::store(word), [reg(REGS[*r]), mem(*off, "fp")]).note("save"),
+                        );
+                    }
+                }
+                TQuad::Pseudo(PseudoOp::Call(_) | PseudoOp::CallC(_)) => {
+                    instrs.extend(runtime::mark_func(&func, word))
+                }
+                TQuad::Label(_) => (),
+                _ => continue,
+            }
+            // the epilogue restores ra first
+            if let TQuad::Label(_) = quad {
+                let restores = quads
+                    .iter()
+                    .skip_while(|q| *q != quad)
+                    .nth(1)
+                    .is_some_and(|q| {
+                        matches!(
+                            q,
+                            TQuad::Mem(TMemOp::Load, Temp::PointerReg(RiscvPointerReg::Ra), _, _)
+                        )
+                    });
+                if restores {
+                    for (r, off) in &saves {
+                        instrs.push(
+                            op(asm::load(word), [reg(REGS[*r]), mem(*off, "fp")]).note("restore"),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    AsmProgram { insns: instrs }
+}
+
+// a temp's register when it's read, with the load of a spilled one into
+// scratch
+fn read(t: &Temp, locs: &HashMap<usize, Loc>, scratch: &str, word: Imm) -> (Option<Insn>, String) {
+    match t {
+        Temp::MachineTemp(n) => match &locs[n] {
+            Loc::Reg(r) => (None, REGS[*r].to_owned()),
+            Loc::Slot(off) => (
+                Some(op(asm::load(word), [reg(scratch), mem(*off, "fp")]).note("reload")),
+                scratch.to_owned(),
+            ),
+        },
+        Temp::PointerReg(pr) => (None, pr.to_string()),
+        Temp::UserTemp(_) => todo!(),
+    }
+}
+
+// a temp's register when it's written, with the store of a spilled one
+fn write(t: &Temp, locs: &HashMap<usize, Loc>, word: Imm) -> (String, Option<Insn>) {
+    match t {
+        Temp::MachineTemp(n) => match &locs[n] {
+            Loc::Reg(r) => (REGS[*r].to_owned(), None),
+            Loc::Slot(off) => (
+                "t2".to_owned(),
+                Some(op(asm::store(word), [reg("t2"), mem(*off, "fp")]).note("spill")),
+            ),
+        },
+        Temp::PointerReg(pr) => (pr.to_string(), None),
+        Temp::UserTemp(_) => todo!(),
+    }
+}
+
+fn emit(quad: &TQuad, locs: &HashMap<usize, Loc>, model: &IntModel, strs: &[&str]) -> Vec<Insn> {
+    let word = model.word();
+    let mut out = vec![];
+    let mut load = |t: &Temp, scratch: &str| {
+        let (reload, reg) = read(t, locs, scratch, word);
+        out.extend(reload);
+        reg
+    };
+    let (insns, store) = match quad {
+        TQuad::Reg(treg_op, d, l, r) => {
+            let (l, r) = (load(l, "t1"), load(r, "t0"));
+            let (d, store) = write(d, locs, word);
+            let check = match treg_op {
+                TRegOp::Div | TRegOp::Rem | TRegOp::Divu | TRegOp::Remu => {
+                    runtime::check_div(treg_op, &l, &r, model)
+                }
+                _ => vec![],
+            };
+            let operate = op(reg_op(treg_op, word), [reg(&d), reg(l), reg(r)]);
+            (
+                [check, vec![operate], extend(&d, treg_op, model)].concat(),
+                store,
+            )
+        }
+        TQuad::Imm(TImmOp::AddI, d, Temp::PointerReg(RiscvPointerReg::Z), k) => {
+            let (d, store) = write(d, locs, word);
+            (vec![op("li", [reg(d), imm(*k)])], store)
+        }
+        TQuad::Imm(timm_op, d, l, k) => {
+            let l = load(l, "t1");
+            let (d, store) = write(d, locs, word);
+            (vec![op(timm_op, [reg(d), reg(l), imm(*k)])], store)
+        }
+        TQuad::Mem(
+            tmem_op @ (TMemOp::Load | TMemOp::LoadByte | TMemOp::LoadByteU),
+            d,
+            off,
+            base,
+        ) => {
+            let (d, store) = write(d, locs, word);
+            (
+                vec![op(mem_op(tmem_op, word), [reg(d), mem(*off, base)])],
+                store,
+            )
+        }
+        TQuad::Mem(tmem_op @ (TMemOp::Store | TMemOp::StoreByte), t, off, base) => {
+            let t = load(t, "t0");
+            (
+                vec![op(mem_op(tmem_op, word), [reg(t), mem(*off, base)])],
+                None,
+            )
+        }
+        TQuad::Deref(tmem_op @ (TMemOp::Load | TMemOp::LoadByte | TMemOp::LoadByteU), d, a) => {
+            let a = load(a, "t0");
+            let (d, store) = write(d, locs, word);
+            let insn = op(mem_op(tmem_op, word), [reg(d), mem(0, a)]).note("load through pointer");
+            (vec![insn], store)
+        }
+        TQuad::Deref(tmem_op @ (TMemOp::Store | TMemOp::StoreByte), t, a) => {
+            let (t, a) = (load(t, "t1"), load(a, "t0"));
+            let insn = op(mem_op(tmem_op, word), [reg(t), mem(0, a)]).note("store through pointer");
+            (vec![insn], None)
+        }
+        TQuad::Str(d, s) => {
+            let n = strs
+                .iter()
+                .position(|t| t == s)
+                .expect("strs has every literal");
+            let (d, store) = write(d, locs, word);
+            let la = op("la", [reg(d), sym(runtime::str_sym(n))]).note("string literal");
+            (vec![la], store)
+        }
+        TQuad::Static(d, alias) => {
+            let (d, store) = write(d, locs, word);
+            (vec![op("la", [reg(d), sym(alias)]).note("static")], store)
+        }
+        TQuad::Data(alias, words, global) => (data(alias, words, *global, word), None),
+        TQuad::Branch(tbranch_op, l, r, target) => {
+            let (l, r) = (load(l, "t1"), load(r, "t0"));
+            (vec![op(tbranch_op, [reg(l), reg(r), sym(target)])], None)
+        }
+        TQuad::Float(tfloat_op, d, l, r) => {
+            let (l, r) = (load(l, "t1"), load(r, "t0"));
+            let (d, store) = write(d, locs, word);
+            let operate = match tfloat_op {
+                TFloatOp::FLt | TFloatOp::FLe | TFloatOp::FEq => {
+                    vec![op(tfloat_op, [reg(d), reg("ft1"), reg("ft0")])]
+                }
+                _ => vec![
+                    op(tfloat_op, [reg("ft2"), reg("ft1"), reg("ft0")]),
+                    op("fmv.x.w", [reg(d), reg("ft2")]),
+                ],
+            };
+            let moves = vec![
+                op("fmv.w.x", [reg("ft0"), reg(r)]),
+                op("fmv.w.x", [reg("ft1"), reg(l)]),
+            ];
+            ([moves, operate].concat(), store)
+        }
+        TQuad::Cvt(tcvt_op, d, s) => {
+            let s = load(s, "t0");
+            let (d, store) = write(d, locs, word);
+            let convert = match tcvt_op {
+                TCvtOp::FcvtSW | TCvtOp::FcvtSWu => vec![
+                    op(tcvt_op, [reg("ft0"), reg(s)]),
+                    op("fmv.x.w", [reg(d), reg("ft0")]),
+                ],
+                TCvtOp::FcvtWS | TCvtOp::FcvtWuS => vec![
+                    op("fmv.w.x", [reg("ft0"), reg(s)]),
+                    op(tcvt_op, [reg(d), reg("ft0"), sym("rtz")]),
+                ],
+            };
+            (convert, store)
+        }
+        TQuad::JumpTable(t, table, ls) => {
+            let t = load(t, "t0");
+            (jump_table(&t, table, ls), None)
+        }
+        TQuad::Pseudo(pseudo_op) => (vec![pseudo(pseudo_op)], None),
+        TQuad::Label(l) => (vec![Insn::Label(l.to_string())], None),
+    };
+    out.into_iter().chain(insns).chain(store).collect()
+}
+
 #[cfg(test)]
 mod test_arith {
     use crate::lexer;
@@ -92,49 +813,4416 @@ mod test_arith {
     use crate::selector;
     use crate::translator;
     use crate::typer;
+    use crate::IntModel;
     use std::fs;
 
     const TEST_DIR: &str = "tests/fixtures/snap/shared/arith";
 
     #[test]
     fn add() {
-        let chars = fs::read(format!("{TEST_DIR}/add.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let src = fs::read_to_string(format!("{TEST_DIR}/add.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let src_tree = parser_ast::parse_prg(&tokens).unwrap();
         let _ = typer::type_prg(&src_tree).unwrap();
         let trgt_tree = translator::translate(&src_tree);
         let abs_as = selector::select(&trgt_tree);
-        let assembly = super::allocate(&abs_as, super::OptLevel::O0);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
         insta::assert_yaml_snapshot!(assembly, @r###"
         ---
         - ".text"
         - ".globl main"
         - ".section .text"
         - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
         - "addi sp, sp, -16"
         - "sw ra, 12(sp)"
         - "sw fp, 8(sp)"
         - "addi fp, sp, 16"
         - "addi t2, zero, 9"
-        - "addi sp,sp,-8 # grow stack\nsw t2, 0(sp) # push t2 ->\n"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
         - "addi t2, zero, 10"
-        - "addi sp,sp,-8 # grow stack\nsw t2, 0(sp) # push t2 ->\n"
-        - "lw t0, 0(sp) # t0 <- pop\naddi sp,sp,8 # shrink stack"
-        - "lw t1, 0(sp) # t1 <- pop\naddi sp,sp,8 # shrink stack"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
         - "add t2, t1, t0 # operate"
-        - "addi sp,sp,-8 # grow stack\nsw t2, 0(sp) # push t2 ->\n"
-        - "lw t0, 0(sp) # t0 <- pop\naddi sp,sp,8 # shrink stack"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn neg() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/neg.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 5"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 2"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "mul t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 3"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn modulo() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/mod.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, zero, 17"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 5"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beqz t0, __picoc_div_zero # check divisor"
+        - "rem t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 3"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "mul t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn shr() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/shr.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 16"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 2"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sra t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn unsigned() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/unsigned.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 8"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 255"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "and t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -16(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beqz t0, __picoc_div_zero # check divisor"
+        - "divu t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn floats() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/floats.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "half:"
+        - "la t0, __picoc_sym_half"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark half"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 2"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "fcvt.s.w ft0, t0 # convert"
+        - "fmv.x.w t2, ft0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "fmv.w.x ft0, t0"
+        - "fmv.w.x ft1, t1"
+        - "fdiv.s ft2, ft1, ft0 # operate"
+        - "fmv.x.w t2, ft2"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
         - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
         - "lw ra, 12(sp)"
         - "lw fp, 8(sp)"
         - "addi sp, sp, 16"
         - ret
-        - "\n"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -48"
+        - "sw ra, 44(sp)"
+        - "sw fp, 40(sp)"
+        - "addi fp, sp, 48"
+        - "li t2, 1069547520"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "li t2, 1103626240"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -16(fp) # store local"
+        - "addi t2, zero, 7"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -20(fp) # store local"
+        - "lw t2, -20(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "fcvt.s.w ft0, t0 # convert"
+        - "fmv.x.w t2, ft0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - call half
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -24(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "li t2, -2147483648"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "xor t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "fmv.w.x ft0, t0"
+        - "fmv.w.x ft1, t1"
+        - "fmul.s ft2, ft1, ft0 # operate"
+        - "fmv.x.w t2, ft2"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -24(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "fmv.w.x ft0, t0"
+        - "fmv.w.x ft1, t1"
+        - "fadd.s ft2, ft1, ft0 # operate"
+        - "fmv.x.w t2, ft2"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -28(fp) # store local"
+        - "lw t2, -28(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "fmv.w.x ft0, t0"
+        - "fcvt.w.s t2, ft0, rtz # convert, truncating"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -32(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "fmv.w.x ft0, t0"
+        - "fmv.w.x ft1, t1"
+        - "flt.s t2, ft1, ft0 # compare"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beq t0, zero, .L2"
+        - "addi t2, zero, 100"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - j .L3
+        - ".L2:"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - ".L3:"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -36(fp) # store local"
+        - "lw t2, -32(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -36(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "fcvt.s.w ft0, t0 # convert"
+        - "fmv.x.w t2, ft0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "fmv.w.x ft0, t0"
+        - "fmv.w.x ft1, t1"
+        - "fadd.s ft2, ft1, ft0 # operate"
+        - "fmv.x.w t2, ft2"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "fmv.w.x ft0, t0"
+        - "fcvt.w.s t2, ft0, rtz # convert, truncating"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L1
+        - ".L1:"
+        - "lw ra, 44(sp)"
+        - "lw fp, 40(sp)"
+        - "addi sp, sp, 48"
+        - ret
         "###);
     }
 }
+
+#[cfg(test)]
+mod test_runtime {
+    use crate::lexer;
+    use crate::parser_ast;
+    use crate::selector;
+    use crate::translator;
+    use crate::typer;
+    use crate::IntModel;
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/arith";
+
+    #[test]
+    fn div() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/div.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, zero, 100"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 9"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beqz t0, __picoc_div_zero # check divisor"
+        - "div t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+}
+
+#[cfg(test)]
+mod test_bindings {
+    use crate::lexer;
+    use crate::parser_ast;
+    use crate::selector;
+    use crate::translator;
+    use crate::typer;
+    use crate::IntModel;
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/bindings";
+
+    #[test]
+    fn asnmt_reassign() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/asnmt_reassign.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, zero, 5"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn asnmt_compound() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/asnmt_compound.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, zero, 5"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 3"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "mul t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 2"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beqz t0, __picoc_div_zero # check divisor"
+        - "div t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beqz t0, __picoc_div_zero # check divisor"
+        - "rem t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn incdec_value() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/incdec_value.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -32"
+        - "sw ra, 28(sp)"
+        - "sw fp, 24(sp)"
+        - "addi fp, sp, 32"
+        - "addi t2, zero, 5"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -16(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -20(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -24(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -28(fp) # store local"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1000"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "mul t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -20(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 100"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "mul t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -24(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 10"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "mul t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -28(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
+        - "lw ra, 28(sp)"
+        - "lw fp, 24(sp)"
+        - "addi sp, sp, 32"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn ptr() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/ptr.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "set:"
+        - "la t0, __picoc_sym_set"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark set"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "addi t2, a1, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -16(fp) # store local"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t1, 0(t0) # store through pointer"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -32"
+        - "sw ra, 28(sp)"
+        - "sw fp, 24(sp)"
+        - "addi fp, sp, 32"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "addi t2, fp, -12"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -16(fp) # store local"
+        - "addi t2, zero, 3"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t1, 0(t0) # store through pointer"
+        - "addi t2, fp, -16"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -20(fp) # store local"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t2, 0(t0) # load through pointer"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -20(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t2, 0(t0) # load through pointer"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t1, 0(t0) # store through pointer"
+        - "addi t2, fp, -12"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 2"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "mul t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a1, t0, 0"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - call set
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -24(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L1
+        - ".L1:"
+        - "lw ra, 28(sp)"
+        - "lw fp, 24(sp)"
+        - "addi sp, sp, 32"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn array() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/array.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -64"
+        - "sw ra, 60(sp)"
+        - "sw fp, 56(sp)"
+        - "addi fp, sp, 64"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, fp, -48"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "mul t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t1, 0(t0) # store through pointer"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -52(fp) # store local"
+        - ".L0:"
+        - "lw t2, -52(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 10"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "slt t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beq t0, zero, .L2"
+        - j .L1
+        - ".L1:"
+        - "addi t2, fp, -48"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -52(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "mul t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t2, 0(t0) # load through pointer"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, fp, -48"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -52(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "mul t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t1, 0(t0) # store through pointer"
+        - ".L3:"
+        - "lw t2, -52(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -52(fp) # store local"
+        - j .L0
+        - ".L2:"
+        - "addi t2, fp, -48"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "mul t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -56(fp) # store local"
+        - "lw t2, -56(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t2, 0(t0) # load through pointer"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 10"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "mul t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -56(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t1, 0(t0) # store through pointer"
+        - "addi t2, fp, -48"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 9"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "mul t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t2, 0(t0) # load through pointer"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, fp, -48"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "mul t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t2, 0(t0) # load through pointer"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L4
+        - ".L4:"
+        - "lw ra, 60(sp)"
+        - "lw fp, 56(sp)"
+        - "addi sp, sp, 64"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn structs() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/structs.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -32"
+        - "sw ra, 28(sp)"
+        - "sw fp, 24(sp)"
+        - "addi fp, sp, 32"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, fp, -24"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t1, 0(t0) # store through pointer"
+        - "addi t2, zero, 2"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, fp, -24"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t1, 0(t0) # store through pointer"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, fp, -24"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 8"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t1, 0(t0) # store through pointer"
+        - "addi t2, zero, 7"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, fp, -24"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 8"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t1, 0(t0) # store through pointer"
+        - "addi t2, fp, -24"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 8"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -28(fp) # store local"
+        - "lw t2, -28(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t2, 0(t0) # load through pointer"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 3"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -28(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t1, 0(t0) # store through pointer"
+        - "addi t2, fp, -24"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 8"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t2, 0(t0) # load through pointer"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, fp, -24"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 8"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t2, 0(t0) # load through pointer"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "mul t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, fp, -24"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t2, 0(t0) # load through pointer"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, fp, -24"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t2, 0(t0) # load through pointer"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
+        - "lw ra, 28(sp)"
+        - "lw fp, 24(sp)"
+        - "addi sp, sp, 32"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn chars() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/chars.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "next:"
+        - "la t0, __picoc_sym_next"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark next"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 255"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "and t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 128"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "xor t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 128"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 255"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "and t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 128"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "xor t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 128"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -32"
+        - "sw ra, 28(sp)"
+        - "sw fp, 24(sp)"
+        - "addi fp, sp, 32"
+        - "addi t2, zero, 10"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 255"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "and t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 128"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "xor t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 128"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "addi t2, zero, 97"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 255"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "and t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 128"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "xor t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 128"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - call next
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 255"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "and t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 128"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "xor t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 128"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -16(fp) # store local"
+        - "addi t2, zero, 200"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 255"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "and t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 128"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "xor t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 128"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -20(fp) # store local"
+        - "addi t2, zero, 127"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 255"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "and t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 128"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "xor t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 128"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -24(fp) # store local"
+        - "lw t2, -24(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 255"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "and t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 128"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "xor t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 128"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -24(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -28(fp) # store local"
+        - "lw t2, -28(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -20(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -24(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 92"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L1
+        - ".L1:"
+        - "lw ra, 28(sp)"
+        - "lw fp, 24(sp)"
+        - "addi sp, sp, 32"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn unions() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/unions.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -32"
+        - "sw ra, 28(sp)"
+        - "sw fp, 24(sp)"
+        - "addi fp, sp, 32"
+        - "addi t2, zero, 300"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, fp, -16"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t1, 0(t0) # store through pointer"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, fp, -16"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t1, 0(t0) # store through pointer"
+        - "addi t2, fp, -16"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -20(fp) # store local"
+        - "lw t2, -20(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t2, 0(t0) # load through pointer"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -20(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t1, 0(t0) # store through pointer"
+        - "addi t2, zero, 258"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 255"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "and t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 128"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "xor t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 128"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, fp, -24"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sb t1, 0(t0) # store through pointer"
+        - "addi t2, fp, -16"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t2, 0(t0) # load through pointer"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, fp, -16"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t2, 0(t0) # load through pointer"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, fp, -16"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t2, 0(t0) # load through pointer"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "mul t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, fp, -24"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lb t2, 0(t0) # load through pointer"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
+        - "lw ra, 28(sp)"
+        - "lw fp, 24(sp)"
+        - "addi sp, sp, 32"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn blocks() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/blocks.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -32"
+        - "sw ra, 28(sp)"
+        - "sw fp, 24(sp)"
+        - "addi fp, sp, 32"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -16(fp) # store local"
+        - "addi t2, zero, 10"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -20(fp) # store local"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -20(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -16(fp) # store local"
+        - "addi t2, zero, 100"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -24(fp) # store local"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -24(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -16(fp) # store local"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -20(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -16(fp) # store local"
+        - "lw t2, -20(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -20(fp) # store local"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -16(fp) # store local"
+        - "addi t2, zero, 1000"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -20(fp) # store local"
+        - "addi t2, fp, -12"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -24(fp) # store local"
+        - "lw t2, -24(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t2, 0(t0) # load through pointer"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -20(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -24(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t1, 0(t0) # store through pointer"
+        - "addi t2, zero, 5"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -20(fp) # store local"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -20(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -16(fp) # store local"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
+        - "lw ra, 28(sp)"
+        - "lw fp, 24(sp)"
+        - "addi sp, sp, 32"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn void() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/void.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "bump:"
+        - "la t0, __picoc_sym_bump"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark bump"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t2, 0(t0) # load through pointer"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t1, 0(t0) # store through pointer"
+        - ".L0:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        - "twice:"
+        - "la t0, __picoc_sym_twice"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark twice"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - call bump
+        - "la t0, __picoc_sym_twice"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark twice"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi zero, t0, 0"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - call bump
+        - "la t0, __picoc_sym_twice"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark twice"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi zero, t0, 0"
+        - j .L1
+        - ".L1:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, zero, 40"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "addi t2, fp, -12"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - call twice
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi zero, t0, 0"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L2
+        - ".L2:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+}
+
+#[cfg(test)]
+mod test_functions {
+    use crate::lexer;
+    use crate::optimizer;
+    use crate::parser_ast;
+    use crate::selector;
+    use crate::translator;
+    use crate::typer;
+    use crate::IntModel;
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/bindings";
+
+    #[test]
+    fn attrs() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/attrs.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let levels = optimizer::levels(&src_tree);
+        let assembly =
+            super::allocate_levels(&abs_as, super::OptLevel::O2, &levels, &IntModel::default())
+                .render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "double_it:"
+        - "la t0, __picoc_sym_double_it"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark double_it"
+        - "addi sp, sp, -32"
+        - "sw ra, 28(sp)"
+        - "sw fp, 24(sp)"
+        - "addi fp, sp, 32"
+        - "sw s1, -20(fp) # save"
+        - "sw s2, -24(fp) # save"
+        - "sw s3, -28(fp) # save"
+        - "addi s1, a0, 0"
+        - "sw s1, -12(fp)"
+        - "lw s1, -12(fp)"
+        - "li s2, 2"
+        - "mul s3, s1, s2"
+        - "addi a0, s3, 0"
+        - ".L0:"
+        - "lw s1, -20(fp) # restore"
+        - "lw s2, -24(fp) # restore"
+        - "lw s3, -28(fp) # restore"
+        - "lw ra, 28(sp)"
+        - "lw fp, 24(sp)"
+        - "addi sp, sp, 32"
+        - ret
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, zero, 21"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - call double_it
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L1
+        - ".L1:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn formal_param_multi() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/formal_param_multi.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "f:"
+        - "la t0, __picoc_sym_f"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark f"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "addi t2, a1, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -16(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, zero, 9"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 10"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a1, t0, 0"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - call f
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L1
+        - ".L1:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn printf() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/printf.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        let call = assembly
+            .iter()
+            .skip_while(|l| !l.starts_with("mv t0, sp"))
+            .take(7)
+            .collect::<Vec<_>>();
+        insta::assert_yaml_snapshot!(call, @r###"
+        ---
+        - "mv t0, sp"
+        - "andi sp, sp, -16 # align"
+        - "addi sp, sp, -16"
+        - "sw t0, 0(sp)"
+        - call printf
+        - "lw sp, 0(sp) # unalign"
+        - "la t0, __picoc_sym_main"
+        "###);
+    }
+}
+
+#[cfg(test)]
+mod test_models {
+    use crate::lexer;
+    use crate::parser_ast;
+    use crate::selector;
+    use crate::translator;
+    use crate::typer;
+    use crate::{DataModel, IntModel};
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/arith";
+
+    #[test]
+    fn add_int16() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/add.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let model = IntModel::new(16, DataModel::ILP32).unwrap();
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &model).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, zero, 9"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 10"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "slli t2, t2, 16 # truncate"
+        - "srai t2, t2, 16 # sign extend"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn unsigned_int16() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/unsigned16.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let model = IntModel::new(16, DataModel::ILP32).unwrap();
+        let trgt_tree = translator::translate_for(&src_tree, &model);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &model).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "li t2, 40000"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "slli t2, t2, 16 # truncate"
+        - "srli t2, t2, 16 # zero extend"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 2"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beqz t0, __picoc_div_zero # check divisor"
+        - "divu t2, t1, t0 # operate"
+        - "slli t2, t2, 16 # truncate"
+        - "srli t2, t2, 16 # zero extend"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "li t2, 10000"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sgtu t2, t1, t0 # operate"
+        - "slli t2, t2, 16 # truncate"
+        - "srai t2, t2, 16 # sign extend"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn array_rv64() {
+        let src =
+            fs::read_to_string("tests/fixtures/snap/shared/bindings/array.c").expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let model = IntModel::new(32, DataModel::LP64).unwrap();
+        let trgt_tree = translator::translate_for(&src_tree, &model);
+        let abs_as = selector::select_linked(&trgt_tree, &Default::default(), &model);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O2, &model).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sd t0, 0(t1) # mark main"
+        - "addi sp, sp, -160"
+        - "sd ra, 152(sp)"
+        - "sd fp, 144(sp)"
+        - "addi fp, sp, 160"
+        - "sd s1, -120(fp) # save"
+        - "sd s2, -128(fp) # save"
+        - "sd s3, -136(fp) # save"
+        - "sd s4, -144(fp) # save"
+        - "sd s5, -152(fp) # save"
+        - "li s1, 1"
+        - "addi s2, fp, -96"
+        - "li s3, 0"
+        - "li s4, 8"
+        - "mulw s5, s3, s4"
+        - "add s3, s2, s5"
+        - "sd s1, 0(s3) # store through pointer"
+        - "li s1, 1"
+        - "sd s1, -104(fp)"
+        - ".L0:"
+        - "ld s1, -104(fp)"
+        - "li s2, 10"
+        - "slt s3, s1, s2"
+        - "beq s3, zero, .L2"
+        - ".L1:"
+        - "addi s1, fp, -96"
+        - "ld s2, -104(fp)"
+        - "li s3, 1"
+        - "subw s4, s2, s3"
+        - "li s2, 8"
+        - "mulw s3, s4, s2"
+        - "add s2, s1, s3"
+        - "ld s1, 0(s2) # load through pointer"
+        - "li s2, 1"
+        - "addw s3, s1, s2"
+        - "addi s1, fp, -96"
+        - "ld s2, -104(fp)"
+        - "li s4, 8"
+        - "mulw s5, s2, s4"
+        - "add s2, s1, s5"
+        - "sd s3, 0(s2) # store through pointer"
+        - ".L3:"
+        - "ld s1, -104(fp)"
+        - "li s2, 1"
+        - "addw s3, s1, s2"
+        - "sd s3, -104(fp)"
+        - j .L0
+        - ".L2:"
+        - "addi s1, fp, -96"
+        - "li s2, 4"
+        - "li s3, 8"
+        - "mulw s4, s2, s3"
+        - "add s2, s1, s4"
+        - "sd s2, -112(fp)"
+        - "ld s1, -112(fp)"
+        - "ld s2, 0(s1) # load through pointer"
+        - "li s1, 10"
+        - "mulw s3, s2, s1"
+        - "ld s1, -112(fp)"
+        - "sd s3, 0(s1) # store through pointer"
+        - "addi s1, fp, -96"
+        - "li s2, 9"
+        - "li s3, 8"
+        - "mulw s4, s2, s3"
+        - "add s2, s1, s4"
+        - "ld s1, 0(s2) # load through pointer"
+        - "addi s2, fp, -96"
+        - "li s3, 4"
+        - "li s4, 8"
+        - "mulw s5, s3, s4"
+        - "add s3, s2, s5"
+        - "ld s2, 0(s3) # load through pointer"
+        - "addw s3, s1, s2"
+        - "addi a0, s3, 0"
+        - ".L4:"
+        - "ld s1, -120(fp) # restore"
+        - "ld s2, -128(fp) # restore"
+        - "ld s3, -136(fp) # restore"
+        - "ld s4, -144(fp) # restore"
+        - "ld s5, -152(fp) # restore"
+        - "ld ra, 152(sp)"
+        - "ld fp, 144(sp)"
+        - "addi sp, sp, 160"
+        - ret
+        "###);
+    }
+}
+
+#[cfg(test)]
+mod test_control {
+    use crate::lexer;
+    use crate::parser_ast;
+    use crate::selector;
+    use crate::translator;
+    use crate::typer;
+    use crate::IntModel;
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/control";
+
+    #[test]
+    fn while_false() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/while_false.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - ".L0:"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beq t0, zero, .L2"
+        - j .L1
+        - ".L1:"
+        - "addi t2, zero, 9"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L3
+        - j .L0
+        - ".L2:"
+        - "addi t2, zero, 10"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L3
+        - ".L3:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn while_lt() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/while_lt.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - ".L0:"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 3"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "slt t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beq t0, zero, .L2"
+        - j .L1
+        - ".L1:"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - j .L0
+        - ".L2:"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L3
+        - ".L3:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn while_and() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/while_and.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - ".L0:"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 3"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "slt t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beq t0, zero, .L4"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 2"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "xor t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sltiu t2, t0, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "xori t2, t0, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beq t0, zero, .L4"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - j .L5
+        - ".L4:"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - ".L5:"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beq t0, zero, .L2"
+        - j .L1
+        - ".L1:"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - j .L0
+        - ".L2:"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L3
+        - ".L3:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn cond() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/cond.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "xor t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sltiu t2, t0, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "xori t2, t0, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beq t0, zero, .L1"
+        - "addi t2, zero, 10"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beqz t0, __picoc_div_zero # check divisor"
+        - "div t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - j .L2
+        - ".L1:"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "xor t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sltiu t2, t0, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beq t0, zero, .L3"
+        - "addi t2, zero, 7"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - j .L4
+        - ".L3:"
+        - "addi t2, zero, 8"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - ".L4:"
+        - ".L2:"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -16(fp) # store local"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn switch_dense() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/switch_dense.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "classify:"
+        - "la t0, __picoc_sym_classify"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark classify"
+        - "addi sp, sp, -32"
+        - "sw ra, 28(sp)"
+        - "sw fp, 24(sp)"
+        - "addi fp, sp, 32"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -16(fp) # store local"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -20(fp) # store local"
+        - "lw t2, -20(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "slt t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "bne t0, zero, .L9"
+        - "lw t2, -20(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 5"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sgt t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "bne t0, zero, .L9"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - j .L10
+        - ".L9:"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - ".L10:"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beq t0, zero, .L7"
+        - j .L6
+        - ".L7:"
+        - "lw t2, -20(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "slli t0, t0, 2 # index -> offset"
+        - "la t1, .L11"
+        - "add t0, t1, t0"
+        - "lw t0, 0(t0) # load target's offset"
+        - "add t1, t1, t0"
+        - jr t1
+        - ".align 2"
+        - ".L11: .word .L1 - .L11, .L2 - .L11, .L3 - .L11, .L4 - .L11, .L6 - .L11, .L5 - .L11"
+        - ".L1:"
+        - "addi t2, zero, 10"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -16(fp) # store local"
+        - ".L2:"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -16(fp) # store local"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L8
+        - ".L3:"
+        - ".L4:"
+        - "addi t2, zero, 30"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L8
+        - ".L5:"
+        - "addi t2, zero, 50"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L8
+        - ".L6:"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sub t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L8
+        - ".L0:"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L8
+        - ".L8:"
+        - "lw ra, 28(sp)"
+        - "lw fp, 24(sp)"
+        - "addi sp, sp, 32"
+        - ret
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - call classify
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - call classify
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 3"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - call classify
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 4"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - call classify
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 5"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - call classify
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 9"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - call classify
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi t2, a0, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L12
+        - ".L12:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn do_while() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/do_while.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, super::OptLevel::O0, &IntModel::default()).render();
+        insta::assert_yaml_snapshot!(assembly, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -32"
+        - "sw ra, 28(sp)"
+        - "sw fp, 24(sp)"
+        - "addi fp, sp, 32"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - ".L0:"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -12(fp) # store local"
+        - ".L1:"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "slt t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beq t0, zero, .L2"
+        - j .L0
+        - ".L2:"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -16(fp) # store local"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -20(fp) # store local"
+        - ".L3:"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -16(fp) # store local"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 2"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beqz t0, __picoc_div_zero # check divisor"
+        - "rem t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -24(fp) # store local"
+        - "lw t2, -24(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 0"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "xor t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sltiu t2, t0, 1"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beq t0, zero, .L8"
+        - j .L7
+        - ".L8:"
+        - j .L6
+        - ".L7:"
+        - j .L4
+        - ".L6:"
+        - "lw t2, -20(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "sw t0, -20(fp) # store local"
+        - ".L4:"
+        - "lw t2, -16(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 9"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "slt t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "beq t0, zero, .L5"
+        - j .L3
+        - ".L5:"
+        - "lw t2, -12(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 100"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "mul t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t2, -20(fp) # load local"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L9
+        - ".L9:"
+        - "lw ra, 28(sp)"
+        - "lw fp, 24(sp)"
+        - "addi sp, sp, 32"
+        - ret
+        "###);
+    }
+}
+
+#[cfg(test)]
+mod test_regs {
+    use super::Loc;
+    use crate::{lexer, parser_ast, selector, translator, IntModel, OptLevel};
+    use std::fs;
+
+    #[test]
+    fn registers() {
+        let src = fs::read_to_string("tests/fixtures/snap/opt/cse.c").expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = super::allocate(&abs_as, OptLevel::O2, &IntModel::default()).render();
+        insta::assert_snapshot!(assembly[..32].join("\n"), @r###"
+        .text
+        .globl main
+        .section .text
+        f:
+        la t0, __picoc_sym_f
+        la t1, __picoc_func
+        sw t0, 0(t1) # mark f
+        addi sp, sp, -48
+        sw ra, 44(sp)
+        sw fp, 40(sp)
+        addi fp, sp, 48
+        sw s1, -36(fp) # save
+        sw s2, -40(fp) # save
+        sw s3, -44(fp) # save
+        sw s4, -48(fp) # save
+        addi s1, a0, 0
+        sw s1, -12(fp)
+        addi s1, a1, 0
+        sw s1, -16(fp)
+        lw s1, -12(fp)
+        lw s2, -16(fp)
+        mul s3, s1, s2
+        li s1, 1
+        add s2, s3, s1
+        sw s2, -20(fp)
+        lw s1, -12(fp)
+        lw s2, -16(fp)
+        mul s3, s1, s2
+        li s1, 2
+        add s2, s3, s1
+        sw s2, -24(fp)
+        lw s1, -12(fp)
+        "###);
+    }
+
+    #[test]
+    fn spills() {
+        // three temps live at once, with two registers: the one that ends
+        // last is spilled, and its register goes to the one that starts
+        let intervals = [(0, 0, 9), (1, 1, 3), (2, 2, 4), (3, 5, 6)];
+        let (locs, spills) = super::scan(&intervals, 2);
+        assert_eq!(spills, 1);
+        assert_eq!(locs[&0], Loc::Slot(1));
+        assert_eq!((&locs[&1], &locs[&2]), (&Loc::Reg(1), &Loc::Reg(0)));
+        assert_eq!(locs[&3], Loc::Reg(0));
+    }
+}
@@ -0,0 +1,272 @@
+use crate::Imm;
+use serde::Serialize;
+use std::fmt;
+
+// the assembly the allocators emit, as instructions rather than text, so
+// the passes after them can match on operands instead of parsing lines.
+// render prints it a line per instruction, the form the runtime, counters
+// and symmap take it in
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct AsmProgram {
+    pub insns: Vec<Insn>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub enum Insn {
+    Label(String),
+    Dir(String, Vec<String>),                 // .section .text
+    Data(String, String, Vec<String>),        // a label with its contents: l: .word 1, 2
+    Op(String, Vec<Operand>, Option<String>), // the mnemonic, then an optional comment
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub enum Operand {
+    Reg(String),
+    Imm(Imm),
+    Mem(Imm, String), // an offset from a base register
+    Sym(String),      // a label, or rtz for a rounding mode
+}
+
+pub fn op<const N: usize>(mnemonic: impl fmt::Display, operands: [Operand; N]) -> Insn {
+    Insn::Op(mnemonic.to_string(), operands.into(), None)
+}
+
+pub fn dir<const N: usize>(name: &str, args: [&str; N]) -> Insn {
+    Insn::Dir(name.to_owned(), args.map(|a| a.to_owned()).into())
+}
+
+pub fn reg(r: impl fmt::Display) -> Operand {
+    Operand::Reg(r.to_string())
+}
+
+pub fn imm(k: Imm) -> Operand {
+    Operand::Imm(k)
+}
+
+pub fn mem(off: Imm, base: impl fmt::Display) -> Operand {
+    Operand::Mem(off, base.to_string())
+}
+
+pub fn sym(l: impl fmt::Display) -> Operand {
+    Operand::Sym(l.to_string())
+}
+
+// what moves and lays out a word, which is 4 bytes on rv32 and 8 on rv64
+pub fn load(word: Imm) -> &'static str {
+    match word {
+        8 => "ld",
+        _ => "lw",
+    }
+}
+
+pub fn store(word: Imm) -> &'static str {
+    match word {
+        8 => "sd",
+        _ => "sw",
+    }
+}
+
+pub fn words(word: Imm) -> &'static str {
+    match word {
+        8 => ".dword",
+        _ => ".word",
+    }
+}
+
+pub fn align(word: Imm) -> Insn {
+    match word {
+        8 => dir(".align", ["3"]),
+        _ => dir(".align", ["2"]),
+    }
+}
+
+impl Insn {
+    // the instruction with a comment after it
+    pub fn note(self, comment: impl fmt::Display) -> Insn {
+        match self {
+            Insn::Op(op, operands, _) => Insn::Op(op, operands, Some(comment.to_string())),
+            insn => insn,
+        }
+    }
+}
+
+impl AsmProgram {
+    pub fn render(&self) -> Vec<String> {
+        self.insns.iter().map(|insn| insn.to_string()).collect()
+    }
+
+    // the numbered labels, .L3, renamed .Lf.3, so a function whose labels
+    // were numbered from its own start doesn't share them with another's
+    pub fn relabel(self, f: &str) -> AsmProgram {
+        let rename = |l: String| match l.strip_prefix(".L") {
+            Some(n) if n.parse::<usize>().is_ok() => format!(".L{f}.{n}"),
+            _ => l,
+        };
+        let insns = self
+            .insns
+            .into_iter()
+            .map(|insn| match insn {
+                Insn::Label(l) => Insn::Label(rename(l)),
+                // a jump table's entries are differences of labels
+                Insn::Data(l, kind, vals) => {
+                    let vals = vals
+                        .iter()
+                        .map(|v| v.split(" - ").map(|l| rename(l.to_owned())))
+                        .map(|ls| ls.collect::<Vec<_>>().join(" - "))
+                        .collect();
+                    Insn::Data(rename(l), kind, vals)
+                }
+                Insn::Op(op, operands, comment) => {
+                    let operands = operands
+                        .into_iter()
+                        .map(|o| match o {
+                            Operand::Sym(l) => Operand::Sym(rename(l)),
+                            o => o,
+                        })
+                        .collect();
+                    Insn::Op(op, operands, comment)
+                }
+                insn => insn,
+            })
+            .collect();
+        AsmProgram { insns }
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Reg(r) | Operand::Sym(r) => write!(f, "{r}"),
+            Operand::Imm(k) => write!(f, "{k}"),
+            Operand::Mem(off, base) => write!(f, "{off}({base})"),
+        }
+    }
+}
+
+impl fmt::Display for Insn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |xs: &[String]| match xs.is_empty() {
+            true => String::new(),
+            false => format!(" {}", xs.join(", ")),
+        };
+        match self {
+            Insn::Label(l) => write!(f, "{l}:"),
+            Insn::Dir(name, args) => write!(f, "{name}{}", list(args)),
+            Insn::Data(l, name, args) => write!(f, "{l}: {name}{}", list(args)),
+            Insn::Op(op, operands, comment) => {
+                let operands = operands.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+                write!(f, "{op}{}", list(&operands))?;
+                match comment {
+                    Some(c) => write!(f, " # {c}"),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_asm {
+    use super::{dir, mem, op, reg, sym, Insn};
+    use crate::{allocator, lexer, parser_ast, selector, translator, IntModel, OptLevel};
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/arith";
+
+    #[test]
+    fn render() {
+        let insns = [
+            dir(".section", [".text"]),
+            Insn::Label("main".to_owned()),
+            op("lw", [reg("t0"), mem(-12, "fp")]).note("load local"),
+            op("beq", [reg("t0"), reg("zero"), sym(".L2")]),
+            op("ret", []),
+            Insn::Data(
+                "x".to_owned(),
+                ".word".to_owned(),
+                vec!["1".into(), "2".into()],
+            ),
+        ];
+        let lines = insns.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                ".section .text",
+                "main:",
+                "lw t0, -12(fp) # load local",
+                "beq t0, zero, .L2",
+                "ret",
+                "x: .word 1, 2",
+            ]
+        );
+    }
+
+    #[test]
+    fn div() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/div.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let prg = allocator::allocate(&abs_as, OptLevel::O2, &IntModel::default());
+
+        // each division checks its divisor, in the register it's in
+        let divs = prg
+            .insns
+            .iter()
+            .enumerate()
+            .filter_map(|(i, insn)| match insn {
+                Insn::Op(m, args, _) if m == "div" => Some((i, args[2].clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(!divs.is_empty());
+        for (i, r) in divs {
+            assert!(matches!(
+                &prg.insns[i - 1],
+                Insn::Op(m, args, _) if m == "beqz" && args[0] == r
+            ));
+        }
+    }
+
+    #[test]
+    fn checked_div() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/div.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let model = IntModel {
+            checked_arith: true,
+            ..IntModel::default()
+        };
+        let prg = allocator::allocate(&abs_as, OptLevel::O2, &model);
+
+        // and, under --checked-arith, its operands for INT_MIN / -1
+        let i = prg
+            .insns
+            .iter()
+            .position(|insn| matches!(insn, Insn::Op(m, _, _) if m == "div"))
+            .unwrap();
+        let Insn::Op(_, args, _) = &prg.insns[i] else {
+            unreachable!()
+        };
+        let guard = prg.insns[i - 6..i]
+            .iter()
+            .map(|insn| insn.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            guard,
+            [
+                format!("beqz {}, __picoc_div_zero # check divisor", args[2]),
+                format!("addi t3, {}, 1", args[2]),
+                "li t4, -2147483648".to_owned(),
+                format!("xor t4, {}, t4", args[1]),
+                "or t3, t3, t4".to_owned(),
+                "beqz t3, __picoc_div_overflow # check INT_MIN / -1".to_owned(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,715 @@
+// the program compiled for the evaluator's stack machine, which
+// interpretc0 --vm runs in place of walking the tree. each function is a
+// flat stream of ops over a stack of values, with its locals at fixed
+// offsets in its frame, so running it neither clones environments nor
+// looks names up. the frame is laid out by allocating the locals in a
+// scratch copy of the env as they're compiled, the way the tree walker
+// would as it ran, so operands' arithmetic is read off the same cells.
+// unlike the tree walker, goto works, since a jump is just an op
+
+use crate::evaluator::{self, Arith, RET};
+use crate::source::{self, Span};
+use crate::{
+    LambdaVal, SBinOp, SBitOp, SContract, SContractKind, SExpr, SIncDecOp, SLogOp, SRelOp, SStmt,
+    SUnaryOp, SVarDecl, SVarDef, Spanned, Type, Vnv, Width, RESULT,
+};
+use std::collections::{HashMap, HashSet};
+use std::io;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Program {
+    pub funcs: Vec<Func>,
+    pub main: usize, // an index into funcs
+    pub strs: Vec<String>,
+}
+
+// a frame is the params' cells, then the return slot's, then every
+// local's in the body
+#[derive(Clone, Debug, PartialEq)]
+pub struct Func {
+    pub alias: String,
+    pub params: Vec<(i32, Width)>, // each param's offset, and its first cell's width
+    pub frame: Vec<Width>,
+    pub ops: Vec<Op>,
+    pub spans: Vec<Span>, // the stmt each op is from, for locating errors
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Op {
+    Push(i32),
+    Str(usize),                  // a string literal's address, by its index into strs
+    Local(i32),                  // the address of a cell in the frame
+    Offset(i32),                 // addr -> addr + k, for members
+    Index,                       // base, idx -> base + idx, bounds checked
+    Load,                        // addr -> val
+    Store(Arith, Option<Width>), // val, addr ->, converted to the member's width, or the cell's
+    Update(SBinOp, evaluator::Target, Arith), // val, addr ->, a compound store: *addr op= val
+    Pun(Width),                  // val -> val, a member's bits read as its own width
+    Zero(i32, Type), // a local's cells, from an offset, zeroed and typed as it's declared
+    Unset(i32),      // a local's cell, declared without an initializer, unreadable until stored to
+    Assigned(String), // addr -> addr, once it's checked alias's cell isn't unset
+    Drop,
+    Unary(SUnaryOp, Arith),
+    Bin(SBinOp, Arith, Arith),
+    Ptr(SBinOp, Option<i32>, Option<i32>), // pointer arithmetic, by the cells each pointer steps by
+    Rel(SRelOp, Arith, Arith),
+    Bit(SBitOp, bool), // whether a shift right is unsigned
+    IncDec(SIncDecOp), // addr -> val
+    ToFloat(Arith),
+    Cast(Arith, Type), // val -> val, converted from the arithmetic to the type
+    Jump(usize),
+    JumpIf(bool, usize), // pops, and jumps when it's nonzero, or when it's zero
+    Switch(Vec<(i32, usize)>, usize), // the first arm matching, or the default
+    Call(usize, Vec<Arith>), // the args' arithmetic, to convert them to the params'
+    Intrinsic(String, Vec<Arith>),
+    Alloc(Type),      // a zeroed block on the heap
+    AllocArray(Type), // n -> the first of n zeroed elements on the heap
+    Ret,
+    Fail(String),
+}
+
+// nv is the env the program runs in, its statics already allocated
+pub fn compile(nv: &Vnv) -> Result<Program, io::Error> {
+    let mut aliases = nv.fnv.keys().collect::<Vec<_>>();
+    aliases.sort();
+    let index = aliases
+        .iter()
+        .enumerate()
+        .map(|(i, alias)| (alias.as_str(), i))
+        .collect::<HashMap<_, _>>();
+    let main = *index
+        .get("main")
+        .ok_or(io::Error::other("main function not found"))?;
+
+    let scratch = nv.clone();
+    let base = scratch.mem.borrow().len() as i32;
+    let mut strs = vec![];
+    let funcs = aliases
+        .iter()
+        .map(|alias| {
+            scratch.mem.borrow_mut().truncate(base as usize);
+            let mut c = Compiler {
+                nv: &scratch,
+                index: &index,
+                strs: &mut strs,
+                base,
+                ops: vec![],
+                spans: vec![],
+                span: Span::default(),
+                jumps: vec![],
+                labels: HashMap::new(),
+                gotos: vec![],
+                returns: None,
+                unset: HashSet::new(),
+            };
+            c.func(alias, &nv.fnv[*alias])
+        })
+        .collect::<Result<Vec<_>, io::Error>>()?;
+
+    Ok(Program { funcs, main, strs })
+}
+
+struct Compiler<'a> {
+    nv: &'a Vnv, // a scratch env, whose memory the frame is laid out in
+    index: &'a HashMap<&'a str, usize>,
+    strs: &'a mut Vec<String>,
+    base: i32, // where the frame starts in the scratch memory
+    ops: Vec<Op>,
+    spans: Vec<Span>,
+    span: Span,
+    jumps: Vec<Jumps>, // the enclosing loops and switches, innermost last
+    labels: HashMap<String, usize>,
+    gotos: Vec<(usize, String)>,
+    returns: Option<Vec<usize>>, // the jumps to the epilogue, when there are ensures to check there
+    unset: HashSet<String>,      // the locals declared without an initializer
+}
+
+// the breaks and continues to patch once a loop's end is known. switches
+// take breaks but not continues
+struct Jumps {
+    breaks: Vec<usize>,
+    continues: Option<Vec<usize>>,
+}
+
+impl Compiler<'_> {
+    fn func(&mut self, alias: &str, l: &LambdaVal) -> Result<Func, io::Error> {
+        let mut lvnv = self.nv.vnv.clone();
+        let params =
+            l.fp.iter()
+                .map(|(fp, typ)| {
+                    let addr = evaluator::alloc_zeroed(self.nv, typ)?;
+                    lvnv.insert(fp.clone(), addr);
+                    Ok((addr - self.base, evaluator::width(self.nv, typ)?))
+                })
+                .collect::<Result<Vec<_>, io::Error>>()?;
+        lvnv.insert(RET.to_owned(), evaluator::alloc_zeroed(self.nv, &l.ret)?);
+
+        // contracts see the params, and ensures reads \result from the
+        // return slot, in an epilogue every return jumps to
+        let mut clvnv = lvnv.clone();
+        clvnv.insert(RESULT.to_owned(), lvnv[RET]);
+        let (requires, ensures) = l
+            .contracts
+            .iter()
+            .partition::<Vec<_>, _>(|c| c.kind == SContractKind::Requires);
+        self.contracts(requires, &mut clvnv)?;
+        self.returns = (!ensures.is_empty()).then(Vec::new);
+
+        self.stmts(&l.body, &mut lvnv)?;
+        self.span = Span::default();
+        match (&l.ret, &self.returns) {
+            (Type::Void, None) => {
+                self.emit(Op::Push(0)); // void functions can run off their end
+                self.emit(Op::Ret);
+            }
+            (Type::Void, Some(_)) => (), // into the epilogue
+            _ => {
+                self.emit(Op::Fail("no return stmt".to_owned()));
+            }
+        }
+        if let Some(returns) = self.returns.take() {
+            for at in returns {
+                self.patch(at, self.ops.len());
+            }
+            self.contracts(ensures, &mut clvnv)?;
+            self.span = Span::default();
+            match l.ret {
+                Type::Void => _ = self.emit(Op::Push(0)),
+                _ => {
+                    self.addr(RET, &clvnv)?;
+                    self.emit(Op::Load);
+                }
+            }
+            self.emit(Op::Ret);
+        }
+        for (at, label) in std::mem::take(&mut self.gotos) {
+            let to = *self
+                .labels
+                .get(&label)
+                .ok_or(io::Error::other(format!("goto {label}: undefined label")))?;
+            self.patch(at, to);
+        }
+
+        let frame = self.nv.mem.borrow()[self.base as usize..]
+            .iter()
+            .map(|(_, w)| *w)
+            .collect();
+        Ok(Func {
+            alias: alias.to_owned(),
+            params,
+            frame,
+            ops: std::mem::take(&mut self.ops),
+            spans: std::mem::take(&mut self.spans),
+        })
+    }
+
+    // each contract fails at itself unless it holds
+    fn contracts<'c>(
+        &mut self,
+        contracts: impl IntoIterator<Item = &'c SContract>,
+        lvnv: &mut HashMap<String, i32>,
+    ) -> Result<(), io::Error> {
+        for c in contracts {
+            let outer = self.span;
+            self.span = c.expr.span;
+            self.expr(&c.expr, lvnv)?;
+            let holds = self.emit(Op::JumpIf(true, usize::MAX));
+            self.emit(Op::Fail(evaluator::violated(&c.kind)));
+            self.patch(holds, self.ops.len());
+            self.span = outer;
+        }
+        Ok(())
+    }
+
+    // out of the function, or to the epilogue with the value in the return slot
+    fn ret(&mut self) {
+        let at = match self.returns {
+            Some(_) => self.emit(Op::Jump(usize::MAX)),
+            None => self.emit(Op::Ret),
+        };
+        if let Some(returns) = &mut self.returns {
+            returns.push(at);
+        }
+    }
+
+    fn emit(&mut self, op: Op) -> usize {
+        self.ops.push(op);
+        self.spans.push(self.span);
+        self.ops.len() - 1
+    }
+
+    // points the jump at at to
+    fn patch(&mut self, at: usize, to: usize) {
+        match &mut self.ops[at] {
+            Op::Jump(l) | Op::JumpIf(_, l) => *l = to,
+            op => unreachable!("{op:?} isn't a jump"),
+        }
+    }
+
+    // errors are located at the innermost stmt they're from, as the tree
+    // walker's are
+    fn stmts(
+        &mut self,
+        stmts: &[Spanned<SStmt>],
+        lvnv: &mut HashMap<String, i32>,
+    ) -> Result<(), io::Error> {
+        for stmt in stmts {
+            let outer = self.span;
+            self.span = stmt.span;
+            self.stmt(stmt, lvnv)
+                .map_err(|e| source::locate(e, stmt.span))?;
+            self.span = outer;
+        }
+        Ok(())
+    }
+
+    // a block's bindings are its own
+    fn block(
+        &mut self,
+        stmts: &[Spanned<SStmt>],
+        lvnv: &HashMap<String, i32>,
+    ) -> Result<(), io::Error> {
+        self.stmts(stmts, &mut lvnv.clone())
+    }
+
+    // a loop's body, and the breaks and continues in it, to be patched
+    fn body(
+        &mut self,
+        stmts: &[Spanned<SStmt>],
+        lvnv: &HashMap<String, i32>,
+    ) -> Result<Jumps, io::Error> {
+        self.jumps.push(Jumps {
+            breaks: vec![],
+            continues: Some(vec![]),
+        });
+        let body = self.block(stmts, lvnv);
+        let jumps = self.jumps.pop().expect("pushed above");
+        body.map(|_| jumps)
+    }
+
+    fn close(&mut self, jumps: Jumps, end: usize, cont: usize) {
+        for at in jumps.breaks {
+            self.patch(at, end);
+        }
+        for at in jumps.continues.unwrap_or_default() {
+            self.patch(at, cont);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &SStmt, lvnv: &mut HashMap<String, i32>) -> Result<(), io::Error> {
+        match stmt {
+            // statics were allocated and initialized before main
+            SStmt::Asnmt(SVarDef {
+                alias,
+                is_static: true,
+                ..
+            })
+            | SStmt::Decl(SVarDecl {
+                alias,
+                is_static: true,
+                ..
+            }) if self.nv.vnv.contains_key(alias) => {
+                lvnv.insert(alias.clone(), self.nv.vnv[alias]);
+            }
+            SStmt::Asnmt(var_def) => {
+                let from = evaluator::arith(&var_def.expr, self.nv, lvnv);
+                self.expr(&var_def.expr, lvnv)?; // before the binding's in scope
+                let addr = self.local(&var_def.typ)?;
+                self.emit(Op::Local(addr - self.base));
+                self.emit(Op::Store(from, None));
+                lvnv.insert(var_def.alias.clone(), addr);
+            }
+            SStmt::Decl(var_decl) => {
+                let addr = self.local(&var_decl.typ)?;
+                if !matches!(
+                    var_decl.typ,
+                    Type::Arr(..) | Type::Struct(_) | Type::Union(_)
+                ) {
+                    self.emit(Op::Unset(addr - self.base));
+                    self.unset.insert(var_decl.alias.clone());
+                }
+                lvnv.insert(var_decl.alias.clone(), addr);
+            }
+            SStmt::Update(var_update) => {
+                let from = evaluator::arith(&var_update.expr, self.nv, lvnv);
+                self.expr(&var_update.expr, lvnv)?;
+                self.addr(&var_update.alias, lvnv)?;
+                self.emit(Op::Store(from, None));
+            }
+            SStmt::Store(st) => {
+                let from = evaluator::arith(&st.expr, self.nv, lvnv);
+                self.expr(&st.expr, lvnv)?;
+                self.lval(&st.lval, lvnv)?;
+                match &st.op {
+                    None => {
+                        let w = evaluator::member(&st.lval, self.nv, lvnv);
+                        self.emit(Op::Store(from, w));
+                    }
+                    Some(op) => {
+                        let target = evaluator::target(&st.lval, self.nv, lvnv);
+                        self.emit(Op::Update(op.clone(), target, from));
+                    }
+                }
+            }
+            SStmt::Return(None) if self.returns.is_some() => self.ret(),
+            SStmt::Return(None) => {
+                self.emit(Op::Push(0));
+                self.emit(Op::Ret);
+            }
+            SStmt::Return(Some(e)) => {
+                // through the return slot, so the value converts to its type
+                let from = evaluator::arith(e, self.nv, lvnv);
+                self.expr(e, lvnv)?;
+                self.addr(RET, lvnv)?;
+                self.emit(Op::Store(from, None));
+                if self.returns.is_none() {
+                    self.addr(RET, lvnv)?;
+                    self.emit(Op::Load);
+                }
+                self.ret();
+            }
+            SStmt::Contract(c) => self.contracts([c], lvnv)?,
+            SStmt::Assert(e) => {
+                self.expr(e, lvnv)?;
+                let holds = self.emit(Op::JumpIf(true, usize::MAX));
+                self.emit(Op::Fail("assertion failed".to_owned()));
+                self.patch(holds, self.ops.len());
+            }
+            SStmt::Call(e) => {
+                self.expr(e, lvnv)?;
+                self.emit(Op::Drop);
+            }
+            SStmt::Block(stmts) => self.block(stmts, lvnv)?,
+            SStmt::Label(label) => {
+                self.labels.insert(label.clone(), self.ops.len());
+            }
+            SStmt::Goto(label) => {
+                let at = self.emit(Op::Jump(usize::MAX));
+                self.gotos.push((at, label.clone()));
+            }
+            SStmt::Break => match self.jumps.len() {
+                0 => _ = self.emit(Op::Fail("jump outside a loop".to_owned())),
+                n => {
+                    let at = self.emit(Op::Jump(usize::MAX));
+                    self.jumps[n - 1].breaks.push(at);
+                }
+            },
+            SStmt::Continue => {
+                let at = self.emit(Op::Jump(usize::MAX));
+                match self
+                    .jumps
+                    .iter_mut()
+                    .rev()
+                    .find_map(|j| j.continues.as_mut())
+                {
+                    Some(continues) => continues.push(at),
+                    None => self.ops[at] = Op::Fail("jump outside a loop".to_owned()),
+                }
+            }
+            SStmt::IfEls { cond, then, els } => {
+                self.expr(cond, lvnv)?;
+                let skip = self.emit(Op::JumpIf(false, usize::MAX));
+                self.block(std::slice::from_ref(then.as_ref()), lvnv)?;
+                match els {
+                    Some(els) => {
+                        let over = self.emit(Op::Jump(usize::MAX));
+                        self.patch(skip, self.ops.len());
+                        self.block(std::slice::from_ref(els.as_ref()), lvnv)?;
+                        self.patch(over, self.ops.len());
+                    }
+                    None => self.patch(skip, self.ops.len()),
+                }
+            }
+            SStmt::Switch { cond, arms } => {
+                // the arms are laid out in order, so each falls through
+                self.expr(cond, lvnv)?;
+                let at = self.emit(Op::Switch(vec![], usize::MAX));
+                self.jumps.push(Jumps {
+                    breaks: vec![],
+                    continues: None,
+                });
+                let mut blvnv = lvnv.clone();
+                let mut starts = vec![];
+                for arm in arms {
+                    starts.push(self.ops.len());
+                    if let Err(e) = self.stmts(&arm.body, &mut blvnv) {
+                        self.jumps.pop();
+                        return Err(e);
+                    }
+                }
+                let end = self.ops.len();
+                let jumps = self.jumps.pop().expect("pushed above");
+                let table = arms
+                    .iter()
+                    .zip(&starts)
+                    .filter_map(|(arm, start)| Some((arm.label?, *start)))
+                    .collect();
+                let default = arms
+                    .iter()
+                    .position(|arm| arm.label.is_none())
+                    .map_or(end, |i| starts[i]);
+                self.ops[at] = Op::Switch(table, default);
+                self.close(jumps, end, end);
+            }
+            SStmt::While {
+                cond,
+                body,
+                invariants,
+            } => {
+                let top = self.ops.len();
+                self.contracts(invariants, lvnv)?;
+                self.expr(cond, lvnv)?;
+                let exit = self.emit(Op::JumpIf(false, usize::MAX));
+                let jumps = self.body(body, lvnv)?;
+                self.emit(Op::Jump(top));
+                let end = self.ops.len();
+                self.patch(exit, end);
+                self.close(jumps, end, top);
+            }
+            SStmt::DoWhile { body, cond } => {
+                // the body runs before the first test
+                let top = self.ops.len();
+                let jumps = self.body(body, lvnv)?;
+                let cont = self.ops.len();
+                self.expr(cond, lvnv)?;
+                self.emit(Op::JumpIf(true, top));
+                self.close(jumps, self.ops.len(), cont);
+            }
+            SStmt::For {
+                asnmt,
+                cond,
+                update,
+                body,
+                invariants,
+            } => {
+                // the header's bindings are scoped to the loop
+                let mut flvnv = lvnv.clone();
+                self.stmt(asnmt, &mut flvnv)?;
+                let top = self.ops.len();
+                self.contracts(invariants, &mut flvnv)?;
+                self.expr(cond, &mut flvnv)?;
+                let exit = self.emit(Op::JumpIf(false, usize::MAX));
+                let jumps = self.body(body, &flvnv)?;
+                let cont = self.ops.len();
+                self.stmt(update, &mut flvnv)?;
+                self.emit(Op::Jump(top));
+                let end = self.ops.len();
+                self.patch(exit, end);
+                self.close(jumps, end, cont);
+            }
+        }
+        Ok(())
+    }
+
+    // a new local's cells in the frame, zeroed where it's declared
+    fn local(&mut self, typ: &Type) -> Result<i32, io::Error> {
+        let addr = evaluator::alloc_zeroed(self.nv, typ)?;
+        self.emit(Op::Zero(addr - self.base, typ.clone()));
+        Ok(addr)
+    }
+
+    // a binding's address to read through, checked to have been assigned
+    // when it was declared without an initializer
+    fn read(&mut self, alias: &str, lvnv: &HashMap<String, i32>) -> Result<(), io::Error> {
+        self.addr(alias, lvnv)?;
+        if self.unset.contains(alias) {
+            self.emit(Op::Assigned(alias.to_owned()));
+        }
+        Ok(())
+    }
+
+    // a binding's address: in the frame, or a static's
+    fn addr(&mut self, alias: &str, lvnv: &HashMap<String, i32>) -> Result<(), io::Error> {
+        let addr = evaluator::addr_of(alias, lvnv)?;
+        match addr >= self.base {
+            true => self.emit(Op::Local(addr - self.base)),
+            false => self.emit(Op::Push(addr)),
+        };
+        Ok(())
+    }
+
+    // where an lvalue lives: x, *e or a[e]
+    fn lval(&mut self, e: &SExpr, lvnv: &mut HashMap<String, i32>) -> Result<(), io::Error> {
+        match e {
+            SExpr::VarApp(alias) => self.addr(alias, lvnv)?,
+            SExpr::Deref(l) => self.expr(l, lvnv)?,
+            SExpr::Index { alias, idx } => {
+                self.read(alias, lvnv)?;
+                let addr = evaluator::addr_of(alias, lvnv)?;
+                if let Some(Type::Ptr(_)) = self.nv.types.borrow().get(&addr) {
+                    self.emit(Op::Load); // a pointer's elements are where it points
+                }
+                self.expr(idx, lvnv)?;
+                self.emit(Op::Index);
+            }
+            SExpr::Member { base, field } => {
+                let offset = evaluator::offset(base, field, self.nv, lvnv)?;
+                self.lval(base, lvnv)?;
+                self.emit(Op::Offset(offset));
+            }
+            _ => return Err(io::Error::other("lvalue required")),
+        }
+        Ok(())
+    }
+
+    fn expr(&mut self, e: &SExpr, lvnv: &mut HashMap<String, i32>) -> Result<(), io::Error> {
+        let model = &self.nv.model;
+        match e {
+            SExpr::Int(n) => _ = self.emit(Op::Push(model.wrap(*n as i64))),
+            SExpr::TypedInt { val, typ } => {
+                let val = match typ {
+                    Type::UInt => model.wrap(*val as i64),
+                    _ => *val, // longs keep all 32 bits whatever the int width
+                };
+                self.emit(Op::Push(val));
+            }
+            SExpr::Float { lit, .. } => {
+                let f = evaluator::float(lit.parse().expect("checked by the parser"));
+                self.emit(Op::Push(f));
+            }
+            SExpr::Bool(b) => _ = self.emit(Op::Push(*b as i32)),
+            SExpr::Str(s) => {
+                let n = match self.strs.iter().position(|t| t == s) {
+                    Some(n) => n,
+                    None => {
+                        self.strs.push(s.clone());
+                        self.strs.len() - 1
+                    }
+                };
+                self.emit(Op::Str(n));
+            }
+            SExpr::SizeOf(l) => {
+                let typ = evaluator::type_of(l, self.nv, lvnv)?;
+                let size = self.nv.layouts.sizeof(&typ, model)?;
+                self.emit(Op::Push(size as i32));
+            }
+            SExpr::SizeOfType(t) => {
+                let size = self.nv.layouts.sizeof(t, model)?;
+                self.emit(Op::Push(size as i32));
+            }
+            SExpr::Alloc(t) => _ = self.emit(Op::Alloc(t.clone())),
+            SExpr::AllocArray(t, n) => {
+                self.expr(n, lvnv)?;
+                self.emit(Op::AllocArray(t.clone()));
+            }
+            SExpr::UnaryE { op, l } => {
+                let a = evaluator::arith(l, self.nv, lvnv);
+                self.expr(l, lvnv)?;
+                self.emit(Op::Unary(op.clone(), a));
+            }
+            SExpr::Cast(t, l) => {
+                let a = evaluator::arith(l, self.nv, lvnv);
+                self.expr(l, lvnv)?;
+                self.emit(Op::Cast(a, t.clone()));
+            }
+            SExpr::BinE { op, l, r }
+                if evaluator::stride(l, self.nv)
+                    .or(evaluator::stride(r, self.nv))
+                    .is_some() =>
+            {
+                let (ls, rs) = (evaluator::stride(l, self.nv), evaluator::stride(r, self.nv));
+                self.expr(l, lvnv)?;
+                self.expr(r, lvnv)?;
+                self.emit(Op::Ptr(op.clone(), ls, rs));
+            }
+            SExpr::BinE { op, l, r } => {
+                let (la, ra) = (
+                    evaluator::arith(l, self.nv, lvnv),
+                    evaluator::arith(r, self.nv, lvnv),
+                );
+                self.expr(l, lvnv)?;
+                self.expr(r, lvnv)?;
+                self.emit(Op::Bin(op.clone(), la, ra));
+            }
+            SExpr::LogE { op, l, r } => {
+                // short circuits: r is only evaluated when l doesn't decide
+                let decides = matches!(op, SLogOp::Or);
+                self.expr(l, lvnv)?;
+                let left = self.emit(Op::JumpIf(decides, usize::MAX));
+                self.expr(r, lvnv)?;
+                let right = self.emit(Op::JumpIf(decides, usize::MAX));
+                self.emit(Op::Push(!decides as i32));
+                let over = self.emit(Op::Jump(usize::MAX));
+                self.patch(left, self.ops.len());
+                self.patch(right, self.ops.len());
+                self.emit(Op::Push(decides as i32));
+                self.patch(over, self.ops.len());
+            }
+            SExpr::CondE { cond, then, els } => {
+                // the taken branch converts to the type of the whole
+                let to = evaluator::arith(e, self.nv, lvnv);
+                self.expr(cond, lvnv)?;
+                let skip = self.emit(Op::JumpIf(false, usize::MAX));
+                self.branch(then, to, lvnv)?;
+                let over = self.emit(Op::Jump(usize::MAX));
+                self.patch(skip, self.ops.len());
+                self.branch(els, to, lvnv)?;
+                self.patch(over, self.ops.len());
+            }
+            SExpr::BitE { op, l, r } => {
+                let u = evaluator::arith(l, self.nv, lvnv) == Arith::Unsigned;
+                self.expr(l, lvnv)?;
+                self.expr(r, lvnv)?;
+                self.emit(Op::Bit(op.clone(), u));
+            }
+            SExpr::RelE { op, l, r } => {
+                let (la, ra) = (
+                    evaluator::arith(l, self.nv, lvnv),
+                    evaluator::arith(r, self.nv, lvnv),
+                );
+                self.expr(l, lvnv)?;
+                self.expr(r, lvnv)?;
+                self.emit(Op::Rel(op.clone(), la, ra));
+            }
+            SExpr::VarApp(alias) => {
+                self.read(alias, lvnv)?;
+                // an array's value is its first element's address
+                let addr = evaluator::addr_of(alias, lvnv)?;
+                if !matches!(self.nv.types.borrow().get(&addr), Some(Type::Arr(..))) {
+                    self.emit(Op::Load);
+                }
+            }
+            SExpr::AddrOf(l) => self.lval(l, lvnv)?,
+            SExpr::Deref(_) | SExpr::Index { .. } | SExpr::Member { .. } => {
+                self.lval(e, lvnv)?;
+                self.emit(Op::Load);
+                if let Some(w) = evaluator::member(e, self.nv, lvnv) {
+                    self.emit(Op::Pun(w));
+                }
+            }
+            SExpr::FuncApp { alias, aps } => {
+                let mut froms = vec![];
+                for ap in aps {
+                    froms.push(evaluator::arith(ap, self.nv, lvnv));
+                    self.expr(ap, lvnv)?;
+                }
+                match self.index.get(alias.as_str()) {
+                    Some(f) => self.emit(Op::Call(*f, froms)),
+                    None => self.emit(Op::Intrinsic(alias.clone(), froms)),
+                };
+            }
+            SExpr::IncDec { op, lval } => {
+                match &lval.node {
+                    SExpr::VarApp(alias) => self.read(alias, lvnv)?,
+                    l => self.lval(l, lvnv)?,
+                }
+                self.emit(Op::IncDec(op.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    fn branch(
+        &mut self,
+        e: &SExpr,
+        to: Arith,
+        lvnv: &mut HashMap<String, i32>,
+    ) -> Result<(), io::Error> {
+        let from = evaluator::arith(e, self.nv, lvnv);
+        self.expr(e, lvnv)?;
+        if to == Arith::Float {
+            self.emit(Op::ToFloat(from));
+        }
+        Ok(())
+    }
+}
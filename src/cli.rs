@@ -0,0 +1,547 @@
+use crate::{
+    diagnostics::Warnings, preprocessor::Options, source, DataModel, IntModel, OptLevel, Overflow,
+};
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+// the command line: a strategy, then the files it's given, with options
+// anywhere among them. options take their value as -o out, -oout,
+// --emit asm or --emit=asm. an O0 after the source is an -O0, as older
+// scripts pass it
+pub const USAGE: &str = "usage: picoc089 <strategy> [options] <file.c>...
+
+a file of - is read from stdin. several files are compiled apart, then
+linked into one program.
+
+strategies:
+  interpretc0        walk the program's tree
+  debug              walk the program's tree, stopping for commands on stdin:
+                     break <line>, delete <line>, step, next, finish,
+                     continue, locals, print <var>, backtrace and quit
+  runc89             compile, then assemble and run under an emulator
+  difftest           interpret, then compile and run, and report where the two
+                     runs' exit status, output or faults diverge
+  compilec89         compile to assembly
+  repl               read, eval, print
+  stats              count each file's lines, tokens and definitions
+  fmt                print the file formatted, comments kept. it isn't
+                     preprocessed, and what the parser desugars, like x++
+                     and typedefs, is printed as what it became
+
+options:
+  -o <file>          write the output to file (compilec89 defaults to ./tmp.s,
+                     or when linking, to ./a.out)
+  --link             assemble and link what compilec89 emits with $PICOC_CC,
+                     as several files always are. each .s goes beside the
+                     executable
+  --emit <stage>     stop after tokens, ast, ir, cfg (as json) or asm, and
+                     print it. c prints the checked program as c89, for
+                     gcc or clang, and llvm the ir as llvm's, for clang
+                     or opt. ast-dot and cfg-dot draw the tree and the cfg
+                     for graphviz's dot. symtab lists each symbol's type,
+                     scope depth, location and the lines it's used on
+  --target <arch>    the architecture compiled for: rv32i (riscv32), or
+                     rv64i (riscv64), which is lp64
+  -O<level>          optimize at level 0, 1 or 2. -O1 folds and propagates
+                     constants, deletes dead code, shares common
+                     subexpressions and cleans up the assembly. -O2 also
+                     keeps values in registers
+  -fPIC              compile to be linked into a shared object: globals are
+                     addressed through the got, and the rest pc relative
+  -I <dir>           search dir for included files
+  -D <name>[=<body>] define name as a macro, to 1 if no body is given
+  --int-width=<n>    int is 16 or 32 bits
+  --data-model=<m>   ilp32 or lp64. lp64 is for compiled code only
+  --overflow=<o>     signed int overflow wraps (wrap, the default) or stops
+                     the program with an error (trap), interpreted or compiled
+  --checked-arith    compiled divisions also stop at INT_MIN / -1, which riscv
+                     gives INT_MIN for. their divisor is always checked for 0
+  -W<warning>        warn about unused-variable, unreachable-code, implicit-int,
+                     return-type or maybe-uninitialized (implicit-int and
+                     return-type are on). -Wno-<warning> turns one off, -Wall
+                     turns them all on, and -Werror makes them errors
+  --tab-width=<n>    tab stops for the columns in errors (8)
+  --vm               interpretc0 compiles the program to bytecode for a stack
+                     machine, rather than walking its tree
+  --trace            interpretc0 prints each stmt it runs to stderr, with the
+                     variables it changed and what it returned
+  --max-steps <n>    interpretc0 stops the program after n stmts and loop
+                     tests (ops with --vm)
+  --max-heap <n>     interpretc0 stops the program once alloc has handed out
+                     more than n bytes
+  --dyn-check        interpretc0 checks the //@requires, //@ensures,
+                     //@loop_invariant and //@assert contracts, which are
+                     otherwise comments
+  --count            count the instructions a compiled program runs
+  --watch            runc89 runs the program again each time one of its files
+                     changes, reusing the executable when what's compiled
+                     didn't, as when only comments or spacing did
+  --quiet            print nothing but the program's output and errors
+  --help             print this";
+
+const STRATEGIES: [&str; 8] = [
+    "interpretc0",
+    "debug",
+    "runc89",
+    "difftest",
+    "compilec89",
+    "repl",
+    "stats",
+    "fmt",
+];
+
+// exit statuses, so a script can tell the picoc089 invocation was wrong
+// from the program it was given being wrong, or failing as it ran
+pub const EXIT_COMPILE: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_RUNTIME: i32 = 3;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Emit {
+    Tokens,
+    Ast,
+    Ir,
+    Cfg,
+    Asm,
+    C,
+    Llvm,
+    AstDot,
+    CfgDot,
+    Symtab,
+}
+
+// todo: --target-os. only elf for linux is emitted: windows x64 and mach-o
+//       would need the x86-64 target first, then their directives, a
+//       leading underscore on mach-o symbols, and the microsoft convention
+//       for calls
+#[derive(Clone, Debug, PartialEq)]
+pub enum Target {
+    RV32I,
+    RV64I,
+}
+
+#[derive(Clone, Debug)]
+pub struct Args {
+    pub strat: String,
+    pub files: Vec<String>,
+    pub output: Option<PathBuf>,
+    pub emit: Option<Emit>,
+    pub target: Target,
+    pub opt: OptLevel,
+    pub model: IntModel,
+    pub pp: Options,
+    pub tab_width: usize,
+    pub count: bool,
+    pub quiet: bool,
+    pub link: bool,
+    pub pic: bool,
+    pub vm: bool,
+    pub dyn_check: bool,
+    pub trace: bool,
+    pub watch: bool,
+    pub max_steps: Option<u64>,
+    pub max_heap: Option<usize>,
+    pub warnings: Warnings,
+    pub help: bool,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Args {
+            strat: String::new(),
+            files: vec![],
+            output: None,
+            emit: None,
+            target: Target::RV32I,
+            opt: OptLevel::O0,
+            model: IntModel::default(),
+            pp: Options::default(),
+            tab_width: 8,
+            count: false,
+            quiet: false,
+            link: false,
+            pic: false,
+            vm: false,
+            dyn_check: false,
+            trace: false,
+            watch: false,
+            max_steps: None,
+            max_heap: None,
+            warnings: Warnings::default(),
+            help: false,
+        }
+    }
+}
+
+// an error is a usage error, for printing with the usage
+pub fn parse_args(argv: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut args = Args::default();
+    let mut argv = argv.into_iter();
+    let mut positional = vec![];
+
+    while let Some(arg) = argv.next() {
+        // the option, and its value if it's in the same arg
+        let (opt, inline) = match arg.split_once('=') {
+            Some((opt, val)) if arg.starts_with("--") => (opt.to_owned(), Some(val.to_owned())),
+            _ => match arg.as_str() {
+                a if a.starts_with("--") => (arg.clone(), None),
+                a if a.len() > 2 && a.starts_with('-') && !a.starts_with("-O") => {
+                    (a[..2].to_owned(), Some(a[2..].to_owned()))
+                }
+                a => (a.to_owned(), None),
+            },
+        };
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| argv.next())
+                .ok_or(format!("{opt} needs a value"))
+        };
+
+        match opt.as_str() {
+            "--help" | "-h" => args.help = true,
+            "--quiet" | "-q" => args.quiet = true,
+            "--count" => args.count = true,
+            "--link" => args.link = true,
+            "--vm" => args.vm = true,
+            "--dyn-check" => args.dyn_check = true,
+            "--trace" => args.trace = true,
+            "--watch" => args.watch = true,
+            "-W" => args.warnings.set(&value()?)?,
+            "-f" => match value()?.as_str() {
+                "PIC" | "pic" => args.pic = true,
+                f => return Err(format!("unknown flag: \"-f{f}\"")),
+            },
+            "-o" => args.output = Some(value()?.into()),
+            "--emit" => {
+                args.emit = Some(match value()?.as_str() {
+                    "tokens" => Emit::Tokens,
+                    "ast" => Emit::Ast,
+                    "ir" => Emit::Ir,
+                    "cfg" => Emit::Cfg,
+                    "asm" => Emit::Asm,
+                    "c" => Emit::C,
+                    "llvm" => Emit::Llvm,
+                    "ast-dot" => Emit::AstDot,
+                    "cfg-dot" => Emit::CfgDot,
+                    "symtab" => Emit::Symtab,
+                    e => return Err(format!("unknown stage to emit: {e}")),
+                })
+            }
+            "--target" => {
+                args.target = match value()?.as_str() {
+                    "rv32i" | "riscv32" => Target::RV32I,
+                    "rv64i" | "riscv64" => Target::RV64I,
+                    t => return Err(format!("unknown target: {t}")),
+                }
+            }
+            "-I" | "--include-path" => args.pp.include_paths.push(value()?.into()),
+            "-D" | "--define" => {
+                let def = value()?;
+                let (name, body) = def.split_once('=').unwrap_or((&def, "1"));
+                args.pp.defines.push((name.to_owned(), body.to_owned()));
+            }
+            "--max-steps" => {
+                args.max_steps = Some(
+                    value()?
+                        .parse()
+                        .map_err(|_| "invalid max steps given (invalid number)")?,
+                )
+            }
+            "--max-heap" => {
+                args.max_heap = Some(
+                    value()?
+                        .parse()
+                        .map_err(|_| "invalid max heap given (invalid number)")?,
+                )
+            }
+            "--tab-width" => {
+                args.tab_width = value()?
+                    .parse()
+                    .map_err(|_| "invalid tab width given (invalid number)")?
+            }
+            "--int-width" => {
+                let width = value()?
+                    .parse()
+                    .map_err(|_| "invalid int width given (invalid number)")?;
+                args.model.int_width = IntModel::new(width, args.model.data_model.clone())
+                    .map_err(|e| e.trim_start_matches("picoc-error: "))?
+                    .int_width;
+            }
+            "--checked-arith" => args.model.checked_arith = true,
+            "--overflow" => {
+                args.model.overflow = match value()?.as_str() {
+                    "wrap" => Overflow::Wrap,
+                    "trap" => Overflow::Trap,
+                    o => return Err(format!("unknown overflow behavior: {o}")),
+                }
+            }
+            "--data-model" => {
+                args.model.data_model = match value()?.as_str() {
+                    "ilp32" => DataModel::ILP32,
+                    "lp64" => DataModel::LP64,
+                    m => return Err(format!("unknown data model: {m}")),
+                }
+            }
+            o if o.starts_with("-O") => args.opt = opt_level(&o[2..])?,
+            o if o.starts_with('-') && o != "-" => return Err(format!("unknown flag: {o:?}")),
+            _ => positional.push(arg),
+        }
+    }
+
+    // rv64's pointers and longs are a word, whatever --data-model said
+    if args.target == Target::RV64I {
+        args.model.data_model = DataModel::LP64;
+    }
+    args.pp.model = args.model.clone();
+
+    let mut positional = positional.into_iter();
+    if args.help {
+        return Ok(args);
+    }
+    args.strat = positional.next().ok_or("no strategy given")?;
+    if !STRATEGIES.contains(&args.strat.as_str()) {
+        return Err(format!("unknown strategy: {:?}", args.strat));
+    }
+    // fmt's output is the source
+    if args.strat == "fmt" {
+        args.quiet = true;
+    }
+    if args.vm && args.strat != "interpretc0" {
+        return Err("--vm is for interpretc0".to_owned());
+    }
+    if args.dyn_check && args.strat != "interpretc0" {
+        return Err("--dyn-check is for interpretc0".to_owned());
+    }
+    if args.trace && args.strat != "interpretc0" {
+        return Err("--trace is for interpretc0".to_owned());
+    }
+    if args.trace && args.vm {
+        return Err("--trace walks the tree, so it can't go with --vm".to_owned());
+    }
+    if args.watch && args.strat != "runc89" {
+        return Err("--watch is for runc89".to_owned());
+    }
+    if args.model.checked_arith && args.strat == "interpretc0" {
+        return Err("--checked-arith is for compiled code".to_owned());
+    }
+    let interpreted = ["interpretc0", "debug", "difftest", "repl"].contains(&args.strat.as_str());
+    if args.model.data_model == DataModel::LP64 && interpreted {
+        return Err("lp64 is for compiled code, as the interpreter's longs are 32 bits".to_owned());
+    }
+    if (args.max_steps.is_some() || args.max_heap.is_some()) && args.strat != "interpretc0" {
+        return Err("--max-steps and --max-heap are for interpretc0".to_owned());
+    }
+    args.files = positional.collect();
+    // the O0 older scripts pass after the source
+    if let [_, .., level] = args.files.as_slice() {
+        let level = level.strip_prefix('O').filter(|l| l.parse::<u32>().is_ok());
+        if let Some(level) = level {
+            args.opt = opt_level(level)?;
+            args.files.pop();
+        }
+    }
+    if args.files.is_empty() && args.strat != "repl" {
+        return Err("no source file given".to_owned());
+    }
+    if args.watch && args.files.iter().any(|f| f == "-") {
+        return Err("--watch reads its files again, so they can't be stdin".to_owned());
+    }
+    // only compiled programs are linked
+    if args.files.len() > 1 {
+        if !["compilec89", "runc89", "stats"].contains(&args.strat.as_str()) {
+            return Err(format!("{} takes one file", args.strat));
+        }
+        if args.emit.is_some() || args.count {
+            return Err("--emit and --count take one file".to_owned());
+        }
+    }
+    Ok(args)
+}
+
+// a file's decoded text, or stdin's for -
+pub fn read_source(file: &str, mut stdin: impl Read) -> Result<String, io::Error> {
+    let bytes = match file {
+        "-" => {
+            let mut bytes = vec![];
+            stdin.read_to_end(&mut bytes)?;
+            bytes
+        }
+        file => fs::read(file)?,
+    };
+    source::decode(&bytes)
+}
+
+// what errors call a file, and the path includes are relative to: for
+// stdin, the working directory
+pub fn name(file: &str) -> &str {
+    match file {
+        "-" => "<stdin>",
+        file => file,
+    }
+}
+
+fn opt_level(level: &str) -> Result<OptLevel, String> {
+    let level = level
+        .parse::<u32>()
+        .map_err(|_| format!("invalid optimization level given: {level}"))?;
+    OptLevel::try_from(level).map_err(|e| e.trim_start_matches("picoc-error: ").to_owned())
+}
+
+#[cfg(test)]
+mod test_cli {
+    use super::{Emit, Target};
+    use crate::{diagnostics::Warning, DataModel, OptLevel, Overflow};
+
+    fn parse(args: &str) -> Result<super::Args, String> {
+        super::parse_args(args.split_whitespace().map(str::to_owned))
+    }
+
+    #[test]
+    fn flags() {
+        let args =
+            parse("compilec89 -o out.s --emit=asm -I inc -Iinc2 -DN=3 -DDEBUG f.c -O2").unwrap();
+        assert_eq!(args.strat, "compilec89");
+        assert_eq!(args.files, ["f.c"]);
+        assert_eq!(args.output, Some("out.s".into()));
+        assert_eq!(args.emit, Some(Emit::Asm));
+        assert_eq!(args.opt, OptLevel::O2);
+        assert_eq!(
+            args.pp.include_paths,
+            ["inc".into(), "inc2".into()] as [std::path::PathBuf; 2]
+        );
+        #[rustfmt::skip]
+        assert_eq!(args.pp.defines, [("N".to_owned(), "3".to_owned()), ("DEBUG".to_owned(), "1".to_owned())]);
+
+        // the older forms still work
+        assert!(parse("compilec89 --link f.c").unwrap().link);
+        assert!(parse("compilec89 -fPIC f.c").unwrap().pic);
+
+        let args = parse("compilec89 --int-width=16 --data-model lp64 --quiet f.c O1").unwrap();
+        assert_eq!((args.files.len(), args.opt), (1, OptLevel::O1));
+        assert_eq!(
+            (args.model.int_width, args.model.data_model),
+            (16, DataModel::LP64)
+        );
+        assert!(args.quiet);
+        assert!(parse("interpretc0 --vm f.c").unwrap().vm);
+        assert!(parse("interpretc0 --vm --dyn-check f.c").unwrap().dyn_check);
+        assert!(parse("interpretc0 --trace f.c").unwrap().trace);
+        assert!(parse("runc89 --watch f.c g.c").unwrap().watch);
+        let args = parse("interpretc0 --overflow=trap --int-width 16 f.c").unwrap();
+        assert_eq!(
+            (args.model.overflow, args.model.int_width),
+            (Overflow::Trap, 16)
+        );
+        assert!(
+            parse("runc89 --checked-arith f.c")
+                .unwrap()
+                .model
+                .checked_arith
+        );
+        let args = parse("interpretc0 --max-steps 1000 --max-heap=4096 f.c").unwrap();
+        assert_eq!((args.max_steps, args.max_heap), (Some(1000), Some(4096)));
+
+        let args = parse("compilec89 --target riscv64 --data-model=ilp32 f.c").unwrap();
+        assert_eq!(args.target, Target::RV64I);
+        assert_eq!(args.model.data_model, DataModel::LP64);
+        assert_eq!(args.model.word(), 8);
+
+        let args = parse("runc89 -Wall -Wno-implicit-int -Werror f.c").unwrap();
+        assert!(args.warnings.error);
+        assert!(args.warnings.enabled(Warning::UnusedVariable));
+        assert!(!args.warnings.enabled(Warning::ImplicitInt));
+
+        assert!(parse("--help").unwrap().help);
+        assert!(parse("repl").is_ok());
+        assert!(parse("fmt f.c").unwrap().quiet);
+        assert_eq!(
+            parse("interpretc0 --emit c f.c").unwrap().emit,
+            Some(Emit::C)
+        );
+        assert_eq!(
+            parse("compilec89 --emit=llvm f.c").unwrap().emit,
+            Some(Emit::Llvm)
+        );
+        assert_eq!(
+            parse("compilec89 --emit cfg-dot f.c").unwrap().emit,
+            Some(Emit::CfgDot)
+        );
+        assert_eq!(
+            parse("interpretc0 --emit symtab f.c").unwrap().emit,
+            Some(Emit::Symtab)
+        );
+    }
+
+    #[test]
+    fn usage_errors() {
+        let errs = [
+            "",
+            "compilec89",
+            "compilec89 f.c -o",
+            "compilec89 --emit bytes f.c",
+            "compilec89 --target x86 f.c",
+            "compilec89 -O7 f.c",
+            "compilec89 --frobnicate f.c",
+            "compilec89 -fomit-frame-pointer f.c",
+            "compilec89 -Wpedantic f.c",
+            "jit f.c",
+            "interpretc0 f.c g.c",
+            "fmt f.c g.c",
+            "runc89 --count f.c g.c",
+            "runc89 --vm f.c",
+            "compilec89 --dyn-check f.c",
+            "interpretc0 --max-steps lots f.c",
+            "runc89 --max-heap 64 f.c",
+            "runc89 --trace f.c",
+            "interpretc0 --vm --trace f.c",
+            "compilec89 --overflow saturate f.c",
+            "interpretc0 --checked-arith f.c",
+            "interpretc0 --data-model=lp64 f.c",
+            "difftest --target riscv64 f.c",
+            "interpretc0 --watch f.c",
+            "runc89 --watch -",
+        ]
+        .map(|args| parse(args).unwrap_err());
+        insta::assert_debug_snapshot!(errs, @r###"
+        [
+            "no strategy given",
+            "no source file given",
+            "-o needs a value",
+            "unknown stage to emit: bytes",
+            "unknown target: x86",
+            "invalid optimization level given",
+            "unknown flag: \"--frobnicate\"",
+            "unknown flag: \"-fomit-frame-pointer\"",
+            "unknown warning: -Wpedantic",
+            "unknown strategy: \"jit\"",
+            "interpretc0 takes one file",
+            "fmt takes one file",
+            "--emit and --count take one file",
+            "--vm is for interpretc0",
+            "--dyn-check is for interpretc0",
+            "invalid max steps given (invalid number)",
+            "--max-steps and --max-heap are for interpretc0",
+            "--trace is for interpretc0",
+            "--trace walks the tree, so it can't go with --vm",
+            "unknown overflow behavior: saturate",
+            "--checked-arith is for compiled code",
+            "lp64 is for compiled code, as the interpreter's longs are 32 bits",
+            "lp64 is for compiled code, as the interpreter's longs are 32 bits",
+            "--watch is for runc89",
+            "--watch reads its files again, so they can't be stdin",
+        ]
+        "###);
+    }
+
+    #[test]
+    fn stdin() {
+        let args = parse("interpretc0 - O0").unwrap();
+        assert_eq!(args.files, ["-"]);
+
+        let src = super::read_source("-", "\u{feff}int main() { return 0; }\r\n".as_bytes());
+        assert_eq!(src.unwrap(), "int main() { return 0; }\n");
+        assert_eq!(super::name("-"), "<stdin>");
+    }
+}
@@ -0,0 +1,192 @@
+// --count instruments compiled code with cost counters, dumped to stderr
+// at exit, so implementations can be compared like c0's cost semantics.
+// each basic block adds its own static counts on entry, so totals are
+// approximate: pseudo instructions count as one, and a fault skips the dump
+//   - __picoc_counts holds executed instructions, loads and stores
+//   - the counters' own updates aren't counted
+
+use crate::asm;
+
+const BUMP: [&str; 3] = ["0", "4", "8"]; // offsets into __picoc_counts
+
+pub fn instrument(assembly: &[String], word: i32) -> Vec<String> {
+    let lines = assembly
+        .iter()
+        .flat_map(|s| s.lines())
+        .filter(|l| !l.trim().is_empty())
+        .collect::<Vec<_>>();
+
+    // blocks start after labels and after control transfers
+    let mut instrumented = vec![];
+    let mut block: Vec<&str> = vec![];
+    for l in lines {
+        let op = l.split_whitespace().next().unwrap_or("");
+        if op.ends_with(':') {
+            flush(&mut instrumented, &mut block);
+            instrumented.push(l.to_owned());
+        } else {
+            block.push(l);
+            if matches!(
+                op,
+                "j" | "jal" | "jalr" | "ret" | "call" | "beq" | "bne" | "blt" | "bge" | "beqz"
+            ) {
+                flush(&mut instrumented, &mut block);
+            }
+        }
+    }
+    flush(&mut instrumented, &mut block);
+
+    instrumented.into_iter().chain(support(word)).collect()
+}
+
+fn flush(instrumented: &mut Vec<String>, block: &mut Vec<&str>) {
+    let instrs = block
+        .iter()
+        .map(|l| l.split('#').next().unwrap_or("").trim())
+        .filter(|l| !l.is_empty() && !l.starts_with('.'))
+        .map(|l| l.split_whitespace().next().unwrap_or(""))
+        .collect::<Vec<_>>();
+
+    if !instrs.is_empty() {
+        let counts = [
+            instrs.len(),
+            instrs
+                .iter()
+                .filter(|op| matches!(**op, "ld" | "lw" | "lh" | "lhu" | "lb" | "lbu"))
+                .count(),
+            instrs
+                .iter()
+                .filter(|op| matches!(**op, "sd" | "sw" | "sh" | "sb"))
+                .count(),
+        ];
+
+        instrumented.push("la t3, __picoc_counts # count".to_owned());
+        for (off, n) in BUMP.iter().zip(counts).filter(|(_, n)| *n > 0) {
+            instrumented.push(format!(
+                "lw t4, {off}(t3)\naddi t4, t4, {n}\nsw t4, {off}(t3)"
+            ));
+        }
+    }
+
+    instrumented.extend(block.drain(..).map(|l| l.to_owned()));
+}
+
+// the dump, run from .fini_array, whose entry and saved ra are a word
+fn support(word: i32) -> Vec<String> {
+    let (store, load) = (asm::store(word), asm::load(word));
+    vec![
+        ".section .rodata".to_owned(),
+        "__picoc_counts_fmt: .string \"instructions %u, loads %u, stores %u\\n\"".to_owned(),
+        ".section .data".to_owned(),
+        ".align 2".to_owned(),
+        "__picoc_counts: .word 0, 0, 0".to_owned(),
+        ".section .fini_array,\"aw\"".to_owned(),
+        asm::align(word).to_string(),
+        format!("{} __picoc_counts_dump", asm::words(word)),
+        ".section .text".to_owned(),
+        "__picoc_counts_dump:".to_owned(),
+        "addi sp, sp, -16".to_owned(),
+        format!("{store} ra, {}(sp)", 16 - word),
+        "la t0, __picoc_counts".to_owned(),
+        "lw a2, 0(t0)".to_owned(),
+        "lw a3, 4(t0)".to_owned(),
+        "lw a4, 8(t0)".to_owned(),
+        "li a0, 2 # stderr".to_owned(),
+        "la a1, __picoc_counts_fmt".to_owned(),
+        "call dprintf".to_owned(),
+        format!("{load} ra, {}(sp)", 16 - word),
+        "addi sp, sp, 16".to_owned(),
+        "ret".to_owned(),
+    ]
+}
+
+#[cfg(test)]
+mod test_arith {
+    use crate::lexer;
+    use crate::parser_ast;
+    use crate::selector;
+    use crate::translator;
+    use crate::typer;
+    use crate::{allocator, IntModel, OptLevel};
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/arith";
+
+    #[test]
+    fn add() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/add.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = allocator::allocate(&abs_as, OptLevel::O0, &IntModel::default()).render();
+        let instrumented = super::instrument(&assembly, 4);
+        insta::assert_yaml_snapshot!(instrumented, @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t3, __picoc_counts # count"
+        - "lw t4, 0(t3)\naddi t4, t4, 24\nsw t4, 0(t3)"
+        - "lw t4, 4(t3)\naddi t4, t4, 3\nsw t4, 4(t3)"
+        - "lw t4, 8(t3)\naddi t4, t4, 6\nsw t4, 8(t3)"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - "addi t2, zero, 9"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "addi t2, zero, 10"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "lw t1, 0(sp) # t1 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "add t2, t1, t0 # operate"
+        - "addi sp, sp, -8 # grow stack"
+        - "sw t2, 0(sp) # push t2 ->"
+        - "lw t0, 0(sp) # t0 <- pop"
+        - "addi sp, sp, 8 # shrink stack"
+        - "addi a0, t0, 0"
+        - j .L0
+        - ".L0:"
+        - "la t3, __picoc_counts # count"
+        - "lw t4, 0(t3)\naddi t4, t4, 4\nsw t4, 0(t3)"
+        - "lw t4, 4(t3)\naddi t4, t4, 2\nsw t4, 4(t3)"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        - ".section .rodata"
+        - "__picoc_counts_fmt: .string \"instructions %u, loads %u, stores %u\\n\""
+        - ".section .data"
+        - ".align 2"
+        - "__picoc_counts: .word 0, 0, 0"
+        - ".section .fini_array,\"aw\""
+        - ".align 2"
+        - ".word __picoc_counts_dump"
+        - ".section .text"
+        - "__picoc_counts_dump:"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "la t0, __picoc_counts"
+        - "lw a2, 0(t0)"
+        - "lw a3, 4(t0)"
+        - "lw a4, 8(t0)"
+        - "li a0, 2 # stderr"
+        - "la a1, __picoc_counts_fmt"
+        - call dprintf
+        - "lw ra, 12(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+}
@@ -0,0 +1,428 @@
+use crate::{
+    evaluator,
+    source::{self, SourceMap, Span},
+    Budget, Event, Hook, IntModel, SDef, SPrg, SStmt, Signal, Sink, Spanned, Vnv,
+};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::io::{self, BufRead};
+use std::rc::Rc;
+
+// a source level debugger over the tree walker. it stops before main's
+// first stmt, and reads commands until one runs the program on:
+//   - break <line>    stops before each stmt on line
+//   - delete <line>   removes line's breakpoint
+//   - step            runs to the next stmt, into calls
+//   - next            runs to the next stmt, over calls
+//   - finish          runs until the function returns
+//   - continue        runs to the next breakpoint
+//   - locals          prints the stopped frame's variables
+//   - print <var>     prints var, which may be a global
+//   - backtrace       prints the calls the program is in, innermost first
+//   - quit            stops the program
+// commands go by their first letter too, and bt is backtrace. once input
+// runs out the program runs to its end
+pub fn debug(
+    prg: &SPrg,
+    text: &str,
+    map: &SourceMap,
+    input: impl BufRead + 'static,
+    out: Sink,
+    model: IntModel,
+) -> Result<Option<i32>, io::Error> {
+    let session = Rc::new(RefCell::new(Session {
+        text: text.to_owned(),
+        map: map.clone(),
+        stmts: stmt_lines(prg, text, map),
+        breaks: BTreeSet::new(),
+        mode: Mode::Step,
+        lines: vec![],
+        input: Box::new(input.lines()),
+        out: out.clone(),
+        quit: false,
+    }));
+
+    let hooked = session.clone();
+    let hook = Hook(Some(Rc::new(RefCell::new(
+        move |event: Event, gnv: &Vnv, lvnv: &HashMap<String, i32>| match event {
+            Event::Stmt(s) => hooked.borrow_mut().stop(s.span, gnv, lvnv),
+            Event::Done(..) => Ok(()),
+        },
+    ))));
+    let exit = evaluator::eval_prg_hooked(prg, model, out.clone(), Budget::default(), hook);
+    match exit {
+        Err(_) if session.borrow().quit => Ok(None),
+        Err(e) => Err(e),
+        Ok(exit) => {
+            writeln!(out.0.borrow_mut(), "exited with {exit}")?;
+            Ok(Some(exit))
+        }
+    }
+}
+
+// --trace. each simple stmt the walker runs is printed once it's run, with
+// the variables it changed or declared, and the return, break or continue
+// it left by. the stmts with bodies aren't, since theirs are
+pub fn tracer(text: &str, map: &SourceMap, out: Sink) -> Hook {
+    let (text, map) = (text.to_owned(), map.clone());
+    let mut befores = vec![]; // the variables as they were before each stmt still running
+    Hook(Some(Rc::new(RefCell::new(
+        move |event: Event, gnv: &Vnv, lvnv: &HashMap<String, i32>| {
+            let vars = || {
+                vars(lvnv, |_| true)
+                    .into_iter()
+                    .map(|(name, addr)| Ok((name, evaluator::show(gnv, addr)?)))
+                    .collect::<Result<HashMap<_, _>, io::Error>>()
+            };
+            let (s, sig) = match event {
+                Event::Stmt(_) => return vars().map(|vars| befores.push(vars)),
+                Event::Done(s, sig) => (s, sig),
+            };
+            let before = befores.pop().unwrap_or_default();
+            if matches!(
+                s.node,
+                SStmt::IfEls { .. }
+                    | SStmt::Switch { .. }
+                    | SStmt::While { .. }
+                    | SStmt::DoWhile { .. }
+                    | SStmt::For { .. }
+                    | SStmt::Block(_)
+            ) {
+                return Ok(());
+            }
+
+            let mut changed = vars()?
+                .into_iter()
+                .filter(|(name, val)| before.get(name) != Some(val))
+                .map(|(name, val)| format!("{name} = {val}"))
+                .collect::<Vec<_>>();
+            changed.sort();
+            changed.extend(match sig {
+                Some(Signal::Ret(val)) => Some(format!("return {val}")),
+                Some(Signal::Break) => Some("break".to_owned()),
+                Some(Signal::Continue) => Some("continue".to_owned()),
+                None => None,
+            });
+            let (file, line) = origin(&text, &map, s.span);
+            let src = text[s.span.offset(&text)..].lines().next();
+            let mut out = out.0.borrow_mut();
+            match changed.is_empty() {
+                true => writeln!(out, "{file}:{line}: {}", src.unwrap_or_default().trim()),
+                false => writeln!(
+                    out,
+                    "{file}:{line}: {} => {}",
+                    src.unwrap_or_default().trim(),
+                    changed.join(", ")
+                ),
+            }
+        },
+    ))))
+}
+
+// how far the program runs before it stops again. next stops at the first
+// stmt at most as deep in calls as the one it was given at
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Mode {
+    Step,
+    Next(usize),
+    Continue,
+}
+
+struct Session {
+    text: String,
+    map: SourceMap,
+    stmts: BTreeSet<usize>, // the lines a stmt starts on, where a break can stop
+    breaks: BTreeSet<usize>,
+    mode: Mode,
+    lines: Vec<usize>, // each call's line, main's first
+    input: Box<dyn Iterator<Item = io::Result<String>>>,
+    out: Sink,
+    quit: bool,
+}
+
+impl Session {
+    fn stop(&mut self, span: Span, gnv: &Vnv, lvnv: &HashMap<String, i32>) -> io::Result<()> {
+        let (file, line) = origin(&self.text, &self.map, span);
+        let calls = gnv.calls.borrow();
+        self.lines.resize(calls.len(), line);
+        self.lines[calls.len() - 1] = line;
+
+        let stop = match self.mode {
+            Mode::Step => true,
+            Mode::Next(depth) => calls.len() <= depth,
+            Mode::Continue => false,
+        };
+        if !stop && !self.breaks.contains(&line) {
+            return Ok(());
+        }
+        let src = self.text[span.offset(&self.text)..].lines().next();
+        writeln!(
+            self.out.0.borrow_mut(),
+            "{file}:{line} in {}: {}",
+            calls[calls.len() - 1],
+            src.unwrap_or_default().trim()
+        )?;
+
+        loop {
+            write!(self.out.0.borrow_mut(), "(picoc) ")?;
+            self.out.0.borrow_mut().flush()?;
+            let Some(cmd) = self.input.next().transpose()? else {
+                // out of commands, the program runs out
+                self.breaks.clear();
+                self.mode = Mode::Continue;
+                return Ok(());
+            };
+
+            let mut out = self.out.0.borrow_mut();
+            match cmd.split_whitespace().collect::<Vec<_>>().as_slice() {
+                [] => (),
+                ["b" | "break", n] | ["d" | "delete", n] => match n.parse::<usize>() {
+                    Ok(n) if cmd.starts_with('d') => match self.breaks.remove(&n) {
+                        true => writeln!(out, "deleted the breakpoint at line {n}")?,
+                        false => writeln!(out, "picoc-error: no breakpoint at line {n}")?,
+                    },
+                    Ok(n) if self.stmts.contains(&n) => {
+                        self.breaks.insert(n);
+                        writeln!(out, "breakpoint at line {n}")?
+                    }
+                    Ok(n) => writeln!(out, "picoc-error: no stmt on line {n}")?,
+                    Err(_) => writeln!(out, "picoc-error: expected a line, got {n}")?,
+                },
+                ["s" | "step"] => {
+                    self.mode = Mode::Step;
+                    return Ok(());
+                }
+                ["n" | "next"] => {
+                    self.mode = Mode::Next(calls.len());
+                    return Ok(());
+                }
+                ["f" | "finish"] => {
+                    self.mode = Mode::Next(calls.len() - 1);
+                    return Ok(());
+                }
+                ["c" | "continue"] => {
+                    self.mode = Mode::Continue;
+                    return Ok(());
+                }
+                ["l" | "locals"] => {
+                    for (name, addr) in locals(gnv, lvnv) {
+                        writeln!(out, "{name} = {}", evaluator::show(gnv, addr)?)?
+                    }
+                }
+                ["p" | "print", var] => match lookup(lvnv, var) {
+                    Some(addr) => writeln!(out, "{var} = {}", evaluator::show(gnv, addr)?)?,
+                    None => writeln!(out, "picoc-error: no variable {var} here")?,
+                },
+                ["bt" | "backtrace"] => {
+                    for (i, (f, line)) in calls.iter().zip(&self.lines).rev().enumerate() {
+                        writeln!(out, "#{i} {f} at line {line}")?
+                    }
+                }
+                ["q" | "quit"] => {
+                    self.quit = true;
+                    return Err(io::Error::other("quit"));
+                }
+                _ => writeln!(out, "picoc-error: unknown command: {}", cmd.trim())?,
+            }
+        }
+    }
+}
+
+// the file and line of the original source a span starts on
+fn origin(text: &str, map: &SourceMap, span: Span) -> (String, usize) {
+    let line = source::pos(text, span.offset(text), 1).line;
+    let (file, line) = map.origin(line);
+    (file.to_owned(), line)
+}
+
+fn stmt_lines(prg: &SPrg, text: &str, map: &SourceMap) -> BTreeSet<usize> {
+    fn walk(stmts: &[Spanned<SStmt>], text: &str, map: &SourceMap, lines: &mut BTreeSet<usize>) {
+        for s in stmts {
+            lines.insert(origin(text, map, s.span).1);
+            match &s.node {
+                SStmt::IfEls { then, els, .. } => {
+                    walk(std::slice::from_ref(then), text, map, lines);
+                    if let Some(els) = els {
+                        walk(std::slice::from_ref(els), text, map, lines);
+                    }
+                }
+                SStmt::Switch { arms, .. } => {
+                    arms.iter().for_each(|a| walk(&a.body, text, map, lines))
+                }
+                SStmt::While { body, .. }
+                | SStmt::DoWhile { body, .. }
+                | SStmt::For { body, .. }
+                | SStmt::Block(body) => walk(body, text, map, lines),
+                _ => (),
+            }
+        }
+    }
+
+    let mut lines = BTreeSet::new();
+    for def in prg {
+        if let SDef::FuncDef(fd) = &def.node {
+            walk(&fd.body, text, map, &mut lines);
+        }
+    }
+    lines
+}
+
+fn locals(gnv: &Vnv, lvnv: &HashMap<String, i32>) -> Vec<(String, i32)> {
+    vars(lvnv, |name| !gnv.vnv.contains_key(name))
+}
+
+// the variables in scope that keep says to, by their names in the source.
+// locals are renamed apart, x to x.1 and so on, and the one declared last
+// is innermost
+fn vars(lvnv: &HashMap<String, i32>, keep: impl Fn(&str) -> bool) -> Vec<(String, i32)> {
+    let mut names = lvnv
+        .keys()
+        .filter(|name| keep(name) && name.as_str() != evaluator::RET)
+        .map(|name| name.split('.').next().unwrap_or(name).to_owned())
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .filter_map(|name| lookup(lvnv, &name).map(|addr| (name, addr)))
+        .collect()
+}
+
+fn lookup(lvnv: &HashMap<String, i32>, var: &str) -> Option<i32> {
+    lvnv.iter()
+        .filter_map(|(name, addr)| match name.split_once('.') {
+            None if name == var => Some((0, *addr)),
+            Some((base, n)) if base == var => n.parse::<usize>().ok().map(|n| (n, *addr)),
+            _ => None,
+        })
+        .max()
+        .map(|(_, addr)| addr)
+}
+
+#[cfg(test)]
+mod test_debugger {
+    use crate::{evaluator, lexer, parser_ast, source::SourceMap, Budget, IntModel, Sink};
+    use std::cell::RefCell;
+    use std::fs;
+    use std::io::Cursor;
+    use std::rc::Rc;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/debugger";
+
+    fn session(fixture: &str, cmds: &'static str) -> (Option<i32>, String) {
+        let src = fs::read_to_string(format!("{TEST_DIR}/{fixture}")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let out = Rc::new(RefCell::new(vec![]));
+        let map = SourceMap::new(fixture);
+        let exit = super::debug(
+            &tree,
+            &src,
+            &map,
+            Cursor::new(cmds),
+            Sink(out.clone()),
+            IntModel::default(),
+        )
+        .unwrap();
+        let out = String::from_utf8_lossy(&out.borrow()).replace("(picoc) ", "(picoc)\n");
+        (exit, out)
+    }
+
+    #[test]
+    fn breakpoints() {
+        let cmds = "b 4\nc\nbt\nlocals\np total\nfinish\nlocals\nd 4\nb 14\nc\nn\np half\n";
+        let (exit, out) = session("sum.c", cmds);
+        assert_eq!(exit, Some(6));
+        insta::assert_snapshot!(out, @r###"
+        sum.c:9 in main: int a[3];
+        (picoc)
+        breakpoint at line 4
+        (picoc)
+        sum.c:4 in add: total = total + x;
+        (picoc)
+        #0 add at line 4
+        #1 main at line 12
+        (picoc)
+        x = 1
+        (picoc)
+        total = 0
+        (picoc)
+        sum.c:11 in main: a[i] = i + 1;
+        (picoc)
+        a = {1, 0, 0}
+        i = 1
+        (picoc)
+        deleted the breakpoint at line 4
+        (picoc)
+        breakpoint at line 14
+        (picoc)
+        sum.c:14 in main: float half = 0.5f;
+        (picoc)
+        sum.c:15 in main: return total;
+        (picoc)
+        half = 0.5
+        (picoc)
+        exited with 6
+        "###);
+    }
+
+    #[test]
+    fn trace() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/sum.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let out = Rc::new(RefCell::new(vec![]));
+        let hook = super::tracer(&src, &SourceMap::new("sum.c"), Sink(out.clone()));
+        let exit = evaluator::eval_prg_hooked(
+            &tree,
+            IntModel::default(),
+            Sink::default(),
+            Budget::default(),
+            hook,
+        );
+        assert_eq!(exit.unwrap(), 6);
+        let out = String::from_utf8_lossy(&out.borrow()).into_owned();
+        insta::assert_snapshot!(out, @r###"
+        sum.c:9: int a[3]; => a = {0, 0, 0}
+        sum.c:11: a[i] = i + 1; => a = {1, 0, 0}
+        sum.c:4: total = total + x; => total = 1
+        sum.c:5: return total; => return 1
+        sum.c:12: add(a[i]); => total = 1
+        sum.c:11: a[i] = i + 1; => a = {1, 2, 0}
+        sum.c:4: total = total + x; => total = 3
+        sum.c:5: return total; => return 3
+        sum.c:12: add(a[i]); => total = 3
+        sum.c:11: a[i] = i + 1; => a = {1, 2, 3}
+        sum.c:4: total = total + x; => total = 6
+        sum.c:5: return total; => return 6
+        sum.c:12: add(a[i]); => total = 6
+        sum.c:14: float half = 0.5f; => half = 0.5
+        sum.c:15: return total; => return 6
+        "###);
+    }
+
+    #[test]
+    fn steps() {
+        let cmds = "b 2\nb 99\nwhere\ns\ns\ns\np a\nq\n";
+        let (exit, out) = session("sum.c", cmds);
+        assert_eq!(exit, None);
+        insta::assert_snapshot!(out, @r###"
+        sum.c:9 in main: int a[3];
+        (picoc)
+        picoc-error: no stmt on line 2
+        (picoc)
+        picoc-error: no stmt on line 99
+        (picoc)
+        picoc-error: unknown command: where
+        (picoc)
+        sum.c:10 in main: for (int i = 0; i < 3; i++) {
+        (picoc)
+        sum.c:11 in main: a[i] = i + 1;
+        (picoc)
+        sum.c:12 in main: add(a[i]);
+        (picoc)
+        a = {1, 0, 0}
+        (picoc)
+        "###);
+    }
+}
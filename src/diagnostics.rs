@@ -0,0 +1,383 @@
+use crate::lexer::{LexError, LexErrorKind};
+use crate::preprocessor::PreprocessError;
+use crate::source::{self, SourceMap, Span};
+use std::fmt;
+use std::io;
+
+// what's reported to whoever ran picoc089: a severity, a code to look the
+// problem up by, the message, and where in the source it is if that's
+// known. the passes keep returning io errors, located by source::locate,
+// and a diagnostic is made of one where it's reported. codes go by the
+// phase that found the problem, a hundred to each:
+//   E01xx lexer, E02xx parser, E03xx checker, E04xx evaluator,
+//   E05xx backend and toolchain, E06xx preprocessor, E07xx linker
+// an xx of 00 is a problem of the phase that has no code of its own yet.
+// warnings are W03xx, as the checker gives them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Note,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Lex,
+    Parse,
+    Check,
+    Run,
+    Compile,
+    Preprocess,
+    Link,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub msg: String,
+    pub span: Option<Span>,
+}
+
+// the warnings the checker can give, each with the name its -W flag takes.
+// implicit-int and return-type are on unless they're turned off, the rest
+// are turned on by -Wall or their own flag
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Warning {
+    UnusedVariable,
+    UnreachableCode,
+    ImplicitInt,
+    ReturnType,
+    MaybeUninitialized,
+}
+
+#[rustfmt::skip]
+const WARNINGS: [(Warning, &str, &str, bool); 5] = [
+    (Warning::UnusedVariable, "W0301", "unused-variable", false),
+    (Warning::UnreachableCode, "W0302", "unreachable-code", false),
+    (Warning::ImplicitInt, "W0303", "implicit-int", true),
+    (Warning::ReturnType, "W0304", "return-type", true),
+    (Warning::MaybeUninitialized, "W0305", "maybe-uninitialized", false),
+];
+
+impl Warning {
+    pub fn code(&self) -> &'static str {
+        WARNINGS
+            .iter()
+            .find(|w| w.0 == *self)
+            .map_or("W0300", |w| w.1)
+    }
+
+    pub fn name(&self) -> &'static str {
+        WARNINGS.iter().find(|w| w.0 == *self).map_or("", |w| w.2)
+    }
+}
+
+// which warnings are given, and whether they're errors
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warnings {
+    pub enabled: Vec<Warning>,
+    pub error: bool,
+}
+
+impl Default for Warnings {
+    fn default() -> Self {
+        let enabled = WARNINGS.iter().filter(|w| w.3).map(|w| w.0).collect();
+        Warnings {
+            enabled,
+            error: false,
+        }
+    }
+}
+
+impl Warnings {
+    // a -W flag without its -W: all, error, a warning's name, or no- one
+    pub fn set(&mut self, flag: &str) -> Result<(), String> {
+        let (on, name) = match flag.strip_prefix("no-") {
+            Some(name) => (false, name),
+            None => (true, flag),
+        };
+        let named = |name| WARNINGS.iter().find(|w| w.2 == name).map(|w| w.0);
+        match (on, name) {
+            (_, "all") => self.enabled = WARNINGS.iter().filter(|_| on).map(|w| w.0).collect(),
+            (_, "error") => self.error = on,
+            (_, name) => {
+                let w = named(name).ok_or(format!("unknown warning: -W{flag}"))?;
+                self.enabled.retain(|e| *e != w);
+                if on {
+                    self.enabled.push(w);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn enabled(&self, w: Warning) -> bool {
+        self.enabled.contains(&w)
+    }
+
+    // a warning at span, or an error under -Werror
+    pub fn diagnostic(&self, w: Warning, msg: String, span: Span) -> Diagnostic {
+        let severity = match self.error {
+            true => Severity::Error,
+            false => Severity::Warning,
+        };
+        let msg = format!("{msg} [-W{}]", w.name());
+        Diagnostic::new(severity, w.code(), msg, Some(span))
+    }
+}
+
+// a phase's codes, by what their messages contain. more specific ones come
+// first, since the first match wins
+#[rustfmt::skip]
+const CODES: [(Phase, &str, &str); 74] = [
+    (Phase::Parse, "E0201", "expected: "),
+    (Phase::Parse, "E0202", "unexpected end of input"),
+    (Phase::Parse, "E0203", "token not recognizable"),
+    (Phase::Parse, "E0204", "unknown type name"),
+    (Phase::Parse, "E0205", "invalid type specifiers"),
+    (Phase::Parse, "E0206", "lvalue required as"),
+    (Phase::Parse, "E0207", "case label needs a constant"),
+    (Phase::Parse, "E0208", "unknown attribute"),
+    (Phase::Parse, "E0209", "requires a named argument before ..."),
+    (Phase::Parse, "E0210", "parameter name omitted"),
+    (Phase::Parse, "E0211", "has no stdarg.h"),
+    (Phase::Parse, "E0212", "expected a function body"),
+    (Phase::Parse, "E0213", "annotation"),
+    (Phase::Parse, "E0214", "nesting deeper than"),
+    (Phase::Parse, "E0215", "wrong kind of tag"),
+    (Phase::Check, "E0301", "use of undeclared variable"),
+    (Phase::Check, "E0302", "call to undeclared function"),
+    (Phase::Check, "E0303", "arguments but"),
+    (Phase::Check, "E0304", "assignment of read-only variable"),
+    (Phase::Check, "E0305", "is not constant"),
+    (Phase::Check, "E0306", "type error"),
+    (Phase::Check, "E0307", "redefinition of"),
+    (Phase::Check, "E0308", "conflicting types for"),
+    (Phase::Check, "E0309", "used but not defined"),
+    (Phase::Check, "E0310", "label"),
+    (Phase::Check, "E0311", "break statement not within"),
+    (Phase::Check, "E0312", "continue statement not within"),
+    (Phase::Check, "E0313", "duplicate case value"),
+    (Phase::Check, "E0314", "enumerator"),
+    (Phase::Check, "E0315", "should not return a value"),
+    (Phase::Check, "E0316", "should return a value"),
+    (Phase::Check, "E0317", "sizeof to void"),
+    (Phase::Check, "E0318", "undefined struct"),
+    (Phase::Check, "E0318", "undefined union"),
+    (Phase::Check, "E0319", "struct"),
+    (Phase::Check, "E0320", "main function not found"),
+    (Phase::Check, "E0321", "before its declaration"),
+    (Phase::Check, "E0322", "control reaches the end"),
+    (Phase::Check, "E0323", "wrong kind of tag"),
+    (Phase::Run, "E0401", "invalid memory access"),
+    (Phase::Run, "E0402", "write to read-only memory"),
+    (Phase::Run, "E0403", "undefined variable"),
+    (Phase::Run, "E0404", "undefined function"),
+    (Phase::Run, "E0405", "undefined member"),
+    (Phase::Run, "E0406", "lvalue required"),
+    (Phase::Run, "E0407", "printf"),
+    (Phase::Run, "E0408", "no return stmt"),
+    (Phase::Run, "E0409", "jump outside a loop"),
+    (Phase::Run, "E0410", "goto"),
+    (Phase::Run, "E0411", "time limit exceeded"),
+    (Phase::Run, "E0412", "main function not found"),
+    (Phase::Run, "E0413", "null pointer dereference"),
+    (Phase::Run, "E0414", "index out of bounds"),
+    (Phase::Run, "E0415", "negative array size"),
+    (Phase::Run, "E0416", "out of heap memory"),
+    (Phase::Run, "E0417", "annotation failed"),
+    (Phase::Run, "E0418", "assertion failed"),
+    (Phase::Run, "E0419", "resource limit exceeded"),
+    (Phase::Run, "E0420", "uninitialized variable"),
+    (Phase::Run, "E0421", "signed integer overflow"),
+    (Phase::Run, "E0422", "division by zero"),
+    (Phase::Run, "E0423", "division overflow"),
+    (Phase::Compile, "E0501", "aren't compiled yet"),
+    (Phase::Compile, "E0502", "isn't implemented"),
+    (Phase::Compile, "E0503", "assembler"),
+    (Phase::Compile, "E0504", "undefined reference"),
+    (Phase::Compile, "E0505", "unable to run"),
+    (Phase::Preprocess, "E0601", "#error"),
+    (Phase::Preprocess, "E0602", "unknown directive"),
+    (Phase::Preprocess, "E0603", "#line"),
+    (Phase::Preprocess, "E0604", "not found"),
+    (Phase::Link, "E0701", "multiple definition"),
+    (Phase::Link, "E0702", "conflicting types"),
+    (Phase::Link, "E0703", "is static"),
+];
+
+fn code(phase: Phase, msg: &str) -> &'static str {
+    let found = CODES
+        .iter()
+        .find(|(p, _, m)| *p == phase && msg.contains(m))
+        .map(|(_, code, _)| *code);
+    found.unwrap_or(match phase {
+        Phase::Lex => "E0100",
+        Phase::Parse => "E0200",
+        Phase::Check => "E0300",
+        Phase::Run => "E0400",
+        Phase::Compile => "E0500",
+        Phase::Preprocess => "E0600",
+        Phase::Link => "E0700",
+    })
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, code: &'static str, msg: String, span: Option<Span>) -> Self {
+        Diagnostic {
+            severity,
+            code,
+            msg,
+            span,
+        }
+    }
+
+    // an error a phase returned, at its span if it's located
+    pub fn error(phase: Phase, e: &io::Error) -> Self {
+        let pp = e
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<PreprocessError>());
+        let msg = e.to_string();
+        let code = code(phase, pp.map_or(&msg, |e| &e.msg));
+        let span = source::located(e).map(|l| l.span);
+        Diagnostic::new(Severity::Error, code, msg, span)
+    }
+
+    pub fn lex(e: &LexError) -> Self {
+        let code = match e.kind {
+            LexErrorKind::Unexpected(_) => "E0101",
+            LexErrorKind::NonAscii(_) => "E0102",
+            LexErrorKind::UnterminatedComment => "E0103",
+            LexErrorKind::UnterminatedStr => "E0104",
+            LexErrorKind::MalformedChar => "E0105",
+            LexErrorKind::UnknownEscape(_) => "E0106",
+            LexErrorKind::NoExponentDigits(_) => "E0107",
+            LexErrorKind::MalformedInt(_) => "E0108",
+            LexErrorKind::IntTooLarge(_) => "E0109",
+        };
+        // a non-ascii character is underlined whole
+        let len = match e.kind {
+            LexErrorKind::NonAscii(c) => c.len_utf8(),
+            _ => 1,
+        };
+        let span = Span::new(e.rest, len);
+        Diagnostic::new(Severity::Error, code, e.kind.to_string(), Some(span))
+    }
+
+    // file:line:col of the original source, then the preprocessed line with
+    // the span underlined up to the line's end. one without a span is just
+    // at file
+    pub fn render(&self, file: &str, text: &str, map: &SourceMap, tab_width: usize) -> String {
+        let head = format!("picoc-{}[{}]", self.severity, self.code);
+        let Some(span) = self.span else {
+            return match file {
+                "" => format!("{head}: {}", self.msg),
+                file => format!("{head}: {file}: {}", self.msg),
+            };
+        };
+
+        let offset = span.offset(text);
+        let caret = map.caret(text, offset, tab_width);
+        let (at, snippet) = caret.split_once('\n').unwrap_or((&caret, ""));
+        let start = source::pos(text, offset, tab_width);
+        // a span ending inside a character runs to the end of it
+        let mut end = (offset + span.len).min(text.len());
+        while !text.is_char_boundary(end) {
+            end += 1;
+        }
+        let end = source::pos(text, end, tab_width);
+        let (line, under) = snippet.split_once('\n').unwrap_or((snippet, "^"));
+        let width = match end.line == start.line {
+            true => end.col.saturating_sub(start.col),
+            false => (line.chars().count() + 1).saturating_sub(start.col),
+        };
+        let under = format!(
+            "{}{}",
+            under.trim_end_matches('^'),
+            "^".repeat(width.max(1))
+        );
+
+        format!("{head}: {at}: {}\n{line}\n{under}", self.msg)
+    }
+}
+
+// without the source: the severity, code and message
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "picoc-{}[{}]: {}", self.severity, self.code, self.msg)
+    }
+}
+
+#[cfg(test)]
+mod test_diagnostics {
+    use super::{Diagnostic, Phase};
+    use crate::{lexer, parser_ast, preprocessor, typecheck};
+    use std::fs;
+    use std::path::Path;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/diagnostics";
+
+    #[test]
+    fn render() {
+        let path = format!("{TEST_DIR}/errors.c");
+        let src = fs::read_to_string(&path).expect("file dne");
+        let (text, map) =
+            preprocessor::preprocess(&src, Path::new(&path), &Default::default()).unwrap();
+
+        let (prg, errs) = parser_ast::parse(&lexer::lex(&text).unwrap());
+        let mut diags = errs
+            .into_iter()
+            .map(|e| Diagnostic::error(Phase::Parse, &e.into()))
+            .collect::<Vec<_>>();
+        let e = typecheck::check(&prg).unwrap_err();
+        diags.push(Diagnostic::error(Phase::Check, &e));
+        let e = lexer::lex("int x = 1 @ 2;").unwrap_err();
+        diags.push(Diagnostic::lex(&e[0]));
+
+        let rendered = diags
+            .iter()
+            .take(2)
+            .map(|d| d.render("errors.c", &text, &map, 4))
+            .chain([diags[2].to_string()])
+            .collect::<Vec<_>>()
+            .join("\n");
+        insta::assert_snapshot!(rendered, @r###"
+        picoc-error[E0201]: tests/fixtures/snap/diagnostics/errors.c:3:5: expected: PuncSemiColon got: KeywordRet
+            return x;
+            ^^^^^^
+        picoc-error[E0301]: tests/fixtures/snap/diagnostics/errors.c:7:12: use of undeclared variable count in main
+            return count + 1;
+                   ^^^^^
+        picoc-error[E0101]: unexpected token: '@'
+        "###);
+    }
+
+    #[test]
+    fn codes() {
+        let codes = [
+            (Phase::Parse, "expected: PuncSemiColon got: KeywordInt"),
+            (Phase::Check, "label out used but not defined"),
+            (Phase::Check, "label out redefined"),
+            (Phase::Run, "invalid memory access"),
+            (Phase::Link, "multiple definition of add: in a.c and b.c"),
+            (Phase::Compile, "something new"),
+        ]
+        .map(|(phase, msg)| super::code(phase, msg));
+        assert_eq!(
+            codes,
+            ["E0201", "E0309", "E0310", "E0401", "E0701", "E0500"]
+        );
+    }
+}
@@ -0,0 +1,629 @@
+use crate::{
+    allocator, asm::AsmProgram, counters, evaluator, ir, json, linker, optimizer, pic, runtime,
+    symmap, translator, typecheck, Budget, IntModel, Label, OptLevel, SPrg, Sink, TQuad,
+};
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// evaluation strategies behind one interface, so the cli and tests can
+// swap them without knowing how a program is run.
+//   - interpretc0  walks the source tree with the evaluator
+//   - vm           compiles the tree to bytecode for the evaluator's stack
+//                  machine, for interpretc0 --vm
+//   - runc89       compiles, then assembles and runs under an emulator
+// todo: a jit engine
+pub trait ExecutionEngine {
+    fn run(&self, prg: &SPrg, stdin: &str, limits: &Limits) -> Result<RunOutput, io::Error>;
+
+    // several units linked into one program. only an engine that compiles
+    // can link
+    fn run_units(
+        &self,
+        units: &[linker::Unit],
+        stdin: &str,
+        limits: &Limits,
+    ) -> Result<RunOutput, io::Error> {
+        match units {
+            [unit] => self.run(&unit.prg, stdin, limits),
+            _ => Err(io::Error::other("this engine runs one file")),
+        }
+    }
+}
+
+// a compiled program only has a timeout. the interpreters also count
+// their steps and the heap alloc hands out
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Limits {
+    pub timeout: Option<Duration>,
+    pub max_steps: Option<u64>,
+    pub max_heap: Option<usize>, // in bytes
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunOutput {
+    pub exit: i32, // main's return value
+    pub stdout: String,
+    pub stderr: String,
+}
+
+pub fn engine(
+    strat: &str,
+    opt: OptLevel,
+    model: IntModel,
+    count: bool,
+) -> Option<Box<dyn ExecutionEngine>> {
+    match strat {
+        "interpretc0" => Some(Box::new(TreeWalker { model })),
+        "vm" => Some(Box::new(Vm { model })),
+        "runc89" => Some(Box::new(CompileAndRun::new(opt, model, count))),
+        _ => None,
+    }
+}
+
+pub struct TreeWalker {
+    pub model: IntModel,
+}
+
+impl ExecutionEngine for TreeWalker {
+    fn run(&self, prg: &SPrg, _stdin: &str, limits: &Limits) -> Result<RunOutput, io::Error> {
+        interpret(prg, &self.model, evaluator::eval_prg_to, limits)
+    }
+}
+
+pub struct Vm {
+    pub model: IntModel,
+}
+
+impl ExecutionEngine for Vm {
+    fn run(&self, prg: &SPrg, _stdin: &str, limits: &Limits) -> Result<RunOutput, io::Error> {
+        interpret(prg, &self.model, evaluator::eval_prg_vm_to, limits)
+    }
+}
+
+type Eval = fn(&SPrg, IntModel, Sink, Budget) -> Result<i32, io::Error>;
+
+fn interpret(
+    prg: &SPrg,
+    model: &IntModel,
+    eval: Eval,
+    limits: &Limits,
+) -> Result<RunOutput, io::Error> {
+    let budget = Budget {
+        steps: limits.max_steps,
+        heap: limits.max_heap,
+        ..Budget::default()
+    };
+    let (exit, stdout) = match limits.timeout {
+        None => walk(prg, model.clone(), eval, budget)?,
+        Some(timeout) => {
+            // a runaway evaluation is abandoned, not killed
+            let (tx, rx) = mpsc::channel();
+            let (prg, model) = (prg.clone(), model.clone());
+            thread::Builder::new()
+                .stack_size(evaluator::STACK)
+                .spawn(move || tx.send(walk(&prg, model, eval, budget)))?;
+            rx.recv_timeout(timeout)
+                .map_err(|_| io::Error::other("time limit exceeded"))??
+        }
+    };
+
+    Ok(RunOutput {
+        exit,
+        stdout,
+        stderr: String::new(),
+    })
+}
+
+// the exit code and what printf wrote, as a compiled program's would be
+fn walk(
+    prg: &SPrg,
+    model: IntModel,
+    eval: Eval,
+    budget: Budget,
+) -> Result<(i32, String), io::Error> {
+    let out = Rc::new(RefCell::new(vec![]));
+    let exit = eval(prg, model, Sink(out.clone()), budget)?;
+    let stdout = String::from_utf8_lossy(&out.borrow()).into_owned();
+    Ok((exit, stdout))
+}
+
+// the toolchain is picked with PICOC_CC and PICOC_EMU, since the
+// runtime needs a hosted libc. binaries are cached by a hash of the typed
+// program and the options it's compiled with, so an engine that's kept
+// around (runc89 --watch) only reruns unchanged programs. the hash leaves
+// out where the program was parsed from, which what's compiled doesn't
+// depend on, so an edit to comments or spacing is unchanged too. each
+// engine builds in a directory of its own, which goes when it does.
+// a changed program only has its changed functions allocated again: each
+// function's assembly is cached by a hash of its optimized quads, with its
+// temps and labels numbered from its own start, and its labels are renamed
+// after it when it's assembled, so they don't collide with another's
+// todo: an internal linker for a freestanding target. it needs an encoder
+//       first, since only assembly text is emitted, and a runtime that makes
+//       syscalls instead of calling write, sigaction and _exit
+pub struct CompileAndRun {
+    pub opt: OptLevel,
+    pub model: IntModel,
+    pub count: bool, // counter totals go to stderr
+    cache: RefCell<HashMap<u64, PathBuf>>,
+    funcs: RefCell<HashMap<u64, AsmProgram>>, // by a hash of the function's quads
+    dir: PathBuf,
+}
+
+// engines made so far, for their directories' names
+static ENGINES: AtomicUsize = AtomicUsize::new(0);
+
+impl CompileAndRun {
+    pub fn new(opt: OptLevel, model: IntModel, count: bool) -> Self {
+        let n = ENGINES.fetch_add(1, Ordering::Relaxed);
+        CompileAndRun {
+            opt,
+            model,
+            count,
+            cache: RefCell::default(),
+            funcs: RefCell::default(),
+            dir: env::temp_dir().join(format!("picoc-{}-{n}", std::process::id())),
+        }
+    }
+
+    fn key(&self, prg: &SPrg) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (
+            json::to_string(prg).ok(),
+            &self.opt,
+            &self.model,
+            self.count,
+        )
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn build(&self, prg: &SPrg, key: u64) -> Result<PathBuf, io::Error> {
+        let assembly = self.compile(prg);
+        self.build_units(&[assembly], key)
+    }
+
+    // compile's assembly, with each function's taken from funcs when its
+    // quads are ones allocated before
+    fn compile(&self, prg: &SPrg) -> Vec<String> {
+        let model = &self.model;
+        let trgt_tree = translator::translate_for(prg, model);
+        let cfg = ir::lower(&trgt_tree, &HashSet::new(), model);
+        let levels = optimizer::levels(prg);
+        let abs_as = optimizer::optimize(cfg, &self.opt, &levels, model).quads();
+        let strs = runtime::strs(&abs_as);
+
+        let mut insns = allocator::header();
+        for segment in ir::segments(&abs_as) {
+            let TQuad::Label(Label::UserLabel(f)) = &segment[0] else {
+                let statics = allocator::allocate_run(segment, &self.opt, &strs, model);
+                insns.extend(statics.insns);
+                continue;
+            };
+            let level = levels.get(f).unwrap_or(&self.opt);
+            let quads = ir::local(segment);
+            let mut hasher = DefaultHasher::new();
+            (json::to_string(&quads).ok(), level, model).hash(&mut hasher);
+            // a literal is named by its place in the program's
+            for quad in &quads {
+                if let TQuad::Str(_, s) = quad {
+                    strs.iter().position(|t| t == s).hash(&mut hasher);
+                }
+            }
+            let key = hasher.finish();
+
+            let cached = self.funcs.borrow().get(&key).cloned();
+            let func = cached.unwrap_or_else(|| {
+                let func = allocator::allocate_run(&quads, level, &strs, model);
+                self.funcs.borrow_mut().insert(key, func.clone());
+                func
+            });
+            insns.extend(func.relabel(f).insns);
+        }
+
+        let assembly = AsmProgram { insns }.render();
+        let assembly = match self.count {
+            true => counters::instrument(&assembly, model.word()),
+            false => assembly,
+        };
+        [assembly, runtime::runtime(&abs_as, model)].concat()
+    }
+
+    // each unit's assembly, linked
+    fn build_units(&self, assembly: &[Vec<String>], key: u64) -> Result<PathBuf, io::Error> {
+        fs::create_dir_all(&self.dir)?;
+        let bin = self.dir.join(format!("prg-{key:016x}"));
+        let srcs = (0..assembly.len())
+            .map(|i| self.dir.join(format!("prg-{key:016x}-{i}.s")))
+            .collect::<Vec<_>>();
+        for (src, assembly) in srcs.iter().zip(assembly) {
+            fs::write(src, assembly.join("\n"))?;
+        }
+
+        let linked = linker::link(&srcs, &bin, self.model.word());
+        for src in &srcs {
+            fs::remove_file(src)?;
+        }
+        linked.map(|_| bin)
+    }
+
+    fn cached(
+        &self,
+        key: u64,
+        build: impl FnOnce() -> Result<PathBuf, io::Error>,
+    ) -> Result<PathBuf, io::Error> {
+        let cached = self.cache.borrow().get(&key).cloned();
+        match cached {
+            Some(bin) => Ok(bin),
+            None => {
+                let bin = build()?;
+                self.cache.borrow_mut().insert(key, bin.clone());
+                Ok(bin)
+            }
+        }
+    }
+
+    fn exec(&self, bin: &Path, stdin: &str, limits: &Limits) -> Result<RunOutput, io::Error> {
+        let emu = env::var("PICOC_EMU").unwrap_or(format!("qemu-riscv{}", self.model.word() * 8));
+        let mut child = Command::new(&emu)
+            .arg(bin)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut input) = child.stdin.take() {
+            input.write_all(stdin.as_bytes())?;
+        }
+
+        let start = Instant::now();
+        while child.try_wait()?.is_none() {
+            if limits.timeout.is_some_and(|t| start.elapsed() > t) {
+                child.kill()?;
+                return Err(io::Error::other("time limit exceeded"));
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        let output = child.wait_with_output()?;
+
+        Ok(RunOutput {
+            exit: output.status.code().unwrap_or(-1),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
+
+impl Drop for CompileAndRun {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+impl ExecutionEngine for CompileAndRun {
+    fn run(&self, prg: &SPrg, stdin: &str, limits: &Limits) -> Result<RunOutput, io::Error> {
+        typecheck::check(prg)?;
+        unsupported(prg)?;
+        let key = self.key(prg);
+        let bin = self.cached(key, || self.build(prg, key))?;
+        self.exec(&bin, stdin, limits)
+    }
+
+    // counters are kept per unit, so they're left out
+    fn run_units(
+        &self,
+        units: &[linker::Unit],
+        stdin: &str,
+        limits: &Limits,
+    ) -> Result<RunOutput, io::Error> {
+        if let [unit] = units {
+            return self.run(&unit.prg, stdin, limits);
+        }
+        for unit in units {
+            typecheck::check_unit(&unit.prg)?;
+            unsupported(&unit.prg)?;
+        }
+        linker::check(units)?;
+
+        let mut hasher = DefaultHasher::new();
+        for unit in units {
+            json::to_string(&unit.prg).ok().hash(&mut hasher);
+        }
+        (&self.opt, &self.model).hash(&mut hasher);
+        let key = hasher.finish();
+        let bin = self.cached(key, || {
+            let assembly = linker::compile(units, self.opt.clone(), &self.model, false);
+            self.build_units(&assembly, key)
+        })?;
+        self.exec(&bin, stdin, limits)
+    }
+}
+
+// where one engine's run of a program parts from another's. a compiled
+// program's exit status is a byte, and it faults by reporting to stderr and
+// exiting 1, so only main's low byte is compared, and faults agree whatever
+// they say. what's expected is the reference's
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence {
+    pub what: &'static str, // "exit status", "stdout" or "fault"
+    pub expected: String,
+    pub found: String,
+}
+
+// prg run by both, the reference first. the reference's errors are the
+// program's faults, but the subject's are its own, as a missing toolchain
+// is, so they're returned
+pub fn difftest(
+    prg: &SPrg,
+    stdin: &str,
+    limits: &Limits,
+    reference: &dyn ExecutionEngine,
+    subject: &dyn ExecutionEngine,
+) -> Result<Option<Divergence>, io::Error> {
+    let expected = reference.run(prg, stdin, limits);
+    let found = subject.run(prg, stdin, limits)?;
+    let diverged = |what, expected: String, found: String| {
+        Some(Divergence {
+            what,
+            expected,
+            found,
+        })
+    };
+
+    let faulted = !found.stderr.is_empty();
+    Ok(match expected {
+        Err(_) if faulted => None,
+        Err(e) => diverged("fault", e.to_string(), format!("exit {}", found.exit)),
+        Ok(out) if faulted => diverged(
+            "fault",
+            format!("exit {}", out.exit),
+            found.stderr.trim().to_owned(),
+        ),
+        Ok(out) if out.exit as u8 != found.exit as u8 => diverged(
+            "exit status",
+            (out.exit as u8).to_string(),
+            (found.exit as u8).to_string(),
+        ),
+        Ok(out) if out.stdout != found.stdout => diverged("stdout", out.stdout, found.stdout),
+        Ok(_) => None,
+    })
+}
+
+// what the backend can't compile yet. compile panics on any of it, so the
+// callers check first
+pub fn unsupported(prg: &SPrg) -> Result<(), io::Error> {
+    translator::unsupported(prg)
+}
+
+// the compilec89 pipeline: assembly linked with the runtime, and its symbol map
+pub fn compile(
+    prg: &SPrg,
+    opt: OptLevel,
+    model: &IntModel,
+    src: &str,
+    count: bool,
+    pic: bool,
+) -> (Vec<String>, Vec<symmap::Symbol>) {
+    let trgt_tree = translator::translate_for(prg, model);
+    let cfg = ir::lower(&trgt_tree, &HashSet::new(), model);
+    let levels = optimizer::levels(prg);
+    let abs_as = optimizer::optimize(cfg, &opt, &levels, model).quads();
+    let assembly = allocator::allocate_levels(&abs_as, opt, &levels, model).render();
+    let assembly = match count {
+        true => counters::instrument(&assembly, model.word()),
+        false => assembly,
+    };
+    let assembly = [assembly, runtime::runtime(&abs_as, model)].concat();
+    let assembly = match pic {
+        true => pic::relocate(&assembly),
+        false => assembly,
+    };
+    let map = symmap::symbol_map(&assembly, src);
+
+    (assembly, map)
+}
+
+#[cfg(test)]
+mod test_engines {
+    use crate::{lexer, parser_ast, typer, IntModel, OptLevel};
+    use std::cell::Cell;
+    use std::fs;
+    use std::path::PathBuf;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/bindings";
+
+    #[test]
+    fn treewalk() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/composition.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let engine =
+            super::engine("interpretc0", OptLevel::O0, IntModel::default(), false).unwrap();
+        let output = engine
+            .run(&src_tree, "", &super::Limits::default())
+            .unwrap();
+        insta::assert_debug_snapshot!(output, @r###"
+        RunOutput {
+            exit: 30,
+            stdout: "",
+            stderr: "",
+        }
+        "###);
+    }
+
+    #[test]
+    fn cache_key() {
+        let parse = |fixture: &str| {
+            let src = fs::read_to_string(format!("{TEST_DIR}/{fixture}")).expect("file dne");
+            let tokens = lexer::lex(&src).unwrap();
+            parser_ast::parse_prg(&tokens).unwrap()
+        };
+        let engine = |count| super::CompileAndRun::new(OptLevel::O0, IntModel::default(), count);
+
+        let key = engine(false).key(&parse("composition.c"));
+        assert_eq!(key, engine(false).key(&parse("composition.c")));
+        assert_ne!(key, engine(true).key(&parse("composition.c")));
+        assert_ne!(key, engine(false).key(&parse("ptr.c")));
+
+        // where the program was parsed from isn't compiled
+        let parse = |src: &str| parser_ast::parse_prg(&lexer::lex(src).unwrap()).unwrap();
+        let key = engine(false).key(&parse("int main() { return 1; }"));
+        let moved = parse("// one\nint main() {\n    return 1;\n}\n");
+        assert_eq!(key, engine(false).key(&moved));
+    }
+
+    #[test]
+    fn cache_hit() {
+        let engine = super::CompileAndRun::new(OptLevel::O0, IntModel::default(), false);
+        let builds = Cell::new(0);
+        let build = || {
+            builds.set(builds.get() + 1);
+            Ok(engine.dir.join("prg"))
+        };
+        let bin = engine.cached(1, build).unwrap();
+        assert_eq!(engine.cached(1, build).unwrap(), bin);
+        assert_ne!(engine.cached(2, build).unwrap(), PathBuf::new());
+        assert_eq!(builds.get(), 2);
+
+        // an engine's binaries are its own, and go when it does
+        let other = super::CompileAndRun::new(OptLevel::O0, IntModel::default(), false);
+        assert_ne!(engine.dir, other.dir);
+        fs::create_dir_all(&engine.dir).unwrap();
+        fs::create_dir_all(&other.dir).unwrap();
+        let dir = engine.dir.clone();
+        drop(engine);
+        assert!(!dir.exists());
+        assert!(other.dir.exists());
+    }
+
+    #[test]
+    fn cache_funcs() {
+        let parse = |src: &str| parser_ast::parse_prg(&lexer::lex(src).unwrap()).unwrap();
+        let engine = super::CompileAndRun::new(OptLevel::O2, IntModel::default(), false);
+        let f = "int f(int x) { while (x) { x = x - 1; } return x; }";
+        let before = engine.compile(&parse(&format!("{f} int main() {{ return f(1); }}")));
+        assert_eq!(engine.funcs.borrow().len(), 2);
+
+        // only main changed, and f's labels are its own whatever's before it
+        let g = "int g(int y) { while (y) { y = y - 2; } return y; }";
+        let after = engine.compile(&parse(&format!("{g} {f} int main() {{ return f(0); }}")));
+        assert_eq!(engine.funcs.borrow().len(), 4);
+        let labels = |assembly: &[String]| {
+            let mut labels = assembly
+                .iter()
+                .filter(|l| l.starts_with(".Lf.") && l.ends_with(':'))
+                .cloned()
+                .collect::<Vec<_>>();
+            labels.sort();
+            labels
+        };
+        assert!(!labels(&before).is_empty());
+        assert_eq!(labels(&before), labels(&after));
+        assert!(after.iter().any(|l| l.starts_with(".Lg.")));
+        assert!(!after.iter().any(|l| l.starts_with(".L0")));
+    }
+
+    // an engine that prints and exits as it's told, whatever the program
+    struct Canned(super::RunOutput);
+
+    impl super::ExecutionEngine for Canned {
+        fn run(
+            &self,
+            _: &crate::SPrg,
+            _: &str,
+            _: &super::Limits,
+        ) -> Result<super::RunOutput, std::io::Error> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn difftest() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/printf.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let [walker, vm] = ["interpretc0", "vm"]
+            .map(|strat| super::engine(strat, OptLevel::O0, IntModel::default(), false).unwrap());
+        let limits = super::Limits::default();
+        let diverged = super::difftest(&src_tree, "", &limits, &*walker, &*vm).unwrap();
+        assert_eq!(diverged, None);
+
+        let canned = |exit, stdout: &str, stderr: &str| {
+            Canned(super::RunOutput {
+                exit,
+                stdout: stdout.to_owned(),
+                stderr: stderr.to_owned(),
+            })
+        };
+        let subjects = [
+            canned(5 + 256, "42 x\ndone\n", ""), // a status is a byte
+            canned(6, "42 x\ndone\n", ""),
+            canned(5, "42 x\n", ""),
+            canned(1, "", "picoc-error: segmentation fault in main\n"),
+        ];
+        let diverged = subjects
+            .iter()
+            .map(|subject| super::difftest(&src_tree, "", &limits, &*walker, subject).unwrap())
+            .collect::<Vec<_>>();
+        insta::assert_debug_snapshot!(diverged, @r###"
+        [
+            None,
+            Some(
+                Divergence {
+                    what: "exit status",
+                    expected: "5",
+                    found: "6",
+                },
+            ),
+            Some(
+                Divergence {
+                    what: "stdout",
+                    expected: "42 x\ndone\n",
+                    found: "42 x\n",
+                },
+            ),
+            Some(
+                Divergence {
+                    what: "fault",
+                    expected: "exit 5",
+                    found: "picoc-error: segmentation fault in main",
+                },
+            ),
+        ]
+        "###);
+    }
+
+    #[test]
+    fn treewalk_printf() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/printf.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let engine =
+            super::engine("interpretc0", OptLevel::O0, IntModel::default(), false).unwrap();
+        let output = engine
+            .run(&src_tree, "", &super::Limits::default())
+            .unwrap();
+        insta::assert_debug_snapshot!(output, @r###"
+        RunOutput {
+            exit: 5,
+            stdout: "42 x\ndone\n",
+            stderr: "",
+        }
+        "###);
+    }
+}
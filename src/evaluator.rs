@@ -1,165 +1,2592 @@
-use crate::{BinOp, Def, Expr, LambdaVal, Prg, Stmt, Vnv};
-use std::{collections::HashMap, io};
+use crate::{
+    bytecode, layout, source, translator, typecheck, typer, Budget, DataModel, Event, Hook,
+    IntModel, LambdaType, LambdaVal, SBinOp, SBitOp, SCase, SContract, SContractKind, SDef, SExpr,
+    SFuncDef, SIncDecOp, SLogOp, SPrg, SRelOp, SStmt, SUnaryOp, SVarDecl, SVarDef, Signal, Sink,
+    Spanned, Tnv, Type, Vnv, Width, RESULT,
+};
+use std::{cell::RefCell, collections::HashMap, io, iter};
 
-pub fn eval_prg(prg: Prg) -> Result<i32, io::Error> {
+pub fn eval_prg(prg: &SPrg, model: IntModel) -> Result<i32, io::Error> {
+    eval_prg_to(prg, model, Sink::default(), Budget::default())
+}
+
+// as eval_prg, with printf writing to out rather than stdout, and the
+// program cut off once it's used its budget
+pub fn eval_prg_to(
+    prg: &SPrg,
+    model: IntModel,
+    out: Sink,
+    budget: Budget,
+) -> Result<i32, io::Error> {
+    let nv = env(prg, model, out, budget)?;
+    eval_main(&nv)
+}
+
+// as eval_prg_to, calling hook before and after each stmt, as a debugger
+// or tracer does
+pub fn eval_prg_hooked(
+    prg: &SPrg,
+    model: IntModel,
+    out: Sink,
+    budget: Budget,
+    hook: Hook,
+) -> Result<i32, io::Error> {
+    let mut nv = env(prg, model, out, budget)?;
+    nv.hook = hook;
+    eval_main(&nv)
+}
+
+fn eval_main(nv: &Vnv) -> Result<i32, io::Error> {
+    // defining nv here so eval_fn can borrow both
+    let lvnv = nv.vnv.clone(); // clone it first, before giving &mut
+    let main = nv
+        .fnv
+        .get("main")
+        .ok_or(io::Error::other("main function not found"))?;
+    nv.calls.borrow_mut().push("main".to_owned());
+    eval_func(main, nv, lvnv)
+}
+
+// as eval_prg_to, compiling the program to bytecode and running that on a
+// stack machine rather than walking the tree
+pub fn eval_prg_vm_to(
+    prg: &SPrg,
+    model: IntModel,
+    out: Sink,
+    budget: Budget,
+) -> Result<i32, io::Error> {
+    let nv = env(prg, model, out, budget)?;
+    let prg = bytecode::compile(&nv)?;
+    run(&prg, &nv)
+}
+
+// cells are 32 bits, so longs are only as wide as ilp32 has them. an lp64
+// long would be sizeof 8 but wrap at 32 bits, so lp64 is left to compiled
+// code
+pub fn interpretable(model: &IntModel) -> Result<(), io::Error> {
+    match model.data_model {
+        DataModel::ILP32 => Ok(()),
+        DataModel::LP64 => Err(io::Error::other(
+            "lp64 is for compiled code, as the interpreter's longs are 32 bits",
+        )),
+    }
+}
+
+// the functions, and the statics allocated and initialized, before main
+pub fn env(prg: &SPrg, model: IntModel, out: Sink, budget: Budget) -> Result<Vnv, io::Error> {
+    interpretable(&model)?;
+    typecheck::resolve(prg)?; // before anything runs, even code that never would
+
+    // locals are renamed apart program wide, as for the backend, so each
+    // local static has a name of its own in the global env
+    let mut n = 0;
     let fnv = prg
         .iter()
-        .map(|defs| match defs {
-            Def::FuncDef(fd) => (
+        .filter_map(|defs| match &defs.node {
+            SDef::FuncDef(fd) => Some((
                 // funcdef simply creates the lambda
                 fd.alias.clone(),
                 LambdaVal {
-                    fp: fd.fp.clone(),
-                    body: fd.body.clone(),
+                    fp: fd.fps.clone(),
+                    ret: fd.typ.clone(),
+                    body: translator::rename_stmts(&fd.body, &HashMap::new(), false, &mut n),
+                    contracts: fd.contracts.clone(),
                 },
-            ),
-            _ => todo!(), // next: top-level vardefs
+            )),
+            SDef::FuncDecl(_)
+            | SDef::VarDef(_)
+            | SDef::VarDecl(_)
+            | SDef::StructDef(_)
+            | SDef::UnionDef(_)
+            | SDef::EnumDef(_) => None,
         })
         .collect::<HashMap<String, LambdaVal>>();
 
-    let vnv = HashMap::new(); // todo: parse global vardefs
-    let nv = Vnv { fnv, vnv };
+    let mut nv = Vnv {
+        fnv,
+        vnv: HashMap::new(),
+        mem: RefCell::new(vec![(0, Width::Word)]), // address 0 is never handed out, so null faults
+        rodata: RefCell::new(vec![]),
+        strs: RefCell::new(HashMap::new()),
+        types: RefCell::new(HashMap::new()),
+        heap: RefCell::new(vec![]),
+        model,
+        layouts: layout::layouts(prg)?,
+        out,
+        budget,
+        used: RefCell::default(),
+        hook: Hook::default(),
+        calls: RefCell::default(),
+        uninit: RefCell::default(),
+    };
 
-    // defining nv here so eval_fn can borrow both
-    let lvnv = nv.vnv.clone(); // clone it first, before giving &mut
-    eval_func(&nv.fnv["main"], &nv, lvnv)
+    // statics are allocated before main's frame, so they outlive every call
+    let mut statics = vec![];
+    for def in prg {
+        match &def.node {
+            SDef::VarDef(vd) => statics.push((vd.alias.as_str(), &vd.typ, Some(&**vd.expr))),
+            SDef::VarDecl(vd) => statics.push((vd.alias.as_str(), &vd.typ, None)),
+            SDef::FuncDef(fd) => local_statics(&nv.fnv[&fd.alias].body, &mut statics),
+            SDef::FuncDecl(_) | SDef::StructDef(_) | SDef::UnionDef(_) | SDef::EnumDef(_) => (),
+        }
+    }
+    let vnv = statics
+        .into_iter()
+        .map(|(alias, typ, init)| {
+            let addr = match init {
+                Some(e) => alloc(&nv, typ, eval_const(e, typ, &nv.layouts, &nv.model)?)?,
+                None => alloc_zeroed(&nv, typ)?,
+            };
+            Ok((alias.to_owned(), addr))
+        })
+        .collect::<Result<HashMap<_, _>, io::Error>>()?;
+    nv.vnv = vnv;
+    Ok(nv)
+}
+
+// what runs without --dyn-check: annotations are comments, as they are to a
+// c compiler. assert(e) is a stmt, and still checked
+pub fn erase_contracts(prg: &SPrg) -> SPrg {
+    fn stmts(ss: &[Spanned<SStmt>]) -> Vec<Spanned<SStmt>> {
+        ss.iter().map(stmt).collect()
+    }
+    fn stmt(s: &Spanned<SStmt>) -> Spanned<SStmt> {
+        let node = match &s.node {
+            SStmt::Contract(_) => SStmt::Block(vec![]), // it may be an if's only stmt
+            SStmt::IfEls { cond, then, els } => SStmt::IfEls {
+                cond: cond.clone(),
+                then: Box::new(stmt(then)),
+                els: els.as_ref().map(|els| Box::new(stmt(els))),
+            },
+            SStmt::Switch { cond, arms } => SStmt::Switch {
+                cond: cond.clone(),
+                arms: arms
+                    .iter()
+                    .map(|a| SCase {
+                        label: a.label,
+                        body: stmts(&a.body),
+                    })
+                    .collect(),
+            },
+            SStmt::While { cond, body, .. } => SStmt::While {
+                cond: cond.clone(),
+                body: stmts(body),
+                invariants: vec![],
+            },
+            SStmt::DoWhile { body, cond } => SStmt::DoWhile {
+                body: stmts(body),
+                cond: cond.clone(),
+            },
+            SStmt::For {
+                asnmt,
+                cond,
+                update,
+                body,
+                ..
+            } => SStmt::For {
+                asnmt: asnmt.clone(),
+                cond: cond.clone(),
+                update: update.clone(),
+                body: stmts(body),
+                invariants: vec![],
+            },
+            SStmt::Block(body) => SStmt::Block(stmts(body)),
+            node => node.clone(),
+        };
+        Spanned::new(node, s.span)
+    }
+
+    prg.iter()
+        .map(|def| match &def.node {
+            SDef::FuncDef(fd) => Spanned::new(
+                SDef::FuncDef(SFuncDef {
+                    body: stmts(&fd.body),
+                    contracts: vec![],
+                    ..fd.clone()
+                }),
+                def.span,
+            ),
+            _ => def.clone(),
+        })
+        .collect()
+}
+
+// a constant expression's value, converted to typ as if stored in it.
+// static initializers are constants, checked by typecheck, so the backend
+// folds them with this too, under its own model
+pub fn eval_const(
+    e: &SExpr,
+    typ: &Type,
+    layouts: &layout::Layouts,
+    model: &IntModel,
+) -> Result<i32, io::Error> {
+    let nv = Vnv {
+        fnv: HashMap::new(),
+        vnv: HashMap::new(),
+        mem: RefCell::new(vec![]),
+        rodata: RefCell::new(vec![]),
+        strs: RefCell::new(HashMap::new()),
+        types: RefCell::new(HashMap::new()),
+        heap: RefCell::new(vec![]),
+        model: model.clone(),
+        layouts: layouts.clone(),
+        out: Sink::default(),
+        budget: Budget::default(),
+        used: RefCell::default(),
+        hook: Hook::default(),
+        calls: RefCell::default(),
+        uninit: RefCell::default(),
+    };
+    let (from, to) = (arith(e, &nv, &HashMap::new()), width(&nv, typ)?);
+    let val = eval_expr(e, &nv, &mut HashMap::new())?;
+    Ok(narrow(convert(val, from, to), to))
 }
 
+fn local_statics<'a>(
+    stmts: &'a [Spanned<SStmt>],
+    statics: &mut Vec<(&'a str, &'a Type, Option<&'a SExpr>)>,
+) {
+    for s in stmts {
+        match &s.node {
+            SStmt::Asnmt(vd) if vd.is_static => {
+                statics.push((vd.alias.as_str(), &vd.typ, Some(&**vd.expr)))
+            }
+            SStmt::Decl(vd) if vd.is_static => statics.push((vd.alias.as_str(), &vd.typ, None)),
+            SStmt::IfEls { then, els, .. } => {
+                local_statics(std::slice::from_ref(then), statics);
+                if let Some(els) = els {
+                    local_statics(std::slice::from_ref(els), statics);
+                }
+            }
+            SStmt::Switch { arms, .. } => arms.iter().for_each(|a| local_statics(&a.body, statics)),
+            SStmt::While { body, .. } | SStmt::DoWhile { body, .. } | SStmt::Block(body) => {
+                local_statics(body, statics)
+            }
+            SStmt::For { asnmt, body, .. } => {
+                local_statics(std::slice::from_ref(asnmt), statics);
+                local_statics(body, statics)
+            }
+            _ => (),
+        }
+    }
+}
+
+// returns store into a slot of the function's return type, so they convert
+// like assignments do. its name can't be an identifier's
+pub const RET: &str = "$ret";
+
+// contracts see the params, whatever the body declares. ensures reads
+// \result from the return slot, which holds what was returned
 fn eval_func(l: &LambdaVal, gnv: &Vnv, mut lvnv: HashMap<String, i32>) -> Result<i32, io::Error> {
-    l.body
+    lvnv.insert(RET.to_owned(), alloc_zeroed(gnv, &l.ret)?);
+    let mut clvnv = lvnv.clone();
+    clvnv.insert(RESULT.to_owned(), lvnv[RET]);
+    let (requires, ensures) = l
+        .contracts
         .iter()
-        .try_fold(None, |acc, stmt| {
-            if acc.is_none() {
-                eval_stmt(stmt, gnv, &mut lvnv)
-            } else {
-                Ok(acc) // can't break from closures. switch to loop if perf is an issue
-            }
-        })?
-        .ok_or(io::Error::new(io::ErrorKind::Other, "no return stmt"))
+        .partition::<Vec<_>, _>(|c| c.kind == SContractKind::Requires);
+
+    checks(requires, gnv, &mut clvnv)?;
+    let val = match eval_stmts(&l.body, gnv, &mut lvnv)? {
+        Some(Signal::Ret(val)) => Ok(val),
+        Some(Signal::Break | Signal::Continue) => Err(io::Error::other("jump outside a loop")),
+        None if l.ret == Type::Void => Ok(0), // void functions can run off their end
+        None => Err(io::Error::other("no return stmt")),
+    }?;
+    checks(ensures, gnv, &mut clvnv)?;
+    Ok(val)
+}
+
+// a contract that doesn't hold is an error at it
+fn checks<'a>(
+    contracts: impl IntoIterator<Item = &'a SContract>,
+    gnv: &Vnv,
+    lvnv: &mut HashMap<String, i32>,
+) -> Result<(), io::Error> {
+    contracts.into_iter().try_for_each(|c| {
+        match eval_expr(&c.expr, gnv, lvnv) {
+            Ok(0) => Err(io::Error::other(violated(&c.kind))),
+            Ok(_) => Ok(()),
+            Err(e) => Err(e),
+        }
+        .map_err(|e| source::locate(e, c.expr.span))
+    })
+}
+
+pub fn violated(kind: &SContractKind) -> String {
+    let kind = match kind {
+        SContractKind::Requires => "requires",
+        SContractKind::Ensures => "ensures",
+        SContractKind::LoopInvariant => "loop_invariant",
+        SContractKind::Assert => "assert",
+    };
+    format!("@{kind} annotation failed")
+}
+
+// errors are located at the innermost stmt they come from, which may be
+// in a function that was called
+fn eval_stmts(
+    stmts: &[Spanned<SStmt>],
+    gnv: &Vnv,
+    lvnv: &mut HashMap<String, i32>,
+) -> Result<Option<Signal>, io::Error> {
+    for stmt in stmts {
+        if let Some(hook) = &gnv.hook.0 {
+            hook.borrow_mut()(Event::Stmt(stmt), gnv, lvnv)?;
+        }
+        let sig = step(gnv)
+            .and_then(|_| eval_stmt(stmt, gnv, lvnv))
+            .map_err(|e| source::locate(e, stmt.span))?;
+        if let Some(hook) = &gnv.hook.0 {
+            hook.borrow_mut()(Event::Done(stmt, sig), gnv, lvnv)?;
+        }
+        if let Some(sig) = sig {
+            return Ok(Some(sig));
+        }
+    }
+
+    Ok(None)
+}
+
+// blocks introduce a scope: their own bindings are dropped on exit,
+// but updates to bindings of the enclosing scope are kept, since those
+// went through to memory.
+fn eval_block(
+    stmts: &[Spanned<SStmt>],
+    gnv: &Vnv,
+    lvnv: &mut HashMap<String, i32>,
+) -> Result<Option<Signal>, io::Error> {
+    let top = gnv.mem.borrow().len();
+    let val = eval_stmts(stmts, gnv, &mut lvnv.clone());
+    gnv.mem.borrow_mut().truncate(top);
+    val
+}
+
+// locals map aliases to addresses, and memory is a stack of words which
+// blocks and calls pop on exit. pointers into popped frames dangle, as in C
+fn alloc(gnv: &Vnv, typ: &Type, val: i32) -> Result<i32, io::Error> {
+    let addr = alloc_zeroed(gnv, typ)?;
+    store(gnv, addr, val)?;
+    Ok(addr)
+}
+
+// a cell per word, for arrays and structs. they're zeroed, though c leaves them uninitialized
+pub fn alloc_zeroed(gnv: &Vnv, typ: &Type) -> Result<i32, io::Error> {
+    let cells = gnv.layouts.cells(typ)?;
+    let addr = gnv.mem.borrow().len() as i32;
+    gnv.mem.borrow_mut().extend(cells.iter().map(|w| (0, *w)));
+    retype(gnv, addr, typ, cells.len());
+    // a popped frame's uninitialized locals may have been here
+    let mut uninit = gnv.uninit.borrow_mut();
+    (addr..addr + cells.len() as i32).for_each(|a| _ = uninit.remove(&a));
+    Ok(addr)
+}
+
+// records what's allocated at addr. the types popped frames left inside
+// it are stale, and would bound pointers into it
+fn retype(gnv: &Vnv, addr: i32, typ: &Type, cells: usize) {
+    let mut types = gnv.types.borrow_mut();
+    (addr + 1..addr + cells as i32).for_each(|a| _ = types.remove(&a));
+    types.insert(addr, typ.clone());
+}
+
+// string literals are pooled apart from the stack, from RODATA up, so
+// they outlive the frames that first evaluate them and can't be written
+pub const RODATA: i32 = 1 << 24;
+
+fn intern(gnv: &Vnv, s: &str) -> i32 {
+    *gnv.strs
+        .borrow_mut()
+        .entry(s.to_owned())
+        .or_insert_with(|| {
+            let mut rodata = gnv.rodata.borrow_mut();
+            let addr = RODATA + rodata.len() as i32;
+            rodata.extend(s.bytes().map(|b| b as i8 as i32).chain(iter::once(0)));
+            addr
+        })
+}
+
+// a variable's value as a debugger shows it: arrays and structs cell by
+// cell, and pointers in hex
+pub fn show(gnv: &Vnv, addr: i32) -> Result<String, io::Error> {
+    let typ = gnv.types.borrow().get(&addr).cloned();
+    match typ {
+        Some(Type::Ptr(_)) => Ok(format!("{:#x}", load(gnv, addr)?)),
+        Some(typ @ (Type::Arr(..) | Type::Struct(_) | Type::Union(_))) => {
+            let cells = gnv.layouts.cells(&typ)?.len() as i32;
+            let vals = (addr..addr + cells)
+                .map(|a| show_cell(gnv, a))
+                .collect::<Result<Vec<_>, io::Error>>()?;
+            Ok(format!("{{{}}}", vals.join(", ")))
+        }
+        _ => show_cell(gnv, addr),
+    }
+}
+
+// a cell's value as its width reads it
+pub fn show_cell(gnv: &Vnv, addr: i32) -> Result<String, io::Error> {
+    let val = load(gnv, addr)?;
+    Ok(match cell(gnv, addr) {
+        Width::Float => unfloat(val).to_string(),
+        Width::UWord => (val as u32).to_string(),
+        _ => val.to_string(),
+    })
+}
+
+// one more stmt or loop test for the walker, or op for the vm
+fn step(gnv: &Vnv) -> Result<(), io::Error> {
+    let steps = &mut gnv.used.borrow_mut().0;
+    *steps += 1;
+    match gnv.budget.steps {
+        Some(max) if *steps > max => Err(io::Error::other(format!(
+            "resource limit exceeded: more than {max} steps"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+// how many calls deep a program may go, unless its budget says otherwise.
+// each of the walker's takes several of the host's frames, kilobytes of
+// stack in a debug build, so it's run on a thread with STACK bytes of it
+pub const DEPTH: usize = 10_000;
+pub const STACK: usize = 512 << 20;
+
+// one more call, depth deep counting main's, for the walker or the vm
+fn call(gnv: &Vnv, depth: usize) -> Result<(), io::Error> {
+    match gnv.budget.depth {
+        Some(max) if depth > max => Err(io::Error::other(format!(
+            "resource limit exceeded: more than {max} calls deep"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+// alloc's blocks are below RODATA, from HEAP up. they're never freed, and
+// the stack never grows into them
+pub const HEAP: i32 = 1 << 23;
+
+// a zeroed block on the heap, recorded as typ so it's bounds checked. a
+// zero length array still takes a cell, so its address is its own
+fn alloc_heap(gnv: &Vnv, typ: Type) -> Result<i32, io::Error> {
+    let bytes = gnv.layouts.sizeof(&typ, &gnv.model)?;
+    let used = &mut gnv.used.borrow_mut().1;
+    match gnv.budget.heap {
+        Some(max) if *used + bytes > max => {
+            return Err(io::Error::other(format!(
+                "resource limit exceeded: more than {max} bytes of heap"
+            )))
+        }
+        _ => *used += bytes,
+    }
+    let cells = gnv.layouts.cells(&typ)?;
+    let mut heap = gnv.heap.borrow_mut();
+    let addr = HEAP + heap.len() as i32;
+    if cells.len().max(1) > (RODATA - addr) as usize {
+        return Err(io::Error::other("out of heap memory"));
+    }
+    match cells.is_empty() {
+        true => heap.push((0, Width::Word)),
+        false => heap.extend(cells.into_iter().map(|w| (0, w))),
+    }
+    gnv.types.borrow_mut().insert(addr, typ);
+    Ok(addr)
+}
+
+// alloc_array(typ, n). n is checked before the block's laid out, so a huge
+// one fails rather than filling the host's memory
+fn alloc_array(gnv: &Vnv, typ: &Type, n: i32) -> Result<i32, io::Error> {
+    let cells = gnv.layouts.cells(typ)?.len() as i64 * n as i64;
+    match n {
+        ..0 => Err(io::Error::other(format!("negative array size {n}"))),
+        _ if cells > (RODATA - HEAP) as i64 => Err(io::Error::other("out of heap memory")),
+        _ => alloc_heap(gnv, Type::Arr(Box::new(typ.clone()), n as usize)),
+    }
+}
+
+// a[i]'s address. an allocation that isn't an array is one of a single
+// element, as in c. it's bounded by its cells, so an int * cast from an
+// array of structs reaches all of theirs. addresses inside a struct, or
+// past an array's first element, have no type of their own, so those
+// aren't checked
+fn index(gnv: &Vnv, base: i32, idx: i32) -> Result<i32, io::Error> {
+    let len = match gnv.types.borrow().get(&base) {
+        _ if base == 0 => return Err(io::Error::other("null pointer dereference")),
+        Some(t) => Some(gnv.layouts.cells(t)?.len() as i64),
+        None => None,
+    };
+    match len {
+        Some(n) if !(0..n).contains(&(idx as i64)) => Err(io::Error::other(format!(
+            "index out of bounds: {idx} of an array of {n}"
+        ))),
+        _ => Ok(base + idx),
+    }
+}
+
+pub fn load(gnv: &Vnv, addr: i32) -> Result<i32, io::Error> {
+    match addr {
+        RODATA.. => gnv.rodata.borrow().get((addr - RODATA) as usize).copied(),
+        HEAP.. => gnv
+            .heap
+            .borrow()
+            .get((addr - HEAP) as usize)
+            .map(|(v, _)| *v),
+        1.. => gnv.mem.borrow().get(addr as usize).map(|(v, _)| *v),
+        0 => return Err(io::Error::other("null pointer dereference")),
+        _ => None,
+    }
+    .ok_or(io::Error::other("invalid memory access"))
+}
+
+fn store(gnv: &Vnv, addr: i32, val: i32) -> Result<(), io::Error> {
+    gnv.uninit.borrow_mut().remove(&addr);
+    let cell = |(v, w): &mut (i32, Width)| *v = narrow(val, *w);
+    match addr {
+        RODATA.. => return Err(io::Error::other("write to read-only memory")),
+        HEAP.. => gnv
+            .heap
+            .borrow_mut()
+            .get_mut((addr - HEAP) as usize)
+            .map(cell),
+        1.. => gnv.mem.borrow_mut().get_mut(addr as usize).map(cell),
+        0 => return Err(io::Error::other("null pointer dereference")),
+        _ => None,
+    }
+    .ok_or(io::Error::other("invalid memory access"))
+}
+
+// a member's value is its bits read as its own type, whichever member of a
+// union stored them: a char reads an int's low byte, and a float an int's
+// bits. this is what makes type punning through unions work
+fn load_as(gnv: &Vnv, addr: i32, w: Option<Width>) -> Result<i32, io::Error> {
+    let val = load(gnv, addr)?;
+    Ok(w.map_or(val, |w| narrow(val, w)))
+}
+
+// a store through a member converts to the member's type, and one narrower
+// than its cell only overwrites its low bytes, as on a little endian machine
+fn store_as(
+    gnv: &Vnv,
+    addr: i32,
+    val: i32,
+    from: Arith,
+    w: Option<Width>,
+) -> Result<(), io::Error> {
+    let Some(w) = w else {
+        return store(gnv, addr, convert(val, from, cell(gnv, addr)));
+    };
+    let mask = match w {
+        Width::Byte | Width::UByte => 0xff,
+        Width::Half | Width::UHalf => 0xffff,
+        Width::Word | Width::UWord | Width::Float => -1,
+    };
+    let old = load(gnv, addr)?;
+    store(gnv, addr, old & !mask | convert(val, from, w) & mask)
+}
+
+// what a compound store needs of its lval: its arithmetic, the cells it
+// steps by when it's a pointer, and the width it's read and written as
+pub type Target = (Arith, Option<i32>, Option<Width>);
+
+pub fn target(lval: &SExpr, gvnv: &Vnv, lvnv: &HashMap<String, i32>) -> Target {
+    let stride = match lval_type(lval, gvnv, lvnv) {
+        Some(Type::Ptr(t)) => gvnv.layouts.words(&t).ok().map(|n| n as i32),
+        _ => None,
+    };
+    (arith(lval, gvnv, lvnv), stride, member(lval, gvnv, lvnv))
+}
+
+// lval op= r, for the lval at addr. it's read and written through addr
+// once each
+fn update(
+    op: &SBinOp,
+    (la, stride, w): Target,
+    addr: i32,
+    (ra, r): (Arith, i32),
+    gvnv: &Vnv,
+) -> Result<(), io::Error> {
+    let l = load_as(gvnv, addr, w)?;
+    let (from, val) = match stride {
+        Some(n) => (Arith::Signed, pointer(op, (Some(n), l), (None, r))),
+        None => (la.max(ra), binary(op, (la, l), (ra, r), &gvnv.model)?),
+    };
+    store_as(gvnv, addr, val, from, w)
+}
+
+// the width a member's read and written as, when e is one of a scalar
+// type. none for anything else, which is read as its cell is
+pub fn member(e: &SExpr, gvnv: &Vnv, lvnv: &HashMap<String, i32>) -> Option<Width> {
+    let SExpr::Member { .. } = e else {
+        return None;
+    };
+    match lval_type(e, gvnv, lvnv)? {
+        Type::Arr(..) | Type::Struct(_) | Type::Union(_) => None,
+        t => width(gvnv, &t).ok(),
+    }
+}
+
+// an lvalue's type, or a pointer's that's dereferenced, as far as the
+// bindings' recorded types tell it
+fn lval_type(e: &SExpr, gvnv: &Vnv, lvnv: &HashMap<String, i32>) -> Option<Type> {
+    let pointee = |t| match t {
+        Type::Ptr(t) | Type::Arr(t, _) => Some(*t),
+        _ => None,
+    };
+    match e {
+        SExpr::VarApp(alias) => gvnv.types.borrow().get(lvnv.get(alias)?).cloned(),
+        SExpr::Index { alias, .. } => {
+            pointee(lval_type(&SExpr::VarApp(alias.clone()), gvnv, lvnv)?)
+        }
+        SExpr::Deref(l) => pointee(lval_type(l, gvnv, lvnv)?),
+        SExpr::Member { base, field } => match lval_type(base, gvnv, lvnv)? {
+            Type::Struct(s) | Type::Union(s) => gvnv.layouts.field(&s, field).cloned(),
+            _ => None,
+        },
+        SExpr::Cast(t, _) => Some(t.clone()),
+        SExpr::Alloc(t) | SExpr::AllocArray(t, _) => Some(Type::Ptr(Box::new(t.clone()))),
+        SExpr::AddrOf(l) => Some(Type::Ptr(Box::new(lval_type(l, gvnv, lvnv)?))),
+        SExpr::FuncApp { alias, .. } => gvnv.fnv.get(alias).map(|f| f.ret.clone()),
+        SExpr::BinE {
+            op: SBinOp::Add | SBinOp::Sub,
+            l,
+            r,
+        } => [l, r]
+            .into_iter()
+            .filter_map(|e| lval_type(e, gvnv, lvnv).map(typer::decay))
+            .find(|t| matches!(t, Type::Ptr(_))),
+        _ => None,
+    }
+}
+
+// where base.field is in base, by the struct base is
+pub fn offset(
+    base: &SExpr,
+    field: &str,
+    gvnv: &Vnv,
+    lvnv: &HashMap<String, i32>,
+) -> Result<i32, io::Error> {
+    match lval_type(base, gvnv, lvnv) {
+        Some(Type::Struct(s) | Type::Union(s)) => gvnv.layouts.offset(&s, field),
+        _ => None,
+    }
+    .map(|o| o as i32)
+    .ok_or(io::Error::other("undefined member"))
+}
+
+// a member's address in the struct at addr. p->f with p null is caught
+// here, since f's offset would take it past the null check of a load
+fn member_addr(addr: i32, offset: i32) -> Result<i32, io::Error> {
+    match addr {
+        0 => Err(io::Error::other("null pointer dereference")),
+        _ => Ok(addr + offset),
+    }
+}
+
+fn narrow(val: i32, w: Width) -> i32 {
+    match w {
+        Width::Byte => val as i8 as i32, // chars are signed
+        Width::UByte => val as u8 as i32,
+        Width::Half => val as i16 as i32,
+        Width::UHalf => val as u16 as i32,
+        Width::Word | Width::UWord | Width::Float => val,
+    }
+}
+
+fn cell(gnv: &Vnv, addr: i32) -> Width {
+    match addr {
+        HEAP..RODATA => gnv.heap.borrow().get((addr - HEAP) as usize).copied(),
+        _ => gnv.mem.borrow().get(addr as usize).copied(),
+    }
+    .map_or(Width::Word, |(_, w)| w)
+}
+
+pub fn width(gnv: &Vnv, typ: &Type) -> Result<Width, io::Error> {
+    Ok(gnv
+        .layouts
+        .cells(typ)?
+        .first()
+        .copied()
+        .unwrap_or(Width::Word))
+}
+
+pub fn addr_of(alias: &str, lvnv: &HashMap<String, i32>) -> Result<i32, io::Error> {
+    lvnv.get(alias)
+        .copied()
+        .ok_or(io::Error::other("undefined variable"))
+}
+
+// alias's address, to read it through. a local declared without an
+// initializer can't be read before it's assigned, as c0 has it, where c
+// leaves its value indeterminate
+fn initialized(gnv: &Vnv, alias: &str, addr: i32) -> Result<i32, io::Error> {
+    match gnv.uninit.borrow().contains(&addr) {
+        true => {
+            let alias = alias.split('.').next().unwrap_or(alias);
+            let msg = format!("use of uninitialized variable {alias}");
+            Err(io::Error::other(msg))
+        }
+        false => Ok(addr),
+    }
+}
+
+// where the elements a[i] indexes are: an array's are its own cells, and a
+// pointer's where it points
+fn elems(gnv: &Vnv, addr: i32) -> Result<i32, io::Error> {
+    let ptr = matches!(gnv.types.borrow().get(&addr), Some(Type::Ptr(_)));
+    match ptr {
+        true => load(gnv, addr),
+        false => Ok(addr),
+    }
+}
+
+// where an lvalue lives: x, *e or a[e]
+fn eval_lval(e: &SExpr, gvnv: &Vnv, lvnv: &mut HashMap<String, i32>) -> Result<i32, io::Error> {
+    match e {
+        SExpr::VarApp(alias) => addr_of(alias, lvnv),
+        SExpr::Deref(l) => eval_expr(l, gvnv, lvnv),
+        SExpr::Index { alias, idx } => {
+            let base = elems(gvnv, initialized(gvnv, alias, addr_of(alias, lvnv)?)?)?;
+            let idx = eval_expr(idx, gvnv, lvnv)?;
+            index(gvnv, base, idx)
+        }
+        SExpr::Member { base, field } => {
+            let offset = offset(base, field, gvnv, lvnv)?;
+            member_addr(eval_lval(base, gvnv, lvnv)?, offset)
+        }
+        _ => Err(io::Error::other("lvalue required")),
+    }
 }
 
-fn eval_stmt(
-    stmt: &Stmt,
+// statements run to completion, or signal a return, break or continue
+// for the enclosing function, loop or switch to act on
+pub fn eval_stmt(
+    stmt: &SStmt,
     gnv: &Vnv,
     lvnv: &mut HashMap<String, i32>,
-) -> Result<Option<i32>, io::Error> {
+) -> Result<Option<Signal>, io::Error> {
     Ok(match stmt {
-        Stmt::Asnmt(var_def) => {
-            let val = eval_expr(&var_def.expr, gnv, &lvnv)?; // eager
-            lvnv.insert(var_def.alias.clone(), val);
+        // statics were allocated and initialized before main. the repl's
+        // top level has no before, so there they're like any other local
+        SStmt::Asnmt(SVarDef {
+            alias,
+            is_static: true,
+            ..
+        })
+        | SStmt::Decl(SVarDecl {
+            alias,
+            is_static: true,
+            ..
+        }) if gnv.vnv.contains_key(alias) => {
+            lvnv.insert(alias.clone(), gnv.vnv[alias]);
             None
         }
-        Stmt::Return(e) => Some(eval_expr(e, gnv, &lvnv)?),
-        Stmt::IfEls { cond, then, els } => {
-            let mut new_lvnv = lvnv.clone();
-
-            if eval_expr(cond, gnv, &new_lvnv)? == 1 {
-                eval_stmt(then, gnv, &mut new_lvnv)?
+        SStmt::Asnmt(var_def) => {
+            let from = arith(&var_def.expr, gnv, lvnv);
+            let val = eval_expr(&var_def.expr, gnv, lvnv)?; // eager
+            let val = convert(val, from, width(gnv, &var_def.typ)?);
+            lvnv.insert(var_def.alias.clone(), alloc(gnv, &var_def.typ, val)?);
+            None
+        }
+        SStmt::Decl(var_decl) => {
+            let addr = alloc_zeroed(gnv, &var_decl.typ)?;
+            if !matches!(
+                var_decl.typ,
+                Type::Arr(..) | Type::Struct(_) | Type::Union(_)
+            ) {
+                gnv.uninit.borrow_mut().insert(addr);
+            }
+            lvnv.insert(var_decl.alias.clone(), addr);
+            None
+        }
+        SStmt::Update(var_update) => {
+            let from = arith(&var_update.expr, gnv, lvnv);
+            let val = eval_expr(&var_update.expr, gnv, lvnv)?;
+            let addr = addr_of(&var_update.alias, lvnv)?;
+            store(gnv, addr, convert(val, from, cell(gnv, addr)))?;
+            None
+        }
+        SStmt::Store(st) => {
+            let from = arith(&st.expr, gnv, lvnv);
+            let val = eval_expr(&st.expr, gnv, lvnv)?;
+            let addr = eval_lval(&st.lval, gnv, lvnv)?;
+            match &st.op {
+                None => store_as(gnv, addr, val, from, member(&st.lval, gnv, lvnv))?,
+                Some(op) => update(op, target(&st.lval, gnv, lvnv), addr, (from, val), gnv)?,
+            }
+            None
+        }
+        SStmt::Return(None) => Some(Signal::Ret(0)), // the value of a void call is never used
+        SStmt::Return(Some(e)) => {
+            let from = arith(e, gnv, lvnv);
+            let val = eval_expr(e, gnv, lvnv)?;
+            match lvnv.get(RET) {
+                Some(&slot) => {
+                    store(gnv, slot, convert(val, from, cell(gnv, slot)))?;
+                    Some(Signal::Ret(load(gnv, slot)?))
+                }
+                None => Some(Signal::Ret(val)), // the repl's top level isn't in a function
+            }
+        }
+        SStmt::Contract(c) => {
+            checks([c], gnv, lvnv)?;
+            None
+        }
+        SStmt::Assert(e) => match eval_expr(e, gnv, lvnv)? {
+            0 => return Err(io::Error::other("assertion failed")),
+            _ => None,
+        },
+        SStmt::Call(e) => {
+            eval_expr(e, gnv, lvnv)?;
+            None
+        }
+        SStmt::Block(stmts) => eval_block(stmts, gnv, lvnv)?, // shadows, as blocks copy the env
+        SStmt::Label(_) => None,
+        SStmt::Goto(label) => {
+            // a tree walker can't jump into the middle of a block
+            return Err(io::Error::other(format!(
+                "goto {label}: the c0 interpreter doesn't support goto, compile with compilec89"
+            )));
+        }
+        SStmt::Break => Some(Signal::Break),
+        SStmt::Continue => Some(Signal::Continue),
+        SStmt::IfEls { cond, then, els } => {
+            if eval_expr(cond, gnv, lvnv)? != 0 {
+                eval_block(std::slice::from_ref(then.as_ref()), gnv, lvnv)?
+            } else if let Some(els) = els {
+                eval_block(std::slice::from_ref(els.as_ref()), gnv, lvnv)?
             } else {
-                eval_stmt(els, gnv, &mut new_lvnv)?
+                None
             }
         }
-        Stmt::While { cond, body } => {
-            while eval_expr(cond, gnv, &lvnv)? == 1 {
-                eval_stmt(body, gnv, lvnv)?;
+        SStmt::Switch { cond, arms } => {
+            // run from the matching arm, or default, through to the end
+            let val = eval_expr(cond, gnv, lvnv)?;
+            let arm = arms
+                .iter()
+                .position(|a| a.label == Some(val))
+                .or(arms.iter().position(|a| a.label.is_none()));
+            match arm {
+                Some(i) => {
+                    let body = arms[i..]
+                        .iter()
+                        .flat_map(|a| a.body.iter().cloned())
+                        .collect::<Vec<_>>();
+                    match eval_block(&body, gnv, lvnv)? {
+                        Some(Signal::Break) => None,
+                        sig => sig, // continues are for the enclosing loop
+                    }
+                }
+                None => None,
+            }
+        }
+        SStmt::While {
+            cond,
+            body,
+            invariants,
+        } => {
+            while {
+                step(gnv)?; // an empty body still takes its tests
+                checks(invariants, gnv, lvnv)?;
+                eval_expr(cond, gnv, lvnv)? != 0
+            } {
+                match eval_block(body, gnv, lvnv)? {
+                    Some(Signal::Break) => break,
+                    Some(Signal::Ret(val)) => return Ok(Some(Signal::Ret(val))),
+                    Some(Signal::Continue) | None => (),
+                }
+            }
+            None
+        }
+        SStmt::DoWhile { body, cond } => {
+            // the body runs before the first test
+            loop {
+                match eval_block(body, gnv, lvnv)? {
+                    Some(Signal::Break) => break,
+                    Some(Signal::Ret(val)) => return Ok(Some(Signal::Ret(val))),
+                    Some(Signal::Continue) | None => (), // continue still tests
+                }
+                step(gnv)?;
+                if eval_expr(cond, gnv, lvnv)? == 0 {
+                    break;
+                }
             }
             None
         }
+        SStmt::For {
+            asnmt,
+            cond,
+            update,
+            body,
+            invariants,
+        } => {
+            // the header's bindings are scoped to the loop
+            let (mut flvnv, top) = (lvnv.clone(), gnv.mem.borrow().len());
+            eval_stmt(asnmt, gnv, &mut flvnv)?;
+
+            let val = loop {
+                step(gnv)?;
+                checks(invariants, gnv, &mut flvnv)?;
+                if eval_expr(cond, gnv, &mut flvnv)? == 0 {
+                    break None;
+                }
+                match eval_block(body, gnv, &mut flvnv)? {
+                    Some(Signal::Break) => break None,
+                    Some(Signal::Ret(val)) => break Some(Signal::Ret(val)),
+                    Some(Signal::Continue) | None => (), // continue still updates
+                }
+                eval_stmt(update, gnv, &mut flvnv)?;
+            };
+
+            gnv.mem.borrow_mut().truncate(top);
+            val
+        }
     })
 }
 
-fn eval_expr(e: &Expr, gvnv: &Vnv, lvnv: &HashMap<String, i32>) -> Result<i32, io::Error> {
+// expressions can update locals, through ++ and -- and pointers
+pub fn eval_expr(e: &SExpr, gvnv: &Vnv, lvnv: &mut HashMap<String, i32>) -> Result<i32, io::Error> {
     match e {
-        Expr::Int(n) => Ok(*n),
-        Expr::Str(_) => todo!(), // check c0 spec
-        Expr::UnaryE { op, l } => todo!(),
-        Expr::BinE { op, l, r } => match op {
-            BinOp::Add => Ok(eval_expr(l, gvnv, lvnv)? + eval_expr(r, gvnv, lvnv)?),
-            BinOp::Sub => Ok(eval_expr(l, gvnv, lvnv)? - eval_expr(r, gvnv, lvnv)?),
-            BinOp::Mult => Ok(eval_expr(l, gvnv, lvnv)? * eval_expr(r, gvnv, lvnv)?),
-            BinOp::Div => Ok(eval_expr(l, gvnv, lvnv)? / eval_expr(r, gvnv, lvnv)?),
-            BinOp::Mod => Ok(eval_expr(l, gvnv, lvnv)? % eval_expr(r, gvnv, lvnv)?),
+        SExpr::Int(n) => Ok(gvnv.model.wrap(*n as i64)),
+        // longs keep all 32 bits whatever the int width
+        SExpr::TypedInt { val, typ } => match typ {
+            Type::UInt => Ok(gvnv.model.wrap(*val as i64)),
+            _ => Ok(*val),
         },
-        Expr::LogE { op, l, r } => todo!(),
-        Expr::BitE { op, l, r } => todo!(),
-        Expr::RelE { op, l, r } => todo!(),
-        Expr::VarApp(alias) => {
-            if lvnv.contains_key(alias) {
-                Ok(lvnv[alias].clone())
-            } else {
-                Err(io::Error::new(io::ErrorKind::Other, "undefined variable"))
+        SExpr::Float { lit, .. } => Ok(float(lit.parse().expect("checked by the parser"))),
+        SExpr::Bool(b) => Ok(*b as i32),
+        SExpr::Str(s) => Ok(intern(gvnv, s)),
+        SExpr::SizeOf(l) => Ok(gvnv.layouts.sizeof(&type_of(l, gvnv, lvnv)?, &gvnv.model)? as i32),
+        SExpr::SizeOfType(t) => Ok(gvnv.layouts.sizeof(t, &gvnv.model)? as i32),
+        SExpr::Alloc(t) => alloc_heap(gvnv, t.clone()),
+        SExpr::AllocArray(t, n) => {
+            let n = eval_expr(n, gvnv, lvnv)?;
+            alloc_array(gvnv, t, n)
+        }
+        SExpr::UnaryE { op, l } => {
+            let a = arith(l, gvnv, lvnv);
+            unary(op, a, eval_expr(l, gvnv, lvnv)?, &gvnv.model)
+        }
+        SExpr::Cast(t, l) => {
+            let from = arith(l, gvnv, lvnv);
+            cast(eval_expr(l, gvnv, lvnv)?, from, t, gvnv)
+        }
+        SExpr::BinE { op, l, r } if stride(l, gvnv).or(stride(r, gvnv)).is_some() => {
+            let (ls, rs) = (stride(l, gvnv), stride(r, gvnv));
+            let (l, r) = (eval_expr(l, gvnv, lvnv)?, eval_expr(r, gvnv, lvnv)?);
+            Ok(pointer(op, (ls, l), (rs, r)))
+        }
+        SExpr::BinE { op, l, r } => {
+            let (la, ra) = (arith(l, gvnv, lvnv), arith(r, gvnv, lvnv));
+            let (l, r) = (eval_expr(l, gvnv, lvnv)?, eval_expr(r, gvnv, lvnv)?);
+            binary(op, (la, l), (ra, r), &gvnv.model)
+        }
+        SExpr::LogE { op, l, r } => {
+            // short circuits: r is only evaluated when l doesn't decide
+            let l = eval_expr(l, gvnv, lvnv)? != 0;
+            let val = match op {
+                SLogOp::And => l && eval_expr(r, gvnv, lvnv)? != 0,
+                SLogOp::Or => l || eval_expr(r, gvnv, lvnv)? != 0,
+            };
+            Ok(val as i32)
+        }
+        SExpr::CondE { cond, then, els } => {
+            // the taken branch converts to the type of the whole
+            let to = arith(e, gvnv, lvnv);
+            let e = match eval_expr(cond, gvnv, lvnv)? != 0 {
+                true => then,
+                false => els,
+            };
+            let from = arith(e, gvnv, lvnv);
+            let val = eval_expr(e, gvnv, lvnv)?;
+            Ok(match to {
+                Arith::Float => float(to_float(val, from)),
+                _ => val,
+            })
+        }
+        SExpr::BitE { op, l, r } => {
+            let u = arith(l, gvnv, lvnv) == Arith::Unsigned;
+            let (l, r) = (eval_expr(l, gvnv, lvnv)?, eval_expr(r, gvnv, lvnv)?);
+            Ok(bitwise(op, u, l, r, &gvnv.model))
+        }
+        SExpr::RelE { op, l, r } => {
+            let (la, ra) = (arith(l, gvnv, lvnv), arith(r, gvnv, lvnv));
+            let (l, r) = (eval_expr(l, gvnv, lvnv)?, eval_expr(r, gvnv, lvnv)?);
+            Ok(relational(op, (la, l), (ra, r), &gvnv.model))
+        }
+        SExpr::VarApp(alias) => {
+            let addr = initialized(gvnv, alias, addr_of(alias, lvnv)?)?;
+            // an array's value is its first element's address
+            let arr = matches!(gvnv.types.borrow().get(&addr), Some(Type::Arr(..)));
+            match arr {
+                true => Ok(addr),
+                false => load(gvnv, addr),
             }
         }
-        Expr::FuncApp { alias, ap } => {
-            let l = &gvnv.fnv[alias];
+        SExpr::AddrOf(l) => eval_lval(l, gvnv, lvnv),
+        SExpr::Deref(_) | SExpr::Index { .. } | SExpr::Member { .. } => {
+            let addr = eval_lval(e, gvnv, lvnv)?;
+            load_as(gvnv, addr, member(e, gvnv, lvnv))
+        }
+        SExpr::FuncApp { alias, aps } => {
+            let Some(l) = gvnv.fnv.get(alias) else {
+                return intrinsic(alias, aps, gvnv, lvnv);
+            };
             let mut new_lvnv = gvnv.vnv.clone(); // this is what gnv is for. each func app needs it's own lvnv extended from gnv
 
-            let _ =
-                l.fp.iter()
-                    .zip(ap.iter())
-                    .map(|(fp, ap)| {
-                        eval_expr(ap, gvnv, &lvnv).and_then(|evaluated_ap| {
-                            new_lvnv
-                                .insert(fp.clone(), evaluated_ap)
-                                .ok_or(io::Error::new(io::ErrorKind::Other, "undefined variable"))
-                        })
-                    })
-                    .collect::<Result<Vec<_>, _>>()?;
+            // aps are evaluated before the callee's frame is pushed
+            let aps = aps
+                .iter()
+                .map(|ap| Ok((arith(ap, gvnv, lvnv), eval_expr(ap, gvnv, lvnv)?)))
+                .collect::<Result<Vec<_>, io::Error>>()?;
+            call(gvnv, gvnv.calls.borrow().len() + 1)?;
+            let top = gvnv.mem.borrow().len();
+            for ((fp, typ), (from, ap)) in l.fp.iter().zip(aps) {
+                let ap = convert(ap, from, width(gvnv, typ)?);
+                new_lvnv.insert(fp.clone(), alloc(gvnv, typ, ap)?);
+            }
 
-            eval_func(l, gvnv, new_lvnv) // reusing lvnv would be dynamic scope!
+            gvnv.calls.borrow_mut().push(alias.clone());
+            let val = eval_func(l, gvnv, new_lvnv); // reusing lvnv would be dynamic scope!
+            gvnv.calls.borrow_mut().pop();
+            gvnv.mem.borrow_mut().truncate(top);
+            val
+        }
+        SExpr::IncDec { op, lval } => {
+            let addr = match &lval.node {
+                SExpr::VarApp(alias) => initialized(gvnv, alias, addr_of(alias, lvnv)?)?,
+                l => eval_lval(l, gvnv, lvnv)?,
+            };
+            inc_dec(op, addr, gvnv)
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{lexer, parser};
-    use std::fs;
-    const TEST_DIR: &str = "tests/fixtures/snap/bindings";
+// the stack machine bytecode runs on. a call pushes the callee's whole
+// frame onto memory at once, and its return pops it, so pointers into
+// popped frames dangle as they do for the tree walker
+struct Machine {
+    stack: Vec<i32>,
+    frames: Vec<(usize, usize, i32)>, // the callers' funcs, pcs and fps
+    func: usize,
+    pc: usize,
+    fp: i32,
+}
 
-    #[test]
-    fn dyn_scope() {
-        let chars = fs::read(format!("tests/fixtures/snap/illegal/dyn_scope.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
-        let tokens = lexer::lex(&chars).unwrap();
-        let tree = parser::parse_prg(&tokens).unwrap();
-        let val = eval_prg(tree);
-        assert!(matches!(
-            val,
-            Err(e) if e.kind() == io::ErrorKind::Other && e.to_string() == "undefined variable"
-        ));
+fn run(prg: &bytecode::Program, gnv: &Vnv) -> Result<i32, io::Error> {
+    let mut m = Machine {
+        stack: vec![],
+        frames: vec![],
+        func: prg.main,
+        pc: 0,
+        fp: push_frame(&prg.funcs[prg.main], gnv),
+    };
+    loop {
+        let f = &prg.funcs[m.func];
+        let (op, span) = (&f.ops[m.pc], f.spans[m.pc]);
+        m.pc += 1;
+        match step(gnv).and_then(|_| m.step(op, prg, gnv)) {
+            Ok(Some(exit)) => return Ok(exit),
+            Ok(None) => (),
+            // an error without a stmt of its own is at its call's
+            Err(e) => {
+                let calls = m
+                    .frames
+                    .iter()
+                    .rev()
+                    .map(|(f, pc, _)| prg.funcs[*f].spans[pc - 1]);
+                return Err(iter::once(span).chain(calls).fold(e, source::locate));
+            }
+        }
     }
+}
 
-    #[test]
-    fn if_scope() {
-        let chars = fs::read(format!("tests/fixtures/snap/illegal/if_scope.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
-        let tokens = lexer::lex(&chars).unwrap();
-        let tree = parser::parse_prg(&tokens).unwrap();
-        let val = eval_prg(tree);
-        assert!(matches!(
-            val,
-            Err(e) if e.kind() == io::ErrorKind::Other && e.to_string() == "undefined variable"
-        ));
+fn push_frame(f: &bytecode::Func, gnv: &Vnv) -> i32 {
+    let mut mem = gnv.mem.borrow_mut();
+    let fp = mem.len() as i32;
+    mem.extend(f.frame.iter().map(|w| (0, *w)));
+    let mut uninit = gnv.uninit.borrow_mut();
+    (fp..fp + f.frame.len() as i32).for_each(|a| _ = uninit.remove(&a)); // as in alloc_zeroed
+    fp
+}
+
+impl Machine {
+    fn pop(&mut self) -> i32 {
+        self.stack
+            .pop()
+            .expect("ops leave their operands on the stack")
     }
 
-    #[test]
-    fn static_scope() {
-        let chars = fs::read(format!("{TEST_DIR}/static_scope.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
-        let tokens = lexer::lex(&chars).unwrap();
-        let tree = parser::parse_prg(&tokens).unwrap();
-        let val = eval_prg(tree).unwrap();
-        assert_eq!(val, 19);
+    fn args(&mut self, n: usize) -> Vec<i32> {
+        self.stack.split_off(self.stack.len() - n)
+    }
+
+    // main's return value, once it returns
+    fn step(
+        &mut self,
+        op: &bytecode::Op,
+        prg: &bytecode::Program,
+        gnv: &Vnv,
+    ) -> Result<Option<i32>, io::Error> {
+        use bytecode::Op;
+        let model = &gnv.model;
+        let val = match op {
+            Op::Push(k) => *k,
+            Op::Str(n) => intern(gnv, &prg.strs[*n]),
+            Op::Local(off) => self.fp + off,
+            Op::Offset(k) => member_addr(self.pop(), *k)?,
+            Op::Index => {
+                let (idx, base) = (self.pop(), self.pop());
+                index(gnv, base, idx)?
+            }
+            Op::Load => {
+                let addr = self.pop();
+                load(gnv, addr)?
+            }
+            Op::Store(from, w) => {
+                let (addr, val) = (self.pop(), self.pop());
+                store_as(gnv, addr, val, *from, *w)?;
+                return Ok(None);
+            }
+            Op::Update(op, target, from) => {
+                let (addr, val) = (self.pop(), self.pop());
+                update(op, *target, addr, (*from, val), gnv)?;
+                return Ok(None);
+            }
+            Op::Pun(w) => {
+                let val = self.pop();
+                narrow(val, *w)
+            }
+            Op::Unset(off) => {
+                gnv.uninit.borrow_mut().insert(self.fp + off);
+                return Ok(None);
+            }
+            Op::Assigned(alias) => {
+                let addr = self.pop();
+                initialized(gnv, alias, addr)?
+            }
+            Op::Zero(off, typ) => {
+                let (from, cells) = (self.fp + off, gnv.layouts.cells(typ)?.len());
+                gnv.mem.borrow_mut()[from as usize..from as usize + cells]
+                    .iter_mut()
+                    .for_each(|(v, _)| *v = 0);
+                retype(gnv, from, typ, cells);
+                return Ok(None);
+            }
+            Op::Drop => {
+                self.pop();
+                return Ok(None);
+            }
+            Op::Unary(op, a) => {
+                let val = self.pop();
+                unary(op, *a, val, model)?
+            }
+            Op::Bin(op, la, ra) => {
+                let (r, l) = (self.pop(), self.pop());
+                binary(op, (*la, l), (*ra, r), model)?
+            }
+            Op::Ptr(op, ls, rs) => {
+                let (r, l) = (self.pop(), self.pop());
+                pointer(op, (*ls, l), (*rs, r))
+            }
+            Op::Rel(op, la, ra) => {
+                let (r, l) = (self.pop(), self.pop());
+                relational(op, (*la, l), (*ra, r), model)
+            }
+            Op::Bit(op, u) => {
+                let (r, l) = (self.pop(), self.pop());
+                bitwise(op, *u, l, r, model)
+            }
+            Op::IncDec(op) => {
+                let addr = self.pop();
+                inc_dec(op, addr, gnv)?
+            }
+            Op::ToFloat(from) => {
+                let val = self.pop();
+                float(to_float(val, *from))
+            }
+            Op::Cast(from, t) => {
+                let val = self.pop();
+                cast(val, *from, t, gnv)?
+            }
+            Op::Jump(l) => {
+                self.pc = *l;
+                return Ok(None);
+            }
+            Op::JumpIf(taken, l) => {
+                if (self.pop() != 0) == *taken {
+                    self.pc = *l;
+                }
+                return Ok(None);
+            }
+            Op::Switch(arms, default) => {
+                let val = self.pop();
+                self.pc = arms
+                    .iter()
+                    .find(|(k, _)| *k == val)
+                    .map_or(*default, |(_, l)| *l);
+                return Ok(None);
+            }
+            Op::Call(f, froms) => {
+                // args are evaluated before the callee's frame is pushed
+                call(gnv, self.frames.len() + 2)?; // main's frame isn't in frames
+                let aps = self.args(froms.len());
+                let callee = &prg.funcs[*f];
+                self.frames.push((self.func, self.pc, self.fp));
+                (self.func, self.pc, self.fp) = (*f, 0, push_frame(callee, gnv));
+                for ((off, w), (from, ap)) in callee.params.iter().zip(froms.iter().zip(aps)) {
+                    store(gnv, self.fp + off, convert(ap, *from, *w))?;
+                }
+                return Ok(None);
+            }
+            Op::Intrinsic(alias, froms) => {
+                let aps = self.args(froms.len());
+                let aps = froms.iter().copied().zip(aps).collect::<Vec<_>>();
+                libc(alias, &aps, gnv)?
+            }
+            Op::Alloc(t) => alloc_heap(gnv, t.clone())?,
+            Op::AllocArray(t) => {
+                let n = self.pop();
+                alloc_array(gnv, t, n)?
+            }
+            Op::Ret => {
+                let val = self.pop();
+                gnv.mem.borrow_mut().truncate(self.fp as usize);
+                match self.frames.pop() {
+                    Some((func, pc, fp)) => (self.func, self.pc, self.fp) = (func, pc, fp),
+                    None => return Ok(Some(val)),
+                }
+                val
+            }
+            Op::Fail(msg) => return Err(io::Error::other(msg.clone())),
+        };
+        self.stack.push(val);
+        Ok(None)
+    }
+}
+
+// the operators, as both the tree walker and the vm apply them to values.
+// a value's arith is what its operand's was
+fn unary(op: &SUnaryOp, a: Arith, val: i32, model: &IntModel) -> Result<i32, io::Error> {
+    Ok(match op {
+        SUnaryOp::Not => (val == 0) as i32,
+        SUnaryOp::Add => val,
+        SUnaryOp::Sub if a == Arith::Float => float(-unfloat(val)),
+        SUnaryOp::Sub if a == Arith::Unsigned => model.wrap(-(val as i64)),
+        SUnaryOp::Sub => model.wrap_signed(-(val as i64))?,
+        SUnaryOp::BitNot => !val,
+    })
+}
+
+// the cells a pointer arithmetic operand steps by, if it's a pointer. the
+// typer marks those with a cast to what they point to
+pub fn stride(e: &SExpr, gvnv: &Vnv) -> Option<i32> {
+    match e {
+        SExpr::Cast(Type::Ptr(t), _) => gvnv.layouts.words(t).ok().map(|n| n as i32),
+        _ => None,
+    }
+}
+
+// p + i is i elements past p, and p - q the elements between them. an
+// address is a cell's, whatever the int width, so it isn't wrapped to it
+fn pointer(op: &SBinOp, (ls, l): (Option<i32>, i32), (rs, r): (Option<i32>, i32)) -> i32 {
+    match (op, ls, rs) {
+        (SBinOp::Sub, Some(n), Some(_)) => l.wrapping_sub(r) / n,
+        (SBinOp::Sub, Some(n), None) => l.wrapping_sub(r.wrapping_mul(n)),
+        (_, Some(n), None) => l.wrapping_add(r.wrapping_mul(n)),
+        (_, None, Some(n)) => r.wrapping_add(l.wrapping_mul(n)),
+        _ => unreachable!("checked by the typer"),
+    }
+}
+
+// computed in 64 bits, then wrapped to the model's int width. signed add,
+// sub and mult overflow when it doesn't fit, which --overflow=trap stops at.
+// division checks its operands either way
+fn binary(
+    op: &SBinOp,
+    (la, l): (Arith, i32),
+    (ra, r): (Arith, i32),
+    model: &IntModel,
+) -> Result<i32, io::Error> {
+    if la.max(ra) == Arith::Float {
+        let (l, r) = (to_float(l, la), to_float(r, ra));
+        return Ok(float(match op {
+            SBinOp::Add => l + r,
+            SBinOp::Sub => l - r,
+            SBinOp::Mult => l * r,
+            SBinOp::Div => l / r,
+            SBinOp::Mod => l % r,
+        }));
+    }
+    let (l, r) = match la.max(ra) {
+        Arith::Unsigned => (model.unsigned(l), model.unsigned(r)),
+        _ => (l as i64, r as i64),
+    };
+    let val = match op {
+        SBinOp::Add => l + r,
+        SBinOp::Sub => l - r,
+        SBinOp::Mult => l * r,
+        SBinOp::Div => return model.divide(l, r, false),
+        SBinOp::Mod => return model.divide(l, r, true),
+    };
+    match (op, la.max(ra)) {
+        (SBinOp::Add | SBinOp::Sub | SBinOp::Mult, Arith::Signed) => model.wrap_signed(val),
+        _ => Ok(model.wrap(val)),
+    }
+}
+
+// shifts are by the low five bits, like riscv's sll, sra and srl
+fn bitwise(op: &SBitOp, unsigned: bool, l: i32, r: i32, model: &IntModel) -> i32 {
+    let val = match op {
+        SBitOp::And => l & r,
+        SBitOp::Or => l | r,
+        SBitOp::Xor => l ^ r,
+        SBitOp::Shl => l.wrapping_shl(r as u32),
+        SBitOp::Shr if unsigned => (model.unsigned(l) as u32).wrapping_shr(r as u32) as i32,
+        SBitOp::Shr => l.wrapping_shr(r as u32),
+    };
+    model.wrap(val as i64)
+}
+
+fn relational(op: &SRelOp, (la, l): (Arith, i32), (ra, r): (Arith, i32), model: &IntModel) -> i32 {
+    if la.max(ra) == Arith::Float {
+        let (l, r) = (to_float(l, la), to_float(r, ra));
+        return match op {
+            SRelOp::Eq => l == r,
+            SRelOp::Neq => l != r,
+            SRelOp::Lt => l < r,
+            SRelOp::LtEq => l <= r,
+            SRelOp::Gt => l > r,
+            SRelOp::GtEq => l >= r,
+        } as i32;
+    }
+    let (l, r) = match la.max(ra) {
+        Arith::Unsigned => (model.unsigned(l), model.unsigned(r)),
+        _ => (l as i64, r as i64),
+    };
+    let val = match op {
+        SRelOp::Eq => l == r,
+        SRelOp::Neq => l != r,
+        SRelOp::Lt => l < r,
+        SRelOp::LtEq => l <= r,
+        SRelOp::Gt => l > r,
+        SRelOp::GtEq => l >= r,
+    };
+    val as i32
+}
+
+fn inc_dec(op: &SIncDecOp, addr: i32, gvnv: &Vnv) -> Result<i32, io::Error> {
+    let old = load(gvnv, addr)?;
+    let new = match op {
+        SIncDecOp::PreInc | SIncDecOp::PostInc => old as i64 + 1,
+        SIncDecOp::PreDec | SIncDecOp::PostDec => old as i64 - 1,
+    };
+    // chars and shorts are promoted, so only an int's step can overflow
+    let new = match cell(gvnv, addr) {
+        Width::Word => gvnv.model.wrap_signed(new)?,
+        _ => gvnv.model.wrap(new),
+    };
+    store(gvnv, addr, new)?;
+    match op {
+        SIncDecOp::PreInc | SIncDecOp::PreDec => Ok(new),
+        SIncDecOp::PostInc | SIncDecOp::PostDec => Ok(old),
+    }
+}
+
+// which arithmetic an operand's value is for, in the order the usual
+// conversions rank them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Arith {
+    Signed,
+    Unsigned,
+    Float,
+}
+
+// the evaluator doesn't carry types, so an operand's arithmetic is read off
+// the cells it comes from. unsigned chars and shorts are promoted to int, so
+// only unsigned words count
+// todo: operands loaded through pointers and arrays
+pub fn arith(e: &SExpr, gvnv: &Vnv, lvnv: &HashMap<String, i32>) -> Arith {
+    let of = |w| match w {
+        Width::UWord => Arith::Unsigned,
+        Width::Float => Arith::Float,
+        _ => Arith::Signed,
+    };
+    match e {
+        SExpr::Float { .. } => Arith::Float,
+        SExpr::TypedInt { typ, .. } | SExpr::Cast(typ, _) => {
+            width(gvnv, typ).map_or(Arith::Signed, of)
+        }
+        SExpr::VarApp(alias) => lvnv
+            .get(alias)
+            .map_or(Arith::Signed, |addr| of(cell(gvnv, *addr))),
+        SExpr::IncDec { lval, .. } => arith(lval, gvnv, lvnv),
+        SExpr::Member { .. } => member(e, gvnv, lvnv).map_or(Arith::Signed, of),
+        SExpr::FuncApp { alias, .. } => gvnv
+            .fnv
+            .get(alias)
+            .and_then(|l| width(gvnv, &l.ret).ok())
+            .map_or(Arith::Signed, of),
+        SExpr::UnaryE {
+            op: SUnaryOp::Add | SUnaryOp::Sub | SUnaryOp::BitNot,
+            l,
+        } => arith(l, gvnv, lvnv),
+        SExpr::BitE {
+            op: SBitOp::Shl | SBitOp::Shr,
+            l,
+            ..
+        } => arith(l, gvnv, lvnv),
+        SExpr::BinE { l, r, .. } | SExpr::BitE { l, r, .. } => {
+            arith(l, gvnv, lvnv).max(arith(r, gvnv, lvnv))
+        }
+        SExpr::CondE { then, els, .. } => arith(then, gvnv, lvnv).max(arith(els, gvnv, lvnv)),
+        _ => Arith::Signed,
+    }
+}
+
+// sizeof's operand is typed but never run. the typer sees the locals in
+// scope as the types they were allocated with
+pub fn type_of(e: &SExpr, gvnv: &Vnv, lvnv: &HashMap<String, i32>) -> Result<Type, io::Error> {
+    let types = gvnv.types.borrow();
+    let ltnv = lvnv
+        .iter()
+        .filter_map(|(alias, addr)| Some((alias.clone(), types.get(addr)?.clone())))
+        .collect::<HashMap<_, _>>();
+    let fnv = gvnv
+        .fnv
+        .iter()
+        .map(|(f, l)| {
+            let fp = l.fp.iter().map(|(_, t)| t.clone()).collect();
+            (
+                f.clone(),
+                LambdaType {
+                    fp,
+                    body: l.ret.clone(),
+                    variadic: false,
+                },
+            )
+        })
+        .collect();
+    let tnv = Tnv {
+        fnv,
+        vnv: HashMap::new(),
+        layouts: gvnv.layouts.clone(),
+        model: gvnv.model.clone(),
+        c0: false,
+    };
+    typer::type_expr(e, &tnv, &ltnv)
+}
+
+// floats are held as their ieee-754 bits
+// functions declared but not defined are provided by the interpreter, in
+// place of a libc: printf, with %d %i %u %x %c %s %f and %%, and c0's print.
+// both return what they write's length
+fn intrinsic(
+    alias: &str,
+    aps: &[Spanned<SExpr>],
+    gvnv: &Vnv,
+    lvnv: &mut HashMap<String, i32>,
+) -> Result<i32, io::Error> {
+    let aps = aps
+        .iter()
+        .map(|ap| Ok((arith(ap, gvnv, lvnv), eval_expr(ap, gvnv, lvnv)?)))
+        .collect::<Result<Vec<_>, io::Error>>()?;
+    libc(alias, &aps, gvnv)
+}
+
+// an intrinsic applied to its evaluated args
+fn libc(alias: &str, aps: &[(Arith, i32)], gvnv: &Vnv) -> Result<i32, io::Error> {
+    let str = |addr| -> Result<Vec<u8>, io::Error> {
+        (addr..)
+            .map(|a| load(gvnv, a).map(|c| c as u8))
+            .take_while(|c| !matches!(c, Ok(0)))
+            .collect()
+    };
+
+    let bytes = match (alias, aps) {
+        ("printf", [(_, fmt), args @ ..]) => {
+            let (fmt, mut args) = (str(*fmt)?, args.iter());
+            let mut arg = |conv| {
+                args.next().copied().ok_or(io::Error::other(format!(
+                    "too few arguments for %{conv} in printf"
+                )))
+            };
+
+            let (mut out, mut cs) = (vec![], fmt.iter());
+            while let Some(c) = cs.next() {
+                if *c != b'%' {
+                    out.push(*c);
+                    continue;
+                }
+                let conv = cs.find(|c| !matches!(c, b'l' | b'h')).copied(); // lengths are all a word
+                match conv.map(char::from) {
+                    Some('%') => out.push(b'%'),
+                    Some(conv @ ('d' | 'i')) => out.extend(arg(conv)?.1.to_string().bytes()),
+                    Some(conv @ 'u') => out.extend((arg(conv)?.1 as u32).to_string().bytes()),
+                    Some(conv @ 'x') => out.extend(format!("{:x}", arg(conv)?.1).bytes()),
+                    Some(conv @ 'c') => out.push(arg(conv)?.1 as u8),
+                    Some(conv @ 's') => out.extend(str(arg(conv)?.1)?),
+                    Some(conv @ 'f') => {
+                        let f = match arg(conv)? {
+                            (Arith::Float, val) => unfloat(val),
+                            (_, val) => val as f32,
+                        };
+                        out.extend(format!("{f:.6}").bytes())
+                    }
+                    Some(conv) => {
+                        return Err(io::Error::other(format!(
+                            "unsupported printf conversion %{conv}"
+                        )))
+                    }
+                    None => return Err(io::Error::other("incomplete printf conversion")),
+                }
+            }
+            out
+        }
+        ("print", [(_, s)]) => str(*s)?,
+        _ => return Err(io::Error::other("undefined function")),
+    };
+
+    gvnv.out.0.borrow_mut().write_all(&bytes)?;
+    Ok(bytes.len() as i32)
+}
+
+pub fn float(f: f32) -> i32 {
+    f.to_bits() as i32
+}
+
+fn unfloat(val: i32) -> f32 {
+    f32::from_bits(val as u32)
+}
+
+fn to_float(val: i32, from: Arith) -> f32 {
+    match from {
+        Arith::Signed => val as f32,
+        Arith::Unsigned => val as u32 as f32,
+        Arith::Float => unfloat(val),
+    }
+}
+
+// what a value becomes when stored in a cell. floats truncate toward zero
+fn convert(val: i32, from: Arith, to: Width) -> i32 {
+    match (from, to) {
+        (Arith::Float, Width::Float) => val,
+        (_, Width::Float) => float(to_float(val, from)),
+        (Arith::Float, _) => unfloat(val) as i64 as i32,
+        _ => val,
+    }
+}
+
+// what a cast makes of a value: a number converts as it would stored in a
+// cell of the type, and ints wrap to the int width besides. a bool is
+// whether it's nonzero. pointers and void keep the value as it is
+fn cast(val: i32, from: Arith, to: &Type, gvnv: &Vnv) -> Result<i32, io::Error> {
+    Ok(match to {
+        Type::Bool if from == Arith::Float => (unfloat(val) != 0.0) as i32,
+        Type::Bool => (val != 0) as i32,
+        Type::Int | Type::UInt => gvnv.model.wrap(convert(val, from, Width::Word) as i64),
+        t if typer::arith(t) => {
+            let w = width(gvnv, t)?;
+            narrow(convert(val, from, w), w)
+        }
+        _ => val,
+    })
+}
+
+#[cfg(test)]
+mod test_arith {
+    use crate::{lexer, parser_ast, Budget, DataModel, IntModel, Overflow, Sink};
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/arith";
+
+    #[test]
+    fn mult_overflow() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/mult_overflow.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 40000);
+    }
+
+    #[test]
+    fn mult_overflow_int16() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/mult_overflow.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let model = IntModel::new(16, DataModel::ILP32).unwrap();
+        let val = super::eval_prg(&tree, model).unwrap();
+        assert_eq!(val, -25536);
+    }
+
+    #[test]
+    fn mult_overflow_trap() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/mult_overflow.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let model = IntModel {
+            overflow: Overflow::Trap,
+            ..IntModel::new(16, DataModel::ILP32).unwrap()
+        };
+        let err = super::eval_prg(&tree, model).unwrap_err();
+        assert_eq!(err.to_string(), "signed integer overflow");
+        let span = crate::source::located(&err).unwrap().span;
+        assert_eq!(&src[span.offset(&src)..][..17], "return 200 * 200;");
+
+        // at 32 bits it fits
+        let model = IntModel {
+            overflow: Overflow::Trap,
+            ..IntModel::default()
+        };
+        assert_eq!(super::eval_prg(&tree, model).unwrap(), 40000);
+    }
+
+    #[test]
+    fn casts() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/casts.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 390);
+    }
+
+    #[test]
+    fn div_checks() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/div_zero.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let err = super::eval_prg(&tree, IntModel::default()).unwrap_err();
+        assert_eq!(err.to_string(), "division by zero");
+        let span = crate::source::located(&err).unwrap().span;
+        assert_eq!(&src[span.offset(&src)..][..13], "return a / b;");
+
+        let src = fs::read_to_string(format!("{TEST_DIR}/div_overflow.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let err = super::eval_prg(&tree, IntModel::default()).unwrap_err();
+        assert_eq!(err.to_string(), "integer division overflow");
+    }
+
+    #[test]
+    fn neg() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/neg.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 7);
+    }
+
+    #[test]
+    fn modulo() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/mod.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 6);
+    }
+
+    #[test]
+    fn bits() {
+        for (f, expected) in [("bits_precedence", 19), ("shr", -4), ("bitnot", 250)] {
+            let src = fs::read_to_string(format!("{TEST_DIR}/{f}.c")).expect("file dne");
+            let tokens = lexer::lex(&src).unwrap();
+            let tree = parser_ast::parse_prg(&tokens).unwrap();
+            let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+            assert_eq!(val, expected, "{f}");
+        }
+    }
+
+    #[test]
+    fn integers() {
+        for (f, expected) in [
+            ("integers", 40264),
+            ("unsigned", 17318416),
+            ("int_lits", 145),
+        ] {
+            let src = fs::read_to_string(format!("{TEST_DIR}/{f}.c")).expect("file dne");
+            let tokens = lexer::lex(&src).unwrap();
+            let tree = parser_ast::parse_prg(&tokens).unwrap();
+            let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+            assert_eq!(val, expected, "{f}");
+        }
+    }
+
+    #[test]
+    fn floats() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/floats.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 91);
+    }
+
+    #[test]
+    fn sizeof() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/sizeof.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 37);
+    }
+
+    // an lp64 long is 64 bits, which the interpreter's cells aren't, so it
+    // won't run one rather than wrap it at 32
+    #[test]
+    fn long_lp64() {
+        let src = "int main() { long x = 2147483647L; x = x + 1L; return x > 0L; }";
+        let tokens = lexer::lex(src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let model = IntModel::new(32, DataModel::LP64).unwrap();
+        for eval in [super::eval_prg_to, super::eval_prg_vm_to] {
+            let err = eval(&tree, model.clone(), Sink::default(), Budget::default()).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "lp64 is for compiled code, as the interpreter's longs are 32 bits"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_bindings {
+    use crate::{lexer, parser_ast, typer, Budget, IntModel, Sink};
+    use std::{cell::RefCell, fs, io, rc::Rc};
+
+    #[test]
+    fn dyn_scope() {
+        let src = fs::read_to_string("tests/fixtures/snap/lexical/dyn_scope.c").expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default());
+        assert!(matches!(
+            val,
+            Err(e) if e.kind() == io::ErrorKind::Other && e.to_string() == "f takes 0 arguments but 1 were given"
+        ));
+    }
+
+    #[test]
+    fn if_scope() {
+        let src = fs::read_to_string("tests/fixtures/snap/lexical/if_scope.c").expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default());
+        assert!(matches!(
+            val,
+            Err(e) if e.kind() == io::ErrorKind::Other && e.to_string() == "use of undeclared variable x in main"
+        ));
+    }
+
+    #[test]
+    fn asnmt_reassign() {
+        let src = fs::read_to_string("tests/fixtures/snap/shared/bindings/asnmt_reassign.c")
+            .expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 6);
+    }
+
+    #[test]
+    fn asnmt_compound() {
+        let src = fs::read_to_string("tests/fixtures/snap/shared/bindings/asnmt_compound.c")
+            .expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 3);
+    }
+
+    #[test]
+    fn asnmt_lval() {
+        // a[i--] *= 3 steps i once, as the walker and the vm both take the
+        // address once
+        let src = fs::read_to_string("tests/fixtures/snap/shared/bindings/asnmt_lval.c")
+            .expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 3989);
+        let ran = super::eval_prg_vm_to(
+            &tree,
+            IntModel::default(),
+            Sink::default(),
+            Budget::default(),
+        );
+        assert_eq!(ran.unwrap(), 3989);
+    }
+
+    #[test]
+    fn incdec_value() {
+        let src = fs::read_to_string("tests/fixtures/snap/shared/bindings/incdec_value.c")
+            .expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 5775);
+    }
+
+    #[test]
+    fn formal_param_multi() {
+        let src = fs::read_to_string("tests/fixtures/snap/shared/bindings/formal_param_multi.c")
+            .expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 19);
+    }
+
+    #[test]
+    fn static_scope() {
+        let src = fs::read_to_string("tests/fixtures/snap/shared/bindings/static_scope.c")
+            .expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 19);
+    }
+
+    #[test]
+    fn ptr() {
+        let src =
+            fs::read_to_string("tests/fixtures/snap/shared/bindings/ptr.c").expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 14);
+    }
+
+    #[test]
+    fn array() {
+        let src =
+            fs::read_to_string("tests/fixtures/snap/shared/bindings/array.c").expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 60);
+    }
+
+    #[test]
+    fn ptr_arith() {
+        let src = fs::read_to_string("tests/fixtures/snap/shared/bindings/ptr_arith.c")
+            .expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        typer::type_prg(&tree).unwrap();
+        let tree = typer::explicit(&tree);
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 24058);
+        let ran = super::eval_prg_vm_to(
+            &tree,
+            IntModel::default(),
+            Sink::default(),
+            Budget::default(),
+        );
+        assert_eq!(ran.unwrap(), 24058);
+    }
+
+    #[test]
+    fn structs() {
+        let src =
+            fs::read_to_string("tests/fixtures/snap/shared/bindings/structs.c").expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 37);
+    }
+
+    #[test]
+    fn members() {
+        // y is at a different offset in each struct
+        let src =
+            fs::read_to_string("tests/fixtures/snap/shared/bindings/members.c").expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        typer::type_prg(&tree).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 3621);
+        let ran = super::eval_prg_vm_to(
+            &tree,
+            IntModel::default(),
+            Sink::default(),
+            Budget::default(),
+        );
+        assert_eq!(ran.unwrap(), 3621);
+    }
+
+    #[test]
+    fn chars() {
+        let src =
+            fs::read_to_string("tests/fixtures/snap/shared/bindings/chars.c").expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 16);
+    }
+
+    #[test]
+    fn strs() {
+        let src =
+            fs::read_to_string("tests/fixtures/snap/shared/bindings/strs.c").expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 104);
+    }
+
+    #[test]
+    fn strs_write() {
+        let src = fs::read_to_string("tests/fixtures/snap/shared/bindings/strs_write.c")
+            .expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let err = super::eval_prg(&tree, IntModel::default()).unwrap_err();
+        assert_eq!(err.to_string(), "write to read-only memory");
+    }
+
+    #[test]
+    fn alloc() {
+        let src =
+            fs::read_to_string("tests/fixtures/snap/shared/bindings/alloc.c").expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 74); // 30, and 300 narrowed to a char
+    }
+
+    #[test]
+    fn heap_structs() {
+        // fields stored and loaded through p->f and (*p).f, on the heap
+        let src = fs::read_to_string("tests/fixtures/snap/shared/bindings/heap_structs.c")
+            .expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        typer::type_prg(&tree).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 80);
+        let ran = super::eval_prg_vm_to(
+            &tree,
+            IntModel::default(),
+            Sink::default(),
+            Budget::default(),
+        );
+        assert_eq!(ran.unwrap(), 80);
+
+        // a member past a null pointer isn't some other cell
+        let src = "struct node { int val; struct node *next; };
+            int main() { struct node *n = NULL; n->next = n; return 0; }";
+        let tokens = lexer::lex(src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let err = super::eval_prg(&tree, IntModel::default()).unwrap_err();
+        assert_eq!(err.to_string(), "null pointer dereference");
+        let ran = super::eval_prg_vm_to(
+            &tree,
+            IntModel::default(),
+            Sink::default(),
+            Budget::default(),
+        );
+        assert_eq!(ran.unwrap_err().to_string(), "null pointer dereference");
+    }
+
+    #[test]
+    fn alloc_bounds() {
+        let src = fs::read_to_string("tests/fixtures/snap/shared/bindings/alloc_bounds.c")
+            .expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let err = super::eval_prg(&tree, IntModel::default()).unwrap_err();
+        assert_eq!(err.to_string(), "index out of bounds: 4 of an array of 4");
+        let span = crate::source::located(&err).unwrap().span;
+        assert_eq!(&src[span.offset(&src)..][..9], "a[i] = i;");
+    }
+
+    #[test]
+    fn alloc_null() {
+        let src = fs::read_to_string("tests/fixtures/snap/shared/bindings/alloc_null.c")
+            .expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let err = super::eval_prg(&tree, IntModel::default()).unwrap_err();
+        assert_eq!(err.to_string(), "null pointer dereference");
+    }
+
+    #[test]
+    fn null_deref() {
+        let src = fs::read_to_string("tests/fixtures/snap/shared/bindings/null_deref.c")
+            .expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let err = super::eval_prg(&tree, IntModel::default()).unwrap_err();
+        assert_eq!(err.to_string(), "null pointer dereference");
+        let span = crate::source::located(&err).unwrap().span;
+        assert_eq!(&src[span.offset(&src)..][..10], "return *q;");
+        let ran = super::eval_prg_vm_to(
+            &tree,
+            IntModel::default(),
+            Sink::default(),
+            Budget::default(),
+        );
+        assert_eq!(ran.unwrap_err().to_string(), "null pointer dereference");
+    }
+
+    #[test]
+    fn uninit() {
+        let src =
+            fs::read_to_string("tests/fixtures/snap/shared/bindings/uninit.c").expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let err = super::eval_prg(&tree, IntModel::default()).unwrap_err();
+        assert_eq!(err.to_string(), "use of uninitialized variable x");
+        let span = crate::source::located(&err).unwrap().span;
+        assert_eq!(&src[span.offset(&src)..][..13], "return x + y;");
+    }
+
+    #[test]
+    fn typedefs() {
+        let src =
+            fs::read_to_string("tests/fixtures/snap/shared/bindings/typedefs.c").expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 108);
+    }
+
+    #[test]
+    fn enums() {
+        let src =
+            fs::read_to_string("tests/fixtures/snap/shared/bindings/enums.c").expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 65);
+    }
+
+    #[test]
+    fn unions() {
+        let src =
+            fs::read_to_string("tests/fixtures/snap/shared/bindings/unions.c").expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 327);
+    }
+
+    #[test]
+    fn union_pun() {
+        let src = fs::read_to_string("tests/fixtures/snap/shared/bindings/union_pun.c")
+            .expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 75454);
+        let ran = super::eval_prg_vm_to(
+            &tree,
+            IntModel::default(),
+            Sink::default(),
+            Budget::default(),
+        );
+        assert_eq!(ran.unwrap(), 75454);
+    }
+
+    #[test]
+    fn blocks() {
+        let src =
+            fs::read_to_string("tests/fixtures/snap/shared/bindings/blocks.c").expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 1127);
+    }
+
+    #[test]
+    fn undeclared() {
+        let src = fs::read_to_string("tests/fixtures/snap/shared/bindings/undeclared.c")
+            .expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let err = super::eval_prg(&tree, IntModel::default()).unwrap_err();
+        assert_eq!(err.to_string(), "use of undeclared variable y in main"); // though the loop never runs
+    }
+
+    #[test]
+    fn void() {
+        let src =
+            fs::read_to_string("tests/fixtures/snap/shared/bindings/void.c").expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 42);
+    }
+
+    #[test]
+    fn constant() {
+        let src =
+            fs::read_to_string("tests/fixtures/snap/shared/bindings/const.c").expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 35);
+    }
+
+    #[test]
+    fn statics() {
+        let src =
+            fs::read_to_string("tests/fixtures/snap/shared/bindings/static.c").expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 446);
+    }
+
+    #[test]
+    fn globals() {
+        let src =
+            fs::read_to_string("tests/fixtures/snap/shared/bindings/globals.c").expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 94);
+    }
+
+    #[test]
+    fn prototypes() {
+        let src = fs::read_to_string("tests/fixtures/snap/shared/bindings/prototypes.c")
+            .expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 14);
+    }
+
+    #[test]
+    fn printf() {
+        let src =
+            fs::read_to_string("tests/fixtures/snap/shared/bindings/printf.c").expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let out = Rc::new(RefCell::new(vec![]));
+        let val = super::eval_prg_to(
+            &tree,
+            IntModel::default(),
+            Sink(out.clone()),
+            Budget::default(),
+        )
+        .unwrap();
+        assert_eq!(val, 5);
+        assert_eq!(String::from_utf8_lossy(&out.borrow()), "42 x\ndone\n");
+    }
+
+    #[test]
+    fn identifiers() {
+        let src = fs::read_to_string("tests/fixtures/snap/shared/bindings/identifiers.c")
+            .expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 10);
+    }
+}
+
+#[cfg(test)]
+mod test_control {
+    use crate::{lexer, parser_ast, Budget, IntModel, Sink};
+    use std::{fs, thread};
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/control";
+
+    #[test]
+    fn while_false() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/while_false.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 10);
+    }
+
+    #[test]
+    fn while_update() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/while.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 0);
+    }
+
+    #[test]
+    fn while_lt() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/while_lt.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 3);
+    }
+
+    #[test]
+    fn for_loop() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/for.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 20);
+    }
+
+    #[test]
+    fn for_empty() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/for_empty.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 1303);
+    }
+
+    #[test]
+    fn while_and() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/while_and.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 2);
+    }
+
+    #[test]
+    fn and_short_circuit() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/and_short_circuit.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 2);
+    }
+
+    #[test]
+    fn logs() {
+        let cases = [
+            ("and_true.c", 1),
+            ("and_false.c", 0),
+            ("or_true.c", 1),
+            ("or_false.c", 0),
+            ("not.c", 1),
+        ];
+
+        for (fixture, expected) in cases {
+            let src = fs::read_to_string(format!("{TEST_DIR}/{fixture}")).expect("file dne");
+            let tokens = lexer::lex(&src).unwrap();
+            let tree = parser_ast::parse_prg(&tokens).unwrap();
+            let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+            assert_eq!(val, expected, "{fixture}");
+        }
+    }
+
+    #[test]
+    fn cmps() {
+        let cases = [
+            ("eq_true.c", 1),
+            ("eq_false.c", 0),
+            ("neq_true.c", 1),
+            ("neq_false.c", 0),
+            ("lt_true.c", 1),
+            ("lteq_true.c", 1),
+            ("lteq2_true.c", 1),
+            ("gt_true.c", 1),
+            ("gteq_true.c", 1),
+            ("gteq2_true.c", 1),
+            ("eq_lt_precedence.c", 1),
+        ];
+
+        for (fixture, expected) in cases {
+            let src = fs::read_to_string(format!("{TEST_DIR}/{fixture}")).expect("file dne");
+            let tokens = lexer::lex(&src).unwrap();
+            let tree = parser_ast::parse_prg(&tokens).unwrap();
+            let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+            assert_eq!(val, expected, "{fixture}");
+        }
+    }
+
+    #[test]
+    fn while_ret() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/while_ret.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 9);
+    }
+
+    #[test]
+    fn contracts() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/contracts.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        assert_eq!(super::eval_prg(&tree, IntModel::default()).unwrap(), 36);
+        let ran = super::eval_prg_vm_to(
+            &tree,
+            IntModel::default(),
+            Sink::default(),
+            Budget::default(),
+        );
+        assert_eq!(ran.unwrap(), 36);
+    }
+
+    // each violation is located at its contract's expression. without
+    // --dyn-check the contracts are erased, and the programs run on
+    #[test]
+    fn contract_violations() {
+        let cases = [
+            (
+                "contracts_requires.c",
+                "@requires annotation failed",
+                "n >= 0",
+                0,
+            ),
+            (
+                "contracts_ensures.c",
+                "@ensures annotation failed",
+                "\\result > n",
+                5,
+            ),
+            (
+                "contracts_invariant.c",
+                "@loop_invariant annotation failed",
+                "i < 5",
+                10,
+            ),
+        ];
+
+        for (fixture, msg, contract, erased) in cases {
+            let src = fs::read_to_string(format!("{TEST_DIR}/{fixture}")).expect("file dne");
+            let tokens = lexer::lex(&src).unwrap();
+            let tree = parser_ast::parse_prg(&tokens).unwrap();
+            for eval in [super::eval_prg_to, super::eval_prg_vm_to] {
+                let err = eval(
+                    &tree,
+                    IntModel::default(),
+                    Sink::default(),
+                    Budget::default(),
+                )
+                .unwrap_err();
+                assert_eq!(err.to_string(), msg, "{fixture}");
+                let span = crate::source::located(&err).unwrap().span;
+                assert_eq!(&src[span.offset(&src)..][..contract.len()], contract);
+            }
+            let tree = super::erase_contracts(&tree);
+            let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+            assert_eq!(val, erased, "{fixture}");
+        }
+    }
+
+    // a loop that never ends, or allocates without end, is cut off at its
+    // budget, by the walker and the vm alike
+    #[test]
+    fn limits() {
+        let cases = [
+            (
+                "int main() { while (1) {} return 0; }",
+                Budget {
+                    steps: Some(1000),
+                    ..Budget::default()
+                },
+                "resource limit exceeded: more than 1000 steps",
+            ),
+            (
+                "int main() { while (1) { int *p = alloc(int); } return 0; }",
+                Budget {
+                    heap: Some(64),
+                    ..Budget::default()
+                },
+                "resource limit exceeded: more than 64 bytes of heap",
+            ),
+            (
+                "int f() { return f(); } int main() { return f(); }",
+                Budget {
+                    depth: Some(100),
+                    ..Budget::default()
+                },
+                "resource limit exceeded: more than 100 calls deep",
+            ),
+        ];
+
+        for (src, budget, msg) in cases {
+            let tokens = lexer::lex(src).unwrap();
+            let tree = parser_ast::parse_prg(&tokens).unwrap();
+            for eval in [super::eval_prg_to, super::eval_prg_vm_to] {
+                let err = eval(&tree, IntModel::default(), Sink::default(), budget).unwrap_err();
+                assert_eq!(err.to_string(), msg);
+            }
+        }
+
+        // within its budget, a program runs as it would without one
+        let src = fs::read_to_string(format!("{TEST_DIR}/while.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let budget = Budget {
+            steps: Some(1000),
+            heap: Some(0),
+            ..Budget::default()
+        };
+        let val = super::eval_prg_to(&tree, IntModel::default(), Sink::default(), budget);
+        assert_eq!(val.unwrap(), 0);
+    }
+
+    // recursion without end is cut off at the default depth, before it
+    // overflows a stack the size the cli runs the walker on
+    #[test]
+    fn recursion() {
+        let walk = || {
+            let src = "int f() { return f(); } int main() { return f(); }";
+            let tokens = lexer::lex(src).unwrap();
+            let tree = parser_ast::parse_prg(&tokens).unwrap();
+            super::eval_prg(&tree, IntModel::default()).map_err(|e| e.to_string())
+        };
+        let walker = thread::Builder::new()
+            .stack_size(super::STACK)
+            .spawn(walk)
+            .unwrap();
+        let err = walker.join().unwrap().unwrap_err();
+        assert_eq!(
+            err,
+            format!(
+                "resource limit exceeded: more than {} calls deep",
+                super::DEPTH
+            )
+        );
+    }
+
+    #[test]
+    fn cond() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/cond.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 7);
+    }
+
+    #[test]
+    fn switch_dense() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/switch_dense.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 90);
+    }
+
+    #[test]
+    fn switch_sparse() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/switch_sparse.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 1100);
+    }
+
+    #[test]
+    fn break_continue() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/break_continue.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 61908);
+    }
+
+    #[test]
+    fn goto() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/goto.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let err = super::eval_prg(&tree, IntModel::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "goto top: the c0 interpreter doesn't support goto, compile with compilec89"
+        );
+    }
+
+    #[test]
+    fn do_while() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/do_while.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let val = super::eval_prg(&tree, IntModel::default()).unwrap();
+        assert_eq!(val, 125);
+    }
+}
+
+#[cfg(test)]
+mod test_vm {
+    use crate::{bytecode, lexer, parser_ast, Budget, IntModel, Sink};
+    use std::{cell::RefCell, fs, rc::Rc};
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared";
+
+    #[test]
+    fn while_lt() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/control/while_lt.c")).expect("file dne");
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let nv = super::env(
+            &tree,
+            IntModel::default(),
+            Sink::default(),
+            Budget::default(),
+        )
+        .unwrap();
+        let prg = bytecode::compile(&nv).unwrap();
+        let ops = prg.funcs[prg.main].ops.iter().map(|op| format!("{op:?}"));
+        insta::assert_yaml_snapshot!(ops.collect::<Vec<_>>(), @r###"
+        ---
+        - Push(0)
+        - "Zero(1, Int)"
+        - Local(1)
+        - "Store(Signed, None)"
+        - Local(1)
+        - Load
+        - Push(3)
+        - "Rel(Lt, Signed, Signed)"
+        - "JumpIf(false, 16)"
+        - Local(1)
+        - Load
+        - Push(1)
+        - "Bin(Add, Signed, Signed)"
+        - Local(1)
+        - "Store(Signed, None)"
+        - Jump(4)
+        - Local(1)
+        - Load
+        - Local(0)
+        - "Store(Signed, None)"
+        - Local(0)
+        - Load
+        - Ret
+        - "Fail(\"no return stmt\")"
+        "###);
+    }
+
+    // the vm runs every program the tree walker does to the same end, but
+    // for those with gotos, which only the vm runs
+    #[test]
+    fn tree_walker() {
+        let mut fixtures = ["arith", "bindings", "control"]
+            .iter()
+            .flat_map(|dir| fs::read_dir(format!("{TEST_DIR}/{dir}")).unwrap())
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "c"))
+            .filter(|path| !path.to_string_lossy().contains("goto"))
+            .collect::<Vec<_>>();
+        fixtures.sort();
+
+        for fixture in fixtures {
+            let src = fs::read_to_string(&fixture).expect("file dne");
+            let Ok(tokens) = lexer::lex(&src) else {
+                continue;
+            };
+            let Ok(tree) = parser_ast::parse_prg(&tokens) else {
+                continue;
+            };
+            let [walked, ran] = [super::eval_prg_to, super::eval_prg_vm_to].map(|eval| {
+                let out = Rc::new(RefCell::new(vec![]));
+                let exit = eval(
+                    &tree,
+                    IntModel::default(),
+                    Sink(out.clone()),
+                    Budget::default(),
+                );
+                let out = String::from_utf8_lossy(&out.borrow()).into_owned();
+                (exit.map_err(|e| e.to_string()), out)
+            });
+            assert_eq!(walked, ran, "{}", fixture.display());
+        }
     }
 }
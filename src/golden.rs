@@ -0,0 +1,233 @@
+use crate::{
+    diagnostics::{Diagnostic, Phase},
+    engine::{self, Limits},
+    evaluator, lexer, parser_ast, preprocessor,
+    source::SourceMap,
+    typecheck, IntModel, OptLevel,
+};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// a corpus of programs beside what picoc089 should say about them, so a
+// test is two files dropped in:
+//   - valid/f.c      runs, and f.expected is its stdout then the
+//                    `picoc-info: evaluated: n` line
+//   - invalid/f.c    is rejected, and f.expected is its diagnostics
+// both are what `picoc089 <strat> -q f.c` prints from the crate root, so
+// that's how an .expected is made. each case runs under every strategy,
+// and f.<strat>.expected is what one strategy says where it differs
+pub const STRATEGIES: [&str; 2] = ["interpretc0", "runc89"];
+
+// one case's .c, in the corpus's valid/ or invalid/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Case {
+    pub path: PathBuf,
+    pub valid: bool,
+}
+
+// a case a strategy got wrong, at the first line that differs
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mismatch {
+    pub path: PathBuf,
+    pub strat: &'static str,
+    pub line: usize,
+    pub expected: String,
+    pub found: String,
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} under {}: line {}: expected {:?}, found {:?}",
+            self.path.display(),
+            self.strat,
+            self.line,
+            self.expected,
+            self.found
+        )
+    }
+}
+
+// the cases under root, in order
+pub fn discover(root: &Path) -> Result<Vec<Case>, io::Error> {
+    let mut cases = vec![];
+    for (dir, valid) in [("valid", true), ("invalid", false)] {
+        let dir = root.join(dir);
+        if !dir.is_dir() {
+            continue;
+        }
+        let mut paths = fs::read_dir(&dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.retain(|p| p.extension().is_some_and(|ext| ext == "c"));
+        paths.sort();
+        cases.extend(paths.into_iter().map(|path| Case { path, valid }));
+    }
+    Ok(cases)
+}
+
+// every case under root under every strategy, with what each got wrong.
+// a strategy that can't run here, runc89 without its toolchain, is
+// skipped and said so in the notes
+pub fn check(root: &Path) -> Result<(Vec<Mismatch>, Vec<String>), io::Error> {
+    let (mut mismatches, mut notes) = (vec![], vec![]);
+    for case in discover(root)? {
+        for strat in STRATEGIES {
+            let expected = expected(&case, strat)?;
+            let found = match output(&case.path, strat) {
+                Ok(found) => found,
+                Err(e) if e.to_string().starts_with("unable to run") => {
+                    notes.push(format!("skipped {strat}: {e}"));
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            mismatches.extend(compare(&case.path, strat, &expected, &found));
+        }
+    }
+    notes.dedup();
+    Ok((mismatches, notes))
+}
+
+// f.<strat>.expected as it is, else f.expected, where a valid case ends
+// with its result and an invalid one says why it's rejected
+fn expected(case: &Case, strat: &str) -> Result<String, io::Error> {
+    let own = case.path.with_extension(format!("{strat}.expected"));
+    if own.exists() {
+        return fs::read_to_string(own);
+    }
+    let path = case.path.with_extension("expected");
+    let expected = fs::read_to_string(&path)
+        .map_err(|e| io::Error::other(format!("{}: {e}", path.display())))?;
+    let evaluated = expected
+        .lines()
+        .last()
+        .is_some_and(|l| l.starts_with("picoc-info: evaluated: "));
+    match (case.valid, evaluated, expected.contains("picoc-error")) {
+        (true, true, false) | (false, false, true) => Ok(expected),
+        (true, ..) => Err(io::Error::other(format!(
+            "{}: a valid case's output ends with what it evaluated to",
+            path.display()
+        ))),
+        (false, ..) => Err(io::Error::other(format!(
+            "{}: an invalid case's output is its errors",
+            path.display()
+        ))),
+    }
+}
+
+fn compare(path: &Path, strat: &'static str, expected: &str, found: &str) -> Option<Mismatch> {
+    let (expected, found) = (expected.trim_end(), found.trim_end());
+    let (mut e, mut f) = (expected.lines(), found.lines());
+    for line in 1.. {
+        match (e.next(), f.next()) {
+            (None, None) => return None,
+            (e, f) if e == f => continue,
+            (e, f) => {
+                return Some(Mismatch {
+                    path: path.to_owned(),
+                    strat,
+                    line,
+                    expected: e.unwrap_or_default().to_owned(),
+                    found: f.unwrap_or_default().to_owned(),
+                })
+            }
+        }
+    }
+    unreachable!("the lines run out")
+}
+
+// what `picoc089 <strat> -q path` prints, without stdin. the only error is
+// one running it at all
+pub fn output(path: &Path, strat: &str) -> Result<String, io::Error> {
+    let src = path.display().to_string();
+    let text = fs::read_to_string(path)?;
+    let unlocated = |phase, src: &str, e: &io::Error| {
+        let d = Diagnostic::error(phase, e);
+        d.render(src, "", &SourceMap::new(src), 0) + "\n"
+    };
+    let (text, map) = match preprocessor::preprocess(&text, path, &Default::default()) {
+        Ok(out) => out,
+        Err(e) => return Ok(unlocated(Phase::Preprocess, "", &e)),
+    };
+    let report = |phase, e: &io::Error| Diagnostic::error(phase, e).render(&src, &text, &map, 8);
+
+    let tokens = match lexer::lex(&text) {
+        Ok(tokens) => tokens,
+        Err(errs) => {
+            let errs = errs
+                .iter()
+                .map(|e| Diagnostic::lex(e).render(&src, &text, &map, 8));
+            return Ok(errs.map(|e| e + "\n").collect());
+        }
+    };
+    let (prg, errs) = parser_ast::parse(&tokens);
+    if !errs.is_empty() {
+        let errs = errs.into_iter().map(|e| report(Phase::Parse, &e.into()));
+        return Ok(errs.map(|e| e + "\n").collect());
+    }
+    if let Err(e) = typecheck::check(&prg) {
+        return Ok(report(Phase::Check, &e) + "\n");
+    }
+    if strat != "interpretc0" {
+        if let Err(e) = engine::unsupported(&prg) {
+            return Ok(report(Phase::Compile, &e) + "\n");
+        }
+    }
+
+    let prg = evaluator::erase_contracts(&prg);
+    let exec = engine::engine(strat, OptLevel::O0, IntModel::default(), false)
+        .ok_or_else(|| io::Error::other(format!("unknown strategy: {strat:?}")))?;
+    let limits = Limits {
+        timeout: Some(Duration::from_secs(10)),
+        ..Limits::default()
+    };
+    match exec.run(&prg, "", &limits) {
+        Ok(out) => Ok(format!(
+            "{}picoc-info: evaluated: {}\n",
+            out.stdout, out.exit
+        )),
+        Err(e) if e.to_string().starts_with("unable to run") => Err(e),
+        Err(e) => Ok(report(Phase::Run, &e) + "\n"),
+    }
+}
+
+#[cfg(test)]
+mod test_golden {
+    use super::*;
+
+    #[test]
+    fn corpus() {
+        let (mismatches, notes) = check(Path::new("tests")).unwrap();
+        for note in notes {
+            println!("{note}");
+        }
+        let mismatches = mismatches.iter().map(|m| m.to_string()).collect::<Vec<_>>();
+        assert!(mismatches.is_empty(), "\n{}", mismatches.join("\n"));
+    }
+
+    #[test]
+    fn cases() {
+        let cases = discover(Path::new("tests")).unwrap();
+        assert!(cases.iter().any(|c| c.valid) && cases.iter().any(|c| !c.valid));
+        let case = |name: &str| cases.iter().find(|c| c.path.ends_with(name)).unwrap();
+        assert!(case("valid/hello.c").valid);
+        assert!(!case("invalid/undeclared.c").valid);
+
+        let found = "picoc-info: evaluated: 3\n";
+        let m = compare(
+            Path::new("f.c"),
+            "interpretc0",
+            "picoc-info: evaluated: 2\n",
+            found,
+        );
+        assert_eq!(
+            m.unwrap().to_string(),
+            "f.c under interpretc0: line 1: expected \"picoc-info: evaluated: 2\", found \"picoc-info: evaluated: 3\""
+        );
+        assert_eq!(compare(Path::new("f.c"), "interpretc0", found, found), None);
+    }
+}
@@ -0,0 +1,302 @@
+use crate::{
+    selector, IPrg, Imm, IntModel, Label, Levels, OptLevel, PseudoOp, RiscvPointerReg, TImmOp,
+    TQuad, Temp,
+};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+// the selected 3ac as a control flow graph, which is what the backend's
+// passes run over before allocation. the quads stay in program order, cut
+// into basic blocks: a block starts at a label, and ends at a jump, branch,
+// jump table or return, so control only enters at its top and leaves at
+// its bottom. temps are virtual registers, as many as the selector makes.
+// a machine temp is assigned once, but for the value a ?: or && and ||
+// leaves, which both arms assign and a phi would join in ssa. locals aren't
+// temps at all, but frame slots loaded and stored around each use, so the
+// graph is in ssa for everything but those joins
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Cfg {
+    pub blocks: Vec<Block>,
+}
+
+// a block's quads, with its label first if it has one, and the blocks
+// control can go to next. statics' data is a block of its own that nothing
+// reaches
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Block {
+    pub quads: Vec<TQuad>,
+    pub succs: Vec<usize>,
+}
+
+impl Block {
+    pub fn label(&self) -> Option<&Label> {
+        match self.quads.first() {
+            Some(TQuad::Label(l)) => Some(l),
+            _ => None,
+        }
+    }
+
+    // whether control can run off the bottom into the next block
+    fn falls_through(&self) -> bool {
+        !matches!(
+            self.quads.last(),
+            Some(
+                TQuad::Pseudo(PseudoOp::Jump(_) | PseudoOp::Ret)
+                    | TQuad::JumpTable(..)
+                    | TQuad::Data(..)
+            )
+        )
+    }
+}
+
+// the translated program, selected and cut into blocks. linked are the
+// functions other units define, as select_linked takes them
+pub fn lower(prg: &IPrg, linked: &HashSet<&str>, model: &IntModel) -> Cfg {
+    build(selector::select_linked(prg, linked, model))
+}
+
+// the quads cut into runs of functions compiled at the same level, which is
+// a function's own in levels, or opt. statics go with the run before them
+pub fn runs(abs_as: &[TQuad], opt: &OptLevel, levels: &Levels) -> Vec<(OptLevel, Vec<TQuad>)> {
+    let mut runs: Vec<(OptLevel, Vec<TQuad>)> = vec![];
+    for quad in abs_as {
+        match (quad, runs.last_mut()) {
+            (TQuad::Label(Label::UserLabel(f)), Some((run, quads)))
+                if levels.get(f).unwrap_or(opt) == run =>
+            {
+                quads.push(quad.clone())
+            }
+            (TQuad::Label(Label::UserLabel(f)), _) => {
+                runs.push((levels.get(f).unwrap_or(opt).clone(), vec![quad.clone()]))
+            }
+            (_, Some((_, quads))) => quads.push(quad.clone()),
+            (_, None) => runs.push((opt.clone(), vec![quad.clone()])),
+        }
+    }
+    runs
+}
+
+// the quads cut at each function's label, and at each static's data
+pub fn segments(abs_as: &[TQuad]) -> Vec<&[TQuad]> {
+    let mut starts = abs_as
+        .iter()
+        .enumerate()
+        .filter(|(_, q)| matches!(q, TQuad::Label(Label::UserLabel(_)) | TQuad::Data(..)))
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    if starts.first() != Some(&0) {
+        starts.insert(0, 0);
+    }
+    starts.push(abs_as.len());
+    starts
+        .windows(2)
+        .map(|w| &abs_as[w[0]..w[1]])
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+// a function's quads with their machine temps and labels numbered from 0,
+// in the order they first appear, so they're the same whatever comes
+// before the function in the program
+pub fn local(quads: &[TQuad]) -> Vec<TQuad> {
+    let (mut temps, mut labels) = (HashMap::new(), HashMap::new());
+    let mut t = |t: &Temp| match t {
+        Temp::MachineTemp(n) => {
+            let next = temps.len();
+            Temp::MachineTemp(*temps.entry(*n).or_insert(next))
+        }
+        t => t.clone(),
+    };
+    let mut l = |l: &Label| match l {
+        Label::MachineLabel(n) => {
+            let next = labels.len();
+            Label::MachineLabel(*labels.entry(*n).or_insert(next))
+        }
+        l => l.clone(),
+    };
+    quads
+        .iter()
+        .map(|quad| match quad {
+            TQuad::Reg(op, d, a, b) => TQuad::Reg(op.clone(), t(d), t(a), t(b)),
+            TQuad::Imm(op, d, a, k) => TQuad::Imm(op.clone(), t(d), t(a), *k),
+            TQuad::Mem(op, d, off, base) => TQuad::Mem(op.clone(), t(d), *off, base.clone()),
+            TQuad::Deref(op, d, a) => TQuad::Deref(op.clone(), t(d), t(a)),
+            TQuad::Str(d, s) => TQuad::Str(t(d), s.clone()),
+            TQuad::Static(d, alias) => TQuad::Static(t(d), alias.clone()),
+            TQuad::Branch(op, a, b, to) => TQuad::Branch(op.clone(), t(a), t(b), l(to)),
+            TQuad::Float(op, d, a, b) => TQuad::Float(op.clone(), t(d), t(a), t(b)),
+            TQuad::Cvt(op, d, a) => TQuad::Cvt(op.clone(), t(d), t(a)),
+            TQuad::JumpTable(a, table, ls) => {
+                TQuad::JumpTable(t(a), l(table), ls.iter().map(&mut l).collect())
+            }
+            TQuad::Pseudo(PseudoOp::Jump(to)) => TQuad::Pseudo(PseudoOp::Jump(l(to))),
+            TQuad::Label(at) => TQuad::Label(l(at)),
+            quad => quad.clone(),
+        })
+        .collect()
+}
+
+pub fn build(abs_as: Vec<TQuad>) -> Cfg {
+    let mut blocks: Vec<Block> = vec![];
+    let mut quads = vec![];
+    for quad in abs_as {
+        let leader = matches!(quad, TQuad::Label(_) | TQuad::Data(..));
+        if leader && !quads.is_empty() {
+            blocks.push(Block {
+                quads: std::mem::take(&mut quads),
+                succs: vec![],
+            });
+        }
+        let ends = matches!(
+            quad,
+            TQuad::Pseudo(PseudoOp::Jump(_) | PseudoOp::Ret)
+                | TQuad::Branch(..)
+                | TQuad::JumpTable(..)
+                | TQuad::Data(..)
+        );
+        quads.push(quad);
+        if ends {
+            blocks.push(Block {
+                quads: std::mem::take(&mut quads),
+                succs: vec![],
+            });
+        }
+    }
+    if !quads.is_empty() {
+        blocks.push(Block {
+            quads,
+            succs: vec![],
+        });
+    }
+
+    let find = |l: &Label| blocks.iter().position(|b| b.label() == Some(l));
+    let succs = blocks
+        .iter()
+        .enumerate()
+        .map(|(i, b)| {
+            let jumps = match b.quads.last() {
+                Some(TQuad::Pseudo(PseudoOp::Jump(l)) | TQuad::Branch(_, _, _, l)) => vec![l],
+                Some(TQuad::JumpTable(_, _, ls)) => ls.iter().collect(),
+                _ => vec![],
+            };
+            let next = (b.falls_through() && i + 1 < blocks.len()).then_some(i + 1);
+            let mut succs = next.into_iter().collect::<Vec<_>>();
+            for j in jumps.into_iter().filter_map(find) {
+                if !succs.contains(&j) {
+                    succs.push(j);
+                }
+            }
+            succs
+        })
+        .collect::<Vec<_>>();
+    for (b, succs) in blocks.iter_mut().zip(succs) {
+        b.succs = succs;
+    }
+
+    Cfg { blocks }
+}
+
+impl Cfg {
+    // the quads back in program order, for allocation
+    pub fn quads(&self) -> Vec<TQuad> {
+        self.blocks.iter().flat_map(|b| b.quads.clone()).collect()
+    }
+
+    // each block's predecessors, the reverse of succs
+    pub fn preds(&self) -> Vec<Vec<usize>> {
+        let mut preds = vec![vec![]; self.blocks.len()];
+        for (i, b) in self.blocks.iter().enumerate() {
+            for &s in &b.succs {
+                preds[s].push(i);
+            }
+        }
+        preds
+    }
+}
+
+// a function's frame size, from the addi to sp its prologue starts with
+pub fn frame_size(entry: &[TQuad]) -> Option<Imm> {
+    match entry.get(1) {
+        Some(TQuad::Imm(
+            TImmOp::AddI,
+            Temp::PointerReg(RiscvPointerReg::Sp),
+            Temp::PointerReg(RiscvPointerReg::Sp),
+            size,
+        )) => Some(-size),
+        _ => None,
+    }
+}
+
+// the function with its frame grown from size to grown bytes. ra and fp
+// are saved at the top of the frame, so they move with it
+pub fn grow(func: Vec<TQuad>, size: Imm, grown: Imm) -> Vec<TQuad> {
+    let sp = Temp::PointerReg(RiscvPointerReg::Sp);
+    func.into_iter()
+        .map(|quad| match quad {
+            TQuad::Imm(TImmOp::AddI, d, l, k) if l == sp && k.abs() == size => {
+                TQuad::Imm(TImmOp::AddI, d, l, k.signum() * grown)
+            }
+            TQuad::Mem(op, t @ Temp::PointerReg(_), off, RiscvPointerReg::Sp) => {
+                TQuad::Mem(op, t, off - size + grown, RiscvPointerReg::Sp)
+            }
+            quad => quad,
+        })
+        .collect()
+}
+
+// a block to a line, with its quads under it, for reading a pass's work
+impl fmt::Display for Cfg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, b) in self.blocks.iter().enumerate() {
+            writeln!(f, "b{i} -> {:?}", b.succs)?;
+            for quad in &b.quads {
+                writeln!(f, "    {quad:?}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_ir {
+    use crate::{lexer, parser_ast, selector, translator, TQuad};
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/control";
+
+    #[test]
+    fn blocks() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/while_false.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let quads = selector::select(&trgt_tree);
+        let cfg = super::build(quads.clone());
+        let shape = cfg
+            .blocks
+            .iter()
+            .map(|b| {
+                let label = b.label().map_or("-".to_owned(), |l| l.to_string());
+                format!("{label} {} {:?}", b.quads.len(), b.succs)
+            })
+            .collect::<Vec<_>>();
+        insta::assert_debug_snapshot!(shape, @r###"
+        [
+            "main 5 [1]",
+            ".L0 3 [2, 5]",
+            "- 1 [3]",
+            ".L1 4 [6]",
+            "- 1 [1]",
+            ".L2 4 [6]",
+            ".L3 5 []",
+        ]
+        "###);
+        assert_eq!(cfg.preds()[0], [] as [usize; 0]);
+
+        // cutting into blocks leaves the quads as they were
+        assert_eq!(cfg.quads(), quads);
+        assert!(matches!(cfg.blocks[0].quads[0], TQuad::Label(_)));
+    }
+}
@@ -0,0 +1,491 @@
+use serde::ser::{self, Serialize};
+use std::fmt::Display;
+
+// pretty printed json for anything that's Serialize: the tokens and trees
+// --emit dumps for other tools. enums are externally tagged, as in
+// {"Return": ...}, and unit variants are just their names
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    let mut ser = Serializer {
+        out: String::new(),
+        depth: 0,
+    };
+    value.serialize(&mut ser)?;
+    Ok(ser.out)
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct Error(String);
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+pub struct Serializer {
+    out: String,
+    depth: usize,
+}
+
+impl Serializer {
+    fn newline(&mut self) {
+        self.out.push('\n');
+        self.out.push_str(&"  ".repeat(self.depth));
+    }
+
+    fn str(&mut self, s: &str) {
+        self.out.push('"');
+        for c in s.chars() {
+            match c {
+                '"' => self.out.push_str("\\\""),
+                '\\' => self.out.push_str("\\\\"),
+                '\n' => self.out.push_str("\\n"),
+                '\r' => self.out.push_str("\\r"),
+                '\t' => self.out.push_str("\\t"),
+                c if (c as u32) < 0x20 => self.out.push_str(&format!("\\u{:04x}", c as u32)),
+                c => self.out.push(c),
+            }
+        }
+        self.out.push('"');
+    }
+
+    fn open(&mut self, open: char, close: char, tagged: bool) -> Compound<'_> {
+        self.out.push(open);
+        self.depth += 1;
+        Compound {
+            ser: self,
+            close,
+            tagged,
+            empty: true,
+        }
+    }
+
+    // {"variant": then whatever the variant holds
+    fn tag(&mut self, variant: &str) {
+        self.out.push('{');
+        self.depth += 1;
+        self.newline();
+        self.str(variant);
+        self.out.push_str(": ");
+    }
+
+    fn untag(&mut self) {
+        self.depth -= 1;
+        self.newline();
+        self.out.push('}');
+    }
+}
+
+// a seq or map being written. tagged is whether it's a variant's, inside
+// a {"variant": ...} of its own
+pub struct Compound<'a> {
+    ser: &'a mut Serializer,
+    close: char,
+    tagged: bool,
+    empty: bool,
+}
+
+impl Compound<'_> {
+    fn next(&mut self) {
+        if !self.empty {
+            self.ser.out.push(',');
+        }
+        self.empty = false;
+        self.ser.newline();
+    }
+
+    fn key(&mut self, key: &str) {
+        self.next();
+        self.ser.str(key);
+        self.ser.out.push_str(": ");
+    }
+
+    fn close(self) -> Result<(), Error> {
+        self.ser.depth -= 1;
+        if !self.empty {
+            self.ser.newline();
+        }
+        self.ser.out.push(self.close);
+        if self.tagged {
+            self.ser.untag();
+        }
+        Ok(())
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut Serializer {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.out.push_str(if v { "true" } else { "false" });
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        self.out.push_str(&v.to_string());
+        Ok(())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), Error> {
+        self.out.push_str(&v.to_string());
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        self.out.push_str(&v.to_string());
+        Ok(())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), Error> {
+        self.out.push_str(&v.to_string());
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        self.serialize_f64(v.into())
+    }
+
+    // json has no infinities or nans
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        match v.is_finite() {
+            true => self.out.push_str(&v.to_string()),
+            false => self.out.push_str("null"),
+        }
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.str(&v.to_string());
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.str(v);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        v.serialize(self)
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        self.out.push_str("null");
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.str(variant);
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.tag(variant);
+        value.serialize(&mut *self)?;
+        self.untag();
+        Ok(())
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Compound<'a>, Error> {
+        Ok(self.open('[', ']', false))
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Compound<'a>, Error> {
+        Ok(self.open('[', ']', false))
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Compound<'a>, Error> {
+        Ok(self.open('[', ']', false))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Compound<'a>, Error> {
+        self.tag(variant);
+        Ok(self.open('[', ']', true))
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Compound<'a>, Error> {
+        Ok(self.open('{', '}', false))
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Compound<'a>, Error> {
+        Ok(self.open('{', '}', false))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Compound<'a>, Error> {
+        self.tag(variant);
+        Ok(self.open('{', '}', true))
+    }
+}
+
+impl ser::SerializeSeq for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.next();
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close()
+    }
+}
+
+impl ser::SerializeTuple for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close()
+    }
+}
+
+impl ser::SerializeTupleStruct for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close()
+    }
+}
+
+impl ser::SerializeTupleVariant for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close()
+    }
+}
+
+// keys are written as json strings, whatever they serialize as
+impl ser::SerializeMap for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        let key = to_string(key)?;
+        self.next();
+        match key.starts_with('"') {
+            true => self.ser.out.push_str(&key),
+            false => self.ser.str(&key),
+        }
+        self.ser.out.push_str(": ");
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close()
+    }
+}
+
+impl ser::SerializeStruct for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.key(key);
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close()
+    }
+}
+
+impl ser::SerializeStructVariant for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close()
+    }
+}
+
+#[cfg(test)]
+mod test_json {
+    use crate::{lexer, parser_ast};
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    enum E {
+        Unit,
+        Tuple(i32, bool),
+        Struct { s: String },
+    }
+
+    #[test]
+    fn values() {
+        #[rustfmt::skip]
+        let value = (
+            "a \"quoted\"\n\tstring\u{1}",
+            [E::Unit, E::Tuple(-1, true), E::Struct { s: "x".to_owned() }],
+            (None::<i32>, Some(1.5), f64::NAN),
+            (Vec::<i32>::new(), BTreeMap::from([(1, "one")]), BTreeMap::<i32, i32>::new()),
+        );
+        insta::assert_snapshot!(super::to_string(&value).unwrap(), @r###"
+        [
+          "a \"quoted\"\n\tstring\u0001",
+          [
+            "Unit",
+            {
+              "Tuple": [
+                -1,
+                true
+              ]
+            },
+            {
+              "Struct": {
+                "s": "x"
+              }
+            }
+          ],
+          [
+            null,
+            1.5,
+            null
+          ],
+          [
+            [],
+            {
+              "1": "one"
+            },
+            {}
+          ]
+        ]
+        "###);
+    }
+
+    #[test]
+    fn tree() {
+        let tokens = lexer::lex("int main() { return -x; }").unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        insta::assert_snapshot!(super::to_string(&tree).unwrap(), @r###"
+        [
+          {
+            "FuncDef": {
+              "alias": "main",
+              "typ": "Int",
+              "fps": [],
+              "body": [
+                {
+                  "Return": {
+                    "UnaryE": {
+                      "op": "Sub",
+                      "l": {
+                        "VarApp": "x"
+                      }
+                    }
+                  }
+                }
+              ]
+            }
+          }
+        ]
+        "###);
+    }
+}
@@ -0,0 +1,385 @@
+use crate::{IntModel, SDef, SPrg, SStructDef, Type, Width};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+use std::iter;
+
+// struct layouts, in the evaluators' cells. every scalar takes a cell like
+// locals do, and fields are laid out in declaration order.
+// each struct has its own member names, so p.x is resolved by p's type
+// unions are laid out here too: every member at offset 0, as big as the
+// biggest of them. the compiler lays them out in bytes instead, see bytes
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StructLayout {
+    pub fields: Vec<(String, Type, usize)>, // name, type and offset
+    pub words: usize,
+    pub union: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Layouts {
+    structs: HashMap<String, StructLayout>,
+}
+
+pub fn layouts(prg: &SPrg) -> Result<Layouts, io::Error> {
+    let mut layouts = Layouts::default();
+
+    // structs are defined before they're used, so nested ones are known
+    for def in prg {
+        match &def.node {
+            SDef::StructDef(sd) => layouts.define(sd, false)?,
+            SDef::UnionDef(ud) => layouts.define(ud, true)?,
+            _ => (),
+        }
+    }
+
+    Ok(layouts)
+}
+
+type SizeAlign = (usize, usize); // in bytes
+
+fn size(w: Width) -> usize {
+    match w {
+        Width::Byte | Width::UByte => 1,
+        Width::Half | Width::UHalf => 2,
+        Width::Word | Width::UWord | Width::Float => 4,
+    }
+}
+
+impl Layouts {
+    // lays out a struct, or a union, after those it nests. the repl defines
+    // them one at a time, as they're entered
+    pub fn define(&mut self, sd: &SStructDef, union: bool) -> Result<(), io::Error> {
+        if self.structs.contains_key(&sd.alias) {
+            return Err(io::Error::other(format!("struct {} redefined", sd.alias)));
+        }
+
+        let mut fields = vec![];
+        let (mut offset, mut words) = (0, 0);
+        for (field, typ) in &sd.fields {
+            if matches!(typ, Type::Struct(s) | Type::Union(s) if *s == sd.alias) {
+                return Err(io::Error::other(format!(
+                    "struct {} contains itself",
+                    sd.alias
+                )));
+            }
+            fields.push((field.clone(), typ.clone(), offset));
+            words = words.max(offset + self.words(typ)?);
+            if !union {
+                offset = words;
+            }
+        }
+
+        let layout = StructLayout {
+            fields,
+            words,
+            union,
+        };
+        self.structs.insert(sd.alias.clone(), layout);
+        Ok(())
+    }
+
+    pub fn words(&self, typ: &Type) -> Result<usize, io::Error> {
+        match typ {
+            Type::Arr(t, n) => Ok(n * self.words(t)?),
+            Type::Struct(_) | Type::Union(_) => Ok(self.tagged(typ)?.words),
+            _ => Ok(1),
+        }
+    }
+
+    // the widths of a value's cells, one per word
+    pub fn cells(&self, typ: &Type) -> Result<Vec<Width>, io::Error> {
+        match typ {
+            Type::Char => Ok(vec![Width::Byte]),
+            Type::UChar => Ok(vec![Width::UByte]),
+            Type::Short => Ok(vec![Width::Half]),
+            Type::UShort => Ok(vec![Width::UHalf]),
+            Type::UInt | Type::ULong => Ok(vec![Width::UWord]),
+            Type::Float | Type::Double => Ok(vec![Width::Float]), // todo: doubles are single precision
+            Type::Arr(t, n) => Ok(self.cells(t)?.repeat(*n)),
+            Type::Struct(_) | Type::Union(_) => {
+                let layout = self.tagged(typ)?;
+                // a union's cell is as wide as the widest member overlapping
+                // it, so stores through any member fit
+                layout
+                    .fields
+                    .iter()
+                    .try_fold(vec![], |mut cells, (_, t, offset)| {
+                        for (i, w) in self.cells(t)?.into_iter().enumerate() {
+                            match cells.get_mut(offset + i) {
+                                Some(c) if size(w) > size(*c) => *c = w,
+                                Some(_) => {}
+                                None => cells.push(w),
+                            }
+                        }
+                        Ok(cells)
+                    })
+            }
+            _ => Ok(vec![Width::Word]),
+        }
+    }
+
+    // the types of a value's cells, as cells has their widths. a union's
+    // cell is the first member's overlapping it
+    pub fn cell_types(&self, typ: &Type) -> Result<Vec<Type>, io::Error> {
+        match typ {
+            Type::Arr(t, n) => {
+                let cells = self.cell_types(t)?;
+                Ok(iter::repeat_n(cells, *n).flatten().collect())
+            }
+            Type::Struct(_) | Type::Union(_) => {
+                let layout = self.tagged(typ)?;
+                layout
+                    .fields
+                    .iter()
+                    .try_fold(vec![], |mut cells, (_, t, offset)| {
+                        for (i, t) in self.cell_types(t)?.into_iter().enumerate() {
+                            if offset + i == cells.len() {
+                                cells.push(t);
+                            }
+                        }
+                        Ok(cells)
+                    })
+            }
+            t => Ok(vec![t.clone()]),
+        }
+    }
+
+    // sizes in bytes, as c has them rather than as the backends lay them
+    // out: chars are a byte, and fields are padded to their own alignment.
+    // sizeof reports these, so they're the same in the evaluator and the
+    // compiler
+    pub fn sizeof(&self, typ: &Type, model: &IntModel) -> Result<usize, io::Error> {
+        Ok(self.size_align(typ, model)?.0)
+    }
+
+    fn size_align(&self, typ: &Type, model: &IntModel) -> Result<SizeAlign, io::Error> {
+        let scalar = |n: u32| Ok((n as usize, n as usize));
+        match typ {
+            Type::Char | Type::UChar => scalar(1),
+            Type::Short | Type::UShort => scalar(2),
+            Type::Int | Type::UInt | Type::Bool => scalar(model.sizeof_int()), // comparisons are ints
+            Type::Long | Type::ULong => scalar(model.sizeof_long()),
+            Type::Float => scalar(4),
+            Type::Double => scalar(8),
+            Type::Ptr(_) => scalar(model.sizeof_ptr()),
+            Type::Arr(t, n) => {
+                let (size, align) = self.size_align(t, model)?;
+                Ok((n * size, align))
+            }
+            Type::Struct(_) | Type::Union(_) => {
+                let layout = self.tagged(typ)?;
+                Ok(self.aggregate(layout, &|t| self.size_align(t, model))?.1)
+            }
+            Type::Void => Err(io::Error::other("invalid application of sizeof to void")),
+        }
+    }
+
+    // sizes in bytes, as compiled code lays values out in memory: a char is
+    // a byte, and every other scalar a word, since that's what the backend
+    // loads and stores them as. fields are padded to their alignment, as
+    // sizeof's are
+    // todo: shorts still take a whole word
+    pub fn bytes(&self, typ: &Type, word: usize) -> Result<usize, io::Error> {
+        Ok(self.bytes_align(typ, word)?.0)
+    }
+
+    fn bytes_align(&self, typ: &Type, word: usize) -> Result<SizeAlign, io::Error> {
+        match typ {
+            Type::Char | Type::UChar => Ok((1, 1)),
+            Type::Arr(t, n) => {
+                let (size, align) = self.bytes_align(t, word)?;
+                Ok((n * size, align))
+            }
+            Type::Struct(_) | Type::Union(_) => {
+                let layout = self.tagged(typ)?;
+                Ok(self.aggregate(layout, &|t| self.bytes_align(t, word))?.1)
+            }
+            _ => Ok((word, word)),
+        }
+    }
+
+    // a field's offset in bytes, as bytes lays its struct out
+    pub fn byte_offset(&self, strukt: &str, field: &str, word: usize) -> Option<usize> {
+        let layout = self.structs.get(strukt)?;
+        let i = layout.fields.iter().position(|(f, _, _)| f == field)?;
+        let (offsets, _) = self
+            .aggregate(layout, &|t| self.bytes_align(t, word))
+            .ok()?;
+        Some(offsets[i])
+    }
+
+    // the fields' offsets, and the size and alignment of the whole, given
+    // each field's size and alignment. a union's fields all start at 0
+    fn aggregate(
+        &self,
+        layout: &StructLayout,
+        size_align: &dyn Fn(&Type) -> Result<SizeAlign, io::Error>,
+    ) -> Result<(Vec<usize>, SizeAlign), io::Error> {
+        let (mut offsets, mut size, mut align) = (vec![], 0usize, 1);
+        for (_, t, _) in &layout.fields {
+            let (s, a) = size_align(t)?;
+            let offset = if layout.union {
+                0
+            } else {
+                size.next_multiple_of(a)
+            };
+            offsets.push(offset);
+            size = size.max(offset + s);
+            align = align.max(a);
+        }
+        Ok((offsets, (size.next_multiple_of(align), align))) // so arrays of them stay aligned
+    }
+
+    // a struct's or union's layout. the parser rejects a tag used with the
+    // other keyword, but a tree can come from elsewhere
+    fn tagged(&self, typ: &Type) -> Result<&StructLayout, io::Error> {
+        let (s, union) = match typ {
+            Type::Struct(s) => (s, false),
+            Type::Union(s) => (s, true),
+            _ => unreachable!("only structs and unions are tagged"),
+        };
+        let kind = if union { "union" } else { "struct" };
+        match self.structs.get(s) {
+            Some(layout) if layout.union == union => Ok(layout),
+            Some(_) => Err(io::Error::other(format!(
+                "{s} defined as wrong kind of tag"
+            ))),
+            None => Err(io::Error::other(format!("undefined {kind} {s}"))),
+        }
+    }
+
+    pub fn offset(&self, strukt: &str, field: &str) -> Option<usize> {
+        self.member(strukt, field).map(|(_, o)| o)
+    }
+
+    pub fn field(&self, strukt: &str, field: &str) -> Option<&Type> {
+        self.member(strukt, field).map(|(t, _)| t)
+    }
+
+    fn member(&self, strukt: &str, field: &str) -> Option<(&Type, usize)> {
+        self.structs
+            .get(strukt)?
+            .fields
+            .iter()
+            .find(|(f, _, _)| f == field)
+            .map(|(_, t, o)| (t, *o))
+    }
+}
+
+#[cfg(test)]
+mod test_structs {
+    use crate::lexer;
+    use crate::parser_ast;
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/bindings";
+
+    #[test]
+    fn structs() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/structs.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let layouts = super::layouts(&tree).unwrap();
+        let mut structs = layouts.structs.into_iter().collect::<Vec<_>>();
+        structs.sort_by(|a, b| a.0.cmp(&b.0));
+        insta::assert_yaml_snapshot!(structs, @r###"
+        ---
+        - - point
+          - fields:
+              - - x
+                - Int
+                - 0
+              - - y
+                - Int
+                - 1
+            words: 2
+            union: false
+        - - rect
+          - fields:
+              - - lo
+                - Struct: point
+                - 0
+              - - hi
+                - Struct: point
+                - 2
+            words: 4
+            union: false
+        "###);
+    }
+
+    #[test]
+    fn unions() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/unions.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let layouts = super::layouts(&tree).unwrap();
+        let pun = layouts.structs.get("pun").unwrap();
+        let cells = layouts
+            .cells(&crate::Type::Union("small".to_string()))
+            .unwrap();
+        insta::assert_yaml_snapshot!((pun, cells), @r###"
+        ---
+        - fields:
+            - - pt
+              - Struct: point
+              - 0
+            - - pr
+              - Struct: pair
+              - 0
+            - - i
+              - Int
+              - 0
+          words: 2
+          union: true
+        - - Byte
+        "###);
+    }
+
+    #[test]
+    fn bytes() {
+        // as compiled code lays them out: chars are a byte, the rest a word
+        use crate::Type;
+        let src = "struct tagged { char tag; unsigned char flags; int val; char tail; };";
+
+        let tokens = lexer::lex(src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let layouts = super::layouts(&tree).unwrap();
+        let tagged = Type::Struct("tagged".to_string());
+        for word in [4, 8] {
+            let offsets = ["tag", "flags", "val", "tail"]
+                .map(|f| layouts.byte_offset("tagged", f, word).unwrap());
+            assert_eq!(offsets, [0, 1, word, 2 * word]);
+            assert_eq!(layouts.bytes(&tagged, word).unwrap(), 3 * word);
+            let buf = Type::Arr(Box::new(Type::Char), 5);
+            assert_eq!(layouts.bytes(&buf, word).unwrap(), 5);
+        }
+    }
+
+    #[test]
+    fn sizes() {
+        use crate::{IntModel, Type};
+        let src = fs::read_to_string(format!("{TEST_DIR}/unions.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let layouts = super::layouts(&tree).unwrap();
+        let model = IntModel::default();
+        for (typ, expected) in [
+            (Type::Struct("point".to_string()), 8),
+            (Type::Union("pun".to_string()), 8),
+            (Type::Union("small".to_string()), 1),
+            (Type::Arr(Box::new(Type::Short), 3), 6),
+        ] {
+            assert_eq!(layouts.sizeof(&typ, &model).unwrap(), expected, "{typ:?}");
+        }
+        assert!(layouts.sizeof(&Type::Void, &model).is_err());
+        let err = layouts.sizeof(&Type::Struct("pun".to_string()), &model);
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "pun defined as wrong kind of tag"
+        );
+    }
+}
@@ -1,22 +1,43 @@
+use crate::source::Span;
 use serde::{Deserialize, Serialize};
 use std::io;
-use std::iter;
-
-// todo. remove allocations.
-// iterate on slices and iterators.
-// todo: change to iterative
+use std::num::IntErrorKind;
+use std::ops::Range;
 
+// the span isn't serialized, so dumps of the token stream stay positionless.
+// int literals keep their lexeme as written, with what it says in int
 #[rustfmt::skip]
 #[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
-pub struct Token { pub lexeme: String, pub typ: TT }
+pub struct Token { pub lexeme: String, pub typ: TT, #[serde(skip)] pub span: Span, #[serde(skip)] pub int: Option<IntLit> }
+
+// 0x1F is hex and 017 octal. the suffix is u, l or both, in any case or order
+#[rustfmt::skip]
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct IntLit { pub val: u32, pub base: Base, pub unsigned: bool, pub long: bool }
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub enum Base {
+    Dec,
+    Oct,
+    Hex,
+}
+
+impl Token {
+    pub fn offset(&self, src: &str) -> usize {
+        self.span.offset(src)
+    }
+}
 
 #[rustfmt::skip]
 #[derive(Copy, Clone, PartialEq, Serialize, Deserialize, Debug)]
 pub enum TT {
-    LiteralInt, Alias, // introductions (values) RE: [0-9]+ and [a-zA-Z][a-zA-Z0-9]*
-    KeywordInt, KeywordChar, KeywordVoid, KeywordRet, KeywordIf, KeywordEls, KeywordFor, KeywordWhile, KeywordTrue, KeywordFalse, // keywords ⊂ identifiers
-    Plus, Minus, Star, Slash, LeftAngleBracket, RightAngleBracket, Equals, Bang, Amp, Bar, // eliminations (ops)
-    PuncLeftParen, PuncRightParen, PuncLeftBrace, PuncRightBrace, PuncSemiColon, PuncComma,// punctuation
+    LiteralInt, LiteralFloat, LiteralChar, LiteralStr, Alias, // introductions (values) RE: (0[xX][0-9a-fA-F]+|[0-9]+)([uU]?[lL]?|[lL][uU]), [0-9]+(\.[0-9]*)?([eE][+-]?[0-9]+)?[fFlL]?, '[^']', "[^"]*" and [a-zA-Z_][a-zA-Z0-9_]*
+    KeywordInt, KeywordChar, KeywordVoid, KeywordRet, KeywordIf, KeywordEls, KeywordFor, KeywordWhile, KeywordTrue, KeywordFalse, KeywordNull, KeywordAttribute, KeywordStruct, KeywordUnion, KeywordTypedef, KeywordEnum, KeywordSwitch, KeywordCase, KeywordDefault, KeywordBreak, KeywordContinue, KeywordGoto, KeywordDo, KeywordLong, KeywordShort, KeywordUnsigned, KeywordSigned, KeywordFloat, KeywordDouble, KeywordSizeof, KeywordConst, KeywordStatic, KeywordAuto, KeywordExtern, KeywordRegister, KeywordVolatile, // keywords ⊂ identifiers. all of c89's are reserved, whether or not the parser knows them
+    Plus, Minus, Star, Slash, Percent, LeftAngleBracket, RightAngleBracket, Equals, Bang, Amp, Bar, Caret, Tilde, Question, Colon, Dot, // eliminations (ops)
+    LeftAngleBracketEquals, RightAngleBracketEquals, EqualsEquals, BangEquals, AmpAmp, BarBar, LeftAngleBracketLeftAngleBracket, RightAngleBracketRightAngleBracket, MinusRightAngleBracket, // eliminations (two character ops)
+    PlusEquals, MinusEquals, StarEquals, SlashEquals, PercentEquals, PlusPlus, MinusMinus, // updates
+    PuncLeftParen, PuncRightParen, PuncLeftBrace, PuncRightBrace, PuncLeftBracket, PuncRightBracket, PuncSemiColon, PuncComma, PuncEllipsis, // punctuation
+    Annotation, // c0's //@, which starts a contract rather than a comment
 }
 
 //  1. variations are explicitly typed. Collapsing categories like keywords
@@ -24,220 +45,533 @@ pub enum TT {
 //     will produce redundant work for the parser during syntactic analysis
 //  2. non-tokens: comments, preprocessor directives, macros, whitespace
 
-pub fn lex(input: &[char]) -> Result<Vec<Token>, io::Error> {
-    let cs = skip_ws(input);
+// the position of the offending character is kept as the length in bytes of
+// the input from it on, since the lexer only ever sees suffixes of the source
+#[derive(Debug, thiserror::Error)]
+#[error("{kind}")]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub rest: usize,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LexErrorKind {
+    #[error("unexpected token: {0:?}")]
+    Unexpected(char),
+    #[error("non-ascii character outside a string literal: {0:?}")]
+    NonAscii(char),
+    #[error("unterminated comment")]
+    UnterminatedComment,
+    #[error("unterminated string literal")]
+    UnterminatedStr,
+    #[error("malformed character literal")]
+    MalformedChar,
+    #[error("unknown escape sequence: \\{0}")]
+    UnknownEscape(char),
+    #[error("exponent has no digits: {0}")]
+    NoExponentDigits(String),
+    #[error("malformed integer literal: {0}")]
+    MalformedInt(String),
+    #[error("integer literal too large: {0}")]
+    IntTooLarge(String),
+}
+
+impl LexError {
+    pub fn offset(&self, src: &str) -> usize {
+        src.len() - self.rest
+    }
+}
+
+// a token, or an error and the input after the offending text, where lexing
+// carries on so each error is reported at once
+type Scan<'a> = Result<(Token, &'a [u8]), (LexError, &'a [u8])>;
+
+fn recover<'a>(kind: LexErrorKind, at: &[u8], r: &'a [u8]) -> Scan<'a> {
+    let e = LexError {
+        kind,
+        rest: at.len(),
+    };
+    Err((e, r))
+}
+
+// the errors as one, for callers that only pass them on
+pub fn io_error(errs: Vec<LexError>) -> io::Error {
+    let msgs = errs.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+    io::Error::other(msgs.join("; "))
+}
+
+pub fn lex(input: &str) -> Result<Vec<Token>, Vec<LexError>> {
+    let mut errs = vec![];
+    let tokens = Lexer::new(input)
+        .filter_map(|t| t.map_err(|e| errs.push(e)).ok())
+        .collect();
+
+    match errs.is_empty() {
+        true => Ok(tokens),
+        false => Err(errs),
+    }
+}
 
+// a cursor over the input that yields a token at a time. it only ever moves
+// forward over the slice, so nothing is copied but the lexemes themselves.
+// it steps over bytes: every token is ascii, and non-ascii text only gets
+// through inside string literals and comments, where it's kept whole
+pub struct Lexer<'a> {
+    cs: &'a [u8],
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            cs: input.as_bytes(),
+        }
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Result<Token, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cs = match skip_ws(self.cs) {
+            Ok([]) => return None,
+            Ok(cs) => cs,
+            // an unterminated comment runs to the end, so there's nothing after it
+            Err(e) => {
+                self.cs = &[];
+                return Some(Err(e));
+            }
+        };
+
+        let (t, r) = match scan(cs) {
+            Ok((t, r)) => (Ok(t), r),
+            Err((e, r)) => (Err(e), r),
+        };
+        self.cs = r;
+        Some(t)
+    }
+}
+
+// cs is non-empty and starts past any whitespace
+fn scan(cs: &[u8]) -> Scan<'_> {
     // literals and identifiers have arbitrary length
-    // operations and punctuations are single ASCII characters
-    match cs {
-        [] => Ok(vec![]),
+    // operations are one or two ASCII characters, punctuations are one
+    let (lexeme, typ, r) = match cs {
+        [] => unreachable!("the lexer stops at the end of input"),
+        [b'<', b'=', r @ ..] => ("<=", TT::LeftAngleBracketEquals, r),
+        [b'>', b'=', r @ ..] => (">=", TT::RightAngleBracketEquals, r),
+        [b'=', b'=', r @ ..] => ("==", TT::EqualsEquals, r),
+        [b'!', b'=', r @ ..] => ("!=", TT::BangEquals, r),
+        [b'&', b'&', r @ ..] => ("&&", TT::AmpAmp, r),
+        [b'|', b'|', r @ ..] => ("||", TT::BarBar, r),
+        [b'+', b'+', r @ ..] => ("++", TT::PlusPlus, r),
+        [b'-', b'-', r @ ..] => ("--", TT::MinusMinus, r),
+        [b'-', b'>', r @ ..] => ("->", TT::MinusRightAngleBracket, r),
+        [b'+', b'=', r @ ..] => ("+=", TT::PlusEquals, r),
+        [b'-', b'=', r @ ..] => ("-=", TT::MinusEquals, r),
+        [b'*', b'=', r @ ..] => ("*=", TT::StarEquals, r),
+        [b'/', b'/', b'@', r @ ..] => ("//@", TT::Annotation, r),
+        // what a function returns, which only its ensures name
+        [b'\\', b'r', b'e', b's', b'u', b'l', b't', r @ ..]
+            if !r
+                .first()
+                .is_some_and(|c| c.is_ascii_alphanumeric() || *c == b'_') =>
+        {
+            ("\\result", TT::Alias, r)
+        }
+        [b'/', b'=', r @ ..] => ("/=", TT::SlashEquals, r),
+        [b'%', b'=', r @ ..] => ("%=", TT::PercentEquals, r),
+        [b'<', b'<', r @ ..] => ("<<", TT::LeftAngleBracketLeftAngleBracket, r),
+        [b'>', b'>', r @ ..] => (">>", TT::RightAngleBracketRightAngleBracket, r),
         [f, r @ ..] => match f {
-            '0'..='9' => scan_int(cs),
-            'a'..='z' | 'A'..='Z' => scan_id(cs),
-            '+' => {
-                #[rustfmt::skip]
-                let t = Token { lexeme: String::from("+"), typ: TT::Plus };
-                Ok(iter::once(t).chain(lex(r)?).collect())
-            }
-            '-' => {
-                #[rustfmt::skip]
-                let t = Token { lexeme: String::from("-"), typ: TT::Minus };
-                Ok(iter::once(t).chain(lex(r)?).collect())
-            }
-            '*' => {
-                #[rustfmt::skip]
-                let t = Token { lexeme: String::from("*"), typ: TT::Star };
-                Ok(iter::once(t).chain(lex(r)?).collect())
-            }
-            '/' => {
-                #[rustfmt::skip]
-                let t = Token { lexeme: String::from("/"), typ: TT::Slash };
-                Ok(iter::once(t).chain(lex(r)?).collect())
-            }
-            '<' => {
-                #[rustfmt::skip]
-                let t = Token { lexeme: String::from("<"), typ: TT::LeftAngleBracket };
-                Ok(iter::once(t).chain(lex(r)?).collect())
-            }
-            '>' => {
-                #[rustfmt::skip]
-                let t = Token { lexeme: String::from(">"), typ: TT::RightAngleBracket };
-                Ok(iter::once(t).chain(lex(r)?).collect())
-            }
-            '=' => {
-                #[rustfmt::skip]
-                let t = Token { lexeme: String::from("="), typ: TT::Equals };
-                Ok(iter::once(t).chain(lex(r)?).collect())
-            }
-            '!' => {
-                #[rustfmt::skip]
-                let t = Token { lexeme: String::from("!"), typ: TT::Bang };
-                Ok(iter::once(t).chain(lex(r)?).collect())
-            }
-            '&' => {
-                #[rustfmt::skip]
-                let t = Token { lexeme: String::from("&"), typ: TT::Amp };
-                Ok(iter::once(t).chain(lex(r)?).collect())
-            }
-            '|' => {
-                #[rustfmt::skip]
-                let t = Token { lexeme: String::from("|"), typ: TT::Bar };
-                Ok(iter::once(t).chain(lex(r)?).collect())
-            }
-            '(' => {
-                #[rustfmt::skip]
-                let t = Token { lexeme: String::from("("), typ: TT::PuncLeftParen };
-                Ok(iter::once(t).chain(lex(r)?).collect())
-            }
-            ')' => {
-                #[rustfmt::skip]
-                let t = Token { lexeme: String::from(")"), typ: TT::PuncRightParen };
-                Ok(iter::once(t).chain(lex(r)?).collect())
+            b'0'..=b'9' => return scan_int(cs),
+            b'a'..=b'z' | b'A'..=b'Z' | b'_' => return scan_id(cs),
+            b'\'' => return scan_char(cs),
+            b'"' => return scan_str(cs),
+            b'+' => ("+", TT::Plus, r),
+            b'-' => ("-", TT::Minus, r),
+            b'*' => ("*", TT::Star, r),
+            b'/' => ("/", TT::Slash, r),
+            b'%' => ("%", TT::Percent, r),
+            b'<' => ("<", TT::LeftAngleBracket, r),
+            b'>' => (">", TT::RightAngleBracket, r),
+            b'=' => ("=", TT::Equals, r),
+            b'!' => ("!", TT::Bang, r),
+            b'&' => ("&", TT::Amp, r),
+            b'|' => ("|", TT::Bar, r),
+            b'^' => ("^", TT::Caret, r),
+            b'~' => ("~", TT::Tilde, r),
+            b'.' => return scan_dot(cs),
+            b'?' => ("?", TT::Question, r),
+            b':' => (":", TT::Colon, r),
+            b'(' => ("(", TT::PuncLeftParen, r),
+            b')' => (")", TT::PuncRightParen, r),
+            b'[' => ("[", TT::PuncLeftBracket, r),
+            b']' => ("]", TT::PuncRightBracket, r),
+            b'{' => ("{", TT::PuncLeftBrace, r),
+            b'}' => ("}", TT::PuncRightBrace, r),
+            b';' => (";", TT::PuncSemiColon, r),
+            b',' => (",", TT::PuncComma, r),
+            _ if !f.is_ascii() => {
+                let (c, r) = first(cs);
+                return recover(LexErrorKind::NonAscii(c), cs, r);
             }
-            '{' => {
-                #[rustfmt::skip]
-                let t = Token { lexeme: String::from("{"), typ: TT::PuncLeftBrace };
-                Ok(iter::once(t).chain(lex(r)?).collect())
-            }
-            '}' => {
-                #[rustfmt::skip]
-                let t = Token { lexeme: String::from("}"), typ: TT::PuncRightBrace };
-                Ok(iter::once(t).chain(lex(r)?).collect())
-            }
-            ';' => {
-                #[rustfmt::skip]
-                let t = Token { lexeme: String::from(";"), typ: TT::PuncSemiColon };
-                Ok(iter::once(t).chain(lex(r)?).collect())
-            }
-            ',' => {
-                #[rustfmt::skip]
-                let t = Token { lexeme: String::from(","), typ: TT::PuncComma };
-                Ok(iter::once(t).chain(lex(r)?).collect())
-            }
-            _ => Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("unexpected token: {:?}", f),
-            )),
+            _ => return recover(LexErrorKind::Unexpected(*f as char), cs, r),
         },
-    }
+    };
+
+    let t = Token {
+        lexeme: lexeme.to_owned(),
+        typ,
+        span: Span::new(cs.len(), lexeme.len()),
+        int: None,
+    };
+    Ok((t, r))
 }
 
-fn scan_int(input: &[char]) -> Result<Vec<Token>, io::Error> {
-    // scan_int calls skip_whitespace too to remain idempotent
-    let cs = skip_ws(input);
-
-    match cs {
-        [] => Ok(vec![]),
-        [f, _r @ ..] => match f {
-            '0'..='9' => {
-                #[rustfmt::skip]
-                let i = _r
-                    .iter()
-                    .take_while(|&&c| c.is_numeric())
-                    .count();
-
-                let f = cs[..=i].iter().collect::<String>();
-                let r = &cs[i + 1..];
-
-                let t = Token {
-                    lexeme: f,
-                    typ: TT::LiteralInt,
-                };
-
-                Ok(iter::once(t).chain(lex(r)?).collect())
+fn scan_int(cs: &[u8]) -> Scan<'_> {
+    if let [b'0', b'x' | b'X', r @ ..] = cs {
+        let n = 2 + r.iter().take_while(|c| c.is_ascii_hexdigit()).count();
+        return scan_suffix(cs, 2..n, Base::Hex);
+    }
+
+    #[rustfmt::skip]
+    let i = cs[1..]
+        .iter()
+        .take_while(|c| c.is_ascii_digit())
+        .count();
+
+    // a fraction, an exponent or both make it a float: 1.5, 2., 1e-3
+    let digits = |cs: &[u8]| cs.iter().take_while(|c| c.is_ascii_digit()).count();
+    let mut n = i + 1;
+    if cs.get(n) == Some(&b'.') {
+        n += 1 + digits(&cs[n + 1..]);
+    }
+    if matches!(cs.get(n), Some(b'e' | b'E')) {
+        let sign = matches!(cs.get(n + 1), Some(b'+' | b'-')) as usize;
+        match digits(&cs[n + 1 + sign..]) {
+            0 => {
+                let lexeme = text(&cs[..=n]);
+                return recover(LexErrorKind::NoExponentDigits(lexeme), cs, &cs[n + 1..]);
             }
-            _ => Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("unexpected token: {:?}", f),
-            )),
+            d => n += 1 + sign + d,
+        }
+    }
+    if n == i + 1 {
+        // a leading 0 makes it octal, though 0 itself is as good as decimal
+        let base = match cs[0] == b'0' && n > 1 {
+            true => Base::Oct,
+            false => Base::Dec,
+        };
+        return scan_suffix(cs, 0..n, base);
+    }
+    if matches!(cs.get(n), Some(b'f' | b'F' | b'l' | b'L')) {
+        n += 1;
+    }
+
+    let f = text(&cs[..n]);
+    let r = &cs[n..];
+
+    let t = Token {
+        lexeme: f,
+        typ: TT::LiteralFloat,
+        span: Span::new(cs.len(), n),
+        int: None,
+    };
+    Ok((t, r))
+}
+
+// an int literal whose digits are cs[digits], followed by any suffix. its
+// value has to fit in 32 bits, which is as wide as any int type gets here
+fn scan_suffix(cs: &[u8], digits: Range<usize>, base: Base) -> Scan<'_> {
+    let n = digits.end
+        + cs[digits.end..]
+            .iter()
+            .take_while(|c| matches!(c, b'u' | b'U' | b'l' | b'L'))
+            .count();
+    let (lexeme, r) = (text(&cs[..n]), &cs[n..]);
+
+    let (unsigned, long) = match lexeme[digits.end..].to_ascii_lowercase().as_str() {
+        "" => (false, false),
+        "u" => (true, false),
+        "l" => (false, true),
+        "ul" | "lu" => (true, true),
+        _ => return recover(LexErrorKind::MalformedInt(lexeme), cs, r),
+    };
+    let radix = match base {
+        Base::Dec => 10,
+        Base::Oct => 8,
+        Base::Hex => 16,
+    };
+    // 0x without digits and 09 are malformed
+    let val = match u32::from_str_radix(&lexeme[digits], radix) {
+        Ok(val) => val,
+        Err(e) if *e.kind() == IntErrorKind::PosOverflow => {
+            return recover(LexErrorKind::IntTooLarge(lexeme), cs, r)
+        }
+        Err(_) => return recover(LexErrorKind::MalformedInt(lexeme), cs, r),
+    };
+
+    let t = Token {
+        lexeme,
+        typ: TT::LiteralInt,
+        span: Span::new(cs.len(), n),
+        int: Some(IntLit {
+            val,
+            base,
+            unsigned,
+            long,
+        }),
+    };
+    Ok((t, r))
+}
+
+fn scan_id(cs: &[u8]) -> Scan<'_> {
+    // the longest run of identifier characters, so while1 is one identifier
+    #[rustfmt::skip]
+    let i = cs[1..]
+        .iter()
+        .take_while(|&&c| c.is_ascii_alphanumeric() || c == b'_')
+        .count();
+
+    let f = text(&cs[..=i]);
+    let new_r = &cs[i + 1..];
+
+    // keywords are the identifiers that are reserved
+    let typ = match f.as_str() {
+        "int" => TT::KeywordInt,
+        "char" => TT::KeywordChar,
+        "void" => TT::KeywordVoid,
+        "if" => TT::KeywordIf,
+        "else" => TT::KeywordEls,
+        "for" => TT::KeywordFor,
+        "while" => TT::KeywordWhile,
+        "return" => TT::KeywordRet,
+        "true" => TT::KeywordTrue,
+        "false" => TT::KeywordFalse,
+        "NULL" => TT::KeywordNull,
+        "struct" => TT::KeywordStruct,
+        "union" => TT::KeywordUnion,
+        "enum" => TT::KeywordEnum,
+        "switch" => TT::KeywordSwitch,
+        "case" => TT::KeywordCase,
+        "default" => TT::KeywordDefault,
+        "break" => TT::KeywordBreak,
+        "continue" => TT::KeywordContinue,
+        "do" => TT::KeywordDo,
+        "goto" => TT::KeywordGoto,
+        "long" => TT::KeywordLong,
+        "short" => TT::KeywordShort,
+        "unsigned" => TT::KeywordUnsigned,
+        "signed" => TT::KeywordSigned,
+        "float" => TT::KeywordFloat,
+        "double" => TT::KeywordDouble,
+        "const" => TT::KeywordConst,
+        "static" => TT::KeywordStatic,
+        "sizeof" => TT::KeywordSizeof,
+        "typedef" => TT::KeywordTypedef,
+        "auto" => TT::KeywordAuto,
+        "extern" => TT::KeywordExtern,
+        "register" => TT::KeywordRegister,
+        "volatile" => TT::KeywordVolatile,
+        "__attribute__" => TT::KeywordAttribute,
+        _ => TT::Alias,
+    };
+    let t = Token {
+        lexeme: f,
+        typ,
+        span: Span::new(cs.len(), i + 1),
+        int: None,
+    };
+    Ok((t, new_r))
+}
+
+fn scan_dot(input: &[u8]) -> Scan<'_> {
+    let (lexeme, typ, r) = match input {
+        [b'.', b'.', b'.', r @ ..] => ("...", TT::PuncEllipsis, r),
+        [_, r @ ..] => (".", TT::Dot, r),
+        [] => unreachable!("lex only scans a dot"),
+    };
+
+    let t = Token {
+        lexeme: lexeme.to_owned(),
+        typ,
+        span: Span::new(input.len(), lexeme.len()),
+        int: None,
+    };
+    Ok((t, r))
+}
+
+// the lexeme is the character itself, with any escape already applied
+fn scan_char(input: &[u8]) -> Scan<'_> {
+    let skipped = &input[literal_len(input, b'\'')..];
+    let (c, r) = match input {
+        [b'\'', b'\\', e, b'\'', r @ ..] => match unescape(*e) {
+            Some(c) => (c, r),
+            None => return recover(LexErrorKind::UnknownEscape(*e as char), &input[1..], r),
         },
+        [b'\'', c, ..] if !c.is_ascii() => {
+            return recover(
+                LexErrorKind::NonAscii(first(&input[1..]).0),
+                &input[1..],
+                skipped,
+            )
+        }
+        [b'\'', c, b'\'', r @ ..] if *c != b'\'' && *c != b'\n' => (*c, r),
+        _ => return recover(LexErrorKind::MalformedChar, input, skipped),
+    };
+
+    let t = Token {
+        lexeme: (c as char).to_string(),
+        typ: TT::LiteralChar,
+        span: Span::new(input.len(), input.len() - r.len()),
+        int: None,
+    };
+    Ok((t, r))
+}
+
+fn unescape(e: u8) -> Option<u8> {
+    match e {
+        b'n' => Some(b'\n'),
+        b't' => Some(b'\t'),
+        b'r' => Some(b'\r'),
+        b'0' => Some(b'\0'),
+        b'\\' => Some(b'\\'),
+        b'\'' => Some(b'\''),
+        b'"' => Some(b'"'),
+        _ => None,
     }
 }
 
-// TODO: support identifiers with alpha*numeric* characters after first alphabetic
-fn scan_id(input: &[char]) -> Result<Vec<Token>, io::Error> {
-    // scan_id calls skip_whitespace too to remain idempotent
-    let cs = skip_ws(input);
+// the lexeme is the contents without quotes, with escapes applied
+fn scan_str(input: &[u8]) -> Scan<'_> {
+    let mut r = match input {
+        [b'"', r @ ..] => r,
+        _ => unreachable!("lex only scans a string"),
+    };
 
-    match cs {
-        [] => Ok(vec![]),
-        [f, r @ ..] => match f {
-            'a'..='z' => {
-                // Find the index where the alphabetic characters end
-                #[rustfmt::skip]
-                let i = r
-                    .iter()
-                    .take_while(|&&c| c.is_alphabetic())
-                    .count();
-
-                let f = (cs[..=i].iter()).collect::<String>();
-                let new_r = &cs[i + 1..];
-
-                let keyword = match f.as_str() {
-                    "int" => Some(Token {
-                        lexeme: f.to_string(),
-                        typ: TT::KeywordInt,
-                    }),
-                    "if" => Some(Token {
-                        lexeme: f.to_string(),
-                        typ: TT::KeywordIf,
-                    }),
-                    "else" => Some(Token {
-                        lexeme: f.to_string(),
-                        typ: TT::KeywordEls,
-                    }),
-                    "for" => Some(Token {
-                        lexeme: f.to_string(),
-                        typ: TT::KeywordFor,
-                    }),
-                    "while" => Some(Token {
-                        lexeme: f.to_string(),
-                        typ: TT::KeywordWhile,
-                    }),
-                    "return" => Some(Token {
-                        lexeme: f.to_string(),
-                        typ: TT::KeywordRet,
-                    }),
-                    "true" => Some(Token {
-                        lexeme: f.to_string(),
-                        typ: TT::KeywordTrue,
-                    }),
-                    "false" => Some(Token {
-                        lexeme: f.to_string(),
-                        typ: TT::KeywordFalse,
-                    }),
-                    _ => None,
-                };
-
-                let t = match keyword {
-                    Some(k) => k,
-                    None => Token {
-                        lexeme: f,
-                        typ: TT::Alias,
-                    },
-                };
-
-                Ok(iter::once(t).chain(lex(new_r)?).collect())
+    let skipped = &input[literal_len(input, b'"')..];
+    let mut lexeme = vec![];
+    let r = loop {
+        r = match r {
+            [b'"', r @ ..] => break r,
+            [b'\\', _, _r @ ..] => match unescape(r[1]) {
+                Some(c) => {
+                    lexeme.push(c);
+                    _r
+                }
+                None => {
+                    let e = first(&r[1..]).0;
+                    return recover(LexErrorKind::UnknownEscape(e), &r[1..], skipped);
+                }
+            },
+            // the bytes of a non-ascii char are kept in order, so it stays whole
+            [c, r @ ..] if *c != b'\n' => {
+                lexeme.push(*c);
+                r
             }
-            _ => Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("unexpected token: {:?}", f),
-            )),
-        },
+            _ => return recover(LexErrorKind::UnterminatedStr, input, r),
+        }
+    };
+
+    let t = Token {
+        lexeme: text(&lexeme),
+        typ: TT::LiteralStr,
+        span: Span::new(input.len(), input.len() - r.len()),
+        int: None,
+    };
+    Ok((t, r))
+}
+
+// comments are whitespace to the parser, see trivia() for tools that need them
+fn skip_ws(mut input: &[u8]) -> Result<&[u8], LexError> {
+    loop {
+        input = match input {
+            [b'/', b'/', ..] | [b'/', b'*', ..] => match comment_len(input) {
+                Some(0) => return Ok(input),
+                Some(n) => &input[n..],
+                None => {
+                    return Err(LexError {
+                        kind: LexErrorKind::UnterminatedComment,
+                        rest: input.len(),
+                    })
+                }
+            },
+            [b' ' | b'\t' | b'\n' | b'\r' | b'\x0B' | b'\x0C', r @ ..] => r,
+            _ => return Ok(input),
+        }
     }
 }
 
-fn skip_ws(input: &[char]) -> &[char] {
+// None for a /* without its */
+fn comment_len(input: &[u8]) -> Option<usize> {
     match input {
-        [] => input,
-        [f, r @ ..] => {
-            if f.is_whitespace() {
-                skip_ws(r)
-            } else {
-                input
+        [b'/', b'/', b'@', ..] => Some(0), // an annotation's tokens
+        [b'/', b'/', r @ ..] => Some(2 + r.iter().take_while(|&&c| c != b'\n').count()),
+        [b'/', b'*', r @ ..] => r.windows(2).position(|w| w == b"*/").map(|i| 2 + i + 2),
+        _ => Some(0),
+    }
+}
+
+// a comment the lexer skipped, with the range of bytes it spans
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct Comment {
+    pub text: String,
+    pub span: Range<usize>,
+}
+
+// the trivia the lexer skips, for tools like stats. only comments for now.
+// literals are stepped over, so "//" in a string isn't one, and an
+// unterminated comment runs to the end, since the lexer reports it
+pub fn trivia(input: &str) -> Vec<Comment> {
+    let input = input.as_bytes();
+    let mut comments = vec![];
+    let mut i = 0;
+    while i < input.len() {
+        match (input[i], comment_len(&input[i..])) {
+            (q @ (b'"' | b'\''), _) => i += literal_len(&input[i..], q),
+            (_, Some(0)) => i += 1,
+            (_, n) => {
+                let end = n.map_or(input.len(), |n| i + n);
+                comments.push(Comment {
+                    text: text(&input[i..end]),
+                    span: i..end,
+                });
+                i = end;
             }
         }
     }
+
+    comments
+}
+
+// a string or char literal's length, quotes included
+fn literal_len(input: &[u8], q: u8) -> usize {
+    let mut i = 1;
+    while i < input.len() && input[i] != q && input[i] != b'\n' {
+        i += if input[i] == b'\\' { 2 } else { 1 };
+    }
+    (i + 1).min(input.len())
+}
+
+// the char input starts with, and the input after it. the lexer only stops
+// at ascii bytes, so input always starts on a char
+fn first(input: &[u8]) -> (char, &[u8]) {
+    let n = match input[0] {
+        0xF0.. => 4,
+        0xE0.. => 3,
+        0xC0.. => 2,
+        _ => 1,
+    };
+    let c = std::str::from_utf8(&input[..n.min(input.len())])
+        .ok()
+        .and_then(|s| s.chars().next())
+        .expect("the source is utf-8");
+    (c, &input[n..])
+}
+
+// lexemes are cut at ascii bytes, so they're utf-8 whenever the source is
+fn text(input: &[u8]) -> String {
+    String::from_utf8(input.to_vec()).expect("the source is utf-8")
 }
 
 #[cfg(test)]
@@ -247,14 +581,9 @@ mod test_arith {
 
     #[test]
     fn lit() {
-        #[rustfmt::skip]
-        let input = fs::read(format!("{TEST_DIR}/lit.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let input = fs::read_to_string(format!("{TEST_DIR}/lit.c")).expect("file dne");
 
-        let output = super::lex(input.as_slice()).unwrap();
+        let output = super::lex(&input).unwrap();
         insta::assert_yaml_snapshot!(output, @r###"
         ---
         - lexeme: int
@@ -280,14 +609,9 @@ mod test_arith {
 
     #[test]
     fn add() {
-        #[rustfmt::skip]
-        let input = fs::read(format!("{TEST_DIR}/add.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let input = fs::read_to_string(format!("{TEST_DIR}/add.c")).expect("file dne");
 
-        let output = super::lex(input.as_slice()).unwrap();
+        let output = super::lex(&input).unwrap();
         insta::assert_yaml_snapshot!(output, @r###"
         ---
         - lexeme: int
@@ -317,14 +641,9 @@ mod test_arith {
 
     #[test]
     fn add_multi() {
-        #[rustfmt::skip]
-        let input = fs::read(format!("{TEST_DIR}/add_multi.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let input = fs::read_to_string(format!("{TEST_DIR}/add_multi.c")).expect("file dne");
 
-        let output = super::lex(input.as_slice()).unwrap();
+        let output = super::lex(&input).unwrap();
         insta::assert_yaml_snapshot!(output, @r###"
         ---
         - lexeme: int
@@ -358,14 +677,9 @@ mod test_arith {
 
     #[test]
     fn sub() {
-        #[rustfmt::skip]
-        let input = fs::read(format!("{}/sub.c", TEST_DIR))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let input = fs::read_to_string(format!("{}/sub.c", TEST_DIR)).expect("file dne");
 
-        let output = super::lex(input.as_slice()).unwrap();
+        let output = super::lex(&input).unwrap();
         insta::assert_yaml_snapshot!(output, @r###"
         ---
         - lexeme: int
@@ -395,14 +709,9 @@ mod test_arith {
 
     #[test]
     fn mult() {
-        #[rustfmt::skip]
-        let input = fs::read(format!("{TEST_DIR}/mult.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let input = fs::read_to_string(format!("{TEST_DIR}/mult.c")).expect("file dne");
 
-        let output = super::lex(input.as_slice()).unwrap();
+        let output = super::lex(&input).unwrap();
         insta::assert_yaml_snapshot!(output, @r###"
         ---
         - lexeme: int
@@ -432,14 +741,9 @@ mod test_arith {
 
     #[test]
     fn div() {
-        #[rustfmt::skip]
-        let input = fs::read(format!("{TEST_DIR}/div.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let input = fs::read_to_string(format!("{TEST_DIR}/div.c")).expect("file dne");
 
-        let output = super::lex(input.as_slice()).unwrap();
+        let output = super::lex(&input).unwrap();
         insta::assert_yaml_snapshot!(output, @r###"
         ---
         - lexeme: int
@@ -466,6 +770,155 @@ mod test_arith {
           typ: PuncRightBrace
         "###);
     }
+
+    #[test]
+    fn modulo() {
+        let input = fs::read_to_string(format!("{TEST_DIR}/mod.c")).expect("file dne");
+
+        let output = super::lex(&input).unwrap();
+        insta::assert_yaml_snapshot!(output, @r###"
+        ---
+        - lexeme: int
+          typ: KeywordInt
+        - lexeme: main
+          typ: Alias
+        - lexeme: (
+          typ: PuncLeftParen
+        - lexeme: )
+          typ: PuncRightParen
+        - lexeme: "{"
+          typ: PuncLeftBrace
+        - lexeme: return
+          typ: KeywordRet
+        - lexeme: "17"
+          typ: LiteralInt
+        - lexeme: "%"
+          typ: Percent
+        - lexeme: "5"
+          typ: LiteralInt
+        - lexeme: "*"
+          typ: Star
+        - lexeme: "3"
+          typ: LiteralInt
+        - lexeme: ;
+          typ: PuncSemiColon
+        - lexeme: "}"
+          typ: PuncRightBrace
+        "###);
+    }
+
+    #[test]
+    fn bits_precedence() {
+        let input = fs::read_to_string(format!("{TEST_DIR}/bits_precedence.c")).expect("file dne");
+
+        let output = super::lex(&input).unwrap();
+        insta::assert_yaml_snapshot!(output, @r###"
+        ---
+        - lexeme: int
+          typ: KeywordInt
+        - lexeme: main
+          typ: Alias
+        - lexeme: (
+          typ: PuncLeftParen
+        - lexeme: )
+          typ: PuncRightParen
+        - lexeme: "{"
+          typ: PuncLeftBrace
+        - lexeme: return
+          typ: KeywordRet
+        - lexeme: "1"
+          typ: LiteralInt
+        - lexeme: "|"
+          typ: Bar
+        - lexeme: "6"
+          typ: LiteralInt
+        - lexeme: "&"
+          typ: Amp
+        - lexeme: "3"
+          typ: LiteralInt
+        - lexeme: ^
+          typ: Caret
+        - lexeme: "8"
+          typ: LiteralInt
+        - lexeme: "<<"
+          typ: LeftAngleBracketLeftAngleBracket
+        - lexeme: "1"
+          typ: LiteralInt
+        - lexeme: ;
+          typ: PuncSemiColon
+        - lexeme: "}"
+          typ: PuncRightBrace
+        "###);
+    }
+
+    #[test]
+    fn int_lits() {
+        let input = fs::read_to_string(format!("{TEST_DIR}/int_lits.c")).expect("file dne");
+
+        let lits = super::lex(&input)
+            .unwrap()
+            .into_iter()
+            .filter_map(|t| Some((t.lexeme, t.int?)))
+            .collect::<Vec<_>>();
+        insta::assert_yaml_snapshot!(lits, @r###"
+        ---
+        - - "0x1F"
+          - val: 31
+            base: Hex
+            unsigned: false
+            long: false
+        - - "017"
+          - val: 15
+            base: Oct
+            unsigned: false
+            long: false
+        - - 42L
+          - val: 42
+            base: Dec
+            unsigned: false
+            long: true
+        - - 42u
+          - val: 42
+            base: Dec
+            unsigned: true
+            long: false
+        - - "1"
+          - val: 1
+            base: Dec
+            unsigned: false
+            long: false
+        - - 0u
+          - val: 0
+            base: Dec
+            unsigned: true
+            long: false
+        - - "100"
+          - val: 100
+            base: Dec
+            unsigned: false
+            long: false
+        - - "0"
+          - val: 0
+            base: Dec
+            unsigned: false
+            long: false
+        - - 0XfUL
+          - val: 15
+            base: Hex
+            unsigned: true
+            long: true
+        "###);
+
+        let errs = super::lex("0x 09 42uu 4294967296").unwrap_err();
+        let errs = errs.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        insta::assert_yaml_snapshot!(errs, @r###"
+        ---
+        - "malformed integer literal: 0x"
+        - "malformed integer literal: 09"
+        - "malformed integer literal: 42uu"
+        - "integer literal too large: 4294967296"
+        "###);
+    }
 }
 
 #[cfg(test)]
@@ -475,14 +928,9 @@ mod test_bindings {
 
     #[test]
     fn asnmt() {
-        #[rustfmt::skip]
-        let input = fs::read(format!("{TEST_DIR}/asnmt.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let input = fs::read_to_string(format!("{TEST_DIR}/asnmt.c")).expect("file dne");
 
-        let output = super::lex(input.as_slice()).unwrap();
+        let output = super::lex(&input).unwrap();
         insta::assert_yaml_snapshot!(output, @r###"
         ---
         - lexeme: int
@@ -518,14 +966,9 @@ mod test_bindings {
 
     #[test]
     fn composition() {
-        #[rustfmt::skip]
-        let input = fs::read(format!("{TEST_DIR}/composition.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let input = fs::read_to_string(format!("{TEST_DIR}/composition.c")).expect("file dne");
 
-        let output = super::lex(input.as_slice()).unwrap();
+        let output = super::lex(&input).unwrap();
         insta::assert_yaml_snapshot!(output, @r###"
         ---
         - lexeme: int
@@ -622,6 +1065,252 @@ mod test_bindings {
           typ: PuncRightBrace
         "###);
     }
+
+    #[test]
+    fn chars() {
+        let input = fs::read_to_string(format!("{TEST_DIR}/chars.c")).expect("file dne");
+
+        let output = super::lex(&input).unwrap();
+        insta::assert_yaml_snapshot!(output, @r###"
+        ---
+        - lexeme: char
+          typ: KeywordChar
+        - lexeme: next
+          typ: Alias
+        - lexeme: (
+          typ: PuncLeftParen
+        - lexeme: char
+          typ: KeywordChar
+        - lexeme: c
+          typ: Alias
+        - lexeme: )
+          typ: PuncRightParen
+        - lexeme: "{"
+          typ: PuncLeftBrace
+        - lexeme: return
+          typ: KeywordRet
+        - lexeme: c
+          typ: Alias
+        - lexeme: +
+          typ: Plus
+        - lexeme: "1"
+          typ: LiteralInt
+        - lexeme: ;
+          typ: PuncSemiColon
+        - lexeme: "}"
+          typ: PuncRightBrace
+        - lexeme: int
+          typ: KeywordInt
+        - lexeme: main
+          typ: Alias
+        - lexeme: (
+          typ: PuncLeftParen
+        - lexeme: )
+          typ: PuncRightParen
+        - lexeme: "{"
+          typ: PuncLeftBrace
+        - lexeme: char
+          typ: KeywordChar
+        - lexeme: nl
+          typ: Alias
+        - lexeme: "="
+          typ: Equals
+        - lexeme: "\n"
+          typ: LiteralChar
+        - lexeme: ;
+          typ: PuncSemiColon
+        - lexeme: char
+          typ: KeywordChar
+        - lexeme: c
+          typ: Alias
+        - lexeme: "="
+          typ: Equals
+        - lexeme: next
+          typ: Alias
+        - lexeme: (
+          typ: PuncLeftParen
+        - lexeme: a
+          typ: LiteralChar
+        - lexeme: )
+          typ: PuncRightParen
+        - lexeme: ;
+          typ: PuncSemiColon
+        - lexeme: char
+          typ: KeywordChar
+        - lexeme: big
+          typ: Alias
+        - lexeme: "="
+          typ: Equals
+        - lexeme: "200"
+          typ: LiteralInt
+        - lexeme: ;
+          typ: PuncSemiColon
+        - lexeme: char
+          typ: KeywordChar
+        - lexeme: max
+          typ: Alias
+        - lexeme: "="
+          typ: Equals
+        - lexeme: "127"
+          typ: LiteralInt
+        - lexeme: ;
+          typ: PuncSemiColon
+        - lexeme: max
+          typ: Alias
+        - lexeme: ++
+          typ: PlusPlus
+        - lexeme: ;
+          typ: PuncSemiColon
+        - lexeme: int
+          typ: KeywordInt
+        - lexeme: sum
+          typ: Alias
+        - lexeme: "="
+          typ: Equals
+        - lexeme: nl
+          typ: Alias
+        - lexeme: +
+          typ: Plus
+        - lexeme: c
+          typ: Alias
+        - lexeme: +
+          typ: Plus
+        - lexeme: "\u0000"
+          typ: LiteralChar
+        - lexeme: ;
+          typ: PuncSemiColon
+        - lexeme: return
+          typ: KeywordRet
+        - lexeme: sum
+          typ: Alias
+        - lexeme: +
+          typ: Plus
+        - lexeme: big
+          typ: Alias
+        - lexeme: +
+          typ: Plus
+        - lexeme: max
+          typ: Alias
+        - lexeme: +
+          typ: Plus
+        - lexeme: "\\"
+          typ: LiteralChar
+        - lexeme: ;
+          typ: PuncSemiColon
+        - lexeme: "}"
+          typ: PuncRightBrace
+        "###);
+    }
+
+    #[test]
+    fn strs() {
+        let input = fs::read_to_string(format!("{TEST_DIR}/strs.c")).expect("file dne");
+
+        let output = super::lex(&input).unwrap();
+        insta::assert_yaml_snapshot!(output, @r###"
+        ---
+        - lexeme: int
+          typ: KeywordInt
+        - lexeme: main
+          typ: Alias
+        - lexeme: (
+          typ: PuncLeftParen
+        - lexeme: )
+          typ: PuncRightParen
+        - lexeme: "{"
+          typ: PuncLeftBrace
+        - lexeme: char
+          typ: KeywordChar
+        - lexeme: "*"
+          typ: Star
+        - lexeme: s
+          typ: Alias
+        - lexeme: "="
+          typ: Equals
+        - lexeme: "hi\tthere\n"
+          typ: LiteralStr
+        - lexeme: ;
+          typ: PuncSemiColon
+        - lexeme: char
+          typ: KeywordChar
+        - lexeme: "*"
+          typ: Star
+        - lexeme: t
+          typ: Alias
+        - lexeme: "="
+          typ: Equals
+        - lexeme: "hi\tthere\n"
+          typ: LiteralStr
+        - lexeme: ;
+          typ: PuncSemiColon
+        - lexeme: char
+          typ: KeywordChar
+        - lexeme: "*"
+          typ: Star
+        - lexeme: e
+          typ: Alias
+        - lexeme: "="
+          typ: Equals
+        - lexeme: ""
+          typ: LiteralStr
+        - lexeme: ;
+          typ: PuncSemiColon
+        - lexeme: return
+          typ: KeywordRet
+        - lexeme: s
+          typ: Alias
+        - lexeme: "=="
+          typ: EqualsEquals
+        - lexeme: t
+          typ: Alias
+        - lexeme: "?"
+          typ: Question
+        - lexeme: "*"
+          typ: Star
+        - lexeme: s
+          typ: Alias
+        - lexeme: +
+          typ: Plus
+        - lexeme: "*"
+          typ: Star
+        - lexeme: e
+          typ: Alias
+        - lexeme: ":"
+          typ: Colon
+        - lexeme: "0"
+          typ: LiteralInt
+        - lexeme: ;
+          typ: PuncSemiColon
+        - lexeme: "}"
+          typ: PuncRightBrace
+        "###);
+    }
+
+    #[test]
+    fn identifiers() {
+        let input = fs::read_to_string(format!("{TEST_DIR}/identifiers.c")).expect("file dne");
+
+        let aliases = super::lex(&input)
+            .unwrap()
+            .into_iter()
+            .filter(|t| t.typ == super::TT::Alias)
+            .map(|t| t.lexeme)
+            .collect::<Vec<_>>();
+        let expected = ["main", "while1", "_x9", "X_1", "int2"];
+        assert_eq!(aliases, [&expected[..], &expected[1..]].concat());
+
+        let tokens = super::lex("auto extern register volatile").unwrap();
+        let typs = tokens.iter().map(|t| t.typ).collect::<Vec<_>>();
+        assert_eq!(
+            typs,
+            [
+                super::TT::KeywordAuto,
+                super::TT::KeywordExtern,
+                super::TT::KeywordRegister,
+                super::TT::KeywordVolatile
+            ]
+        );
+    }
 }
 
 #[cfg(test)]
@@ -631,14 +1320,9 @@ mod test_control {
 
     #[test]
     fn lit() {
-        #[rustfmt::skip]
-        let input = fs::read(format!("{TEST_DIR}/ifels_then.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let input = fs::read_to_string(format!("{TEST_DIR}/ifels_then.c")).expect("file dne");
 
-        let output = super::lex(input.as_slice()).unwrap();
+        let output = super::lex(&input).unwrap();
         insta::assert_yaml_snapshot!(output, @r###"
         ---
         - lexeme: int
@@ -685,4 +1369,73 @@ mod test_control {
           typ: PuncRightBrace
         "###);
     }
+
+    #[test]
+    fn lteq() {
+        let input = fs::read_to_string(format!("{TEST_DIR}/lteq_true.c")).expect("file dne");
+
+        let output = super::lex(&input).unwrap();
+        insta::assert_yaml_snapshot!(output, @r###"
+        ---
+        - lexeme: int
+          typ: KeywordInt
+        - lexeme: main
+          typ: Alias
+        - lexeme: (
+          typ: PuncLeftParen
+        - lexeme: )
+          typ: PuncRightParen
+        - lexeme: "{"
+          typ: PuncLeftBrace
+        - lexeme: return
+          typ: KeywordRet
+        - lexeme: "9"
+          typ: LiteralInt
+        - lexeme: "<="
+          typ: LeftAngleBracketEquals
+        - lexeme: "10"
+          typ: LiteralInt
+        - lexeme: ;
+          typ: PuncSemiColon
+        - lexeme: "}"
+          typ: PuncRightBrace
+        "###);
+    }
+}
+
+#[cfg(test)]
+mod test_trivia {
+    use crate::source;
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/whitespace";
+
+    #[test]
+    fn comments() {
+        let src =
+            source::decode(&fs::read(format!("{TEST_DIR}/comments.c")).expect("file dne")).unwrap();
+
+        let tokens = super::lex(&src).unwrap();
+        assert_eq!(tokens.len(), 15);
+        insta::assert_yaml_snapshot!(super::trivia(&src), @r###"
+        ---
+        - text: "/* but\n    this is */"
+          span:
+            start: 47
+            end: 68
+        - text: // and this
+          span:
+            start: 85
+            end: 96
+        "###);
+    }
+
+    #[test]
+    fn long_input() {
+        // far more tokens than the test thread has frames for
+        let src = "int x = 1 + 2; // three\n".repeat(100_000);
+        let tokens = super::lex(&src).unwrap();
+        assert_eq!(tokens.len(), 700_000);
+        assert_eq!(tokens.last().unwrap().span.offset(&src), src.len() - 11);
+    }
 }
@@ -1,18 +1,166 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use source::Span;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::fmt::{self, Debug};
+use std::io;
+use std::ops::Deref;
+use std::path::Path;
 use std::rc::Rc;
 
-// pub mod evaluator;
 pub mod allocator;
+pub mod asm;
+pub mod bytecode;
+pub mod cli;
+pub mod counters;
+pub mod debugger;
+pub mod diagnostics;
+pub mod engine;
+pub mod evaluator;
+pub mod golden;
+pub mod ir;
+pub mod json;
+pub mod layout;
 pub mod lexer;
+pub mod linker;
+pub mod llvm;
+pub mod optimizer;
 pub mod parser;
 pub mod parser_ast;
+pub mod peephole;
+pub mod pic;
+pub mod preprocessor;
+pub mod pretty;
+pub mod repl;
+pub mod runtime;
 pub mod selector;
+pub mod source;
+pub mod stats;
+pub mod symmap;
+pub mod symtab;
 pub mod translator;
+pub mod transpiler;
+pub mod typecheck;
 pub mod typer;
 pub mod visualizer;
 
+// the pipeline the cli runs, for tools that embed the compiler instead of
+// shelling out to it. lex and parse are its first stages on their own.
+// interpret and compile take a file's decoded text and the path it's from,
+// which includes are found next to, and their errors say file:line:col
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    pub opt: OptLevel,
+    pub model: IntModel,
+    pub pp: preprocessor::Options,
+    pub pic: bool,
+    pub dyn_check: bool, // interpret checks contracts, rather than taking them for comments
+    pub budget: Budget,  // what interpret may use
+}
+
+// every lexical error, one per line, at line:col
+pub fn lex(src: &str) -> Result<Vec<lexer::Token>, io::Error> {
+    lexer::lex(src).map_err(|errs| {
+        let errs = errs.iter().map(|e| {
+            let pos = source::pos(src, e.offset(src), 1);
+            format!("{}:{}: {e}", pos.line, pos.col)
+        });
+        io::Error::other(errs.collect::<Vec<_>>().join("\n"))
+    })
+}
+
+// the first syntax error, located at its span
+pub fn parse(tokens: &[lexer::Token]) -> Result<SPrg, io::Error> {
+    parser_ast::parse_prg(tokens)
+}
+
+// entry points for fuzz targets: any bytes in, and never a panic out.
+// input that isn't a program is an error, like any other
+pub fn fuzz_lex(data: &[u8]) -> Result<Vec<lexer::Token>, io::Error> {
+    lex(&source::decode(data)?)
+}
+
+pub fn fuzz_parse(data: &[u8]) -> Result<SPrg, io::Error> {
+    let tokens = fuzz_lex(data)?;
+    let (prg, errs) = parser_ast::parse(&tokens);
+    match errs.into_iter().next() {
+        Some(e) => Err(e.into()),
+        None => Ok(prg),
+    }
+}
+
+// main's return value, from walking the program
+pub fn interpret(src: &str, path: &Path, config: &Config) -> Result<i64, io::Error> {
+    let (prg, text, map) = front(src, path, config)?;
+    let prg = match config.dyn_check {
+        true => prg,
+        false => evaluator::erase_contracts(&prg),
+    };
+    let exit = evaluator::eval_prg_to(&prg, config.model.clone(), Sink::default(), config.budget);
+    exit.map(i64::from).map_err(|e| at(&map, &text, e))
+}
+
+// assembly text, linked with the runtime
+pub fn compile(src: &str, path: &Path, config: &Config) -> Result<String, io::Error> {
+    let (prg, text, map) = front(src, path, config)?;
+    engine::unsupported(&prg).map_err(|e| at(&map, &text, e))?;
+    let (assembly, _) = engine::compile(
+        &prg,
+        config.opt.clone(),
+        &config.model,
+        &text,
+        false,
+        config.pic,
+    );
+    Ok(assembly.join("\n"))
+}
+
+// preprocessed, lexed, parsed and typed, with the text and source map for
+// saying where errors are
+fn front(
+    src: &str,
+    path: &Path,
+    config: &Config,
+) -> Result<(SPrg, String, source::SourceMap), io::Error> {
+    let pp = preprocessor::Options {
+        model: config.model.clone(),
+        ..config.pp.clone()
+    };
+    let (text, map) = preprocessor::preprocess(src, path, &pp)?;
+    let errs = match lexer::lex(&text) {
+        Ok(tokens) => match parser_ast::parse(&tokens) {
+            (prg, errs) if errs.is_empty() => {
+                typecheck::check(&prg).map_err(|e| at(&map, &text, e))?;
+                return Ok((prg, text, map));
+            }
+            (_, errs) => errs.into_iter().map(io::Error::from).collect(),
+        },
+        Err(errs) => errs
+            .into_iter()
+            .map(|e| {
+                let span = source::Span::new(e.rest, 0);
+                source::locate(io::Error::other(e), span)
+            })
+            .collect::<Vec<_>>(),
+    };
+    let errs = errs.into_iter().map(|e| at(&map, &text, e).to_string());
+    Err(io::Error::other(errs.collect::<Vec<_>>().join("\n")))
+}
+
+// e, prefixed with where it is if it knows
+fn at(map: &source::SourceMap, text: &str, e: io::Error) -> io::Error {
+    match source::located(&e) {
+        Some(l) => {
+            let caret = map.caret(text, l.span.offset(text), 1);
+            let loc = caret
+                .split_once('\n')
+                .map_or(caret.as_str(), |(loc, _)| loc);
+            io::Error::other(format!("{loc}: {e}"))
+        }
+        None => e,
+    }
+}
+
 macro_rules! common_struct {
 
     ($(#[$meta:meta])* $vis:vis struct $name:ident $body:tt) => {
@@ -30,7 +178,7 @@ macro_rules! common_enum {
     }
 }
 
-common_enum! { pub enum OptLevel { O0, O1, O2 } }
+common_enum! { #[derive(Hash, Default)] pub enum OptLevel { #[default] O0, O1, O2 } }
 impl TryFrom<u32> for OptLevel {
     type Error = &'static str;
 
@@ -44,20 +192,182 @@ impl TryFrom<u32> for OptLevel {
     }
 }
 
+pub type Levels = HashMap<String, OptLevel>; // the functions compiled at a level of their own, by their optimize attribute
+
+// data models fix the widths of long and pointers, int width is picked
+// separately so small (16 bit) teaching targets can be emulated.
+common_enum! { #[derive(Hash)] pub enum DataModel { ILP32, LP64 } }
+common_enum! { #[derive(Copy, Hash, Default)] pub enum Overflow { #[default] Wrap, Trap } } // what signed arithmetic past int's range does: wrap around, as two's complement, or stop the program
+common_struct! { #[derive(Hash)] pub struct IntModel { pub int_width: u32, pub data_model: DataModel, #[serde(default)] pub overflow: Overflow, #[serde(default)] pub checked_arith: bool } } // checked_arith guards compiled divisions against INT_MIN / -1
+impl Default for IntModel {
+    fn default() -> Self {
+        Self {
+            int_width: 32,
+            data_model: DataModel::ILP32,
+            overflow: Overflow::Wrap,
+            checked_arith: false,
+        }
+    }
+}
+
+impl IntModel {
+    pub fn new(int_width: u32, data_model: DataModel) -> Result<Self, &'static str> {
+        match int_width {
+            16 | 32 => Ok(Self {
+                int_width,
+                data_model,
+                overflow: Overflow::Wrap,
+                checked_arith: false,
+            }),
+            _ => Err("picoc-error: invalid int width given"),
+        }
+    }
+
+    pub fn sizeof_int(&self) -> u32 {
+        self.int_width / 8
+    }
+
+    pub fn sizeof_long(&self) -> u32 {
+        match self.data_model {
+            DataModel::ILP32 => 4,
+            DataModel::LP64 => 8,
+        }
+    }
+
+    pub fn sizeof_ptr(&self) -> u32 {
+        self.sizeof_long()
+    }
+
+    // the word compiled code keeps every scalar in is as wide as a pointer,
+    // as are its registers, so ilp32 compiles for rv32 and lp64 for rv64
+    pub fn word(&self) -> i32 {
+        self.sizeof_ptr() as i32
+    }
+
+    pub fn int_max(&self) -> i32 {
+        ((1i64 << (self.int_width - 1)) - 1) as i32
+    }
+
+    pub fn int_min(&self) -> i32 {
+        (-(1i64 << (self.int_width - 1))) as i32
+    }
+
+    // two's complement truncation to int_width bits
+    pub fn wrap(&self, n: i64) -> i32 {
+        let shift = 64 - self.int_width;
+        ((n << shift) >> shift) as i32
+    }
+
+    // as wrap, for a signed op's exact result, which is an error under
+    // --overflow=trap when it doesn't fit
+    pub fn wrap_signed(&self, n: i64) -> Result<i32, io::Error> {
+        match (self.overflow, self.wrap(n)) {
+            (Overflow::Trap, wrapped) if wrapped as i64 != n => {
+                Err(io::Error::other("signed integer overflow"))
+            }
+            (_, wrapped) => Ok(wrapped),
+        }
+    }
+
+    // a division's quotient or remainder, wrapped. it's an error when the
+    // divisor is zero, or for INT_MIN / -1, whose quotient doesn't fit
+    pub fn divide(&self, l: i64, r: i64, rem: bool) -> Result<i32, io::Error> {
+        if r == 0 {
+            return Err(io::Error::other("division by zero"));
+        }
+        if l == self.int_min() as i64 && r == -1 {
+            return Err(io::Error::other("integer division overflow"));
+        }
+        Ok(self.wrap(if rem { l % r } else { l / r }))
+    }
+
+    // the same int_width bits, read as an unsigned int
+    pub fn unsigned(&self, n: i32) -> i64 {
+        n as i64 & ((1i64 << self.int_width) - 1)
+    }
+}
+
 // *********************************************************************************************************************
 // ************************************************ SOURCE ENVIRONMENTS ************************************************
 // *********************************************************************************************************************
 
 // ***** static tnv: Map<Alias, Type> *****
-common_struct! { pub struct Tnv { fnv: HashMap<String, LambdaType>, vnv: HashMap<String, Type> }}
-common_struct! { pub struct LambdaType { fp: Vec<Type>, body: Type } }
-common_enum! { pub enum Type { Int, Bool, Void } } // Cond(Type::Bool, Box<Type>, Box<Type>),
-
-// ***** dynamic vnv: Map<Alias, Val> *****
-common_struct! { pub struct Vnv { fnv: HashMap<String, LambdaVal>, vnv: HashMap<String, i32> }} // todo, -> Val
-common_struct! { pub struct LambdaVal { pub fp: Vec<String>, pub body: Vec<SStmt>} } // fp's only need types (tags) if implementing safety dynamically
+common_struct! { pub struct Tnv { fnv: HashMap<String, LambdaType>, vnv: HashMap<String, Type>, layouts: layout::Layouts, model: IntModel, #[serde(default)] c0: bool }} // model is what the translator sizes things for. c0 is whether it's typed by c0's rules
+common_struct! { pub struct LambdaType { fp: Vec<Type>, body: Type, #[serde(default, skip_serializing_if = "std::ops::Not::not")] variadic: bool } } // variadic functions take any more args after fp
+common_enum! { #[derive(Hash)] pub enum Type { Int, Char, Short, Long, UChar, UShort, UInt, ULong, Float, Double, Bool, Void, Ptr(Box<Type>), Arr(Box<Type>, usize), Struct(String), Union(String) } } // Cond(Type::Bool, Box<Type>, Box<Type>),
+
+// ***** dynamic vnv: Map<Alias, Addr>, mem: Map<Addr, Val> *****
+common_struct! { pub struct Vnv { fnv: HashMap<String, LambdaVal>, vnv: HashMap<String, i32>, mem: RefCell<Vec<(i32, Width)>>, rodata: RefCell<Vec<i32>>, strs: RefCell<HashMap<String, i32>>, types: RefCell<HashMap<i32, Type>>, heap: RefCell<Vec<(i32, Width)>>, model: IntModel, layouts: layout::Layouts, #[serde(skip)] out: Sink, budget: Budget, used: RefCell<(u64, usize)>, #[serde(skip)] hook: Hook, calls: RefCell<Vec<String>>, uninit: RefCell<HashSet<i32>> }} // todo, -> Val. types are what each allocation was declared as, for sizeof and bounds. heap is what alloc hands out, never freed. out is where printf writes. used is the steps taken and heap bytes handed out, against budget. calls are the functions the walker is in, main first. uninit is the cells of locals declared without an initializer and not yet assigned
+common_struct! { #[derive(Copy)] pub struct Budget { pub steps: Option<u64>, pub heap: Option<usize>, pub depth: Option<usize> } } // what an interpreted program may use before it's cut off: stmts and loop tests the walker runs (ops for the vm), bytes alloc hands out, and calls deep. none is unlimited
+common_struct! { pub struct LambdaVal { pub fp: Vec<(String, Type)>, pub ret: Type, pub body: Vec<Spanned<SStmt>>, pub contracts: Vec<SContract> } } // fp and ret types give the params' and return slot's cells their widths
+common_enum! { #[derive(Copy)] pub enum Width { Byte, UByte, Half, UHalf, Word, UWord, Float } } // chars and shorts still take a cell, but their values are narrowed on store. floats hold their ieee-754 bits
 common_enum! { pub enum Val { Int(i32), Bool(bool), Str(String) } }
 
+// a program recursing without end is cut off before it overflows the
+// walker's stack, rather than aborting the host
+impl Default for Budget {
+    fn default() -> Self {
+        Budget {
+            steps: None,
+            heap: None,
+            depth: Some(evaluator::DEPTH),
+        }
+    }
+}
+
+// an interpreted program's output. it's shared, so whoever hands the
+// evaluator a buffer can read it back, as an engine capturing stdout does
+#[derive(Clone)]
+pub struct Sink(pub Rc<RefCell<dyn io::Write>>);
+
+impl Default for Sink {
+    fn default() -> Self {
+        Self(Rc::new(RefCell::new(io::stdout())))
+    }
+}
+
+impl Debug for Sink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Sink")
+    }
+}
+
+impl PartialEq for Sink {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+// what the walker calls before and after each stmt it runs, with the
+// locals in scope, for a debugger to stop there or a tracer to print it. an
+// error stops the program
+pub type HookFn = dyn FnMut(Event, &Vnv, &HashMap<String, i32>) -> Result<(), io::Error>;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Event<'a> {
+    Stmt(&'a Spanned<SStmt>),
+    Done(&'a Spanned<SStmt>, Option<Signal>), // how it left its block, if early
+}
+
+#[derive(Clone, Default)]
+pub struct Hook(pub Option<Rc<RefCell<HookFn>>>);
+
+impl Debug for Hook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Hook")
+    }
+}
+
+impl PartialEq for Hook {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (Some(l), Some(r)) => Rc::ptr_eq(l, r),
+            (l, r) => l.is_none() && r.is_none(),
+        }
+    }
+}
+common_enum! { #[derive(Copy)] pub enum Signal { Ret(i32), Break, Continue } } // how a stmt leaves its block early
+
 // *********************************************************************************************************************
 // *********************************************** SOURCE REPRESENTATION ***********************************************
 // *********************************************************************************************************************
@@ -144,6 +454,12 @@ impl Node for StartNode {
     }
 }
 
+impl Default for StartNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl StartNode {
     pub fn new() -> Self {
         Self {
@@ -218,6 +534,60 @@ impl ConstantNode {
     }
 }
 
+pub struct AddNode {
+    _id: i128,
+    use_def: Vec<Rc<dyn Node>>,
+    _def_use: Vec<Rc<dyn Node>>,
+}
+
+impl Node for AddNode {
+    fn use_defs(&self) -> &[Rc<dyn Node>] {
+        &self.use_def
+    }
+
+    fn print(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.use_def.iter().try_for_each(|n| n.print(f))?;
+        writeln!(f, "AddNode")
+    }
+}
+
+impl AddNode {
+    pub fn new(lhs: Rc<dyn Node>, rhs: Rc<dyn Node>) -> Self {
+        Self {
+            _id: fresh_id(),
+            use_def: vec![lhs, rhs],
+            _def_use: vec![],
+        }
+    }
+}
+
+pub struct SubNode {
+    _id: i128,
+    use_def: Vec<Rc<dyn Node>>,
+    _def_use: Vec<Rc<dyn Node>>,
+}
+
+impl Node for SubNode {
+    fn use_defs(&self) -> &[Rc<dyn Node>] {
+        &self.use_def
+    }
+
+    fn print(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.use_def.iter().try_for_each(|n| n.print(f))?;
+        writeln!(f, "SubNode")
+    }
+}
+
+impl SubNode {
+    pub fn new(lhs: Rc<dyn Node>, rhs: Rc<dyn Node>) -> Self {
+        Self {
+            _id: fresh_id(),
+            use_def: vec![lhs, rhs],
+            _def_use: vec![],
+        }
+    }
+}
+
 // TODO: for loops, etc.
 type _SugaredPrg = Vec<()>;
 
@@ -227,36 +597,94 @@ type _SugaredPrg = Vec<()>;
 // picoc's source representation is a forest of ASTS
 // since variable and function are not values
 
-type SPrg = Vec<SDef>;
-common_enum! { pub enum SDef { FuncDef(SFuncDef), VarDef(SVarDef) } }
-common_struct! { pub struct SFuncDef {pub alias: String,  pub typ: Type, pub fps: Vec<(String, Type)>, pub body: Vec<SStmt> } } // fp needs Type for statics, and String for dynamics
-common_struct! { pub struct SVarDef { pub alias: String, pub typ: Type, pub expr: Box<SExpr> }} // UpdateBind { alias: String, op: BinOp, expr: Box<Expr> }
+type SPrg = Vec<Spanned<SDef>>;
+
+// a node of the tree and the span of source it was parsed from. the span
+// isn't serialized, so dumps of the tree stay positionless, and a node
+// derefs to what it wraps for the passes that don't report anything
+#[derive(Clone, Debug, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Spanned<T> {
+    pub node: T,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+impl<T> Spanned<T> {
+    pub fn new(node: T, span: Span) -> Self {
+        Spanned { node, span }
+    }
+}
+
+impl<T> Deref for Spanned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.node
+    }
+}
+
+// whether two trees are the same but for where they were parsed from,
+// which their dumps leave out
+pub fn same_tree<T: Serialize + ?Sized>(a: &T, b: &T) -> bool {
+    match (json::to_string(a), json::to_string(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+common_enum! { #[derive(Hash)] pub enum SDef { FuncDef(SFuncDef), FuncDecl(SFuncDecl), VarDef(SVarDef), VarDecl(SVarDecl), StructDef(SStructDef), UnionDef(SStructDef), EnumDef(SEnumDef) } }
+common_struct! { #[derive(Hash)] pub struct SFuncDef {pub alias: String,  pub typ: Type, pub fps: Vec<(String, Type)>, pub body: Vec<Spanned<SStmt>>, #[serde(default, skip_serializing_if = "Vec::is_empty")] pub attrs: Vec<SAttr>, #[serde(default, skip_serializing_if = "std::ops::Not::not")] pub is_static: bool, #[serde(default, skip_serializing_if = "std::ops::Not::not")] pub implicit_int: bool, #[serde(default, skip_serializing_if = "Vec::is_empty")] pub contracts: Vec<SContract> } } // fp needs Type for statics, and String for dynamics. static ones aren't exported when units are linked. implicit_int ones had no return type, which c89 takes as int. contracts are its requires and ensures
+common_struct! { #[derive(Hash)] pub struct SFuncDecl { pub alias: String, pub typ: Type, pub fps: Vec<Type>, #[serde(default, skip_serializing_if = "std::ops::Not::not")] pub variadic: bool } } // a prototype: int add(int, int); params needn't be named. variadic ones end in ...
+common_enum! { #[derive(Hash)] pub enum SAttr { NoInline, AlwaysInline, Optimize(OptLevel) } } // optimize compiles the function at its own level. there's no inliner, so every function is already noinline, and always_inline has no effect
+common_struct! { #[derive(Hash)] pub struct SStructDef { pub alias: String, pub fields: Vec<(String, Type)> } }
+common_struct! { #[derive(Hash)] pub struct SEnumDef { pub alias: Option<String>, pub enumerators: Vec<(String, i32)> } } // enumerators are already resolved to their values in the tree
+common_struct! { #[derive(Hash)] pub struct SVarDef { pub alias: String, pub typ: Type, pub expr: Box<Spanned<SExpr>>, #[serde(default, skip_serializing_if = "std::ops::Not::not")] pub constant: bool, #[serde(default, skip_serializing_if = "std::ops::Not::not")] pub is_static: bool }} // const variables are only ever assigned their initializer. static ones are initialized once, before main
+common_struct! { #[derive(Hash)] pub struct SVarDecl { pub alias: String, pub typ: Type, #[serde(default, skip_serializing_if = "std::ops::Not::not")] pub constant: bool, #[serde(default, skip_serializing_if = "std::ops::Not::not")] pub is_static: bool }} // no initializer: int a[10]; static ones are zeroed
+common_struct! { #[derive(Hash)] pub struct SVarUpdate { pub alias: String, pub expr: Box<Spanned<SExpr>> }}
+common_struct! { #[derive(Hash)] pub struct SCase { pub label: Option<i32>, pub body: Vec<Spanned<SStmt>> }} // None is default. each arm falls through into the next
+common_enum! { #[derive(Hash)] pub enum SContractKind { Requires, Ensures, LoopInvariant, Assert } }
+common_struct! { #[derive(Hash)] pub struct SContract { pub kind: SContractKind, pub expr: Spanned<SExpr> }} // c0's //@requires e; annotations, which c takes for comments. they're only checked by interpretc0 --dyn-check. ensures names the return value \result
+pub const RESULT: &str = "\\result";
+common_struct! { #[derive(Hash)] pub struct SStore { pub lval: Box<Spanned<SExpr>>, pub expr: Box<Spanned<SExpr>>, #[serde(default, skip_serializing_if = "Option::is_none")] pub op: Option<SBinOp> }} // assignments to other lvalues: *p = e, a[i] = e. compound ones, a[i] += e, have their op, and take lval's address once
 
 common_enum! {
+    #[derive(Hash)]
     pub enum SStmt {
-        IfEls { cond: Box<SExpr>, then: Box<SStmt>, els: Option<Box<SStmt>> }, While { cond: Box<SExpr>, body: Box<SStmt> }, // control
-        Asnmt(SVarDef), Return(SExpr), // bindings (intros in C)
+        IfEls { cond: Box<Spanned<SExpr>>, then: Box<Spanned<SStmt>>, els: Option<Box<Spanned<SStmt>>> }, Switch { cond: Box<Spanned<SExpr>>, arms: Vec<SCase> }, Label(String), Goto(String), Block(Vec<Spanned<SStmt>>), // control. labels are scoped to their function, locals to their block
+        While { cond: Box<Spanned<SExpr>>, body: Vec<Spanned<SStmt>>, #[serde(default, skip_serializing_if = "Vec::is_empty")] invariants: Vec<SContract> }, DoWhile { body: Vec<Spanned<SStmt>>, cond: Box<Spanned<SExpr>> }, For { asnmt: Box<Spanned<SStmt>>, cond: Box<Spanned<SExpr>>, update: Box<Spanned<SStmt>>, body: Vec<Spanned<SStmt>>, #[serde(default, skip_serializing_if = "Vec::is_empty")] invariants: Vec<SContract> }, Break, Continue, // loops. invariants hold each time the cond is about to be tested
+        Asnmt(SVarDef), Decl(SVarDecl), Update(SVarUpdate), Store(SStore), // bindings (intros in C)
+        Return(Option<Spanned<SExpr>>), Call(Spanned<SExpr>), // functions. void ones return nothing, and a call stmt drops its value
+        Contract(SContract), Assert(Spanned<SExpr>), // //@assert e; is a contract, but c0's assert(e); is always checked
     }
 }
 
 common_enum! {
     #[rustfmt::skip]
+    #[derive(Hash)]
     pub enum SExpr {
         // intros
-        Int(i32), Bool(bool),
+        Int(i32), Float { lit: String, single: bool }, Bool(bool), Str(String), // floats are kept as written, 1.5f being single. strings are pooled read-only, and evaluate to their address
+        TypedInt { val: i32, typ: Type }, // int literals that aren't ints, by their suffix or by not fitting: 42u, 42L, 0xFFFFFFFF. val holds their 32 bits
 
         // elims
-        UnaryE { op: SUnaryOp, l: Box<SExpr> }, BinE { op: SBinOp, l: Box<SExpr>, r: Box<SExpr> }, LogE { op: SLogOp, l: Box<SExpr>, r: Box<SExpr> },
-        BitE { op: SBitOp, l: Box<SExpr>, r: Box<SExpr> }, RelE { op: SRelOp, l: Box<SExpr>, r: Box<SExpr> },
-        VarApp(String), FuncApp{ alias: String, aps: Vec<SExpr> }
+        UnaryE { op: SUnaryOp, l: Box<Spanned<SExpr>> }, BinE { op: SBinOp, l: Box<Spanned<SExpr>>, r: Box<Spanned<SExpr>> }, LogE { op: SLogOp, l: Box<Spanned<SExpr>>, r: Box<Spanned<SExpr>> },
+        BitE { op: SBitOp, l: Box<Spanned<SExpr>>, r: Box<Spanned<SExpr>> }, RelE { op: SRelOp, l: Box<Spanned<SExpr>>, r: Box<Spanned<SExpr>> },
+        VarApp(String), FuncApp{ alias: String, aps: Vec<Spanned<SExpr>> }, IncDec { op: SIncDecOp, lval: Box<Spanned<SExpr>> }, // ++ and --'s lval is x, *e, a[e] or e.f, whose address is taken once
+        CondE { cond: Box<Spanned<SExpr>>, then: Box<Spanned<SExpr>>, els: Box<Spanned<SExpr>> }, // only the taken branch is evaluated
+        AddrOf(Box<Spanned<SExpr>>), Deref(Box<Spanned<SExpr>>), Index { alias: String, idx: Box<Spanned<SExpr>> }, Member { base: Box<Spanned<SExpr>>, field: String }, // pointers, arrays and structs. &'s operand is an lvalue: x, *e, a[e] or e.f
+        SizeOf(Box<Spanned<SExpr>>), SizeOfType(Type), // constants. sizeof's operand is typed, never evaluated
+        Alloc(Type), AllocArray(Type, Box<Spanned<SExpr>>), // c0's heap. alloc(T) is a zeroed T's address, alloc_array(T, n) the first of n's, and only the evaluator runs them
+        Cast(Type, Box<Spanned<SExpr>>), // (T) e converts e's value to T as assigning it would, truncating or extending ints and truncating floats toward zero
     }
 }
 
-common_enum! { pub enum SLogOp { And, Or } }
-common_enum! { pub enum SBitOp { And, Or, Xor } }
-common_enum! { pub enum SRelOp { Eq, Neq, And, Or, LtEq, Lt, GtEq, Gt } }
-common_enum! { pub enum SBinOp { Add, Sub, Mult, Div, Mod } }
-common_enum! { pub enum SUnaryOp { Add, Sub } }
+common_enum! { #[derive(Hash)] pub enum SLogOp { And, Or } }
+common_enum! { #[derive(Hash)] pub enum SBitOp { And, Or, Xor, Shl, Shr } }
+common_enum! { #[derive(Hash)] pub enum SRelOp { Eq, Neq, LtEq, Lt, GtEq, Gt } }
+common_enum! { #[derive(Hash)] pub enum SBinOp { Add, Sub, Mult, Div, Mod } }
+common_enum! { #[derive(Hash)] pub enum SUnaryOp { Add, Sub, Not, BitNot } }
+common_enum! { #[derive(Hash)] pub enum SIncDecOp { PreInc, PreDec, PostInc, PostDec } } // pre yields the updated value, post the old one
 
 //
 //
@@ -276,9 +704,12 @@ common_enum! { pub enum SUnaryOp { Add, Sub } }
 type IPrg = Vec<IStmt>;
 common_enum! {
     pub enum IStmt {
-        Jump(Label), CJump(SExpr, Label, Label), // control
-        Compute(Temp, IExpr), Load(Temp, RiscvPointerReg), Store(RiscvPointerReg, Temp), // bindings
-        Seq(Label, Vec<Box<IStmt>>), Return(IExpr), // functions
+        Jump(Label), CJump(IExpr, Label, Label), Label(Label), JumpTable(IExpr, Vec<Label>), // control. jump tables index their labels, so the expr must be in range
+        Compute(Temp, IExpr), Load(Temp, RiscvPointerReg), Store(RiscvPointerReg, Temp), Move(IExpr, IExpr), MoveByte(IExpr, IExpr), // bindings. move stores the second expr at the first's address, and movebyte only its low byte
+        Seq(Label, Vec<Box<IStmt>>), Return(Option<IExpr>), // functions
+        Alloca(Temp, usize), // reserves words in the frame, for arrays and uninitialized locals
+        Scope(Vec<Box<IStmt>>), // a nested block. its locals' slots are reused once it ends
+        Static(String, Vec<i32>, bool), // a variable with static storage, its initial words, and whether other files see it. its uses are temps of the same name
         // todo: maybe rename seq to func if not used for conditionals
     }
 }
@@ -289,14 +720,20 @@ common_enum! {
 
 common_enum! {
     pub enum IExpr {
-        Const(i32), BinOp(IBinOp, Box<IExpr>, Box<IExpr>), // arithmetic``
-        TempUse(Temp), // bindings
+        Const(i32), BinOp(IBinOp, Box<IExpr>, Box<IExpr>), BitOp(IBitOp, Box<IExpr>, Box<IExpr>), // arithmetic``
+        FloatOp(IFloatOp, Box<IExpr>, Box<IExpr>), Cvt(ICvtOp, Box<IExpr>), // floats are a word of ieee-754 bits, and only convert explicitly
+        Not(Box<IExpr>), LogOp(ILogOp, Box<IExpr>, Box<IExpr>), Cond(Box<IExpr>, Box<IExpr>, Box<IExpr>), // logic, short circuiting
+        TempUse(Temp), ESeq(Box<IStmt>, Box<IExpr>), // bindings. eseq runs the stmt for its effect, then yields the expr
+        Addr(Temp), Mem(Box<IExpr>), MemByte(Box<IExpr>, bool), Str(String), // pointers: a local's address, the word at an address, the byte at one, zero extended if the bool says it's unsigned and sign extended if not, and a pooled string's address
         Call(Label, Vec<IExpr>), // functions
     }
 }
 
-common_enum! { pub enum IBinOp { Add, Sub, Mult, Div, Mod } }
-common_enum! { pub enum IBitOp { And, Or, Xor } }
+common_enum! { pub enum ILogOp { And, Or } }
+common_enum! { pub enum IBinOp { Add, Sub, Mult, Div, Mod, Lt, LtEq, Gt, GtEq, Eq, Neq, AddU, SubU, MultU, DivU, ModU, LtU, LtEqU, GtU, GtEqU, AddPtr } } // comparisons produce 0 or 1. the u ops read their operands as unsigned, and narrow their result as one. addptr offsets an address, so it's never narrowed to an int
+common_enum! { pub enum IBitOp { And, Or, Xor, Shl, ShlU, Shr, ShrU } } // >> is arithmetic on signed ints, logical on unsigned ones
+common_enum! { pub enum IFloatOp { Add, Sub, Mult, Div, Lt, LtEq, Gt, GtEq, Eq, Neq } } // single precision. todo: doubles
+common_enum! { pub enum ICvtOp { IntToFloat, UIntToFloat, FloatToInt, FloatToUInt } } // to ints, truncating toward zero
 common_enum! { pub enum IRelOp { Eq, Neq, And, Or, LtEq, Lt, GtEq, Gt } }
 
 //
@@ -318,36 +755,42 @@ common_enum! { pub enum IRelOp { Eq, Neq, And, Or, LtEq, Lt, GtEq, Gt } }
 type Imm = i32;
 common_enum! { pub enum Temp { UserTemp(String), MachineTemp(usize), PointerReg(RiscvPointerReg) } } // only util regs in abstract assembly
 common_enum! { pub enum Label { UserLabel(String), MachineLabel(usize) } }
-impl ToString for Label {
-    fn to_string(&self) -> String {
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Label::UserLabel(l) => l.clone(),
-            Label::MachineLabel(_l) => todo!(),
+            Label::UserLabel(l) => write!(f, "{l}"),
+            Label::MachineLabel(l) => write!(f, ".L{l}"),
         }
     }
 }
 
-// FIXME: no static mut
-static mut TEMP_COUNTER: usize = 0;
-static mut LABEL_COUNTER: usize = 0;
+// thread local so that each compilation (and each test) counts from zero
+thread_local! {
+    static TEMP_COUNTER: Cell<usize> = const { Cell::new(0) };
+    static LABEL_COUNTER: Cell<usize> = const { Cell::new(0) };
+}
 
 pub fn fresh_temp() -> Temp {
-    unsafe {
-        let temp = TEMP_COUNTER;
-        TEMP_COUNTER += 1;
-        Temp::MachineTemp(temp)
-    }
+    let temp = TEMP_COUNTER.replace(TEMP_COUNTER.get() + 1);
+    Temp::MachineTemp(temp)
 }
 
 pub fn fresh_label() -> Label {
-    unsafe {
-        let label = LABEL_COUNTER;
-        LABEL_COUNTER += 1;
-        Label::MachineLabel(label)
-    }
+    let label = LABEL_COUNTER.replace(LABEL_COUNTER.get() + 1);
+    Label::MachineLabel(label)
 }
 
 common_enum! { pub enum RiscvPointerReg { Z, Ra, Sp, Gp, Tp, Fp, A0, A1, A2, A3, A4, A5, A6, A7, Pc } }
+pub const ARG_REGS: [RiscvPointerReg; 8] = [
+    RiscvPointerReg::A0,
+    RiscvPointerReg::A1,
+    RiscvPointerReg::A2,
+    RiscvPointerReg::A3,
+    RiscvPointerReg::A4,
+    RiscvPointerReg::A5,
+    RiscvPointerReg::A6,
+    RiscvPointerReg::A7,
+];
 impl From<RiscvPointerReg> for RscvReg {
     fn from(ptr: RiscvPointerReg) -> Self {
         match ptr {
@@ -370,24 +813,24 @@ impl From<RiscvPointerReg> for RscvReg {
     }
 }
 
-impl ToString for RiscvPointerReg {
-    fn to_string(&self) -> String {
+impl fmt::Display for RiscvPointerReg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            RiscvPointerReg::Z => "zero".to_string(),
-            RiscvPointerReg::Ra => "ra".to_string(),
-            RiscvPointerReg::Sp => "sp".to_string(),
-            RiscvPointerReg::Gp => "gp".to_string(),
-            RiscvPointerReg::Tp => "tp".to_string(),
-            RiscvPointerReg::Fp => "fp".to_string(),
-            RiscvPointerReg::A0 => "a0".to_string(),
-            RiscvPointerReg::A1 => "a1".to_string(),
-            RiscvPointerReg::A2 => "a2".to_string(),
-            RiscvPointerReg::A3 => "a3".to_string(),
-            RiscvPointerReg::A4 => "a4".to_string(),
-            RiscvPointerReg::A5 => "a5".to_string(),
-            RiscvPointerReg::A6 => "a6".to_string(),
-            RiscvPointerReg::A7 => "a7".to_string(),
-            RiscvPointerReg::Pc => "pc".to_string(),
+            RiscvPointerReg::Z => write!(f, "zero"),
+            RiscvPointerReg::Ra => write!(f, "ra"),
+            RiscvPointerReg::Sp => write!(f, "sp"),
+            RiscvPointerReg::Gp => write!(f, "gp"),
+            RiscvPointerReg::Tp => write!(f, "tp"),
+            RiscvPointerReg::Fp => write!(f, "fp"),
+            RiscvPointerReg::A0 => write!(f, "a0"),
+            RiscvPointerReg::A1 => write!(f, "a1"),
+            RiscvPointerReg::A2 => write!(f, "a2"),
+            RiscvPointerReg::A3 => write!(f, "a3"),
+            RiscvPointerReg::A4 => write!(f, "a4"),
+            RiscvPointerReg::A5 => write!(f, "a5"),
+            RiscvPointerReg::A6 => write!(f, "a6"),
+            RiscvPointerReg::A7 => write!(f, "a7"),
+            RiscvPointerReg::Pc => write!(f, "pc"),
         }
     }
 }
@@ -404,60 +847,109 @@ common_enum! {
     pub enum TQuad {
         Reg(TRegOp, Temp, Temp, Temp),
         Imm(TImmOp, Temp, Temp, Imm),
-        Mem(TMemOp, Temp, usize, RiscvPointerReg),
+        Mem(TMemOp, Temp, Imm, RiscvPointerReg), Deref(TMemOp, Temp, Temp), // deref's address is computed, not a fixed register
+        Str(Temp, String), // the address of a string in the runtime's .rodata pool, a byte per char, as c libraries read them
+        Static(Temp, String), Data(String, Vec<Imm>, bool), // a static's address, and its words in .data, or .bss when they're all zero. globals are .globl
+        Branch(TRegOp, Temp, Temp, Label),
+        Float(TFloatOp, Temp, Temp, Temp), Cvt(TCvtOp, Temp, Temp), // rv32f. operands pass through the float registers
+        JumpTable(Temp, Label, Vec<Label>), // the table's own label, then its targets
         Pseudo(PseudoOp),
         Label(Label),
     }
 }
 
-common_enum! { pub enum TRegOp { Add, Sub, And, Or, Xor, Beq, Bneq, Bge, Blt, Jal } }
-common_enum! { pub enum TImmOp { AddI, SubI, AndI, OrI, XorI } }
-common_enum! { pub enum TMemOp { Load, Store } }
-common_enum! { pub enum PseudoOp { Call(Label), Ret } }
+common_enum! { pub enum TRegOp { Add, AddPtr, Sub, Mul, Div, Rem, Addu, Subu, Mulu, Sllu, Divu, Remu, And, Or, Xor, Sll, Sra, Srl, Slt, Sgt, Sltu, Sgtu, Beq, Bneq, Bge, Blt, Jal } } // addu, subu, mulu and sllu are add, sub, mul and sll on unsigned ints, which narrow by zero extending
+common_enum! { pub enum TImmOp { AddI, SubI, AndI, OrI, XorI, SltIU } }
+common_enum! { pub enum TFloatOp { FAdd, FSub, FMul, FDiv, FLt, FLe, FEq } }
+common_enum! { pub enum TCvtOp { FcvtSW, FcvtSWu, FcvtWS, FcvtWuS } }
+common_enum! { pub enum TMemOp { Load, Store, LoadByte, LoadByteU, StoreByte } } // the byte ops are for chars, which are a byte in memory
+common_enum! { pub enum PseudoOp { Call(Label), CallC(Label), Jump(Label), Ret } } // callc calls a function compiled by a c compiler, with sp aligned as its abi expects
+
+impl fmt::Display for TRegOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TRegOp::Add | TRegOp::AddPtr | TRegOp::Addu => write!(f, "add"),
+            TRegOp::Sub | TRegOp::Subu => write!(f, "sub"),
+            TRegOp::Mul | TRegOp::Mulu => write!(f, "mul"),
+            TRegOp::Div => write!(f, "div"),
+            TRegOp::Rem => write!(f, "rem"),
+            TRegOp::Divu => write!(f, "divu"),
+            TRegOp::Remu => write!(f, "remu"),
+            TRegOp::And => write!(f, "and"),
+            TRegOp::Or => write!(f, "or"),
+            TRegOp::Xor => write!(f, "xor"),
+            TRegOp::Sll | TRegOp::Sllu => write!(f, "sll"),
+            TRegOp::Sra => write!(f, "sra"),
+            TRegOp::Srl => write!(f, "srl"),
+            TRegOp::Slt => write!(f, "slt"),
+            TRegOp::Sgt => write!(f, "sgt"), // pseudo
+            TRegOp::Sltu => write!(f, "sltu"),
+            TRegOp::Sgtu => write!(f, "sgtu"), // pseudo
+            TRegOp::Beq => write!(f, "beq"),
+            TRegOp::Bneq => write!(f, "bne"),
+            TRegOp::Bge => write!(f, "bge"),
+            TRegOp::Blt => write!(f, "blt"),
+            TRegOp::Jal => write!(f, "jal"),
+        }
+    }
+}
+
+impl fmt::Display for TImmOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TImmOp::AddI => write!(f, "addi"),
+            TImmOp::SubI => write!(f, "subi"),
+            TImmOp::AndI => write!(f, "andi"),
+            TImmOp::OrI => write!(f, "ori"),
+            TImmOp::XorI => write!(f, "xori"),
+            TImmOp::SltIU => write!(f, "sltiu"),
+        }
+    }
+}
 
-impl ToString for TRegOp {
-    fn to_string(&self) -> String {
+impl fmt::Display for TFloatOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            TRegOp::Add => "add".to_string(),
-            TRegOp::Sub => "sub".to_string(),
-            TRegOp::And => "and".to_string(),
-            TRegOp::Or => "or".to_string(),
-            TRegOp::Xor => "xor".to_string(),
-            TRegOp::Beq => "beq".to_string(),
-            TRegOp::Bneq => "bne".to_string(),
-            TRegOp::Bge => "bge".to_string(),
-            TRegOp::Blt => "blt".to_string(),
-            TRegOp::Jal => "jal".to_string(),
+            TFloatOp::FAdd => write!(f, "fadd.s"),
+            TFloatOp::FSub => write!(f, "fsub.s"),
+            TFloatOp::FMul => write!(f, "fmul.s"),
+            TFloatOp::FDiv => write!(f, "fdiv.s"),
+            TFloatOp::FLt => write!(f, "flt.s"),
+            TFloatOp::FLe => write!(f, "fle.s"),
+            TFloatOp::FEq => write!(f, "feq.s"),
         }
     }
 }
 
-impl ToString for TImmOp {
-    fn to_string(&self) -> String {
+impl fmt::Display for TCvtOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            TImmOp::AddI => "addi".to_string(),
-            TImmOp::SubI => "subi".to_string(),
-            TImmOp::AndI => "andi".to_string(),
-            TImmOp::OrI => "ori".to_string(),
-            TImmOp::XorI => "xori".to_string(),
+            TCvtOp::FcvtSW => write!(f, "fcvt.s.w"),
+            TCvtOp::FcvtSWu => write!(f, "fcvt.s.wu"),
+            TCvtOp::FcvtWS => write!(f, "fcvt.w.s"),
+            TCvtOp::FcvtWuS => write!(f, "fcvt.wu.s"),
         }
     }
 }
 
-impl ToString for TMemOp {
-    fn to_string(&self) -> String {
+impl fmt::Display for TMemOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            TMemOp::Load => "lw".to_string(),
-            TMemOp::Store => "sw".to_string(),
+            TMemOp::Load => write!(f, "lw"),
+            TMemOp::Store => write!(f, "sw"),
+            TMemOp::LoadByte => write!(f, "lb"),
+            TMemOp::LoadByteU => write!(f, "lbu"),
+            TMemOp::StoreByte => write!(f, "sb"),
         }
     }
 }
 
-impl ToString for PseudoOp {
-    fn to_string(&self) -> String {
+impl fmt::Display for PseudoOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            PseudoOp::Call(l) => format!("call {}", l.to_string()),
-            PseudoOp::Ret => "ret".to_string(),
+            PseudoOp::Call(l) | PseudoOp::CallC(l) => write!(f, "call {l}"),
+            PseudoOp::Jump(l) => write!(f, "j {l}"),
+            PseudoOp::Ret => write!(f, "ret"),
         }
     }
 }
@@ -473,3 +965,94 @@ common_enum! {
         Pc, // program counter
     }
 }
+
+#[cfg(test)]
+mod test_api {
+    use super::parser_ast::MAX_DEPTH;
+    use super::Config;
+    use std::fs;
+    use std::path::Path;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared";
+
+    #[test]
+    fn interpret() {
+        let path = format!("{TEST_DIR}/arith/add.c");
+        let src = fs::read_to_string(&path).expect("file dne");
+        let exit = super::interpret(&src, Path::new(&path), &Config::default()).unwrap();
+        assert_eq!(exit, 19);
+
+        let tokens = super::lex(&src).unwrap();
+        assert_eq!(super::parse(&tokens).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn compile() {
+        let path = format!("{TEST_DIR}/arith/add.c");
+        let src = fs::read_to_string(&path).expect("file dne");
+        let assembly = super::compile(&src, Path::new(&path), &Config::default()).unwrap();
+        assert!(assembly.lines().any(|l| l == "main:"));
+    }
+
+    #[test]
+    fn errors() {
+        // every syntax error, at where it is in the file
+        let path = format!("{TEST_DIR}/control/syntax_errors.c");
+        let src = fs::read_to_string(&path).expect("file dne");
+        let err = super::interpret(&src, Path::new(&path), &Config::default()).unwrap_err();
+        insta::assert_snapshot!(err.to_string().replace(TEST_DIR, ""), @r###"
+        /control/syntax_errors.c:2:17: token not recognizable PuncSemiColon
+        /control/syntax_errors.c:6:18: expected: PuncSemiColon got: LiteralInt
+        /control/syntax_errors.c:12:17: token not recognizable PuncSemiColon
+        /control/syntax_errors.c:17:5: expected: Alias got: Equals
+        "###);
+
+        let err = super::lex("int x = 1;\nint $y;").unwrap_err();
+        assert_eq!(err.to_string(), "2:5: unexpected token: '$'");
+    }
+
+    #[test]
+    fn fuzz() {
+        let err = super::fuzz_lex(b"int x = \xff;").unwrap_err();
+        assert_eq!(err.to_string(), "invalid utf-8 at byte 8");
+        assert_eq!(super::fuzz_lex(b"int x;").unwrap().len(), 3);
+
+        // cut off inside a parameter list and an argument list
+        for src in ["int printf(char *fmt", "int main() { return f(1,"] {
+            assert!(super::fuzz_parse(src.as_bytes()).is_err());
+        }
+
+        // as deep as blocks and pointers may nest, and then too deep
+        let nested = |n: usize| {
+            let blocks = format!("{}{}", "{".repeat(n - 1), "}".repeat(n - 1));
+            format!(
+                "int {}p; int main() {{ {blocks} return 0; }}",
+                "*".repeat(n)
+            )
+        };
+        assert!(super::fuzz_parse(nested(MAX_DEPTH).as_bytes()).is_ok());
+        let err = super::fuzz_parse(nested(MAX_DEPTH + 1).as_bytes()).unwrap_err();
+        assert_eq!(err.to_string(), "nesting deeper than 63 levels");
+        let err = super::fuzz_parse(
+            format!("int main() {{ return {}1; }}", "!".repeat(10000)).as_bytes(),
+        );
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "nesting deeper than 63 levels"
+        );
+
+        // a chain of operators is a tree as deep as it's long, so it's
+        // bounded too, before the tree's too deep for the passes after the
+        // parser, or even to drop
+        let chain = |n: usize| format!("int main() {{ int x = 1; return {}1; }}", "x + ".repeat(n));
+        assert!(super::fuzz_parse(chain(60).as_bytes()).is_ok());
+        for src in [
+            chain(200_000),
+            format!("int main() {{ return {}1; }}", "1 < ".repeat(3000)),
+            format!("int main() {{ return s{}; }}", ".x".repeat(200_000)),
+        ] {
+            let err = super::fuzz_parse(src.as_bytes()).unwrap_err();
+            assert_eq!(err.to_string(), "nesting deeper than 63 levels");
+        }
+    }
+}
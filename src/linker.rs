@@ -0,0 +1,421 @@
+use crate::{
+    allocator, ir, optimizer, pic, runtime, source, translator, IntModel, OptLevel, SDef, SPrg,
+    Type,
+};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// several translation units, compiled apart and linked into one executable.
+// each unit's non-static functions and globals are exported with .globl,
+// and the others call them as picoc functions. only the unit with main
+// carries the whole runtime: the rest pool their own strings and symbols,
+// and report faults through main's __picoc_func. symbols are resolved
+// across units before any is compiled, since the toolchain's errors name
+// neither the source files nor the lines.
+// todo: extern, so a unit can use a global another defines
+pub struct Unit {
+    pub file: String,
+    pub prg: SPrg,
+}
+
+// where each external symbol is defined, and the prototypes in units that
+// don't define what they declare
+struct Symbols<'a> {
+    defs: HashMap<&'a str, Vec<Def<'a>>>,
+    decls: Vec<(&'a str, &'a str, Sig<'a>)>,
+}
+
+type Sig<'a> = (&'a Type, Vec<&'a Type>); // the return type, then the params'
+
+struct Def<'a> {
+    file: &'a str,
+    is_static: bool,
+    sig: Option<Sig<'a>>, // none for variables
+}
+
+fn symbols(units: &[Unit]) -> Symbols<'_> {
+    let mut defs: HashMap<&str, Vec<Def>> = HashMap::new();
+    let mut decls = vec![];
+    for u in units {
+        let file = u.file.as_str();
+        for def in &u.prg {
+            let (alias, def) = match &def.node {
+                SDef::FuncDef(fd) => {
+                    let sig = (&fd.typ, fd.fps.iter().map(|(_, t)| t).collect());
+                    let is_static = fd.is_static;
+                    (
+                        &fd.alias,
+                        Def {
+                            file,
+                            is_static,
+                            sig: Some(sig),
+                        },
+                    )
+                }
+                SDef::VarDef(vd) => (
+                    &vd.alias,
+                    Def {
+                        file,
+                        is_static: vd.is_static,
+                        sig: None,
+                    },
+                ),
+                SDef::VarDecl(vd) => (
+                    &vd.alias,
+                    Def {
+                        file,
+                        is_static: vd.is_static,
+                        sig: None,
+                    },
+                ),
+                SDef::FuncDecl(fd) => {
+                    let sig = (&fd.typ, fd.fps.iter().collect());
+                    decls.push((file, fd.alias.as_str(), sig));
+                    continue;
+                }
+                SDef::StructDef(_) | SDef::UnionDef(_) | SDef::EnumDef(_) => continue,
+            };
+            defs.entry(alias).or_default().push(def);
+        }
+    }
+    decls.retain(|(file, alias, _)| {
+        !defs
+            .get(alias)
+            .is_some_and(|ds| ds.iter().any(|d| d.file == *file))
+    });
+
+    Symbols { defs, decls }
+}
+
+// every error across the units, a line each. a prototype with no
+// definition in any unit is taken to be a c function's, like printf's
+pub fn check(units: &[Unit]) -> Result<(), io::Error> {
+    let Symbols { defs, decls } = symbols(units);
+    let mut errs = vec![];
+
+    let mut aliases = defs.keys().copied().collect::<Vec<_>>();
+    aliases.sort_unstable();
+    for alias in aliases {
+        let exported = defs[alias]
+            .iter()
+            .filter(|d| !d.is_static)
+            .collect::<Vec<_>>();
+        if let [first, rest @ ..] = exported.as_slice() {
+            for d in rest {
+                errs.push(format!(
+                    "multiple definition of {alias}: in {} and {}",
+                    first.file, d.file
+                ));
+            }
+        }
+    }
+
+    for (file, alias, sig) in decls {
+        let Some(ds) = defs.get(alias) else {
+            continue;
+        };
+        match ds.iter().find(|d| !d.is_static) {
+            Some(Def {
+                file: def_file,
+                sig: Some(def_sig),
+                ..
+            }) if *def_sig != sig => errs.push(format!(
+                "conflicting types for {alias}: declared in {file}, defined in {def_file}"
+            )),
+            Some(Def {
+                file: def_file,
+                sig: None,
+                ..
+            }) => errs.push(format!(
+                "{alias} is declared a function in {file}, but is a variable in {def_file}"
+            )),
+            Some(_) => (),
+            None => errs.push(format!(
+                "undefined reference to {alias} in {file}: its definition in {} is static",
+                ds[0].file
+            )),
+        }
+    }
+
+    let mains = defs
+        .get("main")
+        .map_or(0, |ds| ds.iter().filter(|d| d.sig.is_some()).count());
+    if mains == 0 {
+        let files = units.iter().map(|u| u.file.as_str()).collect::<Vec<_>>();
+        errs.push(format!("main function not found in {}", files.join(", ")));
+    }
+
+    match errs.is_empty() {
+        true => Ok(()),
+        false => Err(io::Error::other(errs.join("\n"))),
+    }
+}
+
+// each unit's assembly, in the order given. the units are checked first
+pub fn compile(units: &[Unit], opt: OptLevel, model: &IntModel, pic: bool) -> Vec<Vec<String>> {
+    units
+        .iter()
+        .enumerate()
+        .map(|(i, u)| {
+            let linked = units
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .flat_map(|(_, u)| exports(&u.prg))
+                .collect::<HashSet<_>>();
+            let trgt_tree = translator::translate_for(&u.prg, model);
+            let cfg = ir::lower(&trgt_tree, &linked, model);
+            let levels = optimizer::levels(&u.prg);
+            let abs_as = optimizer::optimize(cfg, &opt, &levels, model).quads();
+            let assembly =
+                allocator::allocate_levels(&abs_as, opt.clone(), &levels, model).render();
+
+            // main is always exported
+            let globls = exports(&u.prg)
+                .into_iter()
+                .filter(|f| *f != "main")
+                .map(|f| format!(".globl {f}"));
+            let rt = match exports(&u.prg).contains(&"main") {
+                true => [runtime::runtime(&abs_as, model), runtime::exports()].concat(),
+                false => runtime::unit(&abs_as, model),
+            };
+            let assembly = assembly
+                .into_iter()
+                .chain(globls)
+                .chain(rt)
+                .collect::<Vec<_>>();
+            match pic {
+                true => pic::relocate(&assembly),
+                false => assembly,
+            }
+        })
+        .collect()
+}
+
+// the functions a unit defines for the others
+fn exports(prg: &SPrg) -> Vec<&str> {
+    prg.iter()
+        .filter_map(|def| match &def.node {
+            SDef::FuncDef(fd) if !fd.is_static => Some(fd.alias.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+// the assembled sources linked into bin, with PICOC_CC, since the runtime
+// needs a hosted libc. it defaults to the gcc for the word the sources were
+// compiled with
+pub fn link(srcs: &[PathBuf], bin: &Path, word: i32) -> Result<(), io::Error> {
+    let cc = env::var("PICOC_CC").unwrap_or(format!("riscv{}-unknown-linux-gnu-gcc", word * 8));
+    let linked = Command::new(&cc)
+        .args(["-static", "-o"])
+        .arg(bin)
+        .args(srcs)
+        .output()
+        .map_err(|e| io::Error::other(format!("unable to run {cc}: {e}")))?;
+    if !linked.status.success() {
+        let stderr = String::from_utf8_lossy(&linked.stderr).into_owned();
+        return Err(io::Error::other(ToolchainError { cc, stderr }));
+    }
+    Ok(())
+}
+
+// what the assembler or linker said, as it said it
+#[derive(Debug, thiserror::Error)]
+#[error("{cc} failed:\n{stderr}")]
+pub struct ToolchainError {
+    pub cc: String,
+    pub stderr: String,
+}
+
+// the toolchain's errors, each located at the definition or prototype it's
+// about along with the index of its unit, when that's clear. an assembler
+// error is at the function its line is in, and an undefined reference at
+// the prototypes of what's undefined. srcs are the units' .s files and
+// the assembly in each
+pub fn diagnose(
+    e: &ToolchainError,
+    units: &[Unit],
+    srcs: &[(PathBuf, Vec<String>)],
+) -> Vec<(Option<usize>, io::Error)> {
+    let mut diags = vec![];
+    for line in e.stderr.lines() {
+        if let Some((at, msg)) = line.split_once(": Error: ") {
+            let located = at.rsplit_once(':').and_then(|(src, n)| {
+                let i = srcs
+                    .iter()
+                    .position(|(s, _)| s.ends_with(src) || Path::new(src).ends_with(s))?;
+                let func = enclosing(&srcs[i].1, n.parse().ok()?)?;
+                let span = units[i].prg.iter().find_map(|def| match &def.node {
+                    SDef::FuncDef(fd) if fd.alias == func => Some(def.span),
+                    _ => None,
+                })?;
+                Some((i, span))
+            });
+            let err = io::Error::other(format!("assembler: {msg}"));
+            diags.push(match located {
+                Some((i, span)) => (Some(i), source::locate(err, span)),
+                None => (None, io::Error::other(format!("assembler: {line}"))),
+            });
+        } else if let Some((_, sym)) = line.split_once("undefined reference to `") {
+            let sym = sym.trim_end_matches('\'');
+            let protos = units.iter().enumerate().flat_map(|(i, u)| {
+                u.prg.iter().filter_map(move |def| match &def.node {
+                    SDef::FuncDecl(fd) if fd.alias == sym => Some((i, def.span)),
+                    _ => None,
+                })
+            });
+            let msg = format!("undefined reference to {sym}: no file or library defines it");
+            let before = diags.len();
+            for (i, span) in protos {
+                diags.push((Some(i), source::locate(io::Error::other(msg.clone()), span)));
+            }
+            if diags.len() == before {
+                diags.push((None, io::Error::other(msg)));
+            }
+        }
+    }
+    if diags.is_empty() {
+        diags.push((None, io::Error::other(e.to_string())));
+    }
+    diags
+}
+
+// the function the 1-based line of assembly is in. elements of assembly
+// can hold several lines
+fn enclosing(assembly: &[String], line: usize) -> Option<String> {
+    let text = assembly.join("\n");
+    text.split('\n')
+        .take(line)
+        .filter_map(|l| l.trim().strip_suffix(':'))
+        .filter(|l| !l.starts_with('.') && !l.starts_with("__picoc"))
+        .last()
+        .map(str::to_owned)
+}
+
+#[cfg(test)]
+mod test_link {
+    use super::Unit;
+    use crate::{lexer, parser_ast, IntModel, OptLevel};
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/link";
+
+    fn units(files: &[&str]) -> Vec<Unit> {
+        files
+            .iter()
+            .map(|file| {
+                let src = fs::read_to_string(format!("{TEST_DIR}/{file}")).expect("file dne");
+                let tokens = lexer::lex(&src).unwrap();
+                let prg = parser_ast::parse_prg(&tokens).unwrap();
+                Unit {
+                    file: file.to_string(),
+                    prg,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resolved() {
+        let units = units(&["main.c", "math.c"]);
+        super::check(&units).unwrap();
+
+        let assembly = super::compile(&units, OptLevel::O0, &IntModel::default(), false);
+        let globls = assembly
+            .iter()
+            .map(|a| {
+                a.iter()
+                    .filter(|l| l.starts_with(".globl"))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        insta::assert_debug_snapshot!(globls, @r###"
+        [
+            [
+                ".globl main",
+                ".globl __picoc_func",
+                ".globl __picoc_div_zero",
+                ".globl __picoc_overflow",
+                ".globl __picoc_div_overflow",
+            ],
+            [
+                ".globl main",
+                ".globl calls",
+                ".globl add",
+                ".globl square",
+            ],
+        ]
+        "###);
+
+        // math.c's functions are called as picoc ones. only printf has its
+        // sp aligned for the c abi
+        let c = assembly[0]
+            .windows(2)
+            .filter(|w| w[0] == "sw t0, 0(sp)")
+            .map(|w| &w[1]);
+        insta::assert_debug_snapshot!(c.collect::<Vec<_>>(), @r###"
+        [
+            "call printf",
+        ]
+        "###);
+    }
+
+    #[test]
+    fn unresolved() {
+        let err = super::check(&units(&["main.c", "math.c", "dup.c"])).unwrap_err();
+        insta::assert_snapshot!(err.to_string(), @r###"
+        multiple definition of add: in math.c and dup.c
+        conflicting types for square: declared in dup.c, defined in math.c
+        undefined reference to twice in dup.c: its definition in math.c is static
+        calls is declared a function in dup.c, but is a variable in math.c
+        "###);
+
+        let err = super::check(&units(&["math.c"])).unwrap_err();
+        insta::assert_snapshot!(err.to_string(), @"main function not found in math.c");
+    }
+
+    #[test]
+    fn toolchain_errors() {
+        let units = units(&["main.c", "math.c"]);
+        let assembly = super::compile(&units, OptLevel::O0, &IntModel::default(), false);
+        let srcs = ["out/main.s", "out/math.s"]
+            .into_iter()
+            .map(Into::into)
+            .zip(assembly)
+            .collect::<Vec<_>>();
+        let line = srcs[1]
+            .1
+            .join("\n")
+            .split('\n')
+            .position(|l| l == "add:")
+            .unwrap()
+            + 2;
+
+        let e = super::ToolchainError {
+            cc: "cc".to_owned(),
+            stderr: format!(
+                "out/math.s: Assembler messages:\n\
+                 out/math.s:{line}: Error: illegal operands\n\
+                 /usr/bin/ld: main.o: in function `main':\n\
+                 (.text+0x1c): undefined reference to `square'\n"
+            ),
+        };
+        let diags = super::diagnose(&e, &units, &srcs)
+            .into_iter()
+            .map(|(i, e)| (i, crate::source::located(&e).map(|l| l.span), e.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(diags.len(), 2);
+        // at add's definition, and square's prototype
+        assert_eq!(
+            (diags[0].0, &*diags[0].2),
+            (Some(1), "assembler: illegal operands")
+        );
+        assert_eq!(diags[0].1, Some(units[1].prg[2].span));
+        assert_eq!(diags[1].0, Some(0));
+        assert_eq!(diags[1].1, Some(units[0].prg[2].span));
+    }
+}
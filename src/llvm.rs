@@ -0,0 +1,599 @@
+use crate::{
+    IBinOp, IBitOp, ICvtOp, IExpr, IFloatOp, ILogOp, IPrg, IStmt, IntModel, Label, RiscvPointerReg,
+    Temp, ARG_REGS,
+};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+// the ir as llvm's textual ir, for --emit llvm, so clang and opt can be
+// run over what picoc089 compiles. the ir is untyped, so every value is a
+// word, i32 or i64 as the model's are, like the registers the backend
+// keeps them in:
+//   - a function takes and returns words. one that returns nothing
+//     returns 0. every local is an alloca in the entry block, which
+//     opt's mem2reg promotes
+//   - addresses are words too, converted to and from ptr where they're
+//     loaded or stored through, so the triple is riscv's, where they fit
+//   - floats are their bits in a word, converted to float for each op
+//   - chars in memory are a byte, loaded as i8 and extended to a word, so
+//     strings are bytes too
+//   - shifts mask their amount, as riscv's do, rather than being poison
+//   - functions the program doesn't define are c's, declared variadic
+// todo: the runtime's checks, like __picoc_div_zero. division by zero is
+//       undefined here, and --overflow=trap's __picoc_addv and the like are
+//       left declared but never defined
+pub fn emit(prg: &IPrg, model: &IntModel) -> String {
+    let word = model.word() as usize;
+    let funcs = prg
+        .iter()
+        .filter_map(|s| match s {
+            IStmt::Seq(Label::UserLabel(f), _) => Some(f.as_str()),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+    let statics = prg
+        .iter()
+        .filter_map(|s| match s {
+            IStmt::Static(alias, _, _) => Some(alias.as_str()),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+    let mut module = Module {
+        word: format!("i{}", word * 8),
+        bits: word * 8,
+        funcs: &funcs,
+        statics: &statics,
+        strs: vec![],
+        externs: BTreeSet::new(),
+    };
+
+    let mut defs = vec![];
+    for s in prg {
+        match s {
+            IStmt::Static(alias, words, global) => {
+                let w = &module.word;
+                let linkage = if *global { "" } else { "internal " };
+                let words = words.iter().map(|v| format!("{w} {v}")).collect::<Vec<_>>();
+                defs.push(format!(
+                    "@{alias} = {linkage}global [{} x {w}] [{}]",
+                    words.len(),
+                    words.join(", ")
+                ));
+            }
+            IStmt::Seq(Label::UserLabel(f), body) => defs.push(module.func(f, body)),
+            _ => unreachable!("only functions and statics are at the top level"),
+        }
+    }
+
+    let mut lines = vec![format!(
+        "target triple = \"riscv{}-unknown-linux-gnu\"",
+        word * 8
+    )];
+    lines.push(String::new());
+    for (i, s) in module.strs.iter().enumerate() {
+        let line = format!(
+            "@__picoc_str_{i} = private unnamed_addr constant [{} x i8] c\"{}\\00\"",
+            s.len() + 1,
+            escape(s)
+        );
+        lines.push(line);
+    }
+    if !module.strs.is_empty() {
+        lines.push(String::new());
+    }
+    lines.push(defs.join("\n\n"));
+    if !module.externs.is_empty() {
+        lines.push(String::new());
+    }
+    for f in &module.externs {
+        lines.push(format!("declare {} @{f}(...)", module.word));
+    }
+    lines.join("\n") + "\n"
+}
+
+// what the functions share: the pooled strings, and the c functions called
+struct Module<'a> {
+    word: String,
+    bits: usize,
+    funcs: &'a HashSet<&'a str>,
+    statics: &'a HashSet<&'a str>,
+    strs: Vec<String>,
+    externs: BTreeSet<String>,
+}
+
+impl Module<'_> {
+    fn func(&mut self, f: &str, body: &[Box<IStmt>]) -> String {
+        let mut slots = HashMap::new();
+        lay_out(body, self.statics, &mut slots);
+        let mut func = Func {
+            module: self,
+            slots: &slots,
+            lines: vec![],
+            block: "entry".to_owned(),
+            terminated: false,
+            n: 0,
+            params: 0,
+        };
+        for s in body {
+            func.stmt(s);
+        }
+        if !func.terminated {
+            func.inst(format!("ret {} 0", func.module.word));
+        }
+        let (lines, params) = (func.lines, func.params);
+
+        let w = &self.word;
+        let params = (0..params)
+            .map(|i| format!("{w} %arg{i}"))
+            .collect::<Vec<_>>();
+        let mut slots = slots.into_iter().collect::<Vec<_>>();
+        slots.sort();
+        let allocas = slots.iter().map(|(alias, n)| match n {
+            1 => format!("  %{alias}.addr = alloca {w}"),
+            n => format!("  %{alias}.addr = alloca [{n} x {w}]"),
+        });
+        let head = [
+            format!("define {w} @{f}({}) {{", params.join(", ")),
+            "entry:".to_owned(),
+        ];
+        head.into_iter()
+            .chain(allocas)
+            .chain(lines)
+            .chain(["}".to_owned()])
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+// each local's slot, in words. statics are globals instead
+fn lay_out(stmts: &[Box<IStmt>], statics: &HashSet<&str>, slots: &mut HashMap<String, usize>) {
+    for s in stmts {
+        match s.as_ref() {
+            IStmt::Compute(Temp::UserTemp(alias), _) if !statics.contains(alias.as_str()) => {
+                slots.entry(alias.clone()).or_insert(1);
+            }
+            IStmt::Alloca(Temp::UserTemp(alias), n) => {
+                let words = slots.entry(alias.clone()).or_insert(1);
+                *words = (*words).max(*n);
+            }
+            IStmt::Scope(stmts) => lay_out(stmts, statics, slots),
+            _ => (),
+        }
+    }
+}
+
+// a function's body as it's emitted. block is the one being emitted into,
+// for phis, and n numbers the values and blocks made up
+struct Func<'a, 'm> {
+    module: &'a mut Module<'m>,
+    slots: &'a HashMap<String, usize>,
+    lines: Vec<String>,
+    block: String,
+    terminated: bool,
+    n: usize,
+    params: usize,
+}
+
+impl Func<'_, '_> {
+    // an instruction. one after a terminator starts a block nothing
+    // branches to
+    fn inst(&mut self, inst: String) {
+        if self.terminated {
+            let dead = self.fresh("dead");
+            self.label(&dead);
+        }
+        self.terminated =
+            inst.starts_with("br ") || inst.starts_with("ret ") || inst.starts_with("switch ");
+        self.lines.push(format!("  {inst}"));
+    }
+
+    // an instruction yielding a value, and the value
+    fn value(&mut self, inst: String) -> String {
+        let v = self.fresh("t");
+        self.inst(format!("%{v} = {inst}"));
+        format!("%{v}")
+    }
+
+    fn fresh(&mut self, prefix: &str) -> String {
+        self.n += 1;
+        format!("{prefix}{}", self.n)
+    }
+
+    fn label(&mut self, l: &str) {
+        // falling into it, or out of entry, which can't be branched to
+        if !self.terminated {
+            self.lines.push(format!("  br label %{l}"));
+        }
+        self.lines.push(format!("{l}:"));
+        self.block = l.to_owned();
+        self.terminated = false;
+    }
+
+    fn slot(&self, alias: &str) -> String {
+        match self.slots.contains_key(alias) {
+            true => format!("%{alias}.addr"),
+            false => format!("@{alias}"),
+        }
+    }
+
+    fn stmt(&mut self, s: &IStmt) {
+        let w = self.module.word.clone();
+        match s {
+            IStmt::Label(l) => self.label(&l.to_string()),
+            IStmt::Jump(l) => self.inst(format!("br label %{l}")),
+            IStmt::CJump(c, t, f) => {
+                let c = self.expr(c);
+                let c = self.value(format!("icmp ne {w} {c}, 0"));
+                self.inst(format!("br i1 {c}, label %{t}, label %{f}"));
+            }
+            // the index is in range, so the default is never taken
+            IStmt::JumpTable(e, ls) => {
+                let e = self.expr(e);
+                let cases = ls
+                    .iter()
+                    .enumerate()
+                    .map(|(i, l)| format!("{w} {i}, label %{l}"));
+                let cases = cases.collect::<Vec<_>>().join(" ");
+                self.inst(format!("switch {w} {e}, label %{} [{cases}]", ls[0]));
+            }
+            IStmt::Compute(Temp::UserTemp(alias), e) => {
+                let e = self.expr(e);
+                let slot = self.slot(alias);
+                self.inst(format!("store {w} {e}, ptr {slot}"));
+            }
+            // a call whose value is dropped
+            IStmt::Compute(Temp::PointerReg(RiscvPointerReg::Z), e) => {
+                self.expr(e);
+            }
+            IStmt::Move(addr, e) => {
+                let e = self.expr(e);
+                let addr = self.expr(addr);
+                let p = self.value(format!("inttoptr {w} {addr} to ptr"));
+                self.inst(format!("store {w} {e}, ptr {p}"));
+            }
+            IStmt::MoveByte(addr, e) => {
+                let e = self.expr(e);
+                let addr = self.expr(addr);
+                let p = self.value(format!("inttoptr {w} {addr} to ptr"));
+                let byte = self.value(format!("trunc {w} {e} to i8"));
+                self.inst(format!("store i8 {byte}, ptr {p}"));
+            }
+            IStmt::Return(e) => {
+                let e = e.as_ref().map_or("0".to_owned(), |e| self.expr(e));
+                self.inst(format!("ret {w} {e}"));
+            }
+            IStmt::Scope(stmts) => stmts.iter().for_each(|s| self.stmt(s)),
+            IStmt::Alloca(_, _) => (), // the entry block already has it
+            IStmt::Compute(_, _)
+            | IStmt::Load(_, _)
+            | IStmt::Store(_, _)
+            | IStmt::Seq(_, _)
+            | IStmt::Static(_, _, _) => unreachable!("not in a function's translation"),
+        }
+    }
+
+    fn expr(&mut self, e: &IExpr) -> String {
+        let w = self.module.word.clone();
+        match e {
+            IExpr::Const(n) => n.to_string(),
+            IExpr::BinOp(op, l, r) => {
+                let (l, r) = (self.expr(l), self.expr(r));
+                let cmp = match op {
+                    IBinOp::Add | IBinOp::AddU | IBinOp::AddPtr => {
+                        return self.value(format!("add {w} {l}, {r}"))
+                    }
+                    IBinOp::Sub | IBinOp::SubU => return self.value(format!("sub {w} {l}, {r}")),
+                    IBinOp::Mult | IBinOp::MultU => return self.value(format!("mul {w} {l}, {r}")),
+                    IBinOp::Div => return self.value(format!("sdiv {w} {l}, {r}")),
+                    IBinOp::Mod => return self.value(format!("srem {w} {l}, {r}")),
+                    IBinOp::DivU => return self.value(format!("udiv {w} {l}, {r}")),
+                    IBinOp::ModU => return self.value(format!("urem {w} {l}, {r}")),
+                    IBinOp::Lt => "slt",
+                    IBinOp::LtEq => "sle",
+                    IBinOp::Gt => "sgt",
+                    IBinOp::GtEq => "sge",
+                    IBinOp::Eq => "eq",
+                    IBinOp::Neq => "ne",
+                    IBinOp::LtU => "ult",
+                    IBinOp::LtEqU => "ule",
+                    IBinOp::GtU => "ugt",
+                    IBinOp::GtEqU => "uge",
+                };
+                let c = self.value(format!("icmp {cmp} {w} {l}, {r}"));
+                self.value(format!("zext i1 {c} to {w}"))
+            }
+            IExpr::BitOp(op, l, r) => {
+                let (l, r) = (self.expr(l), self.expr(r));
+                let op = match op {
+                    IBitOp::And => "and",
+                    IBitOp::Or => "or",
+                    IBitOp::Xor => "xor",
+                    IBitOp::Shl | IBitOp::ShlU => "shl",
+                    IBitOp::Shr => "ashr",
+                    IBitOp::ShrU => "lshr",
+                };
+                let r = match op {
+                    "shl" | "ashr" | "lshr" => {
+                        let bits = self.module.bits - 1;
+                        self.value(format!("and {w} {r}, {bits}"))
+                    }
+                    _ => r,
+                };
+                self.value(format!("{op} {w} {l}, {r}"))
+            }
+            IExpr::FloatOp(op, l, r) => {
+                let (l, r) = (self.expr(l), self.expr(r));
+                let (l, r) = (self.float(&l), self.float(&r));
+                let cmp = match op {
+                    IFloatOp::Add => return self.bits(format!("fadd float {l}, {r}")),
+                    IFloatOp::Sub => return self.bits(format!("fsub float {l}, {r}")),
+                    IFloatOp::Mult => return self.bits(format!("fmul float {l}, {r}")),
+                    IFloatOp::Div => return self.bits(format!("fdiv float {l}, {r}")),
+                    IFloatOp::Lt => "olt",
+                    IFloatOp::LtEq => "ole",
+                    IFloatOp::Gt => "ogt",
+                    IFloatOp::GtEq => "oge",
+                    IFloatOp::Eq => "oeq",
+                    IFloatOp::Neq => "une",
+                };
+                let c = self.value(format!("fcmp {cmp} float {l}, {r}"));
+                self.value(format!("zext i1 {c} to {w}"))
+            }
+            IExpr::Cvt(op, e) => {
+                let e = self.expr(e);
+                match op {
+                    ICvtOp::IntToFloat => self.bits(format!("sitofp {w} {e} to float")),
+                    ICvtOp::UIntToFloat => self.bits(format!("uitofp {w} {e} to float")),
+                    ICvtOp::FloatToInt => {
+                        let f = self.float(&e);
+                        self.value(format!("fptosi float {f} to {w}"))
+                    }
+                    ICvtOp::FloatToUInt => {
+                        let f = self.float(&e);
+                        self.value(format!("fptoui float {f} to {w}"))
+                    }
+                }
+            }
+            IExpr::Not(e) => {
+                let e = self.expr(e);
+                let c = self.value(format!("icmp eq {w} {e}, 0"));
+                self.value(format!("zext i1 {c} to {w}"))
+            }
+            // l decides, or r does:
+            //       br l rhs end (end rhs for ||)
+            // rhs:  br end
+            // end:  phi [decided, l's block] [r != 0, rhs's]
+            IExpr::LogOp(op, l, r) => {
+                let (rhs, end) = (self.fresh("rhs"), self.fresh("end"));
+                let l = self.expr(l);
+                let l = self.value(format!("icmp ne {w} {l}, 0"));
+                let (decided, targets) = match op {
+                    ILogOp::And => ("false", format!("label %{rhs}, label %{end}")),
+                    ILogOp::Or => ("true", format!("label %{end}, label %{rhs}")),
+                };
+                let from = self.block.clone();
+                self.inst(format!("br i1 {l}, {targets}"));
+                self.label(&rhs);
+                let r = self.expr(r);
+                let r = self.value(format!("icmp ne {w} {r}, 0"));
+                let r_from = self.block.clone();
+                self.label(&end);
+                let c = self.value(format!("phi i1 [{decided}, %{from}], [{r}, %{r_from}]"));
+                self.value(format!("zext i1 {c} to {w}"))
+            }
+            IExpr::Cond(c, then, els) => {
+                let (then_l, els_l, end) =
+                    (self.fresh("then"), self.fresh("else"), self.fresh("end"));
+                let c = self.expr(c);
+                let c = self.value(format!("icmp ne {w} {c}, 0"));
+                self.inst(format!("br i1 {c}, label %{then_l}, label %{els_l}"));
+                self.label(&then_l);
+                let then = self.expr(then);
+                let then_from = self.block.clone();
+                self.inst(format!("br label %{end}"));
+                self.label(&els_l);
+                let els = self.expr(els);
+                let els_from = self.block.clone();
+                self.label(&end);
+                self.value(format!(
+                    "phi {w} [{then}, %{then_from}], [{els}, %{els_from}]"
+                ))
+            }
+            IExpr::TempUse(Temp::UserTemp(alias)) => {
+                let slot = self.slot(alias);
+                self.value(format!("load {w}, ptr {slot}"))
+            }
+            IExpr::TempUse(Temp::PointerReg(reg)) => {
+                let i = ARG_REGS
+                    .iter()
+                    .position(|r| r == reg)
+                    .expect("only params are read from registers");
+                self.params = self.params.max(i + 1);
+                format!("%arg{i}")
+            }
+            IExpr::ESeq(s, e) => {
+                self.stmt(s);
+                self.expr(e)
+            }
+            IExpr::Addr(Temp::UserTemp(alias)) => {
+                let slot = self.slot(alias);
+                self.value(format!("ptrtoint ptr {slot} to {w}"))
+            }
+            IExpr::TempUse(Temp::MachineTemp(_)) | IExpr::Addr(_) => {
+                unreachable!("not in a function's translation")
+            }
+            IExpr::Mem(addr) => {
+                let addr = self.expr(addr);
+                let p = self.value(format!("inttoptr {w} {addr} to ptr"));
+                self.value(format!("load {w}, ptr {p}"))
+            }
+            IExpr::MemByte(addr, unsigned) => {
+                let addr = self.expr(addr);
+                let p = self.value(format!("inttoptr {w} {addr} to ptr"));
+                let byte = self.value(format!("load i8, ptr {p}"));
+                let ext = if *unsigned { "zext" } else { "sext" };
+                self.value(format!("{ext} i8 {byte} to {w}"))
+            }
+            IExpr::Str(s) => {
+                let s = self.str(s);
+                self.value(format!("ptrtoint ptr {s} to {w}"))
+            }
+            IExpr::Call(l, aps) => {
+                let f = l.to_string();
+                let c = !self.module.funcs.contains(f.as_str());
+                let aps = aps
+                    .iter()
+                    .map(|ap| match ap {
+                        IExpr::Str(s) if c => format!("ptr {}", self.str(s)),
+                        ap => format!("{w} {}", self.expr(ap)),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                match c {
+                    true => {
+                        self.module.externs.insert(f.clone());
+                        self.value(format!("call {w} (...) @{f}({aps})"))
+                    }
+                    false => self.value(format!("call {w} @{f}({aps})")),
+                }
+            }
+        }
+    }
+
+    // a word's low 32 bits as a float
+    fn float(&mut self, bits: &str) -> String {
+        let bits = match self.module.bits {
+            32 => bits.to_owned(),
+            _ => self.value(format!("trunc {} {bits} to i32", self.module.word)),
+        };
+        self.value(format!("bitcast i32 {bits} to float"))
+    }
+
+    // a float's bits, as a word
+    fn bits(&mut self, inst: String) -> String {
+        let f = self.value(inst);
+        let bits = self.value(format!("bitcast float {f} to i32"));
+        match self.module.bits {
+            32 => bits,
+            _ => self.value(format!("zext i32 {bits} to {}", self.module.word)),
+        }
+    }
+
+    // a pooled string's global
+    fn str(&mut self, s: &str) -> String {
+        let strs = &mut self.module.strs;
+        let i = match strs.iter().position(|t| t == s) {
+            Some(i) => i,
+            None => {
+                strs.push(s.to_owned());
+                strs.len() - 1
+            }
+        };
+        format!("@__picoc_str_{i}")
+    }
+}
+
+// printable ascii as is, but for " and \, and the rest as \xx
+fn escape(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'"' | b'\\' => format!("\\{b:02X}"),
+            b' '..=b'~' => (b as char).to_string(),
+            b => format!("\\{b:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test_llvm {
+    use crate::{lexer, parser_ast, translator, IntModel};
+
+    fn emit(src: &str) -> String {
+        let tokens = lexer::lex(src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        super::emit(&translator::translate(&src_tree), &IntModel::default())
+    }
+
+    #[test]
+    fn lowered() {
+        let src = r#"int printf(char *fmt, ...);
+static int calls = 0;
+int sq(int x) { calls++; return x * x; }
+int main() {
+  int xs[2];
+  int i = 0;
+  while (i < 2 && calls >= 0) { xs[i] = sq(i) >> 1; i++; }
+  printf("%d\n", xs[1]);
+  return 0;
+}"#;
+        insta::assert_snapshot!(emit(src), @r###"
+        target triple = "riscv32-unknown-linux-gnu"
+
+        @__picoc_str_0 = private unnamed_addr constant [4 x i8] c"%d\0A\00"
+
+        @calls = internal global [1 x i32] [i32 0]
+
+        define i32 @sq(i32 %arg0) {
+        entry:
+          %x.addr = alloca i32
+          store i32 %arg0, ptr %x.addr
+          %t1 = load i32, ptr @calls
+          %t2 = add i32 %t1, 1
+          store i32 %t2, ptr @calls
+          %t3 = load i32, ptr %x.addr
+          %t4 = load i32, ptr %x.addr
+          %t5 = mul i32 %t3, %t4
+          ret i32 %t5
+        }
+
+        define i32 @main() {
+        entry:
+          %i.addr = alloca i32
+          %xs.addr = alloca [2 x i32]
+          store i32 0, ptr %i.addr
+          br label %.L0
+        .L0:
+          %t3 = load i32, ptr %i.addr
+          %t4 = icmp slt i32 %t3, 2
+          %t5 = zext i1 %t4 to i32
+          %t6 = icmp ne i32 %t5, 0
+          br i1 %t6, label %rhs1, label %end2
+        rhs1:
+          %t7 = load i32, ptr @calls
+          %t8 = icmp sge i32 %t7, 0
+          %t9 = zext i1 %t8 to i32
+          %t10 = icmp ne i32 %t9, 0
+          br label %end2
+        end2:
+          %t11 = phi i1 [false, %.L0], [%t10, %rhs1]
+          %t12 = zext i1 %t11 to i32
+          %t13 = icmp ne i32 %t12, 0
+          br i1 %t13, label %.L1, label %.L2
+        .L1:
+          %t14 = load i32, ptr %i.addr
+          %t15 = call i32 @sq(i32 %t14)
+          %t16 = and i32 1, 31
+          %t17 = ashr i32 %t15, %t16
+          %t18 = ptrtoint ptr %xs.addr to i32
+          %t19 = load i32, ptr %i.addr
+          %t20 = mul i32 %t19, 4
+          %t21 = add i32 %t18, %t20
+          %t22 = inttoptr i32 %t21 to ptr
+          store i32 %t17, ptr %t22
+          %t23 = load i32, ptr %i.addr
+          %t24 = add i32 %t23, 1
+          store i32 %t24, ptr %i.addr
+          br label %.L0
+        .L2:
+          %t25 = ptrtoint ptr %xs.addr to i32
+          %t26 = mul i32 1, 4
+          %t27 = add i32 %t25, %t26
+          %t28 = inttoptr i32 %t27 to ptr
+          %t29 = load i32, ptr %t28
+          %t30 = call i32 (...) @printf(ptr @__picoc_str_0, i32 %t29)
+          ret i32 0
+        }
+
+        declare i32 @printf(...)
+        "###);
+    }
+}
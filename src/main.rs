@@ -1,11 +1,56 @@
-use picoc089::{allocator, lexer, parser, parser_ast, selector, translator, typer};
+use picoc089::{
+    cli::{self, Args, Emit},
+    debugger,
+    diagnostics::{Diagnostic, Phase},
+    engine::{self, Limits},
+    evaluator, ir, json,
+    lexer::{self, TT},
+    linker, llvm, optimizer, parser, parser_ast, preprocessor, pretty, repl,
+    source::{self, Pos, SourceMap},
+    stats, symtab, translator, transpiler, typecheck, typer, visualizer, Budget, Sink,
+};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::env;
 use std::fs;
-use std::io::Write;
+use std::io::{self, IsTerminal, Read, Write};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::rc::Rc;
+use std::slice;
+use std::thread;
+use std::time::Duration;
 
+// the walker recurses as deep as the program it runs does, so everything
+// runs on a thread with the stack evaluator::DEPTH calls take
 fn main() {
-    println!(
-        "
+    let main = thread::Builder::new()
+        .stack_size(evaluator::STACK)
+        .spawn(run)
+        .expect("failed to spawn the main thread");
+    if let Err(e) = main.join() {
+        panic::resume_unwind(e);
+    }
+}
+
+fn run() {
+    let args = match cli::parse_args(env::args().skip(1)) {
+        Ok(args) if args.help => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Ok(args) => args,
+        Err(e) => {
+            println!("picoc-error: {e}\n\n{}", cli::USAGE);
+            process::exit(cli::EXIT_USAGE);
+        }
+    };
+
+    if !args.quiet {
+        println!(
+            "
     ⠀⠀⠀⠀⠀⣼⣧⠀⠀⠀⠀⠀
     ⠀⠀⠀⠀⣼⣿⣿⣧⠀⠀⠀⠀
     ⠀⠀⠀⠾⠿⠿⠿⠿⠷⠀⠀⠀
@@ -14,66 +59,640 @@ fn main() {
     ⠾⠟⠿⠿⠿⠧⠼⠿⠿⠿⠻⠷
     picoc: aot son-optimizing C89->{{RV32I, LLVM}} compiler
     "
-    );
-
-    let strat = env::args().nth(1).expect("picoc-error: no strategy given");
-    println!("picoc-info: received strategy: {strat}");
-
-    let src = env::args()
-        .nth(2)
-        .expect("picoc-error: no source file given");
-    println!("picoc-info: received source: {src}");
-
-    let opt = env::args()
-        .nth(3)
-        .expect("picoc-error: no optimization level given")
-        .chars()
-        .nth(1)
-        .expect("picoc-error: optimization level must be at least 1 character")
-        .to_digit(10)
-        .expect("picoc-error: invalid optimization level given (invalid number)")
-        .try_into()
-        .expect("picoc-error: invalid optimization level given (invalid level)");
-    println!("picoc-info: received optimization level: {:?}", opt);
-
-    let chars = fs::read(src)
-        .expect("picoc-error: file dne`")
+        );
+    }
+
+    let strat = &args.strat;
+    let info = |msg: &str| {
+        if !args.quiet {
+            println!("picoc-info: {msg}");
+        }
+    };
+    info(&format!("received int model: {:?}", args.model));
+    info(&format!("received strategy: {strat}"));
+
+    if strat == "repl" {
+        repl::repl(io::stdin().lock(), io::stdout(), args.model.clone())
+            .expect("picoc-error: repl failed");
+        return;
+    }
+
+    if strat == "stats" {
+        // one line per file, and a bad file doesn't stop the batch
+        for src in &args.files {
+            let text = match cli::read_source(src, io::stdin()) {
+                Ok(text) => text,
+                Err(e) => {
+                    println!("{src}: picoc-error: {e}");
+                    continue;
+                }
+            };
+            let src = cli::name(src);
+            match stats::stats(&text) {
+                Ok(stats) => println!("{src}: {stats}"),
+                Err(e) => println!("{src}: picoc-error: {e}"),
+            }
+        }
+        return;
+    }
+
+    if strat == "fmt" {
+        fmt(&args);
+        return;
+    }
+
+    if args.watch {
+        info(&format!("watching: {}", args.files.join(", ")));
+        watch(&args);
+        return;
+    }
+
+    if args.files.len() > 1 {
+        info(&format!("received sources: {}", args.files.join(", ")));
+        link(&args);
+        return;
+    }
+
+    let src = cli::name(&args.files[0]);
+    info(&format!("received source: {src}"));
+    info(&format!("received optimization level: {:?}", args.opt));
+    info(&format!("received target: {:?}", args.target));
+
+    let text = match cli::read_source(&args.files[0], io::stdin()) {
+        Ok(text) => text,
+        Err(e) => {
+            unlocated(Phase::Preprocess, src, &e);
+            process::exit(cli::EXIT_COMPILE);
+        }
+    };
+    let (text, map) = match preprocessor::preprocess(&text, Path::new(src), &args.pp) {
+        Ok(out) => out,
+        Err(e) => {
+            unlocated(Phase::Preprocess, "", &e);
+            process::exit(cli::EXIT_COMPILE);
+        }
+    };
+    info("preprocessed");
+    let tokens = match lexer::lex(&text) {
+        Ok(tokens) => tokens,
+        Err(errs) => {
+            for e in errs {
+                let d = Diagnostic::lex(&e);
+                println!("{}", d.render(src, &text, &map, args.tab_width));
+            }
+            process::exit(cli::EXIT_COMPILE);
+        }
+    };
+    info("lexed");
+    if args.emit == Some(Emit::Tokens) {
+        let tokens = tokens.iter().map(|t| {
+            let Pos { line, col } = source::pos(&text, t.offset(&text), args.tab_width);
+            let (typ, lexeme) = (t.typ, t.lexeme.as_str());
+            Dumped {
+                typ,
+                lexeme,
+                line,
+                col,
+            }
+        });
+        emit(
+            &args,
+            &json::to_string(&tokens.collect::<Vec<_>>()).unwrap(),
+        );
+        return;
+    }
+
+    let (src_tree, errs) = parser_ast::parse(&tokens);
+    if !errs.is_empty() {
+        for e in errs {
+            report(Phase::Parse, src, &map, &text, &e.into(), args.tab_width);
+        }
+        process::exit(cli::EXIT_COMPILE);
+    }
+    info("parsed");
+    if args.emit == Some(Emit::Ast) {
+        emit(&args, &json::to_string(&src_tree).unwrap());
+        return;
+    }
+    if args.emit == Some(Emit::AstDot) {
+        emit(&args, &visualizer::ast_dot(&src_tree));
+        return;
+    }
+
+    // the son parser only covers a subset of the language so far
+    match parser::parse_prg(&tokens) {
+        Ok(src_graph) => {
+            info("parsed son");
+            info(&format!("son graph: {:?}", src_graph));
+        }
+        Err(e) => info(&format!("skipped son: {e}")),
+    }
+
+    if let Err(e) = typecheck::check(&src_tree) {
+        report(Phase::Check, src, &map, &text, &e, args.tab_width);
+        process::exit(cli::EXIT_COMPILE);
+    }
+    // c0's static semantics are stricter than c's. a .c0 file's bools
+    // aren't ints either, though c's comparisons give ints
+    if strat == "interpretc0" {
+        let c0 = match src.ends_with(".c0") {
+            true => typer::type_prg_c0(&src_tree).map(|_| ()),
+            false => Ok(()),
+        };
+        if let Err(e) = c0.and_then(|_| typecheck::check_returns(&src_tree)) {
+            report(Phase::Check, src, &map, &text, &e, args.tab_width);
+            process::exit(cli::EXIT_COMPILE);
+        }
+    }
+    if !warn(
+        &args,
+        src,
+        &map,
+        &text,
+        &typecheck::warnings(&src_tree, &args.warnings),
+    ) {
+        process::exit(cli::EXIT_COMPILE);
+    }
+    info("typed");
+    if args.emit == Some(Emit::C) {
+        emit(&args, &transpiler::transpile(&src_tree));
+        return;
+    }
+    if args.emit == Some(Emit::Symtab) {
+        let entries = symtab::symtab(&src_tree, &text, &map, &args.model)
+            .iter()
+            .map(|e| e.to_string().trim_end().to_owned())
+            .collect::<Vec<_>>();
+        let header = format!(
+            "{:<16} {:<7} {:<20} {:>5} {:<16} {:>5} uses",
+            "symbol", "kind", "type", "depth", "location", "line"
+        );
+        emit(&args, &format!("{header}\n{}", entries.join("\n")));
+        return;
+    }
+    // what runs the program, from here on, sees its conversions as casts.
+    // --emit c and the symtab are of the source as written
+    let src_tree = typer::explicit(&src_tree);
+    if args.emit == Some(Emit::Ir) {
+        emit(
+            &args,
+            &json::to_string(&translator::translate_for(&src_tree, &args.model)).unwrap(),
+        );
+        return;
+    }
+
+    if strat == "debug" {
+        let src_tree = evaluator::erase_contracts(&src_tree);
+        let input = io::stdin().lock();
+        match debugger::debug(
+            &src_tree,
+            &text,
+            &map,
+            input,
+            Sink::default(),
+            args.model.clone(),
+        ) {
+            Ok(Some(exit)) => println!("picoc-info: evaluated: {exit}"),
+            Ok(None) => (),
+            Err(e) => {
+                report(Phase::Run, src, &map, &text, &e, args.tab_width);
+                process::exit(cli::EXIT_RUNTIME);
+            }
+        }
+        return;
+    }
+
+    // the compiling strategies panic on what the backend can't do yet
+    if strat != "interpretc0" || matches!(args.emit, Some(Emit::Cfg | Emit::CfgDot | Emit::Llvm)) {
+        if let Err(e) = engine::unsupported(&src_tree) {
+            report(Phase::Compile, src, &map, &text, &e, args.tab_width);
+            process::exit(cli::EXIT_COMPILE);
+        }
+    }
+    if matches!(args.emit, Some(Emit::Cfg | Emit::CfgDot)) {
+        let trgt_tree = translator::translate_for(&src_tree, &args.model);
+        let cfg = ir::lower(&trgt_tree, &HashSet::new(), &args.model);
+        let levels = optimizer::levels(&src_tree);
+        let cfg = optimizer::optimize(cfg, &args.opt, &levels, &args.model);
+        match args.emit {
+            Some(Emit::CfgDot) => emit(&args, &visualizer::cfg_dot(&cfg)),
+            _ => emit(&args, &json::to_string(&cfg).unwrap()),
+        }
+        return;
+    }
+    if args.emit == Some(Emit::Llvm) {
+        let trgt_tree = translator::translate_for(&src_tree, &args.model);
+        emit(&args, &llvm::emit(&trgt_tree, &args.model));
+        return;
+    }
+
+    let (opt, model, count) = (args.opt.clone(), args.model.clone(), args.count);
+    if strat == "compilec89" || args.emit == Some(Emit::Asm) {
+        let (assembly, symbols) = engine::compile(&src_tree, opt, &model, &text, count, args.pic);
+        info("emitted");
+        if args.emit == Some(Emit::Asm) {
+            emit(&args, &assembly.join("\n"));
+            return;
+        }
+
+        // the symbol map goes next to the assembly, and so does the
+        // executable when it's linked
+        let bin = args.output.clone().unwrap_or("./a.out".into());
+        let out = match args.link {
+            true => bin.with_extension("s"),
+            false => args.output.clone().unwrap_or("./tmp.s".into()),
+        };
+        let mut f = fs::File::create(&out).expect("picoc-error: unable to create file");
+        f.write_all(assembly.join("\n").as_bytes())
+            .expect("picoc-error: unable to write data");
+
+        let symbols = symbols
+            .iter()
+            .map(|sym| sym.to_string())
+            .collect::<Vec<_>>();
+        let mut f = fs::File::create(out.with_extension("map"))
+            .expect("picoc-error: unable to create file");
+        writeln!(f, "{:<24} {:<10} {:>6} line", "symbol", "section", "size")
+            .and_then(|_| f.write_all(symbols.join("\n").as_bytes()))
+            .expect("picoc-error: unable to write data");
+        info("wrote symbol map");
+
+        if args.link {
+            if let Err(e) = linker::link(slice::from_ref(&out), &bin, args.model.word()) {
+                let units = [linker::Unit {
+                    file: src.to_owned(),
+                    prg: src_tree,
+                }];
+                toolchain(e, &units, &[(text, map)], &[(out, assembly)], &args);
+            }
+            info(&format!("linked {}", bin.display()));
+        }
+        return;
+    }
+
+    // the program's own input, unless its source was. only a compiled
+    // program is handed it, so the interpreters don't wait on a pipe that
+    // never closes
+    let mut stdin = String::new();
+    let compiled = strat == "difftest" || strat == "runc89" && !args.vm;
+    if compiled && !io::stdin().is_terminal() && args.files[0] != "-" {
+        io::stdin()
+            .read_to_string(&mut stdin)
+            .expect("picoc-error: unable to read stdin");
+    }
+    // without --dyn-check, contracts are the comments they are to c
+    let src_tree = match args.dyn_check {
+        true => src_tree,
+        false => evaluator::erase_contracts(&src_tree),
+    };
+    let limits = Limits {
+        max_steps: args.max_steps,
+        max_heap: args.max_heap,
+        ..Limits::default()
+    };
+
+    if strat == "difftest" {
+        let [Some(reference), Some(subject)] = ["interpretc0", "runc89"]
+            .map(|strat| engine::engine(strat, opt.clone(), model.clone(), false))
+        else {
+            unreachable!("both are engines")
+        };
+        let diverged = engine::difftest(&src_tree, &stdin, &limits, &*reference, &*subject);
+        match diverged {
+            Ok(None) => info("interpretc0 and runc89 agree"),
+            Ok(Some(d)) => {
+                println!(
+                    "picoc-error: the {} diverged: interpretc0 {:?}, runc89 {:?}",
+                    d.what, d.expected, d.found
+                );
+                process::exit(cli::EXIT_RUNTIME);
+            }
+            Err(e) => {
+                unlocated(Phase::Compile, "", &e);
+                process::exit(cli::EXIT_COMPILE);
+            }
+        }
+        return;
+    }
+
+    let strat = match args.vm {
+        true => "vm",
+        false => strat,
+    };
+    let Some(exec) = engine::engine(strat, opt, model, count) else {
+        println!("picoc-error: unknown strategy: {:?}", strat);
+        process::exit(cli::EXIT_USAGE);
+    };
+    // the trace goes to stderr, so stdout is the program's, as compiled
+    if args.trace {
+        let trace = debugger::tracer(&text, &map, Sink(Rc::new(RefCell::new(io::stderr()))));
+        let budget = Budget {
+            steps: args.max_steps,
+            heap: args.max_heap,
+            ..Budget::default()
+        };
+        match evaluator::eval_prg_hooked(
+            &src_tree,
+            args.model.clone(),
+            Sink::default(),
+            budget,
+            trace,
+        ) {
+            Ok(exit) => println!("picoc-info: evaluated: {exit}"),
+            Err(e) => {
+                report(Phase::Run, src, &map, &text, &e, args.tab_width);
+                process::exit(cli::EXIT_RUNTIME);
+            }
+        }
+        return;
+    }
+    let output = match exec.run(&src_tree, &stdin, &limits) {
+        Ok(output) => output,
+        Err(e) => {
+            report(Phase::Run, src, &map, &text, &e, args.tab_width);
+            process::exit(cli::EXIT_RUNTIME);
+        }
+    };
+    print!("{}", output.stdout);
+    eprint!("{}", output.stderr);
+    // the result, even when quiet
+    println!("picoc-info: evaluated: {}", output.exit);
+}
+
+// several files, each through the front end on its own, then linked. a
+// file's errors don't stop the others being checked
+fn link(args: &Args) {
+    let fronts = args
+        .files
+        .iter()
+        .map(|file| front(args, file))
+        .collect::<Vec<_>>();
+    let Some(fronts) = fronts.into_iter().collect::<Option<Vec<_>>>() else {
+        process::exit(cli::EXIT_COMPILE);
+    };
+    let (units, texts): (Vec<_>, Vec<_>) = fronts.into_iter().unzip();
+    if let Err(e) = linker::check(&units) {
+        for e in e.to_string().lines() {
+            unlocated(Phase::Link, "", &io::Error::other(e));
+        }
+        process::exit(cli::EXIT_COMPILE);
+    }
+
+    if args.strat == "runc89" {
+        let exec = engine::engine(&args.strat, args.opt.clone(), args.model.clone(), false)
+            .expect("runc89 is an engine");
+        let mut stdin = String::new();
+        if !io::stdin().is_terminal() && !args.files.iter().any(|f| f == "-") {
+            io::stdin()
+                .read_to_string(&mut stdin)
+                .expect("picoc-error: unable to read stdin");
+        }
+        let output = match exec.run_units(&units, &stdin, &Limits::default()) {
+            Ok(output) => output,
+            Err(e) => {
+                unlocated(Phase::Run, "", &e);
+                process::exit(cli::EXIT_RUNTIME);
+            }
+        };
+        print!("{}", output.stdout);
+        eprint!("{}", output.stderr);
+        println!("picoc-info: evaluated: {}", output.exit);
+        return;
+    }
+
+    // each file's assembly goes beside the executable
+    let bin = args.output.clone().unwrap_or("./a.out".into());
+    let srcs = units
         .iter()
-        .map(|b| *b as char)
+        .map(|u| {
+            let stem = Path::new(&u.file).file_stem().unwrap_or_default();
+            bin.with_file_name(stem).with_extension("s")
+        })
         .collect::<Vec<_>>();
-    let tokens = lexer::lex(&chars).unwrap();
-    println!("picoc-info: lexed");
-    let src_tree = parser_ast::parse_prg(&tokens).unwrap(); // recursive descent -> pratt parsing
-    println!("picoc-info: parsed");
+    if let Some(dup) = srcs
+        .iter()
+        .find(|s| srcs.iter().filter(|t| t == s).count() > 1)
+    {
+        let e = io::Error::other(format!("two files compile to {}", dup.display()));
+        unlocated(Phase::Compile, "", &e);
+        process::exit(cli::EXIT_COMPILE);
+    }
+    let assembly = linker::compile(&units, args.opt.clone(), &args.model, args.pic);
+    for (src, assembly) in srcs.iter().zip(&assembly) {
+        fs::write(src, assembly.join("\n")).expect("picoc-error: unable to write data");
+    }
+    if let Err(e) = linker::link(&srcs, &bin, args.model.word()) {
+        let srcs = srcs.into_iter().zip(assembly).collect::<Vec<_>>();
+        toolchain(e, &units, &texts, &srcs, args);
+    }
+    if !args.quiet {
+        println!("picoc-info: linked {}", bin.display());
+    }
+}
 
-    let src_graph = parser::parse_prg(&tokens).unwrap();
-    println!("picoc-info: parsed son");
-    println!("son graph: {:?}", src_graph);
+// runc89 --watch: the program run again each time one of its files
+// changes, by the one engine, so what's unchanged isn't compiled again.
+// errors are reported and waited out, as the next save may fix them
+fn watch(args: &Args) {
+    let exec = engine::engine("runc89", args.opt.clone(), args.model.clone(), args.count)
+        .expect("runc89 is an engine");
+    let stamps = || {
+        args.files
+            .iter()
+            .map(|f| fs::metadata(f).and_then(|m| m.modified()).ok())
+            .collect::<Vec<_>>()
+    };
 
-    let _typ = typer::type_prg(&src_tree).unwrap();
-    println!("picoc-info: typed");
+    loop {
+        let seen = stamps();
+        let fronts = args
+            .files
+            .iter()
+            .map(|file| front(args, file))
+            .collect::<Option<Vec<_>>>();
+        if let Some(fronts) = fronts {
+            let units = fronts.into_iter().map(|(unit, _)| unit).collect::<Vec<_>>();
+            match linker::check(&units) {
+                Err(e) => e
+                    .to_string()
+                    .lines()
+                    .for_each(|e| unlocated(Phase::Link, "", &io::Error::other(e))),
+                Ok(_) => match exec.run_units(&units, "", &Limits::default()) {
+                    Ok(output) => {
+                        print!("{}", output.stdout);
+                        eprint!("{}", output.stderr);
+                        println!("picoc-info: evaluated: {}", output.exit);
+                    }
+                    Err(e) => unlocated(Phase::Run, "", &e),
+                },
+            }
+        }
+        while stamps() == seen {
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
 
-    match strat.as_str() {
-        // "interpretc0" => {
-        //     let val = evaluator::eval_prg(&src_tree).unwrap();
-        //     println!("picoc-info: evaluated: {val}");
-        // }
-        "compilec89" => {
-            let trgt_tree = translator::translate(&src_tree);
-            println!("picoc-info: translated",);
+// the toolchain's errors, with a caret under the source they're about
+// where that's clear. texts are the units' preprocessed text and maps,
+// and srcs their .s files and assembly
+fn toolchain(
+    e: io::Error,
+    units: &[linker::Unit],
+    texts: &[(String, SourceMap)],
+    srcs: &[(PathBuf, Vec<String>)],
+    args: &Args,
+) -> ! {
+    match e
+        .get_ref()
+        .and_then(|e| e.downcast_ref::<linker::ToolchainError>())
+    {
+        Some(te) => {
+            for (i, e) in linker::diagnose(te, units, srcs) {
+                match i {
+                    Some(i) => {
+                        let (text, map) = &texts[i];
+                        report(
+                            Phase::Compile,
+                            &units[i].file,
+                            map,
+                            text,
+                            &e,
+                            args.tab_width,
+                        )
+                    }
+                    None => unlocated(Phase::Compile, "", &e),
+                }
+            }
+        }
+        None => unlocated(Phase::Compile, "", &e),
+    }
+    process::exit(cli::EXIT_COMPILE);
+}
 
-            let abs_as = selector::select(&trgt_tree);
-            println!("picoc-info: selected");
-            let assembly = allocator::allocate(&abs_as, opt);
-            println!("picoc-info: emitted");
+// the file formatted, to -o or stdout. it's lexed as it's written, since
+// preprocessing would expand it and lose its comments
+fn fmt(args: &Args) {
+    let src = cli::name(&args.files[0]);
+    let text = match cli::read_source(&args.files[0], io::stdin()) {
+        Ok(text) => text,
+        Err(e) => {
+            unlocated(Phase::Preprocess, src, &e);
+            process::exit(cli::EXIT_COMPILE);
+        }
+    };
+    let map = SourceMap::new(src);
+    let tokens = match lexer::lex(&text) {
+        Ok(tokens) => tokens,
+        Err(errs) => {
+            for e in errs {
+                let d = Diagnostic::lex(&e);
+                println!("{}", d.render(src, &text, &map, args.tab_width));
+            }
+            process::exit(cli::EXIT_COMPILE);
+        }
+    };
+    let (prg, errs) = parser_ast::parse(&tokens);
+    if !errs.is_empty() {
+        for e in errs {
+            report(Phase::Parse, src, &map, &text, &e.into(), args.tab_width);
+        }
+        process::exit(cli::EXIT_COMPILE);
+    }
+    let formatted = pretty::formatted(&prg, &text);
+    match &args.output {
+        Some(path) => fs::write(path, formatted).expect("picoc-error: unable to write data"),
+        None => print!("{formatted}"),
+    }
+}
 
-            let mut f = fs::File::create("./tmp.s").expect("picoc-error: unable to create file");
-            f.write_all(assembly.join("\n").as_bytes())
-                .expect("picoc-error: unable to write data");
+// a file preprocessed, lexed, parsed and checked, with its text and map,
+// or none once its errors are reported
+fn front(args: &Args, file: &str) -> Option<(linker::Unit, (String, SourceMap))> {
+    let src = cli::name(file);
+    let text = cli::read_source(file, io::stdin())
+        .map_err(|e| unlocated(Phase::Preprocess, src, &e))
+        .ok()?;
+    let (text, map) = preprocessor::preprocess(&text, Path::new(src), &args.pp)
+        .map_err(|e| unlocated(Phase::Preprocess, "", &e))
+        .ok()?;
+    let tokens = match lexer::lex(&text) {
+        Ok(tokens) => tokens,
+        Err(errs) => {
+            for e in errs {
+                let d = Diagnostic::lex(&e);
+                println!("{}", d.render(src, &text, &map, args.tab_width));
+            }
+            return None;
         }
-        _ => {
-            println!("picoc-error: unknown strategy: {:?}", strat);
-            std::process::exit(1);
+    };
+    let (prg, errs) = parser_ast::parse(&tokens);
+    if !errs.is_empty() {
+        for e in errs {
+            report(Phase::Parse, src, &map, &text, &e.into(), args.tab_width);
+        }
+        return None;
+    }
+    if let Err(e) = typecheck::check_unit(&prg) {
+        report(Phase::Check, src, &map, &text, &e, args.tab_width);
+        return None;
+    }
+    if !warn(
+        args,
+        src,
+        &map,
+        &text,
+        &typecheck::warnings(&prg, &args.warnings),
+    ) {
+        return None;
+    }
+    if let Err(e) = engine::unsupported(&prg) {
+        report(Phase::Compile, src, &map, &text, &e, args.tab_width);
+        return None;
+    }
+
+    let unit = linker::Unit {
+        file: src.to_owned(),
+        prg: typer::explicit(&prg),
+    };
+    Some((unit, (text, map)))
+}
+
+// a token and where it is, as --emit tokens dumps it
+#[derive(Serialize)]
+struct Dumped<'a> {
+    typ: TT,
+    lexeme: &'a str,
+    line: usize,
+    col: usize,
+}
+
+// what --emit stops at, to -o or stdout. all but asm, c, llvm, the dots and symtab is json
+fn emit(args: &Args, out: &str) {
+    match &args.output {
+        Some(path) => fs::write(path, out).expect("picoc-error: unable to write data"),
+        None => println!("{out}"),
+    }
+}
+
+// with the line and a caret under where it happened, if the error knows
+fn report(phase: Phase, src: &str, map: &SourceMap, text: &str, e: &io::Error, tab_width: usize) {
+    let d = Diagnostic::error(phase, e);
+    println!("{}", d.render(src, text, map, tab_width));
+}
+
+// the warnings -W turns on, with --quiet keeping all but -Werror's quiet.
+// false if -Werror made any of them errors
+fn warn(args: &Args, src: &str, map: &SourceMap, text: &str, diags: &[Diagnostic]) -> bool {
+    if !args.quiet || args.warnings.error {
+        for d in diags {
+            println!("{}", d.render(src, text, map, args.tab_width));
         }
     }
+    !args.warnings.error || diags.is_empty()
+}
+
+// an error with no source to point into, at src if it's given
+fn unlocated(phase: Phase, src: &str, e: &io::Error) {
+    report(phase, src, &SourceMap::new(src), "", e, 0);
 }
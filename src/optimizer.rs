@@ -0,0 +1,890 @@
+use crate::ir::{self, Block, Cfg};
+use crate::{
+    Imm, IntModel, Label, Levels, OptLevel, PseudoOp, RiscvPointerReg, SAttr, SDef, SPrg, TImmOp,
+    TMemOp, TQuad, TRegOp, Temp,
+};
+use std::collections::{HashMap, HashSet};
+
+// the passes -O1 runs over the cfg, until none of them changes anything:
+//   - folding: an op on constants is the constant it computes, and a
+//     branch on one is a jump, or nothing
+//   - propagation: a load of a local that holds a known constant on every
+//     path to it is that constant
+//   - dead code: stores to locals that are never loaded, and blocks that
+//     control never reaches, are deleted
+// and then once, as its rewrites would only be undone by the others:
+//   - common subexpressions: an op a block computes again on the same
+//     values is loaded from where its first result was saved
+// the quads are still run as a stack machine, so every rewrite leaves the
+// stack as it was: a push is replaced by a push, and a push is deleted
+// together with the quad right after it that pops it. locals are only
+// tracked in functions that never take a local's address, since anything
+// could store through it
+// functions with a level of their own in levels are optimized at it, apart
+// from the rest
+pub fn optimize(cfg: Cfg, opt: &OptLevel, levels: &Levels, model: &IntModel) -> Cfg {
+    if levels.is_empty() {
+        return optimize_run(cfg, opt, model);
+    }
+    let quads = ir::runs(&cfg.quads(), opt, levels)
+        .into_iter()
+        .flat_map(|(level, quads)| optimize_run(ir::build(quads), &level, model).quads())
+        .collect();
+    ir::build(quads)
+}
+
+// the levels the program's optimize attributes give its functions
+pub fn levels(prg: &SPrg) -> Levels {
+    prg.iter()
+        .filter_map(|def| match &def.node {
+            SDef::FuncDef(fd) => fd.attrs.iter().find_map(|attr| match attr {
+                SAttr::Optimize(opt) => Some((fd.alias.clone(), opt.clone())),
+                _ => None,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+fn optimize_run(cfg: Cfg, opt: &OptLevel, model: &IntModel) -> Cfg {
+    if *opt == OptLevel::O0 {
+        return cfg;
+    }
+    let mut cfg = cfg;
+    loop {
+        let before = cfg.clone();
+        let quads = cfg
+            .blocks
+            .iter()
+            .flat_map(|b| fold(&b.quads, model))
+            .collect();
+        cfg = ir::build(quads);
+        cfg = propagate(cfg);
+        cfg = eliminate(cfg);
+        if cfg == before {
+            return cse(cfg, model.word());
+        }
+    }
+}
+
+// the constant a quad pushes: an addi of an immediate to zero
+fn constant(quad: &TQuad) -> Option<(&Temp, Imm)> {
+    match quad {
+        TQuad::Imm(
+            TImmOp::AddI,
+            t @ Temp::MachineTemp(_),
+            Temp::PointerReg(RiscvPointerReg::Z),
+            k,
+        ) => Some((t, *k)),
+        _ => None,
+    }
+}
+
+fn push(t: Temp, k: Imm) -> TQuad {
+    TQuad::Imm(TImmOp::AddI, t, Temp::PointerReg(RiscvPointerReg::Z), k)
+}
+
+// ops fold as the allocator runs them: register ops wrap, then extend from
+// the int width, zero extending the unsigned ones, and immediate ones only
+// wrap. division by zero is
+// left for the runtime to report, as is INT_MIN / -1 under --checked-arith
+fn reg_op(op: &TRegOp, l: Imm, r: Imm, model: &IntModel) -> Option<Imm> {
+    let (ul, ur) = (l as u32, r as u32);
+    let v = match op {
+        TRegOp::Add | TRegOp::AddPtr | TRegOp::Addu => l.wrapping_add(r),
+        TRegOp::Sub | TRegOp::Subu => l.wrapping_sub(r),
+        TRegOp::Mul | TRegOp::Mulu => l.wrapping_mul(r),
+        TRegOp::Div | TRegOp::Rem | TRegOp::Divu | TRegOp::Remu if r == 0 => return None,
+        TRegOp::Div | TRegOp::Rem if model.checked_arith && l == model.int_min() && r == -1 => {
+            return None
+        }
+        TRegOp::Div => l.wrapping_div(r),
+        TRegOp::Rem => l.wrapping_rem(r),
+        TRegOp::Divu => (ul / ur) as Imm,
+        TRegOp::Remu => (ul % ur) as Imm,
+        TRegOp::And => l & r,
+        TRegOp::Or => l | r,
+        TRegOp::Xor => l ^ r,
+        TRegOp::Sll | TRegOp::Sllu => l.wrapping_shl(ur),
+        TRegOp::Sra => l.wrapping_shr(ur),
+        TRegOp::Srl => ul.wrapping_shr(ur) as Imm,
+        TRegOp::Slt => (l < r) as Imm,
+        TRegOp::Sgt => (l > r) as Imm,
+        TRegOp::Sltu => (ul < ur) as Imm,
+        TRegOp::Sgtu => (ul > ur) as Imm,
+        TRegOp::Beq | TRegOp::Bneq | TRegOp::Bge | TRegOp::Blt | TRegOp::Jal => return None,
+    };
+    let ext = 32 - model.int_width;
+    match op {
+        TRegOp::AddPtr | TRegOp::And | TRegOp::Or | TRegOp::Xor => Some(v),
+        TRegOp::Addu
+        | TRegOp::Subu
+        | TRegOp::Mulu
+        | TRegOp::Sllu
+        | TRegOp::Divu
+        | TRegOp::Remu
+        | TRegOp::Srl => Some(((v as u32).wrapping_shl(ext) >> ext) as Imm),
+        _ => Some(v.wrapping_shl(ext) >> ext),
+    }
+}
+
+fn imm_op(op: &TImmOp, l: Imm, k: Imm) -> Imm {
+    match op {
+        TImmOp::AddI => l.wrapping_add(k),
+        TImmOp::SubI => l.wrapping_sub(k),
+        TImmOp::AndI => l & k,
+        TImmOp::OrI => l | k,
+        TImmOp::XorI => l ^ k,
+        TImmOp::SltIU => ((l as u32) < (k as u32)) as Imm,
+    }
+}
+
+fn taken(op: &TRegOp, l: Imm, r: Imm) -> Option<bool> {
+    match op {
+        TRegOp::Beq => Some(l == r),
+        TRegOp::Bneq => Some(l != r),
+        TRegOp::Bge => Some(l >= r),
+        TRegOp::Blt => Some(l < r),
+        _ => None,
+    }
+}
+
+// a block's quads with its ops on constants folded, innermost first, so a
+// tree of them folds down to one push
+fn fold(quads: &[TQuad], model: &IntModel) -> Vec<TQuad> {
+    let mut out: Vec<TQuad> = vec![];
+    for quad in quads {
+        let n = out.len();
+        // the constants the last one or two quads pushed
+        let top = out.last().and_then(constant).map(|(t, k)| (t.clone(), k));
+        let under = n
+            .checked_sub(2)
+            .and_then(|i| constant(&out[i]))
+            .map(|(t, k)| (t.clone(), k));
+
+        match (quad, &under, &top) {
+            (TQuad::Reg(op, d, l, r), Some((lt, a)), Some((rt, b))) if l == lt && r == rt => {
+                if let Some(v) = reg_op(op, *a, *b, model) {
+                    out.truncate(n - 2);
+                    out.push(push(d.clone(), v));
+                    continue;
+                }
+            }
+            (TQuad::Imm(op, d @ Temp::MachineTemp(_), l, k), _, Some((lt, a))) if l == lt => {
+                out.pop();
+                out.push(push(d.clone(), imm_op(op, *a, *k)));
+                continue;
+            }
+            (TQuad::Branch(op, c, Temp::PointerReg(RiscvPointerReg::Z), l), _, Some((ct, a)))
+                if c == ct =>
+            {
+                if let Some(taken) = taken(op, *a, 0) {
+                    out.pop();
+                    if taken {
+                        out.push(TQuad::Pseudo(PseudoOp::Jump(l.clone())));
+                    }
+                    continue;
+                }
+            }
+            _ => (),
+        }
+        out.push(quad.clone());
+    }
+    out
+}
+
+// the program's functions and statics, each a range of blocks. a function
+// runs from the block labelled with its name up to the next one, or static
+fn segments(cfg: &Cfg) -> Vec<(usize, usize)> {
+    let starts = cfg
+        .blocks
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| entry(b))
+        .map(|(i, _)| i)
+        .chain([cfg.blocks.len()])
+        .collect::<Vec<_>>();
+    starts.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+fn entry(b: &Block) -> bool {
+    matches!(b.label(), Some(Label::UserLabel(_)))
+        || matches!(b.quads.first(), Some(TQuad::Data(..)))
+}
+
+fn funcs(cfg: &Cfg) -> Vec<(usize, usize)> {
+    let mut segments = segments(cfg);
+    segments.retain(|(s, _)| cfg.blocks[*s].label().is_some());
+    segments
+}
+
+// whether the quads take a local's address, which is fp plus its offset
+fn escapes(quads: &[TQuad]) -> bool {
+    quads.iter().any(|q| {
+        matches!(
+            q,
+            TQuad::Imm(
+                _,
+                Temp::MachineTemp(_),
+                Temp::PointerReg(RiscvPointerReg::Fp),
+                _
+            )
+        )
+    })
+}
+
+// the locals known to hold a constant, by their slot's offset from fp
+type Known = HashMap<Imm, Imm>;
+
+// runs a block over what's known at its top, rewriting the loads of known
+// locals when rewrite is given
+fn transfer(quads: &[TQuad], known: &mut Known, mut rewrite: Option<&mut Vec<TQuad>>) {
+    let mut prev: Option<(Temp, Imm)> = None;
+    for quad in quads {
+        let mut quad = quad.clone();
+        match &quad {
+            TQuad::Mem(TMemOp::Store, t, off, RiscvPointerReg::Fp) => {
+                match prev.as_ref().filter(|(pt, _)| pt == t) {
+                    Some((_, k)) => known.insert(*off, *k),
+                    None => known.remove(off),
+                };
+            }
+            TQuad::Mem(TMemOp::Load, t @ Temp::MachineTemp(_), off, RiscvPointerReg::Fp) => {
+                if let Some(k) = known.get(off) {
+                    quad = push(t.clone(), *k);
+                }
+            }
+            _ => (),
+        }
+        prev = constant(&quad).map(|(t, k)| (t.clone(), k));
+        if let Some(out) = rewrite.as_deref_mut() {
+            out.push(quad);
+        }
+    }
+}
+
+// what's known at the top of a block is what every path into it agrees on
+fn meet(ins: &[&Known]) -> Known {
+    let Some((first, rest)) = ins.split_first() else {
+        return Known::new();
+    };
+    first
+        .iter()
+        .filter(|(off, k)| rest.iter().all(|r| r.get(off) == Some(k)))
+        .map(|(off, k)| (*off, *k))
+        .collect()
+}
+
+fn propagate(cfg: Cfg) -> Cfg {
+    let preds = cfg.preds();
+    let mut blocks = cfg.blocks.clone();
+    for (start, end) in funcs(&cfg) {
+        let quads = cfg.blocks[start..end]
+            .iter()
+            .flat_map(|b| b.quads.clone())
+            .collect::<Vec<_>>();
+        if escapes(&quads) {
+            continue;
+        }
+
+        // what's known at the bottom of each block, none until it's been
+        // reached. the entry knows nothing
+        let mut outs: Vec<Option<Known>> = vec![None; end - start];
+        let ins = |outs: &[Option<Known>], i: usize| match i == start {
+            true => Known::new(),
+            false => {
+                let ins = preds[i]
+                    .iter()
+                    .filter_map(|p| outs[p - start].as_ref())
+                    .collect::<Vec<_>>();
+                meet(&ins)
+            }
+        };
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for i in start..end {
+                let reached = i == start || preds[i].iter().any(|p| outs[p - start].is_some());
+                if !reached {
+                    continue;
+                }
+                let mut known = ins(&outs, i);
+                transfer(&cfg.blocks[i].quads, &mut known, None);
+                if outs[i - start].as_ref() != Some(&known) {
+                    outs[i - start] = Some(known);
+                    changed = true;
+                }
+            }
+        }
+
+        for (i, b) in blocks.iter_mut().enumerate().take(end).skip(start) {
+            let mut known = ins(&outs, i);
+            let mut quads = vec![];
+            transfer(&cfg.blocks[i].quads, &mut known, Some(&mut quads));
+            b.quads = quads;
+        }
+    }
+    Cfg { blocks }
+}
+
+// deletes the blocks no function's entry reaches, and the stores of pushed
+// constants and locals to locals that are never loaded
+fn eliminate(cfg: Cfg) -> Cfg {
+    let mut reached = HashSet::new();
+    let mut work = (0..cfg.blocks.len())
+        .filter(|i| entry(&cfg.blocks[*i]))
+        .collect::<Vec<_>>();
+    while let Some(i) = work.pop() {
+        if reached.insert(i) {
+            work.extend(&cfg.blocks[i].succs);
+        }
+    }
+
+    let mut quads = vec![];
+    for (start, end) in segments(&cfg) {
+        let segment = (start..end)
+            .filter(|i| reached.contains(i))
+            .flat_map(|i| cfg.blocks[i].quads.clone())
+            .collect::<Vec<_>>();
+        if cfg.blocks[start].label().is_none() || escapes(&segment) {
+            quads.extend(segment);
+            continue;
+        }
+
+        let loaded = segment
+            .iter()
+            .filter_map(|q| match q {
+                TQuad::Mem(TMemOp::Load, Temp::MachineTemp(_), off, RiscvPointerReg::Fp) => {
+                    Some(*off)
+                }
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        for quad in segment {
+            if let TQuad::Mem(TMemOp::Store, t, off, RiscvPointerReg::Fp) = &quad {
+                let pure = match quads.last() {
+                    Some(TQuad::Mem(TMemOp::Load, pt, _, RiscvPointerReg::Fp)) => pt == t,
+                    Some(q) => constant(q).is_some_and(|(pt, _)| pt == t),
+                    None => false,
+                };
+                if pure && !loaded.contains(off) {
+                    quads.pop();
+                    continue;
+                }
+            }
+            quads.push(quad);
+        }
+    }
+
+    ir::build(quads)
+}
+
+// value numbering over each block. a value is named by how it's computed
+// from the values under it: a constant, a version of a local, or an op on
+// other values. a local's version moves on with each store to it, and with
+// every call or store through a pointer when the function takes a local's
+// address. an op's first result is saved to a slot added to the frame when
+// the block computes it again, and each later computation is a load
+fn cse(cfg: Cfg, word: Imm) -> Cfg {
+    let mut quads = vec![];
+    for (start, end) in segments(&cfg) {
+        let blocks = &cfg.blocks[start..end];
+        let Some(size) = ir::frame_size(&blocks[0].quads) else {
+            quads.extend(blocks.iter().flat_map(|b| b.quads.clone()));
+            continue;
+        };
+        let escaped = escapes(
+            &blocks
+                .iter()
+                .flat_map(|b| b.quads.clone())
+                .collect::<Vec<_>>(),
+        );
+
+        let mut slots = 0;
+        let mut func = vec![];
+        for b in blocks {
+            let again = Numbering::new(escaped).repeated(&b.quads);
+            let saved = again
+                .into_iter()
+                .map(|v| {
+                    slots += 1;
+                    (v, -(size + word * slots))
+                })
+                .collect::<HashMap<_, _>>();
+            func.extend(Numbering::new(escaped).share(&b.quads, &saved));
+        }
+        match slots {
+            0 => quads.extend(func),
+            _ => quads.extend(ir::grow(func, size, size + (word * slots + 15) / 16 * 16)),
+        }
+    }
+    ir::build(quads)
+}
+
+// what's on the stack as a block runs: each entry's value, if it's named,
+// and where in the output the quads computing it start
+struct Numbering {
+    escaped: bool,
+    stack: Vec<(Option<String>, usize)>,
+    versions: HashMap<Imm, usize>,
+    epoch: usize,
+}
+
+impl Numbering {
+    fn new(escaped: bool) -> Self {
+        Numbering {
+            escaped,
+            stack: vec![],
+            versions: HashMap::new(),
+            epoch: 0,
+        }
+    }
+
+    // an entry from an earlier block is unnamed, and starts nowhere here
+    fn pop(&mut self) -> (Option<String>, usize) {
+        self.stack.pop().unwrap_or((None, usize::MAX))
+    }
+
+    fn op(&mut self, name: String, operands: usize, at: usize) -> Option<String> {
+        let popped = (0..operands).map(|_| self.pop()).collect::<Vec<_>>();
+        let start = popped.last().map_or(at, |(_, s)| *s);
+        let names = popped
+            .into_iter()
+            .rev()
+            .map(|(v, _)| v)
+            .collect::<Option<Vec<_>>>();
+        let value = names.filter(|_| start != usize::MAX).map(|mut names| {
+            if commutes(&name) {
+                names.sort();
+            }
+            format!("{name}({})", names.join(", "))
+        });
+        self.stack.push((value.clone(), start));
+        value
+    }
+
+    // runs a quad at index at of the output, returning the value of the op
+    // it computes, if it's named
+    fn step(&mut self, quad: &TQuad, at: usize) -> Option<String> {
+        let (z, fp) = (Temp::PointerReg(RiscvPointerReg::Z), RiscvPointerReg::Fp);
+        match quad {
+            TQuad::Imm(_, Temp::MachineTemp(_), l, k) if *l == z => {
+                self.stack.push((Some(k.to_string()), at));
+            }
+            TQuad::Imm(op, Temp::MachineTemp(_), Temp::MachineTemp(_), k) => {
+                return self.op(format!("{op}.{k}"), 1, at);
+            }
+            TQuad::Reg(op, ..) => return self.op(op.to_string(), 2, at),
+            TQuad::Float(op, ..) => return self.op(op.to_string(), 2, at),
+            TQuad::Cvt(op, ..) => return self.op(op.to_string(), 1, at),
+            TQuad::Mem(TMemOp::Load, Temp::MachineTemp(_), off, base) if *base == fp => {
+                let version = self.versions.get(off).copied().unwrap_or(0);
+                let local = format!("fp{off}.{version}.{}", self.epoch);
+                self.stack.push((Some(local), at));
+            }
+            TQuad::Mem(TMemOp::Store, t, off, base) => {
+                if matches!(t, Temp::MachineTemp(_)) {
+                    self.pop();
+                }
+                if *base == fp {
+                    *self.versions.entry(*off).or_default() += 1;
+                }
+            }
+            TQuad::Imm(_, Temp::MachineTemp(_), _, _)
+            | TQuad::Mem(TMemOp::Load, Temp::MachineTemp(_), _, _)
+            | TQuad::Str(..)
+            | TQuad::Static(..) => self.stack.push((None, at)),
+            TQuad::Imm(_, _, Temp::MachineTemp(_), _)
+            | TQuad::Branch(..)
+            | TQuad::JumpTable(..) => {
+                self.pop();
+            }
+            TQuad::Deref(TMemOp::Load | TMemOp::LoadByte | TMemOp::LoadByteU, ..) => {
+                self.pop();
+                self.stack.push((None, at));
+            }
+            TQuad::Deref(TMemOp::Store | TMemOp::StoreByte, ..) => {
+                self.pop();
+                self.pop();
+                self.epoch += self.escaped as usize;
+            }
+            TQuad::Pseudo(PseudoOp::Call(_) | PseudoOp::CallC(_)) => {
+                self.epoch += self.escaped as usize
+            }
+            _ => (),
+        }
+        None
+    }
+
+    // the ops a block computes more than once
+    fn repeated(mut self, quads: &[TQuad]) -> HashSet<String> {
+        let mut seen = HashSet::new();
+        let mut again = HashSet::new();
+        for (i, quad) in quads.iter().enumerate() {
+            if let Some(v) = self.step(quad, i) {
+                if !seen.insert(v.clone()) {
+                    again.insert(v);
+                }
+            }
+        }
+        again
+    }
+
+    // the block with each saved op stored to its slot the first time, and
+    // loaded from it after
+    fn share(mut self, quads: &[TQuad], saved: &HashMap<String, Imm>) -> Vec<TQuad> {
+        let mut out: Vec<TQuad> = vec![];
+        let mut stored = HashSet::new();
+        for quad in quads {
+            let at = out.len();
+            let Some(v) = self.step(quad, at).filter(|v| saved.contains_key(v)) else {
+                out.push(quad.clone());
+                continue;
+            };
+            let (slot, d) = (saved[&v], quad_dest(quad));
+            let start = self.stack.last().expect("the op was pushed").1;
+            if stored.contains(&v) && out[start..].iter().all(pure) {
+                out.truncate(start);
+                out.push(TQuad::Mem(TMemOp::Load, d, slot, RiscvPointerReg::Fp));
+            } else {
+                // stored and loaded back, since the op's result is still used
+                stored.insert(v);
+                out.push(quad.clone());
+                out.push(TQuad::Mem(
+                    TMemOp::Store,
+                    d.clone(),
+                    slot,
+                    RiscvPointerReg::Fp,
+                ));
+                out.push(TQuad::Mem(TMemOp::Load, d, slot, RiscvPointerReg::Fp));
+            }
+        }
+        out
+    }
+}
+
+fn commutes(op: &str) -> bool {
+    [
+        "add", "mul", "and", "or", "xor", "fadd.s", "fmul.s", "feq.s",
+    ]
+    .contains(&op)
+}
+
+fn quad_dest(quad: &TQuad) -> Temp {
+    match quad {
+        TQuad::Reg(_, d, _, _)
+        | TQuad::Imm(_, d, _, _)
+        | TQuad::Float(_, d, _, _)
+        | TQuad::Cvt(_, d, _) => d.clone(),
+        _ => unreachable!("only ops are numbered"),
+    }
+}
+
+// whether a quad only computes a value, so it can be deleted
+fn pure(quad: &TQuad) -> bool {
+    match quad {
+        TQuad::Mem(TMemOp::Load, Temp::MachineTemp(_), _, RiscvPointerReg::Fp) => true,
+        TQuad::Imm(_, Temp::MachineTemp(_), l, _) => {
+            matches!(
+                l,
+                Temp::MachineTemp(_) | Temp::PointerReg(RiscvPointerReg::Z)
+            )
+        }
+        TQuad::Reg(..) | TQuad::Float(..) | TQuad::Cvt(..) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test_optimizer {
+    use crate::{ir, lexer, parser_ast, translator, IntModel, OptLevel};
+    use std::collections::HashSet;
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/opt";
+
+    fn cfgs(file: &str) -> (ir::Cfg, ir::Cfg) {
+        let src = fs::read_to_string(format!("{TEST_DIR}/{file}")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let before = ir::lower(&trgt_tree, &HashSet::new(), &IntModel::default());
+        let levels = super::levels(&src_tree);
+        let after = super::optimize(before.clone(), &OptLevel::O1, &levels, &IntModel::default());
+        (before, after)
+    }
+
+    #[test]
+    fn attrs() {
+        let (_, after) = cfgs("attrs.c");
+        insta::assert_snapshot!(after.to_string(), @r###"
+        b0 -> [1]
+            Label(UserLabel("debugged"))
+            Imm(AddI, PointerReg(Sp), PointerReg(Sp), -16)
+            Mem(Store, PointerReg(Ra), 12, Sp)
+            Mem(Store, PointerReg(Fp), 8, Sp)
+            Imm(AddI, PointerReg(Fp), PointerReg(Sp), 16)
+            Imm(AddI, MachineTemp(1), PointerReg(Z), 1)
+            Imm(AddI, MachineTemp(2), PointerReg(Z), 2)
+            Reg(Add, MachineTemp(0), MachineTemp(1), MachineTemp(2))
+            Imm(AddI, PointerReg(A0), MachineTemp(0), 0)
+            Pseudo(Jump(MachineLabel(0)))
+        b1 -> []
+            Label(MachineLabel(0))
+            Mem(Load, PointerReg(Ra), 12, Sp)
+            Mem(Load, PointerReg(Fp), 8, Sp)
+            Imm(AddI, PointerReg(Sp), PointerReg(Sp), 16)
+            Pseudo(Ret)
+        b2 -> [3]
+            Label(UserLabel("main"))
+            Imm(AddI, PointerReg(Sp), PointerReg(Sp), -16)
+            Mem(Store, PointerReg(Ra), 12, Sp)
+            Mem(Store, PointerReg(Fp), 8, Sp)
+            Imm(AddI, PointerReg(Fp), PointerReg(Sp), 16)
+            Pseudo(Call(UserLabel("debugged")))
+            Imm(AddI, MachineTemp(4), PointerReg(A0), 0)
+            Imm(AddI, MachineTemp(5), PointerReg(Z), 12)
+            Reg(Add, MachineTemp(3), MachineTemp(4), MachineTemp(5))
+            Imm(AddI, PointerReg(A0), MachineTemp(3), 0)
+            Pseudo(Jump(MachineLabel(1)))
+        b3 -> []
+            Label(MachineLabel(1))
+            Mem(Load, PointerReg(Ra), 12, Sp)
+            Mem(Load, PointerReg(Fp), 8, Sp)
+            Imm(AddI, PointerReg(Sp), PointerReg(Sp), 16)
+            Pseudo(Ret)
+        "###);
+    }
+
+    #[test]
+    fn fold() {
+        let (before, after) = cfgs("fold.c");
+        insta::assert_snapshot!(before.to_string(), @r###"
+        b0 -> [1]
+            Label(UserLabel("main"))
+            Imm(AddI, PointerReg(Sp), PointerReg(Sp), -32)
+            Mem(Store, PointerReg(Ra), 28, Sp)
+            Mem(Store, PointerReg(Fp), 24, Sp)
+            Imm(AddI, PointerReg(Fp), PointerReg(Sp), 32)
+            Imm(AddI, MachineTemp(1), PointerReg(Z), 2)
+            Imm(AddI, MachineTemp(2), PointerReg(Z), 3)
+            Reg(Add, MachineTemp(0), MachineTemp(1), MachineTemp(2))
+            Mem(Store, MachineTemp(0), -12, Fp)
+            Mem(Load, MachineTemp(4), -12, Fp)
+            Imm(AddI, MachineTemp(5), PointerReg(Z), 4)
+            Reg(Mul, MachineTemp(3), MachineTemp(4), MachineTemp(5))
+            Mem(Store, MachineTemp(3), -16, Fp)
+            Mem(Load, MachineTemp(7), -16, Fp)
+            Imm(AddI, MachineTemp(8), PointerReg(Z), 1)
+            Reg(Sub, MachineTemp(6), MachineTemp(7), MachineTemp(8))
+            Mem(Store, MachineTemp(6), -20, Fp)
+        b1 -> [2, 4]
+            Label(MachineLabel(0))
+            Imm(AddI, MachineTemp(9), PointerReg(Z), 0)
+            Branch(Beq, MachineTemp(9), PointerReg(Z), MachineLabel(2))
+        b2 -> [3]
+            Pseudo(Jump(MachineLabel(1)))
+        b3 -> [1]
+            Label(MachineLabel(1))
+            Imm(AddI, MachineTemp(10), PointerReg(Z), 1)
+            Mem(Store, MachineTemp(10), -16, Fp)
+            Pseudo(Jump(MachineLabel(0)))
+        b4 -> [5]
+            Label(MachineLabel(2))
+            Mem(Load, MachineTemp(11), -16, Fp)
+            Imm(AddI, PointerReg(A0), MachineTemp(11), 0)
+            Pseudo(Jump(MachineLabel(3)))
+        b5 -> []
+            Label(MachineLabel(3))
+            Mem(Load, PointerReg(Ra), 28, Sp)
+            Mem(Load, PointerReg(Fp), 24, Sp)
+            Imm(AddI, PointerReg(Sp), PointerReg(Sp), 32)
+            Pseudo(Ret)
+        "###);
+        insta::assert_snapshot!(after.to_string(), @r###"
+        b0 -> [1]
+            Label(UserLabel("main"))
+            Imm(AddI, PointerReg(Sp), PointerReg(Sp), -32)
+            Mem(Store, PointerReg(Ra), 28, Sp)
+            Mem(Store, PointerReg(Fp), 24, Sp)
+            Imm(AddI, PointerReg(Fp), PointerReg(Sp), 32)
+        b1 -> [2]
+            Label(MachineLabel(0))
+            Pseudo(Jump(MachineLabel(2)))
+        b2 -> [3]
+            Label(MachineLabel(2))
+            Imm(AddI, MachineTemp(11), PointerReg(Z), 20)
+            Imm(AddI, PointerReg(A0), MachineTemp(11), 0)
+            Pseudo(Jump(MachineLabel(3)))
+        b3 -> []
+            Label(MachineLabel(3))
+            Mem(Load, PointerReg(Ra), 28, Sp)
+            Mem(Load, PointerReg(Fp), 24, Sp)
+            Imm(AddI, PointerReg(Sp), PointerReg(Sp), 32)
+            Pseudo(Ret)
+        "###);
+    }
+
+    #[test]
+    fn propagate() {
+        // n and sum change in the loop, so only what's stored before it is known
+        let (before, after) = cfgs("loop.c");
+        insta::assert_snapshot!(before.to_string(), @r###"
+        b0 -> [1]
+            Label(UserLabel("main"))
+            Imm(AddI, PointerReg(Sp), PointerReg(Sp), -16)
+            Mem(Store, PointerReg(Ra), 12, Sp)
+            Mem(Store, PointerReg(Fp), 8, Sp)
+            Imm(AddI, PointerReg(Fp), PointerReg(Sp), 16)
+            Imm(AddI, MachineTemp(0), PointerReg(Z), 3)
+            Mem(Store, MachineTemp(0), -12, Fp)
+            Imm(AddI, MachineTemp(1), PointerReg(Z), 0)
+            Mem(Store, MachineTemp(1), -16, Fp)
+        b1 -> [2, 4]
+            Label(MachineLabel(0))
+            Mem(Load, MachineTemp(2), -12, Fp)
+            Branch(Beq, MachineTemp(2), PointerReg(Z), MachineLabel(2))
+        b2 -> [3]
+            Pseudo(Jump(MachineLabel(1)))
+        b3 -> [1]
+            Label(MachineLabel(1))
+            Mem(Load, MachineTemp(4), -16, Fp)
+            Mem(Load, MachineTemp(5), -12, Fp)
+            Reg(Add, MachineTemp(3), MachineTemp(4), MachineTemp(5))
+            Mem(Store, MachineTemp(3), -16, Fp)
+            Mem(Load, MachineTemp(7), -12, Fp)
+            Imm(AddI, MachineTemp(8), PointerReg(Z), 1)
+            Reg(Sub, MachineTemp(6), MachineTemp(7), MachineTemp(8))
+            Mem(Store, MachineTemp(6), -12, Fp)
+            Pseudo(Jump(MachineLabel(0)))
+        b4 -> [5]
+            Label(MachineLabel(2))
+            Mem(Load, MachineTemp(10), -16, Fp)
+            Imm(AddI, MachineTemp(12), PointerReg(Z), 8)
+            Imm(AddI, MachineTemp(13), PointerReg(Z), 4)
+            Reg(Div, MachineTemp(11), MachineTemp(12), MachineTemp(13))
+            Reg(Add, MachineTemp(9), MachineTemp(10), MachineTemp(11))
+            Imm(AddI, PointerReg(A0), MachineTemp(9), 0)
+            Pseudo(Jump(MachineLabel(3)))
+        b5 -> []
+            Label(MachineLabel(3))
+            Mem(Load, PointerReg(Ra), 12, Sp)
+            Mem(Load, PointerReg(Fp), 8, Sp)
+            Imm(AddI, PointerReg(Sp), PointerReg(Sp), 16)
+            Pseudo(Ret)
+        "###);
+        insta::assert_snapshot!(after.to_string(), @r###"
+        b0 -> [1]
+            Label(UserLabel("main"))
+            Imm(AddI, PointerReg(Sp), PointerReg(Sp), -16)
+            Mem(Store, PointerReg(Ra), 12, Sp)
+            Mem(Store, PointerReg(Fp), 8, Sp)
+            Imm(AddI, PointerReg(Fp), PointerReg(Sp), 16)
+            Imm(AddI, MachineTemp(0), PointerReg(Z), 3)
+            Mem(Store, MachineTemp(0), -12, Fp)
+            Imm(AddI, MachineTemp(1), PointerReg(Z), 0)
+            Mem(Store, MachineTemp(1), -16, Fp)
+        b1 -> [2, 4]
+            Label(MachineLabel(0))
+            Mem(Load, MachineTemp(2), -12, Fp)
+            Branch(Beq, MachineTemp(2), PointerReg(Z), MachineLabel(2))
+        b2 -> [3]
+            Pseudo(Jump(MachineLabel(1)))
+        b3 -> [1]
+            Label(MachineLabel(1))
+            Mem(Load, MachineTemp(4), -16, Fp)
+            Mem(Load, MachineTemp(5), -12, Fp)
+            Reg(Add, MachineTemp(3), MachineTemp(4), MachineTemp(5))
+            Mem(Store, MachineTemp(3), -16, Fp)
+            Mem(Load, MachineTemp(7), -12, Fp)
+            Imm(AddI, MachineTemp(8), PointerReg(Z), 1)
+            Reg(Sub, MachineTemp(6), MachineTemp(7), MachineTemp(8))
+            Mem(Store, MachineTemp(6), -12, Fp)
+            Pseudo(Jump(MachineLabel(0)))
+        b4 -> [5]
+            Label(MachineLabel(2))
+            Mem(Load, MachineTemp(10), -16, Fp)
+            Imm(AddI, MachineTemp(11), PointerReg(Z), 2)
+            Reg(Add, MachineTemp(9), MachineTemp(10), MachineTemp(11))
+            Imm(AddI, PointerReg(A0), MachineTemp(9), 0)
+            Pseudo(Jump(MachineLabel(3)))
+        b5 -> []
+            Label(MachineLabel(3))
+            Mem(Load, PointerReg(Ra), 12, Sp)
+            Mem(Load, PointerReg(Fp), 8, Sp)
+            Imm(AddI, PointerReg(Sp), PointerReg(Sp), 16)
+            Pseudo(Ret)
+        "###);
+    }
+
+    #[test]
+    fn cse() {
+        // a * b is saved to a slot the frame grows by, then loaded. b * a is
+        // computed again, as a changed since
+        let (_, after) = cfgs("cse.c");
+        insta::assert_snapshot!(after.to_string(), @r###"
+        b0 -> [1]
+            Label(UserLabel("f"))
+            Imm(AddI, PointerReg(Sp), PointerReg(Sp), -48)
+            Mem(Store, PointerReg(Ra), 44, Sp)
+            Mem(Store, PointerReg(Fp), 40, Sp)
+            Imm(AddI, PointerReg(Fp), PointerReg(Sp), 48)
+            Imm(AddI, MachineTemp(0), PointerReg(A0), 0)
+            Mem(Store, MachineTemp(0), -12, Fp)
+            Imm(AddI, MachineTemp(1), PointerReg(A1), 0)
+            Mem(Store, MachineTemp(1), -16, Fp)
+            Mem(Load, MachineTemp(5), -12, Fp)
+            Mem(Load, MachineTemp(6), -16, Fp)
+            Reg(Mul, MachineTemp(3), MachineTemp(5), MachineTemp(6))
+            Mem(Store, MachineTemp(3), -36, Fp)
+            Mem(Load, MachineTemp(3), -36, Fp)
+            Imm(AddI, MachineTemp(4), PointerReg(Z), 1)
+            Reg(Add, MachineTemp(2), MachineTemp(3), MachineTemp(4))
+            Mem(Store, MachineTemp(2), -20, Fp)
+            Mem(Load, MachineTemp(8), -36, Fp)
+            Imm(AddI, MachineTemp(9), PointerReg(Z), 2)
+            Reg(Add, MachineTemp(7), MachineTemp(8), MachineTemp(9))
+            Mem(Store, MachineTemp(7), -24, Fp)
+            Mem(Load, MachineTemp(13), -12, Fp)
+            Imm(AddI, MachineTemp(14), PointerReg(Z), 1)
+            Reg(Add, MachineTemp(12), MachineTemp(13), MachineTemp(14))
+            Mem(Store, MachineTemp(12), -12, Fp)
+            Mem(Load, MachineTemp(18), -20, Fp)
+            Mem(Load, MachineTemp(19), -24, Fp)
+            Reg(Add, MachineTemp(16), MachineTemp(18), MachineTemp(19))
+            Mem(Load, MachineTemp(20), -16, Fp)
+            Mem(Load, MachineTemp(21), -12, Fp)
+            Reg(Mul, MachineTemp(17), MachineTemp(20), MachineTemp(21))
+            Reg(Add, MachineTemp(15), MachineTemp(16), MachineTemp(17))
+            Imm(AddI, PointerReg(A0), MachineTemp(15), 0)
+            Pseudo(Jump(MachineLabel(0)))
+        b1 -> []
+            Label(MachineLabel(0))
+            Mem(Load, PointerReg(Ra), 44, Sp)
+            Mem(Load, PointerReg(Fp), 40, Sp)
+            Imm(AddI, PointerReg(Sp), PointerReg(Sp), 48)
+            Pseudo(Ret)
+        b2 -> [3]
+            Label(UserLabel("main"))
+            Imm(AddI, PointerReg(Sp), PointerReg(Sp), -16)
+            Mem(Store, PointerReg(Ra), 12, Sp)
+            Mem(Store, PointerReg(Fp), 8, Sp)
+            Imm(AddI, PointerReg(Fp), PointerReg(Sp), 16)
+            Imm(AddI, MachineTemp(23), PointerReg(Z), 3)
+            Imm(AddI, MachineTemp(24), PointerReg(Z), 4)
+            Imm(AddI, PointerReg(A1), MachineTemp(24), 0)
+            Imm(AddI, PointerReg(A0), MachineTemp(23), 0)
+            Pseudo(Call(UserLabel("f")))
+            Imm(AddI, MachineTemp(22), PointerReg(A0), 0)
+            Imm(AddI, PointerReg(A0), MachineTemp(22), 0)
+            Pseudo(Jump(MachineLabel(1)))
+        b3 -> []
+            Label(MachineLabel(1))
+            Mem(Load, PointerReg(Ra), 12, Sp)
+            Mem(Load, PointerReg(Fp), 8, Sp)
+            Imm(AddI, PointerReg(Sp), PointerReg(Sp), 16)
+            Pseudo(Ret)
+        "###);
+    }
+}
@@ -1,24 +1,21 @@
 use crate::{
     lexer::{Token, TT},
-    ConstantNode, Node, ReturnNode, StartNode,
+    AddNode, ConstantNode, Node, ReturnNode, StartNode, SubNode,
 };
 use std::io;
 use std::rc::Rc;
 
 fn mtch(tokens: &[Token], tt: TT) -> Result<(&Token, &[Token]), io::Error> {
     match tokens {
-        [] => Err(io::Error::new(
-            io::ErrorKind::Other,
-            format!("expected: {:?} got: {:?}", tt, tokens),
-        )),
+        [] => Err(io::Error::other(format!(
+            "expected: {:?} got: {:?}",
+            tt, tokens
+        ))),
         [f, r @ ..] => {
             if f.typ == tt {
                 Ok((f, r))
             } else {
-                Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("expected: {:?} got: {:?}", tt, f),
-                ))
+                Err(io::Error::other(format!("expected: {:?} got: {:?}", tt, f)))
             }
         }
     }
@@ -39,10 +36,10 @@ pub fn parse_prg(tokens: &[Token]) -> Result<Rc<dyn Node>, io::Error> {
     if r.is_empty() {
         Ok(stmt.clone())
     } else {
-        Err(io::Error::new(
-            io::ErrorKind::Other,
-            format!("expected empty token stream, got {:?}", r),
-        ))
+        Err(io::Error::other(format!(
+            "expected empty token stream, got {:?}",
+            r
+        )))
     }
 }
 
@@ -51,42 +48,61 @@ fn parse_stmt(
     tokens: &[Token],
 ) -> Result<(Rc<dyn Node>, &[Token]), io::Error> {
     match tokens {
-        [] => Err(io::Error::new(
-            io::ErrorKind::Other,
-            "expected: {:?} got an empty token stream",
-        )),
+        [] => Err(io::Error::other("expected: {:?} got an empty token stream")),
         [f, r @ ..] => match f.typ {
             TT::KeywordRet => {
                 let (expr, r) = parse_expr(start.clone(), r)?;
                 let (_, r) = mtch(r, TT::PuncSemiColon)?;
                 Ok((Rc::new(ReturnNode::new(start, expr)), r))
             }
-            t => Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("expected: {:?} got: {:?}", TT::KeywordRet, t),
-            )),
+            t => Err(io::Error::other(format!(
+                "expected: {:?} got: {:?}",
+                TT::KeywordRet,
+                t
+            ))),
         },
     }
 }
 
+// additions and subtractions of literals, left associative
 fn parse_expr(
     start: Rc<dyn Node>,
     tokens: &[Token],
+) -> Result<(Rc<dyn Node>, &[Token]), io::Error> {
+    let (mut lhs, mut r) = parse_primary(start.clone(), tokens)?;
+    while let [f, _r @ ..] = r {
+        let (rhs, _r) = match f.typ {
+            TT::Plus | TT::Minus => parse_primary(start.clone(), _r)?,
+            _ => break,
+        };
+        lhs = match f.typ {
+            TT::Plus => Rc::new(AddNode::new(lhs, rhs)),
+            _ => Rc::new(SubNode::new(lhs, rhs)),
+        };
+        r = _r;
+    }
+    Ok((lhs, r))
+}
+
+fn parse_primary(
+    start: Rc<dyn Node>,
+    tokens: &[Token],
 ) -> Result<(Rc<dyn Node>, &[Token]), io::Error> {
     match tokens {
-        [] => Err(io::Error::new(
-            io::ErrorKind::Other,
-            "expected: {:?} got an empty token stream",
-        )),
+        [] => Err(io::Error::other("expected: {:?} got an empty token stream")),
         [f, r @ ..] => match f.typ {
             TT::LiteralInt => Ok((
-                Rc::new(ConstantNode::new(start, f.lexeme.parse().unwrap())),
+                Rc::new(ConstantNode::new(
+                    start,
+                    f.int.expect("the lexer reads int literals").val as i32,
+                )),
                 r,
             )),
-            t => Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("expected: {:?} got: {:?}", TT::LiteralInt, t),
-            )),
+            t => Err(io::Error::other(format!(
+                "expected: {:?} got: {:?}",
+                TT::LiteralInt,
+                t
+            ))),
         },
     }
 }
@@ -100,13 +116,9 @@ mod test_arith {
 
     #[test]
     fn lit() {
-        let chars = fs::read(format!("{TEST_DIR}/lit.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let src = fs::read_to_string(format!("{TEST_DIR}/lit.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_debug_snapshot!(tree, @r###"
         StartNode
@@ -117,14 +129,33 @@ mod test_arith {
 
     #[test]
     fn add() {
-        let chars = fs::read(format!("{TEST_DIR}/add.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let src = fs::read_to_string(format!("{TEST_DIR}/add.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
-        insta::assert_debug_snapshot!(tree, @"");
+        insta::assert_debug_snapshot!(tree, @r###"
+        StartNode
+        ConstantNode(9)
+        ConstantNode(10)
+        AddNode
+        ReturnNode
+        "###);
+    }
+
+    #[test]
+    fn sub_associative() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/sub_associative.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_debug_snapshot!(tree, @r###"
+        StartNode
+        ConstantNode(30)
+        ConstantNode(9)
+        SubNode
+        ConstantNode(10)
+        SubNode
+        ReturnNode
+        "###);
     }
 }
@@ -1,199 +1,1040 @@
 use crate::{
-    lexer::{Token, TT},
-    SBinOp, SDef, SExpr, SFuncDef, SPrg, SRelOp, SStmt, SVarDef, Type,
+    lexer::{Base, IntLit, Token, TT},
+    source::{self, Span},
+    OptLevel, SAttr, SBinOp, SBitOp, SCase, SContract, SContractKind, SDef, SEnumDef, SExpr,
+    SFuncDecl, SFuncDef, SIncDecOp, SLogOp, SPrg, SRelOp, SStmt, SStore, SStructDef, SUnaryOp,
+    SVarDecl, SVarDef, SVarUpdate, Spanned, Type,
 };
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::io;
-use std::num::ParseIntError;
+
+// the lexer hack: whether `foo x;` declares x depends on whether foo names a
+// type, so typedef names are tracked while parsing and resolved on the spot.
+// enumerators are too, to their values, so the backends only see constants.
+// thread local so that each parse (and each test) starts from none
+// todo: typedefs and enums in blocks, scoped to the block
+thread_local! {
+    static TYPEDEFS: RefCell<HashMap<String, Type>> = RefCell::new(HashMap::new());
+    static ENUMERATORS: RefCell<HashMap<String, i32>> = RefCell::new(HashMap::new());
+    // the struct and union tags defined so far, and whether each is a union's
+    static TAGS: RefCell<HashMap<String, bool>> = RefCell::new(HashMap::new());
+    // the errors recovered from so far when parsing a whole program with
+    // parse. none otherwise, and then the first error is returned as is
+    static RECOVERED: RefCell<Option<Vec<ParseError>>> = const { RefCell::new(None) };
+    // how deep the stmts, exprs and pointers being parsed nest
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+// nesting is bounded, so that no input recurses the parser (or whatever
+// walks the tree after it) off the stack, even a test thread's. c99 asks
+// for 63 levels of parenthesized expressions. a chain of operators is
+// parsed in a loop, but it's a tree as deep as it's long, so each operator
+// counts as a level too
+pub const MAX_DEPTH: usize = 63;
+
+// one level deeper until it's dropped, or an error past MAX_DEPTH
+struct Depth;
+
+impl Depth {
+    fn enter(tokens: &[Token]) -> Result<Depth, io::Error> {
+        Depth::fits(tokens, 1)?;
+        DEPTH.set(DEPTH.get() + 1);
+        Ok(Depth)
+    }
+
+    // an error, unless an expr n levels high fits under what's being parsed
+    fn fits(tokens: &[Token], n: usize) -> Result<(), io::Error> {
+        if DEPTH.get() + n > MAX_DEPTH {
+            let e = io::Error::other(format!("nesting deeper than {MAX_DEPTH} levels"));
+            return Err(match tokens.first() {
+                Some(t) => source::locate(e, t.span),
+                None => e,
+            });
+        }
+        Ok(())
+    }
+}
+
+// how many levels of exprs e is, itself included. it's only called on
+// trees the parser's built, which Depth keeps shallow
+fn height(e: &SExpr) -> usize {
+    1 + match e {
+        SExpr::Int(_)
+        | SExpr::TypedInt { .. }
+        | SExpr::Float { .. }
+        | SExpr::Bool(_)
+        | SExpr::Str(_)
+        | SExpr::VarApp(_)
+        | SExpr::Alloc(_)
+        | SExpr::SizeOfType(_) => 0,
+        SExpr::AllocArray(_, l)
+        | SExpr::Index { idx: l, .. }
+        | SExpr::UnaryE { l, .. }
+        | SExpr::AddrOf(l)
+        | SExpr::Deref(l)
+        | SExpr::SizeOf(l)
+        | SExpr::Member { base: l, .. }
+        | SExpr::IncDec { lval: l, .. }
+        | SExpr::Cast(_, l) => height(l),
+        SExpr::BinE { l, r, .. }
+        | SExpr::LogE { l, r, .. }
+        | SExpr::BitE { l, r, .. }
+        | SExpr::RelE { l, r, .. } => height(l).max(height(r)),
+        SExpr::CondE { cond, then, els } => height(cond).max(height(then)).max(height(els)),
+        SExpr::FuncApp { aps, .. } => aps.iter().map(|ap| height(ap)).max().unwrap_or(0),
+    }
+}
+
+impl Drop for Depth {
+    fn drop(&mut self) {
+        DEPTH.set(DEPTH.get() - 1);
+    }
+}
+
+// a syntax error, at the token the parser gave up on
+#[derive(Debug, thiserror::Error)]
+#[error("{msg}")]
+pub struct ParseError {
+    pub msg: String,
+    pub span: Span,
+}
+
+impl ParseError {
+    // at is where the construct that didn't parse began, for errors that
+    // don't know where they happened themselves
+    fn new(e: io::Error, at: &Token) -> Self {
+        ParseError {
+            span: source::located(&e).map_or(at.span, |l| l.span),
+            msg: e.to_string(),
+        }
+    }
+}
+
+impl From<ParseError> for io::Error {
+    fn from(e: ParseError) -> Self {
+        source::locate(io::Error::other(e.msg), e.span)
+    }
+}
+
+fn typedef(alias: &str) -> Option<Type> {
+    TYPEDEFS.with_borrow(|tds| tds.get(alias).cloned())
+}
+
+// struct s or union u, as the keyword says. a tag that's been defined has
+// to be used with the keyword it was defined with
+fn tag(keyword: &Token, alias: &Token) -> Result<Type, io::Error> {
+    let union = keyword.typ == TT::KeywordUnion;
+    match TAGS.with_borrow(|tags| tags.get(&alias.lexeme).copied()) {
+        Some(u) if u != union => {
+            let e = format!("{} defined as wrong kind of tag", alias.lexeme);
+            Err(source::locate(io::Error::other(e), keyword.span))
+        }
+        _ if union => Ok(Type::Union(alias.lexeme.to_owned())),
+        _ => Ok(Type::Struct(alias.lexeme.to_owned())),
+    }
+}
+
+fn enumerator(alias: &str) -> Option<i32> {
+    ENUMERATORS.with_borrow(|es| es.get(alias).copied())
+}
 
 fn eat(tokens: &[Token], tt: TT) -> Result<(&Token, &[Token]), io::Error> {
     match tokens {
-        [] => Err(io::Error::new(
-            io::ErrorKind::Other,
-            format!("expected: {:?} got: {:?}", tt, tokens),
-        )),
+        [] => Err(io::Error::other(format!(
+            "expected: {:?} got: end of input",
+            tt
+        ))),
         [f, r @ ..] => {
             if f.typ == tt {
                 Ok((f, r))
             } else {
-                Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("expected: {:?} got: {:?}", tt, f),
-                ))
+                let e = io::Error::other(format!("expected: {:?} got: {:?}", tt, f.typ));
+                Err(source::locate(e, f.span))
             }
         }
     }
 }
 
+// the span of the tokens a node was parsed from: the first of tokens up to
+// r, which is what's left after it
+fn span(tokens: &[Token], r: &[Token]) -> Span {
+    let last = &tokens[tokens.len() - r.len() - 1];
+    tokens[0].span.to(last.span)
+}
+
+fn spanned<T>(node: T, tokens: &[Token], r: &[Token]) -> Spanned<T> {
+    Spanned::new(node, span(tokens, r))
+}
+
+// the first syntax error, if there are any
 pub fn parse_prg(tokens: &[Token]) -> Result<SPrg, io::Error> {
-    let (mut fds, mut r) = (vec![], tokens);
-    while let Ok((fd, _r)) = parse_funcdef(r) {
-        fds.push(fd);
+    let (prg, errs) = parse(tokens);
+    match errs.into_iter().next() {
+        Some(e) => Err(e.into()),
+        None => Ok(prg),
+    }
+}
+
+// as much of the program as parses, and why the rest didn't. after an
+// error the parser skips ahead to the end of the definition or stmt it was
+// in, and carries on from there
+pub fn parse(tokens: &[Token]) -> (SPrg, Vec<ParseError>) {
+    TYPEDEFS.with_borrow_mut(|tds| tds.clear());
+    ENUMERATORS.with_borrow_mut(|es| es.clear());
+    TAGS.with_borrow_mut(|tags| tags.clear());
+    RECOVERED.set(Some(vec![]));
+    DEPTH.set(0);
+
+    let (mut defs, mut r) = (vec![], tokens);
+    while let [f, ..] = r {
+        // struct s { ... }; defines a struct, union u { ... }; a union,
+        // enum e { ... }; some constants, typedef t a; names a type, and
+        // anything else is a variable or a function
+        let def = match r {
+            [f, _, t, ..] if f.typ == TT::KeywordStruct && t.typ == TT::PuncLeftBrace => {
+                parse_structdef(r).map(|(sd, r)| (Some(SDef::StructDef(sd)), r))
+            }
+            [f, _, t, ..] if f.typ == TT::KeywordUnion && t.typ == TT::PuncLeftBrace => {
+                parse_structdef(r).map(|(ud, r)| (Some(SDef::UnionDef(ud)), r))
+            }
+            [f, a, t, ..]
+                if f.typ == TT::KeywordEnum
+                    && (a.typ == TT::PuncLeftBrace || t.typ == TT::PuncLeftBrace) =>
+            {
+                parse_enumdef(r).map(|(ed, r)| (Some(SDef::EnumDef(ed)), r))
+            }
+            [f, ..] if f.typ == TT::KeywordTypedef => parse_typedef(r).map(|r| (None, r)),
+            _ => parse_global(r).map(|(def, r)| (Some(def), r)),
+        };
+        match def {
+            Ok((def, _r)) => {
+                defs.extend(def.map(|def| spanned(def, r, _r)));
+                r = _r;
+            }
+            Err(e) => {
+                recover(e, f).expect("recovering");
+                r = synchronize(r);
+            }
+        }
+    }
+
+    // in source order: a definition's error may be at its start, and only
+    // known after those inside it
+    let mut errs = RECOVERED.take().unwrap_or_default();
+    errs.sort_by_key(|e| std::cmp::Reverse(e.span.rest));
+    (defs, errs)
+}
+
+// keeps e to report later, if parsing a whole program, and otherwise gives
+// it back
+fn recover(e: io::Error, at: &Token) -> Result<(), io::Error> {
+    RECOVERED.with_borrow_mut(|errs| match errs {
+        Some(errs) => {
+            errs.push(ParseError::new(e, at));
+            Ok(())
+        }
+        None => Err(e),
+    })
+}
+
+// the stmts up to the end of a block, or of a switch arm. one that doesn't
+// parse is skipped if it can be recovered from
+fn parse_stmts(
+    tokens: &[Token],
+    end: fn(&TT) -> bool,
+) -> Result<(Vec<Spanned<SStmt>>, &[Token]), io::Error> {
+    let (mut stmts, mut r) = (vec![], tokens);
+    while let [f, ..] = r {
+        if end(&f.typ) {
+            break;
+        }
+        match parse_stmt(r) {
+            Ok((s, _r)) => {
+                stmts.push(s);
+                r = _r;
+            }
+            Err(e) => {
+                recover(e, f)?;
+                r = synchronize(r);
+            }
+        }
+    }
+    Ok((stmts, r))
+}
+
+// panic mode: skips past the ; that ends what tokens start with, or the }
+// that ends its block and any else after it, but not past a } closing the
+// block it's in. a ; inside parens is a for's, and doesn't count
+fn synchronize(tokens: &[Token]) -> &[Token] {
+    let (mut braces, mut parens) = (0, 0);
+    for (i, t) in tokens.iter().enumerate() {
+        let next = tokens.get(i + 1).map(|t| &t.typ);
+        match t.typ {
+            TT::PuncSemiColon if braces == 0 && parens == 0 => return &tokens[i + 1..],
+            TT::PuncLeftParen => parens += 1,
+            TT::PuncRightParen => parens -= 1,
+            TT::PuncLeftBrace => braces += 1,
+            // at least one token's skipped, so a stray } is too
+            TT::PuncRightBrace if braces == 0 => return &tokens[i.max(1)..],
+            TT::PuncRightBrace if braces == 1 && next == Some(&TT::KeywordEls) => braces -= 1,
+            TT::PuncRightBrace if braces == 1 && next == Some(&TT::PuncSemiColon) => {
+                return &tokens[i + 2..]
+            }
+            TT::PuncRightBrace if braces == 1 => return &tokens[i + 1..],
+            TT::PuncRightBrace => braces -= 1,
+            _ => {}
+        }
+    }
+    &[]
+}
+
+// a function is its type and alias, then (, and a prototype is one without
+// a body. a variable is declared like a local. a static function is only
+// local to its file once several are linked
+fn parse_global(tokens: &[Token]) -> Result<(SDef, &[Token]), io::Error> {
+    let (is_static, r) = match eat(tokens, TT::KeywordStatic) {
+        Ok((_, r)) => (true, r),
+        Err(_) => (false, tokens),
+    };
+    let func = |r| match parse_func(r)? {
+        (SDef::FuncDef(fd), r) => Ok((SDef::FuncDef(SFuncDef { is_static, ..fd }), r)),
+        def => Ok(def),
+    };
+    match parse_type(r) {
+        Ok((_, [a, p, ..])) if a.typ == TT::Alias && p.typ == TT::PuncLeftParen => {
+            return func(r);
+        }
+        Err(_) => return func(r), // attributes first
+        Ok(_) => (),
+    }
+
+    let (decl, r) = parse_decl(tokens)?;
+    let (_, r) = eat(r, TT::PuncSemiColon)?;
+    match decl.node {
+        SStmt::Asnmt(vd) => Ok((SDef::VarDef(vd), r)),
+        SStmt::Decl(vd) => Ok((SDef::VarDecl(vd), r)),
+        _ => unreachable!("parse_decl only declares"),
+    }
+}
+
+// typedefs leave nothing in the tree: later uses of the name are parsed as
+// the type it stands for
+fn parse_typedef(tokens: &[Token]) -> Result<&[Token], io::Error> {
+    let (_, r) = eat(tokens, TT::KeywordTypedef)?;
+    let (typ, r) = parse_type(r)?;
+    let (alias, r) = eat(r, TT::Alias)?;
+    let (_, r) = eat(r, TT::PuncSemiColon)?;
+
+    TYPEDEFS.with_borrow_mut(|tds| tds.insert(alias.lexeme.to_owned(), typ));
+    Ok(r)
+}
+
+fn parse_enumdef(tokens: &[Token]) -> Result<(SEnumDef, &[Token]), io::Error> {
+    let (_, r) = eat(tokens, TT::KeywordEnum)?;
+    let (alias, r) = match eat(r, TT::Alias) {
+        Ok((alias, r)) => (Some(alias.lexeme.to_owned()), r),
+        Err(_) => (None, r),
+    };
+    let (_, mut r) = eat(r, TT::PuncLeftBrace)?;
+
+    // each enumerator is one more than the last, unless given a value
+    let (mut enumerators, mut next) = (vec![], 0);
+    while let Ok((e, _r)) = eat(r, TT::Alias) {
+        let (val, _r) = match eat(_r, TT::Equals) {
+            Ok((_, _r)) => {
+                let (expr, _r) = parse_expr(_r)?;
+                let val = const_int(&expr).ok_or(io::Error::other(format!(
+                    "enumerator {} needs a constant value",
+                    e.lexeme
+                )))?;
+                (val, _r)
+            }
+            Err(_) => (next, _r),
+        };
+        ENUMERATORS.with_borrow_mut(|es| es.insert(e.lexeme.to_owned(), val));
+        enumerators.push((e.lexeme.to_owned(), val));
+        next = val.wrapping_add(1);
+
+        r = match eat(_r, TT::PuncComma) {
+            Ok((_, _r)) => _r,
+            Err(_) => _r,
+        };
+    }
+    let (_, r) = eat(r, TT::PuncRightBrace)?;
+    let (_, r) = eat(r, TT::PuncSemiColon)?;
+
+    Ok((SEnumDef { alias, enumerators }, r))
+}
+
+// folds the integer constant expressions enumerators can be given
+fn const_int(e: &SExpr) -> Option<i32> {
+    match e {
+        SExpr::Int(n) | SExpr::TypedInt { val: n, .. } => Some(*n),
+        SExpr::UnaryE { op, l } => match op {
+            SUnaryOp::Add => const_int(l),
+            SUnaryOp::Sub => Some(const_int(l)?.wrapping_neg()),
+            SUnaryOp::BitNot => Some(!const_int(l)?),
+            SUnaryOp::Not => None,
+        },
+        SExpr::BinE { op, l, r } => {
+            let (l, r) = (const_int(l)?, const_int(r)?);
+            match op {
+                SBinOp::Add => Some(l.wrapping_add(r)),
+                SBinOp::Sub => Some(l.wrapping_sub(r)),
+                SBinOp::Mult => Some(l.wrapping_mul(r)),
+                SBinOp::Div => l.checked_div(r),
+                SBinOp::Mod => l.checked_rem(r),
+            }
+        }
+        _ => None,
+    }
+}
+
+// unions are declared like structs, only their members overlap. the tag is
+// known from its name on, so a struct can point to its own kind
+pub fn parse_structdef(tokens: &[Token]) -> Result<(SStructDef, &[Token]), io::Error> {
+    let (keyword, r) = match eat(tokens, TT::KeywordStruct) {
+        Ok(eaten) => eaten,
+        Err(_) => eat(tokens, TT::KeywordUnion)?,
+    };
+    let (alias, r) = eat(r, TT::Alias)?;
+    tag(keyword, alias)?;
+    let union = keyword.typ == TT::KeywordUnion;
+    TAGS.with_borrow_mut(|tags| tags.insert(alias.lexeme.to_owned(), union));
+    let (_, mut r) = eat(r, TT::PuncLeftBrace)?;
+
+    let mut fields = vec![];
+    while r.first().is_some_and(is_type_name) {
+        let (typ, _r) = parse_type(r)?;
+        let (field, _r) = eat(_r, TT::Alias)?;
+        let (_, _r) = eat(_r, TT::PuncSemiColon)?;
+        fields.push((field.lexeme.to_owned(), typ));
         r = _r;
     }
+    let (_, r) = eat(r, TT::PuncRightBrace)?;
+    let (_, r) = eat(r, TT::PuncSemiColon)?;
+
+    Ok((
+        SStructDef {
+            alias: alias.lexeme.to_owned(),
+            fields,
+        },
+        r,
+    ))
+}
 
-    Ok(fds.into_iter().map(SDef::FuncDef).collect())
+pub fn parse_funcdef(tokens: &[Token]) -> Result<(SFuncDef, &[Token]), io::Error> {
+    match parse_func(tokens)? {
+        (SDef::FuncDef(fd), r) => Ok((fd, r)),
+        (_, _) => Err(io::Error::other("expected a function body")),
+    }
 }
 
-fn parse_funcdef(tokens: &[Token]) -> Result<(SFuncDef, &[Token]), io::Error> {
-    let (_, r) = eat(tokens, TT::KeywordInt)?;
+// a definition, or a prototype if the signature ends with ;. prototypes
+// needn't name their params, and their attributes aren't kept. only
+// prototypes are variadic, since there's no va_arg to define one with
+fn parse_func(tokens: &[Token]) -> Result<(SDef, &[Token]), io::Error> {
+    let (mut attrs, mut r) = (vec![], tokens);
+    while let Ok((_, _r)) = eat(r, TT::KeywordAttribute) {
+        let (_attrs, _r) = parse_attrs(_r)?;
+        attrs.extend(_attrs);
+        r = _r;
+    }
+    if attrs.contains(&SAttr::NoInline) && attrs.contains(&SAttr::AlwaysInline) {
+        return Err(io::Error::other(
+            "conflicting attributes: noinline, always_inline",
+        ));
+    }
+
+    // c89 takes a definition with no return type to return int
+    let (typ, r, implicit_int) = match (parse_type(r), r) {
+        (Ok((typ, r)), _) => (typ, r, false),
+        (Err(_), [a, p, ..]) if a.typ == TT::Alias && p.typ == TT::PuncLeftParen => {
+            (Type::Int, r, true)
+        }
+        (Err(e), _) => return Err(e),
+    };
     let (alias, r) = eat(r, TT::Alias)?;
     let (_, r) = eat(r, TT::PuncLeftParen)?;
 
+    // f(void) takes no params
+    let r = match r {
+        [v, p, ..] if v.typ == TT::KeywordVoid && p.typ == TT::PuncRightParen => &r[1..],
+        _ => r,
+    };
     let (mut fps, mut r) = (vec![], r);
-    while let Ok((_fp_type, _r)) = eat(r, TT::KeywordInt) {
-        let (alias, _r) = eat(_r, TT::Alias)?;
-        fps.push((alias.lexeme.to_owned(), Type::Int));
-
-        if let TT::PuncComma = _r[0].typ {
-            r = &_r[1..];
-        } else {
-            r = _r;
+    while r.first().is_some_and(is_type_name) {
+        let (typ, _r) = parse_type(r)?;
+        let (alias, _r) = match eat(_r, TT::Alias) {
+            Ok((alias, _r)) => (Some(alias.lexeme.to_owned()), _r),
+            Err(_) => (None, _r),
+        };
+        fps.push((alias, typ));
+
+        match _r {
+            [c, _r @ ..] if c.typ == TT::PuncComma => r = _r,
+            _ => r = _r,
         }
     }
+    // int printf(char *fmt, ...) takes any more args after its named ones
+    let (variadic, r) = match eat(r, TT::PuncEllipsis) {
+        Ok((_, r)) if !fps.is_empty() => (true, r),
+        Ok(_) => {
+            return Err(io::Error::other(
+                "ISO C requires a named argument before ...",
+            ))
+        }
+        Err(_) => (false, r),
+    };
     let (_, r) = eat(r, TT::PuncRightParen)?;
+    let (contracts, r) = parse_contracts(r, &[SContractKind::Requires, SContractKind::Ensures])?;
+
+    if let Ok((_, r)) = eat(r, TT::PuncSemiColon) {
+        if !contracts.is_empty() {
+            return Err(io::Error::other(format!(
+                "annotations on a prototype aren't checked, put them on {}'s definition",
+                alias.lexeme
+            )));
+        }
+        return Ok((
+            SDef::FuncDecl(SFuncDecl {
+                alias: alias.lexeme.to_string(),
+                typ,
+                fps: fps.into_iter().map(|(_, t)| t).collect(),
+                variadic,
+            }),
+            r,
+        ));
+    }
+    if variadic {
+        return Err(io::Error::other(format!(
+            "variadic function {} has no stdarg.h to read its args",
+            alias.lexeme
+        )));
+    }
+    let fps = fps
+        .into_iter()
+        .map(|(a, t)| {
+            a.map(|a| (a, t)).ok_or(io::Error::other(format!(
+                "parameter name omitted in {}",
+                alias.lexeme
+            )))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
     let (_, r) = eat(r, TT::PuncLeftBrace)?;
 
-    let (mut stmts, mut r) = (vec![], r);
-    while let Ok((s, _r)) = parse_stmt(r) {
-        stmts.push(s);
-        r = _r;
-    }
+    let (stmts, r) = parse_stmts(r, |tt| *tt == TT::PuncRightBrace)?;
     let (_, r) = eat(r, TT::PuncRightBrace)?;
 
     Ok((
-        SFuncDef {
+        SDef::FuncDef(SFuncDef {
             alias: alias.lexeme.to_string(),
-            typ: Type::Int,
+            typ,
             fps,
             body: stmts,
-        },
+            attrs,
+            is_static: false,
+            implicit_int,
+            contracts,
+        }),
         r,
     ))
 }
 
-fn parse_vardef(tokens: &[Token]) -> Result<(SVarDef, &[Token]), io::Error> {
+// //@requires e; and the like, one to an annotation, for as long as they
+// come and are of the kinds allowed where they are
+fn parse_contracts<'a>(
+    tokens: &'a [Token],
+    kinds: &[SContractKind],
+) -> Result<(Vec<SContract>, &'a [Token]), io::Error> {
+    let (mut contracts, mut r) = (vec![], tokens);
+    while r.first().is_some_and(|t| t.typ == TT::Annotation) {
+        let (contract, _r) = parse_contract(r)?;
+        if !kinds.contains(&contract.kind) {
+            let kind = &r[1].lexeme;
+            return Err(io::Error::other(format!(
+                "@{kind} annotation isn't allowed here"
+            )));
+        }
+        contracts.push(contract);
+        r = _r;
+    }
+    Ok((contracts, r))
+}
+
+fn parse_contract(tokens: &[Token]) -> Result<(SContract, &[Token]), io::Error> {
+    let (_, r) = eat(tokens, TT::Annotation)?;
+    let (kind, r) = eat(r, TT::Alias)?;
+    let kind = match kind.lexeme.as_str() {
+        "requires" => SContractKind::Requires,
+        "ensures" => SContractKind::Ensures,
+        "loop_invariant" => SContractKind::LoopInvariant,
+        "assert" => SContractKind::Assert,
+        k => return Err(io::Error::other(format!("unknown annotation @{k}"))),
+    };
+    let (expr, r) = parse_expr(r)?;
+    let (_, r) = eat(r, TT::PuncSemiColon)?;
+    Ok((SContract { kind, expr }, r))
+}
+
+// __attribute__((noinline, always_inline, optimize("O1")))
+fn parse_attrs(tokens: &[Token]) -> Result<(Vec<SAttr>, &[Token]), io::Error> {
+    let (_, r) = eat(tokens, TT::PuncLeftParen)?;
+    let (_, mut r) = eat(r, TT::PuncLeftParen)?;
+
+    let mut attrs = vec![];
+    loop {
+        let (attr, _r) = eat(r, TT::Alias)?;
+        let (attr, _r) = match attr.lexeme.as_str() {
+            "noinline" => (SAttr::NoInline, _r),
+            "always_inline" => (SAttr::AlwaysInline, _r),
+            "optimize" => {
+                let (_, _r) = eat(_r, TT::PuncLeftParen)?;
+                let (lvl, _r) = eat(_r, TT::LiteralStr)?;
+                let opt = lvl
+                    .lexeme
+                    .strip_prefix('O')
+                    .and_then(|n| n.parse::<u32>().ok())
+                    .ok_or(io::Error::other(format!(
+                        "invalid optimize level: {}",
+                        lvl.lexeme
+                    )))
+                    .and_then(|n| OptLevel::try_from(n).map_err(io::Error::other))?;
+                let (_, _r) = eat(_r, TT::PuncRightParen)?;
+                (SAttr::Optimize(opt), _r)
+            }
+            a => return Err(io::Error::other(format!("unknown attribute: {a}"))),
+        };
+        attrs.push(attr);
+
+        match eat(_r, TT::PuncComma) {
+            Ok((_, _r)) => r = _r,
+            Err(_) => {
+                r = _r;
+                break;
+            }
+        }
+    }
+
+    let (_, r) = eat(r, TT::PuncRightParen)?;
+    let (_, r) = eat(r, TT::PuncRightParen)?;
+    Ok((attrs, r))
+}
+
+// int, char, void, struct s, union u, enum e or a typedef name, then one * per level of indirection: int **p
+fn parse_type(tokens: &[Token]) -> Result<(Type, &[Token]), io::Error> {
+    let (typ, _, r) = parse_qualified_type(tokens)?;
+    Ok((typ, r))
+}
+
+// a type, and whether what it declares is const. that's the case when
+// const qualifies the outermost level: const int and int const, or the
+// pointer in int *const p. const int *p is a pointer to const, which is
+// left unchecked
+// todo: const pointees, params and typedefs
+fn parse_qualified_type(tokens: &[Token]) -> Result<(Type, bool, &[Token]), io::Error> {
+    let consts = |r: &[Token]| r.iter().take_while(|t| t.typ == TT::KeywordConst).count();
+    let n = consts(tokens);
+    let (mut constant, tokens) = (n > 0, &tokens[n..]);
+
+    let (mut typ, r) = match tokens {
+        [f, ..] if is_arith_specifier(&f.typ) => parse_arith_specifiers(tokens)?,
+        [f, r @ ..] if f.typ == TT::Alias => match typedef(&f.lexeme) {
+            Some(typ) => (typ, r),
+            None => return Err(io::Error::other(format!("unknown type name: {}", f.lexeme))),
+        },
+        // struct and union tags share a namespace, as do their layouts
+        [f, r @ ..] if f.typ == TT::KeywordStruct || f.typ == TT::KeywordUnion => {
+            let (alias, r) = eat(r, TT::Alias)?;
+            (tag(f, alias)?, r)
+        }
+        [f, r @ ..] if f.typ == TT::KeywordEnum => (Type::Int, eat(r, TT::Alias)?.1), // enums are ints
+        [f, r @ ..] if f.typ == TT::KeywordVoid => (Type::Void, r),
+        _ => (Type::Int, eat(tokens, TT::KeywordInt)?.1),
+    };
+    let n = consts(r);
+    constant |= n > 0;
+    let mut r = &r[n..];
+
+    let mut levels = 0;
+    while let Ok((star, _r)) = eat(r, TT::Star) {
+        levels += 1;
+        if levels > MAX_DEPTH {
+            let e = io::Error::other(format!("nesting deeper than {MAX_DEPTH} levels"));
+            return Err(source::locate(e, star.span));
+        }
+        typ = Type::Ptr(Box::new(typ));
+        let n = consts(_r);
+        (constant, r) = (n > 0, &_r[n..]);
+    }
+
+    Ok((typ, constant, r))
+}
+
+// whether a type name starts at t, rather than an expression
+fn is_type_name(t: &Token) -> bool {
+    is_arith_specifier(&t.typ)
+        || matches!(
+            t.typ,
+            TT::KeywordVoid
+                | TT::KeywordStruct
+                | TT::KeywordUnion
+                | TT::KeywordEnum
+                | TT::KeywordConst
+        )
+        || t.typ == TT::Alias && typedef(&t.lexeme).is_some()
+}
+
+pub fn is_arith_specifier(typ: &TT) -> bool {
+    matches!(
+        typ,
+        TT::KeywordInt
+            | TT::KeywordChar
+            | TT::KeywordShort
+            | TT::KeywordLong
+            | TT::KeywordSigned
+            | TT::KeywordUnsigned
+            | TT::KeywordFloat
+            | TT::KeywordDouble
+    )
+}
+
+// the specifiers come in any order, and int is implied: unsigned, long int,
+// short unsigned int. signed char is plain char, which is signed here. the
+// floating types are float, double and long double
+fn parse_arith_specifiers(tokens: &[Token]) -> Result<(Type, &[Token]), io::Error> {
+    let n = tokens
+        .iter()
+        .take_while(|t| is_arith_specifier(&t.typ))
+        .count();
+    let (specs, r) = tokens.split_at(n);
+    let count = |typ: TT| specs.iter().filter(|t| t.typ == typ).count();
+    let (int, char, short, long, signed, unsigned) = (
+        count(TT::KeywordInt),
+        count(TT::KeywordChar),
+        count(TT::KeywordShort),
+        count(TT::KeywordLong),
+        count(TT::KeywordSigned),
+        count(TT::KeywordUnsigned),
+    );
+    let (float, double) = (count(TT::KeywordFloat), count(TT::KeywordDouble));
+
+    let invalid = || {
+        let specs = specs.iter().map(|t| t.lexeme.as_str()).collect::<Vec<_>>();
+        io::Error::other(format!("invalid type specifiers: {}", specs.join(" ")))
+    };
+    match (float, double, int + char + short + signed + unsigned, long) {
+        (0, 0, _, _) => {}
+        (1, 0, 0, 0) => return Ok((Type::Float, r)),
+        (0, 1, 0, _) => return Ok((Type::Double, r)), // long double is double
+        _ => return Err(invalid()),
+    }
+    if [int, char, short, long, signed, unsigned]
+        .iter()
+        .any(|n| *n > 1)
+        || signed + unsigned > 1
+        || char + short + long > 1
+        || char + int > 1
+    {
+        return Err(invalid()); // long long is c99
+    }
+
+    let typ = match (char, short, long, unsigned) {
+        (1, _, _, 0) => Type::Char,
+        (1, _, _, _) => Type::UChar,
+        (_, 1, _, 0) => Type::Short,
+        (_, 1, _, _) => Type::UShort,
+        (_, _, 1, 0) => Type::Long,
+        (_, _, 1, _) => Type::ULong,
+        (_, _, _, 0) => Type::Int,
+        _ => Type::UInt,
+    };
+
+    Ok((typ, r))
+}
+
+// type alias, an optional [n], then either ; or = e
+fn parse_decl(tokens: &[Token]) -> Result<(Spanned<SStmt>, &[Token]), io::Error> {
+    let (is_static, r) = match eat(tokens, TT::KeywordStatic) {
+        Ok((_, r)) => (true, r),
+        Err(_) => (false, tokens),
+    };
+    let (typ, constant, r) = parse_qualified_type(r)?;
+    let (alias, r) = eat(r, TT::Alias)?;
+    let (typ, r) = match eat(r, TT::PuncLeftBracket) {
+        Ok((_, r)) => {
+            let (len, r) = eat(r, TT::LiteralInt)?;
+            let len = len.int.expect("the lexer reads int literals").val as usize;
+            let (_, r) = eat(r, TT::PuncRightBracket)?;
+            (Type::Arr(Box::new(typ), len), r)
+        }
+        Err(_) => (typ, r),
+    };
+    if matches!(r, [f, ..] if f.typ == TT::PuncSemiColon) {
+        let alias = alias.lexeme.to_owned();
+        let decl = SStmt::Decl(SVarDecl {
+            alias,
+            typ,
+            constant,
+            is_static,
+        });
+        return Ok((spanned(decl, tokens, r), r));
+    }
+    let (_, r) = eat(r, TT::Equals)?;
+    let (expr, r) = parse_expr(r)?;
+
+    let def = SStmt::Asnmt(SVarDef {
+        alias: alias.lexeme.to_owned(),
+        typ,
+        expr: Box::new(expr),
+        constant,
+        is_static,
+    });
+    Ok((spanned(def, tokens, r), r))
+}
+
+// declarations create a binding, assignments update an existing one
+// a for's init or update, which can be left out for an empty block
+fn parse_clause(tokens: &[Token], end: TT) -> Result<(Spanned<SStmt>, &[Token]), io::Error> {
     match tokens {
-        [] => todo!(),
+        [f, ..] if f.typ == end => Ok((Spanned::new(SStmt::Block(vec![]), f.span), tokens)),
+        _ => parse_vardef(tokens),
+    }
+}
+
+fn parse_vardef(tokens: &[Token]) -> Result<(Spanned<SStmt>, &[Token]), io::Error> {
+    let (stmt, r) = match tokens {
+        [] => Err(io::Error::other("unexpected end of input")),
         [f, r @ ..] => match f.typ {
             // for now int is parsed with vardef
-            TT::KeywordInt => {
-                let (alias, r) = eat(r, TT::Alias)?;
-                let (_, r) = eat(r, TT::Equals)?;
-                let (expr, r) = parse_expr(r)?;
-
-                Ok((
-                    SVarDef {
-                        alias: alias.lexeme.to_owned(),
-                        typ: Type::Int,
-                        expr: Box::new(expr),
-                    },
-                    r,
-                ))
+            TT::KeywordInt
+            | TT::KeywordChar
+            | TT::KeywordShort
+            | TT::KeywordLong
+            | TT::KeywordSigned
+            | TT::KeywordUnsigned
+            | TT::KeywordFloat
+            | TT::KeywordDouble
+            | TT::KeywordStruct
+            | TT::KeywordUnion
+            | TT::KeywordEnum
+            | TT::KeywordConst
+            | TT::KeywordStatic => return parse_decl(tokens),
+            TT::Alias if typedef(&f.lexeme).is_some() => return parse_decl(tokens),
+            TT::Alias if matches!(r, [s, ..] if matches!(s.typ, TT::PuncLeftBracket | TT::Dot | TT::MinusRightAngleBracket)) =>
+            {
+                let (lval, r) = parse_postfix(tokens)?;
+                parse_store(lval, r)
+            }
+            TT::Star | TT::PuncLeftParen => {
+                // *e = e and (*p).f = e. the left is parsed as an
+                // expression, which has to be an lvalue
+                let (lval, r) = parse_unary(tokens)?;
+                parse_store(lval, r)
             }
             TT::Alias => match r {
-                [] => todo!(),
-                [s, t, _r @ ..] => match (s.typ, t.typ) {
-                    // (TT::Plus, TT::Equals) => {
-                    //     let (expr, r) = parse_rel_expr(r)?;
-                    //     Ok((
-                    //         VarDef::UpdateBind {
-                    //             alias: f.lexeme.parse().unwrap(),
-                    //             op: BinOp::Add,
-                    //             expr: Box::new(expr),
-                    //         },
-                    //         r,
-                    //     ))
-                    // }
-                    // (TT::Plus, TT::Plus) => Ok((
-                    //     VarDef::UpdateBind {
-                    //         alias: f.lexeme.parse().unwrap(),
-                    //         op: BinOp::Add,
-                    //         expr: Box::new(Expr::Int(1)),
-                    //     },
-                    //     r,
-                    // )),
-                    // (TT::Minus, TT::Equals) => {
-                    //     let (expr, r) = parse_rel_expr(r)?;
-
-                    //     Ok((
-                    //         VarDef::UpdateBind {
-                    //             alias: f.lexeme.parse().unwrap(),
-                    //             op: BinOp::Sub,
-                    //             expr: Box::new(expr),
-                    //         },
-                    //         r,
-                    //     ))
-                    // }
-                    // (TT::Minus, TT::Minus) => Ok((
-                    //     VarDef::UpdateBind {
-                    //         alias: f.lexeme.parse().unwrap(),
-                    //         op: BinOp::Sub,
-                    //         expr: Box::new(Expr::Int(1)),
-                    //     },
-                    //     r,
-                    // )),
-                    // (TT::Star, TT::Equals) => {
-                    //     let (expr, r) = parse_rel_expr(r)?;
-
-                    //     Ok((
-                    //         VarDef::UpdateBind {
-                    //             alias: f.lexeme.parse().unwrap(),
-                    //             op: BinOp::Mult,
-                    //             expr: Box::new(expr),
-                    //         },
-                    //         r,
-                    //     ))
-                    // }
-                    // (TT::Slash, TT::Equals) => {
-                    //     let (expr, r) = parse_rel_expr(r)?;
-
-                    //     Ok((
-                    //         VarDef::UpdateBind {
-                    //             alias: f.lexeme.parse().unwrap(),
-                    //             op: BinOp::Div,
-                    //             expr: Box::new(expr),
-                    //         },
-                    //         r,
-                    //     ))
-                    // }
-                    _t => {
-                        todo!()
-                    }
-                },
-                _t => todo!(),
+                [] => Err(io::Error::other("unexpected end of input")),
+                [s, r @ ..] if s.typ == TT::Equals => {
+                    let (expr, r) = parse_expr(r)?;
+
+                    Ok((
+                        SStmt::Update(SVarUpdate {
+                            alias: f.lexeme.to_owned(),
+                            expr: Box::new(expr),
+                        }),
+                        r,
+                    ))
+                }
+                [s, r @ ..] if compound_op(s.typ).is_some() => {
+                    // x op= e desugars to x = x op e
+                    let (expr, r) = parse_expr(r)?;
+                    let binop = SExpr::BinE {
+                        op: compound_op(s.typ).unwrap(),
+                        l: Box::new(Spanned::new(SExpr::VarApp(f.lexeme.to_owned()), f.span)),
+                        r: Box::new(expr),
+                    };
+
+                    Ok((
+                        SStmt::Update(SVarUpdate {
+                            alias: f.lexeme.to_owned(),
+                            expr: Box::new(spanned(binop, tokens, r)),
+                        }),
+                        r,
+                    ))
+                }
+                [s, r @ ..] if matches!(s.typ, TT::PlusPlus | TT::MinusMinus) => {
+                    // as a statement, x++ and ++x only differ in the discarded value
+                    Ok((step(&f.lexeme, f.span, s.typ), r))
+                }
+                _ => Err(io::Error::other("expected: Equals")),
+            },
+            TT::PlusPlus | TT::MinusMinus => match parse_unary(r)? {
+                (
+                    Spanned {
+                        node: SExpr::VarApp(alias),
+                        span,
+                    },
+                    r,
+                ) => Ok((step(&alias, span, f.typ), r)),
+                (lval, r) => {
+                    let op = match f.typ {
+                        TT::PlusPlus => SIncDecOp::PreInc,
+                        _ => SIncDecOp::PreDec,
+                    };
+                    let e = SExpr::IncDec {
+                        op,
+                        lval: Box::new(lval),
+                    };
+                    parse_store(spanned(e, tokens, r), r)
+                }
             },
-            t => Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("token not recognizable {:?}", t),
-            )),
+            t => {
+                let e = io::Error::other(format!("token not recognizable {:?}", t));
+                Err(source::locate(e, f.span))
+            }
         },
+    }?;
+
+    Ok((spanned(stmt, tokens, r), r))
+}
+
+// the rest of a store to lval: = e, op= e, or nothing when lval was
+// stepped, as a[i]++, which is a[i] += 1 as a statement
+fn parse_store(lval: Spanned<SExpr>, r: &[Token]) -> Result<(SStmt, &[Token]), io::Error> {
+    let store = |lval, expr, op| SStore {
+        lval: Box::new(lval),
+        expr: Box::new(expr),
+        op,
+    };
+    if let SExpr::IncDec { op, lval } = lval.node {
+        if let SExpr::VarApp(alias) = &lval.node {
+            let tt = match op {
+                SIncDecOp::PreInc | SIncDecOp::PostInc => TT::PlusPlus,
+                SIncDecOp::PreDec | SIncDecOp::PostDec => TT::MinusMinus,
+            };
+            return Ok((step(alias, lval.span, tt), r));
+        }
+        let one = Spanned::new(SExpr::Int(1), lval.span);
+        let op = match op {
+            SIncDecOp::PreInc | SIncDecOp::PostInc => SBinOp::Add,
+            SIncDecOp::PreDec | SIncDecOp::PostDec => SBinOp::Sub,
+        };
+        return Ok((SStmt::Store(store(*lval, one, Some(op))), r));
+    }
+    if !lvalue(&lval.node) {
+        let e = io::Error::other("lvalue required as left operand of assignment");
+        return Err(source::locate(e, lval.span));
+    }
+    match r {
+        [s, r @ ..] if compound_op(s.typ).is_some() => {
+            let (expr, r) = parse_expr(r)?;
+            Ok((SStmt::Store(store(lval, expr, compound_op(s.typ))), r))
+        }
+        _ => {
+            let (_, r) = eat(r, TT::Equals)?;
+            let (expr, r) = parse_expr(r)?;
+            Ok((SStmt::Store(store(lval, expr, None)), r))
+        }
     }
 }
 
-fn parse_stmt(tokens: &[Token]) -> Result<(SStmt, &[Token]), io::Error> {
-    match tokens {
-        [] => todo!(),
+// x++ and x-- as updates: x = x + 1, all of it at x
+fn step(alias: &str, span: Span, tt: TT) -> SStmt {
+    let at = |e| Box::new(Spanned::new(e, span));
+    SStmt::Update(SVarUpdate {
+        alias: alias.to_owned(),
+        expr: at(SExpr::BinE {
+            op: match tt {
+                TT::PlusPlus => SBinOp::Add,
+                _ => SBinOp::Sub,
+            },
+            l: at(SExpr::VarApp(alias.to_owned())),
+            r: at(SExpr::Int(1)),
+        }),
+    })
+}
+
+fn compound_op(tt: TT) -> Option<SBinOp> {
+    match tt {
+        TT::PlusEquals => Some(SBinOp::Add),
+        TT::MinusEquals => Some(SBinOp::Sub),
+        TT::StarEquals => Some(SBinOp::Mult),
+        TT::SlashEquals => Some(SBinOp::Div),
+        TT::PercentEquals => Some(SBinOp::Mod),
+        _ => None,
+    }
+}
+
+pub fn parse_stmt(tokens: &[Token]) -> Result<(Spanned<SStmt>, &[Token]), io::Error> {
+    let _depth = Depth::enter(tokens)?;
+    let (stmt, r) = match tokens {
+        [] => Err(io::Error::other("unexpected end of input")),
+        // a label is a stmt of its own, so one can end a block
+        [f, c, r @ ..] if f.typ == TT::Alias && c.typ == TT::Colon => {
+            Ok((SStmt::Label(f.lexeme.to_owned()), r))
+        }
+        [f, k, ..] if f.typ == TT::Annotation => match parse_contract(tokens)? {
+            (c, r) if c.kind == SContractKind::Assert => Ok((SStmt::Contract(c), r)),
+            _ => Err(io::Error::other(format!(
+                "@{} annotation isn't allowed here",
+                k.lexeme
+            ))),
+        },
+        // c0's assert is a stmt, not a function
+        [f, p, r @ ..]
+            if f.typ == TT::Alias && f.lexeme == "assert" && p.typ == TT::PuncLeftParen =>
+        {
+            let (expr, r) = parse_expr(r)?;
+            let (_, r) = eat(r, TT::PuncRightParen)?;
+            let (_, r) = eat(r, TT::PuncSemiColon)?;
+            Ok((SStmt::Assert(expr), r))
+        }
+        [f, p, ..] if f.typ == TT::Alias && p.typ == TT::PuncLeftParen => {
+            let (call, r) = parse_funcapp(tokens)?;
+            let (_, r) = eat(r, TT::PuncSemiColon)?;
+            Ok((SStmt::Call(call), r))
+        }
         [f, r @ ..] => match f.typ {
-            TT::KeywordInt => {
-                // todo: | TT:KeywordAlias{++, --, -=}, etc.
+            TT::KeywordInt
+            | TT::KeywordChar
+            | TT::KeywordShort
+            | TT::KeywordLong
+            | TT::KeywordSigned
+            | TT::KeywordUnsigned
+            | TT::KeywordFloat
+            | TT::KeywordDouble
+            | TT::KeywordStruct
+            | TT::KeywordUnion
+            | TT::KeywordEnum
+            | TT::KeywordConst
+            | TT::KeywordStatic
+            | TT::Alias
+            | TT::PlusPlus
+            | TT::MinusMinus
+            | TT::Star
+            | TT::PuncLeftParen => {
                 let (a, r) = parse_vardef(tokens)?;
                 let (_, r) = eat(r, TT::PuncSemiColon)?;
 
-                Ok((SStmt::Asnmt(a), r))
-            }
-            TT::KeywordRet => {
-                let (expr, r) = parse_rel(r)?;
-                let (_, r) = eat(r, TT::PuncSemiColon)?;
-                Ok((SStmt::Return(expr), r))
+                Ok((a.node, r))
             }
+            TT::KeywordRet => match eat(r, TT::PuncSemiColon) {
+                Ok((_, r)) => Ok((SStmt::Return(None), r)),
+                Err(_) => {
+                    let (expr, r) = parse_expr(r)?;
+                    let (_, r) = eat(r, TT::PuncSemiColon)?;
+                    Ok((SStmt::Return(Some(expr)), r))
+                }
+            },
             TT::KeywordIf => {
                 let (_, r) = eat(r, TT::PuncLeftParen)?;
-                let (cond, r) = parse_rel(r)?;
+                let (cond, r) = parse_expr(r)?;
                 let (_, r) = eat(r, TT::PuncRightParen)?;
                 let (_, r) = eat(r, TT::PuncLeftBrace)?;
                 let (then, r) = parse_stmt(r)?;
                 let (_, r) = eat(r, TT::PuncRightBrace)?;
-                let (els, r) = if let TT::KeywordEls = r[0].typ {
+                let (els, r) = if r.first().is_some_and(|t| t.typ == TT::KeywordEls) {
                     let (_, r) = eat(r, TT::KeywordEls)?;
                     let (_, r) = eat(r, TT::PuncLeftBrace)?;
                     let (els, r) = parse_stmt(r)?;
@@ -214,230 +1055,379 @@ fn parse_stmt(tokens: &[Token]) -> Result<(SStmt, &[Token]), io::Error> {
             }
             TT::KeywordWhile => {
                 let (_, r) = eat(r, TT::PuncLeftParen)?;
-                let (cond, r) = parse_rel(r)?;
+                let (cond, r) = parse_expr(r)?;
                 let (_, r) = eat(r, TT::PuncRightParen)?;
+                let (invariants, r) = parse_contracts(r, &[SContractKind::LoopInvariant])?;
                 let (_, r) = eat(r, TT::PuncLeftBrace)?;
-                let (body, r) = parse_stmt(r)?;
+
+                let (body, r) = parse_stmts(r, |tt| *tt == TT::PuncRightBrace)?;
                 let (_, r) = eat(r, TT::PuncRightBrace)?;
 
                 Ok((
                     SStmt::While {
                         cond: Box::new(cond),
-                        body: Box::new(body),
+                        body,
+                        invariants,
+                    },
+                    r,
+                ))
+            }
+            TT::PuncLeftBrace => {
+                let (body, r) = parse_stmts(r, |tt| *tt == TT::PuncRightBrace)?;
+                let (_, r) = eat(r, TT::PuncRightBrace)?;
+                Ok((SStmt::Block(body), r))
+            }
+            TT::KeywordDo => {
+                let (_, r) = eat(r, TT::PuncLeftBrace)?;
+                let (body, r) = parse_stmts(r, |tt| *tt == TT::PuncRightBrace)?;
+                let (_, r) = eat(r, TT::PuncRightBrace)?;
+                let (_, r) = eat(r, TT::KeywordWhile)?;
+                let (_, r) = eat(r, TT::PuncLeftParen)?;
+                let (cond, r) = parse_expr(r)?;
+                let (_, r) = eat(r, TT::PuncRightParen)?;
+                let (_, r) = eat(r, TT::PuncSemiColon)?;
+
+                Ok((
+                    SStmt::DoWhile {
+                        body,
+                        cond: Box::new(cond),
+                    },
+                    r,
+                ))
+            }
+            TT::KeywordFor => {
+                // an absent cond is true
+                let (_, r) = eat(r, TT::PuncLeftParen)?;
+                let (asnmt, r) = parse_clause(r, TT::PuncSemiColon)?;
+                let (_, r) = eat(r, TT::PuncSemiColon)?;
+                let (cond, r) = match r {
+                    [f, ..] if f.typ == TT::PuncSemiColon => {
+                        (Spanned::new(SExpr::Bool(true), f.span), r)
+                    }
+                    _ => parse_expr(r)?,
+                };
+                let (_, r) = eat(r, TT::PuncSemiColon)?;
+                let (update, r) = parse_clause(r, TT::PuncRightParen)?;
+                let (_, r) = eat(r, TT::PuncRightParen)?;
+                let (invariants, r) = parse_contracts(r, &[SContractKind::LoopInvariant])?;
+                let (_, r) = eat(r, TT::PuncLeftBrace)?;
+
+                let (body, r) = parse_stmts(r, |tt| *tt == TT::PuncRightBrace)?;
+                let (_, r) = eat(r, TT::PuncRightBrace)?;
+
+                Ok((
+                    SStmt::For {
+                        asnmt: Box::new(asnmt),
+                        cond: Box::new(cond),
+                        update: Box::new(update),
+                        body,
+                        invariants,
+                    },
+                    r,
+                ))
+            }
+            TT::KeywordBreak => Ok((SStmt::Break, eat(r, TT::PuncSemiColon)?.1)),
+            TT::KeywordContinue => Ok((SStmt::Continue, eat(r, TT::PuncSemiColon)?.1)),
+            TT::KeywordGoto => {
+                let (label, r) = eat(r, TT::Alias)?;
+                let (_, r) = eat(r, TT::PuncSemiColon)?;
+                Ok((SStmt::Goto(label.lexeme.to_owned()), r))
+            }
+            TT::KeywordSwitch => {
+                let (_, r) = eat(r, TT::PuncLeftParen)?;
+                let (cond, r) = parse_expr(r)?;
+                let (_, r) = eat(r, TT::PuncRightParen)?;
+                let (_, mut r) = eat(r, TT::PuncLeftBrace)?;
+
+                // each case or default label starts an arm, which runs on
+                // into the next one
+                let mut arms = vec![];
+                loop {
+                    let (label, _r) = match r {
+                        [f, _r @ ..] if f.typ == TT::KeywordCase => {
+                            let (expr, _r) = parse_expr(_r)?;
+                            let val = const_int(&expr)
+                                .ok_or(io::Error::other("case label needs a constant value"))?;
+                            (Some(val), _r)
+                        }
+                        [f, _r @ ..] if f.typ == TT::KeywordDefault => (None, _r),
+                        _ => break,
+                    };
+                    let (_, _r) = eat(_r, TT::Colon)?;
+
+                    let (body, _r) = parse_stmts(_r, |tt| {
+                        matches!(
+                            tt,
+                            TT::KeywordCase | TT::KeywordDefault | TT::PuncRightBrace
+                        )
+                    })?;
+                    arms.push(SCase { label, body });
+                    r = _r;
+                }
+                let (_, r) = eat(r, TT::PuncRightBrace)?;
+
+                Ok((
+                    SStmt::Switch {
+                        cond: Box::new(cond),
+                        arms,
                     },
                     r,
                 ))
             }
-            // TT::KeywordFor => {
-            //     let (_, r) = mtch(r, TT::PuncLeftParen)?;
-            //     let (asnmt, r) = parse_asmt(r)?;
-            //     let (_, r) = mtch(r, TT::PuncSemiColon)?;
-            //     let (cond, r) = parse_rel_expr(r)?;
-            //     let (_, r) = mtch(r, TT::PuncSemiColon)?;
-            //     let (update, r) = parse_asmt(r)?;
-            //     let (_, r) = mtch(r, TT::PuncRightParen)?;
-            //     let (_, r) = mtch(r, TT::PuncLeftBrace)?;
-
-            //     let mut body = vec![];
-            //     let mut r0 = r;
-            //     while let Ok((s, r1)) = parse_stmt(r0) {
-            //         body.push(s);
-            //         r0 = r1;
-            //     }
-            //     let (_, r) = mtch(r0, TT::PuncRightBrace)?;
-
-            //     Ok((
-            //         Stmt::For {
-            //             asnmt: Box::new(asnmt),
-            //             cond: Box::new(cond),
-            //             update: Box::new(update),
-            //             body,
-            //         },
-            //         r,
-            //     ))
-            // }
-            t => Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("token not recognizable {:?}", t),
-            )),
+            t => {
+                let e = io::Error::other(format!("token not recognizable {:?}", t));
+                Err(source::locate(e, f.span))
+            }
         },
-    }
+    }?;
+
+    Ok((spanned(stmt, tokens, r), r))
 }
 
-fn parse_expr(tokens: &[Token]) -> Result<(SExpr, &[Token]), io::Error> {
-    parse_rel(tokens)
+pub fn parse_expr(tokens: &[Token]) -> Result<(Spanned<SExpr>, &[Token]), io::Error> {
+    parse_infix(tokens, 0)
 }
 
-fn parse_rel(tokens: &[Token]) -> Result<(SExpr, &[Token]), io::Error> {
-    let (left, r) = parse_term(tokens)?;
+#[derive(Clone)]
+enum Infix {
+    Cond,
+    Log(SLogOp),
+    Bit(SBitOp),
+    Rel(SRelOp),
+    Bin(SBinOp),
+}
 
-    match r {
-        [] => Ok((left, r)),
-        r => {
-            let mut cur_node = left;
-            let mut r = r;
-
-            while let Ok((op, _r)) = parse_rel_op(r) {
-                let (right, _r) = parse_term(_r)?;
-
-                cur_node = SExpr::RelE {
-                    op,
-                    l: Box::new(cur_node),
-                    r: Box::new(right),
-                };
+// the binary operators and how tightly each binds, from ?: (loosest)
+// through ||, &&, the bitwise ones, equality, comparison and shifts to
+// terms and factors. operators at a level are left associative, but for ?:
+#[rustfmt::skip]
+const INFIX: [(TT, u8, Infix); 19] = [
+    (TT::Question,                           1,  Infix::Cond),
+    (TT::BarBar,                             2,  Infix::Log(SLogOp::Or)),
+    (TT::AmpAmp,                             3,  Infix::Log(SLogOp::And)),
+    (TT::Bar,                                4,  Infix::Bit(SBitOp::Or)),
+    (TT::Caret,                              5,  Infix::Bit(SBitOp::Xor)),
+    (TT::Amp,                                6,  Infix::Bit(SBitOp::And)),
+    (TT::EqualsEquals,                       7,  Infix::Rel(SRelOp::Eq)),
+    (TT::BangEquals,                         7,  Infix::Rel(SRelOp::Neq)),
+    (TT::LeftAngleBracket,                   8,  Infix::Rel(SRelOp::Lt)),
+    (TT::LeftAngleBracketEquals,             8,  Infix::Rel(SRelOp::LtEq)),
+    (TT::RightAngleBracket,                  8,  Infix::Rel(SRelOp::Gt)),
+    (TT::RightAngleBracketEquals,            8,  Infix::Rel(SRelOp::GtEq)),
+    (TT::LeftAngleBracketLeftAngleBracket,   9,  Infix::Bit(SBitOp::Shl)),
+    (TT::RightAngleBracketRightAngleBracket, 9,  Infix::Bit(SBitOp::Shr)),
+    (TT::Plus,                               10, Infix::Bin(SBinOp::Add)),
+    (TT::Minus,                              10, Infix::Bin(SBinOp::Sub)),
+    (TT::Star,                               11, Infix::Bin(SBinOp::Mult)),
+    (TT::Slash,                              11, Infix::Bin(SBinOp::Div)),
+    (TT::Percent,                            11, Infix::Bin(SBinOp::Mod)),
+];
+
+fn infix(t: &Token) -> Option<(u8, Infix)> {
+    INFIX
+        .iter()
+        .find(|(tt, _, _)| *tt == t.typ)
+        .map(|(_, prec, op)| (*prec, op.clone()))
+}
 
-                r = _r;
-            }
+// pratt parsing: a unary expression, then operators for as long as they
+// bind at least as tightly as min. each one's right operand only takes
+// operators binding tighter than it, which makes it left associative
+fn parse_infix(tokens: &[Token], min: u8) -> Result<(Spanned<SExpr>, &[Token]), io::Error> {
+    let _depth = Depth::enter(tokens)?;
+    let (mut l, mut r) = parse_unary(tokens)?;
 
-            Ok((cur_node, r))
+    while let Some((prec, op)) = r.first().and_then(infix) {
+        if prec < min {
+            break;
         }
+        Depth::fits(r, height(&l) + 1)?;
+        let (e, _r) = match op {
+            Infix::Cond => {
+                // right associative: a ? b : c ? d : e is a ? b : (c ? d : e)
+                let (then, _r) = parse_expr(&r[1..])?;
+                let (_, _r) = eat(_r, TT::Colon)?;
+                let (els, _r) = parse_infix(_r, prec)?;
+                let e = SExpr::CondE {
+                    cond: Box::new(l),
+                    then: Box::new(then),
+                    els: Box::new(els),
+                };
+                (e, _r)
+            }
+            op => {
+                let (right, _r) = parse_infix(&r[1..], prec + 1)?;
+                let (l, r) = (Box::new(l), Box::new(right));
+                let e = match op {
+                    Infix::Log(op) => SExpr::LogE { op, l, r },
+                    Infix::Bit(op) => SExpr::BitE { op, l, r },
+                    Infix::Rel(op) => SExpr::RelE { op, l, r },
+                    Infix::Bin(op) => SExpr::BinE { op, l, r },
+                    Infix::Cond => unreachable!("?: is parsed above"),
+                };
+                (e, _r)
+            }
+        };
+        l = spanned(e, tokens, _r);
+        r = _r;
     }
+
+    Ok((l, r))
 }
-fn parse_rel_op(tokens: &[Token]) -> Result<(SRelOp, &[Token]), io::Error> {
-    match tokens {
-        [] => todo!(),
-        [f, r @ ..] => match f.typ {
-            TT::LeftAngleBracket => match r {
-                [] => todo!(),
-                [s, r @ ..] => match s.typ {
-                    TT::Equals => Ok((SRelOp::LtEq, r)),
-                    _ => Ok((SRelOp::Lt, &tokens[1..])), // include s
-                },
-            },
-            TT::RightAngleBracket => match r {
-                [] => todo!(),
-                [s, r @ ..] => match s.typ {
-                    TT::Equals => Ok((SRelOp::GtEq, r)),
-                    _ => Ok((SRelOp::Gt, &tokens[1..])), // include s
-                },
-            },
-            TT::Equals => match r {
-                [] => todo!(),
-                [s, r @ ..] => match s.typ {
-                    TT::Equals => Ok((SRelOp::Eq, r)),
-                    t => Err(io::Error::new(
-                        io::ErrorKind::Other,
-                        format!("token not recognizable {:?}", t),
-                    )),
-                },
-            },
-            TT::Bang => match r {
-                [] => todo!(),
-                [s, r @ ..] => match s.typ {
-                    TT::Equals => Ok((SRelOp::Neq, r)),
-                    t => Err(io::Error::new(
-                        io::ErrorKind::Other,
-                        format!("token not recognizable {:?}", t),
-                    )),
-                },
-            },
-            TT::Amp => match r {
-                [] => todo!(),
-                [s, r @ ..] => match s.typ {
-                    TT::Amp => Ok((SRelOp::And, r)),
-                    t => Err(io::Error::new(
-                        io::ErrorKind::Other,
-                        format!("token not recognizable {:?}", t),
-                    )),
-                },
-            },
-            TT::Bar => match r {
-                [] => todo!(),
-                [s, r @ ..] => match s.typ {
-                    TT::Bar => Ok((SRelOp::Or, r)),
-                    t => Err(io::Error::new(
-                        io::ErrorKind::Other,
-                        format!("token not recognizable {:?}", t),
-                    )),
-                },
-            },
-            t => Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("token not recognizable {:?}", t),
-            )),
-        },
-    }
-}
-
-fn parse_term(tokens: &[Token]) -> Result<(SExpr, &[Token]), io::Error> {
-    let (left, r) = parse_factor(tokens)?;
-
-    match r {
-        [] => Ok((left, r)),
-        r => {
-            let mut cur_node = left;
-            let mut r = r;
-
-            while let Ok((op, _r)) = parse_term_op(r) {
-                let (right, _r) = parse_factor(_r)?;
-
-                cur_node = SExpr::BinE {
-                    op,
-                    l: Box::new(cur_node),
-                    r: Box::new(right),
-                };
 
-                r = _r;
+fn parse_unary(tokens: &[Token]) -> Result<(Spanned<SExpr>, &[Token]), io::Error> {
+    let _depth = Depth::enter(tokens)?;
+    let (e, r) = match tokens {
+        [f, r @ ..] if matches!(f.typ, TT::PlusPlus | TT::MinusMinus) => {
+            let op = match f.typ {
+                TT::PlusPlus => SIncDecOp::PreInc,
+                _ => SIncDecOp::PreDec,
+            };
+            match parse_unary(r)? {
+                (l, r) if lvalue(&l) => Ok((
+                    SExpr::IncDec {
+                        op,
+                        lval: Box::new(l),
+                    },
+                    r,
+                )),
+                (l, _) => {
+                    let e = io::Error::other("lvalue required as increment operand");
+                    Err(source::locate(e, l.span))
+                }
             }
-
-            Ok((cur_node, r))
         }
-    }
-}
-fn parse_term_op(tokens: &[Token]) -> Result<(SBinOp, &[Token]), io::Error> {
-    match tokens {
-        [] => todo!(),
-        [f, r @ ..] => match f.typ {
-            TT::Plus => Ok((SBinOp::Add, r)),
-            TT::Minus => Ok((SBinOp::Sub, r)),
-            t => Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("token not recognizable {:?}", t),
-            )),
+        [f, r @ ..] if matches!(f.typ, TT::Bang | TT::Tilde | TT::Plus | TT::Minus) => {
+            let op = match f.typ {
+                TT::Bang => SUnaryOp::Not,
+                TT::Tilde => SUnaryOp::BitNot,
+                TT::Plus => SUnaryOp::Add,
+                _ => SUnaryOp::Sub,
+            };
+            let (l, r) = parse_unary(r)?;
+            Ok((SExpr::UnaryE { op, l: Box::new(l) }, r))
+        }
+        [f, r @ ..] if f.typ == TT::Star => {
+            let (l, r) = parse_unary(r)?;
+            Ok((SExpr::Deref(Box::new(l)), r))
+        }
+        // (int) x is a cast, where (x) is an expression in parens
+        [p, t, ..] if p.typ == TT::PuncLeftParen && is_type_name(t) => {
+            let (typ, r) = parse_type(&tokens[1..])?;
+            let (_, r) = eat(r, TT::PuncRightParen)?;
+            let (l, r) = parse_unary(r)?;
+            Ok((SExpr::Cast(typ, Box::new(l)), r))
+        }
+        // sizeof (int) names a type, sizeof (x) and sizeof x an expression
+        [f, p, t, r @ ..]
+            if f.typ == TT::KeywordSizeof && p.typ == TT::PuncLeftParen && is_type_name(t) =>
+        {
+            let (typ, r) = parse_type(&tokens[2..])?;
+            let (_, r) = eat(r, TT::PuncRightParen)?;
+            Ok((SExpr::SizeOfType(typ), r))
+        }
+        // alloc and alloc_array aren't keywords, so they're only c0's when
+        // they name a type, and calls to a function of the name otherwise
+        [f, p, t, ..]
+            if f.typ == TT::Alias
+                && matches!(f.lexeme.as_str(), "alloc" | "alloc_array")
+                && p.typ == TT::PuncLeftParen
+                && is_type_name(t) =>
+        {
+            let (typ, r) = parse_type(&tokens[2..])?;
+            match f.lexeme.as_str() {
+                "alloc" => {
+                    let (_, r) = eat(r, TT::PuncRightParen)?;
+                    Ok((SExpr::Alloc(typ), r))
+                }
+                _ => {
+                    let (_, r) = eat(r, TT::PuncComma)?;
+                    let (n, r) = parse_expr(r)?;
+                    let (_, r) = eat(r, TT::PuncRightParen)?;
+                    Ok((SExpr::AllocArray(typ, Box::new(n)), r))
+                }
+            }
+        }
+        [f, p, r @ ..] if f.typ == TT::KeywordSizeof && p.typ == TT::PuncLeftParen => {
+            let (l, r) = parse_expr(r)?;
+            let (_, r) = eat(r, TT::PuncRightParen)?;
+            Ok((SExpr::SizeOf(Box::new(l)), r))
+        }
+        [f, r @ ..] if f.typ == TT::KeywordSizeof => {
+            let (l, r) = parse_unary(r)?;
+            Ok((SExpr::SizeOf(Box::new(l)), r))
+        }
+        [f, r @ ..] if f.typ == TT::Amp => match parse_unary(r)? {
+            (l, r) if lvalue(&l) => Ok((SExpr::AddrOf(Box::new(l)), r)),
+            _ => Err(io::Error::other("lvalue required as unary '&' operand")),
         },
-    }
+        _ => return parse_postfix(tokens),
+    }?;
+
+    Ok((spanned(e, tokens, r), r))
 }
 
-fn parse_factor(tokens: &[Token]) -> Result<(SExpr, &[Token]), io::Error> {
-    let (left, r) = parse_funcapp(tokens)?;
+// what can be assigned and have its address taken: x, *e, a[e] and e.f
+fn lvalue(e: &SExpr) -> bool {
+    matches!(
+        e,
+        SExpr::VarApp(_) | SExpr::Deref(_) | SExpr::Index { .. } | SExpr::Member { .. }
+    )
+}
 
-    match r {
-        [] => Ok((left, r)),
-        r => {
-            let mut cur_node = left;
-            let mut r = r;
-            while let Ok((op, _r)) = parse_factor_op(r) {
-                let (right, _r) = parse_atom(_r)?;
-
-                cur_node = SExpr::BinE {
-                    op,
-                    l: Box::new(cur_node),
-                    r: Box::new(right),
+fn parse_postfix(tokens: &[Token]) -> Result<(Spanned<SExpr>, &[Token]), io::Error> {
+    let (mut e, mut r) = parse_funcapp(tokens)?;
+
+    // member accesses chain: a[i].p.x
+    loop {
+        let node = match (e.node, r) {
+            (node, [f, _r @ ..])
+                if matches!(f.typ, TT::PlusPlus | TT::MinusMinus) && lvalue(&node) =>
+            {
+                let op = match f.typ {
+                    TT::PlusPlus => SIncDecOp::PostInc,
+                    _ => SIncDecOp::PostDec,
                 };
-
                 r = _r;
+                let lval = Box::new(Spanned::new(node, e.span));
+                return Ok((spanned(SExpr::IncDec { op, lval }, tokens, r), r));
             }
-
-            Ok((cur_node, r))
-        }
-    }
-}
-
-fn parse_factor_op(tokens: &[Token]) -> Result<(SBinOp, &[Token]), io::Error> {
-    match tokens {
-        [] => todo!(),
-        [f, r @ ..] => match f.typ {
-            TT::Star => Ok((SBinOp::Mult, r)),
-            TT::Slash => Ok((SBinOp::Div, r)),
-            t => Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("token not recognizable {:?}", t),
-            )),
-        },
+            (SExpr::VarApp(alias), [f, _r @ ..]) if f.typ == TT::PuncLeftBracket => {
+                let (idx, _r) = parse_expr(_r)?;
+                let (_, _r) = eat(_r, TT::PuncRightBracket)?;
+                r = _r;
+                SExpr::Index {
+                    alias,
+                    idx: Box::new(idx),
+                }
+            }
+            (base, [f, _r @ ..]) if f.typ == TT::Dot => {
+                Depth::fits(r, height(&base) + 1)?;
+                let (field, _r) = eat(_r, TT::Alias)?;
+                r = _r;
+                SExpr::Member {
+                    base: Box::new(Spanned::new(base, e.span)),
+                    field: field.lexeme.to_owned(),
+                }
+            }
+            // p->f is (*p).f
+            (base, [f, _r @ ..]) if f.typ == TT::MinusRightAngleBracket => {
+                Depth::fits(r, height(&base) + 2)?;
+                let (field, _r) = eat(_r, TT::Alias)?;
+                r = _r;
+                let base = SExpr::Deref(Box::new(Spanned::new(base, e.span)));
+                SExpr::Member {
+                    base: Box::new(Spanned::new(base, e.span)),
+                    field: field.lexeme.to_owned(),
+                }
+            }
+            (node, r) => return Ok((Spanned::new(node, e.span), r)),
+        };
+        e = spanned(node, tokens, r);
     }
 }
 
-fn parse_funcapp(tokens: &[Token]) -> Result<(SExpr, &[Token]), io::Error> {
+fn parse_funcapp(tokens: &[Token]) -> Result<(Spanned<SExpr>, &[Token]), io::Error> {
     let (left, r0) = parse_atom(tokens)?;
 
     match r0 {
@@ -448,20 +1438,18 @@ fn parse_funcapp(tokens: &[Token]) -> Result<(SExpr, &[Token]), io::Error> {
                 while let Ok((ap, _r)) = parse_expr(r) {
                     aps.push(ap);
 
-                    if let TT::PuncComma = _r[0].typ {
-                        r = &_r[1..];
-                    } else {
-                        r = _r;
+                    match _r {
+                        [c, _r @ ..] if c.typ == TT::PuncComma => r = _r,
+                        _ => r = _r,
                     }
                 }
                 let (_, r) = eat(r, TT::PuncRightParen)?;
 
-                match left {
-                    SExpr::VarApp(alias) => Ok((SExpr::FuncApp { alias, aps }, r)),
-                    _ => Err(io::Error::new(
-                        io::ErrorKind::Other,
-                        "expected alias".to_string(),
-                    )),
+                match left.node {
+                    SExpr::VarApp(alias) => {
+                        Ok((spanned(SExpr::FuncApp { alias, aps }, tokens, r), r))
+                    }
+                    _ => Err(io::Error::other("expected alias".to_string())),
                 }
             } else {
                 Ok((left, r0))
@@ -470,43 +1458,676 @@ fn parse_funcapp(tokens: &[Token]) -> Result<(SExpr, &[Token]), io::Error> {
     }
 }
 
-fn parse_atom(tokens: &[Token]) -> Result<(SExpr, &[Token]), io::Error> {
-    match tokens {
-        [] => todo!(),
+// an int literal's type is the first its value fits of those its suffix and
+// base allow (c89 3.1.3.2). ints and longs are both 32 bits as far as
+// literals go, so a decimal too big for int skips to unsigned long
+fn int_type(lit: &IntLit) -> Type {
+    let fits = lit.val <= i32::MAX as u32;
+    match (lit.unsigned, lit.long) {
+        (false, false) if fits => Type::Int,
+        (false, false) if lit.base != Base::Dec => Type::UInt,
+        (true, false) => Type::UInt,
+        (false, true) if fits => Type::Long,
+        _ => Type::ULong,
+    }
+}
+
+fn parse_atom(tokens: &[Token]) -> Result<(Spanned<SExpr>, &[Token]), io::Error> {
+    let (e, r) = match tokens {
+        [] => Err(io::Error::other("unexpected end of input")),
         [f, r @ ..] => match f.typ {
-            TT::Alias => Ok((SExpr::VarApp(f.lexeme.to_owned()), r)),
-            TT::LiteralInt => Ok((
-                SExpr::Int(f.lexeme.parse().map_err(|e: ParseIntError| {
-                    io::Error::new(io::ErrorKind::Other, e.to_string())
-                })?),
+            TT::Alias => match enumerator(&f.lexeme) {
+                Some(val) => Ok((SExpr::Int(val), r)),
+                None => Ok((SExpr::VarApp(f.lexeme.to_owned()), r)),
+            },
+            TT::LiteralInt => {
+                let lit = f.int.expect("the lexer reads int literals");
+                match int_type(&lit) {
+                    Type::Int => Ok((SExpr::Int(lit.val as i32), r)),
+                    typ => Ok((
+                        SExpr::TypedInt {
+                            val: lit.val as i32,
+                            typ,
+                        },
+                        r,
+                    )),
+                }
+            }
+            TT::LiteralFloat => {
+                let single = f.lexeme.ends_with(['f', 'F']);
+                let lit = f.lexeme.trim_end_matches(['f', 'F', 'l', 'L']);
+                lit.parse::<f64>()
+                    .map_err(|e| io::Error::other(e.to_string()))?;
+                Ok((
+                    SExpr::Float {
+                        lit: lit.to_owned(),
+                        single,
+                    },
+                    r,
+                ))
+            }
+            TT::LiteralChar => Ok((
+                SExpr::Int(f.lexeme.chars().next().unwrap_or('\0') as i32),
                 r,
-            )),
+            )), // 'a' is an int in c
+            TT::LiteralStr => Ok((SExpr::Str(f.lexeme.to_owned()), r)),
             TT::KeywordTrue => Ok((SExpr::Bool(true), r)),
             TT::KeywordFalse => Ok((SExpr::Bool(false), r)),
-            t => Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("token not recognizable {:?}", t),
-            )),
+            TT::KeywordNull => Ok((SExpr::Int(0), r)), // c0's null pointer constant
+            // (e) is e, spanning its parens
+            TT::PuncLeftParen => {
+                let (e, r) = parse_expr(r)?;
+                let (_, r) = eat(r, TT::PuncRightParen)?;
+                Ok((e.node, r))
+            }
+            t => {
+                let e = io::Error::other(format!("token not recognizable {:?}", t));
+                Err(source::locate(e, f.span))
+            }
         },
+    }?;
+
+    Ok((spanned(e, tokens, r), r))
+}
+
+#[cfg(test)]
+mod test_arith {
+    use crate::lexer;
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/arith";
+
+    #[test]
+    fn lit() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/lit.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Return:
+                  Int: 8
+        "###);
+    }
+
+    #[test]
+    fn add() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/add.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Return:
+                  BinE:
+                    op: Add
+                    l:
+                      Int: 9
+                    r:
+                      Int: 10
+        "###);
+    }
+
+    #[test]
+    fn add_multi() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/add_multi.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Return:
+                  BinE:
+                    op: Add
+                    l:
+                      BinE:
+                        op: Add
+                        l:
+                          Int: 9
+                        r:
+                          Int: 10
+                    r:
+                      Int: 11
+        "###);
+    }
+
+    #[test]
+    fn sub() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/sub.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Return:
+                  BinE:
+                    op: Sub
+                    l:
+                      Int: 88
+                    r:
+                      Int: 32
+        "###);
+    }
+
+    #[test]
+    fn mult() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/mult.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Return:
+                  BinE:
+                    op: Mult
+                    l:
+                      Int: 9
+                    r:
+                      Int: 10
+        "###);
+    }
+
+    #[test]
+    fn div() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/div.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Return:
+                  BinE:
+                    op: Div
+                    l:
+                      Int: 100
+                    r:
+                      Int: 9
+        "###);
+    }
+
+    #[test]
+    fn add_associative() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/add_associative.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Return:
+                  BinE:
+                    op: Add
+                    l:
+                      BinE:
+                        op: Add
+                        l:
+                          Int: 9
+                        r:
+                          Int: 10
+                    r:
+                      Int: 11
+        "###);
+    }
+
+    #[test]
+    fn sub_associative() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/sub_associative.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Return:
+                  BinE:
+                    op: Sub
+                    l:
+                      BinE:
+                        op: Sub
+                        l:
+                          Int: 30
+                        r:
+                          Int: 9
+                    r:
+                      Int: 10
+        "###);
+    }
+
+    #[test]
+    fn mult_add_precedence() {
+        let src =
+            fs::read_to_string(format!("{TEST_DIR}/mult_add_precedence.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Return:
+                  BinE:
+                    op: Add
+                    l:
+                      BinE:
+                        op: Mult
+                        l:
+                          Int: 9
+                        r:
+                          Int: 10
+                    r:
+                      Int: 11
+        "###);
+    }
+
+    #[test]
+    fn mult_add_precedence_multi() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/mult_add_precedence_multi.c"))
+            .expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Return:
+                  BinE:
+                    op: Add
+                    l:
+                      BinE:
+                        op: Mult
+                        l:
+                          Int: 9
+                        r:
+                          Int: 10
+                    r:
+                      BinE:
+                        op: Mult
+                        l:
+                          Int: 11
+                        r:
+                          Int: 12
+        "###);
+    }
+
+    #[test]
+    fn neg() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/neg.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Return:
+                  BinE:
+                    op: Sub
+                    l:
+                      BinE:
+                        op: Mult
+                        l:
+                          UnaryE:
+                            op: Sub
+                            l:
+                              Int: 5
+                        r:
+                          UnaryE:
+                            op: Sub
+                            l:
+                              Int: 2
+                    r:
+                      UnaryE:
+                        op: Add
+                        l:
+                          Int: 3
+        "###);
+    }
+
+    #[test]
+    fn modulo() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/mod.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Return:
+                  BinE:
+                    op: Mult
+                    l:
+                      BinE:
+                        op: Mod
+                        l:
+                          Int: 17
+                        r:
+                          Int: 5
+                    r:
+                      Int: 3
+        "###);
+    }
+
+    #[test]
+    fn bits_precedence() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/bits_precedence.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Return:
+                  BitE:
+                    op: Or
+                    l:
+                      Int: 1
+                    r:
+                      BitE:
+                        op: Xor
+                        l:
+                          BitE:
+                            op: And
+                            l:
+                              Int: 6
+                            r:
+                              Int: 3
+                        r:
+                          BitE:
+                            op: Shl
+                            l:
+                              Int: 8
+                            r:
+                              Int: 1
+        "###);
+    }
+
+    #[test]
+    fn integers() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/unsigned.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Asnmt:
+                  alias: u
+                  typ: UInt
+                  expr:
+                    UnaryE:
+                      op: Sub
+                      l:
+                        Int: 8
+              - Asnmt:
+                  alias: c
+                  typ: UChar
+                  expr:
+                    VarApp: u
+              - Return:
+                  BinE:
+                    op: Div
+                    l:
+                      VarApp: u
+                    r:
+                      VarApp: c
+        "###);
+    }
+
+    #[test]
+    fn long_long() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/long_long.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let err = super::parse_prg(&tokens).unwrap_err();
+        insta::assert_snapshot!(err.to_string(), @"invalid type specifiers: long long");
+    }
+
+    #[test]
+    fn precedence_table() {
+        // every level of the table, loosest first, with a left associative
+        // pair and a right associative ?:
+        fn show(e: &crate::SExpr) -> String {
+            use crate::SExpr::*;
+            match e {
+                Int(n) => n.to_string(),
+                VarApp(a) => a.to_owned(),
+                LogE { op, l, r } => format!("({op:?} {} {})", show(&l.node), show(&r.node)),
+                BitE { op, l, r } => format!("({op:?} {} {})", show(&l.node), show(&r.node)),
+                RelE { op, l, r } => format!("({op:?} {} {})", show(&l.node), show(&r.node)),
+                BinE { op, l, r } => format!("({op:?} {} {})", show(&l.node), show(&r.node)),
+                CondE { cond, then, els } => format!(
+                    "(? {} {} {})",
+                    show(&cond.node),
+                    show(&then.node),
+                    show(&els.node)
+                ),
+                e => format!("{e:?}"),
+            }
+        }
+        let src = "a || b && c | d ^ e & f == g < h << i + j * k - l ? 1 : m ? 2 : 3";
+        let tokens = lexer::lex(src).unwrap();
+        let (e, r) = super::parse_expr(&tokens).unwrap();
+        assert!(r.is_empty());
+        insta::assert_snapshot!(show(&e.node), @"(? (Or a (And b (Or c (Xor d (And e (Eq f (Lt g (Shl h (Sub (Add i (Mult j k)) l))))))))) 1 (? m 2 3))");
+    }
+
+    #[test]
+    fn casts() {
+        use crate::{SExpr, Type};
+        let parse = |src| {
+            let tokens = lexer::lex(src).unwrap();
+            let (e, r) = super::parse_expr(&tokens).unwrap();
+            assert!(r.is_empty());
+            e.node
+        };
+        let var = |e: &SExpr, x: &str| *e == SExpr::VarApp(x.to_owned());
+
+        // a cast binds as tightly as the other unary operators
+        let SExpr::BinE { l, .. } = parse("(int) x + 1") else {
+            panic!("expected a sum")
+        };
+        assert!(matches!(&l.node, SExpr::Cast(Type::Int, x) if var(&x.node, "x")));
+        let e = parse("(unsigned char *) &x");
+        assert!(matches!(&e, SExpr::Cast(Type::Ptr(t), x)
+            if **t == Type::UChar && matches!(&x.node, SExpr::AddrOf(_))));
+
+        // and a name in parens is only grouped
+        let SExpr::BinE { l, .. } = parse("(x) + 1") else {
+            panic!("expected a sum")
+        };
+        assert!(var(&l.node, "x"));
+    }
+}
+
+#[cfg(test)]
+mod test_control_c0 {
+    use crate::lexer;
+    use std::fs;
+    const TEST_DIR: &str = "tests/fixtures/snap/statics-c0/control";
+
+    #[test]
+    fn ifels() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/if.c0")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - IfEls:
+                  cond:
+                    Bool: true
+                  then:
+                    Return:
+                      Int: 9
+                  els:
+                    Return:
+                      Int: 10
+        "###);
+    }
+
+    #[test]
+    fn ifnoels() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/if4.c0")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - IfEls:
+                  cond:
+                    Bool: true
+                  then:
+                    Asnmt:
+                      alias: x
+                      typ: Int
+                      expr:
+                        Int: 8
+                  els: ~
+              - IfEls:
+                  cond:
+                    Bool: false
+                  then:
+                    Return:
+                      Int: 9
+                  els: ~
+              - IfEls:
+                  cond:
+                    Bool: false
+                  then:
+                    Return:
+                      Int: 10
+                  els: ~
+              - IfEls:
+                  cond:
+                    Bool: false
+                  then:
+                    Return:
+                      Int: 11
+                  els: ~
+              - IfEls:
+                  cond:
+                    Bool: true
+                  then:
+                    Return:
+                      Int: 12
+                  els: ~
+        "###);
     }
 }
 
 #[cfg(test)]
-mod test_arith {
-    use crate::lexer;
+mod test_control_c89 {
+    use crate::{lexer, source, SDef};
     use std::fs;
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/control";
 
-    const TEST_DIR: &str = "tests/fixtures/snap/shared/arith";
+    #[test]
+    fn not() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/not.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Return:
+                  RelE:
+                    op: Eq
+                    l:
+                      UnaryE:
+                        op: Not
+                        l:
+                          Int: 0
+                    r:
+                      UnaryE:
+                        op: Not
+                        l:
+                          UnaryE:
+                            op: Not
+                            l:
+                              Int: 1
+        "###);
+    }
 
     #[test]
-    fn lit() {
-        let chars = fs::read(format!("{TEST_DIR}/lit.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn eq_lt_precedence() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/eq_lt_precedence.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
@@ -516,19 +2137,35 @@ mod test_arith {
             fps: []
             body:
               - Return:
-                  Int: 8
+                  RelE:
+                    op: Eq
+                    l:
+                      RelE:
+                        op: Lt
+                        l:
+                          BinE:
+                            op: Add
+                            l:
+                              Int: 8
+                            r:
+                              Int: 1
+                        r:
+                          Int: 10
+                    r:
+                      RelE:
+                        op: GtEq
+                        l:
+                          Int: 10
+                        r:
+                          Int: 9
         "###);
     }
 
     #[test]
-    fn add() {
-        let chars = fs::read(format!("{TEST_DIR}/add.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn eq() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/eq_true.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
@@ -538,8 +2175,31 @@ mod test_arith {
             fps: []
             body:
               - Return:
-                  BinE:
-                    op: Add
+                  RelE:
+                    op: Eq
+                    l:
+                      Int: 9
+                    r:
+                      Int: 9
+        "###);
+    }
+
+    #[test]
+    fn neq() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/neq_true.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Return:
+                  RelE:
+                    op: Neq
                     l:
                       Int: 9
                     r:
@@ -548,14 +2208,10 @@ mod test_arith {
     }
 
     #[test]
-    fn add_multi() {
-        let chars = fs::read(format!("{TEST_DIR}/add_multi.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn and() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/and_true.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
@@ -565,29 +2221,20 @@ mod test_arith {
             fps: []
             body:
               - Return:
-                  BinE:
-                    op: Add
+                  LogE:
+                    op: And
                     l:
-                      BinE:
-                        op: Add
-                        l:
-                          Int: 9
-                        r:
-                          Int: 10
+                      Int: 1
                     r:
-                      Int: 11
+                      Int: 1
         "###);
     }
 
     #[test]
-    fn sub() {
-        let chars = fs::read(format!("{TEST_DIR}/sub.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn or() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/or_true.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
@@ -597,24 +2244,20 @@ mod test_arith {
             fps: []
             body:
               - Return:
-                  BinE:
-                    op: Sub
+                  LogE:
+                    op: Or
                     l:
-                      Int: 88
+                      Int: 1
                     r:
-                      Int: 32
+                      Int: 1
         "###);
     }
 
     #[test]
-    fn mult() {
-        let chars = fs::read(format!("{TEST_DIR}/mult.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn lt() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/lt_true.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
@@ -624,8 +2267,8 @@ mod test_arith {
             fps: []
             body:
               - Return:
-                  BinE:
-                    op: Mult
+                  RelE:
+                    op: Lt
                     l:
                       Int: 9
                     r:
@@ -634,14 +2277,10 @@ mod test_arith {
     }
 
     #[test]
-    fn div() {
-        let chars = fs::read(format!("{TEST_DIR}/div.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn gt() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/gt_true.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
@@ -651,24 +2290,45 @@ mod test_arith {
             fps: []
             body:
               - Return:
-                  BinE:
-                    op: Div
+                  RelE:
+                    op: Gt
                     l:
-                      Int: 100
+                      Int: 10
                     r:
                       Int: 9
         "###);
     }
 
     #[test]
-    fn add_associative() {
-        let chars = fs::read(format!("{TEST_DIR}/add_associative.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn ifels_then() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/ifels_then.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - IfEls:
+                  cond:
+                    Int: 1
+                  then:
+                    Return:
+                      Int: 9
+                  els:
+                    Return:
+                      Int: 10
+        "###);
+    }
+
+    #[test]
+    fn while_false() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/while_false.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
@@ -677,30 +2337,22 @@ mod test_arith {
             typ: Int
             fps: []
             body:
+              - While:
+                  cond:
+                    Int: 0
+                  body:
+                    - Return:
+                        Int: 9
               - Return:
-                  BinE:
-                    op: Add
-                    l:
-                      BinE:
-                        op: Add
-                        l:
-                          Int: 9
-                        r:
-                          Int: 10
-                    r:
-                      Int: 11
+                  Int: 10
         "###);
     }
 
     #[test]
-    fn sub_associative() {
-        let chars = fs::read(format!("{TEST_DIR}/sub_associative.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn for_loop() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/for.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
@@ -709,30 +2361,356 @@ mod test_arith {
             typ: Int
             fps: []
             body:
+              - Asnmt:
+                  alias: n
+                  typ: Int
+                  expr:
+                    Int: 0
+              - For:
+                  asnmt:
+                    Asnmt:
+                      alias: i
+                      typ: Int
+                      expr:
+                        Int: 0
+                  cond:
+                    RelE:
+                      op: Lt
+                      l:
+                        VarApp: i
+                      r:
+                        Int: 10
+                  update:
+                    Update:
+                      alias: i
+                      expr:
+                        BinE:
+                          op: Add
+                          l:
+                            VarApp: i
+                          r:
+                            Int: 1
+                  body:
+                    - Update:
+                        alias: n
+                        expr:
+                          BinE:
+                            op: Add
+                            l:
+                              VarApp: n
+                            r:
+                              Int: 1
+                    - Update:
+                        alias: n
+                        expr:
+                          BinE:
+                            op: Add
+                            l:
+                              VarApp: n
+                            r:
+                              Int: 1
               - Return:
-                  BinE:
-                    op: Sub
-                    l:
-                      BinE:
-                        op: Sub
-                        l:
-                          Int: 30
-                        r:
-                          Int: 9
-                    r:
-                      Int: 10
+                  VarApp: n
+        "###);
+    }
+
+    // each of a for's clauses can be left out
+    #[test]
+    fn for_empty() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/for_empty.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Asnmt:
+                  alias: n
+                  typ: Int
+                  expr:
+                    Int: 0
+              - Asnmt:
+                  alias: i
+                  typ: Int
+                  expr:
+                    Int: 0
+              - For:
+                  asnmt:
+                    Block: []
+                  cond:
+                    RelE:
+                      op: Lt
+                      l:
+                        VarApp: i
+                      r:
+                        Int: 3
+                  update:
+                    Update:
+                      alias: i
+                      expr:
+                        BinE:
+                          op: Add
+                          l:
+                            VarApp: i
+                          r:
+                            Int: 1
+                  body:
+                    - Update:
+                        alias: n
+                        expr:
+                          BinE:
+                            op: Add
+                            l:
+                              VarApp: n
+                            r:
+                              VarApp: i
+              - For:
+                  asnmt:
+                    Update:
+                      alias: i
+                      expr:
+                        Int: 0
+                  cond:
+                    RelE:
+                      op: Lt
+                      l:
+                        VarApp: i
+                      r:
+                        Int: 3
+                  update:
+                    Block: []
+                  body:
+                    - Update:
+                        alias: i
+                        expr:
+                          BinE:
+                            op: Add
+                            l:
+                              VarApp: i
+                            r:
+                              Int: 1
+                    - Update:
+                        alias: n
+                        expr:
+                          BinE:
+                            op: Add
+                            l:
+                              VarApp: n
+                            r:
+                              Int: 100
+              - For:
+                  asnmt:
+                    Block: []
+                  cond:
+                    Bool: true
+                  update:
+                    Block: []
+                  body:
+                    - Update:
+                        alias: n
+                        expr:
+                          BinE:
+                            op: Add
+                            l:
+                              VarApp: n
+                            r:
+                              Int: 1000
+                    - Break
+              - Return:
+                  VarApp: n
+        "###);
+    }
+
+    #[test]
+    fn cond() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/cond.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Asnmt:
+                  alias: x
+                  typ: Int
+                  expr:
+                    Int: 0
+              - Asnmt:
+                  alias: y
+                  typ: Int
+                  expr:
+                    CondE:
+                      cond:
+                        RelE:
+                          op: Neq
+                          l:
+                            VarApp: x
+                          r:
+                            Int: 0
+                      then:
+                        BinE:
+                          op: Div
+                          l:
+                            Int: 10
+                          r:
+                            VarApp: x
+                      els:
+                        CondE:
+                          cond:
+                            RelE:
+                              op: Eq
+                              l:
+                                VarApp: x
+                              r:
+                                Int: 0
+                          then:
+                            Int: 7
+                          els:
+                            Int: 8
+              - Return:
+                  VarApp: y
+        "###);
+    }
+
+    #[test]
+    fn switch_sparse() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/switch_sparse.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Asnmt:
+                  alias: x
+                  typ: Int
+                  expr:
+                    Int: 100
+              - Asnmt:
+                  alias: y
+                  typ: Int
+                  expr:
+                    Int: 0
+              - Switch:
+                  cond:
+                    VarApp: x
+                  arms:
+                    - label: 1
+                      body:
+                        - Update:
+                            alias: y
+                            expr:
+                              Int: 1
+                    - label: 100
+                      body:
+                        - Update:
+                            alias: y
+                            expr:
+                              BinE:
+                                op: Add
+                                l:
+                                  VarApp: y
+                                r:
+                                  Int: 100
+                    - label: 1000
+                      body:
+                        - Update:
+                            alias: y
+                            expr:
+                              BinE:
+                                op: Add
+                                l:
+                                  VarApp: y
+                                r:
+                                  Int: 1000
+              - Switch:
+                  cond:
+                    VarApp: y
+                  arms:
+                    - label: 7
+                      body:
+                        - Update:
+                            alias: y
+                            expr:
+                              Int: 0
+              - Return:
+                  VarApp: y
+        "###);
+    }
+
+    #[test]
+    fn goto() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/goto.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Asnmt:
+                  alias: i
+                  typ: Int
+                  expr:
+                    Int: 0
+              - Asnmt:
+                  alias: sum
+                  typ: Int
+                  expr:
+                    Int: 0
+              - Label: top
+              - Update:
+                  alias: i
+                  expr:
+                    BinE:
+                      op: Add
+                      l:
+                        VarApp: i
+                      r:
+                        Int: 1
+              - Update:
+                  alias: sum
+                  expr:
+                    BinE:
+                      op: Add
+                      l:
+                        VarApp: sum
+                      r:
+                        VarApp: i
+              - Switch:
+                  cond:
+                    VarApp: i
+                  arms:
+                    - label: 5
+                      body:
+                        - Goto: done
+              - Goto: top
+              - Label: done
+              - Return:
+                  VarApp: sum
         "###);
     }
 
     #[test]
-    fn mult_add_precedence() {
-        let chars = fs::read(format!("{TEST_DIR}/mult_add_precedence.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn do_while() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/do_while.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
@@ -741,6 +2719,78 @@ mod test_arith {
             typ: Int
             fps: []
             body:
+              - Asnmt:
+                  alias: n
+                  typ: Int
+                  expr:
+                    Int: 0
+              - DoWhile:
+                  body:
+                    - Update:
+                        alias: n
+                        expr:
+                          BinE:
+                            op: Add
+                            l:
+                              VarApp: n
+                            r:
+                              Int: 1
+                  cond:
+                    RelE:
+                      op: Lt
+                      l:
+                        VarApp: n
+                      r:
+                        Int: 0
+              - Asnmt:
+                  alias: i
+                  typ: Int
+                  expr:
+                    Int: 0
+              - Asnmt:
+                  alias: sum
+                  typ: Int
+                  expr:
+                    Int: 0
+              - DoWhile:
+                  body:
+                    - Update:
+                        alias: i
+                        expr:
+                          BinE:
+                            op: Add
+                            l:
+                              VarApp: i
+                            r:
+                              Int: 1
+                    - Switch:
+                        cond:
+                          BinE:
+                            op: Mod
+                            l:
+                              VarApp: i
+                            r:
+                              Int: 2
+                        arms:
+                          - label: 0
+                            body:
+                              - Continue
+                    - Update:
+                        alias: sum
+                        expr:
+                          BinE:
+                            op: Add
+                            l:
+                              VarApp: sum
+                            r:
+                              VarApp: i
+                  cond:
+                    RelE:
+                      op: Lt
+                      l:
+                        VarApp: i
+                      r:
+                        Int: 9
               - Return:
                   BinE:
                     op: Add
@@ -748,23 +2798,53 @@ mod test_arith {
                       BinE:
                         op: Mult
                         l:
-                          Int: 9
+                          VarApp: n
                         r:
-                          Int: 10
+                          Int: 100
                     r:
-                      Int: 11
+                      VarApp: sum
         "###);
     }
 
     #[test]
-    fn mult_add_precedence_multi() {
-        let chars = fs::read(format!("{TEST_DIR}/mult_add_precedence_multi.c"))
-            .expect("file dne")
+    fn syntax_errors() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/syntax_errors.c")).expect("file dne");
+
+        // each error is reported, and the definitions around them still parse
+        let tokens = lexer::lex(&src).unwrap();
+        let (tree, errs) = super::parse(&tokens);
+        let errs = errs
             .iter()
-            .map(|b| *b as char)
+            .map(|e| {
+                let pos = source::pos(&src, e.span.offset(&src), 4);
+                format!("{}:{}: {e}", pos.line, pos.col)
+            })
             .collect::<Vec<_>>();
+        insta::assert_yaml_snapshot!(errs, @r###"
+        ---
+        - "2:17: token not recognizable PuncSemiColon"
+        - "6:18: expected: PuncSemiColon got: LiteralInt"
+        - "12:17: token not recognizable PuncSemiColon"
+        - "17:5: expected: Alias got: Equals"
+        "###);
+
+        let defs = tree.iter().map(|d| match &d.node {
+            SDef::FuncDef(fd) => (fd.alias.as_str(), fd.body.len()),
+            _ => ("", 0),
+        });
+        assert_eq!(defs.collect::<Vec<_>>(), [("f", 0), ("g", 2), ("main", 1)]);
 
-        let tokens = lexer::lex(&chars).unwrap();
+        // parse_prg stops at the first
+        let err = super::parse_prg(&tokens).unwrap_err();
+        assert_eq!(format!("2:17: {err}"), errs[0]);
+    }
+
+    #[test]
+    fn contracts() {
+        let src =
+            fs::read_to_string(format!("{TEST_DIR}/contracts_invariant.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
@@ -773,163 +2853,192 @@ mod test_arith {
             typ: Int
             fps: []
             body:
+              - Asnmt:
+                  alias: i
+                  typ: Int
+                  expr:
+                    Int: 0
+              - While:
+                  cond:
+                    RelE:
+                      op: Lt
+                      l:
+                        VarApp: i
+                      r:
+                        Int: 10
+                  body:
+                    - Update:
+                        alias: i
+                        expr:
+                          BinE:
+                            op: Add
+                            l:
+                              VarApp: i
+                            r:
+                              Int: 1
+                  invariants:
+                    - kind: LoopInvariant
+                      expr:
+                        RelE:
+                          op: Lt
+                          l:
+                            VarApp: i
+                          r:
+                            Int: 5
               - Return:
-                  BinE:
-                    op: Add
-                    l:
-                      BinE:
-                        op: Mult
-                        l:
-                          Int: 9
-                        r:
-                          Int: 10
-                    r:
-                      BinE:
-                        op: Mult
-                        l:
-                          Int: 11
-                        r:
-                          Int: 12
+                  VarApp: i
         "###);
+
+        // a loop's invariant isn't a function's
+        let src = "int f() //@loop_invariant 1; { return 0; }";
+        let err = super::parse_prg(&lexer::lex(src).unwrap()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "@loop_invariant annotation isn't allowed here"
+        );
     }
 }
 
 #[cfg(test)]
-mod test_control_c0 {
-    use crate::lexer;
+mod test_bindings {
+    use crate::{lexer, source::Span, SDef, SStmt};
     use std::fs;
-    const TEST_DIR: &str = "tests/fixtures/snap/statics-c0/control";
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/bindings";
 
     #[test]
-    fn ifels() {
-        let chars = fs::read(format!("{TEST_DIR}/if.c0"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn composition() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/composition.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
         - FuncDef:
-            alias: main
+            alias: h
             typ: Int
             fps: []
             body:
-              - IfEls:
-                  cond:
-                    Bool: true
-                  then:
-                    Return:
-                      Int: 9
-                  els:
-                    Return:
+              - Return:
+                  Int: 11
+        - FuncDef:
+            alias: g
+            typ: Int
+            fps: []
+            body:
+              - Return:
+                  BinE:
+                    op: Add
+                    l:
                       Int: 10
+                    r:
+                      FuncApp:
+                        alias: h
+                        aps: []
+        - FuncDef:
+            alias: f
+            typ: Int
+            fps: []
+            body:
+              - Return:
+                  BinE:
+                    op: Add
+                    l:
+                      Int: 9
+                    r:
+                      FuncApp:
+                        alias: g
+                        aps: []
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Return:
+                  FuncApp:
+                    alias: f
+                    aps: []
         "###);
     }
 
     #[test]
-    fn ifnoels() {
-        let chars = fs::read(format!("{TEST_DIR}/if4.c0"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn formal_param() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/formal_param.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
+        - FuncDef:
+            alias: f
+            typ: Int
+            fps:
+              - - x
+                - Int
+            body:
+              - Return:
+                  BinE:
+                    op: Add
+                    l:
+                      VarApp: x
+                    r:
+                      Int: 10
         - FuncDef:
             alias: main
             typ: Int
             fps: []
             body:
-              - IfEls:
-                  cond:
-                    Bool: true
-                  then:
-                    Asnmt:
-                      alias: x
-                      typ: Int
-                      expr:
-                        Int: 8
-                  els: ~
-              - IfEls:
-                  cond:
-                    Bool: false
-                  then:
-                    Return:
-                      Int: 9
-                  els: ~
-              - IfEls:
-                  cond:
-                    Bool: false
-                  then:
-                    Return:
-                      Int: 10
-                  els: ~
-              - IfEls:
-                  cond:
-                    Bool: false
-                  then:
-                    Return:
-                      Int: 11
-                  els: ~
-              - IfEls:
-                  cond:
-                    Bool: true
-                  then:
-                    Return:
-                      Int: 12
-                  els: ~
+              - Return:
+                  FuncApp:
+                    alias: f
+                    aps:
+                      - Int: 9
         "###);
     }
-}
-
-#[cfg(test)]
-mod test_control_c89 {
-    use crate::lexer;
-    use std::fs;
-    const TEST_DIR: &str = "tests/fixtures/snap/shared/control";
 
     #[test]
-    fn eq() {
-        let chars = fs::read(format!("{TEST_DIR}/eq_true.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn formal_param_multi() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/formal_param_multi.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
         - FuncDef:
-            alias: main
+            alias: f
             typ: Int
-            fps: []
+            fps:
+              - - x
+                - Int
+              - - y
+                - Int
             body:
               - Return:
-                  RelE:
-                    op: Eq
+                  BinE:
+                    op: Add
                     l:
-                      Int: 9
+                      VarApp: x
                     r:
-                      Int: 9
+                      VarApp: y
+        - FuncDef:
+            alias: main
+            typ: Int
+            fps: []
+            body:
+              - Return:
+                  FuncApp:
+                    alias: f
+                    aps:
+                      - Int: 9
+                      - Int: 10
         "###);
     }
 
     #[test]
-    fn neq() {
-        let chars = fs::read(format!("{TEST_DIR}/neq_true.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn asnmt() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/assignment.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
@@ -938,25 +3047,21 @@ mod test_control_c89 {
             typ: Int
             fps: []
             body:
+              - Asnmt:
+                  alias: x
+                  typ: Int
+                  expr:
+                    Int: 9
               - Return:
-                  RelE:
-                    op: Neq
-                    l:
-                      Int: 9
-                    r:
-                      Int: 10
+                  VarApp: x
         "###);
     }
 
     #[test]
-    fn and() {
-        let chars = fs::read(format!("{TEST_DIR}/and_true.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn asnmt_reassign() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/asnmt_reassign.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
@@ -965,25 +3070,32 @@ mod test_control_c89 {
             typ: Int
             fps: []
             body:
+              - Asnmt:
+                  alias: x
+                  typ: Int
+                  expr:
+                    Int: 5
+              - Update:
+                  alias: x
+                  expr:
+                    BinE:
+                      op: Add
+                      l:
+                        VarApp: x
+                      r:
+                        Int: 1
               - Return:
-                  RelE:
-                    op: And
-                    l:
-                      Int: 1
-                    r:
-                      Int: 1
+                  VarApp: x
         "###);
     }
 
     #[test]
-    fn or() {
-        let chars = fs::read(format!("{TEST_DIR}/or_true.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn asnmt_lval() {
+        // a compound store keeps its op, so its lval is only found once, and
+        // ++ and -- statements over an lval are compound stores too
+        let src = "int main() { int a[2]; int *p = a; a[0] += 1; *p -= 2; ++a[1]; return (*p)++; }";
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
@@ -992,25 +3104,57 @@ mod test_control_c89 {
             typ: Int
             fps: []
             body:
+              - Decl:
+                  alias: a
+                  typ:
+                    Arr:
+                      - Int
+                      - 2
+              - Asnmt:
+                  alias: p
+                  typ:
+                    Ptr: Int
+                  expr:
+                    VarApp: a
+              - Store:
+                  lval:
+                    Index:
+                      alias: a
+                      idx:
+                        Int: 0
+                  expr:
+                    Int: 1
+                  op: Add
+              - Store:
+                  lval:
+                    Deref:
+                      VarApp: p
+                  expr:
+                    Int: 2
+                  op: Sub
+              - Store:
+                  lval:
+                    Index:
+                      alias: a
+                      idx:
+                        Int: 1
+                  expr:
+                    Int: 1
+                  op: Add
               - Return:
-                  RelE:
-                    op: Or
-                    l:
-                      Int: 1
-                    r:
-                      Int: 1
+                  IncDec:
+                    op: PostInc
+                    lval:
+                      Deref:
+                        VarApp: p
         "###);
     }
 
     #[test]
-    fn lt() {
-        let chars = fs::read(format!("{TEST_DIR}/lt_true.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn asnmt_compound() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/asnmt_compound.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
@@ -1019,25 +3163,66 @@ mod test_control_c89 {
             typ: Int
             fps: []
             body:
+              - Asnmt:
+                  alias: x
+                  typ: Int
+                  expr:
+                    Int: 5
+              - Update:
+                  alias: x
+                  expr:
+                    BinE:
+                      op: Add
+                      l:
+                        VarApp: x
+                      r:
+                        Int: 3
+              - Update:
+                  alias: x
+                  expr:
+                    BinE:
+                      op: Mult
+                      l:
+                        VarApp: x
+                      r:
+                        Int: 4
+              - Update:
+                  alias: x
+                  expr:
+                    BinE:
+                      op: Sub
+                      l:
+                        VarApp: x
+                      r:
+                        Int: 2
+              - Update:
+                  alias: x
+                  expr:
+                    BinE:
+                      op: Div
+                      l:
+                        VarApp: x
+                      r:
+                        Int: 4
+              - Update:
+                  alias: x
+                  expr:
+                    BinE:
+                      op: Mod
+                      l:
+                        VarApp: x
+                      r:
+                        Int: 4
               - Return:
-                  RelE:
-                    op: Lt
-                    l:
-                      Int: 9
-                    r:
-                      Int: 10
+                  VarApp: x
         "###);
     }
 
     #[test]
-    fn gt() {
-        let chars = fs::read(format!("{TEST_DIR}/gt_true.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn asnmt_update() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/asnmt_update.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
@@ -1046,303 +3231,666 @@ mod test_control_c89 {
             typ: Int
             fps: []
             body:
+              - Asnmt:
+                  alias: n
+                  typ: Int
+                  expr:
+                    Int: 0
+              - Update:
+                  alias: n
+                  expr:
+                    BinE:
+                      op: Add
+                      l:
+                        VarApp: n
+                      r:
+                        Int: 10
               - Return:
-                  RelE:
-                    op: Gt
-                    l:
-                      Int: 10
-                    r:
-                      Int: 9
+                  VarApp: n
         "###);
     }
 
     #[test]
-    fn ifels_then() {
-        let chars = fs::read(format!("{TEST_DIR}/ifels_then.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn attrs() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/attrs.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
+        - FuncDef:
+            alias: double_it
+            typ: Int
+            fps:
+              - - x
+                - Int
+            body:
+              - Return:
+                  BinE:
+                    op: Mult
+                    l:
+                      VarApp: x
+                    r:
+                      Int: 2
+            attrs:
+              - NoInline
         - FuncDef:
             alias: main
             typ: Int
             fps: []
             body:
-              - IfEls:
-                  cond:
-                    Int: 1
-                  then:
-                    Return:
-                      Int: 9
-                  els:
-                    Return:
-                      Int: 10
+              - Return:
+                  FuncApp:
+                    alias: double_it
+                    aps:
+                      - Int: 21
+            attrs:
+              - AlwaysInline
+              - Optimize: O0
         "###);
     }
 
-    // #[test]
-    // fn for_loop() {
-    //     let chars = fs::read(format!("{TEST_DIR}/for.c"))
-    //         .expect("file dne")
-    //         .iter()
-    //         .map(|b| *b as char)
-    //         .collect::<Vec<_>>();
-
-    //     let tokens = lexer::lex(&chars).unwrap();
-    //     let tree = super::parse_prg(&tokens).unwrap();
-    //     insta::assert_yaml_snapshot!(tree, @r###"
-    //     ---
-    //     main_function:
-    //       stmts:
-    //         - Asnmt:
-    //             CreateBind:
-    //               id: n
-    //               expr:
-    //                 Int: 0
-    //         - For:
-    //             asnmt:
-    //               CreateBind:
-    //                 id: i
-    //                 expr:
-    //                   Int: 0
-    //             cond:
-    //               RelE:
-    //                 op: Lt
-    //                 l:
-    //                   Var: i
-    //                 r:
-    //                   Int: 10
-    //             update:
-    //               UpdateBind:
-    //                 id: i
-    //                 op: Add
-    //                 expr:
-    //                   Int: 1
-    //             body:
-    //               - Asnmt:
-    //                   UpdateBind:
-    //                     id: n
-    //                     op: Add
-    //                     expr:
-    //                       Int: 1
-    //               - Asnmt:
-    //                   UpdateBind:
-    //                     id: n
-    //                     op: Add
-    //                     expr:
-    //                       Int: 1
-    //         - Return:
-    //             Var: n
-    //     "###);
-    // }
-}
-
-#[cfg(test)]
-mod test_bindings {
-    use crate::lexer;
-    use std::fs;
+    #[test]
+    fn attrs_conflict() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/attrs_conflict.c")).expect("file dne");
 
-    const TEST_DIR: &str = "tests/fixtures/snap/shared/bindings";
+        let tokens = lexer::lex(&src).unwrap();
+        let err = super::parse_prg(&tokens).unwrap_err();
+        insta::assert_snapshot!(err.to_string(), @"conflicting attributes: noinline, always_inline");
+    }
 
     #[test]
-    fn composition() {
-        let chars = fs::read(format!("{TEST_DIR}/composition.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn ptr() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/ptr.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
         - FuncDef:
-            alias: h
+            alias: set
             typ: Int
-            fps: []
+            fps:
+              - - p
+                - Ptr: Int
+              - - v
+                - Int
             body:
+              - Store:
+                  lval:
+                    Deref:
+                      VarApp: p
+                  expr:
+                    VarApp: v
               - Return:
-                  Int: 11
+                  VarApp: v
         - FuncDef:
-            alias: g
+            alias: main
             typ: Int
             fps: []
             body:
+              - Asnmt:
+                  alias: x
+                  typ: Int
+                  expr:
+                    Int: 1
+              - Asnmt:
+                  alias: p
+                  typ:
+                    Ptr: Int
+                  expr:
+                    AddrOf:
+                      VarApp: x
+              - Store:
+                  lval:
+                    Deref:
+                      VarApp: p
+                  expr:
+                    Int: 3
+              - Asnmt:
+                  alias: pp
+                  typ:
+                    Ptr:
+                      Ptr: Int
+                  expr:
+                    AddrOf:
+                      VarApp: p
+              - Store:
+                  lval:
+                    Deref:
+                      Deref:
+                        VarApp: pp
+                  expr:
+                    BinE:
+                      op: Add
+                      l:
+                        Deref:
+                          VarApp: p
+                      r:
+                        Int: 4
+              - Asnmt:
+                  alias: y
+                  typ: Int
+                  expr:
+                    FuncApp:
+                      alias: set
+                      aps:
+                        - AddrOf:
+                            VarApp: x
+                        - BinE:
+                            op: Mult
+                            l:
+                              VarApp: x
+                            r:
+                              Int: 2
               - Return:
-                  BinE:
-                    op: Add
-                    l:
-                      Int: 10
-                    r:
-                      FuncApp:
-                        alias: h
-                        aps: []
+                  VarApp: x
+        "###);
+    }
+
+    #[test]
+    fn addr_of_rvalue() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/addr_of_rvalue.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens);
+        assert!(tree.is_err())
+    }
+
+    #[test]
+    fn array() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/array.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree, @r###"
+        ---
         - FuncDef:
-            alias: f
+            alias: main
             typ: Int
             fps: []
             body:
+              - Decl:
+                  alias: a
+                  typ:
+                    Arr:
+                      - Int
+                      - 10
+              - Store:
+                  lval:
+                    Index:
+                      alias: a
+                      idx:
+                        Int: 0
+                  expr:
+                    Int: 1
+              - For:
+                  asnmt:
+                    Asnmt:
+                      alias: i
+                      typ: Int
+                      expr:
+                        Int: 1
+                  cond:
+                    RelE:
+                      op: Lt
+                      l:
+                        VarApp: i
+                      r:
+                        Int: 10
+                  update:
+                    Update:
+                      alias: i
+                      expr:
+                        BinE:
+                          op: Add
+                          l:
+                            VarApp: i
+                          r:
+                            Int: 1
+                  body:
+                    - Store:
+                        lval:
+                          Index:
+                            alias: a
+                            idx:
+                              VarApp: i
+                        expr:
+                          BinE:
+                            op: Add
+                            l:
+                              Index:
+                                alias: a
+                                idx:
+                                  BinE:
+                                    op: Sub
+                                    l:
+                                      VarApp: i
+                                    r:
+                                      Int: 1
+                            r:
+                              Int: 1
+              - Asnmt:
+                  alias: p
+                  typ:
+                    Ptr: Int
+                  expr:
+                    AddrOf:
+                      Index:
+                        alias: a
+                        idx:
+                          Int: 4
+              - Store:
+                  lval:
+                    Deref:
+                      VarApp: p
+                  expr:
+                    BinE:
+                      op: Mult
+                      l:
+                        Deref:
+                          VarApp: p
+                      r:
+                        Int: 10
               - Return:
                   BinE:
                     op: Add
                     l:
-                      Int: 9
+                      Index:
+                        alias: a
+                        idx:
+                          Int: 9
                     r:
-                      FuncApp:
-                        alias: g
-                        aps: []
-        - FuncDef:
-            alias: main
-            typ: Int
-            fps: []
-            body:
-              - Return:
-                  FuncApp:
-                    alias: f
-                    aps: []
+                      Index:
+                        alias: a
+                        idx:
+                          Int: 4
         "###);
     }
 
     #[test]
-    fn formal_param() {
-        let chars = fs::read(format!("{TEST_DIR}/formal_param.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn structs() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/structs.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
-        - FuncDef:
-            alias: f
-            typ: Int
-            fps:
+        - StructDef:
+            alias: point
+            fields:
               - - x
                 - Int
-            body:
-              - Return:
-                  BinE:
-                    op: Add
-                    l:
-                      VarApp: x
-                    r:
-                      Int: 10
+              - - y
+                - Int
+        - StructDef:
+            alias: rect
+            fields:
+              - - lo
+                - Struct: point
+              - - hi
+                - Struct: point
         - FuncDef:
             alias: main
             typ: Int
             fps: []
             body:
+              - Decl:
+                  alias: r
+                  typ:
+                    Struct: rect
+              - Store:
+                  lval:
+                    Member:
+                      base:
+                        Member:
+                          base:
+                            VarApp: r
+                          field: lo
+                      field: x
+                  expr:
+                    Int: 1
+              - Store:
+                  lval:
+                    Member:
+                      base:
+                        Member:
+                          base:
+                            VarApp: r
+                          field: lo
+                      field: y
+                  expr:
+                    Int: 2
+              - Store:
+                  lval:
+                    Member:
+                      base:
+                        Member:
+                          base:
+                            VarApp: r
+                          field: hi
+                      field: x
+                  expr:
+                    Int: 4
+              - Store:
+                  lval:
+                    Member:
+                      base:
+                        Member:
+                          base:
+                            VarApp: r
+                          field: hi
+                      field: y
+                  expr:
+                    Int: 7
+              - Asnmt:
+                  alias: p
+                  typ:
+                    Ptr: Int
+                  expr:
+                    AddrOf:
+                      Member:
+                        base:
+                          Member:
+                            base:
+                              VarApp: r
+                            field: hi
+                        field: y
+              - Store:
+                  lval:
+                    Deref:
+                      VarApp: p
+                  expr:
+                    BinE:
+                      op: Add
+                      l:
+                        Deref:
+                          VarApp: p
+                      r:
+                        Int: 3
               - Return:
-                  FuncApp:
-                    alias: f
-                    aps:
-                      - Int: 9
+                  BinE:
+                    op: Sub
+                    l:
+                      BinE:
+                        op: Sub
+                        l:
+                          BinE:
+                            op: Mult
+                            l:
+                              Member:
+                                base:
+                                  Member:
+                                    base:
+                                      VarApp: r
+                                    field: hi
+                                field: x
+                            r:
+                              Member:
+                                base:
+                                  Member:
+                                    base:
+                                      VarApp: r
+                                    field: hi
+                                field: y
+                        r:
+                          Member:
+                            base:
+                              Member:
+                                base:
+                                  VarApp: r
+                                field: lo
+                            field: x
+                    r:
+                      Member:
+                        base:
+                          Member:
+                            base:
+                              VarApp: r
+                            field: lo
+                        field: y
         "###);
     }
 
     #[test]
-    fn formal_param_multi() {
-        let chars = fs::read(format!("{TEST_DIR}/formal_param_multi.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn arrow() {
+        let parse = |src| super::parse_prg(&lexer::lex(src).unwrap());
+        let arrow = parse("int f(struct s *p) { p->n->x = p->x; return 0; }").unwrap();
+        let star = parse("int f(struct s *p) { (*(*p).n).x = (*p).x; return 0; }").unwrap();
+        assert!(crate::same_tree(&arrow, &star));
+
+        let err = parse("int f(int *p) { (p + 1) = 2; return 0; }").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "lvalue required as left operand of assignment"
+        );
+    }
+
+    #[test]
+    fn tags() {
+        let parse = |src| super::parse_prg(&lexer::lex(src).unwrap());
+        let tree = parse("union u { int i; }; int main() { union u v; return 0; }").unwrap();
+        let printed = crate::pretty::prg(&tree);
+        assert!(printed.contains("union u v;"), "{printed}");
+
+        // a tag's uses agree with its definition, whichever keyword it was
+        for src in [
+            "union u { int i; }; int main() { struct u v; return 0; }",
+            "struct s { int i; }; int f(union s *p) { return 0; }",
+            "struct s { int i; }; union s { int i; };",
+        ] {
+            let err = parse(src).unwrap_err();
+            assert!(
+                err.to_string().ends_with("defined as wrong kind of tag"),
+                "{src}"
+            );
+        }
+    }
+
+    #[test]
+    fn typedefs() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/typedefs.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
-        - FuncDef:
-            alias: f
-            typ: Int
-            fps:
+        - StructDef:
+            alias: point
+            fields:
               - - x
                 - Int
               - - y
                 - Int
+        - FuncDef:
+            alias: twice
+            typ: Int
+            fps:
+              - - n
+                - Int
             body:
               - Return:
                   BinE:
                     op: Add
                     l:
-                      VarApp: x
+                      VarApp: n
                     r:
-                      VarApp: y
+                      VarApp: n
         - FuncDef:
             alias: main
             typ: Int
             fps: []
             body:
+              - Decl:
+                  alias: p
+                  typ:
+                    Struct: point
+              - Store:
+                  lval:
+                    Member:
+                      base:
+                        VarApp: p
+                      field: x
+                  expr:
+                    Int: 3
+              - Store:
+                  lval:
+                    Member:
+                      base:
+                        VarApp: p
+                      field: y
+                  expr:
+                    FuncApp:
+                      alias: twice
+                      aps:
+                        - Int: 4
+              - Asnmt:
+                  alias: s
+                  typ:
+                    Ptr: Char
+                  expr:
+                    Str: a
+              - Asnmt:
+                  alias: sum
+                  typ: Int
+                  expr:
+                    BinE:
+                      op: Add
+                      l:
+                        Member:
+                          base:
+                            VarApp: p
+                          field: x
+                      r:
+                        Member:
+                          base:
+                            VarApp: p
+                          field: y
               - Return:
-                  FuncApp:
-                    alias: f
-                    aps:
-                      - Int: 9
-                      - Int: 10
+                  BinE:
+                    op: Add
+                    l:
+                      VarApp: sum
+                    r:
+                      Deref:
+                        VarApp: s
         "###);
     }
 
     #[test]
-    fn asnmt() {
-        let chars = fs::read(format!("{TEST_DIR}/assignment.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+    fn enums() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/enums.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = super::parse_prg(&tokens).unwrap();
         insta::assert_yaml_snapshot!(tree, @r###"
         ---
+        - EnumDef:
+            alias: Color
+            enumerators:
+              - - RED
+                - 0
+              - - GREEN
+                - 5
+              - - BLUE
+                - 6
+        - EnumDef:
+            alias: ~
+            enumerators:
+              - - LO
+                - -1
+              - - HI
+                - 12
         - FuncDef:
             alias: main
             typ: Int
             fps: []
             body:
+              - Asnmt:
+                  alias: c
+                  typ: Int
+                  expr:
+                    Int: 6
               - Asnmt:
                   alias: x
                   typ: Int
                   expr:
-                    Int: 9
+                    BinE:
+                      op: Add
+                      l:
+                        BinE:
+                          op: Add
+                          l:
+                            VarApp: c
+                          r:
+                            BinE:
+                              op: Mult
+                              l:
+                                Int: 5
+                              r:
+                                Int: 12
+                      r:
+                        Int: -1
               - Return:
-                  VarApp: x
+                  BinE:
+                    op: Add
+                    l:
+                      VarApp: x
+                    r:
+                      Int: 0
+        "###);
+    }
+
+    #[test]
+    fn printf() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/printf.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        insta::assert_yaml_snapshot!(tree[0], @r###"
+        ---
+        FuncDecl:
+          alias: printf
+          typ: Int
+          fps:
+            - Ptr: Char
+          variadic: true
         "###);
     }
 
-    // #[test]
-    // fn asnmt_update() {
-    //     let chars = fs::read(format!("{TEST_DIR}/asnmt_update.c"))
-    //         .expect("file dne")
-    //         .iter()
-    //         .map(|b| *b as char)
-    //         .collect::<Vec<_>>();
-
-    //     let tokens = lexer::lex(&chars).unwrap();
-    //     let tree = super::parse_prg(&tokens).unwrap();
-    //     insta::assert_yaml_snapshot!(tree, @r###"
-    //     ---
-    //     main_function:
-    //       stmts:
-    //         - Asnmt:
-    //             CreateBind:
-    //               id: n
-    //               expr:
-    //                 Int: 0
-    //         - Asnmt:
-    //             UpdateBind:
-    //               id: n
-    //               op: Add
-    //               expr:
-    //                 Int: 10
-    //         - Return:
-    //             Var: n
-    //     "###);
-    // }
+    #[test]
+    fn spans() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/undeclared.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = super::parse_prg(&tokens).unwrap();
+        let text = |span: Span| {
+            let start = span.offset(&src);
+            src[start..start + span.len].to_owned()
+        };
+        let SDef::FuncDef(main) = &tree[0].node else {
+            panic!("main is a function")
+        };
+        let SStmt::While { cond, body, .. } = &main.body[1].node else {
+            panic!("a while loop")
+        };
+        let spans = [tree[0].span, main.body[0].span, cond.span, body[0].span]
+            .map(text)
+            .join("\n---\n");
+        insta::assert_snapshot!(spans, @r###"
+        int main() {
+            int x = 1;
+            while (x < 0) {
+                x = y;
+            }
+            return x;
+        }
+        ---
+        int x = 1;
+        ---
+        x < 0
+        ---
+        x = y;
+        "###);
+    }
 }
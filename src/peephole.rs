@@ -0,0 +1,187 @@
+// a peephole pass over the emitted assembly, at -O1 and up. it looks
+// through a window of a few instructions at a time, and rewrites what the
+// allocators leave behind when they emit quad by quad:
+//   - moves from a register to itself
+//   - a push followed by a pop, which is a move between the two registers
+//   - jumps to the next instruction, over nothing but labels
+
+use crate::asm::{imm, mem, op, reg, AsmProgram, Insn, Operand};
+
+pub fn optimize(prg: AsmProgram) -> AsmProgram {
+    let mut insns = prg.insns;
+    loop {
+        let next = pass(&insns);
+        if next.len() == insns.len() {
+            return AsmProgram { insns: next };
+        }
+        insns = next;
+    }
+}
+
+fn pass(insns: &[Insn]) -> Vec<Insn> {
+    let mut out = vec![];
+    let mut i = 0;
+    while i < insns.len() {
+        match &insns[i] {
+            Insn::Op(m, args, _) if copies_self(m, args) => i += 1,
+            Insn::Op(m, args, _)
+                if m == "j" && matches!(args.as_slice(), [l] if falls_to(&insns[i + 1..], l)) =>
+            {
+                i += 1
+            }
+            _ => match pushed_popped(&insns[i..]) {
+                Some((r, s)) => {
+                    if r != s {
+                        out.push(op("mv", [s, r]).note("push, pop"));
+                    }
+                    i += 4;
+                }
+                None => {
+                    out.push(insns[i].clone());
+                    i += 1;
+                }
+            },
+        }
+    }
+    out
+}
+
+fn copies_self(m: &str, args: &[Operand]) -> bool {
+    match (m, args) {
+        ("mv", [d, s]) => d == s,
+        ("addi", [d, s, k]) => d == s && *k == imm(0),
+        _ => false,
+    }
+}
+
+// the registers pushed from and popped to, when insns start with a push
+// and then a pop
+fn pushed_popped(insns: &[Insn]) -> Option<(Operand, Operand)> {
+    let ops = insns
+        .get(..4)?
+        .iter()
+        .map(|insn| match insn {
+            Insn::Op(m, args, _) => Some((m.as_str(), args.as_slice())),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let sp = || reg("sp");
+    match ops.as_slice() {
+        [("addi", grow), (sw, [r, push]), (lw, [s, pop]), ("addi", shrink)]
+            if matches!((*sw, *lw), ("sw", "lw") | ("sd", "ld"))
+                && *grow == [sp(), sp(), imm(-8)]
+                && *push == mem(0, "sp")
+                && *pop == mem(0, "sp")
+                && *shrink == [sp(), sp(), imm(8)] =>
+        {
+            Some((r.clone(), s.clone()))
+        }
+        _ => None,
+    }
+}
+
+// whether l labels the next instruction
+fn falls_to(insns: &[Insn], l: &Operand) -> bool {
+    insns
+        .iter()
+        .map_while(|insn| match insn {
+            Insn::Label(label) => Some(label),
+            _ => None,
+        })
+        .any(|label| *l == Operand::Sym(label.clone()))
+}
+
+#[cfg(test)]
+mod test_peephole {
+    use crate::asm::{imm, mem, op, reg, sym, AsmProgram, Insn};
+    use crate::{allocator, lexer, parser_ast, selector, translator, IntModel, OptLevel};
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/control";
+
+    #[test]
+    fn while_false() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/while_false.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = allocator::allocate(&abs_as, OptLevel::O0, &IntModel::default());
+        insta::assert_yaml_snapshot!(super::optimize(assembly).render(), @r###"
+        ---
+        - ".text"
+        - ".globl main"
+        - ".section .text"
+        - "main:"
+        - "la t0, __picoc_sym_main"
+        - "la t1, __picoc_func"
+        - "sw t0, 0(t1) # mark main"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "sw fp, 8(sp)"
+        - "addi fp, sp, 16"
+        - ".L0:"
+        - "addi t2, zero, 0"
+        - "mv t0, t2 # push, pop"
+        - "beq t0, zero, .L2"
+        - ".L1:"
+        - "addi t2, zero, 9"
+        - "mv t0, t2 # push, pop"
+        - "addi a0, t0, 0"
+        - j .L3
+        - j .L0
+        - ".L2:"
+        - "addi t2, zero, 10"
+        - "mv t0, t2 # push, pop"
+        - "addi a0, t0, 0"
+        - ".L3:"
+        - "lw ra, 12(sp)"
+        - "lw fp, 8(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        "###);
+    }
+
+    #[test]
+    fn windows() {
+        let push = [
+            op("addi", [reg("sp"), reg("sp"), imm(-8)]).note("grow stack"),
+            op("sw", [reg("t2"), mem(0, "sp")]).note("push t2 ->"),
+        ];
+        let insns = [
+            vec![
+                op("mv", [reg("t0"), reg("t0")]),
+                op("addi", [reg("s1"), reg("s1"), imm(0)]).note("copy"),
+            ],
+            push.to_vec(),
+            vec![
+                op("lw", [reg("t2"), mem(0, "sp")]).note("t2 <- pop"),
+                op("addi", [reg("sp"), reg("sp"), imm(8)]).note("shrink stack"),
+            ],
+            push.to_vec(),
+            vec![
+                op("sw", [reg("t2"), mem(0, "sp")]),
+                op("j", [sym(".L1")]),
+                Insn::Label(".L0".to_owned()),
+                Insn::Label(".L1".to_owned()),
+                op("j", [sym(".L0")]),
+                op("ret", []),
+            ],
+        ]
+        .concat();
+        let prg = super::optimize(AsmProgram { insns });
+        assert_eq!(
+            prg.render(),
+            [
+                "addi sp, sp, -8 # grow stack",
+                "sw t2, 0(sp) # push t2 ->",
+                "sw t2, 0(sp)",
+                ".L0:",
+                ".L1:",
+                "j .L0",
+                "ret",
+            ]
+        );
+    }
+}
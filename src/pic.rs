@@ -0,0 +1,77 @@
+// -fPIC rewrites compiled assembly to be linked into a shared object.
+// under .option pic the assembler loads what la names from the got, so a
+// global is found wherever the object defining it is loaded, and another
+// can preempt it. what's local to the unit, string literals, statics and
+// the runtime's own symbols, is addressed from the pc with lla instead.
+// jump tables hold offsets, and the assembler takes call through the plt,
+// so nothing in .text needs relocating at load time
+
+use std::collections::HashSet;
+
+pub fn relocate(assembly: &[String]) -> Vec<String> {
+    let lines = assembly.iter().flat_map(|s| s.lines()).collect::<Vec<_>>();
+    let globls = lines
+        .iter()
+        .filter_map(|l| l.strip_prefix(".globl "))
+        .map(str::trim)
+        .collect::<HashSet<_>>();
+    let locals = lines
+        .iter()
+        .filter_map(|l| l.split_once(':'))
+        .map(|(l, _)| l.trim())
+        .filter(|l| !l.is_empty() && !l.contains(char::is_whitespace) && !globls.contains(l))
+        .collect::<HashSet<_>>();
+
+    let rewrite = |l: &str| match l.strip_prefix("la ") {
+        Some(operands) => {
+            let target = operands.split('#').next().unwrap_or("");
+            match target.split_once(',') {
+                Some((_, s)) if locals.contains(s.trim()) => format!("l{l}"),
+                _ => l.to_owned(),
+            }
+        }
+        None => l.to_owned(),
+    };
+    [".option pic".to_owned()]
+        .into_iter()
+        .chain(
+            assembly
+                .iter()
+                .map(|s| s.lines().map(rewrite).collect::<Vec<_>>().join("\n")),
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod test_pic {
+    use crate::{engine, lexer, parser_ast, typer, IntModel, OptLevel};
+    use std::fs;
+
+    #[test]
+    fn globals() {
+        let path = "tests/fixtures/snap/shared/bindings/globals.c";
+        let src = fs::read_to_string(path).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let (assembly, _) = engine::compile(
+            &src_tree,
+            OptLevel::O0,
+            &IntModel::default(),
+            &src,
+            false,
+            true,
+        );
+        let assembly = assembly.iter().flat_map(|s| s.lines()).collect::<Vec<_>>();
+        assert_eq!(assembly[0], ".option pic");
+
+        // the runtime's symbols are local, and the program's globals aren't
+        let las = assembly
+            .iter()
+            .filter(|l| l.starts_with("la ") || l.starts_with("lla "))
+            .collect::<Vec<_>>();
+        assert!(las.iter().any(|l| l.starts_with("lla t1, __picoc_func")));
+        assert!(las.iter().any(|l| l.starts_with("la ")));
+    }
+}
@@ -0,0 +1,923 @@
+use crate::lexer::{self, Token, TT};
+use crate::source::{self, SourceMap};
+use crate::{DataModel, IntModel};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// a c89 preprocessor, from source text to source text, run before the
+// lexer. directive lines and the lines a conditional skips are left blank,
+// so a file's lines stay together, and the source map says where each
+// stretch of them came from
+// todo: __LINE__
+// todo: an expansion isn't rescanned along with the text after it, so an
+//       object-like macro can't expand to a function-like macro's name
+
+// include_paths are searched in order for <...>, and for "..." after the
+// includer's own directory. defines are object-like macros, as with -D.
+// model is what <limits.h>'s limits are for, when no include path has one
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    pub include_paths: Vec<PathBuf>,
+    pub defines: Vec<(String, String)>,
+    pub model: IntModel,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("{}:{line}: {msg}", path.display())]
+pub struct PreprocessError {
+    pub path: PathBuf,
+    pub line: usize,
+    pub msg: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Macro {
+    Object(String),
+    Function { params: Vec<String>, body: String },
+}
+
+// a conditional's groups: whether the one being read is kept, whether one
+// already was, and whether the conditional is inside a skipped group itself
+struct Cond {
+    active: bool,
+    taken: bool,
+    skipped: bool,
+    els: bool,
+}
+
+// marks an identifier that's not to be expanded, as a macro's name inside
+// its own expansion
+const BLUE: char = '\u{1}';
+
+// includes nest at most this deep, which stops a file including itself
+const MAX_DEPTH: usize = 64;
+
+struct Preprocessor<'a> {
+    opts: &'a Options,
+    macros: HashMap<String, Macro>,
+    depth: usize,
+    out: String,
+    // the line of out being written
+    line: usize,
+    map: SourceMap,
+}
+
+pub fn preprocess(
+    src: &str,
+    path: &Path,
+    opts: &Options,
+) -> Result<(String, SourceMap), io::Error> {
+    let mut macros = HashMap::from([("__STDC__".to_owned(), Macro::Object("1".to_owned()))]);
+    for (name, body) in &opts.defines {
+        macros.insert(name.to_owned(), Macro::Object(body.to_owned()));
+    }
+
+    let mut pp = Preprocessor {
+        opts,
+        macros,
+        depth: 0,
+        out: String::new(),
+        line: 1,
+        map: SourceMap::new(&path.display().to_string()),
+    };
+    pp.file(src, path)?;
+    Ok((pp.out, pp.map))
+}
+
+impl Preprocessor<'_> {
+    fn emit(&mut self, text: &str) {
+        self.out.push_str(text);
+        self.line += text.matches('\n').count();
+    }
+
+    // errors are at the line in path, whatever a #line says
+    fn file(&mut self, src: &str, path: &Path) -> Result<(), io::Error> {
+        let at = |line: usize| {
+            move |msg: String| {
+                io::Error::other(PreprocessError {
+                    path: path.to_owned(),
+                    line,
+                    msg,
+                })
+            }
+        };
+        // the file and line it's known as, which a #line changes, are
+        // line + delta in known
+        let (mut known, mut delta) = (path.display().to_string(), 0);
+        self.map.push(self.line, &known, 1);
+        let file = Macro::Object(format!("{known:?}"));
+        let includer = self.macros.insert("__FILE__".to_owned(), file);
+
+        let mut text = String::new();
+        let mut conds: Vec<Cond> = vec![];
+        // text is the lines from start up to the next directive
+        let (mut line, mut start) = (1, 1);
+        for (logical, n) in lines(&blank_comments(src)) {
+            let active = conds.last().is_none_or(|c| c.active);
+            let Some(directive) = logical.trim_start().strip_prefix('#') else {
+                if active {
+                    text.push_str(&logical);
+                }
+                text.push_str(&"\n".repeat(n));
+                line += n;
+                continue;
+            };
+
+            // text is expanded up to each directive, since it may change the macros
+            let expanded = self.expand_all(&text);
+            self.emit(&expanded.map_err(|(n, msg)| at(start + n)(msg))?);
+            text.clear();
+            start = line + n;
+
+            let directive = directive.trim();
+            let (name, rest) = directive.split_at(ident_len(directive));
+            let rest = rest.trim();
+            match (name, active) {
+                ("ifdef" | "ifndef", _) => {
+                    let defined = self.macros.contains_key(ident(rest).map_err(at(line))?);
+                    let cond = active && (defined == (name == "ifdef"));
+                    conds.push(Cond {
+                        active: cond,
+                        taken: cond,
+                        skipped: !active,
+                        els: false,
+                    });
+                }
+                ("if", _) => {
+                    let cond = active && self.condition(rest).map_err(at(line))?;
+                    conds.push(Cond {
+                        active: cond,
+                        taken: cond,
+                        skipped: !active,
+                        els: false,
+                    });
+                }
+                ("elif", _) => {
+                    let Some(c) = conds.last() else {
+                        return Err(at(line)("#elif without #if".to_owned()));
+                    };
+                    if c.els {
+                        return Err(at(line)("#elif after #else".to_owned()));
+                    }
+                    let cond = !c.skipped && !c.taken && self.condition(rest).map_err(at(line))?;
+                    let c = conds.last_mut().expect("checked above");
+                    c.active = cond;
+                    c.taken |= cond;
+                }
+                ("else", _) => match conds.last_mut() {
+                    Some(c) if !c.els => {
+                        c.active = !c.skipped && !c.taken;
+                        c.taken = true;
+                        c.els = true;
+                    }
+                    Some(_) => return Err(at(line)("#else after #else".to_owned())),
+                    None => return Err(at(line)("#else without #if".to_owned())),
+                },
+                ("endif", _) => {
+                    if conds.pop().is_none() {
+                        return Err(at(line)("#endif without #if".to_owned()));
+                    }
+                }
+                (_, false) => {} // skipped groups only nest conditionals
+                ("define", true) => self.define(rest).map_err(at(line))?,
+                ("undef", true) => {
+                    self.macros.remove(ident(rest).map_err(at(line))?);
+                }
+                ("include", true) => {
+                    let (found, included) = self.include(rest, path).map_err(at(line))?;
+                    self.depth += 1;
+                    if self.depth > MAX_DEPTH {
+                        return Err(at(line)("#include nested too deeply".to_owned()));
+                    }
+                    self.file(&included, &found)?;
+                    self.depth -= 1;
+                    if !self.out.is_empty() && !self.out.ends_with('\n') {
+                        self.emit("\n");
+                    }
+                    self.map
+                        .push(self.line, &known, line.saturating_add_signed(delta));
+                }
+                ("line", true) => {
+                    let (to, file) = self.line_directive(rest).map_err(at(line))?;
+                    delta = to as isize - (line + n) as isize;
+                    if let Some(file) = file {
+                        let m = Macro::Object(format!("{file:?}"));
+                        self.macros.insert("__FILE__".to_owned(), m);
+                        known = file;
+                    }
+                    self.map.push(self.line + n, &known, to);
+                }
+                ("error", true) => return Err(at(line)(format!("#error {rest}"))),
+                ("pragma" | "", true) => {} // no pragmas are known, and a lone # is a null directive
+                (name, true) => return Err(at(line)(format!("unknown directive: #{name}"))),
+            }
+            self.emit(&"\n".repeat(n));
+            line += n;
+        }
+        if !conds.is_empty() {
+            return Err(at(line)("unterminated conditional".to_owned()));
+        }
+        let expanded = self.expand_all(&text);
+        self.emit(&expanded.map_err(|(n, msg)| at(start + n)(msg))?);
+
+        match includer {
+            Some(file) => self.macros.insert("__FILE__".to_owned(), file),
+            None => self.macros.remove("__FILE__"),
+        };
+        Ok(())
+    }
+
+    // #line 12 or #line 12 "file", either maybe from a macro. the line
+    // after the directive is line 12
+    fn line_directive(&self, rest: &str) -> Result<(usize, Option<String>), String> {
+        let rest = self.expand_all(rest).map_err(|(_, msg)| msg)?;
+        let (num, file) = rest.trim().split_once(' ').unwrap_or((rest.trim(), ""));
+        let digits = num.bytes().all(|b| b.is_ascii_digit());
+        let num = match num.parse::<usize>() {
+            Ok(n) if n > 0 && digits => n,
+            _ => return Err(format!("expected a line number after #line, got: {num}")),
+        };
+        let file = match file.trim() {
+            "" => None,
+            file => match file.strip_prefix('"').and_then(|f| f.strip_suffix('"')) {
+                Some(f) => Some(f.to_owned()),
+                None => return Err(format!("expected \"file\" after #line {num}, got: {file}")),
+            },
+        };
+        Ok((num, file))
+    }
+
+    // #define NAME body, or #define NAME(a, b) body with the ( right after
+    // the name. a macro can only be defined again the same way
+    fn define(&mut self, rest: &str) -> Result<(), String> {
+        let name = ident(rest)?;
+        let rest = &rest[name.len()..];
+        let m = match rest.strip_prefix('(') {
+            Some(rest) => {
+                let (params, body) = rest
+                    .split_once(')')
+                    .ok_or(format!("missing ) in parameters of {name}"))?;
+                let params = match params.trim() {
+                    "" => vec![],
+                    params => params
+                        .split(',')
+                        .map(|p| ident(p.trim()).map(str::to_owned))
+                        .collect::<Result<_, _>>()?,
+                };
+                Macro::Function {
+                    params,
+                    body: normalize(body),
+                }
+            }
+            None => Macro::Object(normalize(rest)),
+        };
+
+        match self.macros.get(name) {
+            Some(old) if *old != m => Err(format!("{name} redefined differently")),
+            _ => {
+                self.macros.insert(name.to_owned(), m);
+                Ok(())
+            }
+        }
+    }
+
+    // "file" is looked for next to the includer first. either form may come
+    // from a macro
+    fn include(&self, rest: &str, includer: &Path) -> Result<(PathBuf, String), String> {
+        let spec = match rest.starts_with(['"', '<']) {
+            true => rest.to_owned(),
+            false => self
+                .expand_all(rest)
+                .map_err(|(_, msg)| msg)?
+                .trim()
+                .to_owned(),
+        };
+        let (name, local) = match (spec.strip_prefix('"'), spec.strip_prefix('<')) {
+            (Some(s), _) => (s.strip_suffix('"'), true),
+            (_, Some(s)) => (s.strip_suffix('>'), false),
+            _ => (None, false),
+        };
+        let name = name.ok_or(format!("expected \"file\" or <file>, got: {spec}"))?;
+
+        let here = includer.parent().map(Path::to_path_buf);
+        let dirs = local.then_some(here).flatten().into_iter();
+        let found = dirs
+            .chain(self.opts.include_paths.iter().cloned())
+            .map(|dir| dir.join(name))
+            .find(|path| path.is_file());
+        let found = match found {
+            Some(found) => found,
+            None if !local && name == "limits.h" => {
+                return Ok((PathBuf::from("<limits.h>"), limits(&self.opts.model)));
+            }
+            None => return Err(format!("{name} not found")),
+        };
+        let src = fs::read(&found)
+            .and_then(|bytes| source::decode(&bytes))
+            .map_err(|e| format!("{name}: {e}"))?;
+
+        Ok((found, src))
+    }
+
+    // #if and #elif take integer constant expressions. defined X is whether
+    // X is a macro, and any identifier left after expansion is 0
+    fn condition(&self, expr: &str) -> Result<bool, String> {
+        let ps = pieces(expr);
+        let solid = |from: usize| (from..ps.len()).filter(|&j| !blank(ps[j]));
+        let mut text = String::new();
+        let mut i = 0;
+        while i < ps.len() {
+            if ps[i] != "defined" {
+                text.push_str(ps[i]);
+                i += 1;
+                continue;
+            }
+            let next = solid(i + 1).take(3).collect::<Vec<_>>();
+            let (name, end) = match next.iter().map(|&j| ps[j]).collect::<Vec<_>>()[..] {
+                ["(", name, ")", ..] => (name, next[2]),
+                [name, ..] => (name, next[0]),
+                [] => ("", i),
+            };
+            if !is_ident(name) {
+                return Err("expected a macro name after defined".to_owned());
+            }
+            let defined = self.macros.contains_key(name);
+            text.push_str(if defined { "1" } else { "0" });
+            i = end + 1;
+        }
+
+        let text = self.expand_all(&text).map_err(|(_, msg)| msg)?;
+        let text = pieces(&text)
+            .into_iter()
+            .map(|p| if is_ident(p) { "0" } else { p })
+            .collect::<String>();
+        let tokens = lexer::lex(&text).map_err(|errs| lexer::io_error(errs).to_string())?;
+        match eval(&tokens, 0)? {
+            (val, []) => Ok(val != 0),
+            (_, [t, ..]) => Err(format!("unexpected {} in #if", t.lexeme)),
+        }
+    }
+
+    fn expand_all(&self, text: &str) -> Result<String, (usize, String)> {
+        Ok(self.expand(text, &HashSet::new())?.replace(BLUE, ""))
+    }
+
+    // expands the macros in text. hidden are those being expanded already,
+    // which aren't again inside themselves, and never will be: they're
+    // marked, so rescanning the text they end up in passes over them. an
+    // error comes with how many lines into text the macro it's in was
+    fn expand(&self, text: &str, hidden: &HashSet<String>) -> Result<String, (usize, String)> {
+        let ps = pieces(text);
+        let mut out = String::new();
+        let (mut i, mut nl) = (0, 0);
+        while i < ps.len() {
+            let p = ps[i];
+            i += 1;
+            nl += p.matches('\n').count();
+            let at = |(_, msg)| (nl, msg);
+            let m = match self.macros.get(p) {
+                Some(_) if hidden.contains(p) => {
+                    out.push(BLUE);
+                    out.push_str(p);
+                    continue;
+                }
+                Some(m) => m,
+                None => {
+                    out.push_str(p);
+                    continue;
+                }
+            };
+            let mut inner = hidden.clone();
+            inner.insert(p.to_owned());
+
+            match m {
+                Macro::Object(body) => {
+                    let body = paste(
+                        &pieces(body)
+                            .into_iter()
+                            .map(str::to_owned)
+                            .collect::<Vec<_>>(),
+                    );
+                    out.push_str(&self.expand(&body, &inner).map_err(at)?)
+                }
+                Macro::Function { params, body } => {
+                    // a function-like macro's name alone isn't an invocation
+                    let open = i + ps[i..].iter().take_while(|p| blank(p)).count();
+                    if ps.get(open) != Some(&"(") {
+                        out.push_str(p);
+                        continue;
+                    }
+                    let (args, close) =
+                        args(&ps, open + 1).ok_or((nl, format!("unterminated call to {p}")))?;
+                    let args = match (params.len(), args.as_slice()) {
+                        (0, [arg]) if arg.trim().is_empty() => vec![],
+                        _ => args,
+                    };
+                    if args.len() != params.len() {
+                        let msg =
+                            format!("{p} takes {} arguments, given {}", params.len(), args.len());
+                        return Err((nl, msg));
+                    }
+
+                    // arguments are expanded on their own, before they're substituted
+                    let raw = args.iter().map(|a| normalize(a)).collect::<Vec<_>>();
+                    let expanded = raw
+                        .iter()
+                        .map(|a| self.expand(a, hidden))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(at)?;
+                    let body = substitute(params, body, &raw, &expanded);
+                    out.push_str(&self.expand(&body, &inner).map_err(at)?);
+
+                    // an invocation may span lines, which are put back after it
+                    let newlines = ps[i..close]
+                        .iter()
+                        .map(|p| p.matches('\n').count())
+                        .sum::<usize>();
+                    out.push_str(&"\n".repeat(newlines));
+                    nl += newlines;
+                    i = close + 1;
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+// <limits.h>, for the int width and data model compiled for. chars are
+// signed, and an unsigned short only promotes to int when int is wider
+// todo: long's limits under lp64, which don't fit the 32 bit literals
+fn limits(model: &IntModel) -> String {
+    let u = if model.int_width == 16 { "U" } else { "" };
+    let mut limits = vec![
+        "#define CHAR_BIT 8".to_owned(),
+        "#define SCHAR_MIN (-127 - 1)".to_owned(),
+        "#define SCHAR_MAX 127".to_owned(),
+        "#define UCHAR_MAX 255".to_owned(),
+        "#define CHAR_MIN SCHAR_MIN".to_owned(),
+        "#define CHAR_MAX SCHAR_MAX".to_owned(),
+        "#define SHRT_MIN (-32767 - 1)".to_owned(),
+        "#define SHRT_MAX 32767".to_owned(),
+        format!("#define USHRT_MAX 65535{u}"),
+        format!("#define INT_MIN (-{} - 1)", model.int_max()),
+        format!("#define INT_MAX {}", model.int_max()),
+        format!("#define UINT_MAX {}U", model.unsigned(-1)),
+    ];
+    if model.data_model == DataModel::ILP32 {
+        limits.extend([
+            "#define LONG_MIN (-2147483647L - 1)".to_owned(),
+            "#define LONG_MAX 2147483647L".to_owned(),
+            "#define ULONG_MAX 4294967295UL".to_owned(),
+        ]);
+    }
+    limits.join("\n") + "\n"
+}
+
+// the arguments of an invocation whose ( is just before ps[i], split at the
+// commas outside nested parens, and the index of its )
+fn args(ps: &[&str], mut i: usize) -> Option<(Vec<String>, usize)> {
+    let (mut args, mut arg, mut depth) = (vec![], String::new(), 0);
+    loop {
+        match *ps.get(i)? {
+            ")" if depth == 0 => {
+                args.push(arg);
+                return Some((args, i));
+            }
+            "," if depth == 0 => args.push(std::mem::take(&mut arg)),
+            p => {
+                depth += (p == "(") as usize;
+                depth -= (p == ")") as usize;
+                arg.push_str(&p.replace('\n', " "));
+            }
+        }
+        i += 1;
+    }
+}
+
+// the body with each parameter replaced by its argument: #a is the raw
+// argument as a string, next to ## it's raw, and elsewhere it's expanded
+fn substitute(params: &[String], body: &str, raw: &[String], expanded: &[String]) -> String {
+    let ps = pieces(body);
+    let param = |p: &str| params.iter().position(|q| q == p);
+    let solid = |i: usize, step: isize| {
+        let mut j = i.checked_add_signed(step)?;
+        while blank(ps.get(j)?) {
+            j = j.checked_add_signed(step)?;
+        }
+        Some(j)
+    };
+    let pasted = |i| {
+        [-1, 1]
+            .iter()
+            .any(|&s| solid(i, s).is_some_and(|j| ps[j] == "##"))
+    };
+
+    let mut out = vec![];
+    let mut i = 0;
+    while i < ps.len() {
+        let arg = solid(i, 1).and_then(|j| Some((j, param(ps[j])?)));
+        if let ("#", Some((j, n))) = (ps[i], arg) {
+            let s = raw[n].replace('\\', "\\\\").replace('"', "\\\"");
+            out.push(format!("\"{s}\""));
+            i = j + 1;
+            continue;
+        }
+        out.push(match param(ps[i]) {
+            Some(n) if pasted(i) => raw[n].clone(),
+            Some(n) => expanded[n].clone(),
+            None => ps[i].to_owned(),
+        });
+        i += 1;
+    }
+    paste(&out)
+}
+
+// the pieces with the whitespace either side of each ## dropped, joining
+// the two
+fn paste(ps: &[String]) -> String {
+    let mut out = String::new();
+    let mut glue = false;
+    for p in ps {
+        match p.as_str() {
+            "##" => {
+                out.truncate(out.trim_end().len());
+                glue = true;
+            }
+            p if glue && blank(p) => {}
+            p => {
+                out.push_str(p);
+                glue = false;
+            }
+        }
+    }
+    out
+}
+
+// c89's #if, in longs: ?:, then the binary operators by precedence and the
+// unary ones. ts is lexed, so int and char literals are already values
+fn eval(ts: &[Token], min: u8) -> Result<(i64, &[Token]), String> {
+    let (mut l, mut r) = unary(ts)?;
+    while let Some((op, prec)) = r.first().and_then(|t| prec(t.typ).map(|p| (t.typ, p))) {
+        if prec < min {
+            break;
+        }
+        let (v, _r) = eval(&r[1..], prec + 1)?;
+        l = match op {
+            TT::BarBar => (l != 0 || v != 0) as i64,
+            TT::AmpAmp => (l != 0 && v != 0) as i64,
+            TT::Bar => l | v,
+            TT::Caret => l ^ v,
+            TT::Amp => l & v,
+            TT::EqualsEquals => (l == v) as i64,
+            TT::BangEquals => (l != v) as i64,
+            TT::LeftAngleBracket => (l < v) as i64,
+            TT::RightAngleBracket => (l > v) as i64,
+            TT::LeftAngleBracketEquals => (l <= v) as i64,
+            TT::RightAngleBracketEquals => (l >= v) as i64,
+            TT::LeftAngleBracketLeftAngleBracket => l.wrapping_shl(v as u32),
+            TT::RightAngleBracketRightAngleBracket => l.wrapping_shr(v as u32),
+            TT::Plus => l.wrapping_add(v),
+            TT::Minus => l.wrapping_sub(v),
+            TT::Star => l.wrapping_mul(v),
+            TT::Slash | TT::Percent if v == 0 => return Err("division by zero in #if".to_owned()),
+            TT::Slash => l.wrapping_div(v),
+            _ => l.wrapping_rem(v),
+        };
+        r = _r;
+    }
+
+    match r {
+        [q, r @ ..] if q.typ == TT::Question && min == 0 => {
+            let (then, r) = eval(r, 0)?;
+            let r = match r {
+                [c, r @ ..] if c.typ == TT::Colon => r,
+                _ => return Err("expected : in #if".to_owned()),
+            };
+            let (els, r) = eval(r, 0)?;
+            Ok((if l != 0 { then } else { els }, r))
+        }
+        r => Ok((l, r)),
+    }
+}
+
+fn prec(typ: TT) -> Option<u8> {
+    match typ {
+        TT::BarBar => Some(1),
+        TT::AmpAmp => Some(2),
+        TT::Bar => Some(3),
+        TT::Caret => Some(4),
+        TT::Amp => Some(5),
+        TT::EqualsEquals | TT::BangEquals => Some(6),
+        TT::LeftAngleBracket
+        | TT::RightAngleBracket
+        | TT::LeftAngleBracketEquals
+        | TT::RightAngleBracketEquals => Some(7),
+        TT::LeftAngleBracketLeftAngleBracket | TT::RightAngleBracketRightAngleBracket => Some(8),
+        TT::Plus | TT::Minus => Some(9),
+        TT::Star | TT::Slash | TT::Percent => Some(10),
+        _ => None,
+    }
+}
+
+fn unary(ts: &[Token]) -> Result<(i64, &[Token]), String> {
+    match ts {
+        [f, r @ ..] => match f.typ {
+            TT::Minus => unary(r).map(|(v, r)| (v.wrapping_neg(), r)),
+            TT::Plus => unary(r),
+            TT::Bang => unary(r).map(|(v, r)| ((v == 0) as i64, r)),
+            TT::Tilde => unary(r).map(|(v, r)| (!v, r)),
+            TT::PuncLeftParen => match eval(r, 0)? {
+                (v, [c, r @ ..]) if c.typ == TT::PuncRightParen => Ok((v, r)),
+                _ => Err("expected ) in #if".to_owned()),
+            },
+            TT::LiteralInt => Ok((f.int.expect("the lexer reads int literals").val as i64, r)),
+            TT::LiteralChar => Ok((f.lexeme.chars().next().unwrap_or('\0') as i64, r)),
+            _ => Err(format!("unexpected {} in #if", f.lexeme)),
+        },
+        [] => Err("expected an expression in #if".to_owned()),
+    }
+}
+
+// comments are one space, though each of their chars is blanked here so
+// columns don't move. the newlines in them stay. an unterminated one is
+// left for the lexer to report
+fn blank_comments(src: &str) -> String {
+    let mut out = String::with_capacity(src.len());
+    let mut end = 0;
+    for c in lexer::trivia(src) {
+        let open = c.text.starts_with("/*") && (c.text.len() < 4 || !c.text.ends_with("*/"));
+        if open {
+            break;
+        }
+        out.push_str(&src[end..c.span.start]);
+        out.extend(c.text.chars().map(|c| if c == '\n' { c } else { ' ' }));
+        end = c.span.end;
+    }
+    out.push_str(&src[end..]);
+    out
+}
+
+// the logical lines, with a backslash before a newline joining two, and how
+// many physical lines each took
+fn lines(src: &str) -> Vec<(String, usize)> {
+    let mut lines = vec![];
+    let (mut logical, mut n) = (String::new(), 0);
+    for physical in src.split_inclusive('\n') {
+        n += 1;
+        match physical.strip_suffix("\\\n") {
+            Some(l) => logical.push_str(l),
+            None => {
+                logical.push_str(physical.strip_suffix('\n').unwrap_or(physical));
+                lines.push((std::mem::take(&mut logical), n));
+                n = 0;
+            }
+        }
+    }
+    if n > 0 {
+        lines.push((logical, n));
+    }
+
+    // the last line has no newline to put back
+    if let Some(last) = lines.last_mut().filter(|_| !src.ends_with('\n')) {
+        last.1 -= 1;
+    }
+    lines
+}
+
+// text as the lexer would split it, near enough for macros: identifiers,
+// numbers, literals, runs of whitespace and the punctuation between
+fn pieces(text: &str) -> Vec<&str> {
+    let mut ps = vec![];
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let n = match c {
+            c if c.is_whitespace() => rest.len() - rest.trim_start().len(),
+            c if c.is_ascii_alphabetic() || c == '_' => ident_len(rest),
+            BLUE => 1 + ident_len(&rest[1..]),
+            // pp-numbers, so the x in 0x1F isn't taken for an identifier
+            '0'..='9' => rest
+                .char_indices()
+                .find(|(i, c)| {
+                    let sign = matches!(c, '+' | '-') && rest[..*i].ends_with(['e', 'E']);
+                    !(c.is_ascii_alphanumeric() || matches!(c, '_' | '.') || sign)
+                })
+                .map_or(rest.len(), |(i, _)| i),
+            q @ ('"' | '\'') => {
+                let mut i = 1;
+                let bytes = rest.as_bytes();
+                while i < bytes.len() && bytes[i] != q as u8 && bytes[i] != b'\n' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                (i + 1).min(rest.len())
+            }
+            '#' if rest.starts_with("##") => 2,
+            c => c.len_utf8(),
+        };
+        ps.push(&rest[..n]);
+        rest = &rest[n..];
+    }
+    ps
+}
+
+fn ident_len(s: &str) -> usize {
+    match s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        true => s
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(s.len()),
+        false => 0,
+    }
+}
+
+fn is_ident(s: &str) -> bool {
+    !s.is_empty() && ident_len(s) == s.len()
+}
+
+fn ident(s: &str) -> Result<&str, String> {
+    match ident_len(s) {
+        0 => Err(format!("expected a macro name, got: {s:?}")),
+        n => Ok(&s[..n]),
+    }
+}
+
+fn blank(p: &str) -> bool {
+    p.starts_with(char::is_whitespace)
+}
+
+// a single space between pieces, so bodies compare equal whatever their
+// whitespace. string literals are pieces, so theirs is kept
+fn normalize(body: &str) -> String {
+    let ps = pieces(body)
+        .into_iter()
+        .map(|p| if blank(p) { " " } else { p });
+    ps.collect::<String>().trim().to_owned()
+}
+
+#[cfg(test)]
+mod test_preprocessor {
+    use super::Options;
+    use crate::{evaluator, lexer, parser_ast, DataModel, IntModel};
+    use std::fs;
+    use std::path::Path;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/preprocessor";
+
+    fn eval(f: &str, opts: &Options) -> i32 {
+        let path = format!("{TEST_DIR}/{f}");
+        let src = fs::read_to_string(&path).expect("file dne");
+        let (src, _) = super::preprocess(&src, Path::new(&path), opts).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        evaluator::eval_prg(&tree, IntModel::default()).unwrap()
+    }
+
+    #[test]
+    fn macros() {
+        let path = format!("{TEST_DIR}/macros.c");
+        let src = fs::read_to_string(&path).expect("file dne");
+        let (out, _) = super::preprocess(&src, Path::new(&path), &Options::default()).unwrap();
+        // directives are left blank, and the call spanning two lines ends with both
+        assert_eq!(out.lines().count(), src.lines().count());
+        insta::assert_snapshot!(out.trim(), @r###"
+        int main() {
+            int foo = 1;
+
+            int myvar = 10;
+            char *s = "hello \"world\"";
+            int m = myvar > 3 ? myvar : 3
+        ;
+            return m + 2 * 2 + 2 * 2 + foo * 3;
+        }
+        "###);
+
+        assert_eq!(eval("macros.c", &Options::default()), 21);
+    }
+
+    #[test]
+    fn conditionals() {
+        assert_eq!(eval("conditionals.c", &Options::default()), 121);
+    }
+
+    #[test]
+    fn include() {
+        // max.h is included twice, the second time through lib.h, but guarded
+        let opts = Options {
+            include_paths: vec![format!("{TEST_DIR}/sys").into()],
+            ..Options::default()
+        };
+        assert_eq!(eval("include.c", &opts), 42);
+
+        let err = super::preprocess("#include <lib.h>\n", Path::new("t.c"), &Options::default());
+        assert_eq!(err.unwrap_err().to_string(), "t.c:1: lib.h not found");
+    }
+
+    #[test]
+    fn defines() {
+        let opts = Options {
+            defines: vec![("LEVEL".to_owned(), "3".to_owned())],
+            ..Options::default()
+        };
+        let src = "#if LEVEL > 2 && __STDC__\nint x;\n#endif\n#undef LEVEL\n#ifdef LEVEL\nint y;\n#endif\n";
+        let (out, _) = super::preprocess(src, Path::new("t.c"), &opts).unwrap();
+        assert_eq!(out, "\nint x;\n\n\n\n\n\n");
+    }
+
+    #[test]
+    fn limits() {
+        // <limits.h> is for the model, and an include path's comes first
+        let path = format!("{TEST_DIR}/limits.c");
+        let src = fs::read_to_string(&path).expect("file dne");
+        for (width, bits) in [(16, 19), (32, 35)] {
+            let model = IntModel::new(width, DataModel::ILP32).unwrap();
+            let opts = Options {
+                model: model.clone(),
+                ..Options::default()
+            };
+            let (src, _) = super::preprocess(&src, Path::new(&path), &opts).unwrap();
+            let tree = parser_ast::parse_prg(&lexer::lex(&src).unwrap()).unwrap();
+            assert_eq!(evaluator::eval_prg(&tree, model).unwrap(), bits);
+        }
+
+        let opts = Options {
+            include_paths: vec![format!("{TEST_DIR}/sys").into()],
+            ..Options::default()
+        };
+        let src = "#include <limits.h>\nint x = INT_MAX;\n";
+        let (out, _) = super::preprocess(src, Path::new("t.c"), &opts).unwrap();
+        assert_eq!(out.trim(), "int x = 7;");
+        // only <limits.h> is the model's
+        let src = "#include \"limits.h\"\n";
+        let err = super::preprocess(src, Path::new("t.c"), &Options::default());
+        assert_eq!(err.unwrap_err().to_string(), "t.c:1: limits.h not found");
+    }
+
+    #[test]
+    fn errors() {
+        let errs = [
+            "#if 1\n",
+            "#endif\n",
+            "#if 1\n#else\n#else\n#endif\n",
+            "#define F(a, b) a\nF(1)\n",
+            "#define N 1\n#define N 2\n",
+            "#error stop\n",
+            "#frobnicate\n",
+            "#if 1 / 0\n#endif\n",
+        ]
+        .map(|src| {
+            let err = super::preprocess(src, Path::new("t.c"), &Options::default()).unwrap_err();
+            err.to_string()
+        });
+        insta::assert_yaml_snapshot!(errs, @r###"
+        ---
+        - "t.c:2: unterminated conditional"
+        - "t.c:1: #endif without #if"
+        - "t.c:3: #else after #else"
+        - "t.c:2: F takes 2 arguments, given 1"
+        - "t.c:2: N redefined differently"
+        - "t.c:1: #error stop"
+        - "t.c:1: unknown directive: #frobnicate"
+        - "t.c:1: division by zero in #if"
+        "###);
+    }
+
+    #[test]
+    fn source_map() {
+        // each line maps back to the file it came from, across includes
+        let opts = Options {
+            include_paths: vec![format!("{TEST_DIR}/sys").into()],
+            ..Options::default()
+        };
+        let path = format!("{TEST_DIR}/include.c");
+        let src = fs::read_to_string(&path).expect("file dne");
+        let (out, map) = super::preprocess(&src, Path::new(&path), &opts).unwrap();
+        let origin = |text: &str| {
+            let line = out.lines().position(|l| l.contains(text)).unwrap() + 1;
+            let (file, line) = map.origin(line);
+            (file.trim_start_matches(TEST_DIR).to_owned(), line)
+        };
+        assert_eq!(origin("int max"), ("/inc/max.h".to_owned(), 3));
+        assert_eq!(origin("return max"), ("/include.c".to_owned(), 5));
+
+        // and a #line renumbers what follows, renaming it too if given a file
+        let src = "int a;\n#line 100 \"gen.y\"\nchar *b = __FILE__;\n#line 7\nint c = 1 @ 2;\n";
+        let (out, map) = super::preprocess(src, Path::new("t.c"), &Options::default()).unwrap();
+        let origins = (1..=5).map(|l| map.origin(l)).collect::<Vec<_>>();
+        #[rustfmt::skip]
+        assert_eq!(origins, [("t.c", 1), ("t.c", 2), ("gen.y", 100), ("gen.y", 101), ("gen.y", 7)]);
+        assert!(out.contains("char *b = \"gen.y\";"));
+
+        let errs = lexer::lex(&out).unwrap_err();
+        insta::assert_snapshot!(map.caret(&out, errs[0].offset(&out), 4), @r###"
+        gen.y:7:11
+        int c = 1 @ 2;
+                  ^
+        "###);
+
+        let err = super::preprocess("#line x\n", Path::new("t.c"), &Options::default());
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "t.c:1: expected a line number after #line, got: x"
+        );
+    }
+}
@@ -0,0 +1,1103 @@
+use crate::lexer::{self, Comment};
+use crate::source::Span;
+use crate::{
+    SAttr, SBinOp, SBitOp, SContract, SContractKind, SDef, SExpr, SFuncDef, SIncDecOp, SLogOp,
+    SPrg, SRelOp, SStmt, SUnaryOp, Spanned, Type,
+};
+
+// c source for a tree, which the parser reads back as the same tree, spans
+// aside. it's canonical, so printing what was printed changes nothing:
+//   - four space indents, a stmt to a line, and spaces around operators
+//   - parens only where precedence needs them
+//   - what the parser desugars is printed as what it became, x++; as
+//     x = x + 1;, and enumerators as their values. a negative one comes
+//     back as a negation
+const INDENT: &str = "    ";
+
+pub fn prg(prg: &SPrg) -> String {
+    let mut out = Out::default();
+    defs(prg, &mut out);
+    out.text()
+}
+
+// prg as fmt prints it, with the comments in src, what prg was parsed
+// from, kept. one after code on its line stays at the end of that line,
+// and the rest go on lines of their own, before what followed them. those
+// within an expression or a struct's fields go after it
+pub fn formatted(prg: &SPrg, src: &str) -> String {
+    let mut out = Out {
+        lines: vec![],
+        src,
+        comments: lexer::trivia(src).into_iter().rev().collect(),
+    };
+    defs(prg, &mut out);
+    out.text()
+}
+
+// the lines printed so far, and the comments still to print, last first
+#[derive(Default)]
+struct Out<'a> {
+    lines: Vec<String>,
+    src: &'a str,
+    comments: Vec<Comment>,
+}
+
+impl Out<'_> {
+    fn push(&mut self, line: String) {
+        self.lines.push(line);
+    }
+
+    // the comments before offset with code before them on their line, at
+    // the end of the last line
+    fn trailing(&mut self, offset: usize) {
+        while let (Some(c), Some(last)) = (self.comments.last(), self.lines.last_mut()) {
+            let line = self.src[..c.span.start]
+                .rsplit('\n')
+                .next()
+                .unwrap_or_default();
+            if c.span.start >= offset || line.trim().is_empty() {
+                break;
+            }
+            last.push(' ');
+            last.push_str(&c.text);
+            self.comments.pop();
+        }
+    }
+
+    // the comments before offset, at depth indents
+    fn comments(&mut self, offset: usize, depth: usize) {
+        self.trailing(offset);
+        while let Some(c) = self.comments.pop_if(|c| c.span.start < offset) {
+            self.push(format!("{}{}", INDENT.repeat(depth), c.text));
+            self.trailing(offset);
+        }
+    }
+
+    // where span is in src, once there are comments to place there. made
+    // up nodes are nowhere
+    fn offset(&self, span: Span) -> Option<usize> {
+        (!self.comments.is_empty() && span != Span::default()).then(|| span.offset(self.src))
+    }
+
+    // the comments before what's at span
+    fn before(&mut self, span: Span, depth: usize) {
+        if let Some(offset) = self.offset(span) {
+            self.comments(offset, depth);
+        }
+    }
+
+    // the comments within what's at span but not yet printed, before the
+    // line that closes it
+    fn inside(&mut self, span: Span, depth: usize) {
+        if let Some(offset) = self.offset(span) {
+            let close = self.lines.pop().expect("there's a closing line");
+            self.comments(offset + span.len, depth);
+            self.push(close);
+        }
+    }
+
+    fn text(mut self) -> String {
+        self.comments(usize::MAX, 0);
+        self.lines.iter().map(|l| format!("{l}\n")).collect()
+    }
+}
+
+fn defs(prg: &SPrg, out: &mut Out) {
+    for (i, d) in prg.iter().enumerate() {
+        // definitions with a body stand apart, declarations are kept together
+        let multiline = |d: &Spanned<SDef>| {
+            !matches!(
+                d.node,
+                SDef::FuncDecl(_) | SDef::VarDef(_) | SDef::VarDecl(_)
+            )
+        };
+        if let Some(offset) = out.offset(d.span) {
+            out.trailing(offset);
+        }
+        if i > 0 && (multiline(d) || multiline(&prg[i - 1])) {
+            out.push(String::new());
+        }
+        out.before(d.span, 0);
+        def(d, out);
+        if multiline(d) {
+            out.inside(d.span, 1);
+        }
+    }
+}
+
+fn def(d: &SDef, out: &mut Out) {
+    match d {
+        SDef::FuncDef(fd) => funcdef(fd, out),
+        SDef::FuncDecl(fd) => {
+            let mut fps = fd.fps.iter().map(typ).collect::<Vec<_>>();
+            if fd.variadic {
+                fps.push("...".to_owned());
+            }
+            out.push(format!("{}({});", decl(&fd.typ, &fd.alias), fps.join(", ")))
+        }
+        SDef::VarDef(vd) => {
+            let d = qualified(&vd.typ, &vd.alias, vd.constant, vd.is_static);
+            out.push(format!("{d} = {};", expr(&vd.expr)))
+        }
+        SDef::VarDecl(vd) => out.push(format!(
+            "{};",
+            qualified(&vd.typ, &vd.alias, vd.constant, vd.is_static)
+        )),
+        SDef::StructDef(sd) | SDef::UnionDef(sd) => {
+            let kw = match d {
+                SDef::StructDef(_) => "struct",
+                _ => "union",
+            };
+            out.push(format!("{kw} {} {{", sd.alias));
+            for (f, t) in &sd.fields {
+                out.push(format!("{INDENT}{};", decl(t, f)));
+            }
+            out.push("};".to_owned());
+        }
+        SDef::EnumDef(ed) => {
+            let alias = ed.alias.as_ref().map_or(String::new(), |a| format!("{a} "));
+            let enumerators = ed
+                .enumerators
+                .iter()
+                .map(|(e, val)| format!("{e} = {val}"))
+                .collect::<Vec<_>>();
+            out.push(format!("enum {alias}{{ {} }};", enumerators.join(", ")))
+        }
+    }
+}
+
+fn funcdef(fd: &SFuncDef, out: &mut Out) {
+    let mut head = String::new();
+    if fd.is_static {
+        head.push_str("static ");
+    }
+    if !fd.attrs.is_empty() {
+        let attrs = fd.attrs.iter().map(|a| match a {
+            SAttr::NoInline => "noinline".to_owned(),
+            SAttr::AlwaysInline => "always_inline".to_owned(),
+            SAttr::Optimize(opt) => format!("optimize(\"{opt:?}\")"),
+        });
+        head.push_str(&format!(
+            "__attribute__(({})) ",
+            attrs.collect::<Vec<_>>().join(", ")
+        ));
+    }
+    match fd.implicit_int {
+        true => head.push_str(&fd.alias),
+        false => head.push_str(&decl(&fd.typ, &fd.alias)),
+    }
+    let fps = fd.fps.iter().map(|(a, t)| decl(t, a)).collect::<Vec<_>>();
+    head.push_str(&format!("({})", fps.join(", ")));
+
+    match fd.contracts.is_empty() {
+        true => out.push(head + " {"),
+        false => {
+            out.push(head);
+            for c in &fd.contracts {
+                out.push(format!("{INDENT}{}", contract(c)));
+            }
+            out.push("{".to_owned());
+        }
+    }
+    stmts(&fd.body, 1, out);
+    out.push("}".to_owned());
+}
+
+fn stmts(ss: &[Spanned<SStmt>], depth: usize, out: &mut Out) {
+    for s in ss {
+        stmt(s, depth, out);
+    }
+}
+
+// s's lines, at depth indents, after the comments before it
+fn stmt(s: &Spanned<SStmt>, depth: usize, out: &mut Out) {
+    out.before(s.span, depth);
+    let pad = INDENT.repeat(depth);
+    match &s.node {
+        SStmt::Asnmt(_) | SStmt::Decl(_) | SStmt::Update(_) | SStmt::Store(_) => {
+            out.push(format!("{pad}{};", simple(s)))
+        }
+        SStmt::Call(e) => out.push(format!("{pad}{};", expr(e))),
+        SStmt::Return(None) => out.push(format!("{pad}return;")),
+        SStmt::Return(Some(e)) => out.push(format!("{pad}return {};", expr(e))),
+        SStmt::Break => out.push(format!("{pad}break;")),
+        SStmt::Continue => out.push(format!("{pad}continue;")),
+        SStmt::Label(l) => out.push(format!("{pad}{l}:")),
+        SStmt::Goto(l) => out.push(format!("{pad}goto {l};")),
+        SStmt::Contract(c) => out.push(format!("{pad}{}", contract(c))),
+        SStmt::Assert(e) => out.push(format!("{pad}assert({});", expr(e))),
+        SStmt::Block(body) => {
+            out.push(format!("{pad}{{"));
+            stmts(body, depth + 1, out);
+            out.push(format!("{pad}}}"));
+            out.inside(s.span, depth + 1);
+        }
+        // an if's branches are a stmt each, between braces
+        SStmt::IfEls { cond, then, els } => {
+            out.push(format!("{pad}if ({}) {{", expr(cond)));
+            stmt(then, depth + 1, out);
+            if let Some(els) = els {
+                out.before(els.span, depth + 1);
+                out.push(format!("{pad}}} else {{"));
+                stmt(els, depth + 1, out);
+            }
+            out.push(format!("{pad}}}"));
+            out.inside(s.span, depth + 1);
+        }
+        SStmt::Switch { cond, arms } => {
+            out.push(format!("{pad}switch ({}) {{", expr(cond)));
+            for arm in arms {
+                match arm.label {
+                    Some(val) => out.push(format!("{pad}{INDENT}case {val}:")),
+                    None => out.push(format!("{pad}{INDENT}default:")),
+                }
+                stmts(&arm.body, depth + 2, out);
+            }
+            out.push(format!("{pad}}}"));
+            out.inside(s.span, depth + 1);
+        }
+        SStmt::While {
+            cond,
+            body,
+            invariants,
+        } => {
+            let head = format!("{pad}while ({})", expr(cond));
+            looped(head, invariants, body, depth, out);
+            out.inside(s.span, depth + 1);
+        }
+        SStmt::DoWhile { body, cond } => {
+            out.push(format!("{pad}do {{"));
+            stmts(body, depth + 1, out);
+            out.push(format!("{pad}}} while ({});", expr(cond)));
+            out.inside(s.span, depth + 1);
+        }
+        SStmt::For {
+            asnmt,
+            cond,
+            update,
+            body,
+            invariants,
+        } => {
+            let (asnmt, cond, update) = (simple(asnmt), expr(cond), simple(update));
+            let update = match update.as_str() {
+                "" => update,
+                _ => format!(" {update}"),
+            };
+            let head = format!("{pad}for ({asnmt}; {cond};{update})");
+            looped(head, invariants, body, depth, out);
+            out.inside(s.span, depth + 1);
+        }
+    }
+}
+
+// a loop's head, its invariants on lines of their own, then its body
+fn looped(
+    head: String,
+    invariants: &[SContract],
+    body: &[Spanned<SStmt>],
+    depth: usize,
+    out: &mut Out,
+) {
+    let pad = INDENT.repeat(depth);
+    match invariants.is_empty() {
+        true => out.push(head + " {"),
+        false => {
+            out.push(head);
+            for c in invariants {
+                out.push(format!("{pad}{INDENT}{}", contract(c)));
+            }
+            out.push(format!("{pad}{{"));
+        }
+    }
+    stmts(body, depth + 1, out);
+    out.push(format!("{pad}}}"));
+}
+
+// the stmts a for's head is made of, without their ;
+fn simple(s: &SStmt) -> String {
+    match s {
+        SStmt::Asnmt(vd) => {
+            let d = qualified(&vd.typ, &vd.alias, vd.constant, vd.is_static);
+            format!("{d} = {}", expr(&vd.expr))
+        }
+        SStmt::Decl(vd) => qualified(&vd.typ, &vd.alias, vd.constant, vd.is_static),
+        SStmt::Update(vu) => format!("{} = {}", vu.alias, expr(&vu.expr)),
+        SStmt::Store(st) => {
+            let op = st.op.as_ref().map_or("", bin_op);
+            format!("{} {op}= {}", at(&st.lval, UNARY), expr(&st.expr))
+        }
+        SStmt::Block(b) if b.is_empty() => String::new(), // a for's clause left out
+        s => {
+            let mut out = Out::default();
+            stmt(&Spanned::new(s.clone(), Span::default()), 0, &mut out);
+            out.lines.join(" ")
+        }
+    }
+}
+
+fn contract(c: &SContract) -> String {
+    let kind = match c.kind {
+        SContractKind::Requires => "requires",
+        SContractKind::Ensures => "ensures",
+        SContractKind::LoopInvariant => "loop_invariant",
+        SContractKind::Assert => "assert",
+    };
+    format!("//@{kind} {};", expr(&c.expr))
+}
+
+// a declaration of alias, const where it's alias itself that's const: the
+// outermost pointer, or what isn't a pointer
+fn qualified(t: &Type, alias: &str, constant: bool, is_static: bool) -> String {
+    let storage = if is_static { "static " } else { "" };
+    let elem = match t {
+        Type::Arr(elem, _) => elem,
+        t => t,
+    };
+    match (constant, elem) {
+        (false, _) => format!("{storage}{}", decl(t, alias)),
+        (true, Type::Ptr(_)) => format!("{storage}{}", decl(t, &format!("const {alias}"))),
+        (true, _) => format!("{storage}const {}", decl(t, alias)),
+    }
+}
+
+// int **p, int a[3]
+fn decl(t: &Type, alias: &str) -> String {
+    match t {
+        Type::Arr(elem, len) => format!("{}[{len}]", decl(elem, alias)),
+        t => {
+            let (mut base, mut stars) = (t, 0);
+            while let Type::Ptr(t) = base {
+                (base, stars) = (t, stars + 1);
+            }
+            format!("{} {}{alias}", specifiers(base), "*".repeat(stars))
+        }
+    }
+}
+
+// a type as sizeof and prototypes name it: int, char *
+pub fn typ(t: &Type) -> String {
+    decl(t, "").trim_end().to_owned()
+}
+
+fn specifiers(t: &Type) -> String {
+    match t {
+        Type::Int => "int".to_owned(),
+        Type::Char => "char".to_owned(),
+        Type::Short => "short".to_owned(),
+        Type::Long => "long".to_owned(),
+        Type::UChar => "unsigned char".to_owned(),
+        Type::UShort => "unsigned short".to_owned(),
+        Type::UInt => "unsigned".to_owned(),
+        Type::ULong => "unsigned long".to_owned(),
+        Type::Float => "float".to_owned(),
+        Type::Double => "double".to_owned(),
+        Type::Bool => "bool".to_owned(),
+        Type::Void => "void".to_owned(),
+        Type::Struct(alias) => format!("struct {alias}"),
+        Type::Union(alias) => format!("union {alias}"),
+        Type::Ptr(_) | Type::Arr(..) => typ(t),
+    }
+}
+
+pub fn expr(e: &SExpr) -> String {
+    at(e, 0)
+}
+
+// how tightly each expr binds, as the parser's INFIX has it, with the unary
+// operators above the binary ones, and postfix ones and atoms above those
+const UNARY: u8 = 12;
+const POSTFIX: u8 = 13;
+
+fn prec(e: &SExpr) -> u8 {
+    match e {
+        SExpr::CondE { .. } => 1,
+        SExpr::LogE { op: SLogOp::Or, .. } => 2,
+        SExpr::LogE {
+            op: SLogOp::And, ..
+        } => 3,
+        SExpr::BitE { op, .. } => match op {
+            SBitOp::Or => 4,
+            SBitOp::Xor => 5,
+            SBitOp::And => 6,
+            SBitOp::Shl | SBitOp::Shr => 9,
+        },
+        SExpr::RelE { op, .. } => match op {
+            SRelOp::Eq | SRelOp::Neq => 7,
+            _ => 8,
+        },
+        SExpr::BinE { op, .. } => match op {
+            SBinOp::Add | SBinOp::Sub => 10,
+            _ => 11,
+        },
+        SExpr::UnaryE { .. } | SExpr::Deref(_) | SExpr::AddrOf(_) => UNARY,
+        SExpr::SizeOf(_) | SExpr::SizeOfType(_) | SExpr::Cast(..) => UNARY,
+        SExpr::Alloc(_) | SExpr::AllocArray(..) => UNARY, // parsed with the unary ones, so never followed by a postfix
+        SExpr::IncDec {
+            op: SIncDecOp::PreInc | SIncDecOp::PreDec,
+            ..
+        } => UNARY,
+        SExpr::Int(n) if *n < 0 => UNARY,
+        _ => POSTFIX,
+    }
+}
+
+// e, in parens if it binds looser than min
+fn at(e: &SExpr, min: u8) -> String {
+    let s = match e {
+        SExpr::Int(n) => n.to_string(),
+        SExpr::TypedInt { val, typ } => match typ {
+            Type::UInt => format!("{}u", *val as u32),
+            Type::Long => format!("{val}L"),
+            Type::ULong => format!("{}uL", *val as u32),
+            _ => val.to_string(),
+        },
+        SExpr::Float { lit, single } => match single {
+            true => format!("{lit}f"),
+            false => lit.to_owned(),
+        },
+        SExpr::Bool(b) => b.to_string(),
+        SExpr::Str(s) => str(s),
+        SExpr::VarApp(alias) => alias.to_owned(),
+        SExpr::FuncApp { alias, aps } => {
+            let aps = aps.iter().map(|ap| expr(ap)).collect::<Vec<_>>();
+            format!("{alias}({})", aps.join(", "))
+        }
+        SExpr::IncDec { op, lval } => match op {
+            SIncDecOp::PreInc => prefix("++", lval),
+            SIncDecOp::PreDec => prefix("--", lval),
+            SIncDecOp::PostInc => format!("{}++", at(lval, POSTFIX)),
+            SIncDecOp::PostDec => format!("{}--", at(lval, POSTFIX)),
+        },
+        SExpr::UnaryE { op, l } => {
+            let op = match op {
+                SUnaryOp::Add => "+",
+                SUnaryOp::Sub => "-",
+                SUnaryOp::Not => "!",
+                SUnaryOp::BitNot => "~",
+            };
+            prefix(op, l)
+        }
+        SExpr::Deref(l) => prefix("*", l),
+        SExpr::AddrOf(l) => prefix("&", l),
+        SExpr::SizeOf(l) => format!("sizeof({})", expr(l)),
+        SExpr::SizeOfType(t) => format!("sizeof({})", typ(t)),
+        SExpr::Alloc(t) => format!("alloc({})", typ(t)),
+        SExpr::AllocArray(t, n) => format!("alloc_array({}, {})", typ(t), expr(n)),
+        SExpr::Cast(t, l) => format!("({}) {}", typ(t), at(l, UNARY)),
+        SExpr::Index { alias, idx } => format!("{alias}[{}]", expr(idx)),
+        // x++ ends a postfix chain, so (*x++).f has no -> form
+        SExpr::Member { base, field } => match &base.node {
+            SExpr::Deref(p) if !matches!(p.node, SExpr::IncDec { .. }) => {
+                format!("{}->{field}", at(p, POSTFIX))
+            }
+            base => format!("{}.{field}", at(base, POSTFIX)),
+        },
+        SExpr::CondE { cond, then, els } => {
+            format!("{} ? {} : {}", at(cond, 2), expr(then), at(els, 1))
+        }
+        SExpr::BinE { op, l, r } => infix(bin_op(op), prec(e), l, r),
+        SExpr::LogE { op, l, r } => {
+            let op = match op {
+                SLogOp::And => "&&",
+                SLogOp::Or => "||",
+            };
+            infix(op, prec(e), l, r)
+        }
+        SExpr::BitE { op, l, r } => {
+            let op = match op {
+                SBitOp::And => "&",
+                SBitOp::Or => "|",
+                SBitOp::Xor => "^",
+                SBitOp::Shl => "<<",
+                SBitOp::Shr => ">>",
+            };
+            infix(op, prec(e), l, r)
+        }
+        SExpr::RelE { op, l, r } => {
+            let op = match op {
+                SRelOp::Eq => "==",
+                SRelOp::Neq => "!=",
+                SRelOp::Lt => "<",
+                SRelOp::LtEq => "<=",
+                SRelOp::Gt => ">",
+                SRelOp::GtEq => ">=",
+            };
+            infix(op, prec(e), l, r)
+        }
+    };
+    match prec(e) < min {
+        true => format!("({s})"),
+        false => s,
+    }
+}
+
+// operators are left associative, so a right operand as loose needs parens
+fn infix(op: &str, prec: u8, l: &SExpr, r: &SExpr) -> String {
+    format!("{} {op} {}", at(l, prec), at(r, prec + 1))
+}
+
+// - -x would lex as --x, so an operand starting with the operator is put
+// in parens
+fn bin_op(op: &SBinOp) -> &'static str {
+    match op {
+        SBinOp::Add => "+",
+        SBinOp::Sub => "-",
+        SBinOp::Mult => "*",
+        SBinOp::Div => "/",
+        SBinOp::Mod => "%",
+    }
+}
+
+fn prefix(op: &str, l: &SExpr) -> String {
+    let l = at(l, UNARY);
+    match matches!(op, "+" | "-" | "&") && l.starts_with(op) {
+        true => format!("{op}({l})"),
+        false => format!("{op}{l}"),
+    }
+}
+
+fn str(s: &str) -> String {
+    let mut out = String::from('"');
+    for c in s.chars() {
+        match c {
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\0' => out.push_str("\\0"),
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod test_pretty {
+    use crate::{
+        lexer, parser_ast, preprocessor, same_tree, OptLevel, SAttr, SBinOp, SBitOp, SCase,
+        SContract, SContractKind, SDef, SEnumDef, SExpr, SFuncDecl, SFuncDef, SIncDecOp, SLogOp,
+        SPrg, SRelOp, SStmt, SStore, SStructDef, SUnaryOp, SVarDecl, SVarDef, SVarUpdate, Spanned,
+        Type,
+    };
+    use std::fs;
+    use std::path::Path;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared";
+
+    fn parse(src: &str, path: &Path) -> Option<SPrg> {
+        let (text, _) = preprocessor::preprocess(src, path, &Default::default()).ok()?;
+        let tokens = lexer::lex(&text).ok()?;
+        parser_ast::parse_prg(&tokens).ok()
+    }
+
+    // printed, each fixture that parses is parsed back as it was, and
+    // printed again as it was printed. enums.c's negative enumerator comes
+    // back as a negation
+    #[test]
+    fn fixtures() {
+        let mut failed = vec![];
+        for dir in ["arith", "bindings", "control"] {
+            let mut paths = fs::read_dir(format!("{TEST_DIR}/{dir}"))
+                .expect("dir dne")
+                .map(|e| e.unwrap().path())
+                .collect::<Vec<_>>();
+            paths.sort();
+            for path in paths {
+                let src = fs::read_to_string(&path).expect("file dne");
+                let Some(tree) = parse(&src, &path) else {
+                    continue;
+                };
+                let printed = super::prg(&tree);
+                let exact = !path.ends_with("enums.c");
+                let same =
+                    |back: &SPrg| super::prg(back) == printed && (!exact || same_tree(&tree, back));
+                if !parse(&printed, &path).is_some_and(|back| same(&back)) {
+                    failed.push(format!("{}:\n{printed}", path.display()));
+                }
+            }
+        }
+        assert!(failed.is_empty(), "{}", failed.join("\n"));
+    }
+
+    #[test]
+    fn printed() {
+        let src = "int f(int n) //@requires n >= 0;
+{ int s=0; for (int i=0; i<n; i++) { s += i*(n-i); }
+  if (s > 1 && !(s % 2 == 0)) { return -(-s); } else { return (s ? 1 : 2) + 3; } }";
+        let tree = parse(src, Path::new("f.c")).unwrap();
+        insta::assert_snapshot!(super::prg(&tree), @r###"
+        int f(int n)
+            //@requires n >= 0;
+        {
+            int s = 0;
+            for (int i = 0; i < n; i = i + 1) {
+                s = s + i * (n - i);
+            }
+            if (s > 1 && !(s % 2 == 0)) {
+                return -(-s);
+            } else {
+                return (s ? 1 : 2) + 3;
+            }
+        }
+        "###);
+    }
+
+    // comments stay before what they came before, or at the end of their
+    // line, and formatting what was formatted changes nothing
+    #[test]
+    fn comments() {
+        let src = "// sums
+int f(int n) {   int s = 0; // so far
+  /* each */ while (n > 0) { s+=n; n--; /* down */ }
+  if (s) { return s; // some
+  } else { return 0; } // none
+}
+/* done */";
+        let fmt = |src: &str| {
+            let tokens = lexer::lex(src).unwrap();
+            super::formatted(&parser_ast::parse_prg(&tokens).unwrap(), src)
+        };
+        let formatted = fmt(src);
+        assert_eq!(fmt(&formatted), formatted);
+        insta::assert_snapshot!(formatted, @r###"
+        // sums
+        int f(int n) {
+            int s = 0; // so far
+            /* each */
+            while (n > 0) {
+                s = s + n;
+                n = n - 1; /* down */
+            }
+            if (s) {
+                return s; // some
+            } else {
+                return 0;
+            } // none
+        }
+        /* done */
+        "###);
+    }
+
+    // random trees of the kinds the parser makes, each printed and parsed
+    // back the same
+    #[test]
+    fn generated() {
+        let mut gen = Gen(0x5eed);
+        for _ in 0..5000 {
+            let tree = gen.prg();
+            let printed = super::prg(&tree);
+            let back = parse(&printed, Path::new("gen.c"));
+            assert!(
+                back.is_some_and(|back| same_tree(&tree, &back)),
+                "{printed}"
+            );
+        }
+    }
+
+    // xorshift, seeded so a failing tree comes up again
+    struct Gen(u64);
+
+    const ALIASES: [&str; 6] = ["a", "b", "n", "p", "s", "x"];
+    const SCALARS: [Type; 10] = [
+        Type::Int,
+        Type::Char,
+        Type::Short,
+        Type::Long,
+        Type::UChar,
+        Type::UShort,
+        Type::UInt,
+        Type::ULong,
+        Type::Float,
+        Type::Double,
+    ];
+
+    fn at<T>(node: T) -> Spanned<T> {
+        Spanned::new(node, Default::default())
+    }
+
+    impl Gen {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+
+        fn coin(&mut self) -> bool {
+            self.below(2) == 0
+        }
+
+        fn alias(&mut self) -> String {
+            ALIASES[self.below(ALIASES.len())].to_owned()
+        }
+
+        fn typ(&mut self) -> Type {
+            let mut t = match self.below(12) {
+                0 => match self.coin() {
+                    true => Type::Struct("s".to_owned()),
+                    false => Type::Union("u".to_owned()),
+                },
+                n => SCALARS[(n - 1).min(SCALARS.len() - 1)].clone(),
+            };
+            for _ in 0..self.below(4).saturating_sub(1) {
+                t = Type::Ptr(Box::new(t));
+            }
+            t
+        }
+
+        fn exprs(&mut self, depth: usize) -> Vec<Spanned<SExpr>> {
+            (0..self.below(3)).map(|_| self.expr(depth)).collect()
+        }
+
+        fn expr(&mut self, depth: usize) -> Spanned<SExpr> {
+            let sub = |gen: &mut Gen| Box::new(gen.expr(depth - 1));
+            let e = match if depth == 0 { 0 } else { self.below(17) } {
+                0 => match self.below(8) {
+                    0 => SExpr::Int(self.below(1000) as i32),
+                    1 => [
+                        SExpr::TypedInt {
+                            val: 3_000_000_000u32 as i32,
+                            typ: Type::UInt,
+                        },
+                        SExpr::TypedInt {
+                            val: 7,
+                            typ: Type::Long,
+                        },
+                        SExpr::TypedInt {
+                            val: 4_000_000_000u32 as i32,
+                            typ: Type::ULong,
+                        },
+                    ][self.below(3)]
+                    .clone(),
+                    2 => SExpr::Float {
+                        lit: ["1.5", "0.25", "2e3"][self.below(3)].to_owned(),
+                        single: self.coin(),
+                    },
+                    3 => SExpr::Bool(self.coin()),
+                    4 => SExpr::Str(["", "hi", "a\"b\\c\t\n", "it's"][self.below(4)].to_owned()),
+                    _ => SExpr::VarApp(self.alias()),
+                },
+                1 => SExpr::UnaryE {
+                    op: [
+                        SUnaryOp::Add,
+                        SUnaryOp::Sub,
+                        SUnaryOp::Not,
+                        SUnaryOp::BitNot,
+                    ][self.below(4)]
+                    .clone(),
+                    l: sub(self),
+                },
+                2 => SExpr::BinE {
+                    op: [
+                        SBinOp::Add,
+                        SBinOp::Sub,
+                        SBinOp::Mult,
+                        SBinOp::Div,
+                        SBinOp::Mod,
+                    ][self.below(5)]
+                    .clone(),
+                    l: sub(self),
+                    r: sub(self),
+                },
+                3 => SExpr::LogE {
+                    op: [SLogOp::And, SLogOp::Or][self.below(2)].clone(),
+                    l: sub(self),
+                    r: sub(self),
+                },
+                4 => SExpr::BitE {
+                    op: [
+                        SBitOp::And,
+                        SBitOp::Or,
+                        SBitOp::Xor,
+                        SBitOp::Shl,
+                        SBitOp::Shr,
+                    ][self.below(5)]
+                    .clone(),
+                    l: sub(self),
+                    r: sub(self),
+                },
+                5 => SExpr::RelE {
+                    op: [
+                        SRelOp::Eq,
+                        SRelOp::Neq,
+                        SRelOp::Lt,
+                        SRelOp::LtEq,
+                        SRelOp::Gt,
+                        SRelOp::GtEq,
+                    ][self.below(6)]
+                    .clone(),
+                    l: sub(self),
+                    r: sub(self),
+                },
+                6 => SExpr::CondE {
+                    cond: sub(self),
+                    then: sub(self),
+                    els: sub(self),
+                },
+                7 => SExpr::FuncApp {
+                    alias: self.alias(),
+                    aps: self.exprs(depth - 1),
+                },
+                8 => SExpr::IncDec {
+                    op: [
+                        SIncDecOp::PreInc,
+                        SIncDecOp::PreDec,
+                        SIncDecOp::PostInc,
+                        SIncDecOp::PostDec,
+                    ][self.below(4)]
+                    .clone(),
+                    lval: Box::new(self.lval(depth - 1)),
+                },
+                9 => SExpr::AddrOf(Box::new(self.lval(depth - 1))),
+                10 => SExpr::Deref(sub(self)),
+                11 => SExpr::Index {
+                    alias: self.alias(),
+                    idx: sub(self),
+                },
+                12 => SExpr::Member {
+                    base: Box::new(self.lval(depth - 1)),
+                    field: self.alias(),
+                },
+                13 => SExpr::SizeOf(sub(self)),
+                14 => SExpr::SizeOfType(self.typ()),
+                15 => SExpr::Cast(self.typ(), sub(self)),
+                _ => match self.coin() {
+                    true => SExpr::Alloc(self.typ()),
+                    false => SExpr::AllocArray(self.typ(), sub(self)),
+                },
+            };
+            at(e)
+        }
+
+        // what can be assigned to, and have its address taken
+        fn lval(&mut self, depth: usize) -> Spanned<SExpr> {
+            let e = match if depth == 0 { 0 } else { self.below(4) } {
+                0 => SExpr::VarApp(self.alias()),
+                1 => SExpr::Index {
+                    alias: self.alias(),
+                    idx: Box::new(self.expr(depth - 1)),
+                },
+                2 => SExpr::Member {
+                    base: Box::new(self.lval(depth - 1)),
+                    field: self.alias(),
+                },
+                _ => SExpr::Deref(Box::new(self.expr(depth - 1))),
+            };
+            at(e)
+        }
+
+        fn contracts(&mut self, kinds: &[SContractKind]) -> Vec<SContract> {
+            (0..self.below(3))
+                .map(|_| SContract {
+                    kind: kinds[self.below(kinds.len())].clone(),
+                    expr: self.expr(2),
+                })
+                .collect()
+        }
+
+        fn stmts(&mut self, depth: usize) -> Vec<Spanned<SStmt>> {
+            (0..self.below(4)).map(|_| self.stmt(depth)).collect()
+        }
+
+        // the stmts that are an assignment, as a for's head has
+        fn simple(&mut self) -> SStmt {
+            match self.below(4) {
+                0 => SStmt::Asnmt(SVarDef {
+                    alias: self.alias(),
+                    typ: self.typ(),
+                    expr: Box::new(self.expr(2)),
+                    constant: self.coin(),
+                    is_static: self.below(4) == 0,
+                }),
+                1 => SStmt::Decl(SVarDecl {
+                    alias: self.alias(),
+                    typ: match self.coin() {
+                        true => Type::Arr(Box::new(self.typ()), self.below(8) + 1),
+                        false => self.typ(),
+                    },
+                    constant: self.coin(),
+                    is_static: self.below(4) == 0,
+                }),
+                2 => SStmt::Update(SVarUpdate {
+                    alias: self.alias(),
+                    expr: Box::new(self.expr(2)),
+                }),
+                // a stmt starting with an alias is an element or member of
+                // it, as the alias alone is an update, and otherwise it's a
+                // deref
+                _ => {
+                    let lval = match self.below(3) {
+                        0 => SExpr::Deref(Box::new(self.expr(2))),
+                        n => {
+                            let (mut lval, members) = match self.coin() {
+                                true => (SExpr::VarApp(self.alias()), n),
+                                false => {
+                                    let alias = self.alias();
+                                    let idx = Box::new(self.expr(2));
+                                    (SExpr::Index { alias, idx }, n - 1)
+                                }
+                            };
+                            for _ in 0..members + usize::from(self.coin()) {
+                                let base = Box::new(at(lval));
+                                let field = self.alias();
+                                lval = SExpr::Member { base, field };
+                            }
+                            lval
+                        }
+                    };
+                    SStmt::Store(SStore {
+                        lval: Box::new(at(lval)),
+                        expr: Box::new(self.expr(2)),
+                        op: match self.coin() {
+                            true => None,
+                            false => Some(
+                                [
+                                    SBinOp::Add,
+                                    SBinOp::Sub,
+                                    SBinOp::Mult,
+                                    SBinOp::Div,
+                                    SBinOp::Mod,
+                                ][self.below(5)]
+                                .clone(),
+                            ),
+                        },
+                    })
+                }
+            }
+        }
+
+        fn stmt(&mut self, depth: usize) -> Spanned<SStmt> {
+            let s = match if depth == 0 { 0 } else { self.below(18) } {
+                0..=3 => self.simple(),
+                4 => SStmt::Call(at(SExpr::FuncApp {
+                    alias: self.alias(),
+                    aps: self.exprs(2),
+                })),
+                5 => SStmt::Return(self.coin().then(|| self.expr(3))),
+                6 => [SStmt::Break, SStmt::Continue][self.below(2)].clone(),
+                7 => SStmt::Label(format!("l{}", self.below(3))),
+                8 => SStmt::Goto(format!("l{}", self.below(3))),
+                9 => match self.coin() {
+                    true => SStmt::Assert(self.expr(2)),
+                    false => SStmt::Contract(SContract {
+                        kind: SContractKind::Assert,
+                        expr: self.expr(2),
+                    }),
+                },
+                10 => SStmt::Block(self.stmts(depth - 1)),
+                11 | 12 => SStmt::IfEls {
+                    cond: Box::new(self.expr(3)),
+                    then: Box::new(self.stmt(depth - 1)),
+                    els: self.coin().then(|| Box::new(self.stmt(depth - 1))),
+                },
+                13 => SStmt::While {
+                    cond: Box::new(self.expr(3)),
+                    body: self.stmts(depth - 1),
+                    invariants: self.contracts(&[SContractKind::LoopInvariant]),
+                },
+                14 => SStmt::DoWhile {
+                    body: self.stmts(depth - 1),
+                    cond: Box::new(self.expr(3)),
+                },
+                15 | 16 => SStmt::For {
+                    asnmt: Box::new(at(self.simple())),
+                    cond: Box::new(self.expr(3)),
+                    update: Box::new(at(match self.simple() {
+                        s @ (SStmt::Update(_) | SStmt::Store(_)) => s,
+                        _ => SStmt::Update(SVarUpdate {
+                            alias: self.alias(),
+                            expr: Box::new(self.expr(1)),
+                        }),
+                    })),
+                    body: self.stmts(depth - 1),
+                    invariants: self.contracts(&[SContractKind::LoopInvariant]),
+                },
+                _ => SStmt::Switch {
+                    cond: Box::new(self.expr(2)),
+                    arms: (0..self.below(4))
+                        .map(|_| SCase {
+                            label: (!self.coin()).then(|| self.below(200) as i32 - 100),
+                            body: self.stmts(depth - 1),
+                        })
+                        .collect(),
+                },
+            };
+            at(s)
+        }
+
+        fn def(&mut self) -> Spanned<SDef> {
+            let fields = |gen: &mut Gen| {
+                (0..gen.below(4))
+                    .map(|_| (gen.alias(), gen.typ()))
+                    .collect()
+            };
+            let d = match self.below(8) {
+                0..=2 => {
+                    let implicit_int = self.below(4) == 0;
+                    let attrs = match self.below(4) {
+                        0 => vec![SAttr::NoInline, SAttr::Optimize(OptLevel::O1)],
+                        1 => vec![SAttr::AlwaysInline],
+                        _ => vec![],
+                    };
+                    SDef::FuncDef(SFuncDef {
+                        alias: self.alias(),
+                        typ: match (implicit_int, self.coin()) {
+                            (true, _) => Type::Int,
+                            (false, true) => Type::Void,
+                            (false, false) => self.typ(),
+                        },
+                        fps: fields(self),
+                        body: self.stmts(3),
+                        attrs,
+                        is_static: self.below(4) == 0,
+                        implicit_int,
+                        contracts: self
+                            .contracts(&[SContractKind::Requires, SContractKind::Ensures]),
+                    })
+                }
+                3 => {
+                    let fps = (0..self.below(3)).map(|_| self.typ()).collect::<Vec<_>>();
+                    SDef::FuncDecl(SFuncDecl {
+                        alias: self.alias(),
+                        typ: self.typ(),
+                        variadic: !fps.is_empty() && self.coin(),
+                        fps,
+                    })
+                }
+                4 => match self.simple() {
+                    SStmt::Asnmt(vd) => SDef::VarDef(vd),
+                    SStmt::Decl(vd) => SDef::VarDecl(vd),
+                    _ => SDef::VarDecl(SVarDecl {
+                        alias: self.alias(),
+                        typ: self.typ(),
+                        constant: false,
+                        is_static: false,
+                    }),
+                },
+                5 => SDef::StructDef(SStructDef {
+                    alias: self.alias(),
+                    fields: fields(self),
+                }),
+                // u is only ever a union's tag, as a tag's uses have to agree
+                // with its definition
+                6 => SDef::UnionDef(SStructDef {
+                    alias: "u".to_owned(),
+                    fields: fields(self),
+                }),
+                _ => SDef::EnumDef(SEnumDef {
+                    alias: self.coin().then(|| "e".to_owned()),
+                    enumerators: (0..self.below(3))
+                        .map(|i| (format!("E{i}"), self.below(50) as i32))
+                        .collect(),
+                }),
+            };
+            at(d)
+        }
+
+        fn prg(&mut self) -> SPrg {
+            (0..self.below(5) + 1).map(|_| self.def()).collect()
+        }
+    }
+}
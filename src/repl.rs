@@ -0,0 +1,343 @@
+use crate::{
+    evaluator,
+    layout::Layouts,
+    lexer::{self, Token, TT},
+    parser_ast, source, visualizer, Budget, Hook, IntModel, LambdaVal, Signal, Sink, Vnv, Width,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+
+// read-eval-print loop over the c0 interpreter. input is chunked
+// until braces balance, so function definitions can span lines.
+//   - int f(...) { ... }   defines f
+//   - struct s { ... };     defines s, as does union s { ... };
+//   - stmt                  runs stmt, printing the value of returns
+//   - expr;                 prints the value of expr
+//   - lval = expr;          assigns, to a variable or through a pointer
+//   - :dot                  dumps the environment as graphviz
+//   - :save path            writes the definitions and statements entered so far
+//   - :load path            runs a saved session (or any c0 source) into this one
+//   - :quit                 exits
+pub fn repl(input: impl BufRead, mut output: impl Write, model: IntModel) -> Result<(), io::Error> {
+    evaluator::interpretable(&model)?;
+    let mut nv = Vnv {
+        fnv: HashMap::new(),
+        vnv: HashMap::new(),
+        mem: RefCell::new(vec![(0, Width::Word)]),
+        rodata: RefCell::new(vec![]),
+        strs: RefCell::new(HashMap::new()),
+        types: RefCell::new(HashMap::new()),
+        heap: RefCell::new(vec![]),
+        model,
+        layouts: Layouts::default(), // grows as structs are defined
+        out: Sink::default(),
+        budget: Budget::default(),
+        used: RefCell::default(),
+        hook: Hook::default(),
+        calls: RefCell::default(),
+        uninit: RefCell::default(),
+    };
+
+    let mut session = String::new(); // the chunks worth replaying
+    let mut buf = String::new();
+    write!(output, "picoc> ")?;
+    output.flush()?;
+    for line in input.lines() {
+        let line = line?;
+
+        if buf.is_empty() && line.trim_start().starts_with(':') {
+            match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+                [":dot"] => write!(output, "{}", visualizer::gen_vnv_dot(&nv))?,
+                [":save", path] => {
+                    if let Err(e) = fs::write(path, &session) {
+                        writeln!(output, "picoc-error: {e}")?
+                    }
+                }
+                [":load", path] => {
+                    let loaded = fs::read(path).and_then(|bytes| {
+                        let src = source::decode(&bytes)?;
+                        let tokens = lexer::lex(&src).map_err(lexer::io_error)?;
+                        let mut r = tokens.as_slice();
+                        while !r.is_empty() {
+                            (_, r) = exec_item(r, &mut nv)?;
+                        }
+                        Ok(src)
+                    });
+                    match loaded {
+                        Ok(src) => session.push_str(&src),
+                        Err(e) => writeln!(output, "picoc-error: {e}")?,
+                    }
+                }
+                [":save" | ":load"] => writeln!(output, "picoc-error: expected: path")?,
+                [":quit"] => return Ok(()),
+                _ => writeln!(output, "picoc-error: unknown command: {}", line.trim())?,
+            }
+        } else {
+            buf.push_str(&line);
+            buf.push('\n');
+
+            let depth = buf.matches('{').count() as i32 - buf.matches('}').count() as i32;
+            if depth > 0 {
+                continue;
+            }
+
+            match lexer::lex(&buf)
+                .map_err(lexer::io_error)
+                .and_then(|tokens| exec(&tokens, &mut nv))
+            {
+                Ok((val, persist)) => {
+                    if persist {
+                        session.push_str(&buf);
+                    }
+                    if let Some(val) = val {
+                        writeln!(output, "{val}")?
+                    }
+                }
+                Err(e) => writeln!(output, "picoc-error: {e}")?,
+            }
+            buf.clear();
+        }
+
+        write!(output, "picoc> ")?;
+        output.flush()?;
+    }
+
+    Ok(())
+}
+
+// runs one chunk. definitions and statements change the environment,
+// so they persist into the session. expressions and returns don't, and
+// they're the ones with a value
+fn exec(tokens: &[Token], nv: &mut Vnv) -> Result<(Option<i32>, bool), io::Error> {
+    let (val, r) = exec_item(tokens, nv)?;
+    let persist = val.is_none() && !tokens.is_empty()
+        || matches!(
+            tokens.first().map(|t| &t.typ),
+            Some(
+                TT::KeywordInt
+                    | TT::KeywordChar
+                    | TT::KeywordShort
+                    | TT::KeywordLong
+                    | TT::KeywordSigned
+                    | TT::KeywordUnsigned
+                    | TT::KeywordFloat
+                    | TT::KeywordDouble
+                    | TT::KeywordIf
+                    | TT::KeywordWhile
+                    | TT::KeywordDo
+                    | TT::KeywordFor
+            )
+        );
+
+    if r.is_empty() {
+        Ok((val, persist))
+    } else {
+        Err(io::Error::other(format!("unexpected tokens: {:?}", r)))
+    }
+}
+
+// a type's specifiers, then `alias (`
+fn is_funcdef(tokens: &[Token]) -> bool {
+    let n = tokens
+        .iter()
+        .take_while(|t| t.typ == TT::KeywordVoid || parser_ast::is_arith_specifier(&t.typ))
+        .count();
+    n > 0 && matches!(&tokens[n..], [a, p, ..] if a.typ == TT::Alias && p.typ == TT::PuncLeftParen)
+}
+
+fn exec_item<'a>(
+    tokens: &'a [Token],
+    nv: &mut Vnv,
+) -> Result<(Option<i32>, &'a [Token]), io::Error> {
+    Ok(match tokens {
+        [] => (None, tokens),
+        [f, ..] if f.typ == TT::KeywordAttribute || is_funcdef(tokens) => {
+            let (fd, r) = parser_ast::parse_funcdef(tokens)?;
+            nv.fnv.insert(
+                fd.alias.clone(),
+                LambdaVal {
+                    fp: fd.fps.clone(),
+                    ret: fd.typ.clone(),
+                    body: fd.body,
+                    contracts: fd.contracts,
+                },
+            );
+            (None, r)
+        }
+        [f, _, b, ..]
+            if matches!(f.typ, TT::KeywordStruct | TT::KeywordUnion)
+                && b.typ == TT::PuncLeftBrace =>
+        {
+            let (sd, r) = parser_ast::parse_structdef(tokens)?;
+            nv.layouts.define(&sd, f.typ == TT::KeywordUnion)?;
+            (None, r)
+        }
+        [f, ..]
+            if matches!(
+                f.typ,
+                TT::KeywordInt
+                    | TT::KeywordChar
+                    | TT::KeywordShort
+                    | TT::KeywordLong
+                    | TT::KeywordSigned
+                    | TT::KeywordUnsigned
+                    | TT::KeywordFloat
+                    | TT::KeywordDouble
+                    | TT::KeywordRet
+                    | TT::KeywordIf
+                    | TT::KeywordWhile
+                    | TT::KeywordDo
+                    | TT::KeywordFor
+            ) =>
+        {
+            exec_stmt(tokens, nv)?
+        }
+        // what isn't an expr, like x = e; or *p = e;, is an assignment stmt
+        _ => match parser_ast::parse_expr(tokens) {
+            Ok((expr, [f, r @ ..])) if f.typ == TT::PuncSemiColon => {
+                let (mut vnv, mem) = (nv.vnv.clone(), nv.mem.clone());
+                let val =
+                    evaluator::eval_expr(&expr, nv, &mut vnv).inspect_err(|_| nv.mem = mem)?;
+                nv.vnv = vnv;
+                (Some(val), r)
+            }
+            _ => exec_stmt(tokens, nv)?,
+        },
+    })
+}
+
+fn exec_stmt<'a>(
+    tokens: &'a [Token],
+    nv: &mut Vnv,
+) -> Result<(Option<i32>, &'a [Token]), io::Error> {
+    // a failing stmt leaves the environment, and memory, as they were
+    let (stmt, r) = parser_ast::parse_stmt(tokens)?;
+    let (mut vnv, mem) = (nv.vnv.clone(), nv.mem.clone());
+    let val = evaluator::eval_stmt(&stmt, nv, &mut vnv)
+        .and_then(|sig| match sig {
+            Some(Signal::Ret(val)) => Ok(Some(val)),
+            Some(Signal::Break | Signal::Continue) => Err(io::Error::other("jump outside a loop")),
+            None => Ok(None),
+        })
+        .inspect_err(|_| nv.mem = mem)?;
+    nv.vnv = vnv;
+    Ok((val, r))
+}
+
+#[cfg(test)]
+mod test_repl {
+    use crate::IntModel;
+
+    #[test]
+    fn dot() {
+        let input = "int f(int x) {\n    return x + 10;\n}\nint y = 9;\nf(y);\n:dot\n";
+        let mut output = vec![];
+        super::repl(input.as_bytes(), &mut output, IntModel::default()).unwrap();
+        insta::assert_snapshot!(String::from_utf8(output).unwrap(), @r###"
+        picoc> picoc> picoc> 19
+        picoc> digraph vnv {
+        	rankdir=LR;
+        	node [shape=record];
+        	fnv [label="{fnv|<f> f(x)}"];
+        	vnv [label="{vnv|<m1> y = 9}"];
+        }
+        picoc> 
+        "###);
+    }
+
+    // assignments at the prompt are stmts, and persist like declarations
+    #[test]
+    fn assign() {
+        let path = std::env::temp_dir().join("picoc_assign.c0");
+        let path = path.to_str().unwrap();
+
+        let input = format!(
+            "int x = 1;\nx = x + 1;\nint *p = &x;\n*p = *p * 10;\nx;\n:dot\n:save {path}\n"
+        );
+        let mut output = vec![];
+        super::repl(input.as_bytes(), &mut output, IntModel::default()).unwrap();
+        insta::assert_snapshot!(String::from_utf8(output).unwrap(), @r###"
+        picoc> picoc> picoc> picoc> picoc> 20
+        picoc> digraph vnv {
+        	rankdir=LR;
+        	node [shape=record];
+        	fnv [label="{fnv}"];
+        	vnv [label="{vnv|<m2> p = •|<m1> x = 20}"];
+        	vnv:m2 -> vnv:m1;
+        }
+        picoc> picoc> 
+        "###);
+        let saved = std::fs::read_to_string(path).unwrap();
+        insta::assert_snapshot!(saved, @r###"
+        int x = 1;
+        x = x + 1;
+        int *p = &x;
+        *p = *p * 10;
+        "###);
+    }
+
+    #[test]
+    fn save_load() {
+        let path = std::env::temp_dir().join("picoc_save_load.c0");
+        let path = path.to_str().unwrap();
+
+        let input =
+            format!("int f(int x) {{\n    return x + 10;\n}}\nint y = 9;\nf(y);\n:save {path}\n");
+        super::repl(input.as_bytes(), &mut vec![], IntModel::default()).unwrap();
+        let saved = std::fs::read_to_string(path).unwrap();
+        insta::assert_snapshot!(saved, @r###"
+        int f(int x) {
+            return x + 10;
+        }
+        int y = 9;
+        "###);
+
+        let input = format!(":load {path}\nf(y);\n");
+        let mut output = vec![];
+        super::repl(input.as_bytes(), &mut output, IntModel::default()).unwrap();
+        insta::assert_snapshot!(String::from_utf8(output).unwrap(), @r###"
+        picoc> picoc> 19
+        picoc> 
+        "###);
+    }
+
+    // structs defined at the prompt are laid out as they're entered, and
+    // again when a session that defines them is loaded
+    #[test]
+    fn structs() {
+        let path = std::env::temp_dir().join("picoc_structs.c0");
+        let path = path.to_str().unwrap();
+
+        let input = format!(
+            "struct point {{\n    int x;\n    int y;\n}};\nstruct point p;\np.x = 3;\np.y = p.x * 2;\np.y;\n:dot\n:save {path}\n"
+        );
+        let mut output = vec![];
+        super::repl(input.as_bytes(), &mut output, IntModel::default()).unwrap();
+        insta::assert_snapshot!(String::from_utf8(output).unwrap(), @r###"
+        picoc> picoc> picoc> picoc> picoc> 6
+        picoc> digraph vnv {
+        	rankdir=LR;
+        	node [shape=record];
+        	fnv [label="{fnv}"];
+        	vnv [label="{vnv|{p|<m1> 3|<m2> 6}}"];
+        }
+        picoc> picoc>
+        "###);
+
+        let input = format!(":load {path}\np.x + p.y;\n:dot\n");
+        let mut output = vec![];
+        super::repl(input.as_bytes(), &mut output, IntModel::default()).unwrap();
+        insta::assert_snapshot!(String::from_utf8(output).unwrap(), @r###"
+        picoc> picoc> 9
+        picoc> digraph vnv {
+        	rankdir=LR;
+        	node [shape=record];
+        	fnv [label="{fnv}"];
+        	vnv [label="{vnv|{p|<m1> 3|<m2> 6}}"];
+        }
+        picoc>
+        "###);
+    }
+}
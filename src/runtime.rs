@@ -0,0 +1,557 @@
+use crate::asm::{self, imm, mem, op, reg, Insn};
+use crate::{IBinOp, Imm, IntModel, Label, PseudoOp, TQuad, TRegOp};
+use std::collections::HashMap;
+use std::iter;
+
+// tiny runtime linked into compiled programs so faults are reported
+// against the source program, rather than as a bare signal from the os.
+//   - __picoc_func points to the name of the running function. the
+//     allocator updates it on function entry and after every call
+//   - riscv doesn't trap on division by zero, so divisions branch to
+//     __picoc_div_zero themselves. SIGFPE is still handled for targets that do
+//   - nor on INT_MIN / -1, which is INT_MIN. under --checked-arith, signed
+//     divisions branch to __picoc_div_overflow for it
+//   - SIGSEGV and SIGFPE handlers are installed with sigaction from
+//     .init_array, before main. a report is written with write(2), which is
+//     safe in a handler where printf isn't, so its messages are preformatted
+//     and the function's name written after
+//   - faults are reported by function only. there's no line table
+//   - under --overflow=trap, signed add, sub and mult call __picoc_addv,
+//     __picoc_subv and __picoc_mulv, which branch to __picoc_overflow when
+//     the result doesn't fit in an int. they're emitted into the units that
+//     call them
+//   - strings live in one .rodata pool. identical contents are emitted once,
+//     with later labels aliased to the first. the program's string literals
+//     join it as __picoc_str_<n>, a byte per char like every other char in
+//     memory, so a char * to one can be passed to c functions like printf.
+//     writes into them through char* fault like any other invalid access
+// todo: sigaltstack, so stack overflows can be reported too
+
+// the checks before a division of l by r. t3 and t4 are free, as the
+// allocators only hand out s registers
+pub fn check_div(treg_op: &TRegOp, l: &str, r: &str, model: &IntModel) -> Vec<Insn> {
+    let zero = op("beqz", [reg(r), asm::sym("__picoc_div_zero")]).note("check divisor");
+    match (treg_op, model.checked_arith) {
+        (TRegOp::Div | TRegOp::Rem, true) => vec![
+            zero,
+            op("addi", [reg("t3"), reg(r), imm(1)]),
+            op("li", [reg("t4"), imm(model.int_min())]),
+            op("xor", [reg("t4"), reg(l), reg("t4")]),
+            op("or", [reg("t3"), reg("t3"), reg("t4")]),
+            op("beqz", [reg("t3"), asm::sym("__picoc_div_overflow")]).note("check INT_MIN / -1"),
+        ],
+        _ => vec![zero],
+    }
+}
+
+// the helper that checks op for signed overflow, if it's one that can
+pub fn checked(op: &IBinOp) -> Option<&'static str> {
+    match op {
+        IBinOp::Add => Some("__picoc_addv"),
+        IBinOp::Sub => Some("__picoc_subv"),
+        IBinOp::Mult => Some("__picoc_mulv"),
+        _ => None,
+    }
+}
+
+pub fn mark_func(f: &str, word: Imm) -> Vec<Insn> {
+    vec![
+        op("la", [reg("t0"), asm::sym(sym(f))]),
+        op("la", [reg("t1"), asm::sym("__picoc_func")]),
+        op(asm::store(word), [reg("t0"), mem(0, "t1")]).note(format!("mark {f}")),
+    ]
+}
+
+fn sym(f: &str) -> String {
+    format!("__picoc_sym_{f}")
+}
+
+// the program's string literals, numbered in order of first use
+pub fn strs(abs_as: &[TQuad]) -> Vec<&str> {
+    let mut strs = vec![];
+    for quad in abs_as {
+        if let TQuad::Str(_, s) = quad {
+            if !strs.contains(&s.as_str()) {
+                strs.push(s.as_str());
+            }
+        }
+    }
+    strs
+}
+
+pub fn str_sym(n: usize) -> String {
+    format!("__picoc_str_{n}")
+}
+
+// the bytes of a sigset_t, as glibc and musl lay it out
+const SIGSET: usize = 128;
+
+pub fn runtime(abs_as: &[TQuad], model: &IntModel) -> Vec<String> {
+    let word = model.word();
+    let msg = |err| format!("runtime error: {err} near function ");
+    let strs = [
+        ("__picoc_msg_div_zero".to_owned(), msg("division by zero")),
+        ("__picoc_msg_segv".to_owned(), msg("invalid memory access")),
+        (
+            "__picoc_msg_overflow".to_owned(),
+            msg("signed integer overflow"),
+        ),
+        (
+            "__picoc_msg_div_overflow".to_owned(),
+            msg("integer division overflow"),
+        ),
+        ("__picoc_nl".to_owned(), "\\n".to_owned()),
+        ("__picoc_sym_unknown".to_owned(), "?".to_owned()),
+    ]
+    .map(|(label, contents)| (label, format!(".string \"{contents}\"")));
+
+    let (literals, syms) = pools(abs_as);
+    let rodata = pool(literals.into_iter().chain(strs).chain(syms), word);
+
+    let (words, align) = (asm::words(word), asm::align(word).to_string());
+    let (load, store) = (asm::load(word), asm::store(word));
+    // a struct sigaction for each handler: the handler, then a zeroed mask,
+    // flags and restorer
+    let sigaction = |label, handler| {
+        vec![
+            format!("{label}: {words} {handler}"),
+            format!(".zero {}", SIGSET + 2 * word as usize),
+        ]
+    };
+    let data = [
+        vec![
+            ".section .data".to_owned(),
+            align.clone(),
+            format!("__picoc_func: {words} __picoc_sym_unknown"),
+        ],
+        sigaction("__picoc_sa_segv", "__picoc_segv"),
+        sigaction("__picoc_sa_fpe", "__picoc_div_zero"),
+        vec![
+            ".section .init_array,\"aw\"".to_owned(),
+            align,
+            format!("{words} __picoc_init"),
+        ],
+    ]
+    .concat();
+
+    let text = vec![
+        ".section .text".to_owned(),
+        "__picoc_init:".to_owned(),
+        "addi sp, sp, -16".to_owned(),
+        format!("{store} ra, {}(sp)", 16 - word),
+        "li a0, 11 # SIGSEGV".to_owned(),
+        "la a1, __picoc_sa_segv".to_owned(),
+        "li a2, 0".to_owned(),
+        "call sigaction".to_owned(),
+        "li a0, 8 # SIGFPE".to_owned(),
+        "la a1, __picoc_sa_fpe".to_owned(),
+        "li a2, 0".to_owned(),
+        "call sigaction".to_owned(),
+        format!("{load} ra, {}(sp)", 16 - word),
+        "addi sp, sp, 16".to_owned(),
+        "ret".to_owned(),
+        "__picoc_segv:".to_owned(),
+        "la a2, __picoc_msg_segv".to_owned(),
+        "j __picoc_report".to_owned(),
+        "__picoc_overflow:".to_owned(),
+        "la a2, __picoc_msg_overflow".to_owned(),
+        "j __picoc_report".to_owned(),
+        "__picoc_div_overflow:".to_owned(),
+        "la a2, __picoc_msg_div_overflow".to_owned(),
+        "j __picoc_report".to_owned(),
+        "__picoc_div_zero:".to_owned(),
+        "la a2, __picoc_msg_div_zero".to_owned(),
+        "__picoc_report:".to_owned(),
+        "andi sp, sp, -16".to_owned(),
+        "mv a1, a2".to_owned(),
+        "call __picoc_puts".to_owned(),
+        "la t0, __picoc_func".to_owned(),
+        format!("{load} a1, 0(t0)"),
+        "call __picoc_puts".to_owned(),
+        "la a1, __picoc_nl".to_owned(),
+        "call __picoc_puts".to_owned(),
+        "li a0, 1".to_owned(),
+        "call _exit".to_owned(),
+        // writes the string at a1 to stderr
+        "__picoc_puts:".to_owned(),
+        "mv t0, a1".to_owned(),
+        ".Lpicoc_puts_len:".to_owned(),
+        "lbu t1, 0(t0)".to_owned(),
+        "beqz t1, .Lpicoc_puts_write".to_owned(),
+        "addi t0, t0, 1".to_owned(),
+        "j .Lpicoc_puts_len".to_owned(),
+        ".Lpicoc_puts_write:".to_owned(),
+        "sub a2, t0, a1".to_owned(),
+        "li a0, 2 # stderr".to_owned(),
+        "tail write".to_owned(),
+    ];
+
+    let checks = checks(abs_as, model);
+    rodata
+        .into_iter()
+        .chain(data)
+        .chain(text)
+        .chain(checks)
+        .collect()
+}
+
+// the runtime for a unit linked with the one holding main, which has the
+// rest: the unit's own literals and symbols, and the checks it calls
+pub fn unit(abs_as: &[TQuad], model: &IntModel) -> Vec<String> {
+    let word = model.word();
+    let (literals, syms) = pools(abs_as);
+    let rodata = pool(literals.into_iter().chain(syms), word);
+    [rodata, checks(abs_as, model)].concat()
+}
+
+// what the other units use of the runtime in main's
+pub fn exports() -> Vec<String> {
+    vec![
+        ".globl __picoc_func".to_owned(),
+        ".globl __picoc_div_zero".to_owned(),
+        ".globl __picoc_overflow".to_owned(),
+        ".globl __picoc_div_overflow".to_owned(),
+    ]
+}
+
+// the overflow checking helpers the program calls, which take their
+// operands in a0 and a1 and return the result in a0, as c functions do.
+// when ints are narrower than half a register, as they are on rv64 or when
+// they're 16 bits, the exact result fits in one, and overflowed if
+// narrowing it changes it. otherwise the sign of the result is checked
+// against the operands', and a product's high word against its sign
+fn checks(abs_as: &[TQuad], model: &IntModel) -> Vec<String> {
+    let called = |helper: &str| {
+        abs_as.iter().any(|quad| {
+            matches!(quad, TQuad::Pseudo(PseudoOp::CallC(Label::UserLabel(f)) | PseudoOp::Call(Label::UserLabel(f))) if f == helper)
+        })
+    };
+    let xlen = model.word() * 8;
+    let ext = xlen - model.int_width as Imm;
+    let (slli, srai) = (format!("slli t1, t0, {ext}"), format!("srai t1, t1, {ext}"));
+    [IBinOp::Add, IBinOp::Sub, IBinOp::Mult]
+        .iter()
+        .filter_map(|op| {
+            checked(op)
+                .filter(|helper| called(helper))
+                .map(|helper| (op, helper))
+        })
+        .flat_map(|(op, helper)| {
+            let operate = match op {
+                IBinOp::Add => "add t0, a0, a1",
+                IBinOp::Sub => "sub t0, a0, a1",
+                _ => "mul t0, a0, a1",
+            };
+            let check = match (ext >= model.int_width as Imm, op) {
+                (true, _) => vec![
+                    slli.clone(),
+                    srai.clone(),
+                    "bne t0, t1, __picoc_overflow".to_owned(),
+                ],
+                (false, IBinOp::Add) => vec![
+                    "slt t1, t0, a0".to_owned(),
+                    "slti t2, a1, 0".to_owned(),
+                    "bne t1, t2, __picoc_overflow".to_owned(),
+                ],
+                (false, IBinOp::Sub) => vec![
+                    "slt t1, a0, t0".to_owned(),
+                    "slti t2, a1, 0".to_owned(),
+                    "bne t1, t2, __picoc_overflow".to_owned(),
+                ],
+                (false, _) => vec![
+                    "mulh t1, a0, a1".to_owned(),
+                    format!("srai t2, t0, {}", xlen - 1),
+                    "bne t1, t2, __picoc_overflow".to_owned(),
+                ],
+            };
+            [
+                vec![
+                    ".section .text".to_owned(),
+                    format!("{helper}:"),
+                    operate.to_owned(),
+                ],
+                check,
+                vec!["mv a0, t0".to_owned(), "ret".to_owned()],
+            ]
+            .concat()
+        })
+        .collect()
+}
+
+type Pooled = Vec<(String, String)>;
+
+// the string literals, and the symbol table with the name of each function
+fn pools(abs_as: &[TQuad]) -> (Pooled, Pooled) {
+    let syms = abs_as.iter().filter_map(|quad| match quad {
+        TQuad::Label(Label::UserLabel(f)) => Some((sym(f), format!(".string \"{f}\""))),
+        _ => None,
+    });
+    let literals = self::strs(abs_as).into_iter().enumerate().map(|(n, s)| {
+        let bytes = s.bytes().chain(iter::once(0)).map(|b| b.to_string());
+        (
+            str_sym(n),
+            format!(".byte {}", bytes.collect::<Vec<_>>().join(", ")),
+        )
+    });
+
+    (literals.collect(), syms.collect())
+}
+
+// emits (label, directive) pairs as .rodata, deduplicating identical contents
+fn pool(strs: impl Iterator<Item = (String, String)>, word: Imm) -> Vec<String> {
+    let mut pooled: HashMap<String, String> = HashMap::new();
+    let entries = strs.map(|(label, contents)| match pooled.get(&contents) {
+        Some(first) => format!(".set {label}, {first}"),
+        None => {
+            let entry = format!("{label}: {contents}");
+            pooled.insert(contents, label);
+            entry
+        }
+    });
+
+    [".section .rodata".to_owned(), asm::align(word).to_string()]
+        .into_iter()
+        .chain(entries)
+        .collect()
+}
+
+#[cfg(test)]
+mod test_functions {
+    use crate::allocator;
+    use crate::lexer;
+    use crate::parser_ast;
+    use crate::selector;
+    use crate::translator;
+    use crate::typer;
+    use crate::{IntModel, OptLevel};
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/bindings";
+
+    #[test]
+    fn composition() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/composition.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let runtime = super::runtime(&abs_as, &IntModel::default());
+        insta::assert_yaml_snapshot!(runtime, @r###"
+        ---
+        - ".section .rodata"
+        - ".align 2"
+        - "__picoc_msg_div_zero: .string \"runtime error: division by zero near function \""
+        - "__picoc_msg_segv: .string \"runtime error: invalid memory access near function \""
+        - "__picoc_msg_overflow: .string \"runtime error: signed integer overflow near function \""
+        - "__picoc_msg_div_overflow: .string \"runtime error: integer division overflow near function \""
+        - "__picoc_nl: .string \"\\n\""
+        - "__picoc_sym_unknown: .string \"?\""
+        - "__picoc_sym_h: .string \"h\""
+        - "__picoc_sym_g: .string \"g\""
+        - "__picoc_sym_f: .string \"f\""
+        - "__picoc_sym_main: .string \"main\""
+        - ".section .data"
+        - ".align 2"
+        - "__picoc_func: .word __picoc_sym_unknown"
+        - "__picoc_sa_segv: .word __picoc_segv"
+        - ".zero 136"
+        - "__picoc_sa_fpe: .word __picoc_div_zero"
+        - ".zero 136"
+        - ".section .init_array,\"aw\""
+        - ".align 2"
+        - ".word __picoc_init"
+        - ".section .text"
+        - "__picoc_init:"
+        - "addi sp, sp, -16"
+        - "sw ra, 12(sp)"
+        - "li a0, 11 # SIGSEGV"
+        - "la a1, __picoc_sa_segv"
+        - "li a2, 0"
+        - call sigaction
+        - "li a0, 8 # SIGFPE"
+        - "la a1, __picoc_sa_fpe"
+        - "li a2, 0"
+        - call sigaction
+        - "lw ra, 12(sp)"
+        - "addi sp, sp, 16"
+        - ret
+        - "__picoc_segv:"
+        - "la a2, __picoc_msg_segv"
+        - j __picoc_report
+        - "__picoc_overflow:"
+        - "la a2, __picoc_msg_overflow"
+        - j __picoc_report
+        - "__picoc_div_overflow:"
+        - "la a2, __picoc_msg_div_overflow"
+        - j __picoc_report
+        - "__picoc_div_zero:"
+        - "la a2, __picoc_msg_div_zero"
+        - "__picoc_report:"
+        - "andi sp, sp, -16"
+        - "mv a1, a2"
+        - call __picoc_puts
+        - "la t0, __picoc_func"
+        - "lw a1, 0(t0)"
+        - call __picoc_puts
+        - "la a1, __picoc_nl"
+        - call __picoc_puts
+        - "li a0, 1"
+        - call _exit
+        - "__picoc_puts:"
+        - "mv t0, a1"
+        - ".Lpicoc_puts_len:"
+        - "lbu t1, 0(t0)"
+        - "beqz t1, .Lpicoc_puts_write"
+        - "addi t0, t0, 1"
+        - j .Lpicoc_puts_len
+        - ".Lpicoc_puts_write:"
+        - "sub a2, t0, a1"
+        - "li a0, 2 # stderr"
+        - tail write
+        "###);
+    }
+
+    #[test]
+    fn strs() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/strs.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let rodata = super::runtime(&abs_as, &IntModel::default())
+            .into_iter()
+            .take_while(|l| l != ".section .data")
+            .collect::<Vec<_>>();
+        insta::assert_yaml_snapshot!(rodata, @r###"
+        ---
+        - ".section .rodata"
+        - ".align 2"
+        - "__picoc_str_0: .byte 104, 105, 9, 116, 104, 101, 114, 101, 10, 0"
+        - "__picoc_str_1: .byte 0"
+        - "__picoc_msg_div_zero: .string \"runtime error: division by zero near function \""
+        - "__picoc_msg_segv: .string \"runtime error: invalid memory access near function \""
+        - "__picoc_msg_overflow: .string \"runtime error: signed integer overflow near function \""
+        - "__picoc_msg_div_overflow: .string \"runtime error: integer division overflow near function \""
+        - "__picoc_nl: .string \"\\n\""
+        - "__picoc_sym_unknown: .string \"?\""
+        - "__picoc_sym_main: .string \"main\""
+        "###);
+    }
+
+    #[test]
+    fn printf() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/printf.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let strs = super::runtime(&abs_as, &IntModel::default())
+            .into_iter()
+            .filter(|l| l.starts_with("__picoc_str"))
+            .collect::<Vec<_>>();
+        insta::assert_yaml_snapshot!(strs, @r###"
+        ---
+        - "__picoc_str_0: .byte 37, 100, 32, 37, 99, 10, 0"
+        - "__picoc_str_1: .byte 100, 111, 110, 101, 10, 0"
+        "###);
+    }
+
+    #[test]
+    fn printf_str() {
+        // a char * to a literal is passed to printf as is, since every
+        // literal is pooled as bytes
+        let src = fs::read_to_string(format!("{TEST_DIR}/printf_str.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = allocator::allocate(&abs_as, OptLevel::O0, &IntModel::default()).render();
+        let strs = assembly
+            .into_iter()
+            .chain(super::runtime(&abs_as, &IntModel::default()))
+            .filter(|l| l.contains("__picoc_str"))
+            .collect::<Vec<_>>();
+        insta::assert_yaml_snapshot!(strs, @r###"
+        ---
+        - "la t2, __picoc_str_0 # string literal"
+        - "la t2, __picoc_str_1 # string literal"
+        - "__picoc_str_0: .byte 104, 105, 0"
+        - "__picoc_str_1: .byte 37, 115, 10, 0"
+        "###);
+    }
+}
+
+#[cfg(test)]
+mod test_pool {
+    use super::pool;
+
+    fn strs(strs: &[(&str, &str)]) -> std::vec::IntoIter<(String, String)> {
+        let strs = strs.iter().map(|(l, c)| (l.to_string(), c.to_string()));
+        strs.collect::<Vec<_>>().into_iter()
+    }
+
+    #[test]
+    fn allocation() {
+        let rodata = pool(strs(&[("a", ".word 104, 0"), ("b", ".word 0")]), 4);
+        insta::assert_yaml_snapshot!(rodata, @r###"
+        ---
+        - ".section .rodata"
+        - ".align 2"
+        - "a: .word 104, 0"
+        - "b: .word 0"
+        "###);
+    }
+
+    #[test]
+    fn reuse() {
+        let rodata = pool(
+            strs(&[
+                ("a", ".word 104, 0"),
+                ("b", ".word 0"),
+                ("c", ".word 104, 0"),
+                ("d", ".word 0"),
+                ("e", ".word 104, 0"),
+            ]),
+            8,
+        );
+        insta::assert_yaml_snapshot!(rodata, @r###"
+        ---
+        - ".section .rodata"
+        - ".align 3"
+        - "a: .word 104, 0"
+        - "b: .word 0"
+        - ".set c, a"
+        - ".set d, b"
+        - ".set e, a"
+        "###);
+    }
+
+    #[test]
+    fn exhaustion() {
+        // an empty pool still opens the section, so the literals that follow
+        // land in .rodata
+        let rodata = pool(strs(&[]), 4);
+        insta::assert_yaml_snapshot!(rodata, @r###"
+        ---
+        - ".section .rodata"
+        - ".align 2"
+        "###);
+
+        // a literal repeated past any sensible count is still defined once
+        let many = (0..1000).map(|n| (format!("s{n}"), ".word 0".to_owned()));
+        let rodata = pool(many, 4);
+        assert_eq!(rodata.len(), 2 + 1000);
+        assert_eq!(rodata[2], "s0: .word 0");
+        assert!(rodata[3..]
+            .iter()
+            .all(|l| l.starts_with(".set ") && l.ends_with(", s0")));
+    }
+}
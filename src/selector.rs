@@ -1,104 +1,297 @@
 use crate::{
-    fresh_temp, IBinOp, IExpr, IPrg, IStmt, PseudoOp, RiscvPointerReg, TImmOp, TMemOp, TQuad,
-    TRegOp, Temp,
+    fresh_label, fresh_temp, IBinOp, IBitOp, ICvtOp, IExpr, IFloatOp, ILogOp, IPrg, IStmt, Imm,
+    IntModel, Label, PseudoOp, RiscvPointerReg, TCvtOp, TFloatOp, TImmOp, TMemOp, TQuad, TRegOp,
+    Temp, ARG_REGS,
 };
+use std::collections::{HashMap, HashSet};
+use std::iter;
 
 pub fn select(prg: &IPrg) -> Vec<TQuad> {
-    let trgt_prg = prg.iter().flat_map(select_stmt).collect();
+    select_linked(prg, &HashSet::new(), &IntModel::default())
+}
+
+// as select, for a unit linked with others. linked are the functions the
+// others define, which are called as picoc functions rather than c ones.
+// frames are laid out in the model's words
+pub fn select_linked(prg: &IPrg, linked: &HashSet<&str>, model: &IntModel) -> Vec<TQuad> {
+    let statics = prg
+        .iter()
+        .filter_map(|stmt| match stmt {
+            IStmt::Static(alias, _, _) => Some(alias.as_str()),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+
+    let funcs = prg
+        .iter()
+        .filter_map(|stmt| match stmt {
+            IStmt::Seq(Label::UserLabel(f), _) => Some(f.as_str()),
+            _ => None,
+        })
+        .chain(linked.iter().copied())
+        .collect::<HashSet<_>>();
+
+    let trgt_prg = prg
+        .iter()
+        .flat_map(|stmt| match stmt {
+            IStmt::Seq(l, stmts) => select_func(l, stmts, &statics, &funcs, model.word()),
+            IStmt::Static(alias, words, global) => {
+                vec![TQuad::Data(alias.clone(), words.clone(), *global)]
+            }
+            _ => todo!(),
+        })
+        .collect();
     trgt_prg
 }
 
-fn select_stmt(s: &IStmt) -> Vec<TQuad> {
+// frame layout, growing down from fp, in words of 4 bytes on rv32 and 8
+// on rv64. on rv32:
+//   -4(fp)    caller's ra
+//   -8(fp)    caller's fp
+//   -12(fp)   first local
+//   ...
+// the size is rounded up to 16 bytes to keep sp aligned.
+// formal params are spilled to slots on entry, so they are locals too.
+// arrays and structs take as many slots as their bytes need, with a
+// local's offset at the first (lowest) one, so indexing counts up from it.
+// a nested scope's slots start where its enclosing scope's end, so sibling
+// scopes overlap and the frame is only as big as the deepest nesting.
+// statics have no slot, and are loaded and stored through their symbol
+struct Frame<'a> {
+    slots: HashMap<String, Imm>,
+    size: Imm,
+    word: Imm,
+    ret: Label,                  // returns jump to the epilogue
+    funcs: &'a HashSet<&'a str>, // defined in this file or one it's linked with. any other is compiled by a c compiler
+}
+
+impl<'a> Frame<'a> {
+    fn new(
+        stmts: &[Box<IStmt>],
+        statics: &HashSet<&str>,
+        funcs: &'a HashSet<&'a str>,
+        word: Imm,
+    ) -> Self {
+        let mut slots = HashMap::new();
+        let words = Self::lay_out(stmts, statics, &mut slots, 0);
+        for off in slots.values_mut() {
+            *off *= word;
+        }
+
+        let size = ((2 + words as Imm) * word + 15) / 16 * 16;
+        Self {
+            slots,
+            size,
+            word,
+            ret: fresh_label(),
+            funcs,
+        }
+    }
+
+    // returns the most words the scope ever has in use. slots are in words
+    // until new scales them
+    fn lay_out(
+        stmts: &[Box<IStmt>],
+        statics: &HashSet<&str>,
+        slots: &mut HashMap<String, Imm>,
+        base: usize,
+    ) -> usize {
+        let (mut words, mut high) = (base, base);
+        for stmt in stmts {
+            let (alias, n) = match stmt.as_ref() {
+                IStmt::Compute(Temp::UserTemp(alias), _) if statics.contains(alias.as_str()) => {
+                    continue
+                }
+                IStmt::Compute(Temp::UserTemp(alias), _) => (alias, 1),
+                IStmt::Alloca(Temp::UserTemp(alias), n) => (alias, *n),
+                IStmt::Scope(stmts) => {
+                    high = high.max(Self::lay_out(stmts, statics, slots, words));
+                    continue;
+                }
+                _ => continue,
+            };
+            slots.entry(alias.clone()).or_insert_with(|| {
+                words += n;
+                -2 - words as Imm
+            });
+        }
+
+        high.max(words)
+    }
+
+    // None for statics
+    fn slot(&self, alias: &str) -> Option<Imm> {
+        self.slots.get(alias).copied()
+    }
+}
+
+fn select_stmt(s: &IStmt, frame: &Frame) -> Vec<TQuad> {
     match s {
-        IStmt::Jump(_) => todo!(),
-        IStmt::CJump(_sexpr, _, _) => todo!(),
-        IStmt::Compute(_temp, _iexpr) => todo!(),
-        IStmt::Load(_, _) => todo!(),
-        IStmt::Store(_, _) => todo!(),
-        IStmt::Seq(l, stmts) => {
-            let prologue = vec![
-                TQuad::Label(l.clone()),
-                // allocate 4 words
-                TQuad::Imm(
-                    TImmOp::AddI,
-                    Temp::PointerReg(RiscvPointerReg::Sp),
-                    Temp::PointerReg(RiscvPointerReg::Sp),
-                    -16,
-                ),
-                // save caller's ra
-                TQuad::Mem(
-                    TMemOp::Store,
-                    Temp::PointerReg(RiscvPointerReg::Ra),
-                    12,
-                    RiscvPointerReg::Sp,
-                ),
-                // save caller's fp (s0)
-                TQuad::Mem(
-                    TMemOp::Store,
-                    Temp::PointerReg(RiscvPointerReg::Fp),
-                    8,
-                    RiscvPointerReg::Sp,
-                ),
-                // setup callee's fp
-                TQuad::Imm(
-                    TImmOp::AddI,
-                    Temp::PointerReg(RiscvPointerReg::Fp),
-                    Temp::PointerReg(RiscvPointerReg::Sp),
-                    16,
+        IStmt::Jump(l) => vec![TQuad::Pseudo(PseudoOp::Jump(l.clone()))],
+        IStmt::CJump(iexpr, t, f) => {
+            let c = fresh_temp();
+            let cond_instrs = select_expr(c.clone(), iexpr, frame);
+            let jump_instrs = vec![
+                // fall through is not guaranteed, so jump to both targets
+                TQuad::Branch(
+                    TRegOp::Beq,
+                    c,
+                    Temp::PointerReg(RiscvPointerReg::Z),
+                    f.clone(),
                 ),
+                TQuad::Pseudo(PseudoOp::Jump(t.clone())),
             ];
 
-            let _fps: Vec<TQuad> = vec![];
+            cond_instrs.into_iter().chain(jump_instrs).collect()
+        }
+        IStmt::Label(l) => vec![TQuad::Label(l.clone())],
+        IStmt::JumpTable(iexpr, ls) => {
+            let t = fresh_temp();
+            select_expr(t.clone(), iexpr, frame)
+                .into_iter()
+                .chain(iter::once(TQuad::JumpTable(t, fresh_label(), ls.clone())))
+                .collect()
+        }
+        IStmt::Compute(Temp::UserTemp(alias), iexpr) => {
+            let t = fresh_temp();
+            let expr_instrs = select_expr(t.clone(), iexpr, frame);
+            let store_instrs = match frame.slot(alias) {
+                Some(offset) => vec![TQuad::Mem(TMemOp::Store, t, offset, RiscvPointerReg::Fp)],
+                None => {
+                    let a = fresh_temp();
+                    vec![
+                        TQuad::Static(a.clone(), alias.clone()),
+                        TQuad::Deref(TMemOp::Store, t, a),
+                    ]
+                }
+            };
 
-            let body = stmts
-                .iter()
-                .flat_map(|stmt| select_stmt(stmt))
-                .collect::<Vec<_>>();
-
-            let epilogue = vec![
-                // restore ra
-                TQuad::Mem(
-                    TMemOp::Load,
-                    Temp::PointerReg(RiscvPointerReg::Ra),
-                    12,
-                    RiscvPointerReg::Sp,
-                ),
-                // restore fp
-                TQuad::Mem(
-                    TMemOp::Load,
-                    Temp::PointerReg(RiscvPointerReg::Fp),
-                    8,
-                    RiscvPointerReg::Sp,
-                ),
-                // deallocate 4 words
-                TQuad::Imm(
+            expr_instrs.into_iter().chain(store_instrs).collect()
+        }
+        IStmt::Compute(Temp::PointerReg(RiscvPointerReg::Z), iexpr) => {
+            // addi zero, t0, 0 pops the value and drops it
+            let t = fresh_temp();
+            select_expr(t.clone(), iexpr, frame)
+                .into_iter()
+                .chain(iter::once(TQuad::Imm(
                     TImmOp::AddI,
-                    Temp::PointerReg(RiscvPointerReg::Sp),
-                    Temp::PointerReg(RiscvPointerReg::Sp),
-                    16,
-                ),
-                // ret
-                TQuad::Pseudo(PseudoOp::Ret),
-            ];
-
-            prologue.into_iter().chain(body).chain(epilogue).collect()
+                    Temp::PointerReg(RiscvPointerReg::Z),
+                    t,
+                    0,
+                )))
+                .collect()
+        }
+        IStmt::Compute(_temp, _iexpr) => todo!(),
+        IStmt::Alloca(_, _) => vec![], // the frame already has room
+        IStmt::Static(_, _, _) => todo!(), // only at the top level
+        IStmt::Scope(stmts) => stmts.iter().flat_map(|s| select_stmt(s, frame)).collect(),
+        IStmt::Move(addr, val) | IStmt::MoveByte(addr, val) => {
+            let op = match s {
+                IStmt::MoveByte(..) => TMemOp::StoreByte,
+                _ => TMemOp::Store,
+            };
+            let (vt, at) = (fresh_temp(), fresh_temp());
+            select_expr(vt.clone(), val, frame)
+                .into_iter()
+                .chain(select_expr(at.clone(), addr, frame))
+                .chain(iter::once(TQuad::Deref(op, vt, at)))
+                .collect()
         }
-        IStmt::Return(iexpr) => {
+        IStmt::Load(_, _) => todo!(),
+        IStmt::Store(_, _) => todo!(),
+        IStmt::Seq(_, _) => todo!(), // only at the top level
+        IStmt::Return(Some(iexpr)) => {
             let t = fresh_temp();
-            let expr_instrs = select_expr(t.clone(), iexpr);
-            let ret_instr = vec![TQuad::Imm(
-                TImmOp::AddI,
-                Temp::PointerReg(RiscvPointerReg::A0),
-                t,
-                0,
-            )];
+            let expr_instrs = select_expr(t.clone(), iexpr, frame);
+            let ret_instrs = vec![
+                TQuad::Imm(TImmOp::AddI, Temp::PointerReg(RiscvPointerReg::A0), t, 0),
+                TQuad::Pseudo(PseudoOp::Jump(frame.ret.clone())),
+            ];
 
-            expr_instrs.into_iter().chain(ret_instr).collect()
+            expr_instrs.into_iter().chain(ret_instrs).collect()
         }
+        IStmt::Return(None) => vec![TQuad::Pseudo(PseudoOp::Jump(frame.ret.clone()))], // a0 is left as is
     }
 }
 
-fn select_expr(d: Temp, e: &IExpr) -> Vec<TQuad> {
+fn select_func(
+    l: &Label,
+    stmts: &[Box<IStmt>],
+    statics: &HashSet<&str>,
+    funcs: &HashSet<&str>,
+    word: Imm,
+) -> Vec<TQuad> {
+    let frame = Frame::new(stmts, statics, funcs, word);
+    let prologue = vec![
+        TQuad::Label(l.clone()),
+        // allocate frame
+        TQuad::Imm(
+            TImmOp::AddI,
+            Temp::PointerReg(RiscvPointerReg::Sp),
+            Temp::PointerReg(RiscvPointerReg::Sp),
+            -frame.size,
+        ),
+        // save caller's ra
+        TQuad::Mem(
+            TMemOp::Store,
+            Temp::PointerReg(RiscvPointerReg::Ra),
+            frame.size - frame.word,
+            RiscvPointerReg::Sp,
+        ),
+        // save caller's fp (s0)
+        TQuad::Mem(
+            TMemOp::Store,
+            Temp::PointerReg(RiscvPointerReg::Fp),
+            frame.size - 2 * frame.word,
+            RiscvPointerReg::Sp,
+        ),
+        // setup callee's fp
+        TQuad::Imm(
+            TImmOp::AddI,
+            Temp::PointerReg(RiscvPointerReg::Fp),
+            Temp::PointerReg(RiscvPointerReg::Sp),
+            frame.size,
+        ),
+    ];
+
+    let _fps: Vec<TQuad> = vec![];
+
+    let body = stmts
+        .iter()
+        .flat_map(|stmt| select_stmt(stmt, &frame))
+        .collect::<Vec<_>>();
+
+    let epilogue = vec![
+        TQuad::Label(frame.ret.clone()),
+        // restore ra
+        TQuad::Mem(
+            TMemOp::Load,
+            Temp::PointerReg(RiscvPointerReg::Ra),
+            frame.size - frame.word,
+            RiscvPointerReg::Sp,
+        ),
+        // restore fp
+        TQuad::Mem(
+            TMemOp::Load,
+            Temp::PointerReg(RiscvPointerReg::Fp),
+            frame.size - 2 * frame.word,
+            RiscvPointerReg::Sp,
+        ),
+        // deallocate frame
+        TQuad::Imm(
+            TImmOp::AddI,
+            Temp::PointerReg(RiscvPointerReg::Sp),
+            Temp::PointerReg(RiscvPointerReg::Sp),
+            frame.size,
+        ),
+        // ret
+        TQuad::Pseudo(PseudoOp::Ret),
+    ];
+
+    prologue.into_iter().chain(body).chain(epilogue).collect()
+}
+
+fn select_expr(d: Temp, e: &IExpr, frame: &Frame) -> Vec<TQuad> {
     match e {
         IExpr::Const(n) => vec![TQuad::Imm(
             TImmOp::AddI,
@@ -107,42 +300,231 @@ fn select_expr(d: Temp, e: &IExpr) -> Vec<TQuad> {
             *n,
         )],
         IExpr::BinOp(op, l, r) => {
+            let (ltemp, rtemp) = (fresh_temp(), fresh_temp());
+            let (lq, rq) = (
+                select_expr(ltemp.clone(), l, frame),
+                select_expr(rtemp.clone(), r, frame),
+            );
+            let instrs = match op {
+                IBinOp::Add => vec![TQuad::Reg(TRegOp::Add, d, ltemp, rtemp)],
+                IBinOp::AddPtr => vec![TQuad::Reg(TRegOp::AddPtr, d, ltemp, rtemp)],
+                IBinOp::Sub => vec![TQuad::Reg(TRegOp::Sub, d, ltemp, rtemp)],
+                IBinOp::Mult => vec![TQuad::Reg(TRegOp::Mul, d, ltemp, rtemp)], // RV32M
+                IBinOp::Div => vec![TQuad::Reg(TRegOp::Div, d, ltemp, rtemp)],  // RV32M
+                IBinOp::Mod => vec![TQuad::Reg(TRegOp::Rem, d, ltemp, rtemp)],  // RV32M
+                IBinOp::Lt => vec![TQuad::Reg(TRegOp::Slt, d, ltemp, rtemp)],
+                IBinOp::Gt => vec![TQuad::Reg(TRegOp::Sgt, d, ltemp, rtemp)],
+                // riscv only sets on less than, so the rest are built from it
+                IBinOp::LtEq => not(d, TQuad::Reg(TRegOp::Sgt, fresh_temp(), ltemp, rtemp)),
+                IBinOp::GtEq => not(d, TQuad::Reg(TRegOp::Slt, fresh_temp(), ltemp, rtemp)),
+                IBinOp::AddU => vec![TQuad::Reg(TRegOp::Addu, d, ltemp, rtemp)],
+                IBinOp::SubU => vec![TQuad::Reg(TRegOp::Subu, d, ltemp, rtemp)],
+                IBinOp::MultU => vec![TQuad::Reg(TRegOp::Mulu, d, ltemp, rtemp)],
+                IBinOp::DivU => vec![TQuad::Reg(TRegOp::Divu, d, ltemp, rtemp)],
+                IBinOp::ModU => vec![TQuad::Reg(TRegOp::Remu, d, ltemp, rtemp)],
+                IBinOp::LtU => vec![TQuad::Reg(TRegOp::Sltu, d, ltemp, rtemp)],
+                IBinOp::GtU => vec![TQuad::Reg(TRegOp::Sgtu, d, ltemp, rtemp)],
+                IBinOp::LtEqU => not(d, TQuad::Reg(TRegOp::Sgtu, fresh_temp(), ltemp, rtemp)),
+                IBinOp::GtEqU => not(d, TQuad::Reg(TRegOp::Sltu, fresh_temp(), ltemp, rtemp)),
+                IBinOp::Eq => {
+                    let t = fresh_temp();
+                    vec![
+                        TQuad::Reg(TRegOp::Xor, t.clone(), ltemp, rtemp),
+                        TQuad::Imm(TImmOp::SltIU, d, t, 1), // seqz
+                    ]
+                }
+                IBinOp::Neq => {
+                    let t = fresh_temp();
+                    let eq = vec![TQuad::Reg(TRegOp::Xor, t.clone(), ltemp, rtemp)];
+                    eq.into_iter()
+                        .chain(not(d, TQuad::Imm(TImmOp::SltIU, fresh_temp(), t, 1)))
+                        .collect()
+                }
+            };
+
+            lq.into_iter().chain(rq).chain(instrs).collect()
+        }
+        IExpr::BitOp(op, l, r) => {
+            let (ltemp, rtemp) = (fresh_temp(), fresh_temp());
+            let (lq, rq) = (
+                select_expr(ltemp.clone(), l, frame),
+                select_expr(rtemp.clone(), r, frame),
+            );
             let op = match op {
-                IBinOp::Add => TRegOp::Add,
-                IBinOp::Sub => TRegOp::Sub,
-                IBinOp::Mult => todo!(), // RV32M
-                IBinOp::Div => todo!(),  // RV32M
-                IBinOp::Mod => todo!(),  // RV32M
+                IBitOp::And => TRegOp::And,
+                IBitOp::Or => TRegOp::Or,
+                IBitOp::Xor => TRegOp::Xor,
+                IBitOp::Shl => TRegOp::Sll,
+                IBitOp::ShlU => TRegOp::Sllu,
+                IBitOp::Shr => TRegOp::Sra,
+                IBitOp::ShrU => TRegOp::Srl,
             };
 
+            lq.into_iter()
+                .chain(rq)
+                .chain(iter::once(TQuad::Reg(op, d, ltemp, rtemp)))
+                .collect()
+        }
+        IExpr::FloatOp(op, l, r) => {
             let (ltemp, rtemp) = (fresh_temp(), fresh_temp());
-            let (lq, rq) = (select_expr(ltemp.clone(), l), select_expr(rtemp.clone(), r));
-            let instr = vec![TQuad::Reg(op, d, ltemp, rtemp)];
+            let (lq, rq) = (
+                select_expr(ltemp.clone(), l, frame),
+                select_expr(rtemp.clone(), r, frame),
+            );
+            let instrs = match op {
+                IFloatOp::Add => vec![TQuad::Float(TFloatOp::FAdd, d, ltemp, rtemp)],
+                IFloatOp::Sub => vec![TQuad::Float(TFloatOp::FSub, d, ltemp, rtemp)],
+                IFloatOp::Mult => vec![TQuad::Float(TFloatOp::FMul, d, ltemp, rtemp)],
+                IFloatOp::Div => vec![TQuad::Float(TFloatOp::FDiv, d, ltemp, rtemp)],
+                IFloatOp::Lt => vec![TQuad::Float(TFloatOp::FLt, d, ltemp, rtemp)],
+                IFloatOp::LtEq => vec![TQuad::Float(TFloatOp::FLe, d, ltemp, rtemp)],
+                // rv32f only has less than and equal, so > and >= swap their operands
+                IFloatOp::Gt => vec![TQuad::Float(TFloatOp::FLt, d, rtemp, ltemp)],
+                IFloatOp::GtEq => vec![TQuad::Float(TFloatOp::FLe, d, rtemp, ltemp)],
+                IFloatOp::Eq => vec![TQuad::Float(TFloatOp::FEq, d, ltemp, rtemp)],
+                IFloatOp::Neq => not(d, TQuad::Float(TFloatOp::FEq, fresh_temp(), ltemp, rtemp)),
+            };
 
-            lq.into_iter().chain(rq).chain(instr).collect()
+            lq.into_iter().chain(rq).chain(instrs).collect()
+        }
+        IExpr::Cvt(op, e) => {
+            let t = fresh_temp();
+            let op = match op {
+                ICvtOp::IntToFloat => TCvtOp::FcvtSW,
+                ICvtOp::UIntToFloat => TCvtOp::FcvtSWu,
+                ICvtOp::FloatToInt => TCvtOp::FcvtWS,
+                ICvtOp::FloatToUInt => TCvtOp::FcvtWuS,
+            };
+            select_expr(t.clone(), e, frame)
+                .into_iter()
+                .chain(iter::once(TQuad::Cvt(op, d, t)))
+                .collect()
+        }
+        IExpr::ESeq(s, e) => select_stmt(s, frame)
+            .into_iter()
+            .chain(select_expr(d, e, frame))
+            .collect(),
+        IExpr::Not(e) => {
+            let t = fresh_temp();
+            select_expr(t.clone(), e, frame)
+                .into_iter()
+                .chain(iter::once(TQuad::Imm(TImmOp::SltIU, d, t, 1))) // seqz
+                .collect()
+        }
+        IExpr::LogOp(op, l, r) => {
+            // branch past r as soon as l decides. each path leaves exactly
+            // one value for d, which keeps the stack balanced at O0
+            let (decided, cont) = match op {
+                ILogOp::And => (0, TRegOp::Beq), // l == 0 decides false
+                ILogOp::Or => (1, TRegOp::Bneq), // l != 0 decides true
+            };
+            let (ltemp, rtemp) = (fresh_temp(), fresh_temp());
+            let (decided_l, end_l) = (fresh_label(), fresh_label());
+            let z = Temp::PointerReg(RiscvPointerReg::Z);
+
+            select_expr(ltemp.clone(), l, frame)
+                .into_iter()
+                .chain([TQuad::Branch(
+                    cont.clone(),
+                    ltemp,
+                    z.clone(),
+                    decided_l.clone(),
+                )])
+                .chain(select_expr(rtemp.clone(), r, frame))
+                .chain([
+                    TQuad::Branch(cont, rtemp, z.clone(), decided_l.clone()),
+                    TQuad::Imm(TImmOp::AddI, d.clone(), z.clone(), 1 - decided),
+                    TQuad::Pseudo(PseudoOp::Jump(end_l.clone())),
+                    TQuad::Label(decided_l),
+                    TQuad::Imm(TImmOp::AddI, d, z, decided),
+                    TQuad::Label(end_l),
+                ])
+                .collect()
+        }
+        IExpr::Cond(cond, then, els) => {
+            // cond: beq c zero els
+            //       d <- then
+            //       jump end
+            // els:  d <- els
+            // end:
+            let c = fresh_temp();
+            let (els_l, end_l) = (fresh_label(), fresh_label());
+            let z = Temp::PointerReg(RiscvPointerReg::Z);
+
+            select_expr(c.clone(), cond, frame)
+                .into_iter()
+                .chain([TQuad::Branch(TRegOp::Beq, c, z, els_l.clone())])
+                .chain(select_expr(d.clone(), then, frame))
+                .chain([
+                    TQuad::Pseudo(PseudoOp::Jump(end_l.clone())),
+                    TQuad::Label(els_l),
+                ])
+                .chain(select_expr(d, els, frame))
+                .chain([TQuad::Label(end_l)])
+                .collect()
+        }
+        IExpr::TempUse(Temp::UserTemp(alias)) => match frame.slot(alias) {
+            Some(offset) => vec![TQuad::Mem(TMemOp::Load, d, offset, RiscvPointerReg::Fp)],
+            None => {
+                let a = fresh_temp();
+                vec![
+                    TQuad::Static(a.clone(), alias.clone()),
+                    TQuad::Deref(TMemOp::Load, d, a),
+                ]
+            }
+        },
+        IExpr::TempUse(t) => vec![TQuad::Imm(TImmOp::AddI, d, t.clone(), 0)],
+        IExpr::Str(s) => vec![TQuad::Str(d, s.clone())],
+        IExpr::Addr(Temp::UserTemp(alias)) => match frame.slot(alias) {
+            Some(offset) => vec![TQuad::Imm(
+                TImmOp::AddI,
+                d,
+                Temp::PointerReg(RiscvPointerReg::Fp),
+                offset,
+            )],
+            None => vec![TQuad::Static(d, alias.clone())],
+        },
+        IExpr::Addr(_) => todo!(), // only locals and statics live in memory
+        IExpr::Mem(addr) | IExpr::MemByte(addr, _) => {
+            let op = match e {
+                IExpr::MemByte(_, false) => TMemOp::LoadByte,
+                IExpr::MemByte(_, true) => TMemOp::LoadByteU,
+                _ => TMemOp::Load,
+            };
+            let t = fresh_temp();
+            select_expr(t.clone(), addr, frame)
+                .into_iter()
+                .chain(iter::once(TQuad::Deref(op, d, t)))
+                .collect()
         }
-        IExpr::TempUse(_) => todo!(),
         IExpr::Call(l, aps) => {
-            if aps.len() > 8 {
+            if aps.len() > ARG_REGS.len() {
                 panic!("todo: more than 8 args not supported");
             }
 
-            let aps = aps.iter().enumerate().map(|(i, a)| match i {
-                0 => select_expr(Temp::PointerReg(RiscvPointerReg::A0), a),
-                1 => select_expr(Temp::PointerReg(RiscvPointerReg::A1), a),
-                2 => select_expr(Temp::PointerReg(RiscvPointerReg::A2), a),
-                3 => select_expr(Temp::PointerReg(RiscvPointerReg::A3), a),
-                4 => select_expr(Temp::PointerReg(RiscvPointerReg::A4), a),
-                5 => select_expr(Temp::PointerReg(RiscvPointerReg::A5), a),
-                6 => select_expr(Temp::PointerReg(RiscvPointerReg::A6), a),
-                7 => select_expr(Temp::PointerReg(RiscvPointerReg::A7), a),
-                _ => unreachable!(),
-            });
+            let c = matches!(l, Label::UserLabel(f) if !frame.funcs.contains(f.as_str()));
+
+            // all aps are evaluated before any are moved into a0-a7,
+            // so nested calls can't clobber them
+            let temps = aps.iter().map(|_| fresh_temp()).collect::<Vec<_>>();
+            let ap_instrs = temps
+                .iter()
+                .zip(aps)
+                .flat_map(|(t, a)| select_expr(t.clone(), a, frame));
+            let arg_instrs = temps
+                .iter()
+                .zip(ARG_REGS)
+                .rev() // the last ap is on top of the stack
+                .map(|(t, reg)| TQuad::Imm(TImmOp::AddI, Temp::PointerReg(reg), t.clone(), 0));
+            let call = match c {
+                true => PseudoOp::CallC(l.clone()),
+                false => PseudoOp::Call(l.clone()),
+            };
 
-            aps.into_iter()
-                .flatten()
+            ap_instrs
+                .chain(arg_instrs)
                 .chain(vec![
-                    TQuad::Pseudo(PseudoOp::Call(l.clone())),
+                    TQuad::Pseudo(call),
                     TQuad::Imm(TImmOp::AddI, d, Temp::PointerReg(RiscvPointerReg::A0), 0),
                 ])
                 .collect()
@@ -150,6 +532,16 @@ fn select_expr(d: Temp, e: &IExpr) -> Vec<TQuad> {
     }
 }
 
+// logical negation of a quad computing 0 or 1
+fn not(d: Temp, quad: TQuad) -> Vec<TQuad> {
+    let t = match &quad {
+        TQuad::Reg(_, t, _, _) | TQuad::Imm(_, t, _, _) | TQuad::Float(_, t, _, _) => t.clone(),
+        _ => unreachable!(),
+    };
+
+    vec![quad, TQuad::Imm(TImmOp::XorI, d, t, 1)]
+}
+
 #[cfg(test)]
 mod test_arith {
     use crate::lexer;
@@ -162,13 +554,9 @@ mod test_arith {
 
     #[test]
     fn add() {
-        let chars = fs::read(format!("{TEST_DIR}/add.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let src = fs::read_to_string(format!("{TEST_DIR}/add.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let src_tree = parser_ast::parse_prg(&tokens).unwrap();
         let _ = typer::type_prg(&src_tree).unwrap();
         let trgt_tree = translator::translate(&src_tree);
@@ -176,6 +564,8 @@ mod test_arith {
 
         insta::assert_yaml_snapshot!(abs_as, @r###"
         ---
+        - Label:
+            UserLabel: main
         - Imm:
             - AddI
             - PointerReg: Sp
@@ -213,9 +603,14 @@ mod test_arith {
             - MachineTemp: 2
         - Imm:
             - AddI
-            - PointerReg: Ra
+            - PointerReg: A0
             - MachineTemp: 0
             - 0
+        - Pseudo:
+            Jump:
+              MachineLabel: 0
+        - Label:
+            MachineLabel: 0
         - Mem:
             - Load
             - PointerReg: Ra
@@ -0,0 +1,300 @@
+use std::error::Error;
+use std::io;
+
+// source text as the front end sees it. it has to be utf-8, though the
+// lexer only lets non-ascii through in string literals and comments. a
+// utf-8 bom is dropped, and crlf or lone cr line endings become lf, so
+// positions agree whichever editor saved the file
+pub fn decode(bytes: &[u8]) -> Result<String, io::Error> {
+    let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes);
+    let src = std::str::from_utf8(bytes)
+        .map_err(|e| io::Error::other(format!("invalid utf-8 at byte {}", e.valid_up_to())))?;
+
+    Ok(src.replace("\r\n", "\n").replace('\r', "\n"))
+}
+
+// where a token or node is in the source. rest is the length in bytes of
+// the source from its first byte on, since the lexer only ever sees
+// suffixes, and len is how many bytes it covers. the default span is nowhere, for nodes the
+// compiler makes up itself
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub rest: usize,
+    pub len: usize,
+}
+
+impl Span {
+    pub fn new(rest: usize, len: usize) -> Self {
+        Span { rest, len }
+    }
+
+    pub fn offset(&self, src: &str) -> usize {
+        src.len() - self.rest
+    }
+
+    // from the start of self to the end of end
+    pub fn to(self, end: Span) -> Span {
+        Span::new(self.rest, self.rest - (end.rest - end.len))
+    }
+}
+
+// an error at a span of the source. the passes over the tree wrap theirs
+// in the span of the innermost node they were at, so a caret can go under it
+#[derive(Debug, thiserror::Error)]
+#[error("{err}")]
+pub struct Located {
+    pub err: Box<dyn Error + Send + Sync>,
+    pub span: Span,
+}
+
+// the error at span, unless it's already located further in or span is
+// nowhere
+pub fn locate(e: io::Error, span: Span) -> io::Error {
+    if span == Span::default() || located(&e).is_some() {
+        return e;
+    }
+    let err = match e.get_ref() {
+        Some(_) => e.into_inner().expect("it has an inner error"),
+        None => Box::new(e),
+    };
+    io::Error::other(Located { err, span })
+}
+
+pub fn located(e: &io::Error) -> Option<&Located> {
+    e.get_ref().and_then(|e| e.downcast_ref::<Located>())
+}
+
+// 1-based. col is a display column, with tabs advancing to the next stop
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pos {
+    pub line: usize,
+    pub col: usize,
+}
+
+// offset is in bytes, but col counts chars
+pub fn pos(src: &str, offset: usize, tab_width: usize) -> Pos {
+    let before = &src[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+    Pos {
+        line: before.matches('\n').count() + 1,
+        col: width(&before[line_start..], tab_width) + 1,
+    }
+}
+
+// the offending line with a caret under offset, tabs expanded to match
+pub fn caret(src: &str, offset: usize, tab_width: usize) -> String {
+    let Pos { line, col } = pos(src, offset, tab_width);
+    let text = src.split('\n').nth(line - 1).unwrap_or_default();
+
+    format!(
+        "{line}:{col}\n{}\n{}^",
+        expand(text, tab_width),
+        " ".repeat(col - 1)
+    )
+}
+
+// which file and line each line of preprocessed text came from. the lines
+// go in runs, each a stretch of one file, since only an include or a #line
+// breaks the count. the lexer and the passes after it see the preprocessed
+// text, and what they report is mapped back through this
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceMap {
+    runs: Vec<Run>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Run {
+    start: usize,
+    file: String,
+    line: usize,
+}
+
+impl SourceMap {
+    // file's text as it is, line for line
+    pub fn new(file: &str) -> Self {
+        let mut map = SourceMap { runs: vec![] };
+        map.push(1, file, 1);
+        map
+    }
+
+    // from line start of the text on, lines are file's from line on. a run
+    // that's replaced before any of its lines is dropped
+    pub fn push(&mut self, start: usize, file: &str, line: usize) {
+        if self.runs.last().is_some_and(|r| r.start == start) {
+            self.runs.pop();
+        }
+        self.runs.push(Run {
+            start,
+            file: file.to_owned(),
+            line,
+        });
+    }
+
+    pub fn origin(&self, line: usize) -> (&str, usize) {
+        let i = self.runs.partition_point(|r| r.start <= line);
+        match i.checked_sub(1).map(|i| &self.runs[i]) {
+            Some(r) => (&r.file, r.line + (line - r.start)),
+            None => ("", line),
+        }
+    }
+
+    // as caret, at file:line:col of the original. the line shown is the
+    // preprocessed one, so the caret still lines up inside an expansion
+    pub fn caret(&self, src: &str, offset: usize, tab_width: usize) -> String {
+        let caret = caret(src, offset, tab_width);
+        let Pos { line, col } = pos(src, offset, tab_width);
+        let (file, line) = self.origin(line);
+        let snippet = caret.split_once('\n').map_or("", |(_, s)| s);
+
+        format!("{file}:{line}:{col}\n{snippet}")
+    }
+}
+
+fn width(cs: &str, tab_width: usize) -> usize {
+    cs.chars().fold(0, |w, c| match c {
+        '\t' => (w / tab_width + 1) * tab_width,
+        _ => w + 1,
+    })
+}
+
+fn expand(cs: &str, tab_width: usize) -> String {
+    let (mut line, mut w) = (String::new(), 0);
+    for c in cs.chars() {
+        match c {
+            '\t' => {
+                let stop = (w / tab_width + 1) * tab_width;
+                line.push_str(&" ".repeat(stop - w));
+                w = stop;
+            }
+            c => {
+                line.push(c);
+                w += 1;
+            }
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod test_whitespace {
+    use crate::lexer;
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/whitespace";
+
+    #[test]
+    fn bom_crlf_tabs() {
+        let src =
+            super::decode(&fs::read(format!("{TEST_DIR}/bom_crlf_tabs.c")).expect("file dne"))
+                .unwrap();
+
+        let errs = lexer::lex(&src).unwrap_err();
+        let offset = errs[0].offset(&src);
+        let carets = [4, 8]
+            .map(|tab_width| super::caret(&src, offset, tab_width))
+            .join("\n");
+        insta::assert_snapshot!(carets, @r###"
+        3:17
+            return x    @ 2;
+                        ^
+        3:25
+                return x        @ 2;
+                                ^
+        "###);
+    }
+
+    #[test]
+    fn unterminated_comment() {
+        let src = super::decode(
+            &fs::read(format!("{TEST_DIR}/unterminated_comment.c")).expect("file dne"),
+        )
+        .unwrap();
+
+        let errs = lexer::lex(&src).unwrap_err();
+        let offset = errs[0].offset(&src);
+        let caret = super::caret(&src, offset, 4);
+        insta::assert_snapshot!(format!("{}\n{caret}", errs[0]), @r###"
+        unterminated comment
+        2:15
+            return 0; /* never
+                      ^
+        "###);
+    }
+
+    #[test]
+    fn errors() {
+        let src =
+            super::decode(&fs::read(format!("{TEST_DIR}/errors.c")).expect("file dne")).unwrap();
+
+        let errs = lexer::lex(&src).unwrap_err();
+        let carets = errs
+            .iter()
+            .map(|e| format!("{e}\n{}", super::caret(&src, e.offset(&src), 4)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        insta::assert_snapshot!(carets, @r###"
+        unexpected token: '@'
+        2:15
+            int x = 1 @ 2;
+                      ^
+        malformed character literal
+        3:14
+            char c = 'ab';
+                     ^
+        unknown escape sequence: \q
+        4:21
+            char *s = "bad \q escape";
+                            ^
+        unexpected token: '$'
+        5:14
+            return x $ 1e;
+                     ^
+        exponent has no digits: 1e
+        5:16
+            return x $ 1e;
+                       ^
+        "###);
+    }
+
+    #[test]
+    fn utf8() {
+        let src =
+            super::decode(&fs::read(format!("{TEST_DIR}/utf8.c")).expect("file dne")).unwrap();
+
+        let errs = lexer::lex(&src).unwrap_err();
+        let carets = errs
+            .iter()
+            .map(|e| format!("{e}\n{}", super::caret(&src, e.offset(&src), 4)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        insta::assert_snapshot!(carets, @r###"
+        non-ascii character outside a string literal: 'é'
+        4:12
+            int café = 1;
+                   ^
+        non-ascii character outside a string literal: 'é'
+        5:15
+            char c = 'é';
+                      ^
+        non-ascii character outside a string literal: '×'
+        6:17
+            return s[2] × 2;
+                        ^
+        "###);
+
+        // string literals keep theirs whole, and spans are in bytes
+        let tokens = lexer::lex("\"naïve\" x").unwrap();
+        assert_eq!(tokens[0].lexeme, "naïve");
+        assert_eq!(
+            (tokens[0].span.len, tokens[1].offset("\"naïve\" x")),
+            (8, 9)
+        );
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let err = super::decode(b"int main() { return 0; } \xff").unwrap_err();
+        assert_eq!(err.to_string(), "invalid utf-8 at byte 25");
+    }
+}
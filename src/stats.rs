@@ -0,0 +1,122 @@
+use crate::{lexer, parser_ast, SDef, SStmt, Spanned};
+use serde::Serialize;
+use std::{fmt, io};
+
+// code metrics for surveying batches of submissions.
+// function length is measured in statements, nested ones included, so
+// it doesn't depend on how the source is laid out
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Stats {
+    pub loc: usize, // non-blank lines
+    pub tokens: usize,
+    pub funcs: usize,
+    pub avg_func_len: f64,
+    pub comment_ratio: f64, // comment lines over non-blank lines
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "loc {}, tokens {}, funcs {}, avg func len {:.1} stmts, comments {:.0}%",
+            self.loc,
+            self.tokens,
+            self.funcs,
+            self.avg_func_len,
+            self.comment_ratio * 100.0
+        )
+    }
+}
+
+pub fn stats(src: &str) -> Result<Stats, io::Error> {
+    let loc = src.lines().filter(|l| !l.trim().is_empty()).count();
+
+    let tokens = lexer::lex(src).map_err(lexer::io_error)?;
+    let prg = parser_ast::parse_prg(&tokens)?;
+    let func_lens = prg
+        .iter()
+        .filter_map(|def| match &def.node {
+            SDef::FuncDef(fd) => Some(count_stmts(&fd.body)),
+            SDef::FuncDecl(_)
+            | SDef::VarDef(_)
+            | SDef::VarDecl(_)
+            | SDef::StructDef(_)
+            | SDef::UnionDef(_)
+            | SDef::EnumDef(_) => None,
+        })
+        .collect::<Vec<_>>();
+
+    let comment_loc = lexer::trivia(src)
+        .iter()
+        .map(|c| c.text.lines().filter(|l| !l.trim().is_empty()).count())
+        .sum::<usize>();
+
+    Ok(Stats {
+        loc,
+        tokens: tokens.len(),
+        funcs: func_lens.len(),
+        avg_func_len: match func_lens.len() {
+            0 => 0.0,
+            n => func_lens.iter().sum::<usize>() as f64 / n as f64,
+        },
+        comment_ratio: match loc {
+            0 => 0.0,
+            n => comment_loc as f64 / n as f64,
+        },
+    })
+}
+
+fn count_stmts(stmts: &[Spanned<SStmt>]) -> usize {
+    stmts
+        .iter()
+        .map(|stmt| match &stmt.node {
+            SStmt::IfEls { then, els, .. } => {
+                1 + count_stmts(std::slice::from_ref(then.as_ref()))
+                    + els
+                        .as_ref()
+                        .map_or(0, |els| count_stmts(std::slice::from_ref(els.as_ref())))
+            }
+            SStmt::Block(body) => 1 + count_stmts(body),
+            SStmt::Switch { arms, .. } => {
+                1 + arms.iter().map(|a| count_stmts(&a.body)).sum::<usize>()
+            }
+            SStmt::While { body, .. } | SStmt::DoWhile { body, .. } | SStmt::For { body, .. } => {
+                1 + count_stmts(body)
+            }
+            SStmt::Asnmt(_)
+            | SStmt::Decl(_)
+            | SStmt::Update(_)
+            | SStmt::Store(_)
+            | SStmt::Return(_)
+            | SStmt::Call(_)
+            | SStmt::Contract(_)
+            | SStmt::Assert(_)
+            | SStmt::Break
+            | SStmt::Continue
+            | SStmt::Label(_)
+            | SStmt::Goto(_) => 1,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod test_stats {
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/stats";
+
+    #[test]
+    fn commented() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/commented.c")).expect("file dne");
+
+        let stats = super::stats(&src).unwrap();
+        insta::assert_yaml_snapshot!(stats, @r###"
+        ---
+        loc: 11
+        tokens: 30
+        funcs: 2
+        avg_func_len: 1.5
+        comment_ratio: 0.45454545454545453
+        "###);
+    }
+}
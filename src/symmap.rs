@@ -0,0 +1,244 @@
+use crate::lexer::{self, TT};
+use crate::parser_ast;
+use serde::Serialize;
+use std::fmt;
+
+// a .map style listing of the symbols in the emitted assembly, so binary
+// layout can be correlated with the source. sizes are in bytes, with
+// pseudo instructions counted as their expansion
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Symbol {
+    pub name: String,
+    pub section: String,
+    pub size: usize,
+    pub line: Option<usize>, // of the definition. None for the runtime's symbols
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let line = match self.line {
+            Some(l) => l.to_string(),
+            None => "-".to_owned(),
+        };
+        write!(
+            f,
+            "{:<24} {:<10} {:>6} {}",
+            self.name, self.section, self.size, line
+        )
+    }
+}
+
+pub fn symbol_map(assembly: &[String], src: &str) -> Vec<Symbol> {
+    let lines = assembly
+        .iter()
+        .flat_map(|s| s.lines())
+        .map(|l| l.split('#').next().unwrap_or("").trim()) // drop comments
+        .filter(|l| !l.is_empty());
+
+    let mut section = ".text".to_owned();
+    let mut syms: Vec<Symbol> = vec![];
+    for l in lines {
+        match l.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [".text"] => section = ".text".to_owned(),
+            [".section", s, ..] => section = s.trim_end_matches(',').to_owned(),
+            // other directives, but for the padding after a symbol's data
+            [d, ..] if d.starts_with('.') && !d.ends_with(':') && *d != ".zero" => (),
+            [label, r @ ..] if label.ends_with(':') => {
+                let name = label.trim_end_matches(':');
+                let size = match r.first() {
+                    Some(d) => size_of(d, l),
+                    None => 0,
+                };
+                if name.starts_with(".L") {
+                    // local labels belong to the enclosing symbol
+                    if let Some(sym) = syms.last_mut() {
+                        sym.size += size;
+                    }
+                } else {
+                    syms.push(Symbol {
+                        name: name.to_owned(),
+                        section: section.clone(),
+                        size,
+                        line: def_line(src, name),
+                    });
+                }
+            }
+            [op, ..] => {
+                if let Some(sym) = syms.last_mut() {
+                    sym.size += size_of(op, l);
+                }
+            }
+            [] => (),
+        }
+    }
+
+    syms
+}
+
+fn size_of(op: &str, line: &str) -> usize {
+    match op {
+        "la" | "call" | "tail" => 8,              // auipc + addi/jalr
+        ".byte" => line.matches(',').count() + 1, // string literals are a byte per char
+        ".word" => 4 * (line.matches(',').count() + 1),
+        ".dword" => 8 * (line.matches(',').count() + 1),
+        ".zero" => line
+            .split_whitespace()
+            .last()
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(0),
+        ".string" => {
+            // the quoted bytes, unescaped, plus the terminator
+            let quoted = line.split_once('"').map_or("", |(_, q)| q);
+            let quoted = quoted.strip_suffix('"').unwrap_or(quoted);
+            quoted.replace("\\\\", "\\").replace("\\n", "\n").len() + 1
+        }
+        _ => 4,
+    }
+}
+
+// the definition is the first line lexing to `int alias (` for a function,
+// or to `int alias` then =, ; or [ for a variable, with any other integer
+// type, static or const. local statics are renamed, so they have no line
+fn def_line(src: &str, alias: &str) -> Option<usize> {
+    src.split('\n')
+        .position(|line| match lexer::lex(line).as_deref() {
+            Ok(ts) => {
+                let n = ts
+                    .iter()
+                    .take_while(|t| {
+                        matches!(
+                            t.typ,
+                            TT::KeywordVoid | TT::KeywordStatic | TT::KeywordConst
+                        ) || parser_ast::is_arith_specifier(&t.typ)
+                    })
+                    .count();
+                // a prototype's line ends in ; where its definition's doesn't
+                let proto = ts.last().is_some_and(|t| t.typ == TT::PuncSemiColon);
+                n > 0
+                    && matches!(&ts[n..], [a, p, ..] if a.lexeme == alias && match p.typ {
+                        TT::PuncLeftParen => !proto,
+                        TT::Equals | TT::PuncSemiColon | TT::PuncLeftBracket => true,
+                        _ => false,
+                    })
+            }
+            Err(_) => false,
+        })
+        .map(|i| i + 1)
+}
+
+#[cfg(test)]
+mod test_functions {
+    use crate::lexer;
+    use crate::parser_ast;
+    use crate::runtime;
+    use crate::selector;
+    use crate::translator;
+    use crate::typer;
+    use crate::{allocator, IntModel, OptLevel};
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/bindings";
+
+    #[test]
+    fn composition() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/composition.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = allocator::allocate(&abs_as, OptLevel::O0, &IntModel::default()).render();
+        let assembly = [assembly, runtime::runtime(&abs_as, &IntModel::default())].concat();
+        let map = super::symbol_map(&assembly, &src)
+            .iter()
+            .map(|sym| sym.to_string())
+            .collect::<Vec<_>>();
+        insta::assert_yaml_snapshot!(map, @r###"
+        ---
+        - h                        .text          80 1
+        - g                        .text         148 5
+        - f                        .text         148 9
+        - main                     .text         108 13
+        - __picoc_msg_div_zero     .rodata        47 -
+        - __picoc_msg_segv         .rodata        52 -
+        - __picoc_msg_overflow     .rodata        54 -
+        - __picoc_msg_div_overflow .rodata        56 -
+        - __picoc_nl               .rodata         2 -
+        - __picoc_sym_unknown      .rodata         2 -
+        - __picoc_sym_h            .rodata         2 -
+        - __picoc_sym_g            .rodata         2 -
+        - __picoc_sym_f            .rodata         2 -
+        - __picoc_sym_main         .rodata         5 -
+        - __picoc_func             .data           4 -
+        - __picoc_sa_segv          .data         140 -
+        - __picoc_sa_fpe           .data         140 -
+        - __picoc_init             .text          68 -
+        - __picoc_segv             .text          12 -
+        - __picoc_overflow         .text          12 -
+        - __picoc_div_overflow     .text          12 -
+        - __picoc_div_zero         .text           8 -
+        - __picoc_report           .text          64 -
+        - __picoc_puts             .text          36 -
+        "###);
+    }
+
+    #[test]
+    fn statics() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/static.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = allocator::allocate(&abs_as, OptLevel::O0, &IntModel::default()).render();
+        let map = super::symbol_map(&assembly, &src)
+            .iter()
+            .map(|sym| sym.to_string())
+            .collect::<Vec<_>>();
+        insta::assert_yaml_snapshot!(map, @r###"
+        ---
+        - total                    .data           4 1
+        - hits                     .bss            4 2
+        - n.0                      .bss            4 -
+        - count                    .text         328 4
+        - n.1                      .bss            4 -
+        - n.2                      .data           4 -
+        - bump                     .text         488 11
+        - main                     .text         560 22
+        "###);
+    }
+
+    #[test]
+    fn globals() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/globals.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = translator::translate(&src_tree);
+        let abs_as = selector::select(&trgt_tree);
+        let assembly = allocator::allocate(&abs_as, OptLevel::O0, &IntModel::default()).render();
+        let data = assembly
+            .iter()
+            .filter(|l| l.starts_with(".globl") || l.contains(": .")) // the data, not the code
+            .collect::<Vec<_>>();
+        insta::assert_yaml_snapshot!(data, @r###"
+        ---
+        - ".globl main"
+        - ".globl g"
+        - "g: .word 42"
+        - ".globl zero"
+        - "zero: .zero 4"
+        - ".globl k"
+        - "k: .word 5"
+        - ".globl half"
+        - "half: .word 1056964608"
+        - ".globl wrap"
+        - "wrap: .word 44"
+        - ".globl table"
+        - "table: .zero 12"
+        "###);
+    }
+}
@@ -0,0 +1,423 @@
+use crate::source::{self, SourceMap, Span};
+use crate::{
+    layout, pretty, translator, IntModel, SDef, SExpr, SPrg, SStmt, Spanned, Type, ARG_REGS,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::{fmt, iter, slice};
+
+// a listing of every symbol the program declares, for --emit symtab: its
+// type, how deeply its scope nests, where it lives once compiled, and the
+// lines it's declared and used on. globals are depth 0, and a function's
+// params and body are 1, as c has them. locals are where the unoptimized
+// frame has them, params where they arrive and then are spilled to, and
+// statics by their data symbol, which a local one is renamed to
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Entry {
+    pub name: String,
+    pub kind: String,
+    pub typ: String,
+    pub depth: usize,
+    pub location: String,
+    pub line: usize,
+    pub uses: Vec<usize>,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let uses = self.uses.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+        write!(
+            f,
+            "{:<16} {:<7} {:<20} {:>5} {:<16} {:>5} {}",
+            self.name,
+            self.kind,
+            self.typ,
+            self.depth,
+            self.location,
+            self.line,
+            uses.join(" ")
+        )
+    }
+}
+
+// entries in the order they're declared. lines are the original file's,
+// through map. a function declared before it's defined is one entry, at
+// its definition
+pub fn symtab(prg: &SPrg, text: &str, map: &SourceMap, model: &IntModel) -> Vec<Entry> {
+    let layouts = layout::layouts(prg).expect("layouts are checked by the typer");
+    let mut t = Table {
+        entries: vec![],
+        scopes: vec![HashMap::new()],
+        text,
+        map,
+    };
+
+    let mut n = 0; // as translate_for renames, so local statics match its symbols
+    for d in prg {
+        let line = t.line(d.span);
+        match &d.node {
+            SDef::FuncDef(fd) => {
+                let fps = fd.fps.iter().map(|(_, t)| pretty::typ(t));
+                let typ = format!(
+                    "{} ({})",
+                    pretty::typ(&fd.typ),
+                    fps.collect::<Vec<_>>().join(", ")
+                );
+                t.func(&fd.alias, typ, line, true);
+
+                let body = translator::rename_stmts(&fd.body, &HashMap::new(), false, &mut n);
+                let mut slots = HashMap::new();
+                let words = |t: &Type| layouts.words(t).expect("layouts are checked by the typer");
+                for (i, (alias, _)) in fd.fps.iter().enumerate() {
+                    slots.insert(alias.clone(), -3 - i as i32);
+                }
+                lay_out(&body, &words, &mut slots, &mut fd.fps.len());
+                let slot = |alias: &str| format!("{}(fp)", slots[alias] * model.word());
+
+                t.scopes.push(HashMap::new());
+                for ((alias, typ), reg) in fd.fps.iter().zip(ARG_REGS) {
+                    let location = format!("{reg} -> {}", slot(alias));
+                    t.declare(alias, "param", pretty::typ(typ), location, line);
+                }
+                t.stmts(&body, &slot);
+                t.scopes.pop();
+            }
+            SDef::FuncDecl(fd) => {
+                let mut fps = fd.fps.iter().map(pretty::typ).collect::<Vec<_>>();
+                if fd.variadic {
+                    fps.push("...".to_owned());
+                }
+                let typ = format!("{} ({})", pretty::typ(&fd.typ), fps.join(", "));
+                t.func(&fd.alias, typ, line, false);
+            }
+            SDef::VarDef(vd) => {
+                t.expr(&vd.expr);
+                let kind = if vd.is_static { "static" } else { "global" };
+                t.declare(
+                    &vd.alias,
+                    kind,
+                    pretty::typ(&vd.typ),
+                    vd.alias.clone(),
+                    line,
+                );
+            }
+            SDef::VarDecl(vd) => {
+                let kind = if vd.is_static { "static" } else { "global" };
+                t.declare(
+                    &vd.alias,
+                    kind,
+                    pretty::typ(&vd.typ),
+                    vd.alias.clone(),
+                    line,
+                );
+            }
+            SDef::StructDef(sd) => {
+                let typ = format!("struct {}", sd.alias);
+                t.declare(&sd.alias, "struct", typ, "-".to_owned(), line);
+            }
+            SDef::UnionDef(sd) => {
+                let typ = format!("union {}", sd.alias);
+                t.declare(&sd.alias, "union", typ, "-".to_owned(), line);
+            }
+            // enumerators are already their values in the tree, so their uses
+            // can't be listed
+            SDef::EnumDef(ed) => {
+                for (e, v) in &ed.enumerators {
+                    t.declare(e, "enum", "int".to_owned(), format!("= {v}"), line);
+                }
+            }
+        }
+    }
+    t.entries
+}
+
+// each local's slot, in words below fp, as the selector lays out frames:
+// params first, then first come first served. a nested block's slots come
+// after its enclosing one's, where its siblings' go too, but loop bodies
+// and the like aren't blocks of their own. statics have none. returns the
+// most words in use
+fn lay_out(
+    stmts: &[Spanned<SStmt>],
+    words: &dyn Fn(&Type) -> usize,
+    slots: &mut HashMap<String, i32>,
+    next: &mut usize,
+) -> usize {
+    let mut high = *next;
+    for s in stmts {
+        let (alias, n) = match &s.node {
+            SStmt::Asnmt(vd) if !vd.is_static => (&vd.alias, 1),
+            SStmt::Decl(vd) if !vd.is_static => (&vd.alias, words(&vd.typ)),
+            SStmt::Block(body) => {
+                high = high.max(lay_out(body, words, slots, &mut next.clone()));
+                continue;
+            }
+            SStmt::IfEls { then, els, .. } => {
+                for b in iter::once(then).chain(els) {
+                    high = high.max(lay_out(slice::from_ref(b), words, slots, next));
+                }
+                continue;
+            }
+            SStmt::While { body, .. } | SStmt::DoWhile { body, .. } => {
+                high = high.max(lay_out(body, words, slots, next));
+                continue;
+            }
+            SStmt::For { asnmt, body, .. } => {
+                high = high.max(lay_out(slice::from_ref(asnmt), words, slots, next));
+                high = high.max(lay_out(body, words, slots, next));
+                continue;
+            }
+            SStmt::Switch { arms, .. } => {
+                for arm in arms {
+                    high = high.max(lay_out(&arm.body, words, slots, next));
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        slots.entry(alias.clone()).or_insert_with(|| {
+            *next += n;
+            -2 - *next as i32
+        });
+    }
+    high.max(*next)
+}
+
+// the entries so far, and the scopes being resolved in, innermost last.
+// each maps a name to its entry
+struct Table<'a> {
+    entries: Vec<Entry>,
+    scopes: Vec<HashMap<String, usize>>,
+    text: &'a str,
+    map: &'a SourceMap,
+}
+
+impl Table<'_> {
+    fn line(&self, span: Span) -> usize {
+        let pos = source::pos(self.text, span.offset(self.text), 1);
+        self.map.origin(pos.line).1
+    }
+
+    // an entry in the innermost scope, which uses find by name. struct
+    // tags aren't bound, since they're another namespace
+    fn declare(&mut self, name: &str, kind: &str, typ: String, location: String, line: usize) {
+        self.entries.push(Entry {
+            name: source_name(name),
+            kind: kind.to_owned(),
+            typ,
+            depth: self.scopes.len() - 1,
+            location,
+            line,
+            uses: vec![],
+        });
+        if kind != "struct" && kind != "union" {
+            let i = self.entries.len() - 1;
+            self.scopes.last_mut().unwrap().insert(name.to_owned(), i);
+        }
+    }
+
+    // a prototype and its definition are one entry, at the definition
+    fn func(&mut self, name: &str, typ: String, line: usize, defined: bool) {
+        match self.scopes[0].get(name) {
+            Some(&i) if defined => {
+                (self.entries[i].line, self.entries[i].location) = (line, name.to_owned())
+            }
+            Some(_) => (),
+            None => {
+                let location = if defined {
+                    name.to_owned()
+                } else {
+                    "extern".to_owned()
+                };
+                self.declare(name, "func", typ, location, line)
+            }
+        }
+    }
+
+    fn used(&mut self, name: &str, span: Span) {
+        let line = self.line(span);
+        let found = self.scopes.iter().rev().find_map(|s| s.get(name)).copied();
+        if let Some(i) = found {
+            let uses = &mut self.entries[i].uses;
+            if uses.last() != Some(&line) {
+                uses.push(line);
+            }
+        }
+    }
+
+    // names are the renamed ones, as in the frame, and shown as they were
+    fn stmts(&mut self, ss: &[Spanned<SStmt>], slot: &dyn Fn(&str) -> String) {
+        for s in ss {
+            self.stmt(s, slot);
+        }
+    }
+
+    fn scoped(&mut self, ss: &[Spanned<SStmt>], slot: &dyn Fn(&str) -> String) {
+        self.scopes.push(HashMap::new());
+        self.stmts(ss, slot);
+        self.scopes.pop();
+    }
+
+    fn stmt(&mut self, s: &Spanned<SStmt>, slot: &dyn Fn(&str) -> String) {
+        let line = self.line(s.span);
+        match &s.node {
+            SStmt::Asnmt(vd) => {
+                self.expr(&vd.expr);
+                let (kind, location) = match vd.is_static {
+                    true => ("static", vd.alias.clone()),
+                    false => ("local", slot(&vd.alias)),
+                };
+                self.declare(&vd.alias, kind, pretty::typ(&vd.typ), location, line);
+            }
+            SStmt::Decl(vd) => {
+                let (kind, location) = match vd.is_static {
+                    true => ("static", vd.alias.clone()),
+                    false => ("local", slot(&vd.alias)),
+                };
+                self.declare(&vd.alias, kind, pretty::typ(&vd.typ), location, line);
+            }
+            SStmt::Update(vu) => {
+                self.used(&vu.alias, s.span);
+                self.expr(&vu.expr);
+            }
+            SStmt::Store(st) => {
+                self.expr(&st.lval);
+                self.expr(&st.expr);
+            }
+            SStmt::Return(e) => e.iter().for_each(|e| self.expr(e)),
+            SStmt::Call(e) | SStmt::Assert(e) => self.expr(e),
+            SStmt::Contract(c) => self.expr(&c.expr),
+            SStmt::IfEls { cond, then, els } => {
+                self.expr(cond);
+                self.stmt(then, slot);
+                if let Some(els) = els {
+                    self.stmt(els, slot);
+                }
+            }
+            SStmt::Switch { cond, arms } => {
+                self.expr(cond);
+                self.scopes.push(HashMap::new());
+                for arm in arms {
+                    self.stmts(&arm.body, slot);
+                }
+                self.scopes.pop();
+            }
+            SStmt::While {
+                cond,
+                body,
+                invariants,
+            } => {
+                invariants.iter().for_each(|c| self.expr(&c.expr));
+                self.expr(cond);
+                self.scoped(body, slot);
+            }
+            SStmt::DoWhile { body, cond } => {
+                self.scoped(body, slot);
+                self.expr(cond);
+            }
+            SStmt::For {
+                asnmt,
+                cond,
+                update,
+                body,
+                invariants,
+            } => {
+                self.scopes.push(HashMap::new());
+                self.stmt(asnmt, slot);
+                invariants.iter().for_each(|c| self.expr(&c.expr));
+                self.expr(cond);
+                self.stmt(update, slot);
+                self.stmts(body, slot);
+                self.scopes.pop();
+            }
+            SStmt::Block(body) => self.scoped(body, slot),
+            SStmt::Label(_) | SStmt::Goto(_) | SStmt::Break | SStmt::Continue => (),
+        }
+    }
+
+    fn expr(&mut self, e: &Spanned<SExpr>) {
+        match &e.node {
+            SExpr::VarApp(alias) => self.used(alias, e.span),
+            SExpr::IncDec { lval, .. } => self.expr(lval),
+            SExpr::Index { alias, idx } => {
+                self.used(alias, e.span);
+                self.expr(idx);
+            }
+            SExpr::FuncApp { alias, aps } => {
+                self.used(alias, e.span);
+                aps.iter().for_each(|ap| self.expr(ap));
+            }
+            SExpr::UnaryE { l, .. }
+            | SExpr::AddrOf(l)
+            | SExpr::Deref(l)
+            | SExpr::SizeOf(l)
+            | SExpr::AllocArray(_, l)
+            | SExpr::Cast(_, l)
+            | SExpr::Member { base: l, .. } => self.expr(l),
+            SExpr::BinE { l, r, .. }
+            | SExpr::LogE { l, r, .. }
+            | SExpr::BitE { l, r, .. }
+            | SExpr::RelE { l, r, .. } => {
+                self.expr(l);
+                self.expr(r);
+            }
+            SExpr::CondE { cond, then, els } => {
+                self.expr(cond);
+                self.expr(then);
+                self.expr(els);
+            }
+            SExpr::Int(_)
+            | SExpr::Float { .. }
+            | SExpr::Bool(_)
+            | SExpr::Str(_)
+            | SExpr::TypedInt { .. }
+            | SExpr::SizeOfType(_)
+            | SExpr::Alloc(_) => (),
+        }
+    }
+}
+
+// x.3 was x in the source. a local static's symbol is its renamed name
+fn source_name(renamed: &str) -> String {
+    renamed.split('.').next().unwrap_or(renamed).to_owned()
+}
+
+#[cfg(test)]
+mod test_symtab {
+    use crate::{lexer, parser_ast, source::SourceMap, IntModel};
+
+    #[test]
+    fn listed() {
+        let src = "int g = 3;
+int sq(int x) {
+  static int calls = 0;
+  calls++;
+  return x * x;
+}
+int main() {
+  int xs[4];
+  for (int i = 0; i < 4; i++) { xs[i] = sq(i) + g; }
+  {
+    int g = 1;
+    return g;
+  }
+}";
+        let tokens = lexer::lex(src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let map = SourceMap::new("f.c");
+        let entries = super::symtab(&src_tree, src, &map, &IntModel::default());
+        let listed = entries
+            .iter()
+            .map(|e| e.to_string().trim_end().to_owned())
+            .collect::<Vec<_>>();
+        insta::assert_snapshot!(listed.join("\n"), @r###"
+        g                global  int                      0 g                    1 9
+        sq               func    int (int)                0 sq                   2 9
+        x                param   int                      1 a0 -> -12(fp)        2 5
+        calls            static  int                      1 calls.0              3 4
+        main             func    int ()                   0 main                 7
+        xs               local   int [4]                  1 -24(fp)              8 9
+        i                local   int                      2 -28(fp)              9 9
+        g                local   int                      2 -32(fp)             11 12
+        "###);
+    }
+}
@@ -1,89 +1,1345 @@
-use crate::{IBinOp, IExpr, IPrg, IStmt, Label, SBinOp, SDef, SExpr, SFuncDef, SPrg, SStmt, Temp};
+use crate::{
+    evaluator, fresh_label, fresh_temp, runtime, source, typer, IBinOp, IBitOp, ICvtOp, IExpr,
+    IFloatOp, ILogOp, IPrg, IStmt, IntModel, Label, Overflow, RiscvPointerReg, SBinOp, SBitOp,
+    SCase, SContract, SDef, SExpr, SFuncDef, SIncDecOp, SLogOp, SPrg, SRelOp, SStmt, SStore,
+    SUnaryOp, SVarDecl, SVarDef, SVarUpdate, Spanned, Temp, Tnv, Type, ARG_REGS,
+};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::{io, iter, slice};
+
+thread_local! {
+    // the char variables whose address is taken, in the function being
+    // translated or any function, for globals. a byte stored through a
+    // pointer to one leaves the rest of its word as it was, so reading one
+    // narrows it again
+    static ADDRESSED: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+    // the temps pinning an lvalue's address, in the function being
+    // translated. one computed inside an expr, as ++ and -- do, still needs
+    // its slot in the frame
+    static PINS: RefCell<Vec<String>> = const { RefCell::new(vec![]) };
+}
 
 pub fn translate(src_tree: &SPrg) -> IPrg {
+    translate_for(src_tree, &IntModel::default())
+}
+
+// the translation for a data model, whose pointers are the word that
+// indexing and members count in. translate's is ilp32's, for rv32
+pub fn translate_for(src_tree: &SPrg, model: &IntModel) -> IPrg {
+    let mut tnv = typer::tnv(src_tree).expect("layouts are checked by the typer");
+    tnv.model = model.clone();
+    let mut n = 0; // renames are numbered program wide, so local statics' symbols are unique
+    let mut globals = HashSet::new();
+    for def in src_tree {
+        if let SDef::FuncDef(fd) = &def.node {
+            addressed(&fd.body, &mut globals);
+        }
+    }
     let intrm_prg = src_tree
         .iter()
-        .map(|def| match def {
-            SDef::FuncDef(func_def) => translate_func_def(func_def),
-            SDef::VarDef(_var_def) => todo!(),
+        .flat_map(|def| match &def.node {
+            SDef::FuncDef(func_def) => translate_func_def(func_def, &tnv, &globals, &mut n),
+            SDef::VarDef(vd) => vec![IStmt::Static(
+                vd.alias.clone(),
+                initial(&vd.typ, Some(&vd.expr), &tnv),
+                !vd.is_static,
+            )],
+            SDef::VarDecl(vd) => vec![IStmt::Static(
+                vd.alias.clone(),
+                initial(&vd.typ, None, &tnv),
+                !vd.is_static,
+            )],
+            SDef::FuncDecl(_) | SDef::StructDef(_) | SDef::UnionDef(_) | SDef::EnumDef(_) => vec![],
         })
         .collect::<Vec<_>>();
 
     intrm_prg
 }
 
-fn translate_func_def(fd: &SFuncDef) -> IStmt {
+// what translate can't compile yet, as errors at where it is rather than
+// the panics they'd be. locals are typed as they're declared, for what's
+// passed to a variadic function
+pub fn unsupported(src_tree: &SPrg) -> Result<(), io::Error> {
+    fn walk(
+        stmts: &[Spanned<SStmt>],
+        tnv: &Tnv,
+        ltnv: &mut HashMap<String, Type>,
+    ) -> Result<(), io::Error> {
+        stmts.iter().try_for_each(|s| {
+            match &s.node {
+                SStmt::IfEls { .. } => Err(io::Error::other(
+                    "if statements aren't compiled yet, run with interpretc0",
+                )),
+                SStmt::Assert(_) => Err(io::Error::other(
+                    "assert calls aren't compiled yet, there's no assert.h, run with interpretc0",
+                )),
+                _ if allocates(&s.node) => Err(io::Error::other(
+                    "alloc and alloc_array aren't compiled yet, run with interpretc0",
+                )),
+                _ if floats_varargs(&s.node, tnv, ltnv) => Err(io::Error::other(
+                    "floating args to variadic functions aren't compiled yet, as doubles are single precision, run with interpretc0",
+                )),
+                SStmt::Asnmt(SVarDef { alias, typ, .. })
+                | SStmt::Decl(SVarDecl { alias, typ, .. }) => {
+                    ltnv.insert(alias.clone(), typ.clone());
+                    Ok(())
+                }
+                SStmt::Switch { arms, .. } => {
+                    let mut ltnv = ltnv.clone();
+                    arms.iter().try_for_each(|a| walk(&a.body, tnv, &mut ltnv))
+                }
+                SStmt::For { asnmt, body, .. } => {
+                    let mut ltnv = ltnv.clone();
+                    walk(slice::from_ref(asnmt), tnv, &mut ltnv)?;
+                    walk(body, tnv, &mut ltnv)
+                }
+                SStmt::Block(body) | SStmt::While { body, .. } | SStmt::DoWhile { body, .. } => {
+                    walk(body, tnv, &mut ltnv.clone())
+                }
+                _ => Ok(()),
+            }
+            .map_err(|e| source::locate(e, s.span))
+        })
+    }
+
+    let tnv = typer::tnv(src_tree)?;
+    src_tree.iter().try_for_each(|def| match &def.node {
+        SDef::FuncDef(fd) if fd.fps.len() > ARG_REGS.len() => Err(source::locate(
+            io::Error::other(format!(
+                "{} has more than {} params, which aren't compiled yet",
+                fd.alias,
+                ARG_REGS.len()
+            )),
+            def.span,
+        )),
+        SDef::FuncDef(fd) => {
+            let mut ltnv = tnv.vnv.clone();
+            ltnv.extend(fd.fps.iter().cloned());
+            walk(&fd.body, &tnv, &mut ltnv)
+        }
+        _ => Ok(()),
+    })
+}
+
+// the exprs a stmt evaluates itself, not counting the stmts nested in it
+fn exprs(s: &SStmt) -> Vec<&SExpr> {
+    match s {
+        SStmt::IfEls { cond, .. }
+        | SStmt::Switch { cond, .. }
+        | SStmt::While { cond, .. }
+        | SStmt::DoWhile { cond, .. } => vec![cond],
+        SStmt::For {
+            asnmt,
+            cond,
+            update,
+            ..
+        } => [exprs(asnmt), vec![&cond.node], exprs(update)].concat(),
+        SStmt::Asnmt(vd) => vec![&vd.expr],
+        SStmt::Update(vu) => vec![&vu.expr],
+        SStmt::Store(st) => vec![&st.lval, &st.expr],
+        SStmt::Return(Some(e)) | SStmt::Call(e) | SStmt::Assert(e) => vec![e],
+        SStmt::Contract(c) => vec![&c.expr],
+        SStmt::Return(None)
+        | SStmt::Decl(_)
+        | SStmt::Label(_)
+        | SStmt::Goto(_)
+        | SStmt::Block(_)
+        | SStmt::Break
+        | SStmt::Continue => vec![],
+    }
+}
+
+// whether e, or an expr in it, is one that p holds of
+fn any(e: &SExpr, p: &dyn Fn(&SExpr) -> bool) -> bool {
+    p(e) || match e {
+        SExpr::Alloc(_)
+        | SExpr::Int(_)
+        | SExpr::TypedInt { .. }
+        | SExpr::Float { .. }
+        | SExpr::Bool(_)
+        | SExpr::Str(_)
+        | SExpr::VarApp(_)
+        | SExpr::SizeOf(_) // never evaluated
+        | SExpr::SizeOfType(_) => false,
+        SExpr::AllocArray(_, l)
+        | SExpr::IncDec { lval: l, .. }
+        | SExpr::Index { idx: l, .. }
+        | SExpr::UnaryE { l, .. }
+        | SExpr::AddrOf(l)
+        | SExpr::Deref(l)
+        | SExpr::Member { base: l, .. }
+        | SExpr::Cast(_, l) => any(l, p),
+        SExpr::BinE { l, r, .. }
+        | SExpr::LogE { l, r, .. }
+        | SExpr::BitE { l, r, .. }
+        | SExpr::RelE { l, r, .. } => any(l, p) || any(r, p),
+        SExpr::CondE { cond, then, els } => any(cond, p) || any(then, p) || any(els, p),
+        SExpr::FuncApp { aps, .. } => aps.iter().any(|ap| any(ap, p)),
+    }
+}
+
+// whether a stmt's own exprs alloc
+fn allocates(s: &SStmt) -> bool {
+    let alloc = |e: &SExpr| matches!(e, SExpr::Alloc(_) | SExpr::AllocArray(..));
+    exprs(s).into_iter().any(|e| any(e, &alloc))
+}
+
+// whether a stmt's own exprs pass a float or double past a variadic
+// function's params, where c promotes it to a double vararg wouldn't pass
+fn floats_varargs(s: &SStmt, tnv: &Tnv, ltnv: &HashMap<String, Type>) -> bool {
+    let floats = |e: &SExpr| match e {
+        SExpr::FuncApp { alias, aps } => tnv.fnv.get(alias).is_some_and(|f| {
+            f.variadic
+                && aps
+                    .iter()
+                    .skip(f.fp.len())
+                    .any(|ap| typer::type_expr(ap, tnv, ltnv).is_ok_and(|t| typer::floating(&t)))
+        }),
+        _ => false,
+    };
+    exprs(s).into_iter().any(|e| any(e, &floats))
+}
+
+// a function's local statics come first, then the function itself
+fn translate_func_def(
+    fd: &SFuncDef,
+    tnv: &Tnv,
+    globals: &HashSet<String>,
+    n: &mut usize,
+) -> Vec<IStmt> {
     let label = Label::UserLabel(fd.alias.clone());
 
-    // todo: formal params
-    let body = fd
-        .body
+    if fd.fps.len() > ARG_REGS.len() {
+        panic!("todo: more than 8 params not supported");
+    }
+
+    // temps are named by alias alone, so the function's locals can be
+    // typed up front. the types are needed to narrow stores to chars and
+    // shorts, and to pick the float and unsigned ops
+    let body = rename_stmts(&fd.body, &HashMap::new(), false, n);
+    let mut tnv = tnv.clone();
+    tnv.vnv.extend(fd.fps.iter().cloned());
+    locals(&body, &mut tnv.vnv);
+    let tnv = &tnv;
+    let mut statics = vec![];
+    local_statics(&body, tnv, &mut statics);
+    let mut names = globals.clone();
+    addressed(&body, &mut names);
+    ADDRESSED.set(names);
+
+    // c labels become local ones, so they can't clash across functions
+    let mut labels = HashMap::new();
+    user_labels(&body, &mut labels);
+    let jumps = Jumps {
+        brk: None,
+        cont: None,
+        labels: &labels,
+        ret: &fd.typ,
+    };
+
+    // formal params arrive in a0-a7 and are spilled to the frame like locals
+    let fps = fd.fps.iter().zip(ARG_REGS).map(|((alias, typ), reg)| {
+        IStmt::Compute(
+            Temp::UserTemp(alias.clone()),
+            narrow(typ, IExpr::TempUse(Temp::PointerReg(reg))),
+        )
+    });
+
+    let body = body
         .iter()
-        .map(|s_stmt| match s_stmt {
+        .flat_map(|s| translate_stmt(s, tnv, &jumps))
+        .collect::<Vec<_>>();
+    let pins = PINS
+        .take()
+        .into_iter()
+        .map(|a| IStmt::Alloca(Temp::UserTemp(a), 1));
+    let body = fps
+        .chain(pins)
+        .chain(body)
+        .map(Box::new)
+        .collect::<Vec<_>>();
+
+    statics
+        .into_iter()
+        .chain(iter::once(IStmt::Seq(label, body)))
+        .collect()
+}
+
+// a nested block's locals are renamed apart, to x.0, x.1 and so on, which no
+// c name can be. so they can shadow, and scopes that share frame slots can't
+// clash. fresh is whether declarations are renamed, which is only inside a
+// nested block, and n counts the renames. statics are always renamed, since
+// their names are symbols in the assembly, shared by every function
+// todo: loop bodies are scopes too, but their locals still live in the function's
+pub fn rename_stmts(
+    stmts: &[Spanned<SStmt>],
+    names: &HashMap<String, String>,
+    fresh: bool,
+    n: &mut usize,
+) -> Vec<Spanned<SStmt>> {
+    let mut names = names.clone();
+    stmts
+        .iter()
+        .map(|s| rename_stmt(s, &mut names, fresh, n))
+        .collect()
+}
+
+fn rename_stmt(
+    s: &Spanned<SStmt>,
+    names: &mut HashMap<String, String>,
+    fresh: bool,
+    n: &mut usize,
+) -> Spanned<SStmt> {
+    let bind =
+        |alias: &String, is_static: bool, names: &mut HashMap<String, String>, n: &mut usize| {
+            if !fresh && !is_static {
+                return alias.clone();
+            }
+            let name = format!("{alias}.{n}");
+            *n += 1;
+            names.insert(alias.clone(), name.clone());
+            name
+        };
+
+    let node = match &s.node {
+        SStmt::Asnmt(vd) => {
+            let expr = Box::new(rename_expr(&vd.expr, names));
+            SStmt::Asnmt(SVarDef {
+                alias: bind(&vd.alias, vd.is_static, names, n),
+                typ: vd.typ.clone(),
+                expr,
+                constant: vd.constant,
+                is_static: vd.is_static,
+            })
+        }
+        SStmt::Decl(vd) => SStmt::Decl(SVarDecl {
+            alias: bind(&vd.alias, vd.is_static, names, n),
+            typ: vd.typ.clone(),
+            constant: vd.constant,
+            is_static: vd.is_static,
+        }),
+        SStmt::Update(vu) => SStmt::Update(SVarUpdate {
+            alias: rename(&vu.alias, names),
+            expr: Box::new(rename_expr(&vu.expr, names)),
+        }),
+        SStmt::Store(st) => SStmt::Store(SStore {
+            lval: Box::new(rename_expr(&st.lval, names)),
+            expr: Box::new(rename_expr(&st.expr, names)),
+            op: st.op.clone(),
+        }),
+        SStmt::Return(e) => SStmt::Return(e.as_ref().map(|e| rename_expr(e, names))),
+        SStmt::Call(e) => SStmt::Call(rename_expr(e, names)),
+        SStmt::IfEls { cond, then, els } => SStmt::IfEls {
+            cond: Box::new(rename_expr(cond, names)),
+            then: Box::new(rename_stmt(then, &mut names.clone(), fresh, n)),
+            els: els
+                .as_ref()
+                .map(|els| Box::new(rename_stmt(els, &mut names.clone(), fresh, n))),
+        },
+        SStmt::Switch { cond, arms } => {
+            let mut anames = names.clone(); // the arms are one scope
+            SStmt::Switch {
+                cond: Box::new(rename_expr(cond, names)),
+                arms: arms
+                    .iter()
+                    .map(|a| SCase {
+                        label: a.label,
+                        body: a
+                            .body
+                            .iter()
+                            .map(|s| rename_stmt(s, &mut anames, fresh, n))
+                            .collect(),
+                    })
+                    .collect(),
+            }
+        }
+        SStmt::While {
+            cond,
+            body,
+            invariants,
+        } => SStmt::While {
+            cond: Box::new(rename_expr(cond, names)),
+            body: rename_stmts(body, names, fresh, n),
+            invariants: rename_contracts(invariants, names),
+        },
+        SStmt::DoWhile { body, cond } => SStmt::DoWhile {
+            body: rename_stmts(body, names, fresh, n),
+            cond: Box::new(rename_expr(cond, names)),
+        },
+        SStmt::For {
+            asnmt,
+            cond,
+            update,
+            body,
+            invariants,
+        } => {
+            let mut fnames = names.clone(); // the header's bindings are the loop's
+            let asnmt = Box::new(rename_stmt(asnmt, &mut fnames, fresh, n));
+            SStmt::For {
+                asnmt,
+                cond: Box::new(rename_expr(cond, &fnames)),
+                update: Box::new(rename_stmt(update, &mut fnames, fresh, n)),
+                body: rename_stmts(body, &fnames, fresh, n),
+                invariants: rename_contracts(invariants, &fnames),
+            }
+        }
+        SStmt::Contract(c) => {
+            SStmt::Contract(rename_contracts(slice::from_ref(c), names).remove(0))
+        }
+        SStmt::Assert(e) => SStmt::Assert(rename_expr(e, names)),
+        SStmt::Block(body) => SStmt::Block(rename_stmts(body, names, true, n)),
+        SStmt::Break | SStmt::Continue | SStmt::Label(_) | SStmt::Goto(_) => s.node.clone(),
+    };
+    Spanned::new(node, s.span)
+}
+
+fn rename_contracts(cs: &[SContract], names: &HashMap<String, String>) -> Vec<SContract> {
+    cs.iter()
+        .map(|c| SContract {
+            kind: c.kind.clone(),
+            expr: rename_expr(&c.expr, names),
+        })
+        .collect()
+}
+
+fn rename_expr(e: &Spanned<SExpr>, names: &HashMap<String, String>) -> Spanned<SExpr> {
+    let r = |e: &Spanned<SExpr>| Box::new(rename_expr(e, names));
+    let node = match &e.node {
+        SExpr::Int(_)
+        | SExpr::TypedInt { .. }
+        | SExpr::Float { .. }
+        | SExpr::Bool(_)
+        | SExpr::Str(_) => e.node.clone(),
+        SExpr::VarApp(alias) => SExpr::VarApp(rename(alias, names)),
+        SExpr::IncDec { op, lval } => SExpr::IncDec {
+            op: op.clone(),
+            lval: r(lval),
+        },
+        SExpr::Index { alias, idx } => SExpr::Index {
+            alias: rename(alias, names),
+            idx: r(idx),
+        },
+        SExpr::FuncApp { alias, aps } => SExpr::FuncApp {
+            alias: alias.clone(), // functions aren't shadowed by locals, yet
+            aps: aps.iter().map(|ap| rename_expr(ap, names)).collect(),
+        },
+        SExpr::UnaryE { op, l } => SExpr::UnaryE {
+            op: op.clone(),
+            l: r(l),
+        },
+        SExpr::BinE { op, l, r: rt } => SExpr::BinE {
+            op: op.clone(),
+            l: r(l),
+            r: r(rt),
+        },
+        SExpr::LogE { op, l, r: rt } => SExpr::LogE {
+            op: op.clone(),
+            l: r(l),
+            r: r(rt),
+        },
+        SExpr::BitE { op, l, r: rt } => SExpr::BitE {
+            op: op.clone(),
+            l: r(l),
+            r: r(rt),
+        },
+        SExpr::RelE { op, l, r: rt } => SExpr::RelE {
+            op: op.clone(),
+            l: r(l),
+            r: r(rt),
+        },
+        SExpr::CondE { cond, then, els } => SExpr::CondE {
+            cond: r(cond),
+            then: r(then),
+            els: r(els),
+        },
+        SExpr::AddrOf(l) => SExpr::AddrOf(r(l)),
+        SExpr::Deref(l) => SExpr::Deref(r(l)),
+        SExpr::SizeOf(l) => SExpr::SizeOf(r(l)),
+        SExpr::SizeOfType(_) | SExpr::Alloc(_) => e.node.clone(),
+        SExpr::AllocArray(t, n) => SExpr::AllocArray(t.clone(), r(n)),
+        SExpr::Cast(t, l) => SExpr::Cast(t.clone(), r(l)),
+        SExpr::Member { base, field } => SExpr::Member {
+            base: r(base),
+            field: field.clone(),
+        },
+    };
+    Spanned::new(node, e.span)
+}
+
+fn rename(alias: &String, names: &HashMap<String, String>) -> String {
+    names.get(alias).unwrap_or(alias).clone()
+}
+
+fn locals<'a>(
+    stmts: impl IntoIterator<Item = &'a Spanned<SStmt>>,
+    vnv: &mut HashMap<String, Type>,
+) {
+    for s in stmts {
+        match &s.node {
             SStmt::Asnmt(vd) => {
-                let expr = translate_expr(&vd.expr);
-                let temp = Temp::UserTemp(vd.alias.clone());
-                IStmt::Compute(temp, expr)
-                // ************************************* ??????????????zsd
-            }
-            SStmt::IfEls {
-                cond: _,
-                then: _,
-                els: _,
-            } => todo!(),
-            SStmt::While { cond: _, body: _ } => todo!(),
-            SStmt::Return(expr) => IStmt::Return(translate_expr(expr)),
+                vnv.insert(vd.alias.clone(), vd.typ.clone());
+            }
+            SStmt::Decl(vd) => {
+                vnv.insert(vd.alias.clone(), vd.typ.clone());
+            }
+            SStmt::IfEls { then, els, .. } => {
+                locals(iter::once(&**then).chain(els.as_deref()), vnv)
+            }
+            SStmt::Switch { arms, .. } => arms.iter().for_each(|a| locals(&a.body, vnv)),
+            SStmt::While { body, .. } | SStmt::DoWhile { body, .. } | SStmt::Block(body) => {
+                locals(body, vnv)
+            }
+            SStmt::For { asnmt, body, .. } => locals(iter::once(&**asnmt).chain(body), vnv),
+            _ => (),
+        }
+    }
+}
+
+fn local_statics(stmts: &[Spanned<SStmt>], tnv: &Tnv, statics: &mut Vec<IStmt>) {
+    for s in stmts {
+        match &s.node {
+            SStmt::Asnmt(vd) if vd.is_static => statics.push(IStmt::Static(
+                vd.alias.clone(),
+                initial(&vd.typ, Some(&vd.expr), tnv),
+                false,
+            )),
+            SStmt::Decl(vd) if vd.is_static => statics.push(IStmt::Static(
+                vd.alias.clone(),
+                initial(&vd.typ, None, tnv),
+                false,
+            )),
+            SStmt::IfEls { then, els, .. } => {
+                local_statics(slice::from_ref(then), tnv, statics);
+                if let Some(els) = els {
+                    local_statics(slice::from_ref(els), tnv, statics);
+                }
+            }
+            SStmt::Switch { arms, .. } => arms
+                .iter()
+                .for_each(|a| local_statics(&a.body, tnv, statics)),
+            SStmt::While { body, .. } | SStmt::DoWhile { body, .. } | SStmt::Block(body) => {
+                local_statics(body, tnv, statics)
+            }
+            SStmt::For { asnmt, body, .. } => {
+                local_statics(slice::from_ref(asnmt), tnv, statics);
+                local_statics(body, tnv, statics)
+            }
+            _ => (),
+        }
+    }
+}
+
+// the variables stmts take the address of, named as they are in them
+fn addressed(stmts: &[Spanned<SStmt>], names: &mut HashSet<String>) {
+    for s in stmts {
+        let found = RefCell::new(vec![]);
+        for e in exprs(&s.node) {
+            any(e, &|e| {
+                if let SExpr::AddrOf(l) = e {
+                    if let SExpr::VarApp(alias) = &l.node {
+                        found.borrow_mut().push(alias.clone());
+                    }
+                }
+                false // so every expr is visited
+            });
+        }
+        names.extend(found.into_inner());
+        match &s.node {
+            SStmt::IfEls { then, els, .. } => {
+                addressed(slice::from_ref(then), names);
+                if let Some(els) = els {
+                    addressed(slice::from_ref(els), names);
+                }
+            }
+            SStmt::Switch { arms, .. } => arms.iter().for_each(|a| addressed(&a.body, names)),
+            SStmt::While { body, .. }
+            | SStmt::DoWhile { body, .. }
+            | SStmt::For { body, .. }
+            | SStmt::Block(body) => addressed(body, names),
+            _ => (),
+        }
+    }
+}
+
+// a static's words before main runs: its initializer converted as if
+// assigned, or zeros. the backend targets rv32, so it's folded under ilp32
+fn initial(typ: &Type, init: Option<&SExpr>, tnv: &Tnv) -> Vec<i32> {
+    let words = words(typ, tnv);
+    match init {
+        Some(e) => vec![evaluator::eval_const(e, typ, &tnv.layouts, &tnv.model)
+            .expect("static initializers are checked by typecheck")],
+        None => vec![0; words],
+    }
+}
+
+// char and short variables live in a word like everything else, so storing a
+// signed one sign extends its low bits: ((e & 0xff) ^ 0x80) - 0x80, whatever
+// the register width. storing an unsigned one zero extends them: e & 0xff
+fn narrow(typ: &Type, e: IExpr) -> IExpr {
+    let mask = |e, m| IExpr::BitOp(IBitOp::And, Box::new(e), Box::new(IExpr::Const(m)));
+    let sext = |e, m, sign| {
+        IExpr::BinOp(
+            IBinOp::Sub,
+            Box::new(IExpr::BitOp(
+                IBitOp::Xor,
+                Box::new(mask(e, m)),
+                Box::new(IExpr::Const(sign)),
+            )),
+            Box::new(IExpr::Const(sign)),
+        )
+    };
+
+    match typ {
+        Type::Char => sext(e, 0xff, 0x80),
+        Type::Short => sext(e, 0xffff, 0x8000),
+        Type::UChar => mask(e, 0xff),
+        Type::UShort => mask(e, 0xffff),
+        _ => e,
+    }
+}
+
+fn typ(e: &SExpr, tnv: &Tnv) -> Type {
+    typer::type_expr(e, tnv, &tnv.vnv).expect("operands are checked by the typer")
+}
+
+// the bytes a value takes in memory, as compiled code lays it out
+fn bytes(t: &Type, tnv: &Tnv) -> i32 {
+    let word = tnv.model.word() as usize;
+    tnv.layouts.bytes(t, word).expect("checked by the typer") as i32
+}
+
+// the words a value takes in a frame or in .data, its bytes rounded up
+fn words(t: &Type, tnv: &Tnv) -> usize {
+    (bytes(t, tnv) as usize).div_ceil(tnv.model.word() as usize)
+}
+
+// the value at addr, of type t. chars are a byte, and the rest a word
+fn load(t: &Type, addr: IExpr) -> IExpr {
+    match t {
+        Type::Char => IExpr::MemByte(Box::new(addr), false),
+        Type::UChar => IExpr::MemByte(Box::new(addr), true),
+        _ => IExpr::Mem(Box::new(addr)),
+    }
+}
+
+// e stored at addr, as many bytes as t takes
+fn store(t: &Type, addr: IExpr, e: IExpr) -> IStmt {
+    match t {
+        Type::Char | Type::UChar => IStmt::MoveByte(addr, e),
+        _ => IStmt::Move(addr, e),
+    }
+}
+
+// a temp that holds an lvalue's address, so that it's taken once. its
+// name is no c identifier, so it shadows nothing
+fn pin() -> String {
+    let a = match fresh_temp() {
+        Temp::MachineTemp(n) => format!(".addr{n}"),
+        _ => unreachable!("fresh temps are machine temps"),
+    };
+    PINS.with_borrow_mut(|pins| pins.push(a.clone()));
+    a
+}
+
+fn sizeof(t: &Type, tnv: &Tnv) -> IExpr {
+    let size = tnv.layouts.sizeof(t, &tnv.model);
+    IExpr::Const(size.expect("checked by the typer") as i32)
+}
+
+// whether an operator on l and r works on unsigned values, once the usual
+// arithmetic conversions have been applied to them
+fn unsigned(l: &SExpr, r: &SExpr, tnv: &Tnv) -> bool {
+    typer::convert(typ(l, tnv), typ(r, tnv)).is_some_and(|t| typer::unsigned(&t))
+}
+
+// whether an int op in t, promoted, is checked for signed overflow. it's
+// the runtime's helper that does it, since the backend narrows the result
+fn traps(t: Option<Type>, tnv: &Tnv) -> bool {
+    tnv.model.overflow == Overflow::Trap && t.is_some_and(|t| !typer::unsigned(&t))
+}
+
+// l op r, for add, sub and mult, through the helper when it traps
+fn int_op(op: IBinOp, l: IExpr, r: IExpr, traps: bool) -> IExpr {
+    match runtime::checked(&op) {
+        Some(helper) if traps => IExpr::Call(Label::UserLabel(helper.to_owned()), vec![l, r]),
+        _ => IExpr::BinOp(op, Box::new(l), Box::new(r)),
+    }
+}
+
+// the type an operator on l and r works in, if it's a floating one
+fn floating(l: &SExpr, r: &SExpr, tnv: &Tnv) -> Option<Type> {
+    typer::convert(typ(l, tnv), typ(r, tnv)).filter(typer::floating)
+}
+
+// e, converted to dt as if assigned to it: between ints and floats, then
+// narrowed
+fn coerce(dt: &Type, e: &SExpr, tnv: &Tnv) -> IExpr {
+    // the typer's explicit cast to it already converted e
+    if matches!(e, SExpr::Cast(t, _) if t == dt) {
+        return translate_expr(e, tnv);
+    }
+    let et = typ(e, tnv);
+    let cvt = |op, e| IExpr::Cvt(op, Box::new(e));
+    let e = translate_expr(e, tnv);
+    let e = match (typer::floating(dt), typer::floating(&et)) {
+        (true, false) if typer::unsigned(&et) => cvt(ICvtOp::UIntToFloat, e),
+        (true, false) if typer::integral(&et) => cvt(ICvtOp::IntToFloat, e),
+        (false, true) if typer::unsigned(dt) => cvt(ICvtOp::FloatToUInt, e),
+        (false, true) => cvt(ICvtOp::FloatToInt, e),
+        _ => e,
+    };
+    // 16 bit ints are kept extended from their width, like shorts, so a
+    // conversion to one from a type whose values it doesn't hold all of
+    // extends again. constants are extended here
+    let model = &tnv.model;
+    let holds = match dt {
+        Type::Int => matches!(et, Type::Bool | Type::Char | Type::UChar | Type::Short),
+        _ => matches!(et, Type::Bool | Type::UChar | Type::UShort),
+    };
+    match (dt, model.int_width, e) {
+        (_, _, e) if holds || et == *dt => narrow(dt, e),
+        (Type::Int, 16, IExpr::Const(n)) => IExpr::Const(model.wrap(n as i64)),
+        (Type::UInt, 16, IExpr::Const(n)) => IExpr::Const(model.unsigned(n) as i32),
+        (Type::Int, 16, e) => narrow(&Type::Short, e),
+        (Type::UInt, 16, e) => narrow(&Type::UShort, e),
+        (_, _, e) => narrow(dt, e),
+    }
+}
+
+// the bytes a pointer arithmetic operand steps by, if it's a pointer the
+// typer marked with a cast to what it points to
+fn stride(e: &SExpr, tnv: &Tnv) -> Option<i32> {
+    match e {
+        SExpr::Cast(Type::Ptr(t), _) => Some(bytes(t, tnv)),
+        _ => None,
+    }
+}
+
+// an arg past a variadic function's params, after the default promotions.
+// chars and shorts are already words, but floats promote to doubles, which
+// the abi passes in a pair of int registers
+fn vararg(e: &SExpr, tnv: &Tnv) -> IExpr {
+    match typ(e, tnv) {
+        t if typer::floating(&t) => unreachable!("rejected by unsupported"),
+        _ => translate_expr(e, tnv),
+    }
+}
+
+fn user_labels(stmts: &[Spanned<SStmt>], labels: &mut HashMap<String, Label>) {
+    for s in stmts {
+        match &s.node {
+            SStmt::Label(l) => {
+                labels.insert(l.clone(), fresh_label());
+            }
+            SStmt::IfEls { then, els, .. } => {
+                user_labels(slice::from_ref(then), labels);
+                if let Some(els) = els {
+                    user_labels(slice::from_ref(els), labels);
+                }
+            }
+            SStmt::Switch { arms, .. } => arms.iter().for_each(|a| user_labels(&a.body, labels)),
+            SStmt::While { body, .. }
+            | SStmt::DoWhile { body, .. }
+            | SStmt::For { body, .. }
+            | SStmt::Block(body) => user_labels(body, labels),
+            _ => (),
+        }
+    }
+}
+
+// where break and continue go: the innermost enclosing loop's (or
+// switch's, for break) labels. gotos go to the function's, and returns
+// convert to its return type
+#[derive(Clone)]
+struct Jumps<'a> {
+    brk: Option<Label>,
+    cont: Option<Label>,
+    labels: &'a HashMap<String, Label>,
+    ret: &'a Type,
+}
+
+fn translate_stmt(s: &SStmt, tnv: &Tnv, jumps: &Jumps) -> Vec<IStmt> {
+    match s {
+        // statics are initialized before main, and have no slot
+        SStmt::Asnmt(SVarDef {
+            is_static: true, ..
         })
-        .map(Box::new)
+        | SStmt::Decl(SVarDecl {
+            is_static: true, ..
+        }) => vec![],
+        SStmt::Asnmt(vd) => {
+            let expr = coerce(&vd.typ, &vd.expr, tnv);
+            let temp = Temp::UserTemp(vd.alias.clone());
+            vec![IStmt::Compute(temp, expr)]
+        }
+        SStmt::Update(vu) => {
+            let expr = coerce(&tnv.vnv[&vu.alias], &vu.expr, tnv);
+            let temp = Temp::UserTemp(vu.alias.clone());
+            vec![IStmt::Compute(temp, expr)]
+        }
+        SStmt::Decl(vd) => {
+            let words = words(&vd.typ, tnv);
+            vec![IStmt::Alloca(Temp::UserTemp(vd.alias.clone()), words)]
+        }
+        // lval op= e is *a = *a op e, with a the lval's address
+        SStmt::Store(SStore {
+            lval,
+            expr,
+            op: Some(op),
+        }) => {
+            let (a, typ) = (pin(), typ(lval, tnv));
+            let mut tnv = tnv.clone();
+            tnv.vnv.insert(a.clone(), Type::Ptr(Box::new(typ)));
+            let at = |e| Box::new(Spanned::new(e, lval.span));
+            let deref = || at(SExpr::Deref(at(SExpr::VarApp(a.clone()))));
+            let st = SStore {
+                lval: deref(),
+                expr: at(SExpr::BinE {
+                    op: op.clone(),
+                    l: deref(),
+                    r: expr.clone(),
+                }),
+                op: None,
+            };
+            iter::once(IStmt::Compute(
+                Temp::UserTemp(a),
+                translate_addr(lval, &tnv),
+            ))
+            .chain(translate_stmt(&SStmt::Store(st), &tnv, jumps))
+            .collect()
+        }
+        SStmt::Store(st) => {
+            let typ =
+                typer::type_expr(&st.lval, tnv, &tnv.vnv).expect("stores are checked by the typer");
+            let (addr, expr) = (translate_addr(&st.lval, tnv), coerce(&typ, &st.expr, tnv));
+            vec![store(&typ, addr, expr)]
+        }
+        SStmt::IfEls {
+            cond: _,
+            then: _,
+            els: _,
+        } => todo!(),
+        SStmt::Switch { cond, arms } => translate_switch(cond, arms, tnv, jumps),
+        SStmt::While { cond, body, .. } => {
+            // loop: cjump cond body end
+            // body: ...
+            //       jump loop
+            // end:
+            let (loop_l, body_l, end_l) = (fresh_label(), fresh_label(), fresh_label());
+            let jumps = Jumps {
+                brk: Some(end_l.clone()),
+                cont: Some(loop_l.clone()),
+                ..jumps.clone()
+            };
+
+            iter::once(IStmt::Label(loop_l.clone()))
+                .chain([
+                    IStmt::CJump(translate_expr(cond, tnv), body_l.clone(), end_l.clone()),
+                    IStmt::Label(body_l),
+                ])
+                .chain(body.iter().flat_map(|s| translate_stmt(s, tnv, &jumps)))
+                .chain([IStmt::Jump(loop_l), IStmt::Label(end_l)])
+                .collect()
+        }
+        SStmt::DoWhile { body, cond } => {
+            // body: ...
+            // test: cjump cond body end
+            // end:
+            let (body_l, test_l, end_l) = (fresh_label(), fresh_label(), fresh_label());
+            let jumps = Jumps {
+                brk: Some(end_l.clone()),
+                cont: Some(test_l.clone()),
+                ..jumps.clone()
+            };
+
+            iter::once(IStmt::Label(body_l.clone()))
+                .chain(body.iter().flat_map(|s| translate_stmt(s, tnv, &jumps)))
+                .chain([
+                    IStmt::Label(test_l),
+                    IStmt::CJump(translate_expr(cond, tnv), body_l, end_l.clone()),
+                    IStmt::Label(end_l),
+                ])
+                .collect()
+        }
+        SStmt::For {
+            asnmt,
+            cond,
+            update,
+            body,
+            ..
+        } => {
+            // for is while with the asnmt hoisted above the loop
+            // and the update sunk to the end of the body, where
+            // continue jumps to
+            let (loop_l, body_l, end_l) = (fresh_label(), fresh_label(), fresh_label());
+            let update_l = fresh_label();
+            let body_jumps = Jumps {
+                brk: Some(end_l.clone()),
+                cont: Some(update_l.clone()),
+                ..jumps.clone()
+            };
+
+            translate_stmt(asnmt, tnv, jumps)
+                .into_iter()
+                .chain([
+                    IStmt::Label(loop_l.clone()),
+                    IStmt::CJump(translate_expr(cond, tnv), body_l.clone(), end_l.clone()),
+                    IStmt::Label(body_l),
+                ])
+                .chain(
+                    body.iter()
+                        .flat_map(|s| translate_stmt(s, tnv, &body_jumps)),
+                )
+                .chain(iter::once(IStmt::Label(update_l)))
+                .chain(translate_stmt(update, tnv, jumps))
+                .chain([IStmt::Jump(loop_l), IStmt::Label(end_l)])
+                .collect()
+        }
+        SStmt::Return(expr) => vec![IStmt::Return(
+            expr.as_ref().map(|e| coerce(jumps.ret, e, tnv)),
+        )],
+        // contracts are comments to a c compiler. loop invariants are too
+        SStmt::Contract(_) => vec![],
+        SStmt::Assert(_) => unreachable!("rejected by unsupported"),
+        // computed into the zero register, which drops it
+        SStmt::Call(e) => vec![IStmt::Compute(
+            Temp::PointerReg(RiscvPointerReg::Z),
+            translate_expr(e, tnv),
+        )],
+        SStmt::Block(body) => vec![IStmt::Scope(
+            body.iter()
+                .flat_map(|s| translate_stmt(s, tnv, jumps))
+                .map(Box::new)
+                .collect(),
+        )],
+        SStmt::Label(l) => vec![IStmt::Label(jumps.labels[l].clone())],
+        SStmt::Goto(l) => vec![IStmt::Jump(
+            jumps
+                .labels
+                .get(l)
+                .cloned()
+                .expect("labels are checked by the typer"),
+        )],
+        SStmt::Break => vec![IStmt::Jump(
+            jumps.brk.clone().expect("breaks are checked by the typer"),
+        )],
+        SStmt::Continue => vec![IStmt::Jump(
+            jumps
+                .cont
+                .clone()
+                .expect("continues are checked by the typer"),
+        )],
+    }
+}
+
+// switches with at least this many cases, spanning at most DENSITY slots
+// per case, index a jump table. sparser ones compare against each case
+const JUMP_TABLE_CASES: usize = 4;
+const DENSITY: i64 = 3;
+
+fn translate_switch(cond: &SExpr, arms: &[SCase], tnv: &Tnv, jumps: &Jumps) -> Vec<IStmt> {
+    // the scrutinee is evaluated once, into a temp no c name can collide with
+    let end_l = fresh_label();
+    let scrut = Temp::UserTemp(format!("switch{end_l}"));
+    let s = || Box::new(IExpr::TempUse(scrut.clone()));
+
+    let arm_ls = arms.iter().map(|_| fresh_label()).collect::<Vec<_>>();
+    let default_l = arms
+        .iter()
+        .zip(&arm_ls)
+        .find(|(a, _)| a.label.is_none())
+        .map_or(end_l.clone(), |(_, l)| l.clone());
+    let cases = arms
+        .iter()
+        .zip(&arm_ls)
+        .filter_map(|(a, l)| Some((a.label?, l.clone())))
         .collect::<Vec<_>>();
 
-    IStmt::Seq(label, body)
+    let (min, max) = (
+        cases.iter().map(|(v, _)| *v).min().unwrap_or(0),
+        cases.iter().map(|(v, _)| *v).max().unwrap_or(0),
+    );
+    let span = max as i64 - min as i64 + 1;
+    let dispatch = if cases.len() >= JUMP_TABLE_CASES && span <= DENSITY * cases.len() as i64 {
+        //        cjump s < min || s > max default table
+        // table: jumptable s - min [case labels, default in the gaps]
+        let table_l = fresh_label();
+        let out_of_range = IExpr::LogOp(
+            ILogOp::Or,
+            Box::new(IExpr::BinOp(IBinOp::Lt, s(), Box::new(IExpr::Const(min)))),
+            Box::new(IExpr::BinOp(IBinOp::Gt, s(), Box::new(IExpr::Const(max)))),
+        );
+        let targets = (min..=max)
+            .map(|v| {
+                cases
+                    .iter()
+                    .find(|(c, _)| *c == v)
+                    .map_or(default_l.clone(), |(_, l)| l.clone())
+            })
+            .collect();
+
+        vec![
+            IStmt::CJump(out_of_range, default_l, table_l.clone()),
+            IStmt::Label(table_l),
+            IStmt::JumpTable(
+                IExpr::BinOp(IBinOp::Sub, s(), Box::new(IExpr::Const(min))),
+                targets,
+            ),
+        ]
+    } else {
+        //       cjump s == c1 arm1 next
+        // next: cjump s == c2 arm2 next'
+        //       ...
+        //       jump default
+        cases
+            .into_iter()
+            .flat_map(|(v, l)| {
+                let next_l = fresh_label();
+                let eq = IExpr::BinOp(IBinOp::Eq, s(), Box::new(IExpr::Const(v)));
+                [IStmt::CJump(eq, l, next_l.clone()), IStmt::Label(next_l)]
+            })
+            .chain(iter::once(IStmt::Jump(default_l)))
+            .collect()
+    };
+
+    // the arms are laid out in order, so each falls through into the next.
+    // break leaves the switch, and continue is still the enclosing loop's
+    let jumps = Jumps {
+        brk: Some(end_l.clone()),
+        ..jumps.clone()
+    };
+    iter::once(IStmt::Compute(scrut.clone(), translate_expr(cond, tnv)))
+        .chain(dispatch)
+        .chain(arms.iter().zip(arm_ls).flat_map(|(a, l)| {
+            iter::once(IStmt::Label(l))
+                .chain(a.body.iter().flat_map(|s| translate_stmt(s, tnv, &jumps)))
+        }))
+        .chain(iter::once(IStmt::Label(end_l)))
+        .collect()
 }
 
-fn translate_expr(e: &SExpr) -> IExpr {
+fn translate_expr(e: &SExpr, tnv: &Tnv) -> IExpr {
     match e {
-        SExpr::Int(n) => IExpr::Const(*n),
+        SExpr::Int(n) | SExpr::TypedInt { val: n, .. } => IExpr::Const(*n),
+        SExpr::Float { lit, .. } => {
+            IExpr::Const(lit.parse::<f32>().expect("checked by the parser").to_bits() as i32)
+        }
+        SExpr::Str(s) => IExpr::Str(s.clone()),
         SExpr::Bool(b) => IExpr::Const(*b as i32),
-        SExpr::UnaryE { op: _, l: _ } => todo!(),
-        SExpr::BinE { op, l, r } => match op {
-            // C language designed as portable assembly makes tree rewrites straightforward
-            SBinOp::Add => IExpr::BinOp(
-                IBinOp::Add,
-                Box::new(translate_expr(l)),
-                Box::new(translate_expr(r)),
+        SExpr::SizeOf(l) => sizeof(&typ(l, tnv), tnv),
+        SExpr::SizeOfType(t) => sizeof(t, tnv),
+        SExpr::Alloc(_) | SExpr::AllocArray(..) => unreachable!("rejected by unsupported"),
+        // a cast converts as assigning would, and to a bool tests for nonzero
+        SExpr::Cast(Type::Bool, l) => {
+            let zero = Box::new(IExpr::Const(0));
+            match typer::floating(&typ(l, tnv)) {
+                true => IExpr::FloatOp(IFloatOp::Neq, Box::new(translate_expr(l, tnv)), zero),
+                false => IExpr::BinOp(IBinOp::Neq, Box::new(translate_expr(l, tnv)), zero),
+            }
+        }
+        SExpr::Cast(t, l) if typer::arith(t) => coerce(t, l, tnv),
+        SExpr::Cast(_, l) => translate_expr(l, tnv),
+        SExpr::UnaryE { op, l } => match op {
+            SUnaryOp::Not => IExpr::Not(Box::new(translate_expr(l, tnv))),
+            SUnaryOp::Add => translate_expr(l, tnv),
+            // an unsigned int's complement is its width's bits flipped
+            SUnaryOp::BitNot => IExpr::BitOp(
+                IBitOp::Xor,
+                Box::new(translate_expr(l, tnv)),
+                Box::new(IExpr::Const(match unsigned(l, l, tnv) {
+                    true => tnv.model.unsigned(-1) as i32,
+                    false => -1,
+                })),
             ),
-            SBinOp::Sub => IExpr::BinOp(
-                IBinOp::Sub,
-                Box::new(translate_expr(l)),
-                Box::new(translate_expr(r)),
+            // a float is negated by flipping its sign bit
+            SUnaryOp::Sub if typer::floating(&typ(l, tnv)) => IExpr::BitOp(
+                IBitOp::Xor,
+                Box::new(translate_expr(l, tnv)),
+                Box::new(IExpr::Const(i32::MIN)),
+            ),
+            SUnaryOp::Sub if unsigned(l, l, tnv) => IExpr::BinOp(
+                IBinOp::SubU,
+                Box::new(IExpr::Const(0)),
+                Box::new(translate_expr(l, tnv)),
             ),
-            SBinOp::Mult => IExpr::BinOp(
-                IBinOp::Mult,
-                Box::new(translate_expr(l)),
-                Box::new(translate_expr(r)),
+            SUnaryOp::Sub => int_op(
+                IBinOp::Sub,
+                IExpr::Const(0),
+                translate_expr(l, tnv),
+                traps(typer::convert(typ(l, tnv), Type::Int), tnv),
             ),
+        },
+        // p + i is i elements past p, and p - q the elements between them
+        SExpr::BinE { op, l, r } if stride(l, tnv).or(stride(r, tnv)).is_some() => {
+            let offset = |i: &SExpr, n: i32| {
+                IExpr::BinOp(
+                    IBinOp::Mult,
+                    Box::new(translate_expr(i, tnv)),
+                    Box::new(IExpr::Const(n)),
+                )
+            };
+            let ptr = |p: &SExpr, off| {
+                IExpr::BinOp(
+                    IBinOp::AddPtr,
+                    Box::new(translate_expr(p, tnv)),
+                    Box::new(off),
+                )
+            };
+            match (op, stride(l, tnv), stride(r, tnv)) {
+                (SBinOp::Sub, Some(n), Some(_)) => IExpr::BinOp(
+                    IBinOp::Div,
+                    Box::new(IExpr::BinOp(
+                        IBinOp::Sub,
+                        Box::new(translate_expr(l, tnv)),
+                        Box::new(translate_expr(r, tnv)),
+                    )),
+                    Box::new(IExpr::Const(n)),
+                ),
+                (SBinOp::Sub, Some(n), None) => ptr(l, offset(r, -n)),
+                (_, Some(n), None) => ptr(l, offset(r, n)),
+                (_, None, Some(n)) => ptr(r, offset(l, n)),
+                _ => unreachable!("checked by the typer"),
+            }
+        }
+        SExpr::BinE { op, l, r } if floating(l, r, tnv).is_some() => {
+            let t = floating(l, r, tnv).expect("just checked");
+            let op = match op {
+                SBinOp::Add => IFloatOp::Add,
+                SBinOp::Sub => IFloatOp::Sub,
+                SBinOp::Mult => IFloatOp::Mult,
+                SBinOp::Div => IFloatOp::Div,
+                SBinOp::Mod => unreachable!("% on floats is rejected by the typer"),
+            };
+            IExpr::FloatOp(
+                op,
+                Box::new(coerce(&t, l, tnv)),
+                Box::new(coerce(&t, r, tnv)),
+            )
+        }
+        SExpr::BinE { op, l, r } => match op {
+            // C language designed as portable assembly makes tree rewrites straightforward
+            SBinOp::Add | SBinOp::Sub | SBinOp::Mult => {
+                let op = match (op, unsigned(l, r, tnv)) {
+                    (SBinOp::Add, false) => IBinOp::Add,
+                    (SBinOp::Sub, false) => IBinOp::Sub,
+                    (_, false) => IBinOp::Mult,
+                    (SBinOp::Add, true) => IBinOp::AddU,
+                    (SBinOp::Sub, true) => IBinOp::SubU,
+                    (_, true) => IBinOp::MultU,
+                };
+                let traps = traps(typer::convert(typ(l, tnv), typ(r, tnv)), tnv);
+                int_op(op, translate_expr(l, tnv), translate_expr(r, tnv), traps)
+            }
             SBinOp::Div => IExpr::BinOp(
-                IBinOp::Div,
-                Box::new(translate_expr(l)),
-                Box::new(translate_expr(r)),
+                match unsigned(l, r, tnv) {
+                    true => IBinOp::DivU,
+                    false => IBinOp::Div,
+                },
+                Box::new(translate_expr(l, tnv)),
+                Box::new(translate_expr(r, tnv)),
             ),
             SBinOp::Mod => IExpr::BinOp(
-                IBinOp::Mod,
-                Box::new(translate_expr(l)),
-                Box::new(translate_expr(r)),
+                match unsigned(l, r, tnv) {
+                    true => IBinOp::ModU,
+                    false => IBinOp::Mod,
+                },
+                Box::new(translate_expr(l, tnv)),
+                Box::new(translate_expr(r, tnv)),
             ),
         },
-        SExpr::LogE { op: _, l: _, r: _ } => todo!(),
-        SExpr::BitE { op: _, l: _, r: _ } => todo!(),
-        SExpr::RelE { op: _, l: _, r: _ } => todo!(),
+        SExpr::LogE { op, l, r } => {
+            let op = match op {
+                SLogOp::And => ILogOp::And,
+                SLogOp::Or => ILogOp::Or,
+            };
+
+            IExpr::LogOp(
+                op,
+                Box::new(translate_expr(l, tnv)),
+                Box::new(translate_expr(r, tnv)),
+            )
+        }
+        SExpr::CondE { cond, then, els } => {
+            let t = typ(e, tnv); // each branch converts to the type of the whole
+            IExpr::Cond(
+                Box::new(translate_expr(cond, tnv)),
+                Box::new(coerce(&t, then, tnv)),
+                Box::new(coerce(&t, els, tnv)),
+            )
+        }
+        SExpr::BitE { op, l, r } => {
+            let op = match op {
+                SBitOp::And => IBitOp::And,
+                SBitOp::Or => IBitOp::Or,
+                SBitOp::Xor => IBitOp::Xor,
+                SBitOp::Shl if unsigned(l, l, tnv) => IBitOp::ShlU,
+                SBitOp::Shl => IBitOp::Shl,
+                SBitOp::Shr if unsigned(l, l, tnv) => IBitOp::ShrU, // by the left's type alone
+                SBitOp::Shr => IBitOp::Shr,
+            };
+
+            IExpr::BitOp(
+                op,
+                Box::new(translate_expr(l, tnv)),
+                Box::new(translate_expr(r, tnv)),
+            )
+        }
+        SExpr::RelE { op, l, r } if floating(l, r, tnv).is_some() => {
+            let t = floating(l, r, tnv).expect("just checked");
+            let op = match op {
+                SRelOp::Eq => IFloatOp::Eq,
+                SRelOp::Neq => IFloatOp::Neq,
+                SRelOp::Lt => IFloatOp::Lt,
+                SRelOp::LtEq => IFloatOp::LtEq,
+                SRelOp::Gt => IFloatOp::Gt,
+                SRelOp::GtEq => IFloatOp::GtEq,
+            };
+            IExpr::FloatOp(
+                op,
+                Box::new(coerce(&t, l, tnv)),
+                Box::new(coerce(&t, r, tnv)),
+            )
+        }
+        SExpr::RelE { op, l, r } => {
+            let op = match (op, unsigned(l, r, tnv)) {
+                (SRelOp::Eq, _) => IBinOp::Eq,
+                (SRelOp::Neq, _) => IBinOp::Neq,
+                (SRelOp::Lt, false) => IBinOp::Lt,
+                (SRelOp::LtEq, false) => IBinOp::LtEq,
+                (SRelOp::Gt, false) => IBinOp::Gt,
+                (SRelOp::GtEq, false) => IBinOp::GtEq,
+                (SRelOp::Lt, true) => IBinOp::LtU,
+                (SRelOp::LtEq, true) => IBinOp::LtEqU,
+                (SRelOp::Gt, true) => IBinOp::GtU,
+                (SRelOp::GtEq, true) => IBinOp::GtEqU,
+            };
+
+            IExpr::BinOp(
+                op,
+                Box::new(translate_expr(l, tnv)),
+                Box::new(translate_expr(r, tnv)),
+            )
+        }
+        // an array's value is its first element's address
+        SExpr::VarApp(alias) if matches!(tnv.vnv.get(alias), Some(Type::Arr(..))) => {
+            IExpr::Addr(Temp::UserTemp(alias.clone()))
+        }
+        SExpr::VarApp(alias)
+            if matches!(tnv.vnv.get(alias), Some(Type::Char | Type::UChar))
+                && ADDRESSED.with_borrow(|names| names.contains(alias)) =>
+        {
+            narrow(
+                &tnv.vnv[alias],
+                IExpr::TempUse(Temp::UserTemp(alias.clone())),
+            )
+        }
         SExpr::VarApp(alias) => IExpr::TempUse(Temp::UserTemp(alias.clone())),
+        SExpr::AddrOf(l) => translate_addr(l, tnv),
+        SExpr::Deref(_) | SExpr::Index { .. } | SExpr::Member { .. } => {
+            load(&typ(e, tnv), translate_addr(e, tnv))
+        }
+        SExpr::IncDec { op, lval } => {
+            // the post forms undo the step on the updated value rather than
+            // keeping the old one in a temp. exact, since both wrap (or narrow).
+            // an lval other than a variable is read and written through its
+            // address, pinned to a temp first
+            let lt = typ(lval, tnv);
+            let (pinned, t) = match &lval.node {
+                SExpr::VarApp(alias) => (None, Temp::UserTemp(alias.clone())),
+                l => {
+                    let a = Temp::UserTemp(pin());
+                    let addr = IStmt::Compute(a.clone(), translate_addr(l, tnv));
+                    (Some(addr), a)
+                }
+            };
+            let x = || {
+                Box::new(match pinned {
+                    None => IExpr::TempUse(t.clone()),
+                    Some(_) => load(&lt, IExpr::TempUse(t.clone())),
+                })
+            };
+            let one = || Box::new(IExpr::Const(1));
+            let (step, undo) = match (op, unsigned(lval, lval, tnv)) {
+                (SIncDecOp::PreInc | SIncDecOp::PostInc, false) => (IBinOp::Add, IBinOp::Sub),
+                (SIncDecOp::PreDec | SIncDecOp::PostDec, false) => (IBinOp::Sub, IBinOp::Add),
+                (SIncDecOp::PreInc | SIncDecOp::PostInc, true) => (IBinOp::AddU, IBinOp::SubU),
+                (SIncDecOp::PreDec | SIncDecOp::PostDec, true) => (IBinOp::SubU, IBinOp::AddU),
+            };
+            let traps = traps(typer::convert(lt.clone(), Type::Int), tnv);
+            let stepped = narrow(&lt, int_op(step, *x(), *one(), traps));
+            let update = match pinned {
+                None => IStmt::Compute(t.clone(), stepped),
+                Some(_) => store(&lt, IExpr::TempUse(t.clone()), stepped),
+            };
+
+            let e = match op {
+                SIncDecOp::PreInc | SIncDecOp::PreDec => IExpr::ESeq(Box::new(update), x()),
+                SIncDecOp::PostInc | SIncDecOp::PostDec => {
+                    let old = narrow(&lt, IExpr::BinOp(undo, x(), one()));
+                    IExpr::ESeq(Box::new(update), Box::new(old))
+                }
+            };
+            match pinned {
+                None => e,
+                Some(addr) => IExpr::ESeq(Box::new(addr), Box::new(e)),
+            }
+        }
         SExpr::FuncApp { alias, aps: ap } => {
-            let aps = ap.iter().map(translate_expr).collect::<Vec<_>>();
+            let fps = &tnv.fnv[alias].fp;
+            let varargs = ap[fps.len()..].iter().map(|ap| vararg(ap, tnv));
+            let aps = ap
+                .iter()
+                .zip(fps)
+                .map(|(ap, fpt)| coerce(fpt, ap, tnv))
+                .chain(varargs)
+                .collect::<Vec<_>>();
             IExpr::Call(Label::UserLabel(alias.clone()), aps)
         }
     }
 }
 
+// an lvalue's address: &x is x's slot, &*e is just e, and &a[e] is
+// e elements past a's first, and &s.f is f's offset past s, in bytes
+fn translate_addr(lval: &SExpr, tnv: &Tnv) -> IExpr {
+    match lval {
+        SExpr::VarApp(alias) => IExpr::Addr(Temp::UserTemp(alias.clone())),
+        SExpr::Deref(l) => translate_expr(l, tnv),
+        SExpr::Index { alias, idx } => {
+            let (base, elem) = match &tnv.vnv[alias] {
+                // a pointer's elements are where it points, an array's are its own
+                Type::Ptr(t) => (IExpr::TempUse(Temp::UserTemp(alias.clone())), t),
+                Type::Arr(t, _) => (IExpr::Addr(Temp::UserTemp(alias.clone())), t),
+                _ => unreachable!("indexing is checked by the typer"),
+            };
+            IExpr::BinOp(
+                IBinOp::AddPtr,
+                Box::new(base),
+                Box::new(IExpr::BinOp(
+                    IBinOp::Mult,
+                    Box::new(translate_expr(idx, tnv)),
+                    Box::new(IExpr::Const(bytes(elem, tnv))),
+                )),
+            )
+        }
+        SExpr::Member { base, field } => {
+            let (Type::Struct(s) | Type::Union(s)) = typ(base, tnv) else {
+                unreachable!("members are checked by the typer")
+            };
+            let offset = tnv
+                .layouts
+                .byte_offset(&s, field, tnv.model.word() as usize)
+                .expect("members are checked by the typer");
+            IExpr::BinOp(
+                IBinOp::AddPtr,
+                Box::new(translate_addr(base, tnv)),
+                Box::new(IExpr::Const(offset as i32)),
+            )
+        }
+        _ => unreachable!("lvalues are checked by the parser"),
+    }
+}
+
 #[cfg(test)]
 mod test_arith {
     use crate::lexer;
@@ -95,13 +1351,9 @@ mod test_arith {
 
     #[test]
     fn add() {
-        let chars = fs::read(format!("{TEST_DIR}/add.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let src = fs::read_to_string(format!("{TEST_DIR}/add.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let src_tree = parser_ast::parse_prg(&tokens).unwrap();
         let _ = typer::type_prg(&src_tree).unwrap();
         let trgt_tree = super::translate(&src_tree);
@@ -117,6 +1369,83 @@ mod test_arith {
                     - Const: 10
         "###);
     }
+
+    #[test]
+    fn casts() {
+        // narrowed and converted as assigning would
+        let src = "int main() { int n = 300; return (char) n + (int) 2.5f; }";
+
+        let tokens = lexer::lex(src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = super::translate(&src_tree);
+
+        insta::assert_yaml_snapshot!(trgt_tree, @r###"
+        ---
+        - Seq:
+            - UserLabel: main
+            - - Compute:
+                  - UserTemp: n
+                  - Const: 300
+              - Return:
+                  BinOp:
+                    - Add
+                    - BinOp:
+                        - Sub
+                        - BitOp:
+                            - Xor
+                            - BitOp:
+                                - And
+                                - TempUse:
+                                    UserTemp: n
+                                - Const: 255
+                            - Const: 128
+                        - Const: 128
+                    - Cvt:
+                        - FloatToInt
+                        - Const: 1075838976
+        "###);
+    }
+
+    #[test]
+    fn ptr_arith() {
+        // p + i steps by i words, and p - q counts the words between them
+        let src = "int main() { int a[4]; int *p = a + 1; return p - a; }";
+
+        let tokens = lexer::lex(src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = super::translate(&typer::explicit(&src_tree));
+
+        insta::assert_yaml_snapshot!(trgt_tree, @r###"
+        ---
+        - Seq:
+            - UserLabel: main
+            - - Alloca:
+                  - UserTemp: a
+                  - 4
+              - Compute:
+                  - UserTemp: p
+                  - BinOp:
+                      - AddPtr
+                      - Addr:
+                          UserTemp: a
+                      - BinOp:
+                          - Mult
+                          - Const: 1
+                          - Const: 4
+              - Return:
+                  BinOp:
+                    - Div
+                    - BinOp:
+                        - Sub
+                        - TempUse:
+                            UserTemp: p
+                        - Addr:
+                            UserTemp: a
+                    - Const: 4
+        "###);
+    }
 }
 
 #[cfg(test)]
@@ -130,13 +1459,9 @@ mod test_bindings {
 
     #[test]
     fn asnmt() {
-        let chars = fs::read(format!("{TEST_DIR}/asnmt.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let src = fs::read_to_string(format!("{TEST_DIR}/asnmt.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let src_tree = parser_ast::parse_prg(&tokens).unwrap();
         let _ = typer::type_prg(&src_tree).unwrap();
         let trgt_tree = super::translate(&src_tree);
@@ -153,6 +1478,836 @@ mod test_bindings {
                     UserTemp: x
         "###);
     }
+
+    #[test]
+    fn bytes() {
+        // chars are a byte in memory, and a char whose address is taken is
+        // narrowed again when read
+        let src = fs::read_to_string(format!("{TEST_DIR}/bytes.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = super::translate(&src_tree);
+
+        insta::assert_yaml_snapshot!(trgt_tree, @r###"
+        ---
+        - Seq:
+            - UserLabel: main
+            - - Alloca:
+                  - UserTemp: buf
+                  - 1
+              - MoveByte:
+                  - BinOp:
+                      - AddPtr
+                      - Addr:
+                          UserTemp: buf
+                      - BinOp:
+                          - Mult
+                          - Const: 1
+                          - Const: 1
+                  - BinOp:
+                      - Sub
+                      - BitOp:
+                          - Xor
+                          - BitOp:
+                              - And
+                              - Const: 104
+                              - Const: 255
+                          - Const: 128
+                      - Const: 128
+              - Alloca:
+                  - UserTemp: t
+                  - 2
+              - Move:
+                  - BinOp:
+                      - AddPtr
+                      - Addr:
+                          UserTemp: t
+                      - Const: 4
+                  - Const: 7
+              - Compute:
+                  - UserTemp: c
+                  - BinOp:
+                      - Sub
+                      - BitOp:
+                          - Xor
+                          - BitOp:
+                              - And
+                              - BinOp:
+                                  - Sub
+                                  - Const: 0
+                                  - Const: 1
+                              - Const: 255
+                          - Const: 128
+                      - Const: 128
+              - Compute:
+                  - UserTemp: p
+                  - Addr:
+                      UserTemp: c
+              - MoveByte:
+                  - TempUse:
+                      UserTemp: p
+                  - BinOp:
+                      - Sub
+                      - BitOp:
+                          - Xor
+                          - BitOp:
+                              - And
+                              - Const: 98
+                              - Const: 255
+                          - Const: 128
+                      - Const: 128
+              - Return:
+                  BinOp:
+                    - Add
+                    - BinOp:
+                        - Add
+                        - MemByte:
+                            - BinOp:
+                                - AddPtr
+                                - Addr:
+                                    UserTemp: buf
+                                - BinOp:
+                                    - Mult
+                                    - Const: 1
+                                    - Const: 1
+                            - false
+                        - Mem:
+                            BinOp:
+                              - AddPtr
+                              - Addr:
+                                  UserTemp: t
+                              - Const: 4
+                    - BinOp:
+                        - Sub
+                        - BitOp:
+                            - Xor
+                            - BitOp:
+                                - And
+                                - TempUse:
+                                    UserTemp: c
+                                - Const: 255
+                            - Const: 128
+                        - Const: 128
+        "###);
+    }
+
+    #[test]
+    fn members() {
+        // each struct has its own y: q.y is at q's start, p.y a word into p
+        let src = "struct a { int x; int y; }; struct b { int y; int z; };
+            int main() { struct a p; struct b q; p.y = 1; q.y = 2; return 0; }";
+
+        let tokens = lexer::lex(src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = super::translate(&src_tree);
+
+        insta::assert_yaml_snapshot!(trgt_tree, @r###"
+        ---
+        - Seq:
+            - UserLabel: main
+            - - Alloca:
+                  - UserTemp: p
+                  - 2
+              - Alloca:
+                  - UserTemp: q
+                  - 2
+              - Move:
+                  - BinOp:
+                      - AddPtr
+                      - Addr:
+                          UserTemp: p
+                      - Const: 4
+                  - Const: 1
+              - Move:
+                  - BinOp:
+                      - AddPtr
+                      - Addr:
+                          UserTemp: q
+                      - Const: 0
+                  - Const: 2
+              - Return:
+                  Const: 0
+        "###);
+    }
+
+    #[test]
+    fn arrow() {
+        // p->y and (*p).y are both a word past where p points
+        let src = "struct point { int x; int y; };
+            int main() { struct point pt; struct point *p = &pt; p->y = 4; return (*p).y; }";
+
+        let tokens = lexer::lex(src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = super::translate(&src_tree);
+
+        insta::assert_yaml_snapshot!(trgt_tree, @r###"
+        ---
+        - Seq:
+            - UserLabel: main
+            - - Alloca:
+                  - UserTemp: pt
+                  - 2
+              - Compute:
+                  - UserTemp: p
+                  - Addr:
+                      UserTemp: pt
+              - Move:
+                  - BinOp:
+                      - AddPtr
+                      - TempUse:
+                          UserTemp: p
+                      - Const: 4
+                  - Const: 4
+              - Return:
+                  Mem:
+                    BinOp:
+                      - AddPtr
+                      - TempUse:
+                          UserTemp: p
+                      - Const: 4
+        "###);
+    }
+
+    #[test]
+    fn asnmt_lval() {
+        // a[i++]'s address is pinned to a temp, so i++ runs once for both
+        // the read and the write
+        let src = "int main() { int a[2]; int i = 0; a[i++] += 2; return a[0]++; }";
+
+        let tokens = lexer::lex(src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = super::translate(&src_tree);
+
+        insta::assert_yaml_snapshot!(trgt_tree, @r###"
+        ---
+        - Seq:
+            - UserLabel: main
+            - - Alloca:
+                  - UserTemp: ".addr0"
+                  - 1
+              - Alloca:
+                  - UserTemp: ".addr1"
+                  - 1
+              - Alloca:
+                  - UserTemp: a
+                  - 2
+              - Compute:
+                  - UserTemp: i
+                  - Const: 0
+              - Compute:
+                  - UserTemp: ".addr0"
+                  - BinOp:
+                      - AddPtr
+                      - Addr:
+                          UserTemp: a
+                      - BinOp:
+                          - Mult
+                          - ESeq:
+                              - Compute:
+                                  - UserTemp: i
+                                  - BinOp:
+                                      - Add
+                                      - TempUse:
+                                          UserTemp: i
+                                      - Const: 1
+                              - BinOp:
+                                  - Sub
+                                  - TempUse:
+                                      UserTemp: i
+                                  - Const: 1
+                          - Const: 4
+              - Move:
+                  - TempUse:
+                      UserTemp: ".addr0"
+                  - BinOp:
+                      - Add
+                      - Mem:
+                          TempUse:
+                            UserTemp: ".addr0"
+                      - Const: 2
+              - Return:
+                  ESeq:
+                    - Compute:
+                        - UserTemp: ".addr1"
+                        - BinOp:
+                            - AddPtr
+                            - Addr:
+                                UserTemp: a
+                            - BinOp:
+                                - Mult
+                                - Const: 0
+                                - Const: 4
+                    - ESeq:
+                        - Move:
+                            - TempUse:
+                                UserTemp: ".addr1"
+                            - BinOp:
+                                - Add
+                                - Mem:
+                                    TempUse:
+                                      UserTemp: ".addr1"
+                                - Const: 1
+                        - BinOp:
+                            - Sub
+                            - Mem:
+                                TempUse:
+                                  UserTemp: ".addr1"
+                            - Const: 1
+        "###);
+    }
+
+    #[test]
+    fn blocks() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/blocks.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = super::translate(&src_tree);
+
+        insta::assert_yaml_snapshot!(trgt_tree, @r###"
+        ---
+        - Seq:
+            - UserLabel: main
+            - - Compute:
+                  - UserTemp: x
+                  - Const: 1
+              - Compute:
+                  - UserTemp: sum
+                  - Const: 0
+              - Scope:
+                  - Compute:
+                      - UserTemp: x.0
+                      - Const: 10
+                  - Compute:
+                      - UserTemp: sum
+                      - BinOp:
+                          - Add
+                          - TempUse:
+                              UserTemp: sum
+                          - TempUse:
+                              UserTemp: x.0
+                  - Scope:
+                      - Compute:
+                          - UserTemp: x.1
+                          - Const: 100
+                      - Compute:
+                          - UserTemp: sum
+                          - BinOp:
+                              - Add
+                              - TempUse:
+                                  UserTemp: sum
+                              - TempUse:
+                                  UserTemp: x.1
+                  - Compute:
+                      - UserTemp: sum
+                      - BinOp:
+                          - Add
+                          - TempUse:
+                              UserTemp: sum
+                          - TempUse:
+                              UserTemp: x.0
+                  - Compute:
+                      - UserTemp: x.0
+                      - BinOp:
+                          - Add
+                          - TempUse:
+                              UserTemp: x.0
+                          - Const: 1
+              - Compute:
+                  - UserTemp: sum
+                  - BinOp:
+                      - Add
+                      - TempUse:
+                          UserTemp: sum
+                      - TempUse:
+                          UserTemp: x
+              - Scope:
+                  - Compute:
+                      - UserTemp: y.2
+                      - Const: 1000
+                  - Compute:
+                      - UserTemp: p.3
+                      - Addr:
+                          UserTemp: x
+                  - Move:
+                      - TempUse:
+                          UserTemp: p.3
+                      - BinOp:
+                          - Add
+                          - Mem:
+                              TempUse:
+                                UserTemp: p.3
+                          - TempUse:
+                              UserTemp: y.2
+              - Scope:
+                  - Compute:
+                      - UserTemp: z.4
+                      - Const: 5
+                  - Compute:
+                      - UserTemp: sum
+                      - BinOp:
+                          - Add
+                          - TempUse:
+                              UserTemp: sum
+                          - TempUse:
+                              UserTemp: z.4
+              - Return:
+                  BinOp:
+                    - Add
+                    - TempUse:
+                        UserTemp: sum
+                    - TempUse:
+                        UserTemp: x
+        "###);
+    }
+}
+
+#[cfg(test)]
+mod test_control {
+    use crate::lexer;
+    use crate::parser_ast;
+    use crate::typer;
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/control";
+
+    #[test]
+    fn while_false() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/while_false.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = super::translate(&src_tree);
+
+        insta::assert_yaml_snapshot!(trgt_tree, @r###"
+        ---
+        - Seq:
+            - UserLabel: main
+            - - Label:
+                  MachineLabel: 0
+              - CJump:
+                  - Const: 0
+                  - MachineLabel: 1
+                  - MachineLabel: 2
+              - Label:
+                  MachineLabel: 1
+              - Return:
+                  Const: 9
+              - Jump:
+                  MachineLabel: 0
+              - Label:
+                  MachineLabel: 2
+              - Return:
+                  Const: 10
+        "###);
+    }
+
+    #[test]
+    fn switch_sparse() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/switch_sparse.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = super::translate(&src_tree);
+
+        insta::assert_yaml_snapshot!(trgt_tree, @r###"
+        ---
+        - Seq:
+            - UserLabel: main
+            - - Compute:
+                  - UserTemp: x
+                  - Const: 100
+              - Compute:
+                  - UserTemp: y
+                  - Const: 0
+              - Compute:
+                  - UserTemp: switch.L0
+                  - TempUse:
+                      UserTemp: x
+              - CJump:
+                  - BinOp:
+                      - Eq
+                      - TempUse:
+                          UserTemp: switch.L0
+                      - Const: 1
+                  - MachineLabel: 1
+                  - MachineLabel: 4
+              - Label:
+                  MachineLabel: 4
+              - CJump:
+                  - BinOp:
+                      - Eq
+                      - TempUse:
+                          UserTemp: switch.L0
+                      - Const: 100
+                  - MachineLabel: 2
+                  - MachineLabel: 5
+              - Label:
+                  MachineLabel: 5
+              - CJump:
+                  - BinOp:
+                      - Eq
+                      - TempUse:
+                          UserTemp: switch.L0
+                      - Const: 1000
+                  - MachineLabel: 3
+                  - MachineLabel: 6
+              - Label:
+                  MachineLabel: 6
+              - Jump:
+                  MachineLabel: 0
+              - Label:
+                  MachineLabel: 1
+              - Compute:
+                  - UserTemp: y
+                  - Const: 1
+              - Label:
+                  MachineLabel: 2
+              - Compute:
+                  - UserTemp: y
+                  - BinOp:
+                      - Add
+                      - TempUse:
+                          UserTemp: y
+                      - Const: 100
+              - Label:
+                  MachineLabel: 3
+              - Compute:
+                  - UserTemp: y
+                  - BinOp:
+                      - Add
+                      - TempUse:
+                          UserTemp: y
+                      - Const: 1000
+              - Label:
+                  MachineLabel: 0
+              - Compute:
+                  - UserTemp: switch.L7
+                  - TempUse:
+                      UserTemp: y
+              - CJump:
+                  - BinOp:
+                      - Eq
+                      - TempUse:
+                          UserTemp: switch.L7
+                      - Const: 7
+                  - MachineLabel: 8
+                  - MachineLabel: 9
+              - Label:
+                  MachineLabel: 9
+              - Jump:
+                  MachineLabel: 7
+              - Label:
+                  MachineLabel: 8
+              - Compute:
+                  - UserTemp: y
+                  - Const: 0
+              - Label:
+                  MachineLabel: 7
+              - Return:
+                  TempUse:
+                    UserTemp: y
+        "###);
+    }
+
+    #[test]
+    fn break_continue() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/break_continue.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = super::translate(&src_tree);
+
+        insta::assert_yaml_snapshot!(trgt_tree, @r###"
+        ---
+        - Seq:
+            - UserLabel: main
+            - - Compute:
+                  - UserTemp: sum
+                  - Const: 0
+              - Compute:
+                  - UserTemp: i
+                  - Const: 0
+              - Label:
+                  MachineLabel: 0
+              - CJump:
+                  - BinOp:
+                      - Lt
+                      - TempUse:
+                          UserTemp: i
+                      - Const: 10
+                  - MachineLabel: 1
+                  - MachineLabel: 2
+              - Label:
+                  MachineLabel: 1
+              - Compute:
+                  - UserTemp: i
+                  - BinOp:
+                      - Add
+                      - TempUse:
+                          UserTemp: i
+                      - Const: 1
+              - Compute:
+                  - UserTemp: switch.L3
+                  - BinOp:
+                      - Mod
+                      - TempUse:
+                          UserTemp: i
+                      - Const: 3
+              - CJump:
+                  - BinOp:
+                      - Eq
+                      - TempUse:
+                          UserTemp: switch.L3
+                      - Const: 0
+                  - MachineLabel: 4
+                  - MachineLabel: 5
+              - Label:
+                  MachineLabel: 5
+              - Jump:
+                  MachineLabel: 3
+              - Label:
+                  MachineLabel: 4
+              - Jump:
+                  MachineLabel: 0
+              - Label:
+                  MachineLabel: 3
+              - Compute:
+                  - UserTemp: switch.L6
+                  - TempUse:
+                      UserTemp: i
+              - CJump:
+                  - BinOp:
+                      - Eq
+                      - TempUse:
+                          UserTemp: switch.L6
+                      - Const: 8
+                  - MachineLabel: 7
+                  - MachineLabel: 9
+              - Label:
+                  MachineLabel: 9
+              - Jump:
+                  MachineLabel: 8
+              - Label:
+                  MachineLabel: 7
+              - Jump:
+                  MachineLabel: 6
+              - Label:
+                  MachineLabel: 8
+              - Compute:
+                  - UserTemp: sum
+                  - BinOp:
+                      - Add
+                      - TempUse:
+                          UserTemp: sum
+                      - TempUse:
+                          UserTemp: i
+              - Jump:
+                  MachineLabel: 0
+              - Label:
+                  MachineLabel: 6
+              - Jump:
+                  MachineLabel: 2
+              - Jump:
+                  MachineLabel: 0
+              - Label:
+                  MachineLabel: 2
+              - Compute:
+                  - UserTemp: evens
+                  - Const: 0
+              - Compute:
+                  - UserTemp: j
+                  - Const: 0
+              - Label:
+                  MachineLabel: 10
+              - CJump:
+                  - BinOp:
+                      - Lt
+                      - TempUse:
+                          UserTemp: j
+                      - Const: 6
+                  - MachineLabel: 11
+                  - MachineLabel: 12
+              - Label:
+                  MachineLabel: 11
+              - Compute:
+                  - UserTemp: switch.L14
+                  - BinOp:
+                      - Mod
+                      - TempUse:
+                          UserTemp: j
+                      - Const: 2
+              - CJump:
+                  - BinOp:
+                      - Eq
+                      - TempUse:
+                          UserTemp: switch.L14
+                      - Const: 1
+                  - MachineLabel: 15
+                  - MachineLabel: 16
+              - Label:
+                  MachineLabel: 16
+              - Jump:
+                  MachineLabel: 14
+              - Label:
+                  MachineLabel: 15
+              - Jump:
+                  MachineLabel: 13
+              - Label:
+                  MachineLabel: 14
+              - Compute:
+                  - UserTemp: evens
+                  - BinOp:
+                      - Add
+                      - TempUse:
+                          UserTemp: evens
+                      - TempUse:
+                          UserTemp: j
+              - Label:
+                  MachineLabel: 13
+              - Compute:
+                  - UserTemp: j
+                  - BinOp:
+                      - Add
+                      - TempUse:
+                          UserTemp: j
+                      - Const: 1
+              - Jump:
+                  MachineLabel: 10
+              - Label:
+                  MachineLabel: 12
+              - Return:
+                  BinOp:
+                    - Add
+                    - BinOp:
+                        - Add
+                        - BinOp:
+                            - Mult
+                            - TempUse:
+                                UserTemp: evens
+                            - Const: 10000
+                        - BinOp:
+                            - Mult
+                            - TempUse:
+                                UserTemp: sum
+                            - Const: 100
+                    - TempUse:
+                        UserTemp: i
+        "###);
+    }
+
+    #[test]
+    fn goto() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/goto.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let _ = typer::type_prg(&src_tree).unwrap();
+        let trgt_tree = super::translate(&src_tree);
+
+        insta::assert_yaml_snapshot!(trgt_tree, @r###"
+        ---
+        - Seq:
+            - UserLabel: main
+            - - Compute:
+                  - UserTemp: i
+                  - Const: 0
+              - Compute:
+                  - UserTemp: sum
+                  - Const: 0
+              - Label:
+                  MachineLabel: 0
+              - Compute:
+                  - UserTemp: i
+                  - BinOp:
+                      - Add
+                      - TempUse:
+                          UserTemp: i
+                      - Const: 1
+              - Compute:
+                  - UserTemp: sum
+                  - BinOp:
+                      - Add
+                      - TempUse:
+                          UserTemp: sum
+                      - TempUse:
+                          UserTemp: i
+              - Compute:
+                  - UserTemp: switch.L2
+                  - TempUse:
+                      UserTemp: i
+              - CJump:
+                  - BinOp:
+                      - Eq
+                      - TempUse:
+                          UserTemp: switch.L2
+                      - Const: 5
+                  - MachineLabel: 3
+                  - MachineLabel: 4
+              - Label:
+                  MachineLabel: 4
+              - Jump:
+                  MachineLabel: 2
+              - Label:
+                  MachineLabel: 3
+              - Jump:
+                  MachineLabel: 1
+              - Label:
+                  MachineLabel: 2
+              - Jump:
+                  MachineLabel: 0
+              - Label:
+                  MachineLabel: 1
+              - Return:
+                  TempUse:
+                    UserTemp: sum
+        "###);
+    }
+
+    #[test]
+    fn unsupported() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/ifels_then.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let e = super::unsupported(&src_tree).unwrap_err();
+        let span = crate::source::located(&e).unwrap().span;
+        assert_eq!(
+            e.to_string(),
+            "if statements aren't compiled yet, run with interpretc0"
+        );
+        assert_eq!(&src[span.offset(&src)..][..6], "if (1)");
+
+        let src = fs::read_to_string(format!("{TEST_DIR}/while_false.c")).expect("file dne");
+        let src_tree = parser_ast::parse_prg(&lexer::lex(&src).unwrap()).unwrap();
+        assert!(super::unsupported(&src_tree).is_ok());
+
+        // there's no heap to compile alloc to
+        let src =
+            fs::read_to_string("tests/fixtures/snap/shared/bindings/alloc.c").expect("file dne");
+        let src_tree = parser_ast::parse_prg(&lexer::lex(&src).unwrap()).unwrap();
+        let e = super::unsupported(&src_tree).unwrap_err();
+        let span = crate::source::located(&e).unwrap().span;
+        assert_eq!(&src[span.offset(&src)..][..12], "int *a = all");
+
+        // printf's %f takes a double, which floats aren't widened to
+        let src = fs::read_to_string("tests/fixtures/snap/shared/bindings/printf_float.c")
+            .expect("file dne");
+        let src_tree = parser_ast::parse_prg(&lexer::lex(&src).unwrap()).unwrap();
+        let e = super::unsupported(&src_tree).unwrap_err();
+        let span = crate::source::located(&e).unwrap().span;
+        assert_eq!(&src[span.offset(&src)..][..12], "int y = prin");
+        assert!(e.to_string().contains("aren't compiled yet"), "{e}");
+    }
 }
 
 #[cfg(test)]
@@ -166,13 +2321,9 @@ mod test_functions {
 
     #[test]
     fn composition() {
-        let chars = fs::read(format!("{TEST_DIR}/composition.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let src = fs::read_to_string(format!("{TEST_DIR}/composition.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let src_tree = parser_ast::parse_prg(&tokens).unwrap();
         let _ = typer::type_prg(&src_tree).unwrap();
         let trgt_tree = super::translate(&src_tree);
@@ -212,17 +2363,33 @@ mod test_functions {
 
     #[test]
     fn formal_param() {
-        let chars = fs::read(format!("{TEST_DIR}/formal_param.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let src = fs::read_to_string(format!("{TEST_DIR}/formal_param.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let src_tree = parser_ast::parse_prg(&tokens).unwrap();
         let _ = typer::type_prg(&src_tree).unwrap();
         let trgt_tree = super::translate(&src_tree);
 
-        insta::assert_yaml_snapshot!(trgt_tree, @r"");
+        insta::assert_yaml_snapshot!(trgt_tree, @r###"
+        ---
+        - Seq:
+            - UserLabel: f
+            - - Compute:
+                  - UserTemp: x
+                  - TempUse:
+                      PointerReg: A0
+              - Return:
+                  BinOp:
+                    - Add
+                    - TempUse:
+                        UserTemp: x
+                    - Const: 10
+        - Seq:
+            - UserLabel: main
+            - - Return:
+                  Call:
+                    - UserLabel: f
+                    - - Const: 9
+        "###);
     }
 }
@@ -0,0 +1,331 @@
+use crate::{evaluator, pretty, SCase, SDef, SExpr, SFuncDef, SPrg, SStmt, SVarDef, Spanned, Type};
+use std::iter;
+
+// the program as c89, for --emit c, so gcc or clang can check what picoc089
+// accepts. it's the tree lowered to what c89 has, then pretty printed:
+//   - contracts are erased, and assert(e); is assert.h's
+//   - true is 1 and false 0
+//   - alloc(T) is calloc(1, sizeof(T)), and alloc_array(T, n) is
+//     calloc(n, sizeof(T)), which zero the same way
+//   - for loops are while loops, their update after the body. a continue
+//     of theirs jumps to it, as continue_<n>
+//   - declarations come first in their block, so one after a stmt opens a
+//     block of its own, running to the end of the one it was in
+// conversions are the usual arithmetic ones c has, so they're left implicit,
+// and casts are c's own
+pub fn transpile(prg: &SPrg) -> String {
+    let prg = evaluator::erase_contracts(prg);
+    let prg = prg
+        .iter()
+        .map(|d| Spanned::new(def(&d.node), d.span))
+        .collect::<Vec<_>>();
+
+    let src = pretty::prg(&prg);
+    let mut includes = String::new();
+    if src.contains("calloc(") {
+        includes.push_str("#include <stdlib.h>\n");
+    }
+    if src.contains("assert(") {
+        includes.push_str("#include <assert.h>\n");
+    }
+    match includes.is_empty() {
+        true => src,
+        false => format!("{includes}\n{src}"),
+    }
+}
+
+fn def(d: &SDef) -> SDef {
+    match d {
+        SDef::FuncDef(fd) => {
+            let mut n = 0;
+            SDef::FuncDef(SFuncDef {
+                body: stmts(&fd.body, &mut n),
+                ..fd.clone()
+            })
+        }
+        SDef::VarDef(vd) => SDef::VarDef(vardef(vd)),
+        _ => d.clone(),
+    }
+}
+
+fn vardef(vd: &SVarDef) -> SVarDef {
+    SVarDef {
+        expr: Box::new(expr(&vd.expr)),
+        ..vd.clone()
+    }
+}
+
+// n numbers the function's for loops, for their continue labels
+fn stmts(ss: &[Spanned<SStmt>], n: &mut usize) -> Vec<Spanned<SStmt>> {
+    let mut out = vec![];
+    for (i, s) in ss.iter().enumerate() {
+        let decl = matches!(s.node, SStmt::Asnmt(_) | SStmt::Decl(_));
+        let stmts_before = out
+            .iter()
+            .any(|s: &Spanned<SStmt>| !matches!(s.node, SStmt::Asnmt(_) | SStmt::Decl(_)));
+        if decl && stmts_before {
+            let rest = stmts(&ss[i..], n);
+            out.push(Spanned::new(SStmt::Block(rest), s.span));
+            break;
+        }
+        out.push(stmt(s, n));
+    }
+    out
+}
+
+fn stmt(s: &Spanned<SStmt>, n: &mut usize) -> Spanned<SStmt> {
+    let e = |e: &Spanned<SExpr>| Box::new(expr(e));
+    let node = match &s.node {
+        SStmt::Asnmt(vd) => SStmt::Asnmt(vardef(vd)),
+        SStmt::Update(vu) => {
+            let mut vu = vu.clone();
+            vu.expr = e(&vu.expr);
+            SStmt::Update(vu)
+        }
+        SStmt::Store(st) => {
+            let mut st = st.clone();
+            (st.lval, st.expr) = (e(&st.lval), e(&st.expr));
+            SStmt::Store(st)
+        }
+        SStmt::Return(r) => SStmt::Return(r.as_ref().map(expr)),
+        SStmt::Call(c) => SStmt::Call(expr(c)),
+        SStmt::Assert(a) => SStmt::Assert(expr(a)),
+        SStmt::IfEls { cond, then, els } => SStmt::IfEls {
+            cond: e(cond),
+            then: Box::new(stmt(then, n)),
+            els: els.as_ref().map(|els| Box::new(stmt(els, n))),
+        },
+        SStmt::Switch { cond, arms } => SStmt::Switch {
+            cond: e(cond),
+            arms: arms
+                .iter()
+                .map(|a| SCase {
+                    label: a.label,
+                    body: stmts(&a.body, n),
+                })
+                .collect(),
+        },
+        SStmt::While { cond, body, .. } => SStmt::While {
+            cond: e(cond),
+            body: stmts(body, n),
+            invariants: vec![],
+        },
+        SStmt::DoWhile { body, cond } => SStmt::DoWhile {
+            body: stmts(body, n),
+            cond: e(cond),
+        },
+        // { asnmt; while (cond) { body; continue_<n>: update; } }
+        SStmt::For {
+            asnmt,
+            cond,
+            update,
+            body,
+            ..
+        } => {
+            let label = format!("continue_{n}");
+            *n += 1;
+            let mut continued = false;
+            let body = body
+                .iter()
+                .map(|s| continues(s, &label, &mut continued))
+                .collect::<Vec<_>>();
+            let mut body = stmts(&body, n);
+            if continued {
+                body.push(Spanned::new(SStmt::Label(label), update.span));
+            }
+            body.push(stmt(update, n));
+            let looped = SStmt::While {
+                cond: e(cond),
+                body,
+                invariants: vec![],
+            };
+            let looped = iter::once(Spanned::new(looped, s.span));
+            SStmt::Block(iter::once(stmt(asnmt, n)).chain(looped).collect())
+        }
+        SStmt::Block(body) => SStmt::Block(stmts(body, n)),
+        SStmt::Decl(_)
+        | SStmt::Break
+        | SStmt::Continue
+        | SStmt::Label(_)
+        | SStmt::Goto(_)
+        | SStmt::Contract(_) => s.node.clone(),
+    };
+    Spanned::new(node, s.span)
+}
+
+// s with the continues of the loop it's in jumping to label. a nested
+// loop's are its own
+fn continues(s: &Spanned<SStmt>, label: &str, continued: &mut bool) -> Spanned<SStmt> {
+    let mut body = |ss: &[Spanned<SStmt>]| -> Vec<Spanned<SStmt>> {
+        ss.iter().map(|s| continues(s, label, continued)).collect()
+    };
+    let node = match &s.node {
+        SStmt::Continue => {
+            *continued = true;
+            SStmt::Goto(label.to_owned())
+        }
+        SStmt::IfEls { cond, then, els } => {
+            let then = Box::new(continues(then, label, continued));
+            let els = els
+                .as_ref()
+                .map(|els| Box::new(continues(els, label, continued)));
+            SStmt::IfEls {
+                cond: cond.clone(),
+                then,
+                els,
+            }
+        }
+        SStmt::Switch { cond, arms } => SStmt::Switch {
+            cond: cond.clone(),
+            arms: arms
+                .iter()
+                .map(|a| SCase {
+                    label: a.label,
+                    body: body(&a.body),
+                })
+                .collect(),
+        },
+        SStmt::Block(ss) => SStmt::Block(body(ss)),
+        node => node.clone(),
+    };
+    Spanned::new(node, s.span)
+}
+
+fn expr(e: &Spanned<SExpr>) -> Spanned<SExpr> {
+    let r = |e: &Spanned<SExpr>| Box::new(expr(e));
+    let calloc = |n: Spanned<SExpr>, t: &Type| SExpr::FuncApp {
+        alias: "calloc".to_owned(),
+        aps: vec![n, Spanned::new(SExpr::SizeOfType(t.clone()), e.span)],
+    };
+    let node = match &e.node {
+        SExpr::Bool(b) => SExpr::Int(i32::from(*b)),
+        SExpr::Int(_)
+        | SExpr::TypedInt { .. }
+        | SExpr::Float { .. }
+        | SExpr::Str(_)
+        | SExpr::SizeOfType(_)
+        | SExpr::VarApp(_) => e.node.clone(),
+        SExpr::IncDec { op, lval } => SExpr::IncDec {
+            op: op.clone(),
+            lval: r(lval),
+        },
+        SExpr::Index { alias, idx } => SExpr::Index {
+            alias: alias.clone(),
+            idx: r(idx),
+        },
+        SExpr::FuncApp { alias, aps } => SExpr::FuncApp {
+            alias: alias.clone(),
+            aps: aps.iter().map(expr).collect(),
+        },
+        SExpr::UnaryE { op, l } => SExpr::UnaryE {
+            op: op.clone(),
+            l: r(l),
+        },
+        SExpr::BinE { op, l, r: rt } => SExpr::BinE {
+            op: op.clone(),
+            l: r(l),
+            r: r(rt),
+        },
+        SExpr::LogE { op, l, r: rt } => SExpr::LogE {
+            op: op.clone(),
+            l: r(l),
+            r: r(rt),
+        },
+        SExpr::BitE { op, l, r: rt } => SExpr::BitE {
+            op: op.clone(),
+            l: r(l),
+            r: r(rt),
+        },
+        SExpr::RelE { op, l, r: rt } => SExpr::RelE {
+            op: op.clone(),
+            l: r(l),
+            r: r(rt),
+        },
+        SExpr::CondE { cond, then, els } => SExpr::CondE {
+            cond: r(cond),
+            then: r(then),
+            els: r(els),
+        },
+        SExpr::AddrOf(l) => SExpr::AddrOf(r(l)),
+        SExpr::Deref(l) => SExpr::Deref(r(l)),
+        SExpr::SizeOf(l) => SExpr::SizeOf(r(l)),
+        SExpr::Alloc(t) => calloc(Spanned::new(SExpr::Int(1), e.span), t),
+        SExpr::AllocArray(t, len) => calloc(expr(len), t),
+        SExpr::Cast(t, l) => SExpr::Cast(t.clone(), r(l)),
+        SExpr::Member { base, field } => SExpr::Member {
+            base: r(base),
+            field: field.clone(),
+        },
+    };
+    Spanned::new(node, e.span)
+}
+
+#[cfg(test)]
+mod test_transpiler {
+    use crate::{lexer, parser_ast};
+
+    fn transpile(src: &str) -> String {
+        let tokens = lexer::lex(src).unwrap();
+        super::transpile(&parser_ast::parse_prg(&tokens).unwrap())
+    }
+
+    #[test]
+    fn lowered() {
+        let src = "struct p { int on; int *xs; };
+int f(int n) //@requires n >= 0;
+{
+  struct p *p = alloc(struct p);
+  int *xs = alloc_array(int, n);
+  p->xs = xs;
+  (*p).on = p->on + 1;
+  for (int i = 0; i < n; i++) {
+    if (i % 2 == 0) { continue; }
+    for (int j = 0; j < i; j++) { if (j == 1) { continue; } }
+    int odd = true;
+    assert(odd);
+  }
+  return p->on;
+}";
+        insta::assert_snapshot!(transpile(src), @r###"
+        #include <stdlib.h>
+        #include <assert.h>
+
+        struct p {
+            int on;
+            int *xs;
+        };
+
+        int f(int n) {
+            struct p *p = calloc(1, sizeof(struct p));
+            int *xs = calloc(n, sizeof(int));
+            p->xs = xs;
+            p->on = p->on + 1;
+            {
+                int i = 0;
+                while (i < n) {
+                    if (i % 2 == 0) {
+                        goto continue_0;
+                    }
+                    {
+                        int j = 0;
+                        while (j < i) {
+                            if (j == 1) {
+                                goto continue_1;
+                            }
+                            continue_1:
+                            j = j + 1;
+                        }
+                    }
+                    {
+                        int odd = 1;
+                        assert(odd);
+                    }
+                    continue_0:
+                    i = i + 1;
+                }
+            }
+            return p->on;
+        }
+        "###);
+    }
+}
@@ -0,0 +1,1138 @@
+use crate::diagnostics::{Diagnostic, Warning, Warnings};
+use crate::source::{self, Span};
+use crate::{
+    translator, typer, LambdaType, SContractKind, SDef, SExpr, SFuncDef, SPrg, SStmt, Spanned,
+    Type, RESULT,
+};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::{io, mem, slice};
+
+// semantic analysis, between the parser and the backends. names are resolved
+// against a scoped symbol table first, as c scopes them, so a use of an
+// undeclared variable or function, a use before a declaration, and a name
+// declared twice in one scope each get a diagnostic of their own, then every
+// expression is typed by the typer. the table is what the check leaves behind: every function's
+// signature, whether declared by a prototype or its definition, and each
+// defined function's params and locals, with their types and how deeply
+// they're nested
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct SymbolTable {
+    pub sigs: Vec<(String, LambdaType)>, // in the order they're first declared
+    pub funcs: Vec<(String, Vec<Symbol>)>, // in program order
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Symbol {
+    pub name: String,
+    pub typ: Type,
+    pub depth: usize, // 0 for params, 1 for the body's locals, and so on
+}
+
+// an assignment to a const variable
+#[derive(Debug, thiserror::Error)]
+#[error("assignment of read-only variable {alias}")]
+pub struct ConstError {
+    pub alias: String,
+}
+
+pub fn check(prg: &SPrg) -> Result<SymbolTable, io::Error> {
+    let table = resolve(prg)?;
+    typer::type_prg(prg)?;
+    Ok(table)
+}
+
+// as check, for a unit linked with others, which needn't have main
+pub fn check_unit(prg: &SPrg) -> Result<SymbolTable, io::Error> {
+    let table = resolve(prg)?;
+    typer::type_unit(prg)?;
+    Ok(table)
+}
+
+// c0 rejects what c only warns about: a non-void function that can run off
+// its end, which interpretc0 takes for an error. main is still taken to
+// return 0, as the warning has it
+pub fn check_returns(prg: &SPrg) -> Result<(), io::Error> {
+    for def in prg {
+        match &def.node {
+            SDef::FuncDef(fd)
+                if fd.typ != Type::Void && fd.alias != "main" && !returns(&fd.body) =>
+            {
+                let msg = format!("control reaches the end of non-void function {}", fd.alias);
+                return Err(source::locate(io::Error::other(msg), def.span));
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+// what's suspect in a program that checks: locals that are never used,
+// statements after a jump that no label leads back into, functions with no
+// return type, and non-void ones that can run off their end. main is
+// taken to return 0 off its end, as c99 has it
+pub fn warnings(prg: &SPrg, cfg: &Warnings) -> Vec<Diagnostic> {
+    let Ok((globals, arities)) = context(prg) else {
+        return vec![];
+    };
+    let mut diags = vec![];
+    for (i, def) in prg.iter().enumerate() {
+        let SDef::FuncDef(fd) = &def.node else {
+            continue;
+        };
+        let mut warn = |w, msg, span| {
+            if cfg.enabled(w) {
+                diags.push(cfg.diagnostic(w, msg, span))
+            }
+        };
+        if fd.implicit_int {
+            let msg = format!("return type of {} defaults to int", fd.alias);
+            warn(Warning::ImplicitInt, msg, def.span);
+        }
+        if let Ok((_, unused)) = resolve_func(fd, &globals, i, &arities) {
+            for (alias, span) in unused {
+                warn(
+                    Warning::UnusedVariable,
+                    format!("unused variable {alias}"),
+                    span,
+                );
+            }
+        }
+        let mut spans = vec![];
+        unreachable(&fd.body, &mut spans);
+        for span in spans {
+            warn(
+                Warning::UnreachableCode,
+                "unreachable code".to_owned(),
+                span,
+            );
+        }
+        for (alias, span) in uninitialized(fd) {
+            let alias = alias.split('.').next().unwrap_or(&alias);
+            let msg = format!("{alias} may be used uninitialized in {}", fd.alias);
+            warn(Warning::MaybeUninitialized, msg, span);
+        }
+        if fd.typ != Type::Void && fd.alias != "main" && !returns(&fd.body) {
+            let msg = format!("control reaches the end of non-void function {}", fd.alias);
+            warn(Warning::ReturnType, msg, def.span);
+        }
+    }
+    diags
+}
+
+// the first statement of each list that follows a return, break, continue
+// or goto, unless it's a label, which a goto could still reach
+fn unreachable(stmts: &[Spanned<SStmt>], spans: &mut Vec<Span>) {
+    let jump = stmts.iter().position(|s| {
+        matches!(
+            s.node,
+            SStmt::Return(_) | SStmt::Break | SStmt::Continue | SStmt::Goto(_)
+        )
+    });
+    if let Some(after) = jump.and_then(|i| stmts.get(i + 1)) {
+        if !matches!(after.node, SStmt::Label(_)) {
+            spans.push(after.span);
+        }
+    }
+    for s in stmts {
+        match &s.node {
+            SStmt::IfEls { then, els, .. } => {
+                unreachable(slice::from_ref(then), spans);
+                if let Some(els) = els {
+                    unreachable(slice::from_ref(els), spans);
+                }
+            }
+            SStmt::Switch { arms, .. } => arms.iter().for_each(|a| unreachable(&a.body, spans)),
+            SStmt::Block(body)
+            | SStmt::While { body, .. }
+            | SStmt::DoWhile { body, .. }
+            | SStmt::For { body, .. } => unreachable(body, spans),
+            _ => (),
+        }
+    }
+}
+
+// the uses of locals declared without an initializer that some path
+// reaches before any assignment to them, the first use of each. locals
+// are renamed apart first, so a shadowing one is a local of its own, and
+// one whose address is taken is taken to be assigned through it. it's one
+// pass: a loop's body is walked once, as it runs the first time, and what
+// a loop or a switch that may not run assigns is dropped after it
+fn uninitialized(fd: &SFuncDef) -> Vec<(String, Span)> {
+    let body = translator::rename_stmts(&fd.body, &HashMap::new(), false, &mut 0);
+    let mut flow = Flow::default();
+    flow.assigned
+        .extend(fd.fps.iter().map(|(alias, _)| alias.clone()));
+    flow.stmts(&body);
+    flow.uses
+}
+
+#[derive(Clone, Default)]
+struct Flow {
+    declared: HashSet<String>, // the locals without initializers
+    assigned: HashSet<String>, // those assigned on every path so far
+    dead: bool,                // after a jump, until a label
+    uses: Vec<(String, Span)>,
+}
+
+impl Flow {
+    // where two paths meet, what both assigned is. a dead path never gets there
+    fn join(&mut self, other: Flow) {
+        match (self.dead, other.dead) {
+            (true, _) => self.assigned = other.assigned,
+            (_, true) => (),
+            _ => self.assigned.retain(|a| other.assigned.contains(a)),
+        }
+        self.dead &= other.dead;
+        self.uses = other.uses;
+    }
+
+    // a path that may not be taken, whose assignments are dropped after it
+    fn maybe(&mut self, f: impl FnOnce(&mut Flow)) {
+        let mut path = self.clone();
+        f(&mut path);
+        self.uses = path.uses;
+    }
+
+    fn stmts(&mut self, stmts: &[Spanned<SStmt>]) {
+        stmts.iter().for_each(|s| self.stmt(s));
+    }
+
+    fn stmt(&mut self, s: &Spanned<SStmt>) {
+        match &s.node {
+            SStmt::Decl(vd) => {
+                let scalar = !matches!(vd.typ, Type::Arr(..) | Type::Struct(_) | Type::Union(_));
+                if scalar && !vd.is_static {
+                    self.declared.insert(vd.alias.clone());
+                    self.assigned.remove(&vd.alias);
+                }
+            }
+            SStmt::Asnmt(vd) => {
+                self.expr(&vd.expr);
+                self.assigned.insert(vd.alias.clone());
+            }
+            SStmt::Update(vu) => {
+                self.expr(&vu.expr);
+                self.assigned.insert(vu.alias.clone());
+            }
+            SStmt::Store(st) => {
+                self.expr(&st.expr);
+                match st.op {
+                    Some(_) => self.expr(&st.lval),
+                    None => self.lval(&st.lval),
+                }
+            }
+            SStmt::Return(e) => {
+                e.iter().for_each(|e| self.expr(e));
+                self.dead = true;
+            }
+            SStmt::Break | SStmt::Continue | SStmt::Goto(_) => self.dead = true,
+            SStmt::Label(_) => self.dead = false,
+            SStmt::Call(e) | SStmt::Assert(e) => self.expr(e),
+            SStmt::Contract(_) => (),
+            SStmt::Block(body) => self.stmts(body),
+            SStmt::IfEls { cond, then, els } => {
+                self.expr(cond);
+                let mut other = self.clone();
+                self.stmt(then);
+                other.uses = mem::take(&mut self.uses);
+                if let Some(els) = els {
+                    other.stmt(els);
+                }
+                self.join(other);
+            }
+            SStmt::While { cond, body, .. } => {
+                self.expr(cond);
+                self.maybe(|f| f.stmts(body));
+            }
+            SStmt::For {
+                asnmt,
+                cond,
+                update,
+                body,
+                ..
+            } => {
+                self.stmt(asnmt);
+                self.expr(cond);
+                self.maybe(|f| {
+                    f.stmts(body);
+                    f.stmt(update);
+                });
+            }
+            SStmt::DoWhile { body, cond } => match leaves(body, true, true) {
+                true => self.maybe(|f| {
+                    f.stmts(body);
+                    f.expr(cond);
+                }),
+                false => {
+                    self.stmts(body);
+                    self.dead = false;
+                    self.expr(cond);
+                }
+            },
+            SStmt::Switch { cond, arms } => {
+                self.expr(cond);
+                let exhaustive = arms.iter().any(|a| a.label.is_none())
+                    && !arms.iter().any(|a| leaves(&a.body, true, false));
+                for (i, a) in arms.iter().enumerate() {
+                    let last = i + 1 == arms.len();
+                    match exhaustive && last {
+                        true => self.stmts(&a.body),
+                        false => self.maybe(|f| f.stmts(&a.body)),
+                    }
+                }
+            }
+        }
+    }
+
+    // an assignment's target: x is assigned, but *e, a[e] and e.f only read
+    // what they need to find where to store
+    fn lval(&mut self, e: &Spanned<SExpr>) {
+        match &e.node {
+            SExpr::VarApp(alias) => _ = self.assigned.insert(alias.clone()),
+            SExpr::Index { idx, .. } => self.expr(idx),
+            SExpr::Deref(l) => self.expr(l),
+            SExpr::Member { base, .. } => self.lval(base),
+            _ => self.expr(e),
+        }
+    }
+
+    fn read(&mut self, alias: &str, span: Span) {
+        let unset = self.declared.contains(alias) && !self.assigned.contains(alias);
+        if unset && !self.dead && !self.uses.iter().any(|(a, _)| a == alias) {
+            self.uses.push((alias.to_owned(), span));
+        }
+    }
+
+    fn expr(&mut self, e: &Spanned<SExpr>) {
+        match &e.node {
+            SExpr::VarApp(alias) => self.read(alias, e.span),
+            SExpr::IncDec { lval, .. } => self.expr(lval),
+            SExpr::Index { alias, idx } => {
+                self.read(alias, e.span);
+                self.expr(idx);
+            }
+            SExpr::AddrOf(l) => match &l.node {
+                SExpr::VarApp(alias) => _ = self.assigned.insert(alias.clone()),
+                _ => self.lval(l),
+            },
+            SExpr::UnaryE { l, .. } | SExpr::Deref(l) => self.expr(l),
+            SExpr::Member { base, .. } => self.expr(base),
+            SExpr::BinE { l, r, .. } | SExpr::BitE { l, r, .. } | SExpr::RelE { l, r, .. } => {
+                self.expr(l);
+                self.expr(r);
+            }
+            SExpr::LogE { l, r, .. } => {
+                self.expr(l);
+                self.maybe(|f| f.expr(r));
+            }
+            SExpr::CondE { cond, then, els } => {
+                self.expr(cond);
+                self.maybe(|f| f.expr(then));
+                self.maybe(|f| f.expr(els));
+            }
+            SExpr::FuncApp { aps, .. } => aps.iter().for_each(|ap| self.expr(ap)),
+            SExpr::AllocArray(_, n) | SExpr::Cast(_, n) => self.expr(n),
+            SExpr::Int(_)
+            | SExpr::TypedInt { .. }
+            | SExpr::Float { .. }
+            | SExpr::Bool(_)
+            | SExpr::Str(_)
+            | SExpr::SizeOf(_) // never evaluated
+            | SExpr::SizeOfType(_)
+            | SExpr::Alloc(_) => (),
+        }
+    }
+}
+
+// whether no path through stmts runs off their end. a loop whose cond is
+// always true only leaves by a break, and a do-while runs its body once, so
+// either can return on every path. a switch with a default does when no arm
+// breaks out and its last arm returns, the others falling through into it.
+// a label undoes a return before it, since a goto could land after it
+fn returns(stmts: &[Spanned<SStmt>]) -> bool {
+    stmts.iter().fold(false, |returned, s| match &s.node {
+        SStmt::Label(_) => false,
+        _ => returned || stmt_returns(s),
+    })
+}
+
+fn stmt_returns(s: &Spanned<SStmt>) -> bool {
+    match &s.node {
+        SStmt::Return(_) => true,
+        SStmt::Block(body) => returns(body),
+        SStmt::IfEls {
+            then,
+            els: Some(els),
+            ..
+        } => returns(slice::from_ref(then)) && returns(slice::from_ref(els)),
+        SStmt::While { cond, body, .. } | SStmt::For { cond, body, .. } => {
+            always(&cond.node) && !leaves(body, true, false)
+        }
+        SStmt::DoWhile { body, .. } => returns(body) && !leaves(body, true, true),
+        SStmt::Switch { arms, .. } => {
+            arms.iter().any(|a| a.label.is_none())
+                && arms.last().is_some_and(|a| returns(&a.body))
+                && !arms.iter().any(|a| leaves(&a.body, true, false))
+        }
+        _ => false,
+    }
+}
+
+// a cond that can't be false, as in while (1) or while (true)
+fn always(cond: &SExpr) -> bool {
+    matches!(cond, SExpr::Bool(true)) || matches!(cond, SExpr::Int(n) if *n != 0)
+}
+
+// whether a break (when breaks) or continue (when continues) in stmts jumps
+// out of the loop or switch they're the body of. nested loops have their
+// own, and a nested switch its own breaks
+fn leaves(stmts: &[Spanned<SStmt>], breaks: bool, continues: bool) -> bool {
+    stmts.iter().any(|s| match &s.node {
+        SStmt::Break => breaks,
+        SStmt::Continue => continues,
+        SStmt::Block(body) => leaves(body, breaks, continues),
+        SStmt::IfEls { then, els, .. } => {
+            leaves(slice::from_ref(then), breaks, continues)
+                || els
+                    .as_ref()
+                    .is_some_and(|els| leaves(slice::from_ref(els), breaks, continues))
+        }
+        SStmt::Switch { arms, .. } => arms.iter().any(|a| leaves(&a.body, false, continues)),
+        _ => false,
+    })
+}
+
+// just the names. the evaluator runs this much on programs it isn't given
+// typed, since c0 is looser than the typer
+pub fn resolve(prg: &SPrg) -> Result<SymbolTable, io::Error> {
+    let (globals, arities) = context(prg)?;
+    let sigs = typer::signatures(prg)?;
+
+    let funcs = prg
+        .iter()
+        .enumerate()
+        .filter_map(|(i, def)| match &def.node {
+            SDef::FuncDef(fd) => Some(
+                resolve_func(fd, &globals, i, &arities).map(|(syms, _)| (fd.alias.clone(), syms)),
+            ),
+            _ => None,
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(SymbolTable { sigs, funcs })
+}
+
+type Arities = HashMap<String, (usize, bool)>;
+
+// what every function's body sees: the file scope variables, each with the
+// index of the def that declares it, and how many args each function takes.
+// a variable may be declared any number of times with one type, as c89's
+// tentative definitions are, but initialized once, and no function can
+// share its name
+fn context(prg: &SPrg) -> Result<(Vec<Global<'_>>, Arities), io::Error> {
+    let mut seen: HashMap<&str, (Option<&Type>, bool)> = HashMap::new(); // a variable's type, and whether it's initialized
+    let mut globals = vec![];
+    for (i, def) in prg.iter().enumerate() {
+        let (alias, typ, init) = match &def.node {
+            SDef::VarDef(vd) => {
+                constant_init(&vd.alias, &vd.expr).map_err(|e| source::locate(e, def.span))?;
+                globals.push(((vd.alias.as_str(), vd.constant.then_some(&vd.typ)), i));
+                (vd.alias.as_str(), Some(&vd.typ), true)
+            }
+            SDef::VarDecl(vd) => {
+                globals.push(((vd.alias.as_str(), vd.constant.then_some(&vd.typ)), i));
+                (vd.alias.as_str(), Some(&vd.typ), false)
+            }
+            SDef::FuncDef(fd) => (fd.alias.as_str(), None, false),
+            SDef::FuncDecl(fd) => (fd.alias.as_str(), None, false),
+            _ => continue,
+        };
+        let err = match (seen.get(alias), typ) {
+            (Some((Some(_), _)), None) | (Some((None, _)), Some(_)) => Some(format!(
+                "redefinition of {alias} as a different kind of symbol"
+            )),
+            (Some((Some(prev), _)), Some(typ)) if *prev != typ => {
+                Some(format!("conflicting types for {alias}"))
+            }
+            (Some((Some(_), true)), Some(_)) if init => Some(format!("redefinition of {alias}")),
+            _ => None,
+        };
+        if let Some(err) = err {
+            return Err(source::locate(io::Error::other(err), def.span));
+        }
+        let initialized = seen.get(alias).is_some_and(|(_, init)| *init);
+        seen.insert(alias, (typ, init || initialized));
+    }
+
+    let arities = typer::signatures(prg)?
+        .into_iter()
+        .map(|(alias, sig)| (alias, (sig.fp.len(), sig.variadic)))
+        .collect::<HashMap<_, _>>();
+
+    Ok((globals, arities))
+}
+
+type Binding<'a> = (&'a str, Option<&'a Type>);
+type Global<'a> = (Binding<'a>, usize);
+type Local<'a> = (&'a str, Span);
+
+struct Scopes<'a> {
+    func: &'a str,
+    globals: &'a [Global<'a>], // file scope, outside every function's scopes
+    visible: usize,            // how many globals are declared before the function
+    arities: &'a Arities,
+    // innermost last. const ones keep their type, and each its index in locals
+    scopes: Vec<Vec<(Binding<'a>, usize)>>,
+    pending: Vec<&'a [Spanned<SStmt>]>, // what's left of each open block, whose declarations aren't in scope yet
+    syms: Vec<Symbol>,
+    locals: Vec<Local<'a>>, // in the order they're declared. params have no span
+    used: RefCell<HashSet<usize>>,
+}
+
+// the function's symbols, and the locals it never uses. def is the index
+// of the function's def, which only sees the globals declared before it
+fn resolve_func<'a>(
+    fd: &'a SFuncDef,
+    globals: &'a [Global<'a>],
+    def: usize,
+    arities: &'a Arities,
+) -> Result<(Vec<Symbol>, Vec<Local<'a>>), io::Error> {
+    let mut s = Scopes {
+        func: &fd.alias,
+        globals,
+        visible: globals.partition_point(|(_, d)| *d < def),
+        arities,
+        scopes: vec![vec![]],
+        pending: vec![],
+        syms: vec![],
+        locals: vec![],
+        used: RefCell::default(),
+    };
+    for (alias, typ) in &fd.fps {
+        s.declare(alias, typ, false, Span::default())?;
+    }
+    // contracts see the params, and ensures what's returned too
+    for c in &fd.contracts {
+        s.scoped(|s| {
+            if c.kind == SContractKind::Ensures && fd.typ != Type::Void {
+                s.declare(RESULT, &fd.typ, true, Span::default())?;
+            }
+            s.expr(&c.expr)
+        })?;
+    }
+    s.block(&fd.body)?;
+
+    let used = s.used.take();
+    let unused = s
+        .locals
+        .iter()
+        .enumerate()
+        .filter(|(i, (_, span))| *span != Span::default() && !used.contains(i));
+    Ok((s.syms, unused.map(|(_, l)| *l).collect()))
+}
+
+impl<'a> Scopes<'a> {
+    // a name can only be declared once in a scope. the params and the body's
+    // outermost block are one scope, as c has them
+    fn declare(
+        &mut self,
+        alias: &'a str,
+        typ: &'a Type,
+        constant: bool,
+        span: Span,
+    ) -> Result<(), io::Error> {
+        let outermost = match self.scopes.len() {
+            2 => &self.scopes[..],
+            n => &self.scopes[n - 1..],
+        };
+        if outermost.iter().flatten().any(|((a, _), _)| *a == alias) {
+            return Err(io::Error::other(format!(
+                "redefinition of {alias} in {}",
+                self.func
+            )));
+        }
+        self.syms.push(Symbol {
+            name: alias.to_owned(),
+            typ: typ.clone(),
+            depth: self.scopes.len() - 1,
+        });
+        self.scopes
+            .last_mut()
+            .expect("a scope is open")
+            .push(((alias, constant.then_some(typ)), self.locals.len()));
+        self.locals.push((alias, span));
+        Ok(())
+    }
+
+    // the innermost alias in scope, and its type if it's const
+    fn lookup(&self, alias: &str) -> Result<Option<&'a Type>, io::Error> {
+        let local = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.iter().rev().find(|((a, _), _)| *a == alias));
+        if let Some(((_, constant), i)) = local {
+            self.used.borrow_mut().insert(*i);
+            return Ok(*constant);
+        }
+        let global = self.globals[..self.visible]
+            .iter()
+            .rev()
+            .find(|((a, _), _)| *a == alias);
+        if let Some(((_, constant), _)) = global {
+            return Ok(*constant);
+        }
+
+        // declared further on in a block that's open, or at file scope
+        // after the function
+        let later = self.pending.iter().any(|rest| {
+            rest.iter().any(|s| match &s.node {
+                SStmt::Asnmt(vd) => vd.alias == alias,
+                SStmt::Decl(vd) => vd.alias == alias,
+                _ => false,
+            })
+        }) || self.globals[self.visible..]
+            .iter()
+            .any(|((a, _), _)| *a == alias);
+        match later {
+            true => Err(io::Error::other(format!(
+                "use of {alias} before its declaration in {}",
+                self.func
+            ))),
+            false => Err(io::Error::other(format!(
+                "use of undeclared variable {alias} in {}",
+                self.func
+            ))),
+        }
+    }
+
+    // an assignment to alias, or to an element of it if indexed. indexing a
+    // const pointer assigns to what it points to, which is fine
+    fn assign(&self, alias: &str, indexed: bool) -> Result<(), io::Error> {
+        match self.lookup(alias)? {
+            Some(Type::Ptr(_)) if indexed => Ok(()),
+            Some(_) => Err(io::Error::other(ConstError {
+                alias: alias.to_owned(),
+            })),
+            None => Ok(()),
+        }
+    }
+
+    // runs f in a new scope, which is closed whether or not f succeeds
+    fn scoped<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.scopes.push(vec![]);
+        let t = f(self);
+        self.scopes.pop();
+        t
+    }
+
+    fn block(&mut self, stmts: &'a [Spanned<SStmt>]) -> Result<(), io::Error> {
+        self.scoped(|s| s.stmts(stmts))
+    }
+
+    // stmts in the scope that's open, each seeing what's declared before it
+    fn stmts(&mut self, stmts: &'a [Spanned<SStmt>]) -> Result<(), io::Error> {
+        self.pending.push(stmts);
+        let resolved = stmts.iter().enumerate().try_for_each(|(i, stmt)| {
+            *self.pending.last_mut().expect("pushed above") = &stmts[i + 1..];
+            self.stmt(stmt)
+        });
+        self.pending.pop();
+        resolved
+    }
+
+    // errors are located at the innermost node that has them
+    fn stmt(&mut self, stmt: &'a Spanned<SStmt>) -> Result<(), io::Error> {
+        self.stmt_node(&stmt.node, stmt.span)
+            .map_err(|e| source::locate(e, stmt.span))
+    }
+
+    fn stmt_node(&mut self, stmt: &'a SStmt, span: Span) -> Result<(), io::Error> {
+        match stmt {
+            SStmt::Asnmt(vd) if vd.is_static => {
+                constant_init(&vd.alias, &vd.expr)?;
+                self.declare(&vd.alias, &vd.typ, vd.constant, span)
+            }
+            SStmt::Asnmt(vd) => {
+                self.expr(&vd.expr)?; // the initializer can't see the new binding
+                self.declare(&vd.alias, &vd.typ, vd.constant, span)
+            }
+            SStmt::Decl(vd) => self.declare(&vd.alias, &vd.typ, vd.constant, span),
+            SStmt::Update(vu) => {
+                self.assign(&vu.alias, false)?;
+                self.expr(&vu.expr)
+            }
+            SStmt::Store(st) => {
+                self.expr(&st.lval)?;
+                if let Some((alias, indexed)) = root(&st.lval) {
+                    self.assign(alias, indexed)?;
+                }
+                self.expr(&st.expr)
+            }
+            SStmt::Return(e) => e.as_ref().map_or(Ok(()), |e| self.expr(e)),
+            SStmt::Call(e) | SStmt::Assert(e) => self.expr(e),
+            SStmt::Contract(c) => self.expr(&c.expr),
+            SStmt::IfEls { cond, then, els } => {
+                self.expr(cond)?;
+                self.block(slice::from_ref(then))?;
+                els.as_ref()
+                    .map_or(Ok(()), |els| self.block(slice::from_ref(els)))
+            }
+            SStmt::Switch { cond, arms } => {
+                self.expr(cond)?;
+                self.scoped(|s| arms.iter().try_for_each(|a| s.stmts(&a.body)))
+            }
+            SStmt::While {
+                cond,
+                body,
+                invariants,
+            } => {
+                invariants.iter().try_for_each(|c| self.expr(&c.expr))?;
+                self.expr(cond)?;
+                self.block(body)
+            }
+            SStmt::DoWhile { body, cond } => {
+                self.block(body)?;
+                self.expr(cond)
+            }
+            SStmt::For {
+                asnmt,
+                cond,
+                update,
+                body,
+                invariants,
+            } => self.scoped(|s| {
+                s.stmt(asnmt)?;
+                invariants.iter().try_for_each(|c| s.expr(&c.expr))?;
+                s.expr(cond)?;
+                s.stmt(update)?;
+                s.block(body)
+            }),
+            SStmt::Block(body) => self.block(body),
+            SStmt::Break | SStmt::Continue | SStmt::Label(_) | SStmt::Goto(_) => Ok(()),
+        }
+    }
+
+    fn expr(&self, e: &Spanned<SExpr>) -> Result<(), io::Error> {
+        self.expr_node(e).map_err(|err| source::locate(err, e.span))
+    }
+
+    fn expr_node(&self, e: &SExpr) -> Result<(), io::Error> {
+        match e {
+            SExpr::Int(_)
+            | SExpr::TypedInt { .. }
+            | SExpr::Float { .. }
+            | SExpr::Bool(_)
+            | SExpr::Str(_) => Ok(()),
+            SExpr::VarApp(alias) => self.lookup(alias).map(|_| ()),
+            SExpr::IncDec { lval, .. } => {
+                self.expr(lval)?;
+                root(&lval.node).map_or(Ok(()), |(alias, indexed)| self.assign(alias, indexed))
+            }
+            SExpr::Index { alias, idx } => {
+                self.lookup(alias)?;
+                self.expr(idx)
+            }
+            SExpr::FuncApp { alias, aps } => {
+                match self.arities.get(alias.as_str()) {
+                    None => Err(io::Error::other(format!(
+                        "call to undeclared function {alias} in {}",
+                        self.func
+                    ))),
+                    Some((n, false)) if *n != aps.len() => Err(io::Error::other(format!(
+                        "{alias} takes {n} arguments but {} were given",
+                        aps.len()
+                    ))),
+                    Some((n, true)) if *n > aps.len() => Err(io::Error::other(format!(
+                        "{alias} takes at least {n} arguments but {} were given",
+                        aps.len()
+                    ))),
+                    Some(_) => Ok(()),
+                }?;
+                aps.iter().try_for_each(|ap| self.expr(ap))
+            }
+            SExpr::SizeOfType(_) | SExpr::Alloc(_) => Ok(()),
+            SExpr::UnaryE { l, .. }
+            | SExpr::SizeOf(l)
+            | SExpr::AllocArray(_, l)
+            | SExpr::Cast(_, l)
+            | SExpr::AddrOf(l)
+            | SExpr::Deref(l)
+            | SExpr::Member { base: l, .. } => self.expr(l),
+            SExpr::BinE { l, r, .. }
+            | SExpr::LogE { l, r, .. }
+            | SExpr::BitE { l, r, .. }
+            | SExpr::RelE { l, r, .. } => {
+                self.expr(l)?;
+                self.expr(r)
+            }
+            SExpr::CondE { cond, then, els } => {
+                self.expr(cond)?;
+                self.expr(then)?;
+                self.expr(els)
+            }
+        }
+    }
+}
+
+// statics are initialized before main runs, so by a constant expression:
+// one of literals, sizeofs and the operators on them. enumerators are
+// already literals in the tree
+fn constant_init(alias: &str, e: &SExpr) -> Result<(), io::Error> {
+    fn constant(e: &SExpr) -> bool {
+        match e {
+            SExpr::Int(_)
+            | SExpr::TypedInt { .. }
+            | SExpr::Float { .. }
+            | SExpr::Bool(_)
+            | SExpr::SizeOfType(_) => true,
+            SExpr::UnaryE { l, .. } | SExpr::SizeOf(l) => constant(l),
+            SExpr::Cast(t, l) => typer::arith(t) && constant(l),
+            SExpr::BinE { l, r, .. }
+            | SExpr::LogE { l, r, .. }
+            | SExpr::BitE { l, r, .. }
+            | SExpr::RelE { l, r, .. } => constant(l) && constant(r),
+            SExpr::CondE { cond, then, els } => constant(cond) && constant(then) && constant(els),
+            _ => false,
+        }
+    }
+
+    match constant(e) {
+        true => Ok(()),
+        false => Err(io::Error::other(format!(
+            "initializer element of {alias} is not constant"
+        ))),
+    }
+}
+
+// the variable an lvalue is part of, a in a[i] and s in s.f.x, and whether
+// it's indexed. what a pointer points to isn't a variable of its own
+fn root(lval: &SExpr) -> Option<(&str, bool)> {
+    match lval {
+        SExpr::VarApp(alias) => Some((alias, false)),
+        SExpr::Index { alias, .. } => Some((alias, true)),
+        SExpr::Member { base, .. } => root(base),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test_typecheck {
+    use crate::{diagnostics::Warnings, lexer, parser_ast, source, SDef};
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/bindings";
+
+    #[test]
+    fn blocks() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/blocks.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let table = super::check(&tree).unwrap();
+        insta::assert_yaml_snapshot!(table, @r###"
+        ---
+        sigs:
+          - - main
+            - fp: []
+              body: Int
+        funcs:
+          - - main
+            - - name: x
+                typ: Int
+                depth: 1
+              - name: sum
+                typ: Int
+                depth: 1
+              - name: x
+                typ: Int
+                depth: 2
+              - name: x
+                typ: Int
+                depth: 3
+              - name: y
+                typ: Int
+                depth: 2
+              - name: p
+                typ:
+                  Ptr: Int
+                depth: 2
+              - name: z
+                typ: Int
+                depth: 2
+        "###);
+    }
+
+    #[test]
+    fn undeclared() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/undeclared.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let err = super::check(&tree).unwrap_err();
+        assert_eq!(err.to_string(), "use of undeclared variable y in main");
+
+        let span = source::located(&err).unwrap().span;
+        let pos = source::pos(&src, span.offset(&src), 4);
+        assert_eq!((pos.line, pos.col, span.len), (4, 13, 1));
+    }
+
+    #[test]
+    fn arity() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/arity.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let err = super::check(&tree).unwrap_err();
+        assert_eq!(err.to_string(), "f takes 2 arguments but 1 were given");
+    }
+
+    #[test]
+    fn void_return() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/void_return.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let err = super::check(&tree).unwrap_err();
+        assert_eq!(err.to_string(), "void function f should not return a value");
+    }
+
+    #[test]
+    fn const_assign() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/const_assign.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let err = super::check(&tree).unwrap_err();
+        assert_eq!(err.to_string(), "assignment of read-only variable x");
+
+        let located = source::located(&err).unwrap();
+        assert!(located.err.is::<super::ConstError>());
+        let pos = source::pos(&src, located.span.offset(&src), 4);
+        assert_eq!((pos.line, pos.col), (4, 5));
+    }
+
+    #[test]
+    fn static_init() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/static_init.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let err = super::check(&tree).unwrap_err();
+        assert_eq!(err.to_string(), "initializer element of y is not constant");
+    }
+
+    #[test]
+    fn prototypes() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/prototypes.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let table = super::check(&tree).unwrap();
+        insta::assert_yaml_snapshot!(table.sigs, @r###"
+        ---
+        - - add
+          - fp:
+              - Int
+              - Int
+            body: Int
+        - - twice
+          - fp:
+              - Int
+            body: Int
+        - - main
+          - fp: []
+            body: Int
+        "###);
+    }
+
+    #[test]
+    fn prototype_conflict() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/prototype_conflict.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let err = super::check(&tree).unwrap_err();
+        assert_eq!(err.to_string(), "conflicting types for add");
+    }
+
+    #[test]
+    fn redefinition() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/redefinition.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let err = super::check(&tree).unwrap_err();
+        assert_eq!(err.to_string(), "redefinition of n in f");
+
+        // the nested m shadows, but n is the param's scope's
+        let span = source::located(&err).unwrap().span;
+        let pos = source::pos(&src, span.offset(&src), 4);
+        assert_eq!(pos.line, 7);
+    }
+
+    #[test]
+    fn before_decl() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/before_decl.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let err = super::check(&tree).unwrap_err();
+        assert_eq!(err.to_string(), "use of y before its declaration in main");
+
+        let span = source::located(&err).unwrap().span;
+        let pos = source::pos(&src, span.offset(&src), 4);
+        assert_eq!((pos.line, pos.col, span.len), (4, 13, 1));
+
+        // a global is only in scope after its declaration
+        let src = "int f() { return g; }\nint g = 1;\nint main() { return f(); }";
+        let tree = parser_ast::parse_prg(&lexer::lex(src).unwrap()).unwrap();
+        let err = super::check(&tree).unwrap_err();
+        assert_eq!(err.to_string(), "use of g before its declaration in f");
+    }
+
+    #[test]
+    fn global_conflict() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/global_conflict.c")).expect("file dne");
+
+        // int g; is tentative, so int g = 1; after it defines it
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let err = super::check(&tree).unwrap_err();
+        assert_eq!(err.to_string(), "conflicting types for g");
+        let span = source::located(&err).unwrap().span;
+        assert_eq!(source::pos(&src, span.offset(&src), 4).line, 3);
+
+        let check = |src: &str| {
+            let tree = parser_ast::parse_prg(&lexer::lex(src).unwrap()).unwrap();
+            super::check(&tree).map_err(|e| e.to_string())
+        };
+        assert_eq!(
+            check("int g = 1;\nint g = 2;\nint main() { return g; }").unwrap_err(),
+            "redefinition of g"
+        );
+        assert_eq!(
+            check("int main;\nint main() { return 0; }").unwrap_err(),
+            "redefinition of main as a different kind of symbol"
+        );
+        assert_eq!(
+            check("int f(int x, int x) { return x; }\nint main() { return 0; }").unwrap_err(),
+            "redefinition of x in f"
+        );
+    }
+
+    #[test]
+    fn returns() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/returns.c")).expect("file dne");
+
+        // sign, spin and pick return on every path, but half runs off its end
+        // when x is odd
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        super::check(&tree).unwrap();
+        let err = super::check_returns(&tree).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "control reaches the end of non-void function half"
+        );
+        let span = source::located(&err).unwrap().span;
+        assert_eq!(source::pos(&src, span.offset(&src), 4).line, 31);
+
+        let check = |body: &str| {
+            let src = format!("int f(int x) {{ {body} }}\nint main() {{ return 0; }}");
+            let tree = parser_ast::parse_prg(&lexer::lex(&src).unwrap()).unwrap();
+            super::check_returns(&tree).is_ok()
+        };
+        assert!(check("do { return 1; } while (x);"));
+        assert!(!check("do { if (x) { continue; } return 1; } while (x);"));
+        assert!(check(
+            "for (int i = 0; true; i++) { if (i > x) { return i; } }"
+        ));
+        assert!(!check("while (1) { if (x) { break; } }"));
+        assert!(!check("while (x) { return 1; }"));
+        assert!(check(
+            "while (1) { switch (x) { case 1: break; default: return x; } }"
+        ));
+        assert!(!check("switch (x) { case 1: return 1; }"));
+        assert!(!check(
+            "switch (x) { default: if (x) { break; } return 1; }"
+        ));
+        assert!(!check("goto out; return 1; out: x = 2;"));
+    }
+
+    #[test]
+    fn uninitialized() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/uninit.c")).expect("file dne");
+
+        // x is only assigned when n > 0, but y is on every path, and main's
+        // s is shadowed in its block, then assigned
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        super::check(&tree).unwrap();
+        let mut cfg = Warnings::default();
+        assert!(super::warnings(&tree, &cfg).is_empty());
+        cfg.set("maybe-uninitialized").unwrap();
+        let diags = super::warnings(&tree, &cfg);
+        let diags = diags.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            diags,
+            ["picoc-warning[W0305]: x may be used uninitialized in f [-Wmaybe-uninitialized]"]
+        );
+
+        let uses = |body: &str| {
+            let src = format!("int f(int n) {{ {body} }}\nint main() {{ return 0; }}");
+            let tree = parser_ast::parse_prg(&lexer::lex(&src).unwrap()).unwrap();
+            let SDef::FuncDef(fd) = &tree[0].node else {
+                unreachable!()
+            };
+            let uses = super::uninitialized(fd);
+            uses.into_iter().map(|(alias, _)| alias).collect::<Vec<_>>()
+        };
+        assert!(uses("int x; if (n) { x = 1; } else { x = 2; } return x;").is_empty());
+        assert!(uses("int x; if (n) { return 0; } else { x = 2; } return x;").is_empty());
+        assert!(uses("int x; do { x = n; } while (n); return x;").is_empty());
+        assert!(uses("int x; int *p = &x; *p = 1; return x;").is_empty());
+        assert_eq!(
+            uses("int x; for (int i = 0; i < n; i++) { x = i; } return x;"),
+            ["x"]
+        );
+        assert_eq!(
+            uses("int x; switch (n) { case 1: x = 1; } return x;"),
+            ["x"]
+        );
+        assert!(uses("int x; switch (n) { default: x = 1; } return x;").is_empty());
+        assert_eq!(uses("int x; int y = n || x; return y + x;"), ["x"]);
+    }
+
+    #[test]
+    fn warnings() {
+        let path = "tests/fixtures/snap/diagnostics/warnings.c";
+        let src = fs::read_to_string(path).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        super::check(&tree).unwrap();
+        let warn = |flags: &[&str]| {
+            let mut cfg = Warnings::default();
+            flags.iter().for_each(|f| cfg.set(f).unwrap());
+            let diags = super::warnings(&tree, &cfg);
+            diags.iter().map(|d| d.to_string()).collect::<Vec<_>>()
+        };
+        insta::assert_debug_snapshot!(warn(&[]), @r###"
+        [
+            "picoc-warning[W0303]: return type of foo defaults to int [-Wimplicit-int]",
+            "picoc-warning[W0304]: control reaches the end of non-void function bar [-Wreturn-type]",
+        ]
+        "###);
+        insta::assert_debug_snapshot!(warn(&["all", "no-implicit-int", "error"]), @r###"
+        [
+            "picoc-error[W0301]: unused variable x [-Wunused-variable]",
+            "picoc-error[W0302]: unreachable code [-Wunreachable-code]",
+            "picoc-error[W0304]: control reaches the end of non-void function bar [-Wreturn-type]",
+            "picoc-error[W0301]: unused variable y [-Wunused-variable]",
+        ]
+        "###);
+    }
+}
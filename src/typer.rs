@@ -1,44 +1,397 @@
-use crate::{LambdaType, SBinOp, SDef, SExpr, SFuncDef, SPrg, SStmt, Tnv, Type};
-use std::collections::HashMap;
-use std::io;
+use crate::{
+    evaluator::RET,
+    layout,
+    source::{self, Span},
+    IntModel, LambdaType, SBinOp, SBitOp, SCase, SContract, SContractKind, SDef, SExpr, SFuncDef,
+    SPrg, SRelOp, SStmt, SStore, SUnaryOp, SVarDecl, SVarDef, Spanned, Tnv, Type, RESULT,
+};
+use std::collections::{HashMap, HashSet};
+use std::{io, slice};
 
 pub fn type_prg(prg: &SPrg) -> Result<Type, io::Error> {
-    let mut tnv = Tnv {
-        fnv: HashMap::new(),
-        vnv: HashMap::new(),
-    };
+    main_type(&type_unit(prg)?)
+}
 
-    let _ = prg
-        .iter()
-        .map(|def| match def {
-            SDef::FuncDef(fd) => {
-                let ltnv = HashMap::new();
-                let type_check = type_func(fd, &tnv, ltnv).map(|t| {
-                    tnv.fnv.insert(
-                        fd.alias.clone(),
-                        LambdaType {
-                            fp: fd.fps.iter().map(|(_, t)| t.clone()).collect(),
-                            body: t,
-                        },
-                    );
-                });
-                type_check
-            }
-            SDef::VarDef(_vd) => todo!(),
-        })
-        .collect::<Result<Vec<_>, _>>()?;
+// as type_prg, by c0's rules, which keep bools apart from ints
+pub fn type_prg_c0(prg: &SPrg) -> Result<Type, io::Error> {
+    main_type(&type_in(prg, true)?)
+}
 
+fn main_type(tnv: &Tnv) -> Result<Type, io::Error> {
     Ok(tnv
         .fnv
         .get("main")
-        .ok_or(io::Error::new(
-            io::ErrorKind::Other,
-            "main function not found",
-        ))?
+        .ok_or(io::Error::other("main function not found"))?
         .body
         .clone())
 }
 
+// a unit linked with others needn't have main
+pub fn type_unit(prg: &SPrg) -> Result<Tnv, io::Error> {
+    type_in(prg, false)
+}
+
+fn type_in(prg: &SPrg, c0: bool) -> Result<Tnv, io::Error> {
+    let tnv = Tnv { c0, ..tnv(prg)? };
+    check_enums(prg)?;
+
+    let _ = prg
+        .iter()
+        .map(|def| {
+            match &def.node {
+                SDef::FuncDef(fd) => {
+                    check_jumps(&fd.body, false, false)?;
+                    check_labels(&fd.body)?;
+                    check_returns(fd)?;
+                    type_func(fd, &tnv, tnv.vnv.clone()) // file scope variables are in scope
+                }
+                SDef::VarDef(vd) => {
+                    let et = locate_expr(&vd.expr, &tnv, &tnv.vnv)?;
+                    if takes(&vd.typ, &vd.expr, &et, &tnv) {
+                        Ok(Type::Void)
+                    } else {
+                        Err(io::Error::other("type error"))
+                    }
+                }
+                SDef::FuncDecl(_) | SDef::VarDecl(_) => Ok(Type::Void),
+                SDef::StructDef(_) | SDef::UnionDef(_) => Ok(Type::Void), // checked when laid out
+                SDef::EnumDef(_) => Ok(Type::Void),
+            }
+            .map_err(|e| source::locate(e, def.span))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(tnv)
+}
+
+// enumerators share one namespace, whichever enum declares them
+fn check_enums(prg: &SPrg) -> Result<(), io::Error> {
+    let mut seen = HashSet::new();
+    for def in prg {
+        if let SDef::EnumDef(ed) = &def.node {
+            for (e, _) in &ed.enumerators {
+                if !seen.insert(e) {
+                    let err = io::Error::other(format!("enumerator {e} redeclared"));
+                    return Err(source::locate(err, def.span));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// break leaves the innermost loop or switch, and continue the innermost loop
+fn check_jumps(stmts: &[Spanned<SStmt>], brk: bool, cont: bool) -> Result<(), io::Error> {
+    stmts.iter().try_for_each(|s| match &s.node {
+        SStmt::Break if !brk => Err(source::locate(
+            io::Error::other("break statement not within loop or switch"),
+            s.span,
+        )),
+        SStmt::Continue if !cont => Err(source::locate(
+            io::Error::other("continue statement not within a loop"),
+            s.span,
+        )),
+        SStmt::IfEls { then, els, .. } => {
+            check_jumps(slice::from_ref(then), brk, cont)?;
+            els.as_ref()
+                .map_or(Ok(()), |els| check_jumps(slice::from_ref(els), brk, cont))
+        }
+        SStmt::Switch { arms, .. } => arms
+            .iter()
+            .try_for_each(|a| check_jumps(&a.body, true, cont)),
+        SStmt::While { body, .. } | SStmt::DoWhile { body, .. } | SStmt::For { body, .. } => {
+            check_jumps(body, true, true)
+        }
+        SStmt::Block(body) => check_jumps(body, brk, cont),
+        _ => Ok(()),
+    })
+}
+
+// void functions return nothing, and every other function a value
+fn check_returns(fd: &SFuncDef) -> Result<(), io::Error> {
+    fn walk(stmts: &[Spanned<SStmt>], fd: &SFuncDef) -> Result<(), io::Error> {
+        stmts.iter().try_for_each(|s| match &s.node {
+            SStmt::Return(Some(_)) if fd.typ == Type::Void => Err(source::locate(
+                io::Error::other(format!(
+                    "void function {} should not return a value",
+                    fd.alias
+                )),
+                s.span,
+            )),
+            SStmt::Return(None) if fd.typ != Type::Void => Err(source::locate(
+                io::Error::other(format!(
+                    "non-void function {} should return a value",
+                    fd.alias
+                )),
+                s.span,
+            )),
+            SStmt::IfEls { then, els, .. } => {
+                walk(slice::from_ref(then), fd)?;
+                els.as_ref()
+                    .map_or(Ok(()), |els| walk(slice::from_ref(els), fd))
+            }
+            SStmt::Switch { arms, .. } => arms.iter().try_for_each(|a| walk(&a.body, fd)),
+            SStmt::While { body, .. }
+            | SStmt::DoWhile { body, .. }
+            | SStmt::For { body, .. }
+            | SStmt::Block(body) => walk(body, fd),
+            _ => Ok(()),
+        })
+    }
+
+    walk(&fd.body, fd)
+}
+
+// labels are scoped to their function: each is defined once, anywhere in
+// the body, and every goto names one of them
+fn check_labels(body: &[Spanned<SStmt>]) -> Result<(), io::Error> {
+    fn walk<'a>(
+        stmts: &'a [Spanned<SStmt>],
+        labels: &mut HashSet<&'a str>,
+        gotos: &mut Vec<(&'a str, Span)>,
+    ) -> Result<(), io::Error> {
+        stmts.iter().try_for_each(|s| match &s.node {
+            SStmt::Label(l) if !labels.insert(l) => Err(source::locate(
+                io::Error::other(format!("label {l} redefined")),
+                s.span,
+            )),
+            SStmt::Goto(l) => {
+                gotos.push((l, s.span));
+                Ok(())
+            }
+            SStmt::IfEls { then, els, .. } => {
+                walk(slice::from_ref(then), labels, gotos)?;
+                els.as_ref()
+                    .map_or(Ok(()), |els| walk(slice::from_ref(els), labels, gotos))
+            }
+            SStmt::Switch { arms, .. } => {
+                arms.iter().try_for_each(|a| walk(&a.body, labels, gotos))
+            }
+            SStmt::While { body, .. }
+            | SStmt::DoWhile { body, .. }
+            | SStmt::For { body, .. }
+            | SStmt::Block(body) => walk(body, labels, gotos),
+            _ => Ok(()),
+        })
+    }
+
+    let (mut labels, mut gotos) = (HashSet::new(), vec![]);
+    walk(body, &mut labels, &mut gotos)?;
+    match gotos.into_iter().find(|(l, _)| !labels.contains(l)) {
+        Some((l, span)) => Err(source::locate(
+            io::Error::other(format!("label {l} used but not defined")),
+            span,
+        )),
+        None => Ok(()),
+    }
+}
+
+// the global environment: function signatures, file scope variables and
+// struct layouts
+pub fn tnv(prg: &SPrg) -> Result<Tnv, io::Error> {
+    let mut tnv = Tnv {
+        fnv: HashMap::new(),
+        vnv: HashMap::new(),
+        layouts: layout::layouts(prg)?,
+        model: IntModel::default(),
+        c0: false,
+    };
+
+    // signatures come first so functions can be applied
+    // before their definition, and recursively
+    tnv.fnv.extend(signatures(prg)?);
+    prg.iter().for_each(|def| {
+        if let SDef::VarDef(SVarDef { alias, typ, .. })
+        | SDef::VarDecl(SVarDecl { alias, typ, .. }) = &def.node
+        {
+            tnv.vnv.insert(alias.clone(), typ.clone());
+        }
+    });
+
+    Ok(tnv)
+}
+
+// every function's signature, in the order they're first declared. a
+// function may be declared any number of times, but defined once, and each
+// prototype has to agree with the others and with the definition
+pub fn signatures(prg: &SPrg) -> Result<Vec<(String, LambdaType)>, io::Error> {
+    let mut sigs: Vec<(String, LambdaType)> = vec![];
+    let mut defined = HashSet::new();
+    for def in prg {
+        let (alias, sig) = match &def.node {
+            SDef::FuncDef(fd) => {
+                if !defined.insert(&fd.alias) {
+                    let err = io::Error::other(format!("redefinition of {}", fd.alias));
+                    return Err(source::locate(err, def.span));
+                }
+                let fp = fd.fps.iter().map(|(_, t)| t.clone()).collect();
+                (
+                    &fd.alias,
+                    LambdaType {
+                        fp,
+                        body: fd.typ.clone(),
+                        variadic: false,
+                    },
+                )
+            }
+            SDef::FuncDecl(fd) => {
+                let (fp, body, variadic) = (fd.fps.clone(), fd.typ.clone(), fd.variadic);
+                (&fd.alias, LambdaType { fp, body, variadic })
+            }
+            _ => continue,
+        };
+
+        match sigs.iter().find(|(a, _)| a == alias) {
+            Some((_, prev)) if *prev != sig => {
+                let err = io::Error::other(format!("conflicting types for {alias}"));
+                return Err(source::locate(err, def.span));
+            }
+            Some(_) => (),
+            None => sigs.push((alias.clone(), sig)),
+        }
+    }
+
+    Ok(sigs)
+}
+
+pub fn integral(t: &Type) -> bool {
+    matches!(
+        t,
+        Type::Int
+            | Type::Char
+            | Type::Short
+            | Type::Long
+            | Type::UChar
+            | Type::UShort
+            | Type::UInt
+            | Type::ULong
+    )
+}
+
+pub fn floating(t: &Type) -> bool {
+    matches!(t, Type::Float | Type::Double)
+}
+
+pub fn arith(t: &Type) -> bool {
+    integral(t) || floating(t)
+}
+
+pub fn unsigned(t: &Type) -> bool {
+    matches!(t, Type::UChar | Type::UShort | Type::UInt | Type::ULong)
+}
+
+// chars and shorts are promoted to int before any arithmetic. int holds
+// every unsigned short, since ints are wider than shorts
+fn promote(t: Type) -> Type {
+    match t {
+        Type::Char | Type::UChar | Type::Short | Type::UShort => Type::Int,
+        t => t,
+    }
+}
+
+// the usual arithmetic conversions: both sides are promoted, then the lower
+// ranked one converts to the higher's type. the floating types outrank every
+// integer. long and int are the same width under ilp32, so long can't hold
+// every unsigned int and both become unsigned long. a bool, as a comparison
+// gives, is the int 0 or 1 in arithmetic
+pub fn convert(lt: Type, rt: Type) -> Option<Type> {
+    let int = |t| match t {
+        Type::Bool => Type::Int,
+        t => promote(t),
+    };
+    match (int(lt), int(rt)) {
+        (lt, rt) if !arith(&lt) || !arith(&rt) => None,
+        (lt, rt) if lt == rt => Some(lt),
+        (Type::Double, _) | (_, Type::Double) => Some(Type::Double),
+        (Type::Float, _) | (_, Type::Float) => Some(Type::Float),
+        (Type::ULong, _) | (_, Type::ULong) => Some(Type::ULong),
+        (Type::Long, Type::UInt) | (Type::UInt, Type::Long) => Some(Type::ULong),
+        (Type::Long, _) | (_, Type::Long) => Some(Type::Long),
+        _ => Some(Type::UInt),
+    }
+}
+
+// an array used as a value is a pointer to its first element
+pub fn decay(t: Type) -> Type {
+    match t {
+        Type::Arr(t, _) => Type::Ptr(t),
+        t => t,
+    }
+}
+
+// the usual arithmetic conversions as the program's typed to. c0 keeps
+// bools apart from ints, so a bool is no arithmetic operand there
+fn converts(tnv: &Tnv, lt: Type, rt: Type) -> Option<Type> {
+    match tnv.c0 && (lt == Type::Bool || rt == Type::Bool) {
+        true => None,
+        false => convert(lt, rt),
+    }
+}
+
+// numbers convert to each other on assignment, narrowing (or truncating
+// toward zero) when stored, and in c a bool converts to the number 0 or 1.
+// an array is assigned as its first element's address
+fn assignable(dt: &Type, et: &Type, tnv: &Tnv) -> bool {
+    dt == et
+        || arith(dt) && (arith(et) || *et == Type::Bool && !tnv.c0)
+        || matches!(et, Type::Arr(..)) && *dt == decay(et.clone())
+}
+
+// lval op e, which a compound store assigns to lval
+pub fn compound(st: &SStore, op: &SBinOp) -> Spanned<SExpr> {
+    let e = SExpr::BinE {
+        op: op.clone(),
+        l: st.lval.clone(),
+        r: st.expr.clone(),
+    };
+    Spanned::new(e, st.expr.span)
+}
+
+// the integer constant 0 is a null pointer of any pointer type (c89 3.2.2.3)
+fn null(e: &SExpr) -> bool {
+    matches!(e, SExpr::Int(0) | SExpr::TypedInt { val: 0, .. })
+}
+
+// what e, of type et, can be assigned to: what its type is assignable to,
+// and any pointer when it's a null pointer constant
+fn takes(dt: &Type, e: &SExpr, et: &Type, tnv: &Tnv) -> bool {
+    assignable(dt, et, tnv) || matches!(dt, Type::Ptr(_)) && null(e)
+}
+
+// what a condition can be: it's true when it's nonzero
+fn scalar(t: &Type) -> bool {
+    *t == Type::Bool || arith(t) || matches!(t, Type::Ptr(_))
+}
+
+// what a cast converts between (c89 3.3.4): anything to void, numbers and
+// bools to each other, and pointers to each other and to and from integers.
+// structs and arrays don't convert, and neither do floats and pointers
+fn castable(dt: &Type, et: &Type) -> bool {
+    let number = |t: &Type| arith(t) || *t == Type::Bool;
+    match (dt, et) {
+        (Type::Void, _) | (Type::Ptr(_), Type::Ptr(_)) => true,
+        (Type::Ptr(_), t) | (t, Type::Ptr(_)) => integral(t),
+        _ => number(dt) && number(et),
+    }
+}
+
+// what a pointer arithmetic operand points to, once an array's decayed.
+// void has no size to step by
+fn pointer(t: &Type) -> Option<Type> {
+    match decay(t.clone()) {
+        Type::Ptr(t) if *t != Type::Void => Some(*t),
+        _ => None,
+    }
+}
+
+// sizeof gives a size_t, which is unsigned long. sizes differ between int
+// models, but whether a type has one doesn't
+fn sized(t: &Type, gtnv: &Tnv) -> Result<Type, io::Error> {
+    gtnv.layouts.sizeof(t, &IntModel::default())?;
+    Ok(Type::ULong)
+}
+
 pub fn type_func(
     fd: &SFuncDef,
     gnv: &Tnv,
@@ -51,31 +404,66 @@ pub fn type_func(
     fd.fps.iter().for_each(|(a, t)| {
         ltnv.insert(a.clone(), t.clone()); // Γ [e1 <- T1], ... [en <- Tn]
     });
+    ltnv.insert(RET.to_owned(), fd.typ.clone()); // what a return converts to
+
+    // contracts are conditions, like an if's. ensures sees \result : T2
+    let mut rltnv = ltnv.clone();
+    rltnv.insert(RESULT.to_owned(), fd.typ.clone());
+    for c in &fd.contracts {
+        match c.kind {
+            SContractKind::Ensures => locate_expr(&c.expr, gnv, &rltnv)?,
+            _ => locate_expr(&c.expr, gnv, &ltnv)?,
+        };
+    }
 
-    fd.body
+    type_stmts(&fd.body, gnv, &mut ltnv).and_then(|bt| {
+        // ⊢ B : T2
+        if assignable(&fd.typ, &bt, gnv) {
+            Ok(fd.typ.clone()) // Γ ⊢ (lambda e1:T1 ... en:Tn B) : (T1 * ... * Tn -> T2)
+        } else {
+            Err(io::Error::other("type error"))
+        }
+    })
+}
+
+fn type_stmts(
+    stmts: &[Spanned<SStmt>],
+    gnv: &Tnv,
+    ltnv: &mut HashMap<String, Type>,
+) -> Result<Type, io::Error> {
+    stmts
         .iter()
-        .map(|stmt| type_stmt(stmt, gnv, &mut ltnv))
+        .map(|stmt| locate_stmt(stmt, gnv, ltnv))
         .collect::<Result<Vec<_>, _>>()?
         .iter()
         .try_fold(Type::Void, |prev_t, next_t| match (&prev_t, next_t) {
             (_, Type::Void) => Ok(prev_t),
             (Type::Void, _) => Ok(next_t.clone()),
             (prev_t, next_t) => {
-                if prev_t == next_t {
+                if assignable(prev_t, next_t, gnv) {
                     Ok(prev_t.clone())
                 } else {
-                    Err(io::Error::new(io::ErrorKind::Other, "type error"))
+                    Err(io::Error::other("type error"))
                 }
             }
         })
-        .and_then(|bt| {
-            // ⊢ B : T2
-            if bt == fd.typ {
-                Ok(bt) // Γ ⊢ (lambda e1:T1 ... en:Tn B) : (T1 * ... * Tn -> T2)
-            } else {
-                Err(io::Error::new(io::ErrorKind::Other, "type error"))
-            }
-        })
+}
+
+// errors are located at the innermost node they come from
+fn locate_stmt(
+    stmt: &Spanned<SStmt>,
+    gnv: &Tnv,
+    ltnv: &mut HashMap<String, Type>,
+) -> Result<Type, io::Error> {
+    type_stmt(stmt, gnv, ltnv).map_err(|e| source::locate(e, stmt.span))
+}
+
+fn locate_expr(
+    e: &Spanned<SExpr>,
+    gtnv: &Tnv,
+    ltnv: &HashMap<String, Type>,
+) -> Result<Type, io::Error> {
+    type_expr(e, gtnv, ltnv).map_err(|err| source::locate(err, e.span))
 }
 
 pub fn type_stmt(
@@ -85,64 +473,324 @@ pub fn type_stmt(
 ) -> Result<Type, io::Error> {
     match stmt {
         SStmt::IfEls { cond, then, els } => {
-            let ct = type_expr(cond, gnv, ltnv)?;
-            let tt = type_stmt(then, gnv, ltnv)?;
+            let ct = locate_expr(cond, gnv, ltnv)?;
+            let tt = locate_stmt(then, gnv, ltnv)?;
             let et = els
                 .as_ref()
-                .map(|els| type_stmt(els, gnv, ltnv))
+                .map(|els| locate_stmt(els, gnv, ltnv))
                 .transpose()?;
 
-            // todo (for now):
-            // 3. tt and et are Stmt, not Vec<Stmt>
-            // 4. must return expression Type. not void/stmt/valid/cmd Type
+            // the cond is tested for nonzero, as in c. an arm needn't return,
+            // and those that do return what converts to the function's type.
+            // whether every path returns is check_returns'
+            if !scalar(&ct) {
+                return Err(io::Error::other("type error"));
+            }
+            match (tt, et) {
+                (tt, None | Some(Type::Void)) => Ok(tt),
+                (Type::Void, Some(et)) => Ok(et),
+                (tt, Some(et)) if assignable(&tt, &et, gnv) => Ok(tt),
+                (tt, Some(et)) if assignable(&et, &tt, gnv) => Ok(et),
+                _ => Err(io::Error::other("type error")),
+            }
+        }
+        SStmt::Switch { cond, arms } => {
+            if !integral(&locate_expr(cond, gnv, ltnv)?) {
+                return Err(io::Error::other("type error"));
+            }
 
-            match et {
-                Some(et) => {
-                    if ct == Type::Bool && tt == et {
-                        Ok(tt)
-                    } else {
-                        Err(io::Error::new(io::ErrorKind::Other, "type error"))
-                    }
+            let mut labels = HashSet::new();
+            for a in arms {
+                if !labels.insert(a.label) {
+                    return Err(io::Error::other(match a.label {
+                        Some(v) => format!("duplicate case value {v}"),
+                        None => "multiple default labels in one switch".to_owned(),
+                    }));
                 }
-                None => Ok(tt.clone()),
             }
+
+            // the arms are one block, since control falls through them
+            let body = arms
+                .iter()
+                .flat_map(|a| a.body.iter().cloned())
+                .collect::<Vec<_>>();
+            let mut bltnv = ltnv.clone();
+            type_stmts(&body, gnv, &mut bltnv)
+        }
+        SStmt::While {
+            cond,
+            body,
+            invariants,
+        } => {
+            for c in invariants {
+                let _ = locate_expr(&c.expr, gnv, ltnv)?;
+            }
+            let _ = locate_expr(cond, gnv, ltnv)?;
+            let mut bltnv = ltnv.clone(); // body bindings don't escape the loop
+            type_stmts(body, gnv, &mut bltnv)
+        }
+        SStmt::Block(body) => {
+            let mut bltnv = ltnv.clone(); // inner bindings shadow, then go out of scope
+            type_stmts(body, gnv, &mut bltnv)
+        }
+        SStmt::DoWhile { body, cond } => {
+            let mut bltnv = ltnv.clone();
+            let bt = type_stmts(body, gnv, &mut bltnv)?;
+            let _ = locate_expr(cond, gnv, ltnv)?; // the body's bindings are out of scope here
+            Ok(bt)
+        }
+        SStmt::For {
+            asnmt,
+            cond,
+            update,
+            body,
+            invariants,
+        } => {
+            let mut fltnv = ltnv.clone(); // neither do bindings in the header
+            let _ = locate_stmt(asnmt, gnv, &mut fltnv)?;
+            for c in invariants {
+                let _ = locate_expr(&c.expr, gnv, &fltnv)?;
+            }
+            let _ = locate_expr(cond, gnv, &fltnv)?;
+            let _ = locate_stmt(update, gnv, &mut fltnv)?;
+            type_stmts(body, gnv, &mut fltnv)
         }
-        SStmt::While { cond: _, body: _ } => todo!(),
         SStmt::Asnmt(vd) => {
-            let et = type_expr(&vd.expr, gnv, ltnv)?;
-            ltnv.insert(vd.alias.clone(), et.clone()); // Γ [x <- T]
-            Ok(et)
+            // ints are declared from bools too, and keep the bool's type
+            let et = locate_expr(&vd.expr, gnv, ltnv)?;
+            let bt = match (&vd.typ, et) {
+                (Type::Ptr(_), et @ Type::Arr(..)) if assignable(&vd.typ, &et, gnv) => {
+                    vd.typ.clone()
+                }
+                (Type::Ptr(_), _) if null(&vd.expr) => vd.typ.clone(),
+                (_, Type::Struct(_) | Type::Union(_) | Type::Arr(..)) => {
+                    return Err(io::Error::other("type error"))
+                } // no aggregate copies yet
+                (Type::Int, Type::Bool) => Type::Bool,
+                (dt, et) if assignable(dt, &et, gnv) => dt.clone(),
+                _ => return Err(io::Error::other("type error")),
+            };
+            ltnv.insert(vd.alias.clone(), bt); // Γ [x <- T]
+            Ok(Type::Void) // bindings are commands. only returns give the body a type
+        }
+        SStmt::Decl(vd) => {
+            ltnv.insert(vd.alias.clone(), vd.typ.clone()); // Γ [x <- T]
+            Ok(Type::Void)
         }
-        SStmt::Return(expr) => {
-            let rt = type_expr(expr, gnv, ltnv)?;
-            Ok(rt)
+        SStmt::Update(vu) => {
+            // Γ ⊢ x : T    Γ ⊢ e : T
+            let et = locate_expr(&vu.expr, gnv, ltnv)?;
+            match ltnv.get(&vu.alias) {
+                Some(Type::Struct(_) | Type::Union(_) | Type::Arr(..)) => {
+                    Err(io::Error::other("type error"))
+                }
+                Some(xt) if takes(xt, &vu.expr, &et, gnv) => Ok(Type::Void),
+                _ => Err(io::Error::other("type error")),
+            }
+        }
+        SStmt::Store(st) => {
+            // Γ ⊢ *e1 : T    Γ ⊢ e2 : T
+            // a compound store assigns lval op e to lval
+            let (lt, et) = (
+                locate_expr(&st.lval, gnv, ltnv)?,
+                match &st.op {
+                    None => locate_expr(&st.expr, gnv, ltnv)?,
+                    Some(op) => type_expr(&compound(st, op), gnv, ltnv)?,
+                },
+            );
+            if takes(&lt, &st.expr, &et, gnv)
+                && !matches!(lt, Type::Struct(_) | Type::Union(_) | Type::Arr(..))
+            {
+                Ok(Type::Void)
+            } else {
+                Err(io::Error::other("type error"))
+            }
         }
+        SStmt::Return(Some(expr)) => {
+            // a null pointer constant returns as the function's pointer
+            let rt = locate_expr(expr, gnv, ltnv)?;
+            match ltnv.get(RET) {
+                Some(t @ Type::Ptr(_)) if null(expr) => Ok(t.clone()),
+                _ => Ok(rt),
+            }
+        }
+        SStmt::Return(None) => Ok(Type::Void), // placed by check_returns
+        SStmt::Call(e) => {
+            let _ = locate_expr(e, gnv, ltnv)?; // any value is dropped
+            Ok(Type::Void)
+        }
+        SStmt::Contract(SContract { expr: e, .. }) | SStmt::Assert(e) => {
+            let _ = locate_expr(e, gnv, ltnv)?; // a condition, like an if's
+            Ok(Type::Void)
+        }
+        SStmt::Break | SStmt::Continue => Ok(Type::Void), // placed by check_jumps
+        SStmt::Label(_) | SStmt::Goto(_) => Ok(Type::Void), // resolved by check_labels
     }
 }
 
 pub fn type_expr(e: &SExpr, gtnv: &Tnv, ltnv: &HashMap<String, Type>) -> Result<Type, io::Error> {
     match e {
         // ---------------------intros (axioms)-------------------------
-        SExpr::Int(_) => Ok(Type::Int),   // ⊢ n : Int
-        SExpr::Bool(_) => Ok(Type::Bool), // ⊢ b : Bool
+        SExpr::Int(_) => Ok(Type::Int),                 // ⊢ n : Int
+        SExpr::TypedInt { typ, .. } => Ok(typ.clone()), // ⊢ 42u : UInt, ⊢ 42L : Long
+        SExpr::Float { single: true, .. } => Ok(Type::Float), // ⊢ 1.5f : Float
+        SExpr::Float { single: false, .. } => Ok(Type::Double), // ⊢ 1.5 : Double
+        SExpr::Str(_) => Ok(Type::Ptr(Box::new(Type::Char))), // ⊢ s : Char*
+        SExpr::Bool(_) => Ok(Type::Bool),               // ⊢ b : Bool
+        SExpr::SizeOfType(t) => sized(t, gtnv),         // ⊢ sizeof(T) : ULong
+        SExpr::SizeOf(l) => sized(&locate_expr(l, gtnv, ltnv)?, gtnv), // ⊢ e : T ⟹ ⊢ sizeof e : ULong
+        SExpr::Alloc(t) => sized(t, gtnv).map(|_| Type::Ptr(Box::new(t.clone()))), // ⊢ alloc(T) : T*
+        SExpr::AllocArray(t, n) => match locate_expr(n, gtnv, ltnv)? {
+            nt if integral(&nt) => sized(t, gtnv).map(|_| Type::Ptr(Box::new(t.clone()))), // ⊢ e : Int ⟹ ⊢ alloc_array(T, e) : T*
+            _ => Err(io::Error::other("type error")),
+        },
         // ---------------------elims (rules)--------------------------
-        SExpr::UnaryE { op: _, l } => type_expr(l, gtnv, ltnv),
-        SExpr::BinE { op, l, r } => match op {
-            // ignoring distinctions within types
-            SBinOp::Add | SBinOp::Sub | SBinOp::Mult | SBinOp::Div | SBinOp::Mod => {
+        SExpr::Cast(t, l) => match locate_expr(l, gtnv, ltnv)? {
+            lt if castable(t, &decay(lt.clone())) => Ok(t.clone()), // ⊢ e : T', T' converts to T ⟹ ⊢ (T) e : T
+            _ => Err(io::Error::other("type error")),
+        },
+        SExpr::UnaryE { op, l } => match (op, promote(locate_expr(l, gtnv, ltnv)?)) {
+            (SUnaryOp::Not, t) if scalar(&t) => Ok(Type::Bool), // ⊢ e : T, T scalar ⟹ ⊢ !e : Bool
+            (SUnaryOp::Not, _) => Err(io::Error::other("type error")),
+            (SUnaryOp::Add | SUnaryOp::Sub, t) if arith(&t) => Ok(t), // ⊢ e : Int ⟹ ⊢ -e : Int
+            (SUnaryOp::BitNot, t) if integral(&t) => Ok(t),
+            (SUnaryOp::Add | SUnaryOp::Sub | SUnaryOp::BitNot, _) => {
+                Err(io::Error::other("type error"))
+            }
+        },
+        SExpr::BinE { op, l, r } => {
+            match (op, locate_expr(l, gtnv, ltnv)?, locate_expr(r, gtnv, ltnv)?) {
+                // p + e is e elements past p, and p - q the elements between them.
+                // each operand's typed once, or a chain of them would be typed
+                // exponentially often
+                (SBinOp::Add | SBinOp::Sub, lt, rt)
+                    if pointer(&lt).is_some() || pointer(&rt).is_some() =>
+                {
+                    match (op, pointer(&lt), pointer(&rt)) {
+                        (_, Some(t), None) if integral(&rt) => Ok(Type::Ptr(Box::new(t))), // Γ ⊢ p : T*, Γ ⊢ e : Int ⟹ Γ ⊢ p + e : T*
+                        (SBinOp::Add, None, Some(t)) if integral(&lt) => Ok(Type::Ptr(Box::new(t))), // Γ ⊢ e : Int, Γ ⊢ p : T* ⟹ Γ ⊢ e + p : T*
+                        (SBinOp::Sub, Some(t), Some(u)) if t == u => Ok(Type::Long), // Γ ⊢ p : T*, Γ ⊢ q : T* ⟹ Γ ⊢ p - q : Long
+                        _ => Err(io::Error::other("type error")),
+                    }
+                }
+                // ignoring distinctions within types
+                (SBinOp::Add | SBinOp::Sub | SBinOp::Mult | SBinOp::Div | SBinOp::Mod, lt, rt) => {
+                    // ⊢ e1 : Int, ⊢ e2 : Int
+                    // ------------------------ BIN_OP
+                    //     ⊢ e1 + e2 : Int
+                    match converts(gtnv, lt, rt) {
+                        Some(t) if *op == SBinOp::Mod && floating(&t) => {
+                            Err(io::Error::other("type error")) // % is for integers only
+                        }
+                        Some(t) => Ok(t),
+                        None => Err(io::Error::other("type error")),
+                    }
+                } // perserves distinctions between types
+            }
+        }
+        SExpr::RelE { op, l, r } => match op {
+            SRelOp::Lt | SRelOp::LtEq | SRelOp::Gt | SRelOp::GtEq => {
                 // ⊢ e1 : Int, ⊢ e2 : Int
-                // ------------------------ BIN_OP
-                //     ⊢ e1 + e2 : Int
-                match (type_expr(l, gtnv, ltnv)?, type_expr(r, gtnv, ltnv)?) {
-                    (Type::Int, Type::Int) => Ok(Type::Int),
-                    _ => Err(io::Error::new(io::ErrorKind::Other, "type error")),
+                // ------------------------ CMP_OP
+                //     ⊢ e1 < e2 : Bool
+                converts(
+                    gtnv,
+                    locate_expr(l, gtnv, ltnv)?,
+                    locate_expr(r, gtnv, ltnv)?,
+                )
+                .map(|_| Type::Bool)
+                .ok_or(io::Error::other("type error"))
+            }
+            SRelOp::Eq | SRelOp::Neq => {
+                // ⊢ e1 : T, ⊢ e2 : T
+                // ------------------------ EQ_OP
+                //    ⊢ e1 == e2 : Bool
+                // a pointer's compared with a null pointer constant too: p == 0
+                let (lt, rt) = (locate_expr(l, gtnv, ltnv)?, locate_expr(r, gtnv, ltnv)?);
+                let nulled =
+                    matches!(lt, Type::Ptr(_)) && null(r) || matches!(rt, Type::Ptr(_)) && null(l);
+                if nulled
+                    || promote(lt.clone()) == promote(rt.clone())
+                    || converts(gtnv, lt, rt).is_some()
+                {
+                    Ok(Type::Bool)
+                } else {
+                    Err(io::Error::other("type error"))
                 }
-            } // perserves distinctions between types
+            }
+        },
+        SExpr::BitE { op, l, r } => {
+            // ⊢ e1 : Int, ⊢ e2 : Int
+            // ------------------------ BIT_OP
+            //    ⊢ e1 & e2 : Int
+            let (lt, rt) = (locate_expr(l, gtnv, ltnv)?, locate_expr(r, gtnv, ltnv)?);
+            match op {
+                // a shift has its left operand's type, whatever the count's
+                SBitOp::Shl | SBitOp::Shr if integral(&lt) && integral(&rt) => Ok(promote(lt)),
+                SBitOp::Shl | SBitOp::Shr => Err(io::Error::other("type error")),
+                _ => converts(gtnv, lt, rt)
+                    .filter(integral)
+                    .ok_or(io::Error::other("type error")),
+            }
+        }
+        SExpr::LogE { op: _, l, r } => {
+            // ⊢ e1 : T1, ⊢ e2 : T2, T1 T2 scalar
+            // ---------------------------------- LOG_OP
+            //          ⊢ e1 && e2 : Bool
+            let (lt, rt) = (locate_expr(l, gtnv, ltnv)?, locate_expr(r, gtnv, ltnv)?);
+            match scalar(&decay(lt)) && scalar(&decay(rt)) {
+                true => Ok(Type::Bool),
+                false => Err(io::Error::other("type error")),
+            }
+        }
+        SExpr::CondE { cond, then, els } => {
+            // ⊢ e1 : S, ⊢ e2 : T, ⊢ e3 : T, S scalar
+            // -------------------------------------- COND
+            //          ⊢ e1 ? e2 : e3 : T
+            let ct = locate_expr(cond, gtnv, ltnv)?;
+            match (
+                scalar(&decay(ct)),
+                promote(locate_expr(then, gtnv, ltnv)?),
+                promote(locate_expr(els, gtnv, ltnv)?),
+            ) {
+                (true, tt, et) if tt == et => Ok(tt),
+                (true, tt @ Type::Ptr(_), _) if null(els) => Ok(tt),
+                (true, _, et @ Type::Ptr(_)) if null(then) => Ok(et),
+                (true, tt, et) => converts(gtnv, tt, et).ok_or(io::Error::other("type error")),
+                _ => Err(io::Error::other("type error")),
+            }
+        }
+        SExpr::AddrOf(l) => Ok(Type::Ptr(Box::new(locate_expr(l, gtnv, ltnv)?))), // Γ ⊢ x : T ⟹ Γ ⊢ &x : T*
+        SExpr::Deref(l) => match locate_expr(l, gtnv, ltnv)? {
+            Type::Ptr(t) => Ok(*t), // Γ ⊢ e : T* ⟹ Γ ⊢ *e : T
+            _ => Err(io::Error::other("type error")),
+        },
+        SExpr::Index { alias, idx } => match (ltnv.get(alias), locate_expr(idx, gtnv, ltnv)?) {
+            (Some(Type::Arr(t, _) | Type::Ptr(t)), _)
+                if matches!(**t, Type::Struct(_) | Type::Union(_)) =>
+            {
+                Err(io::Error::other("type error")) // todo: index arrays of structs by their stride
+            }
+            (Some(Type::Arr(t, _)), it) if integral(&it) => Ok(*t.clone()), // Γ ⊢ a : T[n], Γ ⊢ e : Int ⟹ Γ ⊢ a[e] : T
+            (Some(Type::Ptr(t)), it) if integral(&it) && **t != Type::Void => Ok(*t.clone()), // Γ ⊢ p : T*, Γ ⊢ e : Int ⟹ Γ ⊢ p[e] : T
+            _ => Err(io::Error::other("type error")),
+        },
+        SExpr::Member { base, field } => match locate_expr(base, gtnv, ltnv)? {
+            Type::Struct(s) | Type::Union(s) => gtnv // Γ ⊢ e : struct s, f : T ∈ s ⟹ Γ ⊢ e.f : T
+                .layouts
+                .field(&s, field)
+                .cloned()
+                .ok_or(io::Error::other("type error")),
+            _ => Err(io::Error::other("type error")),
+        },
+        SExpr::IncDec { lval, .. } => match locate_expr(lval, gtnv, ltnv)? {
+            t if integral(&t) => Ok(t), // Γ ⊢ x : Int ⟹ Γ ⊢ x++ : Int
+            _ => Err(io::Error::other("type error")),
         },
         SExpr::VarApp(alias) => ltnv // Γ ⊢ x: Γ(x)
             .get(alias)
             .cloned()
-            .ok_or(io::Error::new(io::ErrorKind::Other, "type error")),
+            .ok_or(io::Error::other("type error")),
         SExpr::FuncApp { alias, aps: ap } => {
             //    Γ ⊢ f : (T1-> T2)      Γ ⊢ e : T1, ... Γ ⊢ e : Tn
             // ------------------------------------------------------- FUNC_APP
@@ -153,27 +801,381 @@ pub fn type_expr(e: &SExpr, gtnv: &Tnv, ltnv: &HashMap<String, Type>) -> Result<
                 .fnv
                 .get(alias)
                 .cloned()
-                .ok_or(io::Error::new(io::ErrorKind::Other, "type error"))?;
+                .ok_or(io::Error::other("type error"))?;
+
+            if f.fp.len() != ap.len() && !(f.variadic && f.fp.len() < ap.len()) {
+                return Err(io::Error::other("type error"));
+            }
+            // args past a variadic function's params just have to be typed
+            ap[f.fp.len()..]
+                .iter()
+                .try_for_each(|ap| locate_expr(ap, gtnv, ltnv).map(|_| ()))?;
 
             f.fp.iter()
                 .zip(ap.iter())
                 .map(|(fpt, ap)| {
-                    type_expr(ap, gtnv, ltnv).and_then(|apt| {
+                    locate_expr(ap, gtnv, ltnv).and_then(|apt| {
                         //Γ ⊢ e : T1, ... Γ ⊢ e : Tn
-                        if apt == *fpt {
+                        if takes(fpt, ap, &apt, gtnv) {
                             Ok(())
                         } else {
-                            Err(io::Error::new(io::ErrorKind::Other, "type error"))
+                            Err(io::Error::other("type error"))
                         }
                     })
                 })
                 .collect::<Result<Vec<_>, _>>()
                 .map(|_| f.body) // Γ ⊢ f(e) : T2
         }
-        _ => Err(io::Error::new(io::ErrorKind::Other, "type error")),
     }
 }
 
+// the program with its implicit conversions made casts, so what runs it
+// needn't work out a value's width from its operands: the usual arithmetic
+// conversions of binary operators' and ?:'s operands, and the conversions
+// of assignment, to initializers, stores, args and returns. promoting a
+// char or short to int never changes its value, so it's left implicit. an
+// array passed, assigned or cast decays to (T *) &a, and pointer arithmetic's
+// pointers are cast to what they are, for it to step by T. the program has
+// to have been typed
+pub fn explicit(prg: &SPrg) -> SPrg {
+    let tnv = tnv(prg).expect("checked by the typer");
+    prg.iter()
+        .map(|def| {
+            let node = match &def.node {
+                SDef::FuncDef(fd) => {
+                    let mut ltnv = tnv.vnv.clone();
+                    ltnv.extend(fd.fps.iter().cloned());
+                    let mut rltnv = ltnv.clone();
+                    rltnv.insert(RESULT.to_owned(), fd.typ.clone());
+                    let contracts = fd
+                        .contracts
+                        .iter()
+                        .map(|c| match c.kind {
+                            SContractKind::Ensures => explicit_contract(c, &tnv, &rltnv),
+                            _ => explicit_contract(c, &tnv, &ltnv),
+                        })
+                        .collect();
+                    let body = explicit_stmts(&fd.body, &fd.typ, &tnv, &mut ltnv);
+                    SDef::FuncDef(SFuncDef {
+                        body,
+                        contracts,
+                        ..fd.clone()
+                    })
+                }
+                SDef::VarDef(vd) => SDef::VarDef(SVarDef {
+                    expr: Box::new(assigned(&vd.typ, &vd.expr, &tnv, &tnv.vnv)),
+                    ..vd.clone()
+                }),
+                node => node.clone(),
+            };
+            Spanned::new(node, def.span)
+        })
+        .collect()
+}
+
+// the scopes follow type_stmt's
+fn explicit_stmts(
+    stmts: &[Spanned<SStmt>],
+    ret: &Type,
+    tnv: &Tnv,
+    ltnv: &mut HashMap<String, Type>,
+) -> Vec<Spanned<SStmt>> {
+    stmts
+        .iter()
+        .map(|s| explicit_stmt(s, ret, tnv, ltnv))
+        .collect()
+}
+
+fn explicit_stmt(
+    s: &Spanned<SStmt>,
+    ret: &Type,
+    tnv: &Tnv,
+    ltnv: &mut HashMap<String, Type>,
+) -> Spanned<SStmt> {
+    let e =
+        |e: &Spanned<SExpr>, ltnv: &HashMap<String, Type>| Box::new(explicit_expr(e, tnv, ltnv));
+    let invariants = |cs: &[SContract], ltnv: &HashMap<String, Type>| {
+        cs.iter().map(|c| explicit_contract(c, tnv, ltnv)).collect()
+    };
+    let node = match &s.node {
+        SStmt::IfEls { cond, then, els } => SStmt::IfEls {
+            cond: e(cond, ltnv),
+            then: Box::new(explicit_stmt(then, ret, tnv, ltnv)),
+            els: els
+                .as_ref()
+                .map(|els| Box::new(explicit_stmt(els, ret, tnv, ltnv))),
+        },
+        SStmt::Switch { cond, arms } => {
+            let mut bltnv = ltnv.clone();
+            SStmt::Switch {
+                cond: e(cond, ltnv),
+                arms: arms
+                    .iter()
+                    .map(|a| SCase {
+                        label: a.label,
+                        body: explicit_stmts(&a.body, ret, tnv, &mut bltnv),
+                    })
+                    .collect(),
+            }
+        }
+        SStmt::While {
+            cond,
+            body,
+            invariants: cs,
+        } => SStmt::While {
+            cond: e(cond, ltnv),
+            body: explicit_stmts(body, ret, tnv, &mut ltnv.clone()),
+            invariants: invariants(cs, ltnv),
+        },
+        SStmt::DoWhile { body, cond } => SStmt::DoWhile {
+            body: explicit_stmts(body, ret, tnv, &mut ltnv.clone()),
+            cond: e(cond, ltnv),
+        },
+        SStmt::For {
+            asnmt,
+            cond,
+            update,
+            body,
+            invariants: cs,
+        } => {
+            let mut fltnv = ltnv.clone();
+            let asnmt = Box::new(explicit_stmt(asnmt, ret, tnv, &mut fltnv));
+            SStmt::For {
+                asnmt,
+                cond: e(cond, &fltnv),
+                update: Box::new(explicit_stmt(update, ret, tnv, &mut fltnv)),
+                invariants: invariants(cs, &fltnv),
+                body: explicit_stmts(body, ret, tnv, &mut fltnv),
+            }
+        }
+        SStmt::Block(body) => SStmt::Block(explicit_stmts(body, ret, tnv, &mut ltnv.clone())),
+        SStmt::Asnmt(vd) => {
+            let expr = Box::new(assigned(&vd.typ, &vd.expr, tnv, ltnv));
+            let bt = match (&vd.typ, typ(&vd.expr, tnv, ltnv)) {
+                (Type::Int, Type::Bool) => Type::Bool,
+                (dt, _) => dt.clone(),
+            };
+            ltnv.insert(vd.alias.clone(), bt);
+            SStmt::Asnmt(SVarDef { expr, ..vd.clone() })
+        }
+        SStmt::Decl(vd) => {
+            ltnv.insert(vd.alias.clone(), vd.typ.clone());
+            s.node.clone()
+        }
+        SStmt::Update(vu) => {
+            let mut vu = vu.clone();
+            let xt = ltnv.get(&vu.alias).cloned().expect("checked by the typer");
+            vu.expr = Box::new(assigned(&xt, &vu.expr, tnv, ltnv));
+            SStmt::Update(vu)
+        }
+        // a compound store's e is an operand, which converts along with
+        // lval, so it's left to the op
+        SStmt::Store(st) => {
+            let mut st = st.clone();
+            let lt = typ(&st.lval, tnv, ltnv);
+            st.expr = match st.op {
+                None => Box::new(assigned(&lt, &st.expr, tnv, ltnv)),
+                Some(_) => e(&st.expr, ltnv),
+            };
+            st.lval = e(&st.lval, ltnv);
+            SStmt::Store(st)
+        }
+        SStmt::Return(Some(r)) => SStmt::Return(Some(assigned(ret, r, tnv, ltnv))),
+        SStmt::Call(c) => SStmt::Call(explicit_expr(c, tnv, ltnv)),
+        SStmt::Assert(a) => SStmt::Assert(explicit_expr(a, tnv, ltnv)),
+        SStmt::Contract(c) => SStmt::Contract(explicit_contract(c, tnv, ltnv)),
+        SStmt::Return(None) | SStmt::Break | SStmt::Continue | SStmt::Label(_) | SStmt::Goto(_) => {
+            s.node.clone()
+        }
+    };
+    Spanned::new(node, s.span)
+}
+
+fn explicit_contract(c: &SContract, tnv: &Tnv, ltnv: &HashMap<String, Type>) -> SContract {
+    SContract {
+        kind: c.kind.clone(),
+        expr: explicit_expr(&c.expr, tnv, ltnv),
+    }
+}
+
+fn explicit_expr(e: &Spanned<SExpr>, tnv: &Tnv, ltnv: &HashMap<String, Type>) -> Spanned<SExpr> {
+    let x = |e: &Spanned<SExpr>| Box::new(explicit_expr(e, tnv, ltnv));
+    // both operands, in the type the usual arithmetic conversions give them
+    let both = |l: &Spanned<SExpr>, r: &Spanned<SExpr>| match convert(
+        typ(l, tnv, ltnv),
+        typ(r, tnv, ltnv),
+    ) {
+        Some(t) => (
+            Box::new(converted(&t, l, tnv, ltnv)),
+            Box::new(converted(&t, r, tnv, ltnv)),
+        ),
+        // a pointer's compared with, or chosen for, a null pointer constant
+        // as the pointer's type
+        None => match (typ(l, tnv, ltnv), typ(r, tnv, ltnv)) {
+            (t @ Type::Ptr(_), _) if null(&r.node) => (x(l), Box::new(assigned(&t, r, tnv, ltnv))),
+            (_, t @ Type::Ptr(_)) if null(&l.node) => (Box::new(assigned(&t, l, tnv, ltnv)), x(r)),
+            _ => (x(l), x(r)),
+        },
+    };
+    let node = match &e.node {
+        // pointer arithmetic steps by the pointer's elements, so its
+        // operands are marked with what they point to
+        SExpr::BinE {
+            op: op @ (SBinOp::Add | SBinOp::Sub),
+            l,
+            r,
+        } if pointer(&typ(l, tnv, ltnv)).is_some() || pointer(&typ(r, tnv, ltnv)).is_some() => {
+            SExpr::BinE {
+                op: op.clone(),
+                l: Box::new(pointed(l, tnv, ltnv)),
+                r: Box::new(pointed(r, tnv, ltnv)),
+            }
+        }
+        SExpr::BinE { op, l, r } => {
+            let (l, r) = both(l, r);
+            SExpr::BinE { op: op.clone(), l, r }
+        }
+        SExpr::RelE { op, l, r } => {
+            let (l, r) = both(l, r);
+            SExpr::RelE { op: op.clone(), l, r }
+        }
+        // a shift has its left operand's type, whatever the count's
+        SExpr::BitE {
+            op: op @ (SBitOp::Shl | SBitOp::Shr),
+            l,
+            r,
+        } => SExpr::BitE {
+            op: op.clone(),
+            l: x(l),
+            r: x(r),
+        },
+        SExpr::BitE { op, l, r } => {
+            let (l, r) = both(l, r);
+            SExpr::BitE { op: op.clone(), l, r }
+        }
+        SExpr::CondE { cond, then, els } => {
+            let (then, els) = match promote(typ(then, tnv, ltnv)) == promote(typ(els, tnv, ltnv)) {
+                true => (x(then), x(els)),
+                false => both(then, els),
+            };
+            SExpr::CondE {
+                cond: x(cond),
+                then,
+                els,
+            }
+        }
+        SExpr::FuncApp { alias, aps } => {
+            let fp = tnv.fnv.get(alias).map_or(&[][..], |f| &f.fp[..]);
+            let aps = aps
+                .iter()
+                .enumerate()
+                .map(|(i, ap)| match fp.get(i) {
+                    Some(fpt) => assigned(fpt, ap, tnv, ltnv),
+                    None => explicit_expr(ap, tnv, ltnv), // past a variadic function's params
+                })
+                .collect();
+            SExpr::FuncApp {
+                alias: alias.clone(),
+                aps,
+            }
+        }
+        SExpr::LogE { op, l, r } => SExpr::LogE {
+            op: op.clone(),
+            l: x(l),
+            r: x(r),
+        },
+        SExpr::UnaryE { op, l } => SExpr::UnaryE {
+            op: op.clone(),
+            l: x(l),
+        },
+        SExpr::Index { alias, idx } => SExpr::Index {
+            alias: alias.clone(),
+            idx: x(idx),
+        },
+        SExpr::Member { base, field } => SExpr::Member {
+            base: x(base),
+            field: field.clone(),
+        },
+        SExpr::AddrOf(l) => SExpr::AddrOf(x(l)),
+        SExpr::Deref(l) => SExpr::Deref(x(l)),
+        SExpr::IncDec { op, lval } => SExpr::IncDec {
+            op: op.clone(),
+            lval: x(lval),
+        },
+        SExpr::AllocArray(t, n) => SExpr::AllocArray(t.clone(), x(n)),
+        SExpr::Cast(t, l) => SExpr::Cast(t.clone(), Box::new(decayed(l, tnv, ltnv))),
+        SExpr::Int(_)
+        | SExpr::TypedInt { .. }
+        | SExpr::Float { .. }
+        | SExpr::Bool(_)
+        | SExpr::Str(_)
+        | SExpr::VarApp(_)
+        | SExpr::SizeOf(_) // never evaluated
+        | SExpr::SizeOfType(_)
+        | SExpr::Alloc(_) => e.node.clone(),
+    };
+    Spanned::new(node, e.span)
+}
+
+// e, cast to t when it's a number of another type, as assigning converts
+// it, or a null pointer constant, or decayed when it's an array
+fn assigned(
+    t: &Type,
+    e: &Spanned<SExpr>,
+    tnv: &Tnv,
+    ltnv: &HashMap<String, Type>,
+) -> Spanned<SExpr> {
+    let et = typ(e, tnv, ltnv);
+    match *t != et && (arith(t) && arith(&et) || matches!(t, Type::Ptr(_)) && null(&e.node)) {
+        true => {
+            let e = explicit_expr(e, tnv, ltnv);
+            Spanned::new(SExpr::Cast(t.clone(), Box::new(e.clone())), e.span)
+        }
+        false => decayed(e, tnv, ltnv),
+    }
+}
+
+// an array as (T *) &a, its first element's address. what isn't one is
+// left as it is
+fn decayed(e: &Spanned<SExpr>, tnv: &Tnv, ltnv: &HashMap<String, Type>) -> Spanned<SExpr> {
+    let x = explicit_expr(e, tnv, ltnv);
+    match typ(e, tnv, ltnv) {
+        t @ Type::Arr(..) => {
+            let addr = Spanned::new(SExpr::AddrOf(Box::new(x)), e.span);
+            Spanned::new(SExpr::Cast(decay(t), Box::new(addr)), e.span)
+        }
+        _ => x,
+    }
+}
+
+// a pointer arithmetic operand: a pointer as (T *) p, for what runs it to
+// step by T, and an integer as it is
+fn pointed(e: &Spanned<SExpr>, tnv: &Tnv, ltnv: &HashMap<String, Type>) -> Spanned<SExpr> {
+    let x = decayed(e, tnv, ltnv);
+    match typ(e, tnv, ltnv) {
+        _ if matches!(x.node, SExpr::Cast(Type::Ptr(_), _)) => x,
+        Type::Ptr(t) => Spanned::new(SExpr::Cast(Type::Ptr(t), Box::new(x)), e.span),
+        _ => x,
+    }
+}
+
+// e, cast to t by the usual arithmetic conversions, unless it's only promoted
+fn converted(
+    t: &Type,
+    e: &Spanned<SExpr>,
+    tnv: &Tnv,
+    ltnv: &HashMap<String, Type>,
+) -> Spanned<SExpr> {
+    match promote(typ(e, tnv, ltnv)) == *t {
+        true => explicit_expr(e, tnv, ltnv),
+        false => assigned(t, e, tnv, ltnv),
+    }
+}
+
+fn typ(e: &Spanned<SExpr>, tnv: &Tnv, ltnv: &HashMap<String, Type>) -> Type {
+    type_expr(e, tnv, ltnv).expect("checked by the typer")
+}
+
 #[cfg(test)]
 mod test_arith {
     use crate::lexer;
@@ -184,13 +1186,9 @@ mod test_arith {
 
     #[test]
     fn lit() {
-        let chars = fs::read(format!("{TEST_DIR}/lit.c0"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let src = fs::read_to_string(format!("{TEST_DIR}/lit.c0")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = parser_ast::parse_prg(&tokens).unwrap();
         let typ = super::type_prg(&tree).unwrap();
         insta::assert_yaml_snapshot!(typ, @r###"
@@ -198,6 +1196,103 @@ mod test_arith {
         Int
         "###);
     }
+
+    #[test]
+    fn chain() {
+        // each operand's typed once, so a long sum takes as long as it's long
+        let src = format!("int main() {{ int x = 1; return {}1; }}", "x - ".repeat(60));
+        let tree = parser_ast::parse_prg(&lexer::lex(&src).unwrap()).unwrap();
+        assert_eq!(super::type_prg(&tree).unwrap(), crate::Type::Int);
+        let _ = super::explicit(&tree);
+    }
+
+    #[test]
+    fn conversions() {
+        use crate::Type;
+        for (lt, rt, expected) in [
+            (Type::Char, Type::UShort, Some(Type::Int)),
+            (Type::Int, Type::UInt, Some(Type::UInt)),
+            (Type::Long, Type::Int, Some(Type::Long)),
+            (Type::Long, Type::UInt, Some(Type::ULong)),
+            (Type::UChar, Type::ULong, Some(Type::ULong)),
+            (Type::ULong, Type::Float, Some(Type::Float)),
+            (Type::Float, Type::Double, Some(Type::Double)),
+            (Type::Int, Type::Bool, Some(Type::Int)),
+            (Type::Int, Type::Ptr(Box::new(Type::Int)), None),
+        ] {
+            assert_eq!(
+                super::convert(lt.clone(), rt.clone()),
+                expected,
+                "{lt:?} {rt:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn explicit() {
+        let src = "long g = 1;
+float half(float x) { return x / 2; }
+int main() {
+  char c = 100;
+  short s = c + c;
+  unsigned int u = s;
+  float f = half(s) + u;
+  long l = c * g;
+  return f > l ? l : c;
+}";
+        let tokens = lexer::lex(src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        super::type_prg(&tree).unwrap();
+        insta::assert_snapshot!(crate::pretty::prg(&super::explicit(&tree)), @r###"
+        long g = (long) 1;
+
+        float half(float x) {
+            return x / (float) 2;
+        }
+
+        int main() {
+            char c = (char) 100;
+            short s = (short) (c + c);
+            unsigned u = (unsigned) s;
+            float f = half((float) s) + (float) u;
+            long l = (long) c * g;
+            return (int) (f > (float) l ? l : (long) c);
+        }
+        "###);
+    }
+
+    // but for pointer arithmetic, which only steps by more than a cell once
+    // the typer's marked what its pointers point to
+    #[test]
+    fn explicit_preserves() {
+        use crate::{evaluator, IntModel};
+        for dir in ["arith", "bindings", "control"] {
+            let dir = format!("tests/fixtures/snap/shared/{dir}");
+            for entry in fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.to_string_lossy().contains("ptr_arith") {
+                    continue;
+                }
+                let src = fs::read_to_string(&path).unwrap();
+                let Ok(tokens) = lexer::lex(&src) else {
+                    continue;
+                };
+                let Ok(tree) = parser_ast::parse_prg(&tokens) else {
+                    continue;
+                };
+                if super::type_prg(&tree).is_err() {
+                    continue;
+                }
+                let implicit = evaluator::eval_prg(&tree, IntModel::default());
+                let explicit = evaluator::eval_prg(&super::explicit(&tree), IntModel::default());
+                assert_eq!(
+                    implicit.map_err(|e| e.to_string()),
+                    explicit.map_err(|e| e.to_string()),
+                    "{path:?}"
+                );
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -210,13 +1305,9 @@ mod test_control {
 
     #[test]
     fn ifels() {
-        let chars = fs::read(format!("{TEST_DIR}/if.c0"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let src = fs::read_to_string(format!("{TEST_DIR}/if.c0")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = parser_ast::parse_prg(&tokens).unwrap();
         let typ = super::type_prg(&tree).unwrap();
         insta::assert_yaml_snapshot!(typ, @r###"
@@ -227,27 +1318,19 @@ mod test_control {
 
     #[test]
     fn ifels_wrong() {
-        let chars = fs::read(format!("{TEST_DIR}/if2.c0"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let src = fs::read_to_string(format!("{TEST_DIR}/if2.c0")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = parser_ast::parse_prg(&tokens).unwrap();
-        let typ = super::type_prg(&tree);
+        let typ = super::type_prg_c0(&tree);
         assert!(typ.is_err())
     }
 
     #[test]
     fn ifels_multi_side_effect() {
-        let chars = fs::read(format!("{TEST_DIR}/if4.c0"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let src = fs::read_to_string(format!("{TEST_DIR}/if4.c0")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = parser_ast::parse_prg(&tokens).unwrap();
         let typ = super::type_prg(&tree).unwrap();
         insta::assert_yaml_snapshot!(typ, @r###"
@@ -256,15 +1339,155 @@ mod test_control {
         "###);
     }
 
+    #[test]
+    fn ifels_mixed_arms() {
+        let typed = |body: &str| {
+            let src = format!(
+                "struct s {{ int x; }};\nchar c() {{ return 1; }}\nint f(int x) {{ {body} }}\nint main() {{ return f(1); }}"
+            );
+            let tokens = lexer::lex(&src).unwrap();
+            let tree = parser_ast::parse_prg(&tokens).unwrap();
+            super::type_prg(&tree).map(|_| ())
+        };
+        for ok in [
+            "if (x > 0) { return 1; } else { x = 2; } return x;",
+            "if (x) { x = 1; } else { x = 2; } return x;",
+            "if (x) { return c(); } else { return x; }",
+            "if (x == 1) { return x; } else { return c(); }",
+            "int *p = &x; if (p) { return 1; } return 0;",
+        ] {
+            assert!(typed(ok).is_ok(), "{ok}");
+        }
+        for wrong in [
+            "struct s v; if (v) { return 1; } return 0;",
+            "int *p = &x; if (x) { return p; } else { return x; }",
+        ] {
+            assert_eq!(
+                typed(wrong).unwrap_err().to_string(),
+                "type error",
+                "{wrong}"
+            );
+        }
+    }
+
     #[test]
     fn ifels_multi_side_effect_wrong() {
-        let chars = fs::read(format!("{TEST_DIR}/if5.c0"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let src = fs::read_to_string(format!("{TEST_DIR}/if5.c0")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let typ = super::type_prg_c0(&tree);
+        assert!(typ.is_err())
+    }
+
+    // comparisons, ! and the logical ops give bools, which are the ints 0
+    // and 1. their operands, like an if's cond, can be any scalar
+    #[test]
+    fn bool_int() {
+        let parse = |body: &str| {
+            let src = format!(
+                "struct s {{ int x; }};\nint f(int x, int *p) {{ {body} }}\nint main() {{ int n = 1; return f(1, &n); }}"
+            );
+            let tokens = lexer::lex(&src).unwrap();
+            parser_ast::parse_prg(&tokens).unwrap()
+        };
+        let typed = |body: &str| super::type_prg(&parse(body)).map(|_| ());
+        for ok in [
+            "return 1 < 2;",
+            "int y = x == 1; return y + 1;",
+            "int y = 0; y = x > 0; return y;",
+            "return (x < 2) + (x > 0) * 2;",
+            "return !x;",
+            "return x && 1;",
+            "return x || p;",
+            "return !p;",
+            "return x ? 1 : 2;",
+            "char c = x != 0; return c;",
+        ] {
+            assert!(typed(ok).is_ok(), "{ok}");
+        }
+        for wrong in [
+            "struct s v; return !v;",
+            "struct s v; return v && 1;",
+            "struct s v; return v ? 1 : 2;",
+            "int *q = x < 1; return 0;",
+        ] {
+            assert_eq!(
+                typed(wrong).unwrap_err().to_string(),
+                "type error",
+                "{wrong}"
+            );
+        }
+
+        // c0's bools aren't ints
+        for wrong in [
+            "return 1 < 2;",
+            "int y = x == 1; return y + 1;",
+            "return (x < 2) + 1;",
+            "return f(x > 0, p);",
+        ] {
+            assert!(super::type_prg_c0(&parse(wrong)).is_err(), "{wrong}");
+        }
+        assert!(super::type_prg_c0(&parse("return !(x < 2) ? 1 : 2;")).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod test_control_c89 {
+    use crate::lexer;
+    use crate::parser_ast;
+    use std::fs;
+
+    const TEST_DIR: &str = "tests/fixtures/snap/shared/control";
+
+    #[test]
+    fn while_lt() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/while_lt.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let typ = super::type_prg(&tree).unwrap();
+        insta::assert_yaml_snapshot!(typ, @r###"
+        ---
+        Int
+        "###);
+    }
+
+    #[test]
+    fn lt_true() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/lt_true.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let typ = super::type_prg(&tree).unwrap();
+        assert_eq!(typ, crate::Type::Int) // a comparison's bool is the int 0 or 1
+    }
+
+    #[test]
+    fn switch_dup() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/switch_dup.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let typ = super::type_prg(&tree);
+        assert!(typ.is_err())
+    }
+
+    #[test]
+    fn break_outside() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/break_outside.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let typ = super::type_prg(&tree);
+        assert!(typ.is_err())
+    }
+
+    #[test]
+    fn goto_undefined() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/goto_undefined.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
         let tree = parser_ast::parse_prg(&tokens).unwrap();
         let typ = super::type_prg(&tree);
         assert!(typ.is_err())
@@ -281,13 +1504,9 @@ mod test_bindings {
 
     #[test]
     fn func() {
-        let chars = fs::read(format!("{TEST_DIR}/func.c0"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let src = fs::read_to_string(format!("{TEST_DIR}/func.c0")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = parser_ast::parse_prg(&tokens).unwrap();
         let typ = super::type_prg(&tree).unwrap();
         insta::assert_yaml_snapshot!(typ, @r###"
@@ -298,28 +1517,44 @@ mod test_bindings {
 
     #[test]
     fn func2() {
-        let chars = fs::read(format!("{TEST_DIR}/func2.c0"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let src = fs::read_to_string(format!("{TEST_DIR}/func2.c0")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let typ = super::type_prg(&tree).unwrap();
+        assert_eq!(typ, crate::Type::Int) // true is 1
+    }
+
+    #[test]
+    fn func4() {
+        let src = fs::read_to_string(format!("{TEST_DIR}/func4.c0")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
         let tree = parser_ast::parse_prg(&tokens).unwrap();
         let typ = super::type_prg(&tree);
         assert!(typ.is_err())
     }
 
+    #[test]
+    fn static_scope() {
+        const TEST_DIR: &str = "tests/fixtures/snap/shared/bindings";
+        let src = fs::read_to_string(format!("{TEST_DIR}/static_scope.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let typ = super::type_prg(&tree).unwrap();
+        insta::assert_yaml_snapshot!(typ, @r###"
+        ---
+        Int
+        "###);
+    }
+
     #[test]
     fn asnmt_expr() {
         const TEST_DIR: &str = "tests/fixtures/snap/shared/bindings";
-        let chars = fs::read(format!("{TEST_DIR}/asnmt_expr.c"))
-            .expect("file dne")
-            .iter()
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
+        let src = fs::read_to_string(format!("{TEST_DIR}/asnmt_expr.c")).expect("file dne");
 
-        let tokens = lexer::lex(&chars).unwrap();
+        let tokens = lexer::lex(&src).unwrap();
         let tree = parser_ast::parse_prg(&tokens).unwrap();
         let typ = super::type_prg(&tree).unwrap();
         insta::assert_yaml_snapshot!(typ, @r###"
@@ -327,4 +1562,172 @@ mod test_bindings {
         Int
         "###);
     }
+
+    #[test]
+    fn ptr() {
+        const TEST_DIR: &str = "tests/fixtures/snap/shared/bindings";
+        let src = fs::read_to_string(format!("{TEST_DIR}/ptr.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let typ = super::type_prg(&tree).unwrap();
+        insta::assert_yaml_snapshot!(typ, @r###"
+        ---
+        Int
+        "###);
+    }
+
+    #[test]
+    fn casts() {
+        let typed = |body: &str| {
+            let src = format!("struct s {{ int x; }};\nint main() {{ {body} return 0; }}");
+            let tokens = lexer::lex(&src).unwrap();
+            let tree = parser_ast::parse_prg(&tokens).unwrap();
+            super::type_prg(&tree).map(|_| ())
+        };
+        for ok in [
+            "char c = (char) 300;",
+            "int n = (int) 2.5f;",
+            "int *p = (int *) 0;",
+            "int n = 0; char *p = (char *) &n;",
+            "int n = 0; long m = (long) &n;",
+            "int b = (int) (1 < 2);",
+        ] {
+            assert!(typed(ok).is_ok(), "{ok}");
+        }
+        for wrong in [
+            "struct s v; int n = (int) v;",
+            "int n = 0; struct s v = (struct s) n;",
+            "float f = 1.5f; int *p = (int *) f;",
+            "int *p = (int *) 0; float f = (float) p;",
+        ] {
+            assert_eq!(
+                typed(wrong).unwrap_err().to_string(),
+                "type error",
+                "{wrong}"
+            );
+        }
+    }
+
+    #[test]
+    fn ptr_arith() {
+        let typed = |body: &str| {
+            let src = format!(
+                "struct s {{ int x; }};\nint f(int *p) {{ return 0; }}\nint main() {{ int a[4]; int *p = a; {body} return 0; }}"
+            );
+            let tokens = lexer::lex(&src).unwrap();
+            let tree = parser_ast::parse_prg(&tokens).unwrap();
+            super::type_prg(&tree).map(|_| ())
+        };
+        for ok in [
+            "int *q = p + 1;",
+            "int *q = 2 + a;",
+            "int *q = a - 1;",
+            "long n = (p + 3) - a;",
+            "int n = f(a);",
+            "struct s v[2]; struct s *w = v + 1;",
+        ] {
+            assert!(typed(ok).is_ok(), "{ok}");
+        }
+        for wrong in [
+            "int *q = p + p;",
+            "int *q = 1 - p;",
+            "int *q = p + 1.5f;",
+            "char *c = (char *) p; long n = p - c;",
+            "int *q = p * 2;",
+            "char c[2]; int n = f(c);",
+        ] {
+            assert_eq!(
+                typed(wrong).unwrap_err().to_string(),
+                "type error",
+                "{wrong}"
+            );
+        }
+    }
+
+    #[test]
+    fn ptr_mismatch() {
+        const TEST_DIR: &str = "tests/fixtures/snap/shared/bindings";
+        let src = fs::read_to_string(format!("{TEST_DIR}/ptr_mismatch.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let typ = super::type_prg(&tree);
+        assert!(typ.is_err())
+    }
+
+    #[test]
+    fn null() {
+        let typed = |body: &str| {
+            let src = format!(
+                "int *f(int *p) {{ {body} }}\nint g(char *s) {{ return 0; }}\nint main() {{ return 0; }}"
+            );
+            let tokens = lexer::lex(&src).unwrap();
+            let tree = parser_ast::parse_prg(&tokens).unwrap();
+            super::type_prg(&tree).map(|_| ())
+        };
+        for ok in [
+            "int *q = 0; return q;",
+            "int *q = NULL; q = 0; *(&q) = NULL; return p;",
+            "return 0;",
+            "return NULL;",
+            "return p == 0 ? p : NULL;",
+            "int n = NULL == p; return p != NULL ? 0 : p;",
+            "int n = g(0); return p;",
+        ] {
+            assert!(typed(ok).is_ok(), "{ok}");
+        }
+        for wrong in [
+            "int *q = 1; return q;",
+            "int n = 0; int *q = n; return q;",
+            "return 1;",
+            "int n = g(1); return p;",
+            "return p == 1 ? p : 0;",
+        ] {
+            assert_eq!(
+                typed(wrong).unwrap_err().to_string(),
+                "type error",
+                "{wrong}"
+            );
+        }
+    }
+
+    #[test]
+    fn array() {
+        const TEST_DIR: &str = "tests/fixtures/snap/shared/bindings";
+        let src = fs::read_to_string(format!("{TEST_DIR}/array.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let typ = super::type_prg(&tree).unwrap();
+        insta::assert_yaml_snapshot!(typ, @r###"
+        ---
+        Int
+        "###);
+    }
+
+    #[test]
+    fn chars() {
+        const TEST_DIR: &str = "tests/fixtures/snap/shared/bindings";
+        let src = fs::read_to_string(format!("{TEST_DIR}/chars.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let typ = super::type_prg(&tree).unwrap();
+        insta::assert_yaml_snapshot!(typ, @r###"
+        ---
+        Int
+        "###);
+    }
+
+    #[test]
+    fn enums_dup() {
+        const TEST_DIR: &str = "tests/fixtures/snap/shared/bindings";
+        let src = fs::read_to_string(format!("{TEST_DIR}/enums_dup.c")).expect("file dne");
+
+        let tokens = lexer::lex(&src).unwrap();
+        let tree = parser_ast::parse_prg(&tokens).unwrap();
+        let typ = super::type_prg(&tree);
+        assert!(typ.is_err())
+    }
 }
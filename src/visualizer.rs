@@ -1,4 +1,8 @@
-use crate::Node;
+use crate::{
+    evaluator, ir::Cfg, pretty, Label, Node, SContract, SDef, SExpr, SPrg, SStmt, Spanned, TQuad,
+    Type, Vnv,
+};
+use std::iter;
 use std::rc::Rc;
 
 pub fn gen_dot(_start: Rc<dyn Node>) -> String {
@@ -21,3 +25,664 @@ fn gen_edges(_d: &mut str) {}
 fn _graph_vertices(_start: Rc<dyn Node>) -> Vec<Rc<dyn Node>> {
     todo!()
 }
+
+// the interpreter's environment as a graph of records: one for the
+// function environment, one for the variable environment, and one for each
+// block alloc handed out. arrays and structs are a field per cell, and a
+// pointer is an edge from its cell to the cell it points to. ports are
+// named by address, so an edge can point anywhere inside a block
+pub fn gen_vnv_dot(nv: &Vnv) -> String {
+    let mut fnv = nv.fnv.iter().collect::<Vec<_>>();
+    fnv.sort_by(|a, b| a.0.cmp(b.0));
+    let mut vnv = nv.vnv.iter().collect::<Vec<_>>();
+    vnv.sort_by(|a, b| a.0.cmp(b.0));
+    let types = nv.types.borrow();
+    let typ = |addr: i32| types.get(&addr).cloned().unwrap_or(Type::Int);
+    let mut heap = types
+        .keys()
+        .copied()
+        .filter(|addr| (evaluator::HEAP..evaluator::RODATA).contains(addr))
+        .collect::<Vec<_>>();
+    heap.sort();
+
+    // each binding's and block's cells, by the node they're fields of
+    let cells = |addr: i32| {
+        let ts = nv.layouts.cell_types(&typ(addr)).unwrap_or_default();
+        (addr..).zip(ts).collect::<Vec<_>>()
+    };
+    let blocks = vnv
+        .iter()
+        .map(|(_, addr)| ("vnv".to_owned(), cells(**addr)))
+        .chain(heap.iter().map(|addr| (format!("h{addr}"), cells(*addr))))
+        .collect::<Vec<_>>();
+    let node = |target: i32| {
+        blocks
+            .iter()
+            .find(|(_, cs)| cs.iter().any(|(a, _)| *a == target))
+            .map(|(n, _)| n.as_str())
+    };
+
+    let (mut edges, mut strs) = (vec![], vec![]);
+    let mut field = |from: &str, addr: i32, t: &Type| {
+        let val = evaluator::load(nv, addr).unwrap_or_default();
+        let text = match (t, node(val)) {
+            (Type::Ptr(_), _) if val == 0 => "NULL".to_owned(),
+            (Type::Ptr(_), Some(to)) => {
+                edges.push(format!("\t{from}:m{addr} -> {to}:m{val};\n"));
+                "•".to_owned()
+            }
+            (Type::Ptr(_), None) if val >= evaluator::RODATA => {
+                let s = (val..)
+                    .map_while(|a| evaluator::load(nv, a).ok().filter(|c| *c != 0))
+                    .map(|c| c as u8 as char)
+                    .collect::<String>();
+                strs.push(format!(
+                    "\ts{val} [label=\"{}\"];\n",
+                    record(&format!("{s:?}"))
+                ));
+                edges.push(format!("\t{from}:m{addr} -> s{val};\n"));
+                "•".to_owned()
+            }
+            (Type::Ptr(_), None) => format!("{val:#x}"), // dangling
+            _ => evaluator::show_cell(nv, addr).unwrap_or_default(),
+        };
+        format!("<m{addr}> {}", record(&text))
+    };
+
+    let fields = |name: &str, fs: Vec<String>| {
+        iter::once(name.to_owned())
+            .chain(fs)
+            .collect::<Vec<_>>()
+            .join("|")
+    };
+
+    let mut dot = String::new();
+    dot.push_str("digraph vnv {\n");
+    dot.push_str("\trankdir=LR;\n");
+    dot.push_str("\tnode [shape=record];\n");
+    dot.push_str(&format!(
+        "\tfnv [label=\"{{{}}}\"];\n",
+        fields(
+            "fnv",
+            fnv.iter()
+                .map(|(alias, l)| format!(
+                    "<{alias}> {alias}({})",
+                    l.fp.iter()
+                        .map(|(fp, _)| fp.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+                .collect()
+        )
+    ));
+    let bindings = vnv
+        .iter()
+        .map(|(alias, addr)| match typ(**addr) {
+            Type::Arr(..) | Type::Struct(_) | Type::Union(_) => {
+                let cs = cells(**addr).into_iter().map(|(a, t)| field("vnv", a, &t));
+                format!("{{{}}}", fields(alias, cs.collect()))
+            }
+            t => field("vnv", **addr, &t).replacen("> ", &format!("> {alias} = "), 1),
+        })
+        .collect();
+    dot.push_str(&format!(
+        "\tvnv [label=\"{{{}}}\"];\n",
+        fields("vnv", bindings)
+    ));
+    for addr in &heap {
+        let name = record(&pretty::typ(&typ(*addr)));
+        let cs = cells(*addr)
+            .into_iter()
+            .map(|(a, t)| field(&format!("h{addr}"), a, &t));
+        dot.push_str(&format!(
+            "\th{addr} [label=\"{{{}}}\"];\n",
+            fields(&name, cs.collect())
+        ));
+    }
+    dot.extend(strs);
+    dot.extend(edges);
+    dot.push_str("}\n");
+    dot
+}
+
+// a record field's text, its braces, bars, angle brackets and quotes
+// escaped
+fn record(s: &str) -> String {
+    s.chars().fold(String::new(), |mut out, c| {
+        if "{}|<>\"\\".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+        out
+    })
+}
+
+// a graph's nodes and edges as dot lines, numbered in the order they're
+// added
+#[derive(Default)]
+struct Graph {
+    lines: Vec<String>,
+    n: usize,
+}
+
+impl Graph {
+    fn node(&mut self, label: &str) -> usize {
+        self.n += 1;
+        self.lines
+            .push(format!("\tn{} [label=\"{}\"];", self.n, escape(label)));
+        self.n
+    }
+
+    fn edge(&mut self, from: usize, to: usize, label: &str) {
+        match label {
+            "" => self.lines.push(format!("\tn{from} -> n{to};")),
+            l => self
+                .lines
+                .push(format!("\tn{from} -> n{to} [label=\"{l}\"];")),
+        }
+    }
+
+    // a node under parent, by the field of parent's it is
+    fn child(&mut self, parent: usize, field: &str, label: &str) -> usize {
+        let n = self.node(label);
+        self.edge(parent, n, field);
+        n
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// the parse tree for --emit ast-dot, top down. a node is its variant, with
+// what isn't a subtree, like names, types and ops, in its label. edges are
+// the fields they're in, and a list's items are in order left to right
+pub fn ast_dot(prg: &SPrg) -> String {
+    let mut g = Graph::default();
+    let root = g.node("prg");
+    for d in prg {
+        def(&mut g, root, &d.node);
+    }
+    let mut dot = String::new();
+    dot.push_str("digraph ast {\n");
+    dot.push_str("\tordering=\"out\";\n"); // children in the order they're in
+    dot.push_str("\tnode [shape=box];\n");
+    for line in g.lines {
+        dot.push_str(&line);
+        dot.push('\n');
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn def(g: &mut Graph, parent: usize, d: &SDef) {
+    let fields = |fs: &[(String, Type)]| {
+        fs.iter()
+            .map(|(alias, t)| format!("{} {alias}", pretty::typ(t)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match d {
+        SDef::FuncDef(fd) => {
+            let label = format!(
+                "FuncDef {} {}({})",
+                pretty::typ(&fd.typ),
+                fd.alias,
+                fields(&fd.fps)
+            );
+            let n = g.child(parent, "", &label);
+            contracts(g, n, &fd.contracts);
+            stmts(g, n, "body", &fd.body);
+        }
+        SDef::FuncDecl(fd) => {
+            let mut fps = fd.fps.iter().map(pretty::typ).collect::<Vec<_>>();
+            if fd.variadic {
+                fps.push("...".to_owned());
+            }
+            let label = format!(
+                "FuncDecl {} {}({})",
+                pretty::typ(&fd.typ),
+                fd.alias,
+                fps.join(", ")
+            );
+            g.child(parent, "", &label);
+        }
+        SDef::VarDef(vd) => {
+            let n = g.child(
+                parent,
+                "",
+                &format!("VarDef {} {}", pretty::typ(&vd.typ), vd.alias),
+            );
+            expr(g, n, "expr", &vd.expr);
+        }
+        SDef::VarDecl(vd) => {
+            g.child(
+                parent,
+                "",
+                &format!("VarDecl {} {}", pretty::typ(&vd.typ), vd.alias),
+            );
+        }
+        SDef::StructDef(sd) => {
+            g.child(
+                parent,
+                "",
+                &format!("StructDef {} {{{}}}", sd.alias, fields(&sd.fields)),
+            );
+        }
+        SDef::UnionDef(sd) => {
+            g.child(
+                parent,
+                "",
+                &format!("UnionDef {} {{{}}}", sd.alias, fields(&sd.fields)),
+            );
+        }
+        SDef::EnumDef(ed) => {
+            let es = ed.enumerators.iter().map(|(e, v)| format!("{e} = {v}"));
+            let label = format!(
+                "EnumDef {} {{{}}}",
+                ed.alias.as_deref().unwrap_or(""),
+                es.collect::<Vec<_>>().join(", ")
+            );
+            g.child(parent, "", &label);
+        }
+    }
+}
+
+fn contracts(g: &mut Graph, parent: usize, cs: &[SContract]) {
+    for c in cs {
+        let n = g.child(parent, "contract", &format!("{:?}", c.kind));
+        expr(g, n, "expr", &c.expr);
+    }
+}
+
+fn stmts(g: &mut Graph, parent: usize, field: &str, ss: &[Spanned<SStmt>]) {
+    for s in ss {
+        stmt(g, parent, field, s);
+    }
+}
+
+fn stmt(g: &mut Graph, parent: usize, field: &str, s: &Spanned<SStmt>) {
+    match &s.node {
+        SStmt::IfEls { cond, then, els } => {
+            let n = g.child(parent, field, "IfEls");
+            expr(g, n, "cond", cond);
+            stmt(g, n, "then", then);
+            if let Some(els) = els {
+                stmt(g, n, "els", els);
+            }
+        }
+        SStmt::Switch { cond, arms } => {
+            let n = g.child(parent, field, "Switch");
+            expr(g, n, "cond", cond);
+            for arm in arms {
+                let label = arm
+                    .label
+                    .map_or("default".to_owned(), |l| format!("case {l}"));
+                let a = g.child(n, "arm", &label);
+                stmts(g, a, "body", &arm.body);
+            }
+        }
+        SStmt::Label(l) => {
+            g.child(parent, field, &format!("Label {l}"));
+        }
+        SStmt::Goto(l) => {
+            g.child(parent, field, &format!("Goto {l}"));
+        }
+        SStmt::Block(body) => {
+            let n = g.child(parent, field, "Block");
+            stmts(g, n, "body", body);
+        }
+        SStmt::While {
+            cond,
+            body,
+            invariants,
+        } => {
+            let n = g.child(parent, field, "While");
+            contracts(g, n, invariants);
+            expr(g, n, "cond", cond);
+            stmts(g, n, "body", body);
+        }
+        SStmt::DoWhile { body, cond } => {
+            let n = g.child(parent, field, "DoWhile");
+            stmts(g, n, "body", body);
+            expr(g, n, "cond", cond);
+        }
+        SStmt::For {
+            asnmt,
+            cond,
+            update,
+            body,
+            invariants,
+        } => {
+            let n = g.child(parent, field, "For");
+            contracts(g, n, invariants);
+            stmt(g, n, "asnmt", asnmt);
+            expr(g, n, "cond", cond);
+            stmt(g, n, "update", update);
+            stmts(g, n, "body", body);
+        }
+        SStmt::Break => {
+            g.child(parent, field, "Break");
+        }
+        SStmt::Continue => {
+            g.child(parent, field, "Continue");
+        }
+        SStmt::Asnmt(vd) => {
+            let n = g.child(
+                parent,
+                field,
+                &format!("Asnmt {} {}", pretty::typ(&vd.typ), vd.alias),
+            );
+            expr(g, n, "expr", &vd.expr);
+        }
+        SStmt::Decl(vd) => {
+            g.child(
+                parent,
+                field,
+                &format!("Decl {} {}", pretty::typ(&vd.typ), vd.alias),
+            );
+        }
+        SStmt::Update(vu) => {
+            let n = g.child(parent, field, &format!("Update {}", vu.alias));
+            expr(g, n, "expr", &vu.expr);
+        }
+        SStmt::Store(st) => {
+            let n = match &st.op {
+                None => g.child(parent, field, "Store"),
+                Some(op) => g.child(parent, field, &format!("Store {op:?}")),
+            };
+            expr(g, n, "lval", &st.lval);
+            expr(g, n, "expr", &st.expr);
+        }
+        SStmt::Return(e) => {
+            let n = g.child(parent, field, "Return");
+            if let Some(e) = e {
+                expr(g, n, "expr", e);
+            }
+        }
+        SStmt::Call(e) => {
+            let n = g.child(parent, field, "Call");
+            expr(g, n, "expr", e);
+        }
+        SStmt::Contract(c) => {
+            let n = g.child(parent, field, &format!("Contract {:?}", c.kind));
+            expr(g, n, "expr", &c.expr);
+        }
+        SStmt::Assert(e) => {
+            let n = g.child(parent, field, "Assert");
+            expr(g, n, "expr", e);
+        }
+    }
+}
+
+fn expr(g: &mut Graph, parent: usize, field: &str, e: &Spanned<SExpr>) {
+    let mut binary = |op: String, l: &Spanned<SExpr>, r: &Spanned<SExpr>| {
+        let n = g.child(parent, field, &op);
+        expr(g, n, "l", l);
+        expr(g, n, "r", r);
+    };
+    match &e.node {
+        SExpr::BinE { op, l, r } => return binary(format!("BinE {op:?}"), l, r),
+        SExpr::LogE { op, l, r } => return binary(format!("LogE {op:?}"), l, r),
+        SExpr::BitE { op, l, r } => return binary(format!("BitE {op:?}"), l, r),
+        SExpr::RelE { op, l, r } => return binary(format!("RelE {op:?}"), l, r),
+        _ => (),
+    }
+    match &e.node {
+        SExpr::Int(i) => {
+            g.child(parent, field, &format!("Int {i}"));
+        }
+        SExpr::Float { lit, .. } => {
+            g.child(parent, field, &format!("Float {lit}"));
+        }
+        SExpr::Bool(b) => {
+            g.child(parent, field, &format!("Bool {b}"));
+        }
+        SExpr::Str(s) => {
+            g.child(parent, field, &format!("Str {s:?}"));
+        }
+        SExpr::TypedInt { val, typ } => {
+            g.child(
+                parent,
+                field,
+                &format!("TypedInt {val} {}", pretty::typ(typ)),
+            );
+        }
+        SExpr::UnaryE { op, l } => {
+            let n = g.child(parent, field, &format!("UnaryE {op:?}"));
+            expr(g, n, "l", l);
+        }
+        SExpr::VarApp(alias) => {
+            g.child(parent, field, &format!("VarApp {alias}"));
+        }
+        SExpr::FuncApp { alias, aps } => {
+            let n = g.child(parent, field, &format!("FuncApp {alias}"));
+            for ap in aps {
+                expr(g, n, "ap", ap);
+            }
+        }
+        SExpr::IncDec { op, lval } => {
+            let n = g.child(parent, field, &format!("IncDec {op:?}"));
+            expr(g, n, "lval", lval);
+        }
+        SExpr::CondE { cond, then, els } => {
+            let n = g.child(parent, field, "CondE");
+            expr(g, n, "cond", cond);
+            expr(g, n, "then", then);
+            expr(g, n, "els", els);
+        }
+        SExpr::AddrOf(l) => {
+            let n = g.child(parent, field, "AddrOf");
+            expr(g, n, "l", l);
+        }
+        SExpr::Deref(l) => {
+            let n = g.child(parent, field, "Deref");
+            expr(g, n, "l", l);
+        }
+        SExpr::Index { alias, idx } => {
+            let n = g.child(parent, field, &format!("Index {alias}"));
+            expr(g, n, "idx", idx);
+        }
+        SExpr::Member { base, field: f } => {
+            let n = g.child(parent, field, &format!("Member {f}"));
+            expr(g, n, "base", base);
+        }
+        SExpr::SizeOf(l) => {
+            let n = g.child(parent, field, "SizeOf");
+            expr(g, n, "l", l);
+        }
+        SExpr::Cast(t, l) => {
+            let n = g.child(parent, field, &format!("Cast {}", pretty::typ(t)));
+            expr(g, n, "l", l);
+        }
+        SExpr::SizeOfType(t) => {
+            g.child(parent, field, &format!("SizeOfType {}", pretty::typ(t)));
+        }
+        SExpr::Alloc(t) => {
+            g.child(parent, field, &format!("Alloc {}", pretty::typ(t)));
+        }
+        SExpr::AllocArray(t, len) => {
+            let n = g.child(parent, field, &format!("AllocArray {}", pretty::typ(t)));
+            expr(g, n, "len", len);
+        }
+        SExpr::BinE { .. } | SExpr::LogE { .. } | SExpr::BitE { .. } | SExpr::RelE { .. } => {
+            unreachable!("binary ones are above")
+        }
+    }
+}
+
+// the control flow graph for --emit cfg-dot, a box per block with its
+// quads as the cfg's display has them. a function's blocks are clustered
+// under its name, and statics' data is outside any
+pub fn cfg_dot(cfg: &Cfg) -> String {
+    let mut dot = String::new();
+    dot.push_str("digraph cfg {\n");
+    dot.push_str("\tnode [shape=box, fontname=\"monospace\"];\n");
+    let mut clustered = false;
+    for (i, b) in cfg.blocks.iter().enumerate() {
+        let starts = match (b.label(), b.quads.first()) {
+            (Some(Label::UserLabel(f)), _) => Some(Some(f)),
+            (_, Some(TQuad::Data(..))) => Some(None),
+            _ => None,
+        };
+        if let Some(f) = starts {
+            if clustered {
+                dot.push_str("\t}\n");
+            }
+            clustered = f.is_some();
+            if let Some(f) = f {
+                dot.push_str(&format!("\tsubgraph \"cluster_{f}\" {{\n"));
+                dot.push_str(&format!("\t\tlabel=\"{f}\";\n"));
+            }
+        }
+        let quads = b
+            .quads
+            .iter()
+            .map(|quad| escape(&format!("{quad:?}")) + "\\l") // left justified
+            .collect::<String>();
+        let indent = if clustered { "\t\t" } else { "\t" };
+        dot.push_str(&format!("{indent}b{i} [label=\"b{i}\\l{quads}\"];\n"));
+    }
+    if clustered {
+        dot.push_str("\t}\n");
+    }
+    for (i, b) in cfg.blocks.iter().enumerate() {
+        for s in &b.succs {
+            dot.push_str(&format!("\tb{i} -> b{s};\n"));
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(test)]
+mod test_visualizer {
+    use crate::{evaluator, ir, lexer, parser_ast, selector, translator, Budget, IntModel, Sink};
+
+    #[test]
+    fn vnv() {
+        let src = "struct point { int x; int *y; }; int main() { return 0; }";
+        let tokens = lexer::lex(src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let mut nv = evaluator::env(
+            &src_tree,
+            IntModel::default(),
+            Sink::default(),
+            Budget::default(),
+        )
+        .unwrap();
+
+        let src = r#"
+            int n = 3;
+            struct point pt;
+            pt.x = 1;
+            pt.y = &n;
+            int *h = alloc_array(int, 2);
+            h[1] = 9;
+            int **hh = alloc(int *);
+            *hh = &h[1];
+            char *s = "hi";
+        "#;
+        let tokens = lexer::lex(src).unwrap();
+        let mut r = tokens.as_slice();
+        let mut lvnv = nv.vnv.clone();
+        while !r.is_empty() {
+            let (stmt, rest) = parser_ast::parse_stmt(r).unwrap();
+            evaluator::eval_stmt(&stmt, &nv, &mut lvnv).unwrap();
+            r = rest;
+        }
+        nv.vnv = lvnv;
+        insta::assert_snapshot!(super::gen_vnv_dot(&nv), @r###"
+        digraph vnv {
+        	rankdir=LR;
+        	node [shape=record];
+        	fnv [label="{fnv|<main> main()}"];
+        	vnv [label="{vnv|<m4> h = •|<m5> hh = •|<m1> n = 3|{pt|<m2> 1|<m3> •}|<m6> s = •}"];
+        	h8388608 [label="{int [2]|<m8388608> 0|<m8388609> 9}"];
+        	h8388610 [label="{int *|<m8388610> •}"];
+        	s16777216 [label="\"hi\""];
+        	vnv:m4 -> h8388608:m8388608;
+        	vnv:m5 -> h8388610:m8388610;
+        	vnv:m3 -> vnv:m1;
+        	vnv:m6 -> s16777216;
+        	h8388610:m8388610 -> h8388608:m8388609;
+        }
+        "###);
+    }
+
+    #[test]
+    fn ast() {
+        let src = "int f(int n) { while (n > 0) { n--; } return n * 2; }";
+        let tokens = lexer::lex(src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        insta::assert_snapshot!(super::ast_dot(&src_tree), @r###"
+        digraph ast {
+        	ordering="out";
+        	node [shape=box];
+        	n1 [label="prg"];
+        	n2 [label="FuncDef int f(int n)"];
+        	n1 -> n2;
+        	n3 [label="While"];
+        	n2 -> n3 [label="body"];
+        	n4 [label="RelE Gt"];
+        	n3 -> n4 [label="cond"];
+        	n5 [label="VarApp n"];
+        	n4 -> n5 [label="l"];
+        	n6 [label="Int 0"];
+        	n4 -> n6 [label="r"];
+        	n7 [label="Update n"];
+        	n3 -> n7 [label="body"];
+        	n8 [label="BinE Sub"];
+        	n7 -> n8 [label="expr"];
+        	n9 [label="VarApp n"];
+        	n8 -> n9 [label="l"];
+        	n10 [label="Int 1"];
+        	n8 -> n10 [label="r"];
+        	n11 [label="Return"];
+        	n2 -> n11 [label="body"];
+        	n12 [label="BinE Mult"];
+        	n11 -> n12 [label="expr"];
+        	n13 [label="VarApp n"];
+        	n12 -> n13 [label="l"];
+        	n14 [label="Int 2"];
+        	n12 -> n14 [label="r"];
+        }
+        "###);
+    }
+
+    #[test]
+    fn cfg() {
+        let src = "static int k = 1; int main() { int i = 0; while (i < k) { i++; } return i; }";
+        let tokens = lexer::lex(src).unwrap();
+        let src_tree = parser_ast::parse_prg(&tokens).unwrap();
+        let quads = selector::select(&translator::translate(&src_tree));
+        let dot = super::cfg_dot(&ir::build(quads));
+        // each block's quads left out
+        let shape = dot
+            .lines()
+            .map(|l| l.split(" [label=\"b").next().unwrap())
+            .collect::<Vec<_>>()
+            .join("\n");
+        insta::assert_snapshot!(shape, @r###"
+        digraph cfg {
+        	node [shape=box, fontname="monospace"];
+        	b0
+        	subgraph "cluster_main" {
+        		label="main";
+        		b1
+        		b2
+        		b3
+        		b4
+        		b5
+        		b6
+        	}
+        	b1 -> b2;
+        	b2 -> b3;
+        	b2 -> b5;
+        	b3 -> b4;
+        	b4 -> b2;
+        	b5 -> b6;
+        }
+        "###);
+    }
+}
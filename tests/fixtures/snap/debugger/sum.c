@@ -0,0 +1,16 @@
+int total = 0;
+
+int add(int x) {
+    total = total + x;
+    return total;
+}
+
+int main() {
+    int a[3];
+    for (int i = 0; i < 3; i++) {
+        a[i] = i + 1;
+        add(a[i]);
+    }
+    float half = 0.5f;
+    return total;
+}
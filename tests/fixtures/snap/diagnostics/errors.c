@@ -0,0 +1,8 @@
+int f() {
+    int x = 1
+    return x;
+}
+
+int main() {
+    return count + 1;
+}
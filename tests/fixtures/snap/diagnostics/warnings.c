@@ -0,0 +1,16 @@
+foo(int a) {
+  int x = 1;
+  return a;
+  a = 2;
+}
+
+int bar(int b) {
+  if (b) {
+    return 1;
+  }
+}
+
+int main() {
+  int y;
+  return foo(1) + bar(0);
+}
@@ -0,0 +1,7 @@
+long square(int x);
+int twice(int x);
+int calls(int x);
+
+int add(int a, int b) {
+    return twice(a) + calls(b);
+}
@@ -0,0 +1,9 @@
+int printf(char *fmt, ...);
+int add(int a, int b);
+int square(int x);
+
+int main() {
+    int s = square(add(2, 3));
+    printf("%d\n", s);
+    return s;
+}
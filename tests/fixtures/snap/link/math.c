@@ -0,0 +1,15 @@
+int calls = 0;
+
+static int twice(int x) {
+    return x + x;
+}
+
+int add(int a, int b) {
+    calls = calls + 1;
+    return a + b;
+}
+
+int square(int x) {
+    calls = calls + 1;
+    return x * twice(1) * x / 2;
+}
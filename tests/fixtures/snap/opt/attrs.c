@@ -0,0 +1,7 @@
+__attribute__((optimize("O0"))) int debugged() {
+  return 1 + 2;
+}
+
+int main() {
+  return debugged() + 3 * 4;
+}
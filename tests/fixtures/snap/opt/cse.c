@@ -0,0 +1,10 @@
+int f(int a, int b) {
+  int x = a * b + 1;
+  int y = a * b + 2;
+  a = a + 1;
+  return x + y + b * a;
+}
+
+int main() {
+  return f(3, 4);
+}
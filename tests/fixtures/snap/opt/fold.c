@@ -0,0 +1,9 @@
+int main() {
+  int x = 2 + 3;
+  int y = x * 4;
+  int unused = y - 1;
+  while (0) {
+    y = 1;
+  }
+  return y;
+}
@@ -0,0 +1,9 @@
+int main() {
+  int n = 3;
+  int sum = 0;
+  while (n) {
+    sum = sum + n;
+    n = n - 1;
+  }
+  return sum + 8 / 4;
+}
@@ -0,0 +1,32 @@
+#define DEBUG
+#define LEVEL 2
+
+#ifdef DEBUG
+int debug = 1;
+#else
+int debug = 0;
+#endif
+
+#ifndef DEBUG
+#error debug should be defined
+#endif
+
+#if LEVEL > 2
+int level = 3;
+#elif LEVEL == 2 && defined(DEBUG) && !defined RELEASE
+int level = 2;
+#else
+int level = 1;
+#endif
+
+#if 0
+#if 1
+this isn't C, but it's skipped
+#endif
+#elif (1 << 3) - 8 ? 0 : 'a' == 97
+int nested = 1;
+#endif
+
+int main() {
+    return debug * 100 + level * 10 + nested;
+}
@@ -0,0 +1,9 @@
+#ifndef MAX_H
+#define MAX_H
+int max(int a, int b) {
+    if (a > b) {
+        return a;
+    }
+    return b;
+}
+#endif
@@ -0,0 +1,6 @@
+#include "inc/max.h"
+#include <lib.h>
+
+int main() {
+    return max(LIB, 3);
+}
@@ -0,0 +1,13 @@
+#include <limits.h>
+
+#if INT_MAX > 32767
+int bits = 32;
+#else
+int bits = 16;
+#endif
+
+int main() {
+    int lo = INT_MIN;
+    int hi = INT_MAX;
+    return bits + (lo + hi == -1) + (hi + 1 == lo) + (UINT_MAX == -1);
+}
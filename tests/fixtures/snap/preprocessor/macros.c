@@ -0,0 +1,18 @@
+#define N 10
+#define SQUARE(x) x * x
+#define MAX(a, b) a > b ? a : b
+#define TWICE(x) SQUARE(x) + SQUARE(x) /* nested */
+#define CAT(a, b) a ## b
+#define STR(x) #x
+#define LONG_SUM(a, b, c) \
+    a + b + c
+
+int main() {
+    int foo = 1;
+#define foo foo * 3
+    int CAT(my, var) = N;
+    char *s = STR(hello "world");
+    int m = MAX(myvar,
+                3);
+    return LONG_SUM(m, TWICE(2), foo);
+}
@@ -0,0 +1,2 @@
+#include "../inc/max.h"
+#define LIB 42
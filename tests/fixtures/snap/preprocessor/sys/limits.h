@@ -0,0 +1 @@
+#define INT_MAX 7
@@ -0,0 +1,10 @@
+int main() {
+  int n = 300;
+  char c = (char) n;
+  unsigned char u = (unsigned char) -1;
+  float f = 2.75f;
+  int t = (int) f + (int) -f;
+  long l = (long) c * 2;
+  int *p = (int *) 0;
+  return c + u + t + (int) l + (int) (float) 7 / 2;
+}
@@ -0,0 +1,5 @@
+int main() {
+  int min = -2147483647 - 1;
+  int neg = -1;
+  return min % neg;
+}
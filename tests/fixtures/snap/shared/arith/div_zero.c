@@ -0,0 +1,8 @@
+int div(int a, int b) {
+  return a / b;
+}
+
+int main() {
+  int q = div(7, 2);
+  return q + div(q, q - 3);
+}
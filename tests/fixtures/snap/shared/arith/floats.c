@@ -0,0 +1,14 @@
+float half(float x) {
+    return x / 2;
+}
+
+int main() {
+    float f = 1.5f;
+    double d = 2.5e1;
+    int i = 7;
+    float g = half(i);
+    float h = -f * d + g;
+    int n = h;
+    int c = f < d ? 100 : 0;
+    return n + c + d;
+}
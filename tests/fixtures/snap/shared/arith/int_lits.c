@@ -0,0 +1,8 @@
+int main() {
+    int h = 0x1F;
+    int o = 017;
+    long l = 42L;
+    unsigned u = 42u;
+    int neg = -1 < 0u ? 100 : 0;
+    return h + o + l + u + neg + 0XfUL;
+}
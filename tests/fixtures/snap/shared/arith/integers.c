@@ -0,0 +1,12 @@
+int main() {
+    unsigned u = -1;
+    unsigned char uc = 300;
+    short s = 40000;
+    unsigned short int us = -2;
+    signed char c = 200;
+    long int l = 7;
+    int big = u > 1 ? 1 : 0;
+    int half = u / 16777216;
+    int high = u >> 28;
+    return big + uc + s + us + c + half + high + l;
+}
@@ -0,0 +1,3 @@
+long long main() {
+    return 1;
+}
@@ -0,0 +1,3 @@
+int main() {
+  return 17 % 5 * 3;
+}
@@ -0,0 +1,3 @@
+int main() {
+    return 200 * 200;
+}
\ No newline at end of file
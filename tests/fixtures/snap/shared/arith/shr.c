@@ -0,0 +1,3 @@
+int main() {
+  return -16 >> 2;
+}
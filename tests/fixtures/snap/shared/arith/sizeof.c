@@ -0,0 +1,13 @@
+struct pair {
+    char c;
+    int n;
+};
+
+int main() {
+    int a[10];
+    struct pair p;
+    double d = 1.0;
+    char *s = "hi";
+    int n = sizeof(a) / sizeof a[0];
+    return n + sizeof(struct pair) + sizeof(p.c) + sizeof d + sizeof(s) + sizeof(short) + sizeof(long);
+}
@@ -0,0 +1,5 @@
+int main() {
+    unsigned u = -8;
+    unsigned char c = u;
+    return u / c;
+}
@@ -0,0 +1,4 @@
+int main() {
+  unsigned x = 40000u;
+  return (x + 1) / 2 > 10000;
+}
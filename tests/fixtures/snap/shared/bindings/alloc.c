@@ -0,0 +1,22 @@
+int sum(int *a, int n) {
+    int s = 0;
+    for (int i = 0; i < n; i++) {
+        s = s + a[i];
+    }
+    return s;
+}
+
+int main() {
+    int n = 5;
+    int *a = alloc_array(int, n);
+    for (int i = 0; i < n; i++) {
+        a[i] = i * i;
+    }
+
+    int *p = alloc(int);
+    *p = sum(a, n);
+
+    char *c = alloc(char);
+    *c = 300;
+    return *p + *c;
+}
@@ -0,0 +1,9 @@
+int main() {
+    int *a = alloc_array(int, 4);
+    int i = 0;
+    while (i <= 4) {
+        a[i] = i;
+        i++;
+    }
+    return a[0];
+}
@@ -0,0 +1,6 @@
+int main() {
+    int *p = alloc(int);
+    int **pp = alloc(int *);
+    *p = 1;
+    return **pp + *p;
+}
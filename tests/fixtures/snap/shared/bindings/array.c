@@ -0,0 +1,10 @@
+int main() {
+    int a[10];
+    a[0] = 1;
+    for (int i = 1; i < 10; i = i + 1) {
+        a[i] = a[i - 1] + 1;
+    }
+    int *p = &a[4];
+    *p = *p * 10;
+    return a[9] + a[4];
+}
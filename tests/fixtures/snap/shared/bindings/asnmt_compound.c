@@ -0,0 +1,9 @@
+int main() {
+  int x = 5;
+  x += 3;
+  x *= 4;
+  x -= 2;
+  x /= 4;
+  x %= 4;
+  return x;
+}
@@ -0,0 +1,22 @@
+struct vec {
+  int x;
+  int y;
+};
+
+int main() {
+  int a[2];
+  int i = 0;
+  int *p = &i;
+  struct vec v;
+  a[0] = 1;
+  a[1] = 2;
+  v.x = 5;
+  a[i] += 1;
+  *p += 1;
+  v.x += 4;
+  a[0]++;
+  ++a[1];
+  a[i--] *= 3;
+  int b = v.x--;
+  return a[0] * 1000 + a[1] * 100 + v.x * 10 + i + b;
+}
@@ -0,0 +1,5 @@
+int main() {
+    int x = 5;
+    x = x + 1;
+    return x;
+}
\ No newline at end of file
@@ -0,0 +1,8 @@
+__attribute__((noinline)) int double_it(int x) {
+  return x * 2;
+}
+
+__attribute__((always_inline, optimize("O0")))
+int main() {
+  return double_it(21);
+}
@@ -0,0 +1,4 @@
+__attribute__((noinline)) __attribute__((always_inline))
+int main() {
+  return 0;
+}
@@ -0,0 +1,8 @@
+int main() {
+    int x = 1;
+    {
+        x = y;
+        int y = 2;
+    }
+    return x;
+}
@@ -0,0 +1,25 @@
+int main() {
+    int x = 1;
+    int sum = 0;
+    {
+        int x = 10;
+        sum = sum + x;
+        {
+            int x = 100;
+            sum = sum + x;
+        }
+        sum = sum + x;
+        x = x + 1;
+    }
+    sum = sum + x;
+    {
+        int y = 1000;
+        int *p = &x;
+        *p = *p + y;
+    }
+    {
+        int z = 5;
+        sum = sum + z;
+    }
+    return sum + x;
+}
@@ -0,0 +1,15 @@
+struct tagged {
+    char tag;
+    int val;
+};
+
+int main() {
+    char buf[4];
+    buf[1] = 'h';
+    struct tagged t;
+    t.val = 7;
+    char c = -1;
+    char *p = &c;
+    *p = 'b';
+    return buf[1] + t.val + c;
+}
@@ -0,0 +1,13 @@
+char next(char c) {
+    return c + 1;
+}
+
+int main() {
+    char nl = '\n';
+    char c = next('a');
+    char big = 200;
+    char max = 127;
+    max++;
+    int sum = nl + c + '\0';
+    return sum + big + max + '\\';
+}
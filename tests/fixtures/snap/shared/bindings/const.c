@@ -0,0 +1,10 @@
+int main() {
+    const int x = 3;
+    int const y = 4;
+    int a[2];
+    int *const p = &a[0];
+    *p = x;
+    *p = *p + y;
+    const int z = a[0] * 5;
+    return z;
+}
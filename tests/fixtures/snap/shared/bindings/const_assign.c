@@ -0,0 +1,6 @@
+int main() {
+    const int x = 1;
+    int y = x;
+    x = y + 1;
+    return x;
+}
@@ -0,0 +1,9 @@
+enum Color { RED, GREEN = 5, BLUE };
+
+enum { LO = -1, HI = BLUE * 2, };
+
+int main() {
+    enum Color c = BLUE;
+    int x = c + GREEN * HI + LO;
+    return x + RED;
+}
@@ -0,0 +1,7 @@
+enum Color { RED, GREEN, BLUE };
+
+enum Light { GREEN, AMBER };
+
+int main() {
+    return GREEN;
+}
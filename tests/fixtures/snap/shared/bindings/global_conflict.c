@@ -0,0 +1,7 @@
+int g;
+int g = 1;
+char g;
+
+int main() {
+    return g;
+}
@@ -0,0 +1,17 @@
+int g = 6 * 7;
+int zero;
+const int k = sizeof(int) + 1;
+float half = 1 / 2.0;
+char wrap = 300;
+int table[3];
+
+int bump() {
+    g = g + k;
+    return g;
+}
+
+int main() {
+    table[1] = bump();
+    zero++;
+    return table[1] + zero + wrap + half * 4;
+}
@@ -0,0 +1,14 @@
+struct node {
+    int val;
+    struct node *next;
+};
+
+int main() {
+    struct node *n = alloc(struct node);
+    n->val = 5;
+    n->next = alloc(struct node);
+    (*n->next).val = 7;
+    n->next->val = n->next->val * 10;
+    struct node *m = n->next;
+    return n->val + m->val + (*n).val;
+}
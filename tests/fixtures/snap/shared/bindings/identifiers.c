@@ -0,0 +1,7 @@
+int main() {
+    int while1 = 1;
+    int _x9 = 2;
+    int X_1 = 3;
+    int int2 = 4;
+    return while1 + _x9 + X_1 + int2;
+}
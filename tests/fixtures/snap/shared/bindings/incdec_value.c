@@ -0,0 +1,8 @@
+int main() {
+  int x = 5;
+  int a = x++;
+  int b = ++x;
+  int c = x--;
+  int d = --x;
+  return a * 1000 + b * 100 + c * 10 + d;
+}
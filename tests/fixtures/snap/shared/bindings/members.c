@@ -0,0 +1,21 @@
+struct a {
+    int x;
+    int y;
+};
+
+struct b {
+    int y;
+    int z;
+};
+
+int main() {
+    struct a p;
+    struct b q;
+    p.x = 1;
+    p.y = 2;
+    q.y = 30;
+    q.z = 400;
+    struct b *r = &q;
+    q.y = (*r).y + p.y;
+    return p.x + p.y * 10 + q.y * 100 + q.z;
+}
@@ -0,0 +1,8 @@
+int main() {
+    int *p = 0;
+    int *q = NULL;
+    if (p == q && NULL == p) {
+        return *q;
+    }
+    return 1;
+}
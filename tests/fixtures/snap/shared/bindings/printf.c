@@ -0,0 +1,7 @@
+int printf(char *fmt, ...);
+
+int main() {
+    int n = 6 * 7;
+    printf("%d %c\n", n, 'x');
+    return printf("done\n");
+}
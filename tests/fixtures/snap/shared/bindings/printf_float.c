@@ -0,0 +1,10 @@
+int printf(char *fmt, ...);
+
+int main() {
+    float x = 1.5;
+    printf("%d\n", 1);
+    {
+        int y = printf("%f\n", x);
+    }
+    return 0;
+}
@@ -0,0 +1,7 @@
+int printf(char *fmt, ...);
+
+int main() {
+    char *s = "hi";
+    printf("%s\n", s);
+    return 0;
+}
@@ -0,0 +1,9 @@
+int add(int, int);
+
+int main() {
+    return add(1, 2);
+}
+
+int add(int a, char b) {
+    return a + b;
+}
@@ -0,0 +1,15 @@
+int add(int, int);
+int twice(int x);
+int add(int a, int b);
+
+int main() {
+    return twice(add(3, 4));
+}
+
+int add(int a, int b) {
+    return a + b;
+}
+
+int twice(int y) {
+    return add(y, y);
+}
@@ -0,0 +1,14 @@
+int set(int *p, int v) {
+    *p = v;
+    return v;
+}
+
+int main() {
+    int x = 1;
+    int *p = &x;
+    *p = 3;
+    int **pp = &p;
+    **pp = *p + 4;
+    int y = set(&x, x * 2);
+    return x;
+}
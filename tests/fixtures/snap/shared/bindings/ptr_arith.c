@@ -0,0 +1,34 @@
+struct point {
+    int x;
+    int y;
+};
+
+int sum(int *xs, int n) {
+    int s = 0;
+    int *end = xs + n;
+    for (int *p = xs; end - p > 0; p = p + 1) {
+        s = s + *p;
+    }
+    return s;
+}
+
+int main() {
+    int a[4];
+    for (int i = 0; i < 4; i = i + 1) {
+        a[i] = i + 1;
+    }
+    int *q = a + 3;
+    int *r = 1 + a;
+    long d = q - a;
+
+    struct point ps[3];
+    struct point *p = ps + 2;
+    int *y = &(*p).y;
+    *y = 9;
+    struct point *o = p - 1;
+    int *x = &(*o).x;
+    *x = 5;
+    int n = p - ps;
+    int *cells = (int *) ps;
+    return sum(a, 4) * 100 + *q * 10 + *(q - 2) + (int) d * 1000 + *r * 10000 + cells[5] + cells[2] + n;
+}
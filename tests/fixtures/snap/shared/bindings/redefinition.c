@@ -0,0 +1,13 @@
+int f(int n) {
+    int m = n;
+    {
+        int m = 2;
+        n = m;
+    }
+    int n = 3;
+    return n + m;
+}
+
+int main() {
+    return f(1);
+}
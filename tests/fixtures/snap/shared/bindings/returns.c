@@ -0,0 +1,39 @@
+int sign(int x) {
+    if (x < 0) {
+        return -1;
+    } else {
+        if (x > 0) {
+            return 1;
+        } else {
+            return 0;
+        }
+    }
+}
+
+int spin(int x) {
+    while (1) {
+        if (x > 10) {
+            return x;
+        }
+        x = x + 1;
+    }
+}
+
+int pick(int x) {
+    switch (x) {
+    case 0:
+        x = 1;
+    default:
+        return x;
+    }
+}
+
+int half(int x) {
+    if (x % 2 == 0) {
+        return x / 2;
+    }
+}
+
+int main() {
+    return sign(-3) + spin(0) + pick(0) + half(4);
+}
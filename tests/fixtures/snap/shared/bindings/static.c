@@ -0,0 +1,27 @@
+static int total = 10;
+static int hits;
+
+int count() {
+    static int n = 0;
+    hits++;
+    n = n + 1;
+    return n;
+}
+
+int bump(int by) {
+    static int n;
+    {
+        static int n = 100;
+        n = n + by;
+        total = total + n;
+    }
+    n = n + by;
+    return n;
+}
+
+int main() {
+    count();
+    int c = count();
+    bump(1);
+    return c * 100 + bump(2) * 10 + hits + total;
+}
@@ -0,0 +1,6 @@
+int main() {
+    char *s = "hi\tthere\n";
+    char *t = "hi\tthere\n";
+    char *e = "";
+    return s == t ? *s + *e : 0;
+}
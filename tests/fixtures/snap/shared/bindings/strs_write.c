@@ -0,0 +1,5 @@
+int main() {
+    char *s = "hi";
+    *s = 'H';
+    return *s;
+}
@@ -0,0 +1,20 @@
+struct point {
+    int x;
+    int y;
+};
+
+struct rect {
+    struct point lo;
+    struct point hi;
+};
+
+int main() {
+    struct rect r;
+    r.lo.x = 1;
+    r.lo.y = 2;
+    r.hi.x = 4;
+    r.hi.y = 7;
+    int *p = &r.hi.y;
+    *p = *p + 3;
+    return r.hi.x * r.hi.y - r.lo.x - r.lo.y;
+}
@@ -0,0 +1,22 @@
+typedef int num;
+typedef char *str;
+
+struct point {
+    num x;
+    num y;
+};
+
+typedef struct point point;
+
+num twice(num n) {
+    return n + n;
+}
+
+int main() {
+    point p;
+    p.x = 3;
+    p.y = twice(4);
+    str s = "a";
+    num sum = p.x + p.y;
+    return sum + *s;
+}
@@ -0,0 +1,7 @@
+int main() {
+    int x = 1;
+    while (x < 0) {
+        x = y;
+    }
+    return x;
+}
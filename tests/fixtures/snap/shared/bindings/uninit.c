@@ -0,0 +1,24 @@
+int f(int n) {
+    int x;
+    int y;
+    if (n > 0) {
+        x = n;
+    }
+    y = 2;
+    while (n > 10) {
+        int z;
+        z = n;
+        n = n - z;
+    }
+    return x + y;
+}
+
+int main() {
+    int s;
+    {
+        int s = 1;
+        s = s + 1;
+    }
+    s = f(1);
+    return f(0) + s;
+}
@@ -0,0 +1,34 @@
+union word {
+    int i;
+    short h;
+    char c;
+};
+
+union bits {
+    float f;
+    int n;
+};
+
+int main() {
+    union word w;
+    w.i = 258;
+    int c = w.c;
+    w.i = 70000;
+    int h = w.h;
+    w.c = 0;
+
+    union bits b;
+    b.f = 1.0;
+    int n = b.n;
+    b.n = 1069547520;
+    float f = b.f;
+
+    int punned = 0;
+    if (n == 1065353216) {
+        punned = punned + 100;
+    }
+    if (f == 1.5) {
+        punned = punned + 1000;
+    }
+    return c + h + w.i + punned;
+}
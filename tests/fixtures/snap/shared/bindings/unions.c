@@ -0,0 +1,32 @@
+struct point {
+    int x;
+    int y;
+};
+
+struct pair {
+    int fst;
+    int snd;
+};
+
+union pun {
+    struct point pt;
+    struct pair pr;
+    int i;
+};
+
+union small {
+    char c;
+    char d;
+};
+
+int main() {
+    union pun u;
+    u.pt.x = 300;
+    u.pt.y = 4;
+    int *p = &u.pr.snd;
+    *p = *p + 1;
+
+    union small s;
+    s.c = 258;
+    return u.i + u.pr.snd * u.pt.y + s.d;
+}
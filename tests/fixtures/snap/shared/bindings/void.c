@@ -0,0 +1,15 @@
+void bump(int *p) {
+    *p = *p + 1;
+}
+
+void twice(int *p) {
+    bump(p);
+    bump(p);
+    return;
+}
+
+int main(void) {
+    int x = 40;
+    twice(&x);
+    return x;
+}
@@ -0,0 +1,8 @@
+void f() {
+    return 1;
+}
+
+int main() {
+    f();
+    return 0;
+}
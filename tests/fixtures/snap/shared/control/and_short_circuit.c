@@ -0,0 +1,7 @@
+int main() {
+    int x = 0;
+    if (x != 0 && 10 / x > 1) {
+        return 1;
+    }
+    return 2;
+}
\ No newline at end of file
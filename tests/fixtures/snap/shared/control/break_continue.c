@@ -0,0 +1,30 @@
+int main() {
+    int sum = 0;
+    int i = 0;
+    while (i < 10) {
+        i = i + 1;
+        switch (i % 3) {
+        case 0:
+            continue;
+        }
+        switch (i) {
+        case 8:
+            break;
+        default:
+            sum = sum + i;
+            continue;
+        }
+        break;
+    }
+
+    int evens = 0;
+    for (int j = 0; j < 6; j = j + 1) {
+        switch (j % 2) {
+        case 1:
+            continue;
+        }
+        evens = evens + j;
+    }
+
+    return evens * 10000 + sum * 100 + i;
+}
@@ -0,0 +1,5 @@
+int main() {
+    int x = 0;
+    int y = x != 0 ? 10 / x : x == 0 ? 7 : 8;
+    return y;
+}
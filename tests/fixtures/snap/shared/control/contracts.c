@@ -0,0 +1,18 @@
+int sum(int n)
+//@requires n >= 0;
+//@ensures \result >= n;
+{
+    int s = 0;
+    for (int i = 0; i <= n; i++)
+    //@loop_invariant s >= 0;
+    {
+        s += i;
+    }
+    //@assert 2 * s == n * n + n;
+    assert(s > 0);
+    return s;
+}
+
+int main() {
+    return sum(8);
+}
@@ -0,0 +1,12 @@
+int f(int n)
+//@ensures \result > n;
+{
+    if (n > 2) {
+        return n;
+    }
+    return n + 1;
+}
+
+int main() {
+    return f(1) + f(3);
+}
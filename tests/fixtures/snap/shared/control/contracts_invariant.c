@@ -0,0 +1,9 @@
+int main() {
+    int i = 0;
+    while (i < 10)
+    //@loop_invariant i < 5;
+    {
+        i++;
+    }
+    return i;
+}
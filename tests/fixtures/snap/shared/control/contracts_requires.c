@@ -0,0 +1,13 @@
+int sum(int n)
+//@requires n >= 0;
+{
+    int s = 0;
+    for (int i = 0; i <= n; i++) {
+        s += i;
+    }
+    return s;
+}
+
+int main() {
+    return sum(-3);
+}
@@ -0,0 +1,19 @@
+int main() {
+    int n = 0;
+    do {
+        n = n + 1;
+    } while (n < 0);
+
+    int i = 0;
+    int sum = 0;
+    do {
+        i = i + 1;
+        switch (i % 2) {
+        case 0:
+            continue;
+        }
+        sum = sum + i;
+    } while (i < 9);
+
+    return n * 100 + sum;
+}
@@ -0,0 +1,3 @@
+int main() {
+    return 8 + 1 < 10 == 10 >= 9;
+}
\ No newline at end of file
@@ -0,0 +1,16 @@
+int main() {
+    int n = 0;
+    int i = 0;
+    for (; i < 3; i = i + 1) {
+        n = n + i;
+    }
+    for (i = 0; i < 3;) {
+        i = i + 1;
+        n = n + 100;
+    }
+    for (;;) {
+        n = n + 1000;
+        break;
+    }
+    return n;
+}
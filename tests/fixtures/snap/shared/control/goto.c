@@ -0,0 +1,14 @@
+int main() {
+    int i = 0;
+    int sum = 0;
+top:
+    i = i + 1;
+    sum = sum + i;
+    switch (i) {
+    case 5:
+        goto done;
+    }
+    goto top;
+done:
+    return sum;
+}
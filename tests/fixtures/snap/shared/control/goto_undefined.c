@@ -0,0 +1,5 @@
+int main() {
+    int i = 0;
+    goto done;
+    return i;
+}
@@ -0,0 +1,22 @@
+int classify(int n) {
+    int r = 0;
+    switch (n) {
+    case 0:
+        r = 10;
+    case 1:
+        r = r + 1;
+        return r;
+    case 2:
+    case 3:
+        return 30;
+    case 5:
+        return 50;
+    default:
+        return 0 - 1;
+    }
+    return r;
+}
+
+int main() {
+    return classify(0) + classify(1) + classify(3) + classify(4) + classify(5) + classify(9);
+}
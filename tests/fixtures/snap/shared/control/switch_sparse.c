@@ -0,0 +1,17 @@
+int main() {
+    int x = 100;
+    int y = 0;
+    switch (x) {
+    case 1:
+        y = 1;
+    case 100:
+        y = y + 100;
+    case 1000:
+        y = y + 1000;
+    }
+    switch (y) {
+    case 7:
+        y = 0;
+    }
+    return y;
+}
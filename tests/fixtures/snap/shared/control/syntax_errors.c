@@ -0,0 +1,21 @@
+int f(int x) {
+    int y = x + ;
+    if (x > 1) {
+        return y;
+    } else {
+        return 0 0;
+    }
+}
+
+int g(int x) {
+    while (x < 10) {
+        x = x * ;
+    }
+    return x;
+}
+
+int = 3;
+
+int main() {
+    return f(2) + g(1);
+}
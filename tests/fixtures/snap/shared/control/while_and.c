@@ -0,0 +1,7 @@
+int main() {
+    int x = 0;
+    while (x < 3 && x != 2) {
+        x = x + 1;
+    }
+    return x;
+}
\ No newline at end of file
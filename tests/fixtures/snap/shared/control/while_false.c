@@ -0,0 +1,6 @@
+int main() {
+    while (0) {
+        return 9;
+    }
+    return 10;
+}
\ No newline at end of file
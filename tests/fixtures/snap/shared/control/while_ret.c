@@ -0,0 +1,8 @@
+int main() {
+    int x = 1;
+    while (x) {
+        int y = 9;
+        return y;
+    }
+    return 10;
+}
\ No newline at end of file
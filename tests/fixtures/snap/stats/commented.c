@@ -0,0 +1,12 @@
+// adds its argument to ten
+int f(int x) {
+    return x + 10; // the answer is near
+}
+
+/*
+ * entry point
+ */
+int main() {
+    int y = 9;
+    return f(y);
+}
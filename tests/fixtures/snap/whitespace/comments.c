@@ -0,0 +1,5 @@
+int main() {
+    char *s = "// not a comment"; /* but
+    this is */
+    return '"'; // and this
+}
@@ -0,0 +1,6 @@
+int main() {
+    int x = 1 @ 2;
+    char c = 'ab';
+    char *s = "bad \q escape";
+    return x $ 1e;
+}
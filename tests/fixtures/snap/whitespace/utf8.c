@@ -0,0 +1,7 @@
+int main() {
+    // déjà vu in a comment is fine
+    char *s = "naïve";
+    int café = 1;
+    char c = 'é';
+    return s[2] × 2;
+}
@@ -0,0 +1,4 @@
+int main() {
+  int café = 1;
+  return 0;
+}
@@ -0,0 +1,6 @@
+int printf(char *fmt, ...);
+
+int main() {
+  printf("hello, world\n");
+  return 0;
+}
@@ -0,0 +1,10 @@
+int printf(char *fmt, ...);
+
+int main() {
+  int s = 0;
+  for (int i = 1; i <= 4; i++) {
+    s += i * i;
+    printf("%d\n", s);
+  }
+  return s;
+}